@@ -1,18 +1,24 @@
 //! High-level types and functions related to CSS parsing
 use std::{
     num::ParseIntError,
-    fmt,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    fmt, fs, io,
 };
 pub use simplecss::Error as CssSyntaxError;
 use simplecss::Tokenizer;
 
 use crate::css_parser;
-pub use crate::css_parser::CssParsingError;
+pub use crate::css_parser::{CssParsingError, CssAnimationParseError};
 use azul_css::{
-    Css, CssDeclaration, Stylesheet,
+    Css, CssDeclaration, Stylesheet, CssKeyframes, CssKeyframe, PercentageValue,
     DynamicCssProperty, DynamicCssPropertyDefault,
-    CssPropertyType, CssRuleBlock, CssPath, CssPathSelector,
+    CssVariable, CssVarReference,
+    CssPropertyType, CssRuleBlock, CssPath, CssPathSelector, CssSourceLocation,
     CssNthChildSelector, CssPathPseudoSelector, CssNthChildSelector::*,
+    CssAttributeSelector, CssAttributeCondition,
+    CssMediaRule, CssMediaQuery, CssMediaCondition, ColorScheme,
+    CssFontFace, CssFontFaceSource, StyleFontWeight, StyleFontStyle,
     NodeTypePath, NodeTypePathParseError,
 };
 
@@ -34,12 +40,29 @@ pub enum CssParseErrorInner<'a> {
     /// Error parsing dynamic CSS property, such as
     /// `#div { width: {{ my_id }} /* no default case */ }`
     DynamicCssParseError(DynamicCssParseError<'a>),
+    /// Error parsing a custom property or a `var()` function, such as
+    /// `#div { color: var(main-color) /* variable names have to start with "--" */ }`
+    VarParseError(CssVarParseError),
     /// Error while parsing a pseudo selector (like `:aldkfja`)
     PseudoSelectorParseError(CssPseudoSelectorParseError<'a>),
     /// The path has to be either `*`, `div`, `p` or something like that
     NodeTypePath(NodeTypePathParseError<'a>),
+    /// Invalid attribute selector, such as `[data-state="open]`
+    InvalidAttributeSelector(&'a str),
     /// A certain property has an unknown key, for example: `alsdfkj: 500px` = `unknown CSS key "alsdfkj: 500px"`
     UnknownPropertyKey(&'a str, &'a str),
+    /// Error while parsing a `transition` or `animation` property
+    AnimationParseError(CssAnimationParseError<'a>),
+    /// Error while parsing a `@keyframes` rule
+    KeyframesParseError(CssKeyframesParseError<'a>),
+    /// Error while parsing the query of a `@media` rule
+    MediaQueryParseError(CssMediaQueryParseError<'a>),
+    /// Error while parsing a `@font-face` rule
+    FontFaceParseError(CssFontFaceParseError<'a>),
+    /// The path of an `@import` rule has to be a string or an `url()`, i.e. `@import "colors.css";`
+    MalformedImport(&'a str),
+    /// `@import` rules can only be resolved when loading the CSS via `new_from_file`
+    UnresolvedImport(&'a str),
 }
 
 impl_display!{ CssParseErrorInner<'a>, {
@@ -47,9 +70,17 @@ impl_display!{ CssParseErrorInner<'a>, {
     UnclosedBlock => "Unclosed block",
     MalformedCss => "Malformed Css",
     DynamicCssParseError(e) => format!("Error parsing dynamic CSS property: {}", e),
+    VarParseError(e) => format!("Error parsing CSS variable: {}", e),
     PseudoSelectorParseError(e) => format!("Failed to parse pseudo-selector: {}", e),
     NodeTypePath(e) => format!("Failed to parse CSS selector path: {}", e),
+    InvalidAttributeSelector(e) => format!("Invalid attribute selector: \"[{}]\"", e),
     UnknownPropertyKey(k, v) => format!("Unknown CSS key: \"{}: {}\"", k, v),
+    AnimationParseError(e) => format!("Error parsing transition or animation: {}", e),
+    KeyframesParseError(e) => format!("Error parsing @keyframes: {}", e),
+    MediaQueryParseError(e) => format!("Error parsing @media: {}", e),
+    FontFaceParseError(e) => format!("Error parsing @font-face: {}", e),
+    MalformedImport(e) => format!("Malformed @import: \"{}\" - has to be a string, i.e. @import \"file.css\";", e),
+    UnresolvedImport(e) => format!("Can't resolve @import \"{}\" - use new_from_file to load CSS with imports", e),
 }}
 
 impl<'a> From<CssSyntaxError> for CssParseErrorInner<'a> {
@@ -58,9 +89,19 @@ impl<'a> From<CssSyntaxError> for CssParseErrorInner<'a> {
     }
 }
 
+impl<'a> From<CssVarParseError> for CssParseErrorInner<'a> {
+    fn from(e: CssVarParseError) -> Self {
+        CssParseErrorInner::VarParseError(e)
+    }
+}
+
 impl_from! { DynamicCssParseError<'a>, CssParseErrorInner::DynamicCssParseError }
 impl_from! { NodeTypePathParseError<'a>, CssParseErrorInner::NodeTypePath }
 impl_from! { CssPseudoSelectorParseError<'a>, CssParseErrorInner::PseudoSelectorParseError }
+impl_from! { CssAnimationParseError<'a>, CssParseErrorInner::AnimationParseError }
+impl_from! { CssKeyframesParseError<'a>, CssParseErrorInner::KeyframesParseError }
+impl_from! { CssMediaQueryParseError<'a>, CssParseErrorInner::MediaQueryParseError }
+impl_from! { CssFontFaceParseError<'a>, CssParseErrorInner::FontFaceParseError }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CssPseudoSelectorParseError<'a> {
@@ -68,6 +109,8 @@ pub enum CssPseudoSelectorParseError<'a> {
     UnknownSelector(&'a str, Option<&'a str>),
     InvalidNthChildPattern(&'a str),
     InvalidNthChild(ParseIntError),
+    /// The selector inside of `:not()` is empty, invalid or contains a combinator, i.e. `:not(.a > .b)`
+    InvalidNotSelector(&'a str),
 }
 
 impl<'a> From<ParseIntError> for CssPseudoSelectorParseError<'a> {
@@ -91,6 +134,10 @@ impl_display! { CssPseudoSelectorParseError<'a>, {
         number, \"even\" or \"odd\" or a pattern such as \"2n+3\"", selector
     ),
     InvalidNthChild(e) => format!("Invalid :nth-child pseudo-selector: ':{}'", e),
+    InvalidNotSelector(selector) => format!(
+        "Invalid pseudo-selector :not({}) - value has to be a selector \
+        without combinators, such as \".class\" or \"div#id\"", selector
+    ),
 }}
 
 /// "selector" contains the actual selector such as "nth-child" while "value" contains
@@ -104,17 +151,101 @@ fn pseudo_selector_from_str<'a>(selector: &'a str, value: Option<&'a str>)
         "hover" => Ok(CssPathPseudoSelector::Hover),
         "active" => Ok(CssPathPseudoSelector::Active),
         "focus" => Ok(CssPathPseudoSelector::Focus),
+        "dragging" => Ok(CssPathPseudoSelector::Dragging),
+        "drop-target" => Ok(CssPathPseudoSelector::DropTarget),
+        "only-child" => Ok(CssPathPseudoSelector::OnlyChild),
+        "empty" => Ok(CssPathPseudoSelector::Empty),
         "nth-child" => {
             let value = value.ok_or(CssPseudoSelectorParseError::EmptyNthChild)?;
             let parsed = parse_nth_child_selector(value)?;
             Ok(CssPathPseudoSelector::NthChild(parsed))
         },
+        "nth-last-child" => {
+            let value = value.ok_or(CssPseudoSelectorParseError::EmptyNthChild)?;
+            let parsed = parse_nth_child_selector(value)?;
+            Ok(CssPathPseudoSelector::NthLastChild(parsed))
+        },
+        "not" => {
+            let value = value.ok_or(CssPseudoSelectorParseError::InvalidNotSelector(""))?;
+            Ok(CssPathPseudoSelector::Not(parse_not_selector(value)?))
+        },
         _ => {
             Err(CssPseudoSelectorParseError::UnknownSelector(selector, value))
         },
     }
 }
 
+/// Parses the selector inside of `:not()`, i.e. `.a.b` in `:not(.a.b)` - the selector
+/// can only consist of a single group of selectors (no `>`, ` `, `+` or `~` combinators)
+fn parse_not_selector<'a>(value: &'a str) -> Result<Vec<CssPathSelector>, CssPseudoSelectorParseError<'a>> {
+    use self::CssPathSelector::*;
+
+    let path = parse_css_path(value).map_err(|_| CssPseudoSelectorParseError::InvalidNotSelector(value))?;
+
+    let has_combinator = path.selectors.iter().any(|selector| match selector {
+        DirectChildren | Children | AdjacentSibling | GeneralSibling => true,
+        _ => false,
+    });
+
+    if has_combinator {
+        Err(CssPseudoSelectorParseError::InvalidNotSelector(value))
+    } else {
+        Ok(path.selectors)
+    }
+}
+
+/// Parses the content of an attribute selector (the text between the square brackets), i.e.
+/// `data-state="open"` -> `CssAttributeSelector { name: "data-state", condition: Equals("open") }`
+fn parse_attribute_selector(selector: &str) -> Option<CssAttributeSelector> {
+    use self::CssAttributeCondition::*;
+
+    let selector = selector.trim();
+
+    let (name, condition) = match selector.find('=') {
+        None => (selector, Exists),
+        Some(equals_pos) => {
+            let value = parse_attribute_value(&selector[equals_pos + 1..])?;
+            let name = &selector[..equals_pos];
+            // All operators are ASCII characters, so `len() - 1` is always a char boundary
+            match name.chars().last()? {
+                '~' => (&name[..name.len() - 1], ContainsWord(value)),
+                '|' => (&name[..name.len() - 1], DashMatch(value)),
+                '^' => (&name[..name.len() - 1], StartsWith(value)),
+                '$' => (&name[..name.len() - 1], EndsWith(value)),
+                '*' => (&name[..name.len() - 1], Contains(value)),
+                _ => (name, Equals(value)),
+            }
+        },
+    };
+
+    let name = name.trim();
+
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return None;
+    }
+
+    Some(CssAttributeSelector { name: name.to_string(), condition })
+}
+
+/// Parses the value of an attribute selector, which can be quoted (`"open"`, `'open'`) or unquoted (`open`)
+fn parse_attribute_value(value: &str) -> Option<String> {
+    let value = value.trim();
+    for quote in &['"', '\''] {
+        if value.starts_with(*quote) {
+            return if value.len() > 1 && value.ends_with(*quote) {
+                Some(value[1..value.len() - 1].to_string())
+            } else {
+                None
+            };
+        }
+    }
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
 /// Parses the inner value of the `:nth-child` selector, including numbers and patterns.
 ///
 /// I.e.: `"2n+3"` -> `Pattern { repeat: 2, offset: 3 }`
@@ -186,23 +317,31 @@ fn test_css_pseudo_selector_parse() {
         (("hover", None), Hover),
         (("active", None), Active),
         (("focus", None), Focus),
+        (("dragging", None), Dragging),
+        (("drop-target", None), DropTarget),
         (("nth-child", Some("4")), NthChild(Number(4))),
         (("nth-child", Some("even")), NthChild(Even)),
         (("nth-child", Some("odd")), NthChild(Odd)),
         (("nth-child", Some("5n")), NthChild(Pattern { repeat: 5, offset: 0 })),
         (("nth-child", Some("2n+3")), NthChild(Pattern { repeat: 2, offset: 3 })),
+        (("nth-last-child", Some("odd")), NthLastChild(Odd)),
+        (("only-child", None), OnlyChild),
+        (("empty", None), Empty),
+        (("not", Some(".a#b")), Not(vec![CssPathSelector::Class("a".into()), CssPathSelector::Id("b".into())])),
     ];
 
     let err = [
         (("asdf", None), UnknownSelector("asdf", None)),
         (("", None), UnknownSelector("", None)),
         (("nth-child", Some("2n+")), InvalidNthChildPattern("2n+")),
+        (("not", None), InvalidNotSelector("")),
+        (("not", Some(".a > .b")), InvalidNotSelector(".a > .b")),
         // Can't test for ParseIntError because the fields are private.
         // This is an example on why you shouldn't use std::error::Error!
     ];
 
     for ((selector, val), a) in &ok_res {
-        assert_eq!(pseudo_selector_from_str(selector, *val), Ok(*a));
+        assert_eq!(pseudo_selector_from_str(selector, *val), Ok(a.clone()));
     }
 
     for ((selector, val), e) in &err {
@@ -223,37 +362,783 @@ impl<'a> fmt::Display for CssParseError<'a> {
 }
 
 pub fn new_from_str<'a>(css_string: &'a str) -> Result<Css, CssParseError<'a>> {
-    let mut tokenizer = Tokenizer::new(css_string);
-    match new_from_str_inner(css_string, &mut tokenizer) {
-        Ok(stylesheet) => Ok(Css {
+    match new_stylesheet_from_str(css_string, false) {
+        Ok(ref parsed) if !parsed.imports.is_empty() => {
+            Err(CssParseError {
+                error: CssParseErrorInner::UnresolvedImport(parsed.imports[0].path),
+                location: get_error_location(css_string, parsed.imports[0].offset + 1),
+            })
+        },
+        Ok(parsed) => Ok(Css {
             stylesheets: vec![
-                stylesheet
+                parsed.stylesheet
             ],
         }),
-        Err(e) => {
-            let error_location = tokenizer.pos().saturating_sub(1);
-            let line_number: usize = css_string[0..error_location].lines().count();
-
-            // Rust doesn't count "\n" as a character, so we have to add the line number count on top
-            let total_characters: usize = css_string[0..error_location].lines().take(line_number.saturating_sub(1)).map(|line| line.chars().count()).sum();
-            let total_characters = total_characters + line_number;
-            /*println!("line_number: {} error location: {}, total characters: {}", line_number,
-                     error_location, total_characters);*/
-            let characters_in_line = (error_location + 2) - total_characters;
-
-            let error_location = ErrorLocation {
-                line: line_number,
-                column: characters_in_line,
-            };
-
+        Err((e, position)) => {
             Err(CssParseError {
                 error: e,
-                location: error_location,
+                location: get_error_location(css_string, position),
             })
         }
     }
 }
 
+/// Same as `new_from_str`, but doesn't stop at the first error: Like in a browser, invalid
+/// declarations and rules with invalid selectors are skipped. Returns the valid part of the
+/// CSS together with all errors, sorted by their location.
+pub fn new_from_str_lenient<'a>(css_string: &'a str) -> (Css, Vec<CssParseError<'a>>) {
+
+    let (stylesheet, mut errors) = match new_stylesheet_from_str(css_string, true) {
+        Ok(parsed) => {
+            let mut errors = parsed.errors;
+            errors.extend(parsed.imports.iter().map(|import| (CssParseErrorInner::UnresolvedImport(import.path), import.offset + 1)));
+            (parsed.stylesheet, errors)
+        },
+        Err(e) => (Stylesheet::default(), vec![e]),
+    };
+
+    errors.sort_by_key(|(_, position)| *position);
+
+    let errors = errors.into_iter().map(|(error, position)| CssParseError {
+        error,
+        location: get_error_location(css_string, position),
+    }).collect();
+
+    (Css { stylesheets: vec![stylesheet] }, errors)
+}
+
+/// Error that can happen while loading a CSS file (or one of the files it imports)
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssFileError {
+    /// The file could not be read
+    Io(PathBuf, String),
+    /// The file contains invalid CSS
+    Parse(PathBuf, ErrorLocation, String),
+    /// The file imports itself, either directly or via other files
+    ImportCycle(Vec<PathBuf>),
+}
+
+impl fmt::Display for CssFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssFileError::*;
+        match self {
+            Io(file, e) => write!(f, "Io error: Could not load \"{}\": {}", file.display(), e),
+            Parse(file, location, e) => write!(f, "CSS error at {}:{}:{}: {}", file.display(), location.line, location.column, e),
+            ImportCycle(files) => {
+                let files = files.iter().map(|file| file.display().to_string()).collect::<Vec<_>>();
+                write!(f, "Cyclic @import: {}", files.join(" -> "))
+            },
+        }
+    }
+}
+
+/// Loads and parses a CSS file, `@import` rules are resolved relative to the importing file.
+/// The rules of the returned CSS remember the file and line that they were defined at.
+pub fn new_from_file<P: AsRef<Path>>(file_path: P) -> Result<Css, CssFileError> {
+    new_from_file_with_imports(file_path).map(|(css, _)| css)
+}
+
+/// Same as `new_from_file`, but also returns the (canonicalized) paths of all loaded files,
+/// i.e. the file itself and all files that it imports, directly or indirectly
+pub fn new_from_file_with_imports<P: AsRef<Path>>(file_path: P) -> Result<(Css, Vec<PathBuf>), CssFileError> {
+    let mut loaded_files = Vec::new();
+    let stylesheet = load_stylesheet_from_file(file_path.as_ref(), &mut Vec::new(), &mut loaded_files, None)?;
+    Ok((Css { stylesheets: vec![stylesheet] }, loaded_files))
+}
+
+/// CSS file loaded by `new_from_file_lenient`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LenientCssFile {
+    /// The valid rules of the file and the files it imports
+    pub css: Css,
+    /// The (canonicalized) paths of the file and all files that it imports, directly or indirectly
+    pub loaded_files: Vec<PathBuf>,
+    /// Errors of the skipped rules, declarations and imports
+    pub errors: Vec<CssFileError>,
+}
+
+/// Same as `new_from_file_with_imports`, but like `new_from_str_lenient`, invalid rules and declarations
+/// (as well as imported files that can't be loaded) are skipped and their errors are collected.
+/// Only fails if the file itself can't be read.
+pub fn new_from_file_lenient<P: AsRef<Path>>(file_path: P) -> Result<LenientCssFile, CssFileError> {
+    let mut loaded_files = Vec::new();
+    let mut errors = Vec::new();
+    let stylesheet = load_stylesheet_from_file(file_path.as_ref(), &mut Vec::new(), &mut loaded_files, Some(&mut errors))?;
+    Ok(LenientCssFile { css: Css { stylesheets: vec![stylesheet] }, loaded_files, errors })
+}
+
+/// Loads a CSS file and the files it imports. `import_stack` contains the files that
+/// are currently being imported, in order to detect cyclic imports. If `errors` is `Some`,
+/// the file is parsed in lenient mode and the errors are appended to `errors`.
+fn load_stylesheet_from_file(
+    file_path: &Path,
+    import_stack: &mut Vec<PathBuf>,
+    loaded_files: &mut Vec<PathBuf>,
+    mut errors: Option<&mut Vec<CssFileError>>,
+) -> Result<Stylesheet, CssFileError> {
+
+    let io_error = |e: io::Error| CssFileError::Io(file_path.to_path_buf(), e.to_string());
+
+    let canonical_path = fs::canonicalize(file_path).map_err(io_error)?;
+
+    if let Some(cycle_start) = import_stack.iter().position(|file| *file == canonical_path) {
+        let mut cycle = import_stack[cycle_start..].to_vec();
+        cycle.push(canonical_path);
+        return Err(CssFileError::ImportCycle(cycle));
+    }
+
+    let css_string = fs::read_to_string(file_path).map_err(io_error)?;
+
+    if !loaded_files.contains(&canonical_path) {
+        loaded_files.push(canonical_path.clone());
+    }
+
+    let parse_error = |e: CssParseErrorInner, position: usize| {
+        CssFileError::Parse(file_path.to_path_buf(), get_error_location(&css_string, position), e.to_string())
+    };
+
+    let parsed = new_stylesheet_from_str(&css_string, errors.is_some()).map_err(|(e, position)| parse_error(e, position))?;
+    let mut stylesheet = parsed.stylesheet;
+
+    if let Some(errors) = errors.as_mut() {
+        errors.extend(parsed.errors.into_iter().map(|(e, position)| parse_error(e, position)));
+    }
+
+    let media_rules = stylesheet.media_rules.iter_mut().flat_map(|media_rule| media_rule.rules.iter_mut());
+    for rule in stylesheet.rules.iter_mut().chain(media_rules) {
+        if let Some(location) = rule.location.as_mut() {
+            location.file = Some(file_path.to_path_buf());
+        }
+    }
+
+    let directory = file_path.parent().unwrap_or_else(|| Path::new(""));
+
+    // Font files are relative to the stylesheet that contains the `@font-face` rule
+    for source in stylesheet.font_faces.iter_mut().flat_map(|font_face| font_face.sources.iter_mut()) {
+        if let CssFontFaceSource::Url(path) = source {
+            if path.is_relative() {
+                *path = directory.join(path.as_path());
+            }
+        }
+    }
+
+    if parsed.imports.is_empty() {
+        return Ok(stylesheet);
+    }
+
+    // Imported rules come before the rules of the importing file, so that they can be overridden
+    let mut merged = Stylesheet::default();
+
+    import_stack.push(canonical_path);
+    for import in &parsed.imports {
+        let import_path = directory.join(import.path);
+        match load_stylesheet_from_file(&import_path, import_stack, loaded_files, errors.as_mut().map(|errors| &mut **errors)) {
+            Ok(imported) => {
+                merged.rules.extend(imported.rules);
+                merged.keyframes.extend(imported.keyframes);
+                merged.media_rules.extend(imported.media_rules);
+                merged.font_faces.extend(imported.font_faces);
+            },
+            Err(e) => match errors.as_mut() {
+                Some(errors) => errors.push(e),
+                None => return Err(e),
+            },
+        }
+    }
+    import_stack.pop();
+
+    merged.rules.extend(stylesheet.rules);
+    merged.keyframes.extend(stylesheet.keyframes);
+    merged.media_rules.extend(stylesheet.media_rules);
+    merged.font_faces.extend(stylesheet.font_faces);
+
+    Ok(merged)
+}
+
+/// Converts the byte position of an error (as reported by the tokenizer,
+/// one character past the error) into a line and column
+fn get_error_location(css_string: &str, position: usize) -> ErrorLocation {
+    let error_location = position.saturating_sub(1).min(css_string.len());
+    let line_number: usize = css_string[0..error_location].lines().count();
+
+    // Rust doesn't count "\n" as a character, so we have to add the line number count on top
+    let total_characters: usize = css_string[0..error_location].lines().take(line_number.saturating_sub(1)).map(|line| line.chars().count()).sum();
+    let total_characters = total_characters + line_number;
+    /*println!("line_number: {} error location: {}, total characters: {}", line_number,
+             error_location, total_characters);*/
+    let characters_in_line = (error_location + 2).saturating_sub(total_characters);
+
+    ErrorLocation {
+        line: line_number,
+        column: characters_in_line,
+    }
+}
+
+/// Top-level part of a CSS file: Either a list of regular rules (`div { ... }`),
+/// which are parsed by the tokenizer or an at-rule (`@keyframes name { ... }`),
+/// which the tokenizer doesn't understand.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum CssSegment<'a> {
+    /// Regular CSS rules, `offset` is the byte offset of `text` in the whole file
+    Rules { offset: usize, text: &'a str },
+    /// `@name prelude { block }` or `@name prelude;`, `offset` is the position of the `@`,
+    /// the block is stored together with its byte offset in the whole file
+    AtRule { offset: usize, name: &'a str, prelude: &'a str, block: Option<(usize, &'a str)> },
+}
+
+/// Returns the position of the first character after the comment or string
+/// starting at `start` or `None` if there is no comment or string at `start`
+fn skip_comment_or_string(css_string: &str, start: usize) -> Option<usize> {
+    let rest = &css_string[start..];
+    if rest.starts_with("/*") {
+        Some(rest[2..].find("*/").map(|end| start + 2 + end + 2).unwrap_or(css_string.len()))
+    } else if rest.starts_with('"') || rest.starts_with('\'') {
+        let quote = &rest[..1];
+        Some(rest[1..].find(quote).map(|end| start + 1 + end + 1).unwrap_or(css_string.len()))
+    } else {
+        None
+    }
+}
+
+/// Returns the position of the matching `}` for the `{` at `block_start`
+fn find_block_end(css_string: &str, block_start: usize) -> Option<usize> {
+    let mut nesting = 0_usize;
+    let mut position = block_start;
+
+    while position < css_string.len() {
+        if let Some(next) = skip_comment_or_string(css_string, position) {
+            position = next;
+            continue;
+        }
+        let c = css_string[position..].chars().next()?;
+        match c {
+            '{' => nesting += 1,
+            '}' => {
+                nesting -= 1;
+                if nesting == 0 {
+                    return Some(position);
+                }
+            },
+            _ => { },
+        }
+        position += c.len_utf8();
+    }
+
+    None
+}
+
+/// Splits the CSS file into regular rules and at-rules, so that the at-rules can be parsed separately
+fn split_css_segments<'a>(css_string: &'a str) -> Result<Vec<CssSegment<'a>>, (CssParseErrorInner<'a>, usize)> {
+
+    let mut segments = Vec::new();
+    let mut segment_start = 0;
+    let mut block_nesting = 0_usize;
+    let mut position = 0;
+
+    while position < css_string.len() {
+
+        if let Some(next) = skip_comment_or_string(css_string, position) {
+            position = next;
+            continue;
+        }
+
+        let c = css_string[position..].chars().next().unwrap();
+
+        match c {
+            '{' => block_nesting += 1,
+            '}' => block_nesting = block_nesting.saturating_sub(1),
+            '@' if block_nesting == 0 => {
+
+                if position > segment_start {
+                    segments.push(CssSegment::Rules { offset: segment_start, text: &css_string[segment_start..position] });
+                }
+
+                let name_start = position + 1;
+                let name_end = css_string[name_start..]
+                    .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+                    .map(|end| name_start + end)
+                    .unwrap_or(css_string.len());
+                let name = &css_string[name_start..name_end];
+
+                let prelude_end = css_string[name_end..]
+                    .find(|c: char| c == '{' || c == ';')
+                    .map(|end| name_end + end)
+                    .ok_or((CssParseErrorInner::MalformedCss, name_end))?;
+                let prelude = css_string[name_end..prelude_end].trim();
+
+                let (block, at_rule_end) = if css_string[prelude_end..].starts_with('{') {
+                    let block_end = find_block_end(css_string, prelude_end)
+                        .ok_or((CssParseErrorInner::UnclosedBlock, position + 1))?;
+                    (Some((prelude_end + 1, &css_string[prelude_end + 1..block_end])), block_end + 1)
+                } else {
+                    (None, prelude_end + 1)
+                };
+
+                segments.push(CssSegment::AtRule { offset: position, name, prelude, block });
+
+                position = at_rule_end;
+                segment_start = at_rule_end;
+                continue;
+            },
+            _ => { },
+        }
+
+        position += c.len_utf8();
+    }
+
+    if segment_start < css_string.len() || segments.is_empty() {
+        segments.push(CssSegment::Rules { offset: segment_start, text: &css_string[segment_start..] });
+    }
+
+    Ok(segments)
+}
+
+/// `@import "file.css";` rule, `offset` is the position of the `@`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct CssImport<'a> {
+    offset: usize,
+    path: &'a str,
+}
+
+/// Parses the path of an `@import` rule: `"file.css"`, `'file.css'`, `url("file.css")` or `url(file.css)`
+fn parse_import_path(prelude: &str) -> Option<&str> {
+
+    let prelude = prelude.trim();
+
+    let (path, is_url) = if prelude.starts_with("url(") && prelude.ends_with(')') {
+        (prelude[4..prelude.len() - 1].trim(), true)
+    } else {
+        (prelude, false)
+    };
+
+    for quote in &['"', '\''] {
+        if path.starts_with(*quote) {
+            return if path.len() > 2 && path.ends_with(*quote) {
+                Some(&path[1..path.len() - 1])
+            } else {
+                None
+            };
+        }
+    }
+
+    // Only paths inside of an url() can be unquoted
+    if is_url && !path.is_empty() && !path.contains(char::is_whitespace) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Returns the line (starting at 1) of the byte position in the string
+fn get_line_number(css_string: &str, position: usize) -> usize {
+    css_string[..position].matches('\n').count() + 1
+}
+
+/// Result of `new_stylesheet_from_str`: the parsed rules, the `@import` rules (which have to be
+/// resolved by the caller) and, in lenient mode, the errors of the skipped rules and declarations
+struct ParsedStylesheet<'a> {
+    stylesheet: Stylesheet,
+    imports: Vec<CssImport<'a>>,
+    errors: Vec<(CssParseErrorInner<'a>, usize)>,
+}
+
+/// Parses the rules and at-rules of a CSS file. In lenient mode, invalid declarations,
+/// rules and at-rules are skipped and their errors are collected instead of returned.
+/// On error, returns the byte position of the error
+fn new_stylesheet_from_str<'a>(css_string: &'a str, lenient: bool) -> Result<ParsedStylesheet<'a>, (CssParseErrorInner<'a>, usize)> {
+
+    let mut parsed = ParsedStylesheet {
+        stylesheet: Stylesheet::default(),
+        imports: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for segment in split_css_segments(css_string)? {
+        let result = match segment {
+            CssSegment::Rules { offset, text } => {
+                parse_rule_segment(css_string, offset, text, lenient, &mut parsed.stylesheet.rules, &mut parsed.errors)
+            },
+            CssSegment::AtRule { offset, name: "import", prelude, block: None } => {
+                match parse_import_path(prelude) {
+                    Some(path) => { parsed.imports.push(CssImport { offset, path }); Ok(()) },
+                    None => Err((CssParseErrorInner::MalformedImport(prelude), offset + 1)),
+                }
+            },
+            CssSegment::AtRule { offset, name: "keyframes", prelude, block: Some((_, block)) } => {
+                match parse_css_keyframes(prelude, block) {
+                    Ok(keyframes) => { parsed.stylesheet.keyframes.push(keyframes); Ok(()) },
+                    Err(e) => Err((e.into(), offset + 1)),
+                }
+            },
+            CssSegment::AtRule { offset, name: "media", prelude, block: Some((block_offset, block)) } => {
+                match parse_media_queries(prelude) {
+                    Ok(queries) => {
+                        let mut rules = Vec::new();
+                        let result = parse_rule_segment(css_string, block_offset, block, lenient, &mut rules, &mut parsed.errors);
+                        parsed.stylesheet.media_rules.push(CssMediaRule { queries, rules });
+                        result
+                    },
+                    Err(e) => Err((e.into(), offset + 1)),
+                }
+            },
+            CssSegment::AtRule { offset, name: "font-face", prelude: "", block: Some((_, block)) } => {
+                match parse_css_font_face(block) {
+                    Ok(font_face) => { parsed.stylesheet.font_faces.push(font_face); Ok(()) },
+                    Err(e) => Err((e.into(), offset + 1)),
+                }
+            },
+            CssSegment::AtRule { .. } => {
+                // other at-rules are not supported
+                Ok(())
+            },
+        };
+
+        match result {
+            Err(e) if lenient => parsed.errors.push(e),
+            Err(e) => return Err(e),
+            Ok(()) => { },
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Parses the rules of a segment that starts at the byte `offset` of the `css_string` into `rules`.
+/// In lenient mode, the errors of skipped rules and declarations are appended to `errors`
+fn parse_rule_segment<'a>(
+    css_string: &'a str,
+    offset: usize,
+    text: &'a str,
+    lenient: bool,
+    rules: &mut Vec<CssRuleBlock>,
+    errors: &mut Vec<(CssParseErrorInner<'a>, usize)>,
+) -> Result<(), (CssParseErrorInner<'a>, usize)> {
+    let mut tokenizer = Tokenizer::new(text);
+    let first_line = get_line_number(css_string, offset);
+    let mut segment_errors = Vec::new();
+    let result = new_from_str_inner(text, &mut tokenizer, first_line, rules, if lenient { Some(&mut segment_errors) } else { None });
+    errors.extend(segment_errors.into_iter().map(|(e, position)| (e, offset + position)));
+    result.map_err(|e| (e, offset + tokenizer.pos()))
+}
+
+/// Error that can happen while parsing the query of a `@media` rule
+#[derive(Copy, Clone, PartialEq)]
+pub enum CssMediaQueryParseError<'a> {
+    /// Only the `all` and `screen` media types are supported, i.e. `@media print { ... }`
+    UnsupportedMediaType(&'a str),
+    /// The condition has no closing parenthesis or no value, i.e. `(max-width)`
+    MalformedCondition(&'a str),
+    /// Unknown media feature, i.e. `(max-weight: 600px)`
+    UnknownFeature(&'a str, &'a str),
+    /// Invalid value for a media feature, i.e. `(min-resolution: 2px)`
+    InvalidValue(&'a str, &'a str),
+}
+
+impl_debug_as_display!(CssMediaQueryParseError<'a>);
+impl_display!{ CssMediaQueryParseError<'a>, {
+    UnsupportedMediaType(e) => format!("Unsupported media type: \"{}\" - has to be \"all\" or \"screen\"", e),
+    MalformedCondition(e) => format!("Malformed media condition: \"{}\"", e),
+    UnknownFeature(k, v) => format!("Unknown media feature: \"({}: {})\"", k, v),
+    InvalidValue(k, v) => format!("Invalid value for media feature: \"({}: {})\"", k, v),
+}}
+
+/// Parses the comma-separated queries of a `@media` rule, i.e.
+/// `screen and (max-width: 600px), (prefers-color-scheme: dark)`
+fn parse_media_queries<'a>(prelude: &'a str) -> Result<Vec<CssMediaQuery>, CssMediaQueryParseError<'a>> {
+    prelude.split(',').map(parse_media_query).collect()
+}
+
+/// Parses a single media query, i.e. `screen and (min-width: 600px) and (max-width: 900px)`
+fn parse_media_query<'a>(query: &'a str) -> Result<CssMediaQuery, CssMediaQueryParseError<'a>> {
+
+    let mut conditions = Vec::new();
+    let mut rest = query.trim();
+
+    while !rest.is_empty() {
+        if rest.starts_with('(') {
+            let condition_end = rest.find(')').ok_or(CssMediaQueryParseError::MalformedCondition(rest))?;
+            parse_media_condition(&rest[1..condition_end], &mut conditions)?;
+            rest = rest[condition_end + 1..].trim_start();
+        } else {
+            let word_end = rest.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(rest.len());
+            match &rest[..word_end] {
+                "and" | "only" | "all" | "screen" => { },
+                other => return Err(CssMediaQueryParseError::UnsupportedMediaType(other)),
+            }
+            rest = rest[word_end..].trim_start();
+        }
+    }
+
+    Ok(CssMediaQuery { conditions })
+}
+
+/// Parses the content of a media condition (the text between the parentheses),
+/// i.e. `max-width: 600px` and appends the resulting conditions to `conditions`
+fn parse_media_condition<'a>(condition: &'a str, conditions: &mut Vec<CssMediaCondition>)
+-> Result<(), CssMediaQueryParseError<'a>>
+{
+    use self::CssMediaCondition::*;
+
+    let colon = condition.find(':').ok_or(CssMediaQueryParseError::MalformedCondition(condition))?;
+    let feature = condition[..colon].trim();
+    let value = condition[colon + 1..].trim();
+
+    let invalid_value = || CssMediaQueryParseError::InvalidValue(feature, value);
+    let pixels = || css_parser::parse_pixel_value(value).map(|v| v.to_pixels()).map_err(|_| invalid_value());
+    let resolution = || parse_media_resolution(value).ok_or_else(invalid_value);
+
+    match feature {
+        "width" => { let width = pixels()?; conditions.push(MinWidth(width)); conditions.push(MaxWidth(width)); },
+        "min-width" => conditions.push(MinWidth(pixels()?)),
+        "max-width" => conditions.push(MaxWidth(pixels()?)),
+        "height" => { let height = pixels()?; conditions.push(MinHeight(height)); conditions.push(MaxHeight(height)); },
+        "min-height" => conditions.push(MinHeight(pixels()?)),
+        "max-height" => conditions.push(MaxHeight(pixels()?)),
+        "resolution" => { let res = resolution()?; conditions.push(MinResolution(res)); conditions.push(MaxResolution(res)); },
+        "min-resolution" => conditions.push(MinResolution(resolution()?)),
+        "max-resolution" => conditions.push(MaxResolution(resolution()?)),
+        "prefers-color-scheme" => match value {
+            "light" => conditions.push(PrefersColorScheme(ColorScheme::Light)),
+            "dark" => conditions.push(PrefersColorScheme(ColorScheme::Dark)),
+            _ => return Err(invalid_value()),
+        },
+        _ => return Err(CssMediaQueryParseError::UnknownFeature(feature, value)),
+    }
+
+    Ok(())
+}
+
+/// Parses a resolution (`2dppx`, `2x`, `192dpi` or `75.6dpcm`) into device pixels per logical pixel
+fn parse_media_resolution(value: &str) -> Option<f32> {
+    const DPI_PER_DPPX: f32 = 96.0;
+    const CM_PER_INCH: f32 = 2.54;
+
+    let (number, factor) = if value.ends_with("dppx") {
+        (&value[..value.len() - 4], 1.0)
+    } else if value.ends_with("dpcm") {
+        (&value[..value.len() - 4], CM_PER_INCH / DPI_PER_DPPX)
+    } else if value.ends_with("dpi") {
+        (&value[..value.len() - 3], 1.0 / DPI_PER_DPPX)
+    } else if value.ends_with('x') {
+        (&value[..value.len() - 1], 1.0)
+    } else {
+        return None;
+    };
+
+    number.trim().parse::<f32>().ok().map(|number| number * factor)
+}
+
+/// Error that can happen while parsing a `@keyframes` rule
+#[derive(Clone, PartialEq)]
+pub enum CssKeyframesParseError<'a> {
+    /// The name of the animation is missing, i.e. `@keyframes { ... }`
+    MissingName,
+    /// Keyframe selectors have to be `from`, `to` or a percentage, i.e. `50%`
+    InvalidSelector(&'a str),
+    /// The block of a keyframe is not closed, i.e. `from { width: 5px; `
+    UnclosedBlock(&'a str),
+    /// The declaration has no value, i.e. `from { width }`
+    MalformedDeclaration(&'a str),
+    /// A property in a keyframe has an unknown key, i.e. `from { alsdfkj: 500px }`
+    UnknownPropertyKey(&'a str, &'a str),
+    /// Keyframes can only contain static values, no `[[ dynamic ]]` values or `var()` references
+    InvalidValue(CssParsingError<'a>),
+}
+
+impl_debug_as_display!(CssKeyframesParseError<'a>);
+impl_display!{ CssKeyframesParseError<'a>, {
+    MissingName => "Missing name of the animation, i.e. \"@keyframes fade-in { ... }\"",
+    InvalidSelector(e) => format!("Invalid keyframe selector: \"{}\" - has to be \"from\", \"to\" or a percentage", e),
+    UnclosedBlock(e) => format!("Unclosed keyframe block: \"{}\"", e),
+    MalformedDeclaration(e) => format!("Malformed declaration: \"{}\"", e),
+    UnknownPropertyKey(k, v) => format!("Unknown CSS key: \"{}: {}\"", k, v),
+    InvalidValue(e) => format!("Invalid value: {}", e),
+}}
+
+impl_from! { CssParsingError<'a>, CssKeyframesParseError::InvalidValue }
+
+/// Parses the selector of a keyframe (`from`, `to`, `50%` or a comma-separated list of those)
+fn parse_keyframe_selector<'a>(selector: &'a str) -> Result<Vec<PercentageValue>, CssKeyframesParseError<'a>> {
+    selector.split(',').map(|offset| {
+        let offset = offset.trim();
+        match offset {
+            "from" => Ok(PercentageValue::new(0.0)),
+            "to" => Ok(PercentageValue::new(100.0)),
+            other => {
+                if !other.ends_with('%') {
+                    return Err(CssKeyframesParseError::InvalidSelector(other));
+                }
+                match other[..other.len() - 1].trim().parse::<f32>() {
+                    Ok(percent) if percent >= 0.0 && percent <= 100.0 => Ok(PercentageValue::new(percent)),
+                    _ => Err(CssKeyframesParseError::InvalidSelector(other)),
+                }
+            }
+        }
+    }).collect()
+}
+
+/// Parses a `@keyframes` rule, given the name of the animation and the content of the block:
+///
+/// ```no_run,ignore
+/// @keyframes fade-in {
+///     from { background: #00000000; }
+///     to { background: #ff0000; }
+/// }
+/// ```
+fn parse_css_keyframes<'a>(name: &'a str, block: &'a str) -> Result<CssKeyframes, CssKeyframesParseError<'a>> {
+
+    if name.is_empty() {
+        return Err(CssKeyframesParseError::MissingName);
+    }
+
+    let css_property_map = azul_css::get_css_key_map();
+    let mut keyframes = Vec::new();
+    let mut rest = block;
+
+    while let Some(block_start) = rest.find('{') {
+
+        let selector = rest[..block_start].trim();
+        let block_end = rest[block_start..].find('}')
+            .map(|end| block_start + end)
+            .ok_or(CssKeyframesParseError::UnclosedBlock(selector))?;
+
+        let mut properties = Vec::new();
+
+        for declaration in rest[block_start + 1..block_end].split(';') {
+            let declaration = declaration.trim();
+            if declaration.is_empty() {
+                continue;
+            }
+            let colon = declaration.find(':').ok_or(CssKeyframesParseError::MalformedDeclaration(declaration))?;
+            let key = declaration[..colon].trim();
+            let value = declaration[colon + 1..].trim();
+            let key_type = CssPropertyType::from_str(key, &css_property_map)
+                .ok_or(CssKeyframesParseError::UnknownPropertyKey(key, value))?;
+            properties.push(css_parser::parse_key_value_pair(key_type, value)?);
+        }
+
+        for offset in parse_keyframe_selector(selector)? {
+            keyframes.push(CssKeyframe { offset, properties: properties.clone() });
+        }
+
+        rest = &rest[block_end + 1..];
+    }
+
+    if !rest.trim().is_empty() {
+        return Err(CssKeyframesParseError::UnclosedBlock(rest.trim()));
+    }
+
+    // stable sort, so keyframes with the same offset keep the order of the source
+    keyframes.sort_by_key(|keyframe| keyframe.offset);
+
+    Ok(CssKeyframes {
+        name: name.to_string(),
+        keyframes,
+    })
+}
+
+/// Error that can happen while parsing a `@font-face` rule
+#[derive(Copy, Clone, PartialEq)]
+pub enum CssFontFaceParseError<'a> {
+    /// The descriptor has no value, i.e. `@font-face { font-family }`
+    MalformedDeclaration(&'a str),
+    /// The `font-family` descriptor is missing or contains more than one name
+    MissingFamily,
+    /// The `src` descriptor is missing
+    MissingSource,
+    /// A source has to be `url(...)` or `local(...)`, optionally followed by a `format()`
+    InvalidSource(&'a str),
+    /// Invalid value for a descriptor, i.e. `font-weight: heavy`
+    InvalidValue(&'a str, &'a str),
+}
+
+impl_debug_as_display!(CssFontFaceParseError<'a>);
+impl_display!{ CssFontFaceParseError<'a>, {
+    MalformedDeclaration(e) => format!("Malformed declaration: \"{}\"", e),
+    MissingFamily => "Missing or invalid font family, i.e. \"font-family: Roboto;\"",
+    MissingSource => "Missing source of the font, i.e. \"src: url(Roboto.ttf);\"",
+    InvalidSource(e) => format!("Invalid font source: \"{}\" - has to be \"url(...)\" or \"local(...)\"", e),
+    InvalidValue(k, v) => format!("Invalid value for descriptor: \"{}: {}\"", k, v),
+}}
+
+/// Parses the content of a `@font-face` block - unknown descriptors (such as `unicode-range`) are ignored:
+///
+/// ```no_run,ignore
+/// @font-face {
+///     font-family: "Roboto";
+///     src: url("fonts/Roboto-Bold.woff") format("woff"), local("Roboto Bold");
+///     font-weight: bold;
+///     font-style: normal;
+/// }
+/// ```
+fn parse_css_font_face<'a>(block: &'a str) -> Result<CssFontFace, CssFontFaceParseError<'a>> {
+
+    let mut family = None;
+    let mut sources = Vec::new();
+    let mut weight = StyleFontWeight::default();
+    let mut style = StyleFontStyle::default();
+
+    for declaration in block.split(';') {
+        let declaration = declaration.trim();
+        if declaration.is_empty() {
+            continue;
+        }
+        let colon = declaration.find(':').ok_or(CssFontFaceParseError::MalformedDeclaration(declaration))?;
+        let key = declaration[..colon].trim();
+        let value = declaration[colon + 1..].trim();
+        let invalid_value = || CssFontFaceParseError::InvalidValue(key, value);
+
+        match key {
+            "font-family" => {
+                let mut fonts = css_parser::parse_style_font_family(value).map_err(|_| invalid_value())?.fonts;
+                if fonts.len() != 1 || fonts[0].0.is_empty() {
+                    return Err(CssFontFaceParseError::MissingFamily);
+                }
+                family = fonts.pop().map(|font| font.0);
+            },
+            "src" => {
+                sources = css_parser::split_top_level_commas(value).into_iter()
+                    .map(parse_font_face_source)
+                    .collect::<Result<Vec<_>, _>>()?;
+            },
+            "font-weight" => weight = css_parser::parse_style_font_weight(value).map_err(|_| invalid_value())?,
+            "font-style" => style = css_parser::parse_style_font_style(value).map_err(|_| invalid_value())?,
+            _ => { },
+        }
+    }
+
+    let family = family.ok_or(CssFontFaceParseError::MissingFamily)?;
+
+    if sources.is_empty() {
+        return Err(CssFontFaceParseError::MissingSource);
+    }
+
+    Ok(CssFontFace { family, sources, weight, style })
+}
+
+/// Parses one source of the `src` descriptor, i.e. `url("Roboto.woff") format("woff")` or `local(Roboto)` -
+/// the `format()` hint is ignored, since the font loader detects the format from the file
+fn parse_font_face_source<'a>(source: &'a str) -> Result<CssFontFaceSource, CssFontFaceParseError<'a>> {
+
+    let source = source.trim();
+    let location = css_parser::split_top_level_whitespace(source).into_iter().next().unwrap_or("");
+
+    if location.starts_with("url(") {
+        parse_import_path(location)
+            .map(|path| CssFontFaceSource::Url(PathBuf::from(path)))
+            .ok_or(CssFontFaceParseError::InvalidSource(source))
+    } else if location.starts_with("local(") && location.ends_with(')') {
+        let name = location[6..location.len() - 1].trim().trim_matches('"').trim_matches('\'').trim();
+        if name.is_empty() {
+            Err(CssFontFaceParseError::InvalidSource(source))
+        } else {
+            Ok(CssFontFaceSource::Local(name.to_string()))
+        }
+    } else {
+        Err(CssFontFaceParseError::InvalidSource(source))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum CssPathParseError<'a> {
     EmptyPath,
@@ -265,6 +1150,8 @@ pub enum CssPathParseError<'a> {
     NodeTypePath(NodeTypePathParseError<'a>),
     /// Error while parsing a pseudo selector (like `:aldkfja`)
     PseudoSelectorParseError(CssPseudoSelectorParseError<'a>),
+    /// Invalid attribute selector, such as `[data-state="open]`
+    InvalidAttributeSelector(&'a str),
 }
 
 impl_from! { NodeTypePathParseError<'a>, CssPathParseError::NodeTypePath }
@@ -288,7 +1175,7 @@ impl<'a> From<CssSyntaxError> for CssPathParseError<'a> {
 /// # };
 ///
 /// assert_eq!(
-///     parse_css_path("* div #my_id > .class:nth-child(2)"),
+///     parse_css_path("* div #my_id > .class:nth-child(2) + p"),
 ///     Ok(CssPath { selectors: vec![
 ///          Global,
 ///          Type(Div),
@@ -297,6 +1184,8 @@ impl<'a> From<CssSyntaxError> for CssPathParseError<'a> {
 ///          DirectChildren,
 ///          Class("class".to_string()),
 ///          PseudoSelector(NthChild(Number(2))),
+///          AdjacentSibling,
+///          Type(P),
 ///     ]})
 /// );
 /// ```
@@ -330,6 +1219,16 @@ pub fn parse_css_path<'a>(input: &'a str) -> Result<CssPath, CssPathParseError<'
             Token::Combinator(Combinator::Space) => {
                 selectors.push(CssPathSelector::Children);
             },
+            Token::Combinator(Combinator::Plus) => {
+                selectors.push(CssPathSelector::AdjacentSibling);
+            },
+            Token::Combinator(Combinator::Tilde) => {
+                selectors.push(CssPathSelector::GeneralSibling);
+            },
+            Token::AttributeSelector(attribute) => {
+                let attribute = parse_attribute_selector(attribute).ok_or(CssPathParseError::InvalidAttributeSelector(attribute))?;
+                selectors.push(CssPathSelector::Attribute(attribute));
+            },
             Token::PseudoClass { selector, value } => {
                 selectors.push(CssPathSelector::PseudoSelector(pseudo_selector_from_str(selector, value)?));
             },
@@ -349,11 +1248,24 @@ pub fn parse_css_path<'a>(input: &'a str) -> Result<CssPath, CssPathParseError<'
     }
 }
 
-/// Parses a CSS string (single-threaded) and returns the parsed rules in blocks
-fn new_from_str_inner<'a>(css_string: &'a str, tokenizer: &mut Tokenizer<'a>) -> Result<Stylesheet, CssParseErrorInner<'a>> {
+/// Parses a CSS string (single-threaded) and appends the parsed rules in blocks to `css_blocks`,
+/// `first_line` is the line of the start of the `css_string` in the whole file.
+///
+/// If `errors` is `Some`, invalid declarations and rules with invalid selectors are skipped
+/// and the errors (with their position in the `css_string`) are appended to `errors` instead.
+fn new_from_str_inner<'a>(
+    css_string: &'a str,
+    tokenizer: &mut Tokenizer<'a>,
+    first_line: usize,
+    css_blocks: &mut Vec<CssRuleBlock>,
+    mut errors: Option<&mut Vec<(CssParseErrorInner<'a>, usize)>>,
+) -> Result<(), CssParseErrorInner<'a>> {
     use simplecss::{Token, Combinator};
 
-    let mut css_blocks = Vec::new();
+    let mut report_error = |error: CssParseErrorInner<'a>, position: usize| match errors.as_mut() {
+        Some(errors) => { errors.push((error, position)); Ok(()) },
+        None => Err(error),
+    };
 
     // Used for error checking / checking for closed braces
     let mut parser_in_block = false;
@@ -367,6 +1279,10 @@ fn new_from_str_inner<'a>(css_string: &'a str, tokenizer: &mut Tokenizer<'a>) ->
     let mut current_rules = Vec::new();
     // Keep track of the current path during parsing
     let mut last_path = Vec::new();
+    // Line of the `{` of the current block, stored as the location of the rules
+    let mut block_start_line = first_line;
+    // Set if one of the selectors of the current block is invalid, which discards the whole block
+    let mut invalid_selector = false;
 
     let css_property_map = azul_css::get_css_key_map();
     loop {
@@ -379,6 +1295,7 @@ fn new_from_str_inner<'a>(css_string: &'a str, tokenizer: &mut Tokenizer<'a>) ->
                 }
                 parser_in_block = true;
                 block_nesting += 1;
+                block_start_line = first_line + get_line_number(css_string, tokenizer.pos()) - 1;
                 current_paths.push(last_path.clone());
                 last_path.clear();
             },
@@ -392,10 +1309,15 @@ fn new_from_str_inner<'a>(css_string: &'a str, tokenizer: &mut Tokenizer<'a>) ->
                     return Err(CssParseErrorInner::MalformedCss);
                 }
                 parser_in_block = false;
+                if invalid_selector {
+                    current_paths.clear();
+                    invalid_selector = false;
+                }
                 for path in current_paths.drain(..) {
                     css_blocks.push(CssRuleBlock {
                         path: CssPath { selectors: path },
                         declarations: current_rules.clone(),
+                        location: Some(CssSourceLocation { file: None, line: block_start_line }),
                     })
                 }
                 current_rules.clear();
@@ -413,53 +1335,83 @@ fn new_from_str_inner<'a>(css_string: &'a str, tokenizer: &mut Tokenizer<'a>) ->
                 if parser_in_block {
                     return Err(CssParseErrorInner::MalformedCss);
                 }
-                last_path.push(CssPathSelector::Type(NodeTypePath::from_str(div_type)?));
+                match NodeTypePath::from_str(div_type) {
+                    Ok(node_type) => last_path.push(CssPathSelector::Type(node_type)),
+                    Err(e) => { invalid_selector = true; report_error(e.into(), tokenizer.pos())?; },
+                }
+            },
+            Token::IdSelector(id) => {
+                if parser_in_block {
+                    return Err(CssParseErrorInner::MalformedCss);
+                }
+                last_path.push(CssPathSelector::Id(id.to_string()));
+            },
+            Token::ClassSelector(class) => {
+                if parser_in_block {
+                    return Err(CssParseErrorInner::MalformedCss);
+                }
+                last_path.push(CssPathSelector::Class(class.to_string()));
+            },
+            Token::Combinator(Combinator::GreaterThan) => {
+                if parser_in_block {
+                    return Err(CssParseErrorInner::MalformedCss);
+                }
+                last_path.push(CssPathSelector::DirectChildren);
             },
-            Token::IdSelector(id) => {
+            Token::Combinator(Combinator::Space) => {
                 if parser_in_block {
                     return Err(CssParseErrorInner::MalformedCss);
                 }
-                last_path.push(CssPathSelector::Id(id.to_string()));
+                last_path.push(CssPathSelector::Children);
             },
-            Token::ClassSelector(class) => {
+            Token::Combinator(Combinator::Plus) => {
                 if parser_in_block {
                     return Err(CssParseErrorInner::MalformedCss);
                 }
-                last_path.push(CssPathSelector::Class(class.to_string()));
+                last_path.push(CssPathSelector::AdjacentSibling);
             },
-            Token::Combinator(Combinator::GreaterThan) => {
+            Token::Combinator(Combinator::Tilde) => {
                 if parser_in_block {
                     return Err(CssParseErrorInner::MalformedCss);
                 }
-                last_path.push(CssPathSelector::DirectChildren);
+                last_path.push(CssPathSelector::GeneralSibling);
             },
-            Token::Combinator(Combinator::Space) => {
+            Token::AttributeSelector(attribute) => {
                 if parser_in_block {
                     return Err(CssParseErrorInner::MalformedCss);
                 }
-                last_path.push(CssPathSelector::Children);
+                match parse_attribute_selector(attribute) {
+                    Some(attribute) => last_path.push(CssPathSelector::Attribute(attribute)),
+                    None => {
+                        invalid_selector = true;
+                        report_error(CssParseErrorInner::InvalidAttributeSelector(attribute), tokenizer.pos())?;
+                    },
+                }
             },
             Token::PseudoClass { selector, value } => {
                 if parser_in_block {
                     return Err(CssParseErrorInner::MalformedCss);
                 }
-                last_path.push(CssPathSelector::PseudoSelector(pseudo_selector_from_str(selector, value)?));
+                match pseudo_selector_from_str(selector, value) {
+                    Ok(pseudo_selector) => last_path.push(CssPathSelector::PseudoSelector(pseudo_selector)),
+                    Err(e) => { invalid_selector = true; report_error(e.into(), tokenizer.pos())?; },
+                }
             },
             Token::Declaration(key, val) => {
                 if !parser_in_block {
                     return Err(CssParseErrorInner::MalformedCss);
                 }
 
-                let parsed_key = CssPropertyType::from_str(key, &css_property_map)
-                    .ok_or(CssParseErrorInner::UnknownPropertyKey(key, val))?;
-
-                current_rules.push(determine_static_or_dynamic_css_property(parsed_key, val)?);
+                match parse_css_declaration(key, val, &css_property_map) {
+                    Ok(declaration) => current_rules.push(declaration),
+                    Err(e) => report_error(e, tokenizer.pos())?,
+                }
             },
             Token::EndOfStream => {
                 break;
             },
             _ => {
-                // attributes, lang-attributes and @keyframes are not supported
+                // lang-attributes are not supported
             }
         }
     }
@@ -469,7 +1421,33 @@ fn new_from_str_inner<'a>(css_string: &'a str, tokenizer: &mut Tokenizer<'a>) ->
         return Err(CssParseErrorInner::UnclosedBlock);
     }
 
-    Ok(css_blocks.into())
+    Ok(())
+}
+
+/// Parses a single declaration (`key: value`) of a rule block
+fn parse_css_declaration<'a>(key: &'a str, val: &'a str, css_property_map: &BTreeMap<&'static str, CssPropertyType>)
+-> Result<CssDeclaration, CssParseErrorInner<'a>>
+{
+    // Custom properties (`--main-color: red`) don't have a known key
+    if key.starts_with(VARIABLE_PREFIX) {
+        return Ok(CssDeclaration::Variable(parse_css_variable(key, val)?));
+    }
+
+    // Transitions and animations aren't properties of the node itself
+    match key {
+        "transition" => return Ok(CssDeclaration::Transition(css_parser::parse_css_transitions(val)?)),
+        "animation" => return Ok(CssDeclaration::Animation(css_parser::parse_css_animations(val)?)),
+        _ => { },
+    }
+
+    let parsed_key = CssPropertyType::from_str(key, css_property_map)
+        .ok_or(CssParseErrorInner::UnknownPropertyKey(key, val))?;
+
+    if contains_var_function(val) && !val.trim().starts_with(START_BRACE) {
+        Ok(CssDeclaration::VarReference(parse_css_var_reference(parsed_key, val)?))
+    } else {
+        Ok(determine_static_or_dynamic_css_property(parsed_key, val)?)
+    }
 }
 
 /// Error that can happen during `css_parser::parse_key_value_pair`
@@ -585,6 +1563,205 @@ pub fn parse_dynamic_css_property<'a>(key: CssPropertyType, value: &'a str) -> R
     })
 }
 
+/// Error that can happen while parsing or substituting CSS variables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CssVarParseError {
+    /// The parenthesis of a `var()` function aren't closed, i.e. `var(--main-color`
+    UnclosedParenthesis,
+    /// The `var()` function has no variable name, i.e. `var()`
+    EmptyVarFunction,
+    /// The variable name doesn't start with `--`, i.e. `var(main-color)`
+    InvalidVariableName(String),
+    /// The variable isn't defined on the node (or any of its parents)
+    /// and the `var()` function has no fallback value
+    UndefinedVariable(String),
+    /// The variables reference each other, i.e. `--a: var(--b); --b: var(--a);`
+    RecursionLimitReached,
+}
+
+impl_display!{ CssVarParseError, {
+    UnclosedParenthesis => "The parenthesis of a var() function aren't closed, i.e. `var(--main-color`",
+    EmptyVarFunction => "The var() function has no variable name, i.e. `var()`",
+    InvalidVariableName(name) => format!("Invalid variable name: \"{}\" - variable names have to start with \"--\"", name),
+    UndefinedVariable(name) => format!("Variable \"{}\" is not defined and has no fallback value", name),
+    RecursionLimitReached => format!("Recursion limit of {} reached while substituting variables - do the variables reference each other?", MAX_VAR_SUBSTITUTION_DEPTH),
+}}
+
+/// Prefix of custom properties, i.e. the `--` in `--main-color: red`
+pub const VARIABLE_PREFIX: &str = "--";
+pub const VAR_FUNCTION_START: &str = "var(";
+
+/// Maximum nesting depth of `var()` substitutions, necessary to
+/// detect cyclic variables such as `--a: var(--b); --b: var(--a);`
+pub const MAX_VAR_SUBSTITUTION_DEPTH: usize = 32;
+
+/// Location of a `var(--name, fallback)` function in a CSS value
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct VarFunction<'a> {
+    /// Byte offset of the `var(` in the input string
+    start: usize,
+    /// Byte offset of the first character after the closing `)`
+    end: usize,
+    /// `--name`
+    name: &'a str,
+    /// `fallback` (may contain nested `var()` functions)
+    fallback: Option<&'a str>,
+}
+
+fn is_valid_variable_name(name: &str) -> bool {
+    name.len() > VARIABLE_PREFIX.len() &&
+    name.starts_with(VARIABLE_PREFIX) &&
+    !name.contains(char::is_whitespace)
+}
+
+/// Searches for the first `var()` function in the input string
+fn find_var_function<'a>(input: &'a str) -> Result<Option<VarFunction<'a>>, CssVarParseError> {
+
+    let start = match input.find(VAR_FUNCTION_START) {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+
+    let arguments_start = start + VAR_FUNCTION_START.len();
+
+    // The fallback value can contain parenthesis, i.e. `var(--color, rgb(0, 0, 0))`
+    let mut nesting = 1_usize;
+    let mut arguments_end = None;
+
+    for (idx, c) in input[arguments_start..].char_indices() {
+        match c {
+            '(' => nesting += 1,
+            ')' => {
+                nesting -= 1;
+                if nesting == 0 {
+                    arguments_end = Some(arguments_start + idx);
+                    break;
+                }
+            },
+            _ => { },
+        }
+    }
+
+    let arguments_end = arguments_end.ok_or(CssVarParseError::UnclosedParenthesis)?;
+
+    // Variable names can't contain commas, so the first comma separates the name from the fallback
+    let mut comma_split = input[arguments_start..arguments_end].splitn(2, ",");
+    let name = comma_split.next().unwrap_or("").trim();
+    let fallback = comma_split.next().map(|fallback| fallback.trim());
+
+    if name.is_empty() {
+        return Err(CssVarParseError::EmptyVarFunction);
+    }
+
+    if !is_valid_variable_name(name) {
+        return Err(CssVarParseError::InvalidVariableName(name.to_string()));
+    }
+
+    Ok(Some(VarFunction {
+        start,
+        end: arguments_end + 1,
+        name,
+        fallback,
+    }))
+}
+
+/// Checks that all `var()` functions in the value (including the ones
+/// nested in fallback values) are well-formed
+fn check_var_functions(value: &str) -> Result<(), CssVarParseError> {
+    let mut rest = value;
+    while let Some(var_function) = find_var_function(rest)? {
+        if let Some(fallback) = var_function.fallback {
+            check_var_functions(fallback)?;
+        }
+        rest = &rest[var_function.end..];
+    }
+    Ok(())
+}
+
+/// Returns whether the CSS value contains a `var()` function - such values
+/// can only be parsed after the variables have been substituted
+pub fn contains_var_function(value: &str) -> bool {
+    value.contains(VAR_FUNCTION_START)
+}
+
+/// Parses a custom property, i.e. `--main-color: #ff0000`. The value itself
+/// isn't parsed, since its type is only known once it is used by a property.
+pub fn parse_css_variable(key: &str, value: &str) -> Result<CssVariable, CssVarParseError> {
+    let key = key.trim();
+    if !is_valid_variable_name(key) {
+        return Err(CssVarParseError::InvalidVariableName(key.to_string()));
+    }
+    check_var_functions(value)?;
+    Ok(CssVariable {
+        name: key.to_string(),
+        value: value.trim().to_string(),
+    })
+}
+
+/// Parses a property that references CSS variables, i.e. `color: var(--main-color, black)`.
+///
+/// Only the `var()` functions are checked for syntax errors here, the value itself is
+/// parsed when the variables are substituted during the cascade.
+pub fn parse_css_var_reference(key: CssPropertyType, value: &str) -> Result<CssVarReference, CssVarParseError> {
+    check_var_functions(value)?;
+    Ok(CssVarReference {
+        property_type: key,
+        value: value.trim().to_string(),
+    })
+}
+
+/// Replaces all `var()` functions in the value with the value of the variable,
+/// or with the fallback value if the variable isn't defined.
+///
+/// ```rust
+/// # extern crate azul_css_parser;
+/// # use std::collections::BTreeMap;
+/// # use azul_css_parser::substitute_css_variables;
+/// let mut variables = BTreeMap::new();
+/// variables.insert("--main-color".to_string(), "#ff0000".to_string());
+///
+/// assert_eq!(
+///     substitute_css_variables("1px solid var(--main-color)", &variables),
+///     Ok("1px solid #ff0000".to_string())
+/// );
+/// assert_eq!(
+///     substitute_css_variables("var(--other-color, black)", &variables),
+///     Ok("black".to_string())
+/// );
+/// ```
+pub fn substitute_css_variables(value: &str, variables: &BTreeMap<String, String>) -> Result<String, CssVarParseError> {
+    substitute_css_variables_inner(value, variables, 0)
+}
+
+fn substitute_css_variables_inner(value: &str, variables: &BTreeMap<String, String>, depth: usize)
+-> Result<String, CssVarParseError>
+{
+    if depth > MAX_VAR_SUBSTITUTION_DEPTH {
+        return Err(CssVarParseError::RecursionLimitReached);
+    }
+
+    let mut substituted = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(var_function) = find_var_function(rest)? {
+        substituted.push_str(&rest[..var_function.start]);
+
+        // The value of the variable can itself contain var() functions
+        let variable_value = match (variables.get(var_function.name), var_function.fallback) {
+            (Some(variable), _) => substitute_css_variables_inner(variable, variables, depth + 1)?,
+            (None, Some(fallback)) => substitute_css_variables_inner(fallback, variables, depth + 1)?,
+            (None, None) => return Err(CssVarParseError::UndefinedVariable(var_function.name.to_string())),
+        };
+
+        substituted.push_str(&variable_value);
+        rest = &rest[var_function.end..];
+    }
+
+    substituted.push_str(rest);
+
+    Ok(substituted)
+}
+
 #[test]
 fn test_detect_static_or_dynamic_property() {
     use azul_css::{CssProperty, StyleTextAlignmentHorz};
@@ -685,6 +1862,7 @@ fn test_css_parse_1() {
                 ],
             },
             declarations: vec![CssDeclaration::Static(CssProperty::Background(StyleBackground::Color(ColorU { r: 255, g: 0, b: 0, a: 255 })))],
+            location: None,
         }
     ];
 
@@ -710,9 +1888,386 @@ fn test_css_simple_selector_parse() {
             rules: vec![CssRuleBlock {
                 path: CssPath { selectors: parsed },
                 declarations: Vec::new(),
+                location: None,
+            }],
+            keyframes: Vec::new(),
+            media_rules: Vec::new(),
+            font_faces: Vec::new(),
+        }],
+    });
+}
+
+#[test]
+fn test_css_sibling_and_attribute_selector_parse() {
+    use self::CssPathSelector::*;
+    use azul_css::{NodeTypePath, CssAttributeCondition::*};
+    let css = "div[data-state=\"open\"] + p ~ .item:not([disabled]):nth-last-child(2) { }";
+    let parsed = vec![
+        Type(NodeTypePath::Div),
+        Attribute(CssAttributeSelector { name: "data-state".into(), condition: Equals("open".into()) }),
+        AdjacentSibling,
+        Type(NodeTypePath::P),
+        GeneralSibling,
+        Class("item".into()),
+        PseudoSelector(CssPathPseudoSelector::Not(vec![
+            Attribute(CssAttributeSelector { name: "disabled".into(), condition: Exists }),
+        ])),
+        PseudoSelector(CssPathPseudoSelector::NthLastChild(Number(2))),
+    ];
+    assert_eq!(new_from_str(css).unwrap(), Css {
+        stylesheets: vec![Stylesheet {
+            rules: vec![CssRuleBlock {
+                path: CssPath { selectors: parsed },
+                declarations: Vec::new(),
+                location: None,
+            }],
+            keyframes: Vec::new(),
+            media_rules: Vec::new(),
+            font_faces: Vec::new(),
+        }],
+    });
+}
+
+#[test]
+fn test_attribute_selector_parse() {
+    use azul_css::CssAttributeCondition::*;
+    let attribute = |name: &str, condition| Some(CssAttributeSelector { name: name.into(), condition });
+    assert_eq!(parse_attribute_selector("disabled"), attribute("disabled", Exists));
+    assert_eq!(parse_attribute_selector("data-state=open"), attribute("data-state", Equals("open".into())));
+    assert_eq!(parse_attribute_selector("data-state = 'open'"), attribute("data-state", Equals("open".into())));
+    assert_eq!(parse_attribute_selector("lang|=\"en\""), attribute("lang", DashMatch("en".into())));
+    assert_eq!(parse_attribute_selector("class~=\"a\""), attribute("class", ContainsWord("a".into())));
+    assert_eq!(parse_attribute_selector("href^=\"http\""), attribute("href", StartsWith("http".into())));
+    assert_eq!(parse_attribute_selector("href$=\".png\""), attribute("href", EndsWith(".png".into())));
+    assert_eq!(parse_attribute_selector("href*=\"azul\""), attribute("href", Contains("azul".into())));
+    assert_eq!(parse_attribute_selector("data-state=\"open"), None);
+    assert_eq!(parse_attribute_selector("data state"), None);
+    assert_eq!(parse_attribute_selector("=open"), None);
+    assert_eq!(parse_attribute_selector(""), None);
+}
+
+#[test]
+fn test_css_animations_parse() {
+
+    use std::time::Duration;
+    use azul_css::{
+        ColorU, StyleBackground, NodeTypePath, CssProperty, CssAnimation, CssTransition,
+        CssTransitionProperty, CssTimingFunction, CssAnimationIterationCount,
+        CssAnimationDirection, CssAnimationFillMode,
+    };
+
+    let parsed_css = new_from_str("
+        @keyframes pulse {
+            from, to { background: red; }
+            50% { background: blue; }
+        }
+        div {
+            transition: background 200ms ease-in;
+            animation: pulse 1s infinite;
+        }
+        @unknown-rule something;
+    ").unwrap();
+
+    let red = CssProperty::Background(StyleBackground::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+    let blue = CssProperty::Background(StyleBackground::Color(ColorU { r: 0, g: 0, b: 255, a: 255 }));
+
+    assert_eq!(parsed_css, Css {
+        stylesheets: vec![Stylesheet {
+            rules: vec![CssRuleBlock {
+                path: CssPath { selectors: vec![CssPathSelector::Type(NodeTypePath::Div)] },
+                declarations: vec![
+                    CssDeclaration::Transition(vec![CssTransition {
+                        property: CssTransitionProperty::Property(CssPropertyType::Background),
+                        duration: Duration::from_millis(200),
+                        timing_function: CssTimingFunction::EaseIn,
+                        delay: Duration::from_millis(0),
+                    }]),
+                    CssDeclaration::Animation(vec![CssAnimation {
+                        name: "pulse".into(),
+                        duration: Duration::from_secs(1),
+                        timing_function: CssTimingFunction::Ease,
+                        delay: Duration::from_millis(0),
+                        iteration_count: CssAnimationIterationCount::Infinite,
+                        direction: CssAnimationDirection::Normal,
+                        fill_mode: CssAnimationFillMode::None,
+                    }]),
+                ],
+                location: None,
+            }],
+            keyframes: vec![CssKeyframes {
+                name: "pulse".into(),
+                keyframes: vec![
+                    CssKeyframe { offset: PercentageValue::new(0.0), properties: vec![red.clone()] },
+                    CssKeyframe { offset: PercentageValue::new(50.0), properties: vec![blue] },
+                    CssKeyframe { offset: PercentageValue::new(100.0), properties: vec![red] },
+                ],
             }],
+            media_rules: Vec::new(),
+            font_faces: Vec::new(),
         }],
     });
+
+    assert!(new_from_str("@keyframes { from { background: red; } }").is_err());
+    assert!(new_from_str("@keyframes pulse { 150% { background: red; } }").is_err());
+    assert!(new_from_str("@keyframes pulse { from { background: red; }").is_err());
+    assert!(new_from_str("div { transition: background; }").is_err());
+}
+
+#[test]
+fn test_css_media_queries_parse() {
+
+    use azul_css::{ColorU, StyleBackground, NodeTypePath, CssProperty};
+    use azul_css::CssMediaCondition::*;
+
+    let parsed_css = new_from_str("
+        div { background: red; }
+        @media screen and (max-width: 600px), (min-resolution: 2dppx) {
+            div { background: blue; }
+        }
+        @media (prefers-color-scheme: dark) and (min-height: 300px) { }
+    ").unwrap();
+
+    let red = CssProperty::Background(StyleBackground::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+    let blue = CssProperty::Background(StyleBackground::Color(ColorU { r: 0, g: 0, b: 255, a: 255 }));
+    let div_rule = |property| CssRuleBlock {
+        path: CssPath { selectors: vec![CssPathSelector::Type(NodeTypePath::Div)] },
+        declarations: vec![CssDeclaration::Static(property)],
+        location: None,
+    };
+
+    assert_eq!(parsed_css, Css {
+        stylesheets: vec![Stylesheet {
+            rules: vec![div_rule(red)],
+            keyframes: Vec::new(),
+            media_rules: vec![
+                CssMediaRule {
+                    queries: vec![
+                        CssMediaQuery { conditions: vec![MaxWidth(600.0)] },
+                        CssMediaQuery { conditions: vec![MinResolution(2.0)] },
+                    ],
+                    rules: vec![div_rule(blue)],
+                },
+                CssMediaRule {
+                    queries: vec![
+                        CssMediaQuery { conditions: vec![PrefersColorScheme(ColorScheme::Dark), MinHeight(300.0)] },
+                    ],
+                    rules: Vec::new(),
+                },
+            ],
+            font_faces: Vec::new(),
+        }],
+    });
+
+    assert_eq!(parse_media_resolution("192dpi"), Some(2.0));
+    assert_eq!(parse_media_resolution("2x"), Some(2.0));
+    assert_eq!(parse_media_resolution("2px"), None);
+
+    assert_eq!(
+        parse_media_queries("print and (max-width: 600px)"),
+        Err(CssMediaQueryParseError::UnsupportedMediaType("print")),
+    );
+    assert_eq!(
+        parse_media_queries("(max-weight: 600px)"),
+        Err(CssMediaQueryParseError::UnknownFeature("max-weight", "600px")),
+    );
+    assert_eq!(
+        parse_media_queries("(prefers-color-scheme: blue)"),
+        Err(CssMediaQueryParseError::InvalidValue("prefers-color-scheme", "blue")),
+    );
+    assert!(new_from_str("@media (max-width: 600px) { div { background: red; }").is_err());
+    assert!(new_from_str("@media (max-width: 600px) { div { alsdfkj: red; } }").is_err());
+}
+
+#[test]
+fn test_css_font_face_parse() {
+
+    let parsed_css = new_from_str(r#"
+        @font-face {
+            font-family: "Roboto";
+            src: url("fonts/Roboto-BoldItalic.woff") format("woff"), local('Roboto Bold Italic');
+            font-weight: bold;
+            font-style: italic;
+            unicode-range: U+0000-00FF;
+        }
+        @font-face { font-family: Roboto; src: url(fonts/Roboto.ttf); }
+    "#).unwrap();
+
+    assert_eq!(parsed_css.stylesheets[0].font_faces, vec![
+        CssFontFace {
+            family: "Roboto".into(),
+            sources: vec![
+                CssFontFaceSource::Url(PathBuf::from("fonts/Roboto-BoldItalic.woff")),
+                CssFontFaceSource::Local("Roboto Bold Italic".into()),
+            ],
+            weight: StyleFontWeight::BOLD,
+            style: StyleFontStyle::Italic,
+        },
+        CssFontFace {
+            family: "Roboto".into(),
+            sources: vec![CssFontFaceSource::Url(PathBuf::from("fonts/Roboto.ttf"))],
+            weight: StyleFontWeight::NORMAL,
+            style: StyleFontStyle::Normal,
+        },
+    ]);
+
+    assert_eq!(parse_css_font_face("src: url(a.ttf)"), Err(CssFontFaceParseError::MissingFamily));
+    assert_eq!(parse_css_font_face("font-family: A, B; src: url(a.ttf)"), Err(CssFontFaceParseError::MissingFamily));
+    assert_eq!(parse_css_font_face("font-family: A"), Err(CssFontFaceParseError::MissingSource));
+    assert_eq!(parse_css_font_face("font-family: A; src: a.ttf"), Err(CssFontFaceParseError::InvalidSource("a.ttf")));
+    assert_eq!(
+        parse_css_font_face("font-family: A; src: url(a.ttf); font-weight: bolder"),
+        Err(CssFontFaceParseError::InvalidValue("font-weight", "bolder")),
+    );
+    assert!(new_from_str("div { font-weight: 1200; }").is_err());
+}
+
+#[test]
+fn test_css_import_parse() {
+
+    assert_eq!(parse_import_path("\"colors.css\""), Some("colors.css"));
+    assert_eq!(parse_import_path("'colors.css'"), Some("colors.css"));
+    assert_eq!(parse_import_path("url(\"theme/colors.css\")"), Some("theme/colors.css"));
+    assert_eq!(parse_import_path("url(colors.css)"), Some("colors.css"));
+    assert_eq!(parse_import_path("colors.css"), None);
+    assert_eq!(parse_import_path("\"colors.css"), None);
+    assert_eq!(parse_import_path("\"colors.css\" screen"), None);
+
+    let css = "div { }\n@import \"colors.css\";";
+    assert_eq!(new_from_str(css).map_err(|e| e.error), Err(CssParseErrorInner::UnresolvedImport("colors.css")));
+
+    // Every rule remembers the line of its block
+    let parsed_css = new_from_str("div { }\n\np,\n.a { }\n@media (max-width: 600px) {\n  .b { }\n}").unwrap();
+    let lines = |rules: &[CssRuleBlock]| rules.iter().map(|rule| rule.location.as_ref().unwrap().line).collect::<Vec<_>>();
+    assert_eq!(lines(&parsed_css.stylesheets[0].rules), vec![1, 4, 4]);
+    assert_eq!(lines(&parsed_css.stylesheets[0].media_rules[0].rules), vec![6]);
+}
+
+/// Temporary directory for the tests that load CSS files. The name is unique, so that
+/// tests running at the same time don't interfere, and the directory is removed again
+/// when the test ends - even if an assertion fails.
+#[cfg(test)]
+struct TestDirectory(PathBuf);
+
+#[cfg(test)]
+impl TestDirectory {
+    fn new(name: &str) -> Self {
+        use std::{env, process, sync::atomic::{AtomicUsize, Ordering}};
+        static NEXT_DIRECTORY_ID: AtomicUsize = AtomicUsize::new(0);
+        let id = NEXT_DIRECTORY_ID.fetch_add(1, Ordering::SeqCst);
+        let path = env::temp_dir().join(format!("{}_{}_{}", name, process::id(), id));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TestDirectory(path)
+    }
+}
+
+#[cfg(test)]
+impl ::std::ops::Deref for TestDirectory {
+    type Target = Path;
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn test_css_import_from_file() {
+
+    let directory = TestDirectory::new("azul_css_parser_test_import");
+    fs::create_dir_all(directory.join("theme")).unwrap();
+    fs::write(directory.join("main.css"), "@import \"theme/colors.css\";\n.main { }").unwrap();
+    fs::write(directory.join("theme/colors.css"), "@import url(base.css);\n\n.colors { }\n@font-face { font-family: A; src: url(fonts/a.ttf); }").unwrap();
+    fs::write(directory.join("theme/base.css"), ".base { }").unwrap();
+
+    let (css, loaded_files) = new_from_file_with_imports(directory.join("main.css")).unwrap();
+
+    // Imported rules come first, every rule remembers the file it was defined in
+    let rules = css.stylesheets[0].rules.iter().map(|rule| {
+        let location = rule.location.as_ref().unwrap();
+        (rule.path.to_string(), location.file.as_ref().unwrap().file_name().unwrap().to_string_lossy().to_string(), location.line)
+    }).collect::<Vec<_>>();
+
+    assert_eq!(rules, vec![
+        (".base".to_string(), "base.css".to_string(), 1),
+        (".colors".to_string(), "colors.css".to_string(), 3),
+        (".main".to_string(), "main.css".to_string(), 2),
+    ]);
+    assert_eq!(loaded_files.len(), 3);
+
+    // Font files are relative to the file that contains the @font-face rule
+    assert_eq!(css.stylesheets[0].font_faces[0].sources, vec![CssFontFaceSource::Url(directory.join("theme/fonts/a.ttf"))]);
+
+    // Errors name the file that contains the error
+    fs::write(directory.join("theme/base.css"), ".base { alsdfkj: 5px; }").unwrap();
+    match new_from_file(directory.join("main.css")) {
+        Err(CssFileError::Parse(file, _, _)) => assert!(file.ends_with("base.css")),
+        other => panic!("expected parse error in base.css, got {:?}", other),
+    }
+
+    fs::write(directory.join("theme/base.css"), "@import \"../main.css\";").unwrap();
+    match new_from_file(directory.join("main.css")) {
+        Err(CssFileError::ImportCycle(files)) => assert_eq!(files.len(), 4),
+        other => panic!("expected import cycle, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_css_lenient_parse() {
+
+    let css = "
+        div { color: red; alsdfkj: 5px; width: 10px; }
+        p:unknown-pseudo, .also-skipped { color: blue; }
+        .ok { width: 5px }
+        @media (max-wdth: 100px) { .x { } }
+    ";
+
+    assert!(new_from_str(css).is_err());
+
+    let (parsed_css, errors) = new_from_str_lenient(css);
+
+    // The invalid declaration and the rule with the invalid selector are skipped
+    let rules = &parsed_css.stylesheets[0].rules;
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].path.to_string(), "div");
+    assert_eq!(rules[0].declarations.len(), 2);
+    assert_eq!(rules[1].path.to_string(), ".ok");
+    assert!(parsed_css.stylesheets[0].media_rules.is_empty());
+
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0].error, CssParseErrorInner::UnknownPropertyKey("alsdfkj", "5px"));
+    assert_eq!(errors[0].location.line, 2);
+    assert_eq!(errors[1].error, CssParseErrorInner::PseudoSelectorParseError(
+        CssPseudoSelectorParseError::UnknownSelector("unknown-pseudo", None)
+    ));
+    assert_eq!(errors[1].location.line, 3);
+    assert_eq!(errors[2].error, CssParseErrorInner::MediaQueryParseError(
+        CssMediaQueryParseError::UnknownFeature("max-wdth", "100px")
+    ));
+    assert_eq!(errors[2].location.line, 5);
+
+    // Imports that can't be loaded are skipped as well
+    let directory = TestDirectory::new("azul_css_parser_test_lenient");
+    fs::write(directory.join("main.css"), "@import \"missing.css\";\n.main { alsdfkj: 5px; }").unwrap();
+
+    let loaded = new_from_file_lenient(directory.join("main.css")).unwrap();
+    assert_eq!(loaded.css.stylesheets[0].rules.len(), 1);
+    assert_eq!(loaded.errors.len(), 2);
+    match &loaded.errors[0] {
+        CssFileError::Parse(file, location, _) => { assert!(file.ends_with("main.css")); assert_eq!(location.line, 2); },
+        other => panic!("expected parse error in main.css, got {:?}", other),
+    }
+    match &loaded.errors[1] {
+        CssFileError::Io(file, _) => assert!(file.ends_with("missing.css")),
+        other => panic!("expected io error for missing.css, got {:?}", other),
+    }
+
+    assert!(new_from_file_lenient(directory.join("does-not-exist.css")).is_err());
 }
 
 #[cfg(test)]
@@ -743,6 +2298,7 @@ mod stylesheet_parse {
                     declarations: vec![
                         CssDeclaration::Static(red.clone())
                     ],
+                    location: None,
                 },
             ];
             test_css(css_1, expected_rules);
@@ -754,11 +2310,13 @@ mod stylesheet_parse {
             let expected_rules = vec![
                 CssRuleBlock {
                     path: CssPath { selectors: vec![CssPathSelector::Id("my_id".into())] },
-                    declarations: vec![CssDeclaration::Static(red.clone())]
+                    declarations: vec![CssDeclaration::Static(red.clone())],
+                    location: None,
                 },
                 CssRuleBlock {
                     path: CssPath { selectors: vec![CssPathSelector::Class("my_class".into())] },
-                    declarations: vec![CssDeclaration::Static(blue.clone())]
+                    declarations: vec![CssDeclaration::Static(blue.clone())],
+                    location: None,
                 },
             ];
             test_css(css_2, expected_rules);
@@ -770,15 +2328,18 @@ mod stylesheet_parse {
             let expected_rules = vec![
                 CssRuleBlock {
                     path: CssPath { selectors: vec![CssPathSelector::Global] },
-                    declarations: vec![CssDeclaration::Static(black.clone())]
+                    declarations: vec![CssDeclaration::Static(black.clone())],
+                    location: None,
                 },
                 CssRuleBlock {
                     path: CssPath { selectors: vec![CssPathSelector::Class("my_class".into()), CssPathSelector::Id("my_id".into())] },
-                    declarations: vec![CssDeclaration::Static(red.clone())]
+                    declarations: vec![CssDeclaration::Static(red.clone())],
+                    location: None,
                 },
                 CssRuleBlock {
                     path: CssPath { selectors: vec![CssPathSelector::Class("my_class".into())] },
-                    declarations: vec![CssDeclaration::Static(blue.clone())]
+                    declarations: vec![CssDeclaration::Static(blue.clone())],
+                    location: None,
                 },
             ];
             test_css(css_3, expected_rules);
@@ -802,11 +2363,11 @@ fn test_multiple_rules() {
 
     let expected_rules = vec![
         // Rules are sorted by order of appearance in source string
-        CssRuleBlock { path: CssPath { selectors: vec![Global] }, declarations: Vec::new() },
-        CssRuleBlock { path: CssPath { selectors: vec![Global, Type(NodeTypePath::Div), Class("my_class".into()), Id("my_id".into())] }, declarations: Vec::new() },
-        CssRuleBlock { path: CssPath { selectors: vec![Global, Type(NodeTypePath::Div), Id("my_id".into())] }, declarations: Vec::new() },
-        CssRuleBlock { path: CssPath { selectors: vec![Global, Id("my_id".into())] }, declarations: Vec::new() },
-        CssRuleBlock { path: CssPath { selectors: vec![Type(NodeTypePath::Div), Class("my_class".into()), Class("specific".into()), Id("my_id".into())] }, declarations: Vec::new() },
+        CssRuleBlock { path: CssPath { selectors: vec![Global] }, declarations: Vec::new(), location: None },
+        CssRuleBlock { path: CssPath { selectors: vec![Global, Type(NodeTypePath::Div), Class("my_class".into()), Id("my_id".into())] }, declarations: Vec::new(), location: None },
+        CssRuleBlock { path: CssPath { selectors: vec![Global, Type(NodeTypePath::Div), Id("my_id".into())] }, declarations: Vec::new(), location: None },
+        CssRuleBlock { path: CssPath { selectors: vec![Global, Id("my_id".into())] }, declarations: Vec::new(), location: None },
+        CssRuleBlock { path: CssPath { selectors: vec![Type(NodeTypePath::Div), Class("my_class".into()), Class("specific".into()), Id("my_id".into())] }, declarations: Vec::new(), location: None },
     ];
 
     assert_eq!(parsed_css, Css { stylesheets: vec![expected_rules.into()] });
@@ -838,6 +2399,7 @@ fn test_case_issue_93() {
             declarations: vec![
                 CssDeclaration::Static(CssProperty::TextColor(StyleTextColor(color))),
             ],
+            location: None,
         }
     }
 
@@ -848,4 +2410,199 @@ fn test_case_issue_93() {
     ];
 
     assert_eq!(parsed_css, Css { stylesheets: vec![expected_rules.into()] });
-}
\ No newline at end of file
+}
+#[test]
+fn test_css_variables_parse() {
+
+    use azul_css::*;
+    use self::CssPathSelector::*;
+
+    let parsed_css = new_from_str("
+        * {
+            --main-color: #ff0000;
+        }
+        div {
+            color: var(--main-color, black);
+        }
+    ").unwrap();
+
+    let expected_rules = vec![
+        CssRuleBlock {
+            path: CssPath { selectors: vec![Global] },
+            declarations: vec![CssDeclaration::Variable(CssVariable {
+                name: "--main-color".into(),
+                value: "#ff0000".into(),
+            })],
+            location: None,
+        },
+        CssRuleBlock {
+            path: CssPath { selectors: vec![Type(NodeTypePath::Div)] },
+            declarations: vec![CssDeclaration::VarReference(CssVarReference {
+                property_type: CssPropertyType::TextColor,
+                value: "var(--main-color, black)".into(),
+            })],
+            location: None,
+        },
+    ];
+
+    assert_eq!(parsed_css, Css { stylesheets: vec![expected_rules.into()] });
+
+    assert!(new_from_str("div { color: var(main-color); }").is_err());
+    assert!(new_from_str("div { color: var(--main-color; }").is_err());
+}
+
+#[test]
+fn test_substitute_css_variables() {
+
+    let mut variables = BTreeMap::new();
+    variables.insert("--main-color".to_string(), "#ff0000".to_string());
+    variables.insert("--border".to_string(), "1px solid var(--main-color)".to_string());
+    variables.insert("--cycle-a".to_string(), "var(--cycle-b)".to_string());
+    variables.insert("--cycle-b".to_string(), "var(--cycle-a)".to_string());
+
+    assert_eq!(substitute_css_variables("var(--main-color)", &variables), Ok("#ff0000".to_string()));
+    assert_eq!(substitute_css_variables("var(--border)", &variables), Ok("1px solid #ff0000".to_string()));
+    assert_eq!(substitute_css_variables("var(--unknown, rgb(0, 0, 0))", &variables), Ok("rgb(0, 0, 0)".to_string()));
+    assert_eq!(substitute_css_variables("var(--unknown, var(--main-color))", &variables), Ok("#ff0000".to_string()));
+    assert_eq!(substitute_css_variables("var(--unknown)", &variables), Err(CssVarParseError::UndefinedVariable("--unknown".to_string())));
+    assert_eq!(substitute_css_variables("var(--cycle-a)", &variables), Err(CssVarParseError::RecursionLimitReached));
+    assert_eq!(substitute_css_variables("var()", &variables), Err(CssVarParseError::EmptyVarFunction));
+}
+
+#[test]
+fn test_css_print_round_trip() {
+
+    use azul_css::*;
+
+    let css = r#"
+        * {
+            font-family: "Times New Roman", sans-serif;
+            font-size: 12pt;
+            font-weight: 300;
+            font-style: oblique;
+            color: #333333;
+            line-height: 1.2;
+            tab-width: 400%;
+            letter-spacing: 0.5px;
+            word-spacing: 0.333px;
+            text-align: left;
+            direction: rtl;
+            cursor: nesw-resize;
+        }
+
+        div#main.content > p:nth-child(2n + 3) {
+            width: 100%;
+            min-height: calc(100% - 20px);
+            max-width: 50em;
+            padding: 5px 10px;
+            margin-top: 3em;
+            margin-left: 0px;
+            border: 1px solid #ff0000;
+            border-left: 2px dashed #00ff0080;
+            border-radius: 5px 0px 5px 0px;
+            box-shadow: 0px 1px 5px 2px #000000 inset;
+            background: linear-gradient(to bottom right, #ff0000, #0000ff 80%);
+            background-repeat: repeat-x;
+            background-size: cover;
+            overflow-x: hidden;
+            flex-direction: row-reverse;
+            flex-wrap: nowrap;
+            flex-grow: 1.5;
+            justify-content: space-between;
+            align-items: center;
+            align-content: flex-end;
+            position: absolute;
+            top: 0px;
+            opacity: 0.75;
+            transform: translate(10px, 50%) rotate(45deg) scale(1.5, 1.5) skew(10deg, 0deg);
+            transform-origin: 0px 100%;
+            z-index: -1;
+        }
+
+        .button[data-state="open"]:not(.disabled) ~ .label + p:hover {
+            background: [[ button_bg | image("button.png") ]];
+            width: [[ button_width | auto ]];
+            padding-left: [[ button_padding | 5px ]];
+            --main-color: #ff0000;
+            color: var(--main-color, black);
+            transition: background 200ms ease-in 50ms, all 1.5s cubic-bezier(0.5, 0, 1, 2);
+            animation: fade-in 1s linear infinite alternate both;
+        }
+
+        .grid {
+            display: grid;
+            grid-template-columns: 100px 1fr 2.5fr auto;
+            grid-template-rows: none;
+            grid-gap: 10px 5%;
+        }
+
+        .grid > p {
+            grid-column: 1 / span 2;
+            grid-row: -1;
+        }
+
+        .toolbar {
+            flex-wrap: wrap;
+            row-gap: 5px;
+            column-gap: 1em;
+        }
+
+        .toolbar > button {
+            flex-basis: 20%;
+            flex-shrink: 0;
+            order: -1;
+            align-self: flex-start;
+            width: fit-content;
+        }
+
+        .toolbar > .hidden {
+            display: none;
+            visibility: hidden;
+            box-sizing: content-box;
+        }
+
+        img:last {
+            background-image: "logo.png";
+            background: none;
+            overflow: scroll;
+        }
+
+        @font-face {
+            font-family: "Open Sans";
+            src: url("fonts/OpenSans-Bold.ttf"), local("Open Sans Bold");
+            font-weight: bold;
+        }
+
+        @keyframes fade-in {
+            from { opacity: 0; background: #ff000000; }
+            50% { opacity: 0.5; }
+            to { opacity: 1; background: repeating-radial-gradient(circle, #ff0000 0%, #0000ff 100%); }
+        }
+
+        @media (min-width: 600px) and (max-width: 900.5px), (prefers-color-scheme: dark) {
+            .sidebar {
+                background: repeating-linear-gradient(45deg, #ff0000 0%, #00ff00 50%, #0000ff 100%);
+            }
+        }
+
+        @media (min-resolution: 2dppx) {
+            .logo { background: image("logo-2x.png"); }
+        }
+    "#;
+
+    let parsed_css = new_from_str(css).unwrap();
+    let printed_css = parsed_css.to_string();
+
+    assert_eq!(new_from_str(&printed_css).unwrap(), parsed_css);
+    // Printing the re-parsed CSS yields the exact same text again
+    assert_eq!(new_from_str(&printed_css).unwrap().to_string(), printed_css);
+
+    // Properties that don't set all of their sides are written with one key per side
+    let padding = CssProperty::Padding(LayoutPadding {
+        top: Some(PixelValue::px(5.0)),
+        left: Some(PixelValue::px(10.0)),
+        .. Default::default()
+    });
+    assert_eq!(padding.to_string(), "padding-top: 5px; padding-left: 10px");
+    assert_eq!(CssProperty::Padding(LayoutPadding::all(PixelValue::px(5.0))).to_string(), "padding: 5px");
+}
@@ -1,12 +1,14 @@
 //! Contains utilities to convert strings (CSS strings) to servo types
 
 use std::num::{ParseIntError, ParseFloatError};
+use std::time::Duration;
+use std::collections::BTreeMap;
 use azul_css::{
     CssPropertyType,
-    StyleTextAlignmentHorz, Overflow,
+    StyleTextAlignmentHorz, StyleTextDirection, Overflow,
     LayoutAlignItems, LayoutAlignContent, LayoutJustifyContent, Shape,
     LayoutWrap, LayoutDirection, LayoutPosition, CssProperty, LayoutOverflow,
-    StyleFontFamily, StyleFontSize, StyleLineHeight, LayoutFlexShrink, LayoutFlexGrow,
+    StyleFontFamily, StyleFontSize, StyleFontWeight, StyleFontStyle, StyleLineHeight, LayoutFlexShrink, LayoutFlexGrow,
     LayoutLeft, LayoutRight, LayoutTop, LayoutBottom, StyleCursor, StyleWordSpacing, StyleTabWidth,
     LayoutMaxHeight, LayoutMinHeight, LayoutHeight, LayoutMaxWidth, LayoutMinWidth, LayoutWidth,
     StyleBorderRadius, PixelValue, PercentageValue, FloatValue,
@@ -17,6 +19,13 @@ use azul_css::{
     BackgroundType,
 
     SizeMetric, BoxShadowClipMode, ExtendMode, FontId,
+
+    CssTimingFunction, CssTransition, CssTransitionProperty, CssAnimation,
+    CssAnimationIterationCount, CssAnimationDirection, CssAnimationFillMode,
+    StyleOpacity, StyleTransform, StyleTransformFunction, StyleTransformOrigin, StyleZIndex,
+    LayoutDisplay, LayoutGridTemplate, GridTrackSize, LayoutGridGap, LayoutGridPlacement, GridLine,
+    LayoutFlexBasis, LayoutOrder, LayoutAlignSelf, LayoutRowGap, LayoutColumnGap,
+    LayoutBoxSizing, StyleVisibility, LayoutIntrinsicWidth, CalcPixelValue,
 };
 
 /// A parser that can accept a list of items and mappings
@@ -118,6 +127,8 @@ pub fn parse_key_value_pair<'a>(key: CssPropertyType, value: &'a str) -> Result<
         BorderRadius     => Ok(parse_style_border_radius(value)?.into()),
         FontSize         => Ok(parse_style_font_size(value)?.into()),
         FontFamily       => Ok(parse_style_font_family(value)?.into()),
+        FontWeight       => Ok(parse_style_font_weight(value)?.into()),
+        FontStyle        => Ok(parse_style_font_style(value)?.into()),
         LetterSpacing    => Ok(parse_style_letter_spacing(value)?.into()),
         WordSpacing      => Ok(parse_style_word_spacing(value)?.into()),
         TabWidth         => Ok(parse_style_tab_width(value)?.into()),
@@ -130,12 +141,16 @@ pub fn parse_key_value_pair<'a>(key: CssPropertyType, value: &'a str) -> Result<
         BorderLeft       => Ok(border_parser::parse_left(value)?.into()),
         BorderRight      => Ok(border_parser::parse_right(value)?.into()),
 
-        Width            => Ok(parse_layout_width(value)?.into()),
-        Height           => Ok(parse_layout_height(value)?.into()),
-        MinWidth         => Ok(parse_layout_min_width(value)?.into()),
-        MinHeight        => Ok(parse_layout_min_height(value)?.into()),
-        MaxWidth         => Ok(parse_layout_max_width(value)?.into()),
-        MaxHeight        => Ok(parse_layout_max_height(value)?.into()),
+        Width            => match parse_layout_intrinsic_width(value) {
+            Ok(intrinsic_width) => Ok(intrinsic_width.into()),
+            Err(_) => parse_layout_size(key, value, |w| LayoutWidth(w).into()),
+        },
+        Height           => parse_layout_size(key, value, |h| LayoutHeight(h).into()),
+        MinWidth         => parse_layout_size(key, value, |w| LayoutMinWidth(w).into()),
+        MinHeight        => parse_layout_size(key, value, |h| LayoutMinHeight(h).into()),
+        MaxWidth         => parse_layout_size(key, value, |w| LayoutMaxWidth(w).into()),
+        MaxHeight        => parse_layout_size(key, value, |h| LayoutMaxHeight(h).into()),
+        BoxSizing        => Ok(parse_layout_box_sizing(value)?.into()),
 
         Position         => Ok(parse_layout_position(value)?.into()),
         Top              => Ok(parse_layout_top(value)?.into()),
@@ -143,6 +158,7 @@ pub fn parse_key_value_pair<'a>(key: CssPropertyType, value: &'a str) -> Result<
         Left             => Ok(parse_layout_left(value)?.into()),
         Bottom           => Ok(parse_layout_bottom(value)?.into()),
         TextAlign        => Ok(parse_layout_text_align(value)?.into()),
+        TextDirection    => Ok(parse_style_text_direction(value)?.into()),
 
         BoxShadow        => Ok(StyleBoxShadow::all(parse_css_box_shadow(value)?).into()),
         BoxShadowTop     => Ok(box_shadow_parser::parse_top(value)?.into()),
@@ -162,14 +178,26 @@ pub fn parse_key_value_pair<'a>(key: CssPropertyType, value: &'a str) -> Result<
         MarginLeft       => Ok(layout_margin_parser::parse_left(value)?.into()),
         MarginRight      => Ok(layout_margin_parser::parse_right(value)?.into()),
 
+        Display          => Ok(parse_layout_display(value)?.into()),
         FlexWrap         => Ok(parse_layout_wrap(value)?.into()),
         FlexDirection    => Ok(parse_layout_direction(value)?.into()),
         FlexGrow         => Ok(parse_layout_flex_grow(value)?.into()),
         FlexShrink       => Ok(parse_layout_flex_shrink(value)?.into()),
+        FlexBasis        => Ok(parse_layout_flex_basis(value)?.into()),
+        Order            => Ok(parse_layout_order(value)?.into()),
 
         JustifyContent   => Ok(parse_layout_justify_content(value)?.into()),
         AlignItems       => Ok(parse_layout_align_items(value)?.into()),
+        AlignSelf        => Ok(parse_layout_align_self(value)?.into()),
         AlignContent     => Ok(parse_layout_align_content(value)?.into()),
+        RowGap           => Ok(parse_layout_row_gap(value)?.into()),
+        ColumnGap        => Ok(parse_layout_column_gap(value)?.into()),
+
+        GridTemplateColumns => Ok(CssProperty::GridTemplateColumns(parse_layout_grid_template(value)?)),
+        GridTemplateRows => Ok(CssProperty::GridTemplateRows(parse_layout_grid_template(value)?)),
+        GridGap          => Ok(parse_layout_grid_gap(value)?.into()),
+        GridColumn       => Ok(CssProperty::GridColumn(parse_layout_grid_placement(value)?)),
+        GridRow          => Ok(CssProperty::GridRow(parse_layout_grid_placement(value)?)),
 
         Overflow         => {
             let overflow_both_directions = parse_layout_text_overflow(value)?;
@@ -192,6 +220,12 @@ pub fn parse_key_value_pair<'a>(key: CssPropertyType, value: &'a str) -> Result<
                 vertical: Some(overflow_y),
             }.into())
         },
+
+        Opacity          => Ok(parse_style_opacity(value)?.into()),
+        Transform        => Ok(parse_style_transform(value)?.into()),
+        TransformOrigin  => Ok(parse_style_transform_origin(value)?.into()),
+        ZIndex           => Ok(parse_style_z_index(value)?.into()),
+        Visibility       => Ok(parse_style_visibility(value)?.into()),
     }
 }
 
@@ -214,6 +248,9 @@ pub enum CssParsingError<'a> {
     MarginParseError(LayoutMarginParseError<'a>),
     FlexShrinkParseError(FlexShrinkParseError<'a>),
     FlexGrowParseError(FlexGrowParseError<'a>),
+    OpacityParseError(OpacityParseError<'a>),
+    CssTransformParseError(CssTransformParseError<'a>),
+    CssGridParseError(CssGridParseError<'a>),
 }
 
 impl_debug_as_display!(CssParsingError<'a>);
@@ -232,6 +269,9 @@ impl_display!{ CssParsingError<'a>, {
     MarginParseError(e) => format!("{}", e),
     FlexShrinkParseError(e) => format!("{}", e),
     FlexGrowParseError(e) => format!("{}", e),
+    OpacityParseError(e) => format!("{}", e),
+    CssTransformParseError(e) => format!("Invalid transform: {}", e),
+    CssGridParseError(e) => format!("Invalid grid property: {}", e),
 }}
 
 impl_from!(CssBorderParseError<'a>, CssParsingError::CssBorderParseError);
@@ -247,6 +287,9 @@ impl_from!(LayoutPaddingParseError<'a>, CssParsingError::PaddingParseError);
 impl_from!(LayoutMarginParseError<'a>, CssParsingError::MarginParseError);
 impl_from!(FlexShrinkParseError<'a>, CssParsingError::FlexShrinkParseError);
 impl_from!(FlexGrowParseError<'a>, CssParsingError::FlexGrowParseError);
+impl_from!(OpacityParseError<'a>, CssParsingError::OpacityParseError);
+impl_from!(CssTransformParseError<'a>, CssParsingError::CssTransformParseError);
+impl_from!(CssGridParseError<'a>, CssParsingError::CssGridParseError);
 
 impl<'a> From<PercentageParseError> for CssParsingError<'a> {
     fn from(e: PercentageParseError) -> Self {
@@ -462,20 +505,39 @@ pub enum PixelParseError<'a> {
     NoValueGiven(&'a str),
     UnsupportedMetric(f32, String, &'a str),
     ValueParseErr(ParseFloatError, String),
+    /// Syntax error in a `calc()` expression or the expression
+    /// mixes lengths and numbers, i.e. `calc(100% + 5)`
+    InvalidCalcExpression(&'a str),
+    /// `calc()` expression that mixes metrics (i.e. `calc(100% - 20px)`) for a
+    /// property that isn't a `width`, `height` or one of their `min-` / `max-` variants
+    UnsupportedCalcExpression(&'a str),
 }
 
 impl_debug_as_display!(PixelParseError<'a>);
 
 impl_display!{ PixelParseError<'a>, {
-    EmptyString => format!("Missing [px / pt / em] value"),
+    EmptyString => format!("Missing [px / pt / em / rem / % / vw / vh] value"),
     NoValueGiven(input) => format!("Expected floating-point pixel value, got: \"{}\"", input),
     UnsupportedMetric(_, metric, input) => format!("Could not parse \"{}\": Metric \"{}\" is not (yet) implemented.", input, metric),
     ValueParseErr(err, number_str) => format!("Could not parse \"{}\" as floating-point value: \"{}\"", number_str, err),
+    InvalidCalcExpression(input) => format!("Invalid calc() expression: \"{}\"", input),
+    UnsupportedCalcExpression(input) => format!("calc() expressions that mix metrics are only supported for (min-/max-) width and height: \"{}\"", input),
 }}
 
-/// parse a single value such as "15px"
+/// parse a single value such as "15px", "50%" or "calc(2 * 15px)"
+///
+/// `calc()` expressions that mix metrics (i.e. `calc(100% - 15px)`) can't be
+/// represented as a `PixelValue`, see `parse_calc_pixel_value`
 pub fn parse_pixel_value<'a>(input: &'a str)
 -> Result<PixelValue, PixelParseError<'a>>
+{
+    let input = input.trim();
+    parse_calc_pixel_value(input)?.as_pixel_value().ok_or(PixelParseError::UnsupportedCalcExpression(input))
+}
+
+/// parse a single value such as "15px" or "calc(100% - 15px)"
+pub fn parse_calc_pixel_value<'a>(input: &'a str)
+-> Result<CalcPixelValue, PixelParseError<'a>>
 {
     let input = input.trim();
 
@@ -483,6 +545,16 @@ pub fn parse_pixel_value<'a>(input: &'a str)
         return Err(PixelParseError::EmptyString);
     }
 
+    if input.starts_with(CALC_START) {
+        parse_calc_expression(input)
+    } else {
+        parse_pixel_value_no_calc(input).map(CalcPixelValue::from)
+    }
+}
+
+fn parse_pixel_value_no_calc<'a>(input: &'a str)
+-> Result<PixelValue, PixelParseError<'a>>
+{
     let is_part_of_number = |ch: &char| ch.is_numeric() || *ch == '.' || *ch == '-';
 
     // You can't sub-string pixel values, have to call collect() here!
@@ -498,13 +570,172 @@ pub fn parse_pixel_value<'a>(input: &'a str)
     let unit = match unit_str.as_str() {
         "px" => SizeMetric::Px,
         "em" => SizeMetric::Em,
+        "rem" => SizeMetric::Rem,
         "pt" => SizeMetric::Pt,
+        "%" => SizeMetric::Percent,
+        "vw" => SizeMetric::Vw,
+        "vh" => SizeMetric::Vh,
         _ => return Err(PixelParseError::UnsupportedMetric(number, unit_str, input)),
     };
 
     Ok(PixelValue::from_metric(unit, number))
 }
 
+const CALC_START: &str = "calc(";
+
+/// Intermediate result while evaluating a `calc()` expression
+#[derive(Debug, Clone, PartialEq)]
+enum CalcValue {
+    /// Number without unit, i.e. the `2` in `calc(2 * 10px)`
+    Number(f32),
+    Length(CalcPixelValue),
+}
+
+/// Recursive-descent parser for `calc()` expressions, evaluates the expression
+/// while parsing it (since the result can always be simplified to one `CalcPixelValue`)
+struct CalcParser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> CalcParser<'a> {
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self) -> PixelParseError<'a> {
+        PixelParseError::InvalidCalcExpression(self.input)
+    }
+
+    /// `product [ ('+' | '-') product ]*`
+    fn parse_sum(&mut self) -> Result<CalcValue, PixelParseError<'a>> {
+        use self::CalcValue::*;
+
+        let mut sum = self.parse_product()?;
+
+        loop {
+            self.skip_whitespace();
+            let operator = match self.peek() {
+                Some('+') => '+',
+                Some('-') => '-',
+                _ => return Ok(sum),
+            };
+            self.position += 1;
+
+            let operand = self.parse_product()?;
+
+            sum = match (sum, operand, operator) {
+                (Length(a), Length(b), '+') => Length(a + b),
+                (Length(a), Length(b), _) => Length(a - b),
+                (Number(a), Number(b), '+') => Number(a + b),
+                (Number(a), Number(b), _) => Number(a - b),
+                // Can't add a length and a number, i.e. `calc(5px + 2)`
+                _ => return Err(self.error()),
+            };
+        }
+    }
+
+    /// `operand [ ('*' | '/') operand ]*`
+    fn parse_product(&mut self) -> Result<CalcValue, PixelParseError<'a>> {
+        use self::CalcValue::*;
+
+        let mut product = self.parse_operand()?;
+
+        loop {
+            self.skip_whitespace();
+            let operator = match self.peek() {
+                Some('*') => '*',
+                Some('/') => '/',
+                _ => return Ok(product),
+            };
+            self.position += 1;
+
+            let operand = self.parse_operand()?;
+
+            product = match (product, operand, operator) {
+                (Length(a), Number(b), '*') | (Number(b), Length(a), '*') => Length(a * b),
+                (Number(a), Number(b), '*') => Number(a * b),
+                (Length(a), Number(b), '/') if b != 0.0 => Length(a * (1.0 / b)),
+                (Number(a), Number(b), '/') if b != 0.0 => Number(a / b),
+                // Can't multiply two lengths, divide by a length or divide by zero
+                _ => return Err(self.error()),
+            };
+        }
+    }
+
+    /// `'(' sum ')'`, `'calc(' sum ')'`, a number or a length
+    fn parse_operand(&mut self) -> Result<CalcValue, PixelParseError<'a>> {
+
+        self.skip_whitespace();
+        let rest = self.rest();
+
+        let parenthesis_len = if rest.starts_with(CALC_START) {
+            Some(CALC_START.len())
+        } else if rest.starts_with('(') {
+            Some(1)
+        } else {
+            None
+        };
+
+        if let Some(parenthesis_len) = parenthesis_len {
+            self.position += parenthesis_len;
+            let value = self.parse_sum()?;
+            self.skip_whitespace();
+            if self.peek() != Some(')') {
+                return Err(self.error());
+            }
+            self.position += 1;
+            return Ok(value);
+        }
+
+        // CSS requires whitespace around the "+" and "-" operators,
+        // so a leading "-" is the sign of the number, i.e. `-20px`
+        let token_len = rest.char_indices()
+            .find(|&(idx, ch)| ch.is_whitespace() || "()*/".contains(ch) || (idx > 0 && (ch == '+' || ch == '-')))
+            .map(|(idx, _)| idx)
+            .unwrap_or(rest.len());
+
+        let token = &rest[..token_len];
+        if token.is_empty() {
+            return Err(self.error());
+        }
+        self.position += token_len;
+
+        match token.parse::<f32>() {
+            Ok(number) => Ok(CalcValue::Number(number)),
+            Err(_) => Ok(CalcValue::Length(parse_pixel_value_no_calc(token)?.into())),
+        }
+    }
+}
+
+/// Parses and evaluates a `calc()` expression such as `calc(100% - 2 * 10px)`
+fn parse_calc_expression<'a>(input: &'a str)
+-> Result<CalcPixelValue, PixelParseError<'a>>
+{
+    let mut parser = CalcParser { input, position: 0 };
+    let value = parser.parse_operand()?;
+
+    parser.skip_whitespace();
+    if parser.position != input.len() {
+        return Err(parser.error());
+    }
+
+    match value {
+        CalcValue::Length(length) => Ok(length),
+        CalcValue::Number(_) => Err(parser.error()),
+    }
+}
+
 #[derive(Clone, PartialEq, Eq)]
 pub enum PercentageParseError {
     ValueParseErr(ParseFloatError),
@@ -1335,6 +1566,10 @@ impl_from!(CssColorParseError<'a>, CssBackgroundParseError::ColorParseError);
 pub fn parse_style_background<'a>(input: &'a str)
 -> Result<StyleBackground, CssBackgroundParseError<'a>>
 {
+    if input.trim() == "none" {
+        return Ok(StyleBackground::NoBackground);
+    }
+
     match parse_parentheses(input, &[
         "none", "linear-gradient", "repeating-linear-gradient",
         "radial-gradient", "repeating-radial-gradient", "image",
@@ -1520,7 +1755,9 @@ pub fn parse_gradient<'a>(input: &'a str, background_type: BackgroundType)
 // Normalize the percentages of the parsed color stops
 pub fn normalize_color_stops(color_stops: &mut Vec<GradientStopPre>) {
 
-    let mut last_stop = PercentageValue::new(0.0);
+    // The offsets are accumulated as `f32` and only rounded to a `PercentageValue` when they are
+    // assigned, otherwise the rounding errors add up (i.e. the last stop ends at 99.999%)
+    let mut last_stop: f32 = 0.0;
     let mut increase_stop_cnt: Option<f32> = None;
 
     let color_stop_len = color_stops.len();
@@ -1528,15 +1765,15 @@ pub fn normalize_color_stops(color_stops: &mut Vec<GradientStopPre>) {
         let offset = color_stops[i].offset;
         match offset {
             Some(s) => {
-                last_stop = s;
+                last_stop = s.get();
                 increase_stop_cnt = None;
             },
             None => {
                 let (_, next) = color_stops.split_at_mut(i);
 
                 if let Some(increase_stop_cnt) = increase_stop_cnt {
-                    last_stop = PercentageValue::new(last_stop.get() + increase_stop_cnt);
-                    next[0].offset = Some(last_stop);
+                    last_stop += increase_stop_cnt;
+                    next[0].offset = Some(PercentageValue::new(last_stop));
                     continue 'outer;
                 }
 
@@ -1558,15 +1795,15 @@ pub fn normalize_color_stops(color_stops: &mut Vec<GradientStopPre>) {
                 }
 
                 let next_value = next_value.unwrap_or(PercentageValue::new(100.0));
-                let increase = (next_value.get() / (next_count as f32)) - (last_stop.get() / (next_count as f32)) ;
+                let increase = (next_value.get() / (next_count as f32)) - (last_stop / (next_count as f32)) ;
                 increase_stop_cnt = Some(increase);
                 if next_count == 1 && (color_stop_len - i) == 1 {
-                    next[0].offset = Some(last_stop);
+                    next[0].offset = Some(PercentageValue::new(last_stop));
                 } else {
                     if i == 0 {
                         next[0].offset = Some(PercentageValue::new(0.0));
                     } else {
-                        next[0].offset = Some(last_stop);
+                        next[0].offset = Some(PercentageValue::new(last_stop));
                         // last_stop += increase;
                     }
                 }
@@ -1891,6 +2128,18 @@ pub struct RectLayout {
     pub align_content: Option<LayoutAlignContent>,
 }
 
+/// Parses a `width`, `height` or one of their `min-` / `max-` variants, `calc()` expressions
+/// that mix metrics (i.e. `calc(100% - 20px)`) are returned as a `CssProperty::Calc`
+fn parse_layout_size<'a>(key: CssPropertyType, value: &'a str, size: fn(PixelValue) -> CssProperty)
+-> Result<CssProperty, CssParsingError<'a>>
+{
+    let calc = parse_calc_pixel_value(value)?;
+    match calc.as_pixel_value() {
+        Some(pixel_value) => Ok(size(pixel_value)),
+        None => Ok(CssProperty::Calc(key, calc)),
+    }
+}
+
 typed_pixel_value_parser!(parse_layout_width, LayoutWidth);
 typed_pixel_value_parser!(parse_layout_height, LayoutHeight);
 
@@ -1936,6 +2185,23 @@ pub fn parse_layout_flex_shrink<'a>(input: &'a str) -> Result<LayoutFlexShrink,
     }
 }
 
+/// Parses the `flex-basis`, i.e. `auto`, `100px` or `50%`
+pub fn parse_layout_flex_basis<'a>(input: &'a str) -> Result<LayoutFlexBasis, PixelParseError<'a>> {
+    match input.trim() {
+        "auto" => Ok(LayoutFlexBasis::Auto),
+        basis => Ok(LayoutFlexBasis::Exact(parse_pixel_value(basis)?)),
+    }
+}
+
+/// Parses the `order` of a flex item, i.e. `2` or `-1`
+pub fn parse_layout_order<'a>(input: &'a str) -> Result<LayoutOrder, InvalidValueErr<'a>> {
+    let input = input.trim();
+    input.parse::<i32>().map(LayoutOrder).map_err(|_| InvalidValueErr(input))
+}
+
+typed_pixel_value_parser!(parse_layout_row_gap, LayoutRowGap);
+typed_pixel_value_parser!(parse_layout_column_gap, LayoutColumnGap);
+
 pub fn parse_style_tab_width(input: &str)
 -> Result<StyleTabWidth, PercentageParseError>
 {
@@ -2002,6 +2268,38 @@ pub fn parse_style_font_family<'a>(input: &'a str) -> Result<StyleFontFamily, Cs
     })
 }
 
+/// Parses the `font-weight`, i.e. `normal`, `bold` or a number from `1` to `1000`
+///
+/// The relative weights `bolder` and `lighter` are not supported.
+///
+/// ```rust
+/// # extern crate azul_css;
+/// # extern crate azul_css_parser;
+/// # use azul_css_parser::parse_style_font_weight;
+/// # use azul_css::StyleFontWeight;
+/// assert_eq!(parse_style_font_weight("bold"), Ok(StyleFontWeight::BOLD));
+/// assert_eq!(parse_style_font_weight("300"), Ok(StyleFontWeight(300)));
+/// assert!(parse_style_font_weight("1200").is_err());
+/// ```
+pub fn parse_style_font_weight<'a>(input: &'a str) -> Result<StyleFontWeight, InvalidValueErr<'a>> {
+
+    let input = input.trim();
+
+    match input {
+        "normal" => Ok(StyleFontWeight::NORMAL),
+        "bold" => Ok(StyleFontWeight::BOLD),
+        other => match other.parse::<u16>() {
+            Ok(weight) if weight >= 1 && weight <= 1000 => Ok(StyleFontWeight(weight)),
+            _ => Err(InvalidValueErr(input)),
+        },
+    }
+}
+
+multi_type_parser!(parse_style_font_style, StyleFontStyle,
+                    ["normal", Normal],
+                    ["italic", Italic],
+                    ["oblique", Oblique]);
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd)]
 pub enum ParenthesisParseError<'a> {
     UnclosedBraces,
@@ -2112,6 +2410,24 @@ multi_type_parser!(parse_layout_direction, LayoutDirection,
                     ["column", Column],
                     ["column-reverse", ColumnReverse]);
 
+multi_type_parser!(parse_layout_display, LayoutDisplay,
+                    ["flex", Flex],
+                    ["grid", Grid],
+                    ["none", None]);
+
+multi_type_parser!(parse_layout_intrinsic_width, LayoutIntrinsicWidth,
+                    ["min-content", MinContent],
+                    ["max-content", MaxContent],
+                    ["fit-content", FitContent]);
+
+multi_type_parser!(parse_layout_box_sizing, LayoutBoxSizing,
+                    ["content-box", ContentBox],
+                    ["border-box", BorderBox]);
+
+multi_type_parser!(parse_style_visibility, StyleVisibility,
+                    ["visible", Visible],
+                    ["hidden", Hidden]);
+
 multi_type_parser!(parse_layout_wrap, LayoutWrap,
                     ["wrap", Wrap],
                     ["nowrap", NoWrap]);
@@ -2129,6 +2445,13 @@ multi_type_parser!(parse_layout_align_items, LayoutAlignItems,
                     ["stretch", Stretch],
                     ["center", Center]);
 
+multi_type_parser!(parse_layout_align_self, LayoutAlignSelf,
+                    ["auto", Auto],
+                    ["flex-start", Start],
+                    ["flex-end", End],
+                    ["stretch", Stretch],
+                    ["center", Center]);
+
 multi_type_parser!(parse_layout_align_content, LayoutAlignContent,
                     ["flex-start", Start],
                     ["flex-end", End],
@@ -2144,7 +2467,8 @@ multi_type_parser!(parse_shape, Shape,
 multi_type_parser!(parse_layout_position, LayoutPosition,
                     ["static", Static],
                     ["absolute", Absolute],
-                    ["relative", Relative]);
+                    ["relative", Relative],
+                    ["fixed", Fixed]);
 
 multi_type_parser!(parse_layout_text_overflow, Overflow,
                     ["auto", Auto],
@@ -2157,6 +2481,751 @@ multi_type_parser!(parse_layout_text_align, StyleTextAlignmentHorz,
                     ["left", Left],
                     ["right", Right]);
 
+multi_type_parser!(parse_style_text_direction, StyleTextDirection,
+                    ["ltr", Ltr],
+                    ["rtl", Rtl]);
+
+multi_type_parser!(parse_css_animation_direction, CssAnimationDirection,
+                    ["normal", Normal],
+                    ["reverse", Reverse],
+                    ["alternate", Alternate],
+                    ["alternate-reverse", AlternateReverse]);
+
+multi_type_parser!(parse_css_animation_fill_mode, CssAnimationFillMode,
+                    ["none", None],
+                    ["forwards", Forwards],
+                    ["backwards", Backwards],
+                    ["both", Both]);
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CssAnimationParseError<'a> {
+    /// The value of the `transition` or `animation` property is empty
+    EmptyInput,
+    /// The duration is not a number followed by `s` or `ms`, i.e. `200`
+    InvalidDuration(&'a str),
+    /// Unknown timing function or invalid `cubic-bezier()` arguments
+    InvalidTimingFunction(&'a str),
+    /// The transitioned property is not a known CSS key, i.e. `transition: asdf 1s`
+    UnknownTransitionProperty(&'a str),
+    /// The transition or animation has no duration, i.e. `transition: background`
+    MissingDuration(&'a str),
+    /// The animation has no name, i.e. `animation: 1s ease-in`
+    MissingAnimationName(&'a str),
+    /// A value that doesn't belong to the transition or animation, i.e. `transition: width 1s 2s 3s`
+    UnexpectedValue(&'a str),
+}
+
+impl_debug_as_display!(CssAnimationParseError<'a>);
+impl_display!{ CssAnimationParseError<'a>, {
+    EmptyInput => format!("Empty transition or animation"),
+    InvalidDuration(e) => format!("Invalid duration: \"{}\" - durations have to end with \"s\" or \"ms\"", e),
+    InvalidTimingFunction(e) => format!("Invalid timing function: \"{}\"", e),
+    UnknownTransitionProperty(e) => format!("Unknown CSS key in transition: \"{}\"", e),
+    MissingDuration(e) => format!("Missing duration: \"{}\"", e),
+    MissingAnimationName(e) => format!("Missing animation name: \"{}\"", e),
+    UnexpectedValue(e) => format!("Unexpected value: \"{}\"", e),
+}}
+
+/// Splits the input at the commas that aren't enclosed in parentheses, i.e.
+/// `"a 1s cubic-bezier(0, 0, 1, 1), b 2s"` => `["a 1s cubic-bezier(0, 0, 1, 1)", " b 2s"]`
+pub(crate) fn split_top_level_commas(input: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut nesting = 0_usize;
+    let mut last_split = 0;
+
+    for (idx, c) in input.char_indices() {
+        match c {
+            '(' => nesting += 1,
+            ')' => nesting = nesting.saturating_sub(1),
+            ',' if nesting == 0 => {
+                items.push(&input[last_split..idx]);
+                last_split = idx + 1;
+            },
+            _ => { },
+        }
+    }
+
+    items.push(&input[last_split..]);
+    items
+}
+
+/// Splits the input at the whitespace that isn't enclosed in parentheses, i.e.
+/// `"a 1s cubic-bezier(0, 0, 1, 1)"` => `["a", "1s", "cubic-bezier(0, 0, 1, 1)"]`
+pub(crate) fn split_top_level_whitespace(input: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut nesting = 0_usize;
+    let mut item_start = None;
+
+    for (idx, c) in input.char_indices() {
+        match c {
+            '(' => nesting += 1,
+            ')' => nesting = nesting.saturating_sub(1),
+            c if c.is_whitespace() && nesting == 0 => {
+                if let Some(start) = item_start.take() {
+                    items.push(&input[start..idx]);
+                }
+                continue;
+            },
+            _ => { },
+        }
+        if item_start.is_none() {
+            item_start = Some(idx);
+        }
+    }
+
+    if let Some(start) = item_start {
+        items.push(&input[start..]);
+    }
+
+    items
+}
+
+/// Parses a CSS duration, such as `200ms` or `1.5s`
+///
+/// ```rust
+/// # extern crate azul_css_parser;
+/// # use std::time::Duration;
+/// # use azul_css_parser::parse_css_duration;
+/// assert_eq!(parse_css_duration("200ms"), Ok(Duration::from_millis(200)));
+/// assert_eq!(parse_css_duration("1.5s"), Ok(Duration::from_millis(1500)));
+/// assert!(parse_css_duration("200").is_err());
+/// ```
+pub fn parse_css_duration<'a>(input: &'a str) -> Result<Duration, CssAnimationParseError<'a>> {
+
+    let input = input.trim();
+
+    let (number, millis_per_unit) = if input.ends_with("ms") {
+        (&input[..input.len() - 2], 1.0)
+    } else if input.ends_with('s') {
+        (&input[..input.len() - 1], 1000.0)
+    } else {
+        return Err(CssAnimationParseError::InvalidDuration(input));
+    };
+
+    let number = number.parse::<f32>().map_err(|_| CssAnimationParseError::InvalidDuration(input))?;
+
+    if number < 0.0 || !number.is_finite() {
+        return Err(CssAnimationParseError::InvalidDuration(input));
+    }
+
+    Ok(Duration::from_millis((number * millis_per_unit).round() as u64))
+}
+
+/// Parses a timing function, such as `ease-in` or `cubic-bezier(0.1, 0.7, 1.0, 0.1)`
+///
+/// ```rust
+/// # extern crate azul_css;
+/// # extern crate azul_css_parser;
+/// # use azul_css_parser::parse_css_timing_function;
+/// # use azul_css::{CssTimingFunction, FloatValue};
+/// assert_eq!(parse_css_timing_function("ease-in"), Ok(CssTimingFunction::EaseIn));
+/// assert_eq!(
+///     parse_css_timing_function("cubic-bezier(0.5, 0, 1, 2)"),
+///     Ok(CssTimingFunction::CubicBezier(FloatValue::new(0.5), FloatValue::new(0.0), FloatValue::new(1.0), FloatValue::new(2.0)))
+/// );
+/// ```
+pub fn parse_css_timing_function<'a>(input: &'a str) -> Result<CssTimingFunction, CssAnimationParseError<'a>> {
+
+    let input = input.trim();
+
+    match input {
+        "ease" => return Ok(CssTimingFunction::Ease),
+        "linear" => return Ok(CssTimingFunction::Linear),
+        "ease-in" => return Ok(CssTimingFunction::EaseIn),
+        "ease-out" => return Ok(CssTimingFunction::EaseOut),
+        "ease-in-out" => return Ok(CssTimingFunction::EaseInOut),
+        "step-start" => return Ok(CssTimingFunction::StepStart),
+        "step-end" => return Ok(CssTimingFunction::StepEnd),
+        _ => { },
+    }
+
+    let (_, arguments) = parse_parentheses(input, &["cubic-bezier"])
+        .map_err(|_| CssAnimationParseError::InvalidTimingFunction(input))?;
+
+    let points = arguments.split(',')
+        .map(|point| point.trim().parse::<f32>())
+        .collect::<Result<Vec<f32>, _>>()
+        .map_err(|_| CssAnimationParseError::InvalidTimingFunction(input))?;
+
+    if points.len() != 4 {
+        return Err(CssAnimationParseError::InvalidTimingFunction(input));
+    }
+
+    // The x values have to be between 0.0 and 1.0, otherwise the curve isn't a function of x
+    if points[0] < 0.0 || points[0] > 1.0 || points[2] < 0.0 || points[2] > 1.0 {
+        return Err(CssAnimationParseError::InvalidTimingFunction(input));
+    }
+
+    Ok(CssTimingFunction::CubicBezier(
+        FloatValue::new(points[0]),
+        FloatValue::new(points[1]),
+        FloatValue::new(points[2]),
+        FloatValue::new(points[3]),
+    ))
+}
+
+/// Parses the value of the `transition` property, i.e. `background 200ms ease-in, width 1s linear 50ms`.
+///
+/// The first duration is the duration of the transition, the second duration is the delay.
+pub fn parse_css_transitions<'a>(input: &'a str) -> Result<Vec<CssTransition>, CssAnimationParseError<'a>> {
+
+    let input = input.trim();
+
+    if input.is_empty() {
+        return Err(CssAnimationParseError::EmptyInput);
+    }
+
+    let css_property_map = azul_css::get_css_key_map();
+    split_top_level_commas(input).into_iter().map(|transition| parse_css_transition(transition.trim(), &css_property_map)).collect()
+}
+
+fn parse_css_transition<'a>(input: &'a str, css_property_map: &BTreeMap<&'static str, CssPropertyType>)
+-> Result<CssTransition, CssAnimationParseError<'a>>
+{
+    let mut property = None;
+    let mut durations = Vec::new();
+    let mut timing_function = None;
+
+    for component in split_top_level_whitespace(input) {
+        if let Ok(duration) = parse_css_duration(component) {
+            if durations.len() == 2 {
+                return Err(CssAnimationParseError::UnexpectedValue(component));
+            }
+            durations.push(duration);
+        } else if timing_function.is_none() && parse_css_timing_function(component).is_ok() {
+            timing_function = Some(parse_css_timing_function(component)?);
+        } else if property.is_none() && component == "all" {
+            property = Some(CssTransitionProperty::All);
+        } else if property.is_none() {
+            let property_type = CssPropertyType::from_str(component, css_property_map)
+                .ok_or(CssAnimationParseError::UnknownTransitionProperty(component))?;
+            property = Some(CssTransitionProperty::Property(property_type));
+        } else {
+            return Err(CssAnimationParseError::UnexpectedValue(component));
+        }
+    }
+
+    let duration = *durations.get(0).ok_or(CssAnimationParseError::MissingDuration(input))?;
+
+    Ok(CssTransition {
+        property: property.unwrap_or(CssTransitionProperty::All),
+        duration,
+        timing_function: timing_function.unwrap_or_default(),
+        delay: durations.get(1).cloned().unwrap_or_default(),
+    })
+}
+
+/// Parses the `animation-iteration-count`, i.e. `3`, `0.5` or `infinite`
+pub fn parse_css_animation_iteration_count<'a>(input: &'a str) -> Result<CssAnimationIterationCount, InvalidValueErr<'a>> {
+
+    let input = input.trim();
+
+    if input == "infinite" {
+        return Ok(CssAnimationIterationCount::Infinite);
+    }
+
+    match input.parse::<f32>() {
+        Ok(count) if count >= 0.0 && count.is_finite() => Ok(CssAnimationIterationCount::Count(FloatValue::new(count))),
+        _ => Err(InvalidValueErr(input)),
+    }
+}
+
+/// Parses the value of the `animation` property, i.e. `fade-in 1s ease-in-out 200ms infinite alternate`.
+///
+/// The first duration is the duration of one iteration, the second duration is the delay.
+/// The name of the animation refers to a `@keyframes` rule.
+pub fn parse_css_animations<'a>(input: &'a str) -> Result<Vec<CssAnimation>, CssAnimationParseError<'a>> {
+
+    let input = input.trim();
+
+    if input.is_empty() {
+        return Err(CssAnimationParseError::EmptyInput);
+    }
+
+    split_top_level_commas(input).into_iter().map(|animation| parse_css_animation(animation.trim())).collect()
+}
+
+fn parse_css_animation<'a>(input: &'a str) -> Result<CssAnimation, CssAnimationParseError<'a>> {
+
+    let mut name = None;
+    let mut durations = Vec::new();
+    let mut timing_function = None;
+    let mut iteration_count = None;
+    let mut direction = None;
+    let mut fill_mode = None;
+
+    // Keywords take precedence over the name, so `animation: ease 1s` has no name
+    for component in split_top_level_whitespace(input) {
+        if let Ok(duration) = parse_css_duration(component) {
+            if durations.len() == 2 {
+                return Err(CssAnimationParseError::UnexpectedValue(component));
+            }
+            durations.push(duration);
+        } else if timing_function.is_none() && parse_css_timing_function(component).is_ok() {
+            timing_function = Some(parse_css_timing_function(component)?);
+        } else if iteration_count.is_none() && parse_css_animation_iteration_count(component).is_ok() {
+            iteration_count = parse_css_animation_iteration_count(component).ok();
+        } else if direction.is_none() && parse_css_animation_direction(component).is_ok() {
+            direction = parse_css_animation_direction(component).ok();
+        } else if fill_mode.is_none() && parse_css_animation_fill_mode(component).is_ok() {
+            fill_mode = parse_css_animation_fill_mode(component).ok();
+        } else if name.is_none() {
+            name = Some(component);
+        } else {
+            return Err(CssAnimationParseError::UnexpectedValue(component));
+        }
+    }
+
+    let name = name.ok_or(CssAnimationParseError::MissingAnimationName(input))?;
+    let duration = *durations.get(0).ok_or(CssAnimationParseError::MissingDuration(input))?;
+
+    Ok(CssAnimation {
+        name: name.to_string(),
+        duration,
+        timing_function: timing_function.unwrap_or_default(),
+        delay: durations.get(1).cloned().unwrap_or_default(),
+        iteration_count: iteration_count.unwrap_or_default(),
+        direction: direction.unwrap_or_default(),
+        fill_mode: fill_mode.unwrap_or_default(),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpacityParseError<'a> {
+    ParseFloat(ParseFloatError, &'a str),
+}
+
+impl_display!{OpacityParseError<'a>, {
+    ParseFloat(e, orig_str) => format!("opacity: Could not parse floating-point value: \"{}\" - Error: \"{}\"", orig_str, e),
+}}
+
+/// Parses the `opacity`, i.e. `0.5` or `50%` - values outside of `0.0..=1.0` are clamped
+///
+/// ```rust
+/// # extern crate azul_css;
+/// # extern crate azul_css_parser;
+/// # use azul_css_parser::parse_style_opacity;
+/// # use azul_css::{StyleOpacity, FloatValue};
+/// assert_eq!(parse_style_opacity("0.5"), Ok(StyleOpacity(FloatValue::new(0.5))));
+/// assert_eq!(parse_style_opacity("25%"), Ok(StyleOpacity(FloatValue::new(0.25))));
+/// assert_eq!(parse_style_opacity("2"), Ok(StyleOpacity(FloatValue::new(1.0))));
+/// ```
+pub fn parse_style_opacity<'a>(input: &'a str) -> Result<StyleOpacity, OpacityParseError<'a>> {
+
+    let input = input.trim();
+
+    let opacity = if input.ends_with('%') {
+        input[..input.len() - 1].trim().parse::<f32>().map(|percent| percent / 100.0)
+    } else {
+        input.parse::<f32>()
+    };
+
+    match opacity {
+        Ok(o) => Ok(StyleOpacity(FloatValue::new(o.max(0.0).min(1.0)))),
+        Err(e) => Err(OpacityParseError::ParseFloat(e, input)),
+    }
+}
+
+/// Parses the `z-index`, i.e. `5`, `-1` or `auto` (which is the same as `0`)
+pub fn parse_style_z_index<'a>(input: &'a str) -> Result<StyleZIndex, InvalidValueErr<'a>> {
+
+    let input = input.trim();
+
+    if input == "auto" {
+        return Ok(StyleZIndex(0));
+    }
+
+    input.parse::<i32>().map(StyleZIndex).map_err(|_| InvalidValueErr(input))
+}
+
+#[derive(Clone, PartialEq)]
+pub enum CssTransformParseError<'a> {
+    /// The value of the `transform` or `transform-origin` property is empty
+    EmptyInput,
+    /// Unknown transform function, i.e. `perspective(10px)` or `matrix(...)`
+    InvalidTransformFunction(&'a str),
+    /// The function has too many or too few arguments, i.e. `rotate(10deg, 20deg)`
+    WrongNumberOfArguments(&'a str),
+    /// The angle is not a number followed by `deg`, `rad`, `grad` or `turn`
+    InvalidAngle(&'a str),
+    /// The argument of `scale()` is not a number
+    InvalidNumber(&'a str),
+    /// Invalid keyword in the `transform-origin`, i.e. `transform-origin: top top`
+    InvalidTransformOrigin(&'a str),
+    PixelParseError(PixelParseError<'a>),
+}
+
+impl_debug_as_display!(CssTransformParseError<'a>);
+impl_display!{ CssTransformParseError<'a>, {
+    EmptyInput => format!("Empty transform"),
+    InvalidTransformFunction(e) => format!("Invalid or unsupported transform function: \"{}\"", e),
+    WrongNumberOfArguments(e) => format!("Wrong number of arguments: \"{}\"", e),
+    InvalidAngle(e) => format!("Invalid angle: \"{}\" - angles have to end with \"deg\", \"rad\", \"grad\" or \"turn\"", e),
+    InvalidNumber(e) => format!("Expected a number, got: \"{}\"", e),
+    InvalidTransformOrigin(e) => format!("Invalid transform-origin: \"{}\"", e),
+    PixelParseError(e) => format!("{}", e),
+}}
+
+impl_from!(PixelParseError<'a>, CssTransformParseError::PixelParseError);
+
+/// Parses an angle such as `45deg`, `1.5rad`, `100grad` or `0.25turn` into degrees.
+/// A unitless angle is only allowed if it is `0`.
+fn parse_css_angle<'a>(input: &'a str) -> Result<f32, CssTransformParseError<'a>> {
+
+    use std::f32::consts::PI;
+
+    let input = input.trim();
+
+    let (number, degrees_per_unit) = if input.ends_with("deg") {
+        (&input[..input.len() - 3], 1.0)
+    } else if input.ends_with("grad") {
+        (&input[..input.len() - 4], 360.0 / 400.0)
+    } else if input.ends_with("rad") {
+        (&input[..input.len() - 3], 180.0 / PI)
+    } else if input.ends_with("turn") {
+        (&input[..input.len() - 4], 360.0)
+    } else if input == "0" {
+        (input, 0.0)
+    } else {
+        return Err(CssTransformParseError::InvalidAngle(input));
+    };
+
+    match number.parse::<f32>() {
+        Ok(angle) if angle.is_finite() => Ok(angle * degrees_per_unit),
+        _ => Err(CssTransformParseError::InvalidAngle(input)),
+    }
+}
+
+/// Parses the value of the `transform` property, i.e. `translate(-50%, 10px) rotate(45deg) scale(2)`
+///
+/// Supports `none` and the 2D functions `translate`, `translateX`, `translateY`, `rotate`,
+/// `scale`, `scaleX`, `scaleY`, `skew`, `skewX` and `skewY`.
+///
+/// ```rust
+/// # extern crate azul_css;
+/// # extern crate azul_css_parser;
+/// # use azul_css_parser::parse_style_transform;
+/// # use azul_css::{StyleTransform, StyleTransformFunction, PixelValue, FloatValue};
+/// assert_eq!(
+///     parse_style_transform("translateX(10px) rotate(0.5turn)"),
+///     Ok(StyleTransform(vec![
+///         StyleTransformFunction::Translate(PixelValue::px(10.0), PixelValue::px(0.0)),
+///         StyleTransformFunction::Rotate(FloatValue::new(180.0)),
+///     ]))
+/// );
+/// assert_eq!(parse_style_transform("none"), Ok(StyleTransform(Vec::new())));
+/// ```
+pub fn parse_style_transform<'a>(input: &'a str) -> Result<StyleTransform, CssTransformParseError<'a>> {
+
+    let input = input.trim();
+
+    if input.is_empty() {
+        return Err(CssTransformParseError::EmptyInput);
+    }
+
+    if input == "none" {
+        return Ok(StyleTransform(Vec::new()));
+    }
+
+    split_top_level_whitespace(input).into_iter()
+        .map(parse_style_transform_function)
+        .collect::<Result<Vec<_>, _>>()
+        .map(StyleTransform)
+}
+
+fn parse_style_transform_function<'a>(input: &'a str) -> Result<StyleTransformFunction, CssTransformParseError<'a>> {
+
+    use self::StyleTransformFunction::*;
+
+    let (function, arguments) = parse_parentheses(input, &[
+        "translate", "translateX", "translateY",
+        "rotate",
+        "scale", "scaleX", "scaleY",
+        "skew", "skewX", "skewY",
+    ]).map_err(|_| CssTransformParseError::InvalidTransformFunction(input))?;
+
+    let arguments = arguments.split(',').map(|argument| argument.trim()).collect::<Vec<_>>();
+
+    let parse_number = |argument: &'a str| match argument.parse::<f32>() {
+        Ok(number) if number.is_finite() => Ok(FloatValue::new(number)),
+        _ => Err(CssTransformParseError::InvalidNumber(argument)),
+    };
+
+    let zero = PixelValue::px(0.0);
+
+    match (function, arguments.as_slice()) {
+        ("translate", [x]) => Ok(Translate(parse_pixel_value(*x)?, zero)),
+        ("translate", [x, y]) => Ok(Translate(parse_pixel_value(*x)?, parse_pixel_value(*y)?)),
+        ("translateX", [x]) => Ok(Translate(parse_pixel_value(*x)?, zero)),
+        ("translateY", [y]) => Ok(Translate(zero, parse_pixel_value(*y)?)),
+        ("rotate", [angle]) => Ok(Rotate(FloatValue::new(parse_css_angle(*angle)?))),
+        ("scale", [s]) => Ok(Scale(parse_number(*s)?, parse_number(*s)?)),
+        ("scale", [x, y]) => Ok(Scale(parse_number(*x)?, parse_number(*y)?)),
+        ("scaleX", [x]) => Ok(Scale(parse_number(*x)?, FloatValue::new(1.0))),
+        ("scaleY", [y]) => Ok(Scale(FloatValue::new(1.0), parse_number(*y)?)),
+        ("skew", [x]) => Ok(Skew(FloatValue::new(parse_css_angle(*x)?), FloatValue::new(0.0))),
+        ("skew", [x, y]) => Ok(Skew(FloatValue::new(parse_css_angle(*x)?), FloatValue::new(parse_css_angle(*y)?))),
+        ("skewX", [x]) => Ok(Skew(FloatValue::new(parse_css_angle(*x)?), FloatValue::new(0.0))),
+        ("skewY", [y]) => Ok(Skew(FloatValue::new(0.0), FloatValue::new(parse_css_angle(*y)?))),
+        _ => Err(CssTransformParseError::WrongNumberOfArguments(input)),
+    }
+}
+
+/// What a component of the `transform-origin` can be used for
+#[derive(Copy, Clone, PartialEq)]
+enum OriginComponent {
+    /// `left` or `right`
+    Horizontal,
+    /// `top` or `bottom`
+    Vertical,
+    /// `center`
+    Center,
+    /// `10px`, `50%`, ...
+    Length,
+}
+
+/// Parses the `transform-origin`, i.e. `left top`, `50% 100%` or `10px` (in which case the
+/// vertical origin is `center`). If only one keyword is given, the other axis is `center`.
+///
+/// ```rust
+/// # extern crate azul_css;
+/// # extern crate azul_css_parser;
+/// # use azul_css_parser::parse_style_transform_origin;
+/// # use azul_css::{StyleTransformOrigin, PixelValue};
+/// assert_eq!(
+///     parse_style_transform_origin("top right"),
+///     Ok(StyleTransformOrigin { x: PixelValue::percent(100.0), y: PixelValue::percent(0.0) })
+/// );
+/// assert_eq!(
+///     parse_style_transform_origin("10px bottom"),
+///     Ok(StyleTransformOrigin { x: PixelValue::px(10.0), y: PixelValue::percent(100.0) })
+/// );
+/// ```
+pub fn parse_style_transform_origin<'a>(input: &'a str) -> Result<StyleTransformOrigin, CssTransformParseError<'a>> {
+
+    use self::OriginComponent::*;
+
+    let input = input.trim();
+
+    let parse_component = |component: &'a str| -> Result<(PixelValue, OriginComponent), CssTransformParseError<'a>> {
+        match component {
+            "left" => Ok((PixelValue::percent(0.0), Horizontal)),
+            "right" => Ok((PixelValue::percent(100.0), Horizontal)),
+            "top" => Ok((PixelValue::percent(0.0), Vertical)),
+            "bottom" => Ok((PixelValue::percent(100.0), Vertical)),
+            "center" => Ok((PixelValue::percent(50.0), Center)),
+            other => Ok((parse_pixel_value(other)?, Length)),
+        }
+    };
+
+    let components = input.split_whitespace().map(parse_component).collect::<Result<Vec<_>, _>>()?;
+
+    let center = PixelValue::percent(50.0);
+
+    match components.as_slice() {
+        [] => Err(CssTransformParseError::EmptyInput),
+        [(y, Vertical)] => Ok(StyleTransformOrigin { x: center, y: *y }),
+        [(x, _)] => Ok(StyleTransformOrigin { x: *x, y: center }),
+        [(x, x_type), (y, y_type)] if *x_type != Vertical && *y_type != Horizontal => {
+            Ok(StyleTransformOrigin { x: *x, y: *y })
+        },
+        // Keywords can be swapped, i.e. "top left" is the same as "left top"
+        [(y, Vertical), (x, Horizontal)] |
+        [(y, Vertical), (x, Center)] |
+        [(y, Center), (x, Horizontal)] => {
+            Ok(StyleTransformOrigin { x: *x, y: *y })
+        },
+        _ => Err(CssTransformParseError::InvalidTransformOrigin(input)),
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum CssGridParseError<'a> {
+    /// The value of the grid property is empty
+    EmptyInput,
+    /// The track size is not `auto`, a length or a fraction, i.e. `grid-template-columns: 1fx`
+    InvalidTrackSize(&'a str),
+    /// `repeat()` needs a positive number of repetitions and at least one track, i.e. `repeat(3, 1fr)`
+    InvalidRepeat(&'a str),
+    /// The grid line is not `auto`, `span <n>` or a non-zero number, i.e. `grid-column: 0`
+    InvalidGridLine(&'a str),
+    /// More values than the property accepts, i.e. `grid-gap: 1px 2px 3px` or `grid-row: 1 / 2 / 3`
+    TooManyValues(&'a str),
+    PixelParseError(PixelParseError<'a>),
+}
+
+impl_debug_as_display!(CssGridParseError<'a>);
+impl_display!{ CssGridParseError<'a>, {
+    EmptyInput => format!("Empty grid property"),
+    InvalidTrackSize(e) => format!("Invalid track size: \"{}\" - expected \"auto\", a length or a fraction (i.e. \"1fr\")", e),
+    InvalidRepeat(e) => format!("Invalid repeat(): \"{}\"", e),
+    InvalidGridLine(e) => format!("Invalid grid line: \"{}\" - expected \"auto\", \"span <n>\" or a non-zero number", e),
+    TooManyValues(e) => format!("Too many values: \"{}\"", e),
+    PixelParseError(e) => format!("{}", e),
+}}
+
+impl_from!(PixelParseError<'a>, CssGridParseError::PixelParseError);
+
+/// Parses the value of `grid-template-columns` or `grid-template-rows`, i.e. `100px 1fr auto`.
+/// `repeat()` is expanded into the repeated tracks, `none` results in an empty template.
+///
+/// ```rust
+/// # extern crate azul_css;
+/// # extern crate azul_css_parser;
+/// # use azul_css_parser::parse_layout_grid_template;
+/// # use azul_css::{LayoutGridTemplate, GridTrackSize, PixelValue, FloatValue};
+/// assert_eq!(
+///     parse_layout_grid_template("100px repeat(2, 1fr) auto"),
+///     Ok(LayoutGridTemplate(vec![
+///         GridTrackSize::Fixed(PixelValue::px(100.0)),
+///         GridTrackSize::Fraction(FloatValue::new(1.0)),
+///         GridTrackSize::Fraction(FloatValue::new(1.0)),
+///         GridTrackSize::Auto,
+///     ]))
+/// );
+/// assert_eq!(parse_layout_grid_template("none"), Ok(LayoutGridTemplate(Vec::new())));
+/// ```
+pub fn parse_layout_grid_template<'a>(input: &'a str) -> Result<LayoutGridTemplate, CssGridParseError<'a>> {
+
+    let input = input.trim();
+
+    if input.is_empty() {
+        return Err(CssGridParseError::EmptyInput);
+    }
+
+    if input == "none" {
+        return Ok(LayoutGridTemplate(Vec::new()));
+    }
+
+    let mut tracks = Vec::new();
+
+    for item in split_top_level_whitespace(input) {
+
+        if !item.starts_with("repeat(") {
+            tracks.push(parse_grid_track_size(item)?);
+            continue;
+        }
+
+        let (_, arguments) = parse_parentheses(item, &["repeat"])
+            .map_err(|_| CssGridParseError::InvalidRepeat(item))?;
+
+        let (count, repeated) = match arguments.find(',') {
+            Some(comma) => (&arguments[..comma], &arguments[(comma + 1)..]),
+            None => return Err(CssGridParseError::InvalidRepeat(item)),
+        };
+
+        let count = match count.trim().parse::<usize>() {
+            Ok(count) if count > 0 => count,
+            _ => return Err(CssGridParseError::InvalidRepeat(item)),
+        };
+
+        let repeated = split_top_level_whitespace(repeated).into_iter()
+            .map(parse_grid_track_size)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if repeated.is_empty() {
+            return Err(CssGridParseError::InvalidRepeat(item));
+        }
+
+        for _ in 0..count {
+            tracks.extend_from_slice(&repeated);
+        }
+    }
+
+    Ok(LayoutGridTemplate(tracks))
+}
+
+fn parse_grid_track_size<'a>(input: &'a str) -> Result<GridTrackSize, CssGridParseError<'a>> {
+
+    let input = input.trim();
+
+    if input == "auto" {
+        return Ok(GridTrackSize::Auto);
+    }
+
+    if input.ends_with("fr") {
+        return match input[..input.len() - 2].parse::<f32>() {
+            Ok(fr) if fr.is_finite() && fr >= 0.0 => Ok(GridTrackSize::Fraction(FloatValue::new(fr))),
+            _ => Err(CssGridParseError::InvalidTrackSize(input)),
+        };
+    }
+
+    parse_pixel_value(input)
+        .map(GridTrackSize::Fixed)
+        .map_err(|_| CssGridParseError::InvalidTrackSize(input))
+}
+
+/// Parses the `grid-gap`, i.e. `10px` (same gap between rows and columns)
+/// or `10px 20px` (gap between rows, gap between columns)
+pub fn parse_layout_grid_gap<'a>(input: &'a str) -> Result<LayoutGridGap, CssGridParseError<'a>> {
+
+    let input = input.trim();
+
+    match split_top_level_whitespace(input).as_slice() {
+        [] => Err(CssGridParseError::EmptyInput),
+        [gap] => {
+            let gap = parse_pixel_value(*gap)?;
+            Ok(LayoutGridGap { row: gap, column: gap })
+        },
+        [row, column] => Ok(LayoutGridGap { row: parse_pixel_value(*row)?, column: parse_pixel_value(*column)? }),
+        _ => Err(CssGridParseError::TooManyValues(input)),
+    }
+}
+
+/// Parses the value of `grid-column` or `grid-row`, i.e. `2`, `1 / 3`, `1 / span 2` or `span 2`.
+/// If no end line is given, the item spans one track.
+///
+/// ```rust
+/// # extern crate azul_css;
+/// # extern crate azul_css_parser;
+/// # use azul_css_parser::parse_layout_grid_placement;
+/// # use azul_css::{LayoutGridPlacement, GridLine};
+/// assert_eq!(
+///     parse_layout_grid_placement("2 / span 3"),
+///     Ok(LayoutGridPlacement { start: GridLine::Line(2), end: GridLine::Span(3) })
+/// );
+/// assert_eq!(
+///     parse_layout_grid_placement("-1"),
+///     Ok(LayoutGridPlacement { start: GridLine::Line(-1), end: GridLine::Auto })
+/// );
+/// ```
+pub fn parse_layout_grid_placement<'a>(input: &'a str) -> Result<LayoutGridPlacement, CssGridParseError<'a>> {
+
+    let input = input.trim();
+
+    let lines = input.split('/').map(parse_grid_line).collect::<Result<Vec<_>, _>>()?;
+
+    match lines.as_slice() {
+        [start] => Ok(LayoutGridPlacement { start: *start, end: GridLine::Auto }),
+        [start, end] => Ok(LayoutGridPlacement { start: *start, end: *end }),
+        _ => Err(CssGridParseError::TooManyValues(input)),
+    }
+}
+
+fn parse_grid_line<'a>(input: &'a str) -> Result<GridLine, CssGridParseError<'a>> {
+
+    let input = input.trim();
+
+    if input.is_empty() {
+        return Err(CssGridParseError::EmptyInput);
+    }
+
+    let mut words = input.split_whitespace();
+
+    match (words.next(), words.next(), words.next()) {
+        (Some("auto"), None, None) => Ok(GridLine::Auto),
+        (Some("span"), Some(span), None) => match span.parse::<u32>() {
+            Ok(span) if span > 0 => Ok(GridLine::Span(span)),
+            _ => Err(CssGridParseError::InvalidGridLine(input)),
+        },
+        (Some(line), None, None) => match line.parse::<i32>() {
+            Ok(line) if line != 0 => Ok(GridLine::Line(line)),
+            _ => Err(CssGridParseError::InvalidGridLine(input)),
+        },
+        _ => Err(CssGridParseError::InvalidGridLine(input)),
+    }
+}
+
 #[cfg(test)]
 mod css_tests {
     use super::*;
@@ -2774,6 +3843,34 @@ mod css_tests {
         assert_eq!(parse_pixel_value("aslkfdjasdflk"), Err(PixelParseError::NoValueGiven("aslkfdjasdflk")));
     }
 
+    #[test]
+    fn test_parse_pixel_value_relative_units() {
+        assert_eq!(parse_pixel_value("50%"), Ok(PixelValue::percent(50.0)));
+        assert_eq!(parse_pixel_value("1.5rem"), Ok(PixelValue::rem(1.5)));
+        assert_eq!(parse_pixel_value("100vw"), Ok(PixelValue::vw(100.0)));
+        assert_eq!(parse_pixel_value("20vh"), Ok(PixelValue::vh(20.0)));
+    }
+
+    #[test]
+    fn test_parse_pixel_value_calc() {
+        let calc = |terms: &[PixelValue]| terms.iter().fold(CalcPixelValue::default(), |sum, term| sum + *term);
+        assert_eq!(parse_calc_pixel_value("calc(100% - 20px)"), Ok(calc(&[PixelValue::percent(100.0), PixelValue::px(-20.0)])));
+        assert_eq!(parse_calc_pixel_value("calc(2 * (10px + 1em) - -5px)"), Ok(calc(&[PixelValue::px(25.0), PixelValue::em(2.0)])));
+        assert_eq!(parse_calc_pixel_value("calc(100vw / 4 + calc(1rem * 2))"), Ok(calc(&[PixelValue::vw(25.0), PixelValue::rem(2.0)])));
+        assert_eq!(
+            parse_key_value_pair(CssPropertyType::Width, "calc(100% - 20px)"),
+            Ok(CssProperty::Calc(CssPropertyType::Width, calc(&[PixelValue::percent(100.0), PixelValue::px(-20.0)])))
+        );
+        // Expressions that don't mix metrics are simplified to a single value
+        assert_eq!(parse_pixel_value("calc(2 * 10px + 5px)"), Ok(PixelValue::px(25.0)));
+        assert_eq!(parse_key_value_pair(CssPropertyType::Width, "calc(50% + 50%)"), Ok(CssProperty::Width(LayoutWidth::percent(100.0))));
+        assert_eq!(parse_pixel_value("calc(100% - 20px)"), Err(PixelParseError::UnsupportedCalcExpression("calc(100% - 20px)")));
+        assert_eq!(parse_pixel_value("calc(100% - 5)"), Err(PixelParseError::InvalidCalcExpression("calc(100% - 5)")));
+        assert_eq!(parse_pixel_value("calc(10px * 10px)"), Err(PixelParseError::InvalidCalcExpression("calc(10px * 10px)")));
+        assert_eq!(parse_pixel_value("calc(10px / 0)"), Err(PixelParseError::InvalidCalcExpression("calc(10px / 0)")));
+        assert_eq!(parse_pixel_value("calc(10px"), Err(PixelParseError::InvalidCalcExpression("calc(10px")));
+    }
+
     #[test]
     fn test_parse_style_border_radius_1() {
         assert_eq!(parse_style_border_radius("15px"), Ok(StyleBorderRadius(
@@ -2876,4 +3973,147 @@ mod css_tests {
             left: Some(PixelValue::px(100.0)),
         }));
     }
+
+    #[test]
+    fn test_parse_css_transitions() {
+        assert_eq!(parse_css_transitions("background 200ms ease-in"), Ok(vec![CssTransition {
+            property: CssTransitionProperty::Property(CssPropertyType::Background),
+            duration: Duration::from_millis(200),
+            timing_function: CssTimingFunction::EaseIn,
+            delay: Duration::from_millis(0),
+        }]));
+        assert_eq!(parse_css_transitions("width 1s cubic-bezier(0, 0, 1, 1) 0.5s, 2s"), Ok(vec![
+            CssTransition {
+                property: CssTransitionProperty::Property(CssPropertyType::Width),
+                duration: Duration::from_secs(1),
+                timing_function: CssTimingFunction::CubicBezier(FloatValue::new(0.0), FloatValue::new(0.0), FloatValue::new(1.0), FloatValue::new(1.0)),
+                delay: Duration::from_millis(500),
+            },
+            CssTransition {
+                property: CssTransitionProperty::All,
+                duration: Duration::from_secs(2),
+                timing_function: CssTimingFunction::Ease,
+                delay: Duration::from_millis(0),
+            },
+        ]));
+        assert_eq!(parse_css_transitions("background"), Err(CssAnimationParseError::MissingDuration("background")));
+        assert_eq!(parse_css_transitions("asdf 1s"), Err(CssAnimationParseError::UnknownTransitionProperty("asdf")));
+    }
+
+    #[test]
+    fn test_parse_css_animations() {
+        assert_eq!(parse_css_animations("fade-in 1s ease-out 200ms infinite alternate forwards"), Ok(vec![CssAnimation {
+            name: "fade-in".into(),
+            duration: Duration::from_secs(1),
+            timing_function: CssTimingFunction::EaseOut,
+            delay: Duration::from_millis(200),
+            iteration_count: CssAnimationIterationCount::Infinite,
+            direction: CssAnimationDirection::Alternate,
+            fill_mode: CssAnimationFillMode::Forwards,
+        }]));
+        assert_eq!(parse_css_animations("pulse 500ms 3"), Ok(vec![CssAnimation {
+            name: "pulse".into(),
+            duration: Duration::from_millis(500),
+            timing_function: CssTimingFunction::Ease,
+            delay: Duration::from_millis(0),
+            iteration_count: CssAnimationIterationCount::Count(FloatValue::new(3.0)),
+            direction: CssAnimationDirection::Normal,
+            fill_mode: CssAnimationFillMode::None,
+        }]));
+        assert_eq!(parse_css_animations("1s linear"), Err(CssAnimationParseError::MissingAnimationName("1s linear")));
+    }
+
+    #[test]
+    fn test_parse_style_transform() {
+        use azul_css::StyleTransformFunction::*;
+        assert_eq!(parse_style_transform("translate(-50%, 10px) rotate(90deg) scale(2)"), Ok(StyleTransform(vec![
+            Translate(PixelValue::percent(-50.0), PixelValue::px(10.0)),
+            Rotate(FloatValue::new(90.0)),
+            Scale(FloatValue::new(2.0), FloatValue::new(2.0)),
+        ])));
+        assert_eq!(parse_style_transform("scaleY(0.5) skewX(100grad)"), Ok(StyleTransform(vec![
+            Scale(FloatValue::new(1.0), FloatValue::new(0.5)),
+            Skew(FloatValue::new(90.0), FloatValue::new(0.0)),
+        ])));
+        assert_eq!(parse_style_transform("rotate(10)"), Err(CssTransformParseError::InvalidAngle("10")));
+        assert_eq!(parse_style_transform("rotate(1deg, 2deg)"), Err(CssTransformParseError::WrongNumberOfArguments("rotate(1deg, 2deg)")));
+        assert_eq!(parse_style_transform("matrix(1, 0, 0, 1, 0, 0)"), Err(CssTransformParseError::InvalidTransformFunction("matrix(1, 0, 0, 1, 0, 0)")));
+    }
+
+    #[test]
+    fn test_parse_style_transform_origin() {
+        assert_eq!(parse_style_transform_origin("left"), Ok(StyleTransformOrigin { x: PixelValue::percent(0.0), y: PixelValue::percent(50.0) }));
+        assert_eq!(parse_style_transform_origin("bottom"), Ok(StyleTransformOrigin { x: PixelValue::percent(50.0), y: PixelValue::percent(100.0) }));
+        assert_eq!(parse_style_transform_origin("20px 30%"), Ok(StyleTransformOrigin { x: PixelValue::px(20.0), y: PixelValue::percent(30.0) }));
+        assert_eq!(parse_style_transform_origin("top top"), Err(CssTransformParseError::InvalidTransformOrigin("top top")));
+        assert_eq!(parse_style_transform_origin("bottom 10px"), Err(CssTransformParseError::InvalidTransformOrigin("bottom 10px")));
+    }
+
+    #[test]
+    fn test_parse_opacity_and_z_index() {
+        assert_eq!(parse_key_value_pair(CssPropertyType::Opacity, "0.75"), Ok(CssProperty::Opacity(StyleOpacity(FloatValue::new(0.75)))));
+        assert_eq!(parse_style_opacity("-1"), Ok(StyleOpacity(FloatValue::new(0.0))));
+        assert!(parse_style_opacity("half").is_err());
+        assert_eq!(parse_key_value_pair(CssPropertyType::ZIndex, "-3"), Ok(CssProperty::ZIndex(StyleZIndex(-3))));
+        assert_eq!(parse_style_z_index("auto"), Ok(StyleZIndex(0)));
+        assert_eq!(parse_style_z_index("1.5"), Err(InvalidValueErr("1.5")));
+    }
+
+    #[test]
+    fn test_parse_grid_properties() {
+        assert_eq!(parse_key_value_pair(CssPropertyType::Display, "grid"), Ok(CssProperty::Display(LayoutDisplay::Grid)));
+        assert_eq!(parse_layout_grid_template("repeat(2, 50px auto) 2.5fr"), Ok(LayoutGridTemplate(vec![
+            GridTrackSize::Fixed(PixelValue::px(50.0)),
+            GridTrackSize::Auto,
+            GridTrackSize::Fixed(PixelValue::px(50.0)),
+            GridTrackSize::Auto,
+            GridTrackSize::Fraction(FloatValue::new(2.5)),
+        ])));
+        assert_eq!(parse_layout_grid_template("repeat(0, 1fr)"), Err(CssGridParseError::InvalidRepeat("repeat(0, 1fr)")));
+        assert_eq!(parse_layout_grid_template("1fr 1fx"), Err(CssGridParseError::InvalidTrackSize("1fx")));
+        assert_eq!(parse_layout_grid_gap("10px"), Ok(LayoutGridGap { row: PixelValue::px(10.0), column: PixelValue::px(10.0) }));
+        assert_eq!(parse_layout_grid_gap("10px 5%"), Ok(LayoutGridGap { row: PixelValue::px(10.0), column: PixelValue::percent(5.0) }));
+        assert_eq!(parse_layout_grid_gap("1px 2px 3px"), Err(CssGridParseError::TooManyValues("1px 2px 3px")));
+        assert_eq!(parse_layout_grid_placement("span 2"), Ok(LayoutGridPlacement { start: GridLine::Span(2), end: GridLine::Auto }));
+        assert_eq!(parse_layout_grid_placement("1 / -1"), Ok(LayoutGridPlacement { start: GridLine::Line(1), end: GridLine::Line(-1) }));
+        assert_eq!(parse_layout_grid_placement("0"), Err(CssGridParseError::InvalidGridLine("0")));
+        assert_eq!(parse_layout_grid_placement("1 / 2 / 3"), Err(CssGridParseError::TooManyValues("1 / 2 / 3")));
+    }
+
+    #[test]
+    fn test_parse_flex_properties() {
+        assert_eq!(parse_layout_flex_basis("auto"), Ok(LayoutFlexBasis::Auto));
+        assert_eq!(parse_layout_flex_basis("50%"), Ok(LayoutFlexBasis::Exact(PixelValue::percent(50.0))));
+        assert_eq!(parse_key_value_pair(CssPropertyType::FlexBasis, "100px"), Ok(CssProperty::FlexBasis(LayoutFlexBasis::Exact(PixelValue::px(100.0)))));
+        assert_eq!(parse_layout_order("-1"), Ok(LayoutOrder(-1)));
+        assert_eq!(parse_layout_order("first"), Err(InvalidValueErr("first")));
+        assert_eq!(parse_layout_align_self("auto"), Ok(LayoutAlignSelf::Auto));
+        assert_eq!(parse_key_value_pair(CssPropertyType::AlignSelf, "flex-end"), Ok(CssProperty::AlignSelf(LayoutAlignSelf::End)));
+        assert_eq!(parse_key_value_pair(CssPropertyType::RowGap, "1em"), Ok(CssProperty::RowGap(LayoutRowGap::em(1.0))));
+        assert_eq!(parse_key_value_pair(CssPropertyType::ColumnGap, "10px"), Ok(CssProperty::ColumnGap(LayoutColumnGap::px(10.0))));
+    }
+
+    #[test]
+    fn test_parse_display_visibility_and_box_sizing() {
+        assert_eq!(parse_key_value_pair(CssPropertyType::Display, "none"), Ok(CssProperty::Display(LayoutDisplay::None)));
+        assert_eq!(parse_key_value_pair(CssPropertyType::Visibility, "hidden"), Ok(CssProperty::Visibility(StyleVisibility::Hidden)));
+        assert_eq!(parse_style_visibility("collapse"), Err(InvalidValueErr("collapse")));
+        assert_eq!(parse_layout_box_sizing("content-box"), Ok(LayoutBoxSizing::ContentBox));
+        assert_eq!(parse_key_value_pair(CssPropertyType::BoxSizing, "border-box"), Ok(CssProperty::BoxSizing(LayoutBoxSizing::BorderBox)));
+    }
+
+    #[test]
+    fn test_parse_intrinsic_width() {
+        assert_eq!(parse_key_value_pair(CssPropertyType::Width, "min-content"), Ok(CssProperty::IntrinsicWidth(LayoutIntrinsicWidth::MinContent)));
+        assert_eq!(parse_key_value_pair(CssPropertyType::Width, "fit-content"), Ok(CssProperty::IntrinsicWidth(LayoutIntrinsicWidth::FitContent)));
+        assert_eq!(parse_key_value_pair(CssPropertyType::Width, "20px"), Ok(CssProperty::Width(LayoutWidth::px(20.0))));
+        assert_eq!(CssProperty::IntrinsicWidth(LayoutIntrinsicWidth::MaxContent).get_type(), CssPropertyType::Width);
+    }
+
+    #[test]
+    fn test_parse_layout_position() {
+        assert_eq!(parse_layout_position("fixed"), Ok(LayoutPosition::Fixed));
+        assert_eq!(parse_key_value_pair(CssPropertyType::Position, "absolute"), Ok(CssProperty::Position(LayoutPosition::Absolute)));
+        assert_eq!(parse_layout_position("sticky"), Err(InvalidValueErr("sticky")));
+    }
 }
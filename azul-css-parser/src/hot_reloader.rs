@@ -12,31 +12,44 @@ pub const DEFAULT_RELOAD_INTERVAL: Duration = Duration::from_millis(500);
 pub struct HotReloader {
     file_path: PathBuf,
     reload_interval: Duration,
+    /// The CSS file and all files imported by it during the last successful reload
+    watched_files: Vec<PathBuf>,
 }
 
 impl HotReloader {
     /// Creates a HotReloader that will load a style directly from the CSS file
-    /// at the given path.
+    /// at the given path. Files imported via `@import` are reloaded as well.
     pub fn new<P: Into<PathBuf>>(file_path: P) -> Self {
-        Self { file_path: file_path.into(), reload_interval: DEFAULT_RELOAD_INTERVAL }
+        Self { file_path: file_path.into(), reload_interval: DEFAULT_RELOAD_INTERVAL, watched_files: Vec::new() }
     }
 
     pub fn with_reload_interval(self, reload_interval: Duration) -> Self {
         Self { reload_interval, .. self }
     }
+
+    /// Returns the CSS file and all files that it imports (directly or indirectly),
+    /// empty if the style was never successfully loaded
+    pub fn get_watched_files(&self) -> &[PathBuf] {
+        &self.watched_files
+    }
 }
 
 impl HotReloadHandler for HotReloader {
     fn reload_style(&mut self) -> Result<Css, String> {
-        use std::fs;
         use crate::css;
 
-        let file_name = self.file_path.file_name().map(|os_str| os_str.to_string_lossy()).unwrap_or_default();
+        // Every reload resolves the imports again, so changes to imported files are picked up, too
+        let (css, watched_files) = css::new_from_file_with_imports(&self.file_path).map_err(|e| e.to_string())?;
+        self.watched_files = watched_files;
+        Ok(css)
+    }
 
-        let reloaded_css = fs::read_to_string(&self.file_path)
-            .map_err(|e| format!("Io error: Could not load \"{}\" when loading file: \"{}\"", file_name, e))?;
+    fn reload_style_lenient(&mut self) -> Result<(Css, Vec<String>), String> {
+        use crate::css;
 
-        css::new_from_str(&reloaded_css).map_err(|e| format!("{}: {}", file_name, e))
+        let loaded = css::new_from_file_lenient(&self.file_path).map_err(|e| e.to_string())?;
+        self.watched_files = loaded.loaded_files;
+        Ok((loaded.css, loaded.errors.iter().map(|e| e.to_string()).collect()))
     }
 
     fn get_reload_interval(&self) -> Duration {
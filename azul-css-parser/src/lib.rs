@@ -25,9 +25,22 @@ mod hot_reloader;
 
 pub use crate::css::{
     new_from_str,
+    new_from_str_lenient,
+    new_from_file,
+    new_from_file_with_imports,
+    new_from_file_lenient,
     parse_css_path,
+    parse_css_variable,
+    parse_css_var_reference,
+    contains_var_function,
+    substitute_css_variables,
     CssParseError,
+    CssFileError,
+    LenientCssFile,
     CssPathParseError,
+    CssVarParseError,
+    CssKeyframesParseError,
+    CssFontFaceParseError,
 };
 
 pub use crate::css_parser::*;
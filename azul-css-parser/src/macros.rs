@@ -2,15 +2,34 @@
 ///
 /// Example usage:
 /// ```
+/// # macro_rules! impl_display {
+/// #     ($enum:ident<$lt:lifetime>, {$($variant:pat => $fmt_string:expr),+$(,)* }) => {
+/// #         impl<$lt> ::std::fmt::Display for $enum<$lt> {
+/// #             fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+/// #                 use self::$enum::*;
+/// #                 match &self {
+/// #                     $(
+/// #                         $variant => write!(f, "{}", $fmt_string),
+/// #                     )+
+/// #                 }
+/// #             }
+/// #         }
+/// #     };
+/// # }
 /// enum Foo<'a> {
-///     Bar(&'a str)
-///     Baz(i32)
+///     Bar(&'a str),
+///     Baz(i32),
 /// }
 ///
 /// impl_display!{ Foo<'a>, {
 ///     Bar(s) => s,
 ///     Baz(i) => format!("{}", i)
 /// }}
+///
+/// # fn main() {
+/// assert_eq!(Foo::Bar("bar").to_string(), "bar");
+/// assert_eq!(Foo::Baz(5).to_string(), "5");
+/// # }
 /// ```
 macro_rules! impl_display {
     // For a type with a lifetime
@@ -74,14 +93,28 @@ macro_rules! impl_debug_as_display {
 /// Implement the `From` trait for any type.
 /// Example usage:
 /// ```
+/// # macro_rules! impl_from {
+/// #     ($a:ident<$c:lifetime>, $b:ident::$enum_type:ident) => {
+/// #         impl<$c> From<$a<$c>> for $b<$c> {
+/// #             fn from(e: $a<$c>) -> Self {
+/// #                 $b::$enum_type(e)
+/// #             }
+/// #         }
+/// #     };
+/// # }
+/// struct BarError<'a>(&'a str);
+/// struct FooError<'a>(&'a str);
+///
 /// enum MyError<'a> {
-///     Bar(BarError<'a>)
-///     Foo(FooError<'a>)
+///     Bar(BarError<'a>),
+///     Foo(FooError<'a>),
 /// }
 ///
-/// impl_from!(BarError<'a>, Error::Bar);
-/// impl_from!(BarError<'a>, Error::Bar);
+/// impl_from!(BarError<'a>, MyError::Bar);
+/// impl_from!(FooError<'a>, MyError::Foo);
 ///
+/// let error: MyError = FooError("foo").into();
+/// assert!(match error { MyError::Foo(FooError(s)) => s == "foo", _ => false });
 /// ```
 macro_rules! impl_from {
     // From a type with a lifetime to a type which also has a lifetime
@@ -1,6 +1,9 @@
 //! Types and methods used to describe the style of an application
 use crate::css_properties::{CssProperty, CssPropertyType};
-use std::fmt;
+use crate::css_animations::{CssTransition, CssAnimation, CssKeyframes};
+use crate::css_media::{CssMediaRule, CssMediaInfo};
+use crate::css_font_face::CssFontFace;
+use std::{fmt, borrow::Cow, path::PathBuf};
 
 /// Css stylesheet - contains a parsed CSS stylesheet in "rule blocks",
 /// i.e. blocks of key-value pairs associated with a selector path.
@@ -16,11 +19,49 @@ pub struct Css {
 pub struct Stylesheet {
     /// The style rules making up the document - for example, de-duplicated CSS rules
     pub rules: Vec<CssRuleBlock>,
+    /// The `@keyframes` rules of the stylesheet, referenced by the `animation` property
+    pub keyframes: Vec<CssKeyframes>,
+    /// The `@media` rules of the stylesheet, which only apply if the window matches their query
+    pub media_rules: Vec<CssMediaRule>,
+    /// The `@font-face` rules of the stylesheet, which add font files to a font family
+    pub font_faces: Vec<CssFontFace>,
 }
 
 impl From<Vec<CssRuleBlock>> for Stylesheet {
     fn from(rules: Vec<CssRuleBlock>) -> Self {
-        Self { rules }
+        Self { rules, keyframes: Vec::new(), media_rules: Vec::new(), font_faces: Vec::new() }
+    }
+}
+
+/// Writes all stylesheets as CSS text, which can be parsed again with `azul_css_parser::new_from_str`
+impl fmt::Display for Css {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, stylesheet) in self.stylesheets.iter().enumerate() {
+            if idx != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", stylesheet)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the `@font-face` rules, the rules, the `@keyframes` and the `@media` rules
+/// of the stylesheet, separated by empty lines
+impl fmt::Display for Stylesheet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let blocks = self.font_faces.iter().map(|font_face| font_face.to_string())
+            .chain(self.rules.iter().map(|rule| rule.to_string()))
+            .chain(self.keyframes.iter().map(|keyframes| keyframes.to_string()))
+            .chain(self.media_rules.iter().map(|media_rule| media_rule.to_string()));
+
+        for (idx, block) in blocks.enumerate() {
+            if idx != 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}", block)?;
+        }
+        Ok(())
     }
 }
 
@@ -31,16 +72,30 @@ pub enum CssDeclaration {
     Static(CssProperty),
     /// Dynamic key-value pair with default value, such as `width: [[ my_id | 500px ]]`
     Dynamic(DynamicCssProperty),
+    /// Custom property (CSS variable), such as `--main-color: #ff0000`
+    Variable(CssVariable),
+    /// Key-value pair that references a CSS variable, such as `color: var(--main-color, black)`
+    VarReference(CssVarReference),
+    /// Transitions of the node, such as `transition: background 200ms ease-in, width 1s`
+    Transition(Vec<CssTransition>),
+    /// Keyframe animations of the node, such as `animation: fade-in 1s infinite alternate`
+    Animation(Vec<CssAnimation>),
 }
 
 impl CssDeclaration {
 
     /// Returns the type of the property (i.e. the CSS key as a typed enum)
-    pub fn get_type(&self) -> CssPropertyType {
+    ///
+    /// Returns `None` for custom properties (`--main-color: #ff0000`), transitions
+    /// and animations, since they don't set the value of a known CSS key
+    pub fn get_type(&self) -> Option<CssPropertyType> {
         use css::CssDeclaration::*;
         match self {
-            Static(s) => s.get_type(),
-            Dynamic(d) => d.property_type,
+            Static(s) => Some(s.get_type()),
+            Dynamic(d) => Some(d.property_type),
+            Variable(_) => None,
+            VarReference(v) => Some(v.property_type),
+            Transition(_) | Animation(_) => None,
         }
     }
 
@@ -51,6 +106,10 @@ impl CssDeclaration {
         match self {
             Static(s) => s.get_type().is_inheritable(),
             Dynamic(d) => d.is_inheritable(),
+            // Custom properties are always inherited
+            Variable(_) => true,
+            VarReference(v) => v.property_type.is_inheritable(),
+            Transition(_) | Animation(_) => false,
         }
     }
 
@@ -61,10 +120,75 @@ impl CssDeclaration {
         match self {
             Static(s) => s.get_type().can_trigger_relayout(),
             Dynamic(d) => d.can_trigger_relayout(),
+            // The variable could be used by any property, including layout properties
+            Variable(_) => true,
+            VarReference(v) => v.property_type.can_trigger_relayout(),
+            // Transitions only change the value of other properties
+            Transition(_) => false,
+            // The keyframes could contain any property, including layout properties
+            Animation(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for CssDeclaration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssDeclaration::*;
+        match self {
+            Static(s) => write!(f, "{}", s),
+            Dynamic(d) => write!(f, "{}", d),
+            Variable(v) => write!(f, "{}: {}", v.name, v.value),
+            VarReference(v) => write!(f, "{}: {}", v.property_type, v.value),
+            Transition(transitions) => {
+                write!(f, "transition: ")?;
+                fmt_comma_separated(f, transitions)
+            },
+            Animation(animations) => {
+                write!(f, "animation: ")?;
+                fmt_comma_separated(f, animations)
+            },
         }
     }
 }
 
+fn fmt_comma_separated<T: fmt::Display>(f: &mut fmt::Formatter, items: &[T]) -> fmt::Result {
+    for (idx, item) in items.iter().enumerate() {
+        if idx != 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// Custom property, i.e. `--main-color: #ff0000`. Custom properties are inherited
+/// by all children of the node they are set on and can be referenced by other
+/// properties via `var(--main-color)`.
+///
+/// The value is stored as an unparsed string, since the type of the value is only
+/// known once the variable is used by a property.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CssVariable {
+    /// Name of the variable, including the leading `--`, i.e. `"--main-color"`
+    pub name: String,
+    /// Unparsed value of the variable, i.e. `"#ff0000"`
+    pub value: String,
+}
+
+/// Property whose value contains one or more `var()` functions, such as
+/// `border: 1px solid var(--main-color, black)`.
+///
+/// Since the value of a variable depends on where in the DOM the property is applied,
+/// the value can only be parsed after the `var()` functions have been substituted
+/// during the cascade.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CssVarReference {
+    /// Key for this property
+    pub property_type: CssPropertyType,
+    /// Unparsed value, i.e. `"1px solid var(--main-color, black)"`
+    pub value: String,
+}
+
 /// A `DynamicCssProperty` is a type of css property that can be changed on possibly
 /// every frame by the Rust code - for example to implement an `On::Hover` behaviour.
 ///
@@ -125,15 +249,67 @@ impl DynamicCssProperty {
     }
 }
 
+/// Writes the property as `key: [[ dynamic_id | default ]]`
+impl fmt::Display for DynamicCssProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.default {
+            DynamicCssPropertyDefault::Exact(property) => {
+                write!(f, "{}: [[ {} | {} ]]", self.property_type, self.dynamic_id, property.format_value(self.property_type))
+            },
+            DynamicCssPropertyDefault::Auto => {
+                write!(f, "{}: [[ {} | auto ]]", self.property_type, self.dynamic_id)
+            },
+        }
+    }
+}
+
 /// One block of rules that applies a bunch of rules to a "path" in the style, i.e.
 /// `div#myid.myclass -> { ("justify-content", "center") }`
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct CssRuleBlock {
     /// The css path (full selector) of the style ruleset
     pub path: CssPath,
     /// `"justify-content: center"` =>
     /// `CssDeclaration::Static(CssProperty::JustifyContent(LayoutJustifyContent::Center))`
     pub declarations: Vec<CssDeclaration>,
+    /// Where the rule was defined, `None` if the rule wasn't parsed from a CSS file
+    pub location: Option<CssSourceLocation>,
+}
+
+// The location is ignored when comparing rules, otherwise moving a rule
+// to a different line (or file) would force a restyle of the whole DOM
+impl PartialEq for CssRuleBlock {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path && self.declarations == other.declarations
+    }
+}
+
+impl fmt::Display for CssRuleBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} {{", self.path)?;
+        for declaration in &self.declarations {
+            writeln!(f, "    {};", declaration)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// File and line that a `CssRuleBlock` was defined at
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CssSourceLocation {
+    /// The file that contains the rule, `None` if the CSS was parsed from a string
+    pub file: Option<PathBuf>,
+    /// Line of the rule in the file, starting at 1
+    pub line: usize,
+}
+
+impl fmt::Display for CssSourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}", file.display(), self.line),
+            None => write!(f, "line {}", self.line),
+        }
+    }
 }
 
 pub type CssContentGroup<'a> = Vec<&'a CssPathSelector>;
@@ -233,12 +409,18 @@ pub enum CssPathSelector {
     Class(String),
     /// `#something`
     Id(String),
+    /// `[something]`, `[something="value"]`
+    Attribute(CssAttributeSelector),
     /// `:something`
     PseudoSelector(CssPathPseudoSelector),
     /// Represents the `>` selector
     DirectChildren,
     /// Represents the ` ` selector
     Children,
+    /// Represents the `+` selector
+    AdjacentSibling,
+    /// Represents the `~` selector
+    GeneralSibling,
 }
 
 impl Default for CssPathSelector {
@@ -255,14 +437,79 @@ impl fmt::Display for CssPathSelector {
             Type(n) => write!(f, "{}", n),
             Class(c) => write!(f, ".{}", c),
             Id(i) => write!(f, "#{}", i),
+            Attribute(a) => write!(f, "{}", a),
             PseudoSelector(p) => write!(f, ":{}", p),
             DirectChildren => write!(f, ">"),
             Children => write!(f, " "),
+            AdjacentSibling => write!(f, "+"),
+            GeneralSibling => write!(f, "~"),
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// Selects nodes by the attributes set via `Dom::with_attribute`, i.e. `[data-state="open"]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CssAttributeSelector {
+    /// Name of the attribute, i.e. `data-state`
+    pub name: String,
+    /// What the value of the attribute has to look like
+    pub condition: CssAttributeCondition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CssAttributeCondition {
+    /// `[attr]` - the attribute is set, regardless of its value
+    Exists,
+    /// `[attr=value]` - the value is exactly `value`
+    Equals(String),
+    /// `[attr~=value]` - the value is a whitespace-separated list of words, one of which is `value`
+    ContainsWord(String),
+    /// `[attr|=value]` - the value is either exactly `value` or starts with `value-`
+    DashMatch(String),
+    /// `[attr^=value]` - the value starts with `value`
+    StartsWith(String),
+    /// `[attr$=value]` - the value ends with `value`
+    EndsWith(String),
+    /// `[attr*=value]` - the value contains `value`
+    Contains(String),
+}
+
+impl CssAttributeSelector {
+    /// Returns whether an attribute with the given value (`None` if the attribute isn't set) is selected
+    pub fn matches(&self, value: Option<&str>) -> bool {
+        use self::CssAttributeCondition::*;
+        let value = match value {
+            Some(s) => s,
+            None => return false,
+        };
+        match &self.condition {
+            Exists => true,
+            Equals(v) => value == v,
+            ContainsWord(v) => value.split_whitespace().any(|word| word == v),
+            DashMatch(v) => value == v || (value.starts_with(v.as_str()) && value[v.len()..].starts_with('-')),
+            StartsWith(v) => !v.is_empty() && value.starts_with(v.as_str()),
+            EndsWith(v) => !v.is_empty() && value.ends_with(v.as_str()),
+            Contains(v) => !v.is_empty() && value.contains(v.as_str()),
+        }
+    }
+}
+
+impl fmt::Display for CssAttributeSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssAttributeCondition::*;
+        match &self.condition {
+            Exists => write!(f, "[{}]", self.name),
+            Equals(v) => write!(f, "[{}=\"{}\"]", self.name, v),
+            ContainsWord(v) => write!(f, "[{}~=\"{}\"]", self.name, v),
+            DashMatch(v) => write!(f, "[{}|=\"{}\"]", self.name, v),
+            StartsWith(v) => write!(f, "[{}^=\"{}\"]", self.name, v),
+            EndsWith(v) => write!(f, "[{}$=\"{}\"]", self.name, v),
+            Contains(v) => write!(f, "[{}*=\"{}\"]", self.name, v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum CssPathPseudoSelector {
     /// `:first`
     First,
@@ -270,12 +517,24 @@ pub enum CssPathPseudoSelector {
     Last,
     /// `:nth-child`
     NthChild(CssNthChildSelector),
+    /// `:nth-last-child` - same as `:nth-child`, but counts from the last child
+    NthLastChild(CssNthChildSelector),
+    /// `:only-child` - element is the only child of its parent
+    OnlyChild,
+    /// `:empty` - element has no children
+    Empty,
+    /// `:not(.something)` - element doesn't match the (compound) selector in the braces
+    Not(Vec<CssPathSelector>),
     /// `:hover` - mouse is over element
     Hover,
     /// `:active` - mouse is pressed and over element
     Active,
     /// `:focus` - element has received focus
     Focus,
+    /// `:dragging` - element is currently being dragged
+    Dragging,
+    /// `:drop-target` - an element is being dragged and this element accepts drops
+    DropTarget,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -305,9 +564,21 @@ impl fmt::Display for CssPathPseudoSelector {
             First => write!(f, "first"),
             Last => write!(f, "last"),
             NthChild(u) => write!(f, "nth-child({})", u),
+            NthLastChild(u) => write!(f, "nth-last-child({})", u),
+            OnlyChild => write!(f, "only-child"),
+            Empty => write!(f, "empty"),
+            Not(selectors) => {
+                write!(f, "not(")?;
+                for selector in selectors {
+                    write!(f, "{}", selector)?;
+                }
+                write!(f, ")")
+            },
             Hover => write!(f, "hover"),
             Active => write!(f, "active"),
             Focus => write!(f, "focus"),
+            Dragging => write!(f, "dragging"),
+            DropTarget => write!(f, "drop-target"),
         }
     }
 }
@@ -335,6 +606,40 @@ impl Css {
         }
     }
 
+    /// Returns the `@keyframes` rule with the given name - if multiple stylesheets
+    /// define the same keyframes, the last stylesheet takes precedence
+    pub fn get_keyframes(&self, name: &str) -> Option<&CssKeyframes> {
+        self.stylesheets.iter().rev()
+            .flat_map(|stylesheet| stylesheet.keyframes.iter().rev())
+            .find(|keyframes| keyframes.name == name)
+    }
+
+    /// Returns the `@font-face` rules of all stylesheets, in the order of the stylesheets
+    pub fn get_font_faces(&self) -> impl Iterator<Item = &CssFontFace> {
+        self.stylesheets.iter().flat_map(|stylesheet| stylesheet.font_faces.iter())
+    }
+
+    /// Returns the CSS with the rules of all `@media` blocks that match the window merged into the
+    /// regular rules of their stylesheet. The CSS is only copied if it contains any `@media` blocks.
+    pub fn resolve_media_queries<'a>(&'a self, media: &CssMediaInfo) -> Cow<'a, Css> {
+        if self.stylesheets.iter().all(|stylesheet| stylesheet.media_rules.is_empty()) {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(Css {
+                stylesheets: self.stylesheets.iter().map(|stylesheet| stylesheet.resolve_media_queries(media)).collect(),
+            })
+        }
+    }
+
+    /// Returns whether each `@media` block of the stylesheets matches the window - the result
+    /// of `resolve_media_queries` only changes if the returned list changes
+    pub fn get_matching_media_rules(&self, media: &CssMediaInfo) -> Vec<bool> {
+        self.stylesheets.iter()
+            .flat_map(|stylesheet| stylesheet.media_rules.iter())
+            .map(|media_rule| media_rule.matches(media))
+            .collect()
+    }
+
     pub fn rules<'a>(&'a self) -> RuleIterator<'a> {
         RuleIterator {
             current_stylesheet: 0,
@@ -381,17 +686,50 @@ impl Stylesheet {
     pub fn sort_by_specificity(&mut self) {
         self.rules.sort_by(|a, b| get_specificity(&a.path).cmp(&get_specificity(&b.path)));
     }
+
+    /// Returns a stylesheet without `@media` rules, which contains the regular rules and the rules
+    /// of all `@media` blocks that match the window, sorted by specificity. Since the sort is stable,
+    /// rules inside of `@media` blocks override regular rules with the same specificity.
+    pub fn resolve_media_queries(&self, media: &CssMediaInfo) -> Self {
+        let mut resolved = Stylesheet {
+            rules: self.rules.clone(),
+            keyframes: self.keyframes.clone(),
+            media_rules: Vec::new(),
+            font_faces: self.font_faces.clone(),
+        };
+
+        for media_rule in self.media_rules.iter().filter(|media_rule| media_rule.matches(media)) {
+            resolved.rules.extend(media_rule.rules.iter().cloned());
+        }
+
+        resolved.sort_by_specificity();
+        resolved
+    }
 }
 
 /// Returns specificity of the given css path. Further information can be found on
 /// [the w3 website](http://www.w3.org/TR/selectors/#specificity).
 fn get_specificity(path: &CssPath) -> (usize, usize, usize, usize) {
-    let id_count = path.selectors.iter().filter(|x|     if let CssPathSelector::Id(_) = x {     true } else { false }).count();
-    let class_count = path.selectors.iter().filter(|x|  if let CssPathSelector::Class(_) = x {  true } else { false }).count();
-    let div_count = path.selectors.iter().filter(|x|    if let CssPathSelector::Type(_) = x {   true } else { false }).count();
+    let (id_count, class_count, div_count) = count_selectors(&path.selectors);
     (id_count, class_count, div_count, path.selectors.len())
 }
 
+/// Counts the IDs, classes (and attributes) and node types of the selectors -
+/// the selectors inside of a `:not()` count the same as the selectors outside of it
+fn count_selectors(selectors: &[CssPathSelector]) -> (usize, usize, usize) {
+    use self::CssPathSelector::*;
+    selectors.iter().fold((0, 0, 0), |(id_count, class_count, div_count), selector| match selector {
+        Id(_) => (id_count + 1, class_count, div_count),
+        Class(_) | Attribute(_) => (id_count, class_count + 1, div_count),
+        Type(_) => (id_count, class_count, div_count + 1),
+        PseudoSelector(CssPathPseudoSelector::Not(inner)) => {
+            let (inner_ids, inner_classes, inner_divs) = count_selectors(inner);
+            (id_count + inner_ids, class_count + inner_classes, div_count + inner_divs)
+        },
+        _ => (id_count, class_count, div_count),
+    })
+}
+
 #[test]
 fn test_specificity() {
     use self::CssPathSelector::*;
@@ -399,6 +737,28 @@ fn test_specificity() {
     assert_eq!(get_specificity(&CssPath { selectors: vec![Class("hello".into())] }), (0, 1, 0, 1));
     assert_eq!(get_specificity(&CssPath { selectors: vec![Type(NodeTypePath::Div)] }), (0, 0, 1, 1));
     assert_eq!(get_specificity(&CssPath { selectors: vec![Id("hello".into()), Type(NodeTypePath::Div)] }), (1, 0, 1, 2));
+    assert_eq!(get_specificity(&CssPath { selectors: vec![
+        Attribute(CssAttributeSelector { name: "data-state".into(), condition: CssAttributeCondition::Exists }),
+        PseudoSelector(CssPathPseudoSelector::Not(vec![Id("hello".into())])),
+    ] }), (1, 1, 0, 2));
+}
+
+#[test]
+fn test_attribute_selector_matches() {
+    use self::CssAttributeCondition::*;
+    let selector = |condition| CssAttributeSelector { name: "data-state".into(), condition };
+    assert!(selector(Exists).matches(Some("")));
+    assert!(!selector(Exists).matches(None));
+    assert!(selector(Equals("open".into())).matches(Some("open")));
+    assert!(!selector(Equals("open".into())).matches(Some("opened")));
+    assert!(selector(ContainsWord("b".into())).matches(Some("a b c")));
+    assert!(!selector(ContainsWord("b".into())).matches(Some("abc")));
+    assert!(selector(DashMatch("en".into())).matches(Some("en-US")));
+    assert!(!selector(DashMatch("en".into())).matches(Some("english")));
+    assert!(selector(StartsWith("op".into())).matches(Some("open")));
+    assert!(selector(EndsWith("en".into())).matches(Some("open")));
+    assert!(selector(Contains("pe".into())).matches(Some("open")));
+    assert!(!selector(Contains("".into())).matches(Some("open")));
 }
 
 // Assert that order of the style items is correct (in order of CSS path specificity, lowest-to-highest)
@@ -410,12 +770,15 @@ fn test_specificity_sort() {
     let mut input_style = Stylesheet {
         rules: vec![
             // Rules are sorted from lowest-specificity to highest specificity
-            CssRuleBlock { path: CssPath { selectors: vec![Global] }, declarations: Vec::new() },
-            CssRuleBlock { path: CssPath { selectors: vec![Global, Type(Div), Class("my_class".into()), Id("my_id".into())] }, declarations: Vec::new() },
-            CssRuleBlock { path: CssPath { selectors: vec![Global, Type(Div), Id("my_id".into())] }, declarations: Vec::new() },
-            CssRuleBlock { path: CssPath { selectors: vec![Global, Id("my_id".into())] }, declarations: Vec::new() },
-            CssRuleBlock { path: CssPath { selectors: vec![Type(Div), Class("my_class".into()), Class("specific".into()), Id("my_id".into())] }, declarations: Vec::new() },
+            CssRuleBlock { path: CssPath { selectors: vec![Global] }, declarations: Vec::new(), location: None },
+            CssRuleBlock { path: CssPath { selectors: vec![Global, Type(Div), Class("my_class".into()), Id("my_id".into())] }, declarations: Vec::new(), location: None },
+            CssRuleBlock { path: CssPath { selectors: vec![Global, Type(Div), Id("my_id".into())] }, declarations: Vec::new(), location: None },
+            CssRuleBlock { path: CssPath { selectors: vec![Global, Id("my_id".into())] }, declarations: Vec::new(), location: None },
+            CssRuleBlock { path: CssPath { selectors: vec![Type(Div), Class("my_class".into()), Class("specific".into()), Id("my_id".into())] }, declarations: Vec::new(), location: None },
         ],
+        keyframes: Vec::new(),
+        media_rules: Vec::new(),
+        font_faces: Vec::new(),
     };
 
     input_style.sort_by_specificity();
@@ -423,12 +786,15 @@ fn test_specificity_sort() {
     let expected_style = Stylesheet {
         rules: vec![
             // Rules are sorted from lowest-specificity to highest specificity
-            CssRuleBlock { path: CssPath { selectors: vec![Global] }, declarations: Vec::new() },
-            CssRuleBlock { path: CssPath { selectors: vec![Global, Id("my_id".into())] }, declarations: Vec::new() },
-            CssRuleBlock { path: CssPath { selectors: vec![Global, Type(Div), Id("my_id".into())] }, declarations: Vec::new() },
-            CssRuleBlock { path: CssPath { selectors: vec![Global, Type(Div), Class("my_class".into()), Id("my_id".into())] }, declarations: Vec::new() },
-            CssRuleBlock { path: CssPath { selectors: vec![Type(Div), Class("my_class".into()), Class("specific".into()), Id("my_id".into())] }, declarations: Vec::new() },
+            CssRuleBlock { path: CssPath { selectors: vec![Global] }, declarations: Vec::new(), location: None },
+            CssRuleBlock { path: CssPath { selectors: vec![Global, Id("my_id".into())] }, declarations: Vec::new(), location: None },
+            CssRuleBlock { path: CssPath { selectors: vec![Global, Type(Div), Id("my_id".into())] }, declarations: Vec::new(), location: None },
+            CssRuleBlock { path: CssPath { selectors: vec![Global, Type(Div), Class("my_class".into()), Id("my_id".into())] }, declarations: Vec::new(), location: None },
+            CssRuleBlock { path: CssPath { selectors: vec![Type(Div), Class("my_class".into()), Class("specific".into()), Id("my_id".into())] }, declarations: Vec::new(), location: None },
         ],
+        keyframes: Vec::new(),
+        media_rules: Vec::new(),
+        font_faces: Vec::new(),
     };
 
     assert_eq!(input_style, expected_style);
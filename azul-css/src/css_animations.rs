@@ -0,0 +1,745 @@
+//! Types and methods used to describe CSS transitions (`transition: background 200ms ease-in`)
+//! and keyframe animations (`@keyframes` + `animation: fade-in 1s`)
+
+use std::{fmt, collections::BTreeSet, time::Duration};
+use crate::css_properties::*;
+
+/// Number of bisection steps used to solve a `cubic-bezier()` timing function
+const CUBIC_BEZIER_ITERATIONS: usize = 32;
+/// Precision with which a `cubic-bezier()` timing function is solved
+const CUBIC_BEZIER_EPSILON: f32 = 0.0001;
+
+/// Describes how the intermediate values of a transition or animation are calculated,
+/// i.e. the `ease-in` in `transition: background 200ms ease-in`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CssTimingFunction {
+    /// `ease`, equal to `cubic-bezier(0.25, 0.1, 0.25, 1.0)`
+    Ease,
+    /// `linear`
+    Linear,
+    /// `ease-in`, equal to `cubic-bezier(0.42, 0.0, 1.0, 1.0)`
+    EaseIn,
+    /// `ease-out`, equal to `cubic-bezier(0.0, 0.0, 0.58, 1.0)`
+    EaseOut,
+    /// `ease-in-out`, equal to `cubic-bezier(0.42, 0.0, 0.58, 1.0)`
+    EaseInOut,
+    /// `step-start` - jumps to the end value immediately
+    StepStart,
+    /// `step-end` - stays at the start value until the end
+    StepEnd,
+    /// `cubic-bezier(x1, y1, x2, y2)`
+    CubicBezier(FloatValue, FloatValue, FloatValue, FloatValue),
+}
+
+impl Default for CssTimingFunction {
+    fn default() -> Self {
+        CssTimingFunction::Ease
+    }
+}
+
+impl CssTimingFunction {
+
+    /// Maps the linear progress `t` (0.0 - 1.0) of a transition
+    /// to the eased progress, i.e. `ease-in` starts slow and ends fast
+    pub fn evaluate(&self, t: f32) -> f32 {
+        use self::CssTimingFunction::*;
+        let t = t.max(0.0).min(1.0);
+        match self {
+            Ease => solve_cubic_bezier(0.25, 0.1, 0.25, 1.0, t),
+            Linear => t,
+            EaseIn => solve_cubic_bezier(0.42, 0.0, 1.0, 1.0, t),
+            EaseOut => solve_cubic_bezier(0.0, 0.0, 0.58, 1.0, t),
+            EaseInOut => solve_cubic_bezier(0.42, 0.0, 0.58, 1.0, t),
+            StepStart => if t > 0.0 { 1.0 } else { 0.0 },
+            StepEnd => if t < 1.0 { 0.0 } else { 1.0 },
+            CubicBezier(x1, y1, x2, y2) => solve_cubic_bezier(x1.get(), y1.get(), x2.get(), y2.get(), t),
+        }
+    }
+}
+
+/// Returns the y value of the cubic bezier curve (with the points `(0, 0)`, `(x1, y1)`,
+/// `(x2, y2)` and `(1, 1)`) at the given x value. Since the x values are restricted to
+/// `0.0 - 1.0`, the curve is monotonic in x, so the x value can be solved via bisection.
+fn solve_cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+
+    fn bezier(p1: f32, p2: f32, s: f32) -> f32 {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * s * p1 + 3.0 * inv * s * s * p2 + s * s * s
+    }
+
+    let mut lower = 0.0;
+    let mut upper = 1.0;
+    let mut s = x;
+
+    for _ in 0..CUBIC_BEZIER_ITERATIONS {
+        let current_x = bezier(x1, x2, s);
+        if (current_x - x).abs() < CUBIC_BEZIER_EPSILON {
+            break;
+        }
+        if current_x < x {
+            lower = s;
+        } else {
+            upper = s;
+        }
+        s = (lower + upper) / 2.0;
+    }
+
+    bezier(y1, y2, s)
+}
+
+/// Returns the duration in seconds (`Duration::as_float_secs` is not yet stabilized)
+fn duration_to_secs(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
+
+/// Writes the duration in milliseconds, i.e. `200ms` (durations are parsed with millisecond precision)
+fn fmt_duration(f: &mut fmt::Formatter, duration: Duration) -> fmt::Result {
+    write!(f, "{}ms", duration.as_secs() * 1000 + u64::from(duration.subsec_millis()))
+}
+
+impl fmt::Display for CssTimingFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssTimingFunction::*;
+        match self {
+            Ease => write!(f, "ease"),
+            Linear => write!(f, "linear"),
+            EaseIn => write!(f, "ease-in"),
+            EaseOut => write!(f, "ease-out"),
+            EaseInOut => write!(f, "ease-in-out"),
+            StepStart => write!(f, "step-start"),
+            StepEnd => write!(f, "step-end"),
+            CubicBezier(x1, y1, x2, y2) => write!(f, "cubic-bezier({}, {}, {}, {})", x1, y1, x2, y2),
+        }
+    }
+}
+
+/// Which properties a transition applies to, i.e. the `background` in `transition: background 200ms`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CssTransitionProperty {
+    /// `all` - all properties that can be interpolated are transitioned
+    All,
+    /// A single property, i.e. `background`
+    Property(CssPropertyType),
+}
+
+/// One parsed transition, i.e. `transition: background 200ms ease-in 50ms`.
+///
+/// The `transition` property can contain multiple, comma-separated transitions, i.e.
+/// `transition: background 200ms, width 1s linear`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CssTransition {
+    /// Property that should be transitioned
+    pub property: CssTransitionProperty,
+    /// How long the transition takes
+    pub duration: Duration,
+    /// How the intermediate values are calculated
+    pub timing_function: CssTimingFunction,
+    /// How long to wait before the transition starts
+    pub delay: Duration,
+}
+
+impl fmt::Display for CssTransitionProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CssTransitionProperty::All => write!(f, "all"),
+            CssTransitionProperty::Property(p) => write!(f, "{}", p),
+        }
+    }
+}
+
+/// Writes the transition as `property duration timing-function delay`
+impl fmt::Display for CssTransition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ", self.property)?;
+        fmt_duration(f, self.duration)?;
+        write!(f, " {} ", self.timing_function)?;
+        fmt_duration(f, self.delay)
+    }
+}
+
+impl CssTransition {
+
+    /// Returns whether this transition applies to a property of the given type
+    /// (the type of the `CssProperty`, not the type of the CSS key)
+    pub fn applies_to(&self, property_type: CssPropertyType) -> bool {
+        match self.property {
+            CssTransitionProperty::All => true,
+            CssTransitionProperty::Property(p) => p.get_shorthand_type() == property_type,
+        }
+    }
+
+    /// Returns the progress of the transition (`0.0 - 1.0`, with the timing function
+    /// already applied) at the given time since the property changed
+    pub fn get_progress(&self, elapsed: Duration) -> f32 {
+        let duration = duration_to_secs(self.duration);
+        let elapsed = duration_to_secs(elapsed) - duration_to_secs(self.delay);
+        if elapsed <= 0.0 {
+            0.0
+        } else if duration <= 0.0 || elapsed >= duration {
+            1.0
+        } else {
+            self.timing_function.evaluate(elapsed / duration)
+        }
+    }
+
+    /// Returns whether the transition has ended at the given time since the property changed
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        elapsed >= self.delay + self.duration
+    }
+}
+
+/// How often an animation is repeated, i.e. the `3` in `animation: fade-in 1s 3`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CssAnimationIterationCount {
+    Count(FloatValue),
+    Infinite,
+}
+
+impl Default for CssAnimationIterationCount {
+    fn default() -> Self {
+        CssAnimationIterationCount::Count(FloatValue::const_new(1))
+    }
+}
+
+impl fmt::Display for CssAnimationIterationCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CssAnimationIterationCount::Count(count) => write!(f, "{}", count),
+            CssAnimationIterationCount::Infinite => write!(f, "infinite"),
+        }
+    }
+}
+
+/// Whether an animation should play forwards, backwards or alternate between the two
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CssAnimationDirection {
+    Normal,
+    Reverse,
+    Alternate,
+    AlternateReverse,
+}
+
+impl Default for CssAnimationDirection {
+    fn default() -> Self {
+        CssAnimationDirection::Normal
+    }
+}
+
+impl_keyword_display!(CssAnimationDirection,
+    [Normal, "normal"],
+    [Reverse, "reverse"],
+    [Alternate, "alternate"],
+    [AlternateReverse, "alternate-reverse"]);
+
+/// Whether the values of the first / last keyframe are applied before / after the animation runs
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CssAnimationFillMode {
+    None,
+    Forwards,
+    Backwards,
+    Both,
+}
+
+impl Default for CssAnimationFillMode {
+    fn default() -> Self {
+        CssAnimationFillMode::None
+    }
+}
+
+impl_keyword_display!(CssAnimationFillMode,
+    [None, "none"],
+    [Forwards, "forwards"],
+    [Backwards, "backwards"],
+    [Both, "both"]);
+
+impl CssAnimationFillMode {
+
+    fn fills_forwards(&self) -> bool {
+        *self == CssAnimationFillMode::Forwards || *self == CssAnimationFillMode::Both
+    }
+
+    fn fills_backwards(&self) -> bool {
+        *self == CssAnimationFillMode::Backwards || *self == CssAnimationFillMode::Both
+    }
+}
+
+/// One parsed animation, i.e. `animation: fade-in 1s ease-in-out 200ms infinite alternate`.
+///
+/// The `animation` property can contain multiple, comma-separated animations.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CssAnimation {
+    /// Name of the `@keyframes` rule that should be played
+    pub name: String,
+    /// Duration of one iteration of the animation
+    pub duration: Duration,
+    /// How the intermediate values between two keyframes are calculated
+    pub timing_function: CssTimingFunction,
+    /// How long to wait before the animation starts
+    pub delay: Duration,
+    /// How often the animation is repeated
+    pub iteration_count: CssAnimationIterationCount,
+    /// Whether the animation plays forwards, backwards or alternates
+    pub direction: CssAnimationDirection,
+    /// Whether the keyframe values are applied before / after the animation runs
+    pub fill_mode: CssAnimationFillMode,
+}
+
+/// Writes the animation as `name duration timing-function delay iteration-count direction fill-mode`
+impl fmt::Display for CssAnimation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ", self.name)?;
+        fmt_duration(f, self.duration)?;
+        write!(f, " {} ", self.timing_function)?;
+        fmt_duration(f, self.delay)?;
+        write!(f, " {} {} {}", self.iteration_count, self.direction, self.fill_mode)
+    }
+}
+
+impl CssAnimation {
+
+    /// Returns the progress of the animation in the keyframes (`0.0 - 1.0`, without
+    /// the timing function applied) at the given time since the animation was started.
+    ///
+    /// Returns `None` if no keyframe values should be applied at that time, i.e. during
+    /// the delay or after the animation has finished (depending on the `fill_mode`).
+    pub fn get_progress(&self, elapsed: Duration) -> Option<f32> {
+
+        let duration = duration_to_secs(self.duration);
+        let elapsed = duration_to_secs(elapsed) - duration_to_secs(self.delay);
+
+        if elapsed < 0.0 {
+            return if self.fill_mode.fills_backwards() { Some(self.get_directed_progress(0, 0.0)) } else { None };
+        }
+
+        if self.is_finished_inner(elapsed) {
+            if !self.fill_mode.fills_forwards() {
+                return None;
+            }
+            // The animation ends in the middle of an iteration if the iteration count is fractional
+            let iterations = match self.iteration_count {
+                CssAnimationIterationCount::Count(c) => c.get().max(0.0),
+                CssAnimationIterationCount::Infinite => 1.0,
+            };
+            let last_iteration = (iterations.ceil() - 1.0).max(0.0);
+            let progress = if iterations <= 0.0 { 0.0 } else { iterations - last_iteration };
+            return Some(self.get_directed_progress(last_iteration as usize, progress));
+        }
+
+        let iteration = (elapsed / duration).floor();
+        Some(self.get_directed_progress(iteration as usize, elapsed / duration - iteration))
+    }
+
+    /// Returns whether the animation has ended at the given time since the animation was started
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        self.is_finished_inner(duration_to_secs(elapsed) - duration_to_secs(self.delay))
+    }
+
+    fn is_finished_inner(&self, elapsed_after_delay: f32) -> bool {
+        let duration = duration_to_secs(self.duration);
+        if duration <= 0.0 {
+            return true;
+        }
+        match self.iteration_count {
+            CssAnimationIterationCount::Count(c) => elapsed_after_delay >= duration * c.get(),
+            CssAnimationIterationCount::Infinite => false,
+        }
+    }
+
+    fn get_directed_progress(&self, iteration: usize, progress: f32) -> f32 {
+        use self::CssAnimationDirection::*;
+        let is_odd_iteration = iteration % 2 == 1;
+        match self.direction {
+            Normal => progress,
+            Reverse => 1.0 - progress,
+            Alternate => if is_odd_iteration { 1.0 - progress } else { progress },
+            AlternateReverse => if is_odd_iteration { progress } else { 1.0 - progress },
+        }
+    }
+}
+
+/// Parsed `@keyframes` rule, i.e.
+///
+/// ```no_run,ignore
+/// @keyframes fade-in {
+///     from { background: #00000000; }
+///     50% { background: #ff000088; }
+///     to { background: #ff0000; }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CssKeyframes {
+    /// Name of the animation, i.e. `fade-in`
+    pub name: String,
+    /// Keyframes, sorted by their offset
+    pub keyframes: Vec<CssKeyframe>,
+}
+
+/// One keyframe of a `@keyframes` rule, i.e. `50% { background: #ff000088; }`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CssKeyframe {
+    /// Offset of the keyframe in the animation (`from` = `0%`, `to` = `100%`)
+    pub offset: PercentageValue,
+    /// The properties that should be applied at this keyframe
+    pub properties: Vec<CssProperty>,
+}
+
+impl fmt::Display for CssKeyframes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "@keyframes {} {{", self.name)?;
+        for keyframe in &self.keyframes {
+            writeln!(f, "    {}", keyframe)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Writes the keyframe as `50% { key: value; }`
+impl fmt::Display for CssKeyframe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {{", self.offset)?;
+        for property in &self.properties {
+            write!(f, " {};", property)?;
+        }
+        write!(f, " }}")
+    }
+}
+
+impl CssKeyframes {
+
+    /// Returns the types of all properties that are changed by this animation
+    pub fn get_animated_properties(&self) -> BTreeSet<CssPropertyType> {
+        self.keyframes.iter().flat_map(|keyframe| keyframe.properties.iter().map(|p| p.get_type())).collect()
+    }
+
+    /// Returns the value of the property at the given `progress` (`0.0 - 1.0`) of the animation.
+    ///
+    /// If there is no keyframe at `0%` or `100%` for the property, the `base_value` (the value
+    /// that the node would have without the animation) is used instead. Values that can't be
+    /// interpolated (i.e. `position`) switch to the next keyframe value at the half-way point.
+    pub fn get_value(
+        &self,
+        property_type: CssPropertyType,
+        progress: f32,
+        timing_function: CssTimingFunction,
+        base_value: Option<&CssProperty>,
+    ) -> Option<CssProperty> {
+
+        let mut frames = self.keyframes.iter().filter_map(|keyframe| {
+            let property = keyframe.properties.iter().rev().find(|p| p.get_type() == property_type)?;
+            Some((keyframe.offset.get() / 100.0, property))
+        }).collect::<Vec<_>>();
+
+        if let Some(base_value) = base_value {
+            if frames.first().map(|(offset, _)| *offset > 0.0).unwrap_or(true) {
+                frames.insert(0, (0.0, base_value));
+            }
+            if frames.last().map(|(offset, _)| *offset < 1.0).unwrap_or(true) {
+                frames.push((1.0, base_value));
+            }
+        }
+
+        let next_frame = frames.iter().position(|(offset, _)| *offset > progress);
+
+        match next_frame {
+            None => frames.last().map(|(_, property)| (*property).clone()),
+            Some(0) => frames.first().map(|(_, property)| (*property).clone()),
+            Some(next) => {
+                let (start, from) = frames[next - 1];
+                let (end, to) = frames[next];
+                let t = timing_function.evaluate((progress - start) / (end - start));
+                from.interpolate(to, t).or_else(|| Some(if t < 0.5 { from.clone() } else { to.clone() }))
+            }
+        }
+    }
+}
+
+fn interpolate_f32(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn interpolate_color(a: ColorU, b: ColorU, t: f32) -> ColorU {
+    let channel = |a: u8, b: u8| interpolate_f32(a as f32, b as f32, t).round().max(0.0).min(255.0) as u8;
+    ColorU {
+        r: channel(a.r, b.r),
+        g: channel(a.g, b.g),
+        b: channel(a.b, b.b),
+        a: channel(a.a, b.a),
+    }
+}
+
+/// Interpolates two lengths with the same metric, a length of zero can be interpolated with
+/// any metric (i.e. `0px` and `50%`). Returns `None` if the lengths have different metrics.
+fn interpolate_pixel_value(a: PixelValue, b: PixelValue, t: f32) -> Option<PixelValue> {
+    let metric = if a.number.number == 0 {
+        b.metric
+    } else if b.number.number == 0 || a.metric == b.metric {
+        a.metric
+    } else {
+        return None;
+    };
+    Some(PixelValue::from_metric(metric, interpolate_f32(a.number.get(), b.number.get(), t)))
+}
+
+fn interpolate_pixel_size(a: PixelSize, b: PixelSize, t: f32) -> Option<PixelSize> {
+    Some(PixelSize::new(interpolate_pixel_value(a.width, b.width, t)?, interpolate_pixel_value(a.height, b.height, t)?))
+}
+
+/// Interpolates one side of a padding or margin, unset sides are treated as `0px`.
+/// Returns `None` if the sides can't be interpolated.
+fn interpolate_side(a: Option<PixelValue>, b: Option<PixelValue>, t: f32) -> Option<Option<PixelValue>> {
+    match (a, b) {
+        (None, None) => Some(None),
+        (a, b) => interpolate_pixel_value(a.unwrap_or(PixelValue::px(0.0)), b.unwrap_or(PixelValue::px(0.0)), t).map(Some),
+    }
+}
+
+/// Returns the value of a `width`, `height` or their `min-` / `max-` variants as a `calc()` expression
+fn get_calc_size(property: &CssProperty) -> Option<(CssPropertyType, CalcPixelValue)> {
+    use self::CssProperty::*;
+    let size = match property {
+        Width(w) => w.0,
+        Height(h) => h.0,
+        MinWidth(w) => w.0,
+        MinHeight(h) => h.0,
+        MaxWidth(w) => w.0,
+        MaxHeight(h) => h.0,
+        Calc(property_type, value) => return Some((*property_type, value.clone())),
+        _ => return None,
+    };
+    Some((property.get_type(), size.into()))
+}
+
+/// Interpolates two sizes with different metrics (i.e. `50%` and `200px`) as a `calc()` expression
+fn interpolate_calc_size(a: &CssProperty, b: &CssProperty, t: f32) -> Option<CssProperty> {
+    let (a_type, a) = get_calc_size(a)?;
+    let (b_type, b) = get_calc_size(b)?;
+    if a_type != b_type {
+        return None;
+    }
+    Some(CssProperty::Calc(a_type, a.clone() + (b - a) * t))
+}
+
+fn interpolate_float_value(a: FloatValue, b: FloatValue, t: f32) -> FloatValue {
+    FloatValue::new(interpolate_f32(a.get(), b.get(), t))
+}
+
+/// Interpolates two transform functions of the same type, i.e. `rotate(0deg)` and `rotate(90deg)`
+fn interpolate_transform_function(a: StyleTransformFunction, b: StyleTransformFunction, t: f32) -> Option<StyleTransformFunction> {
+    use self::StyleTransformFunction::*;
+    let f = |a, b| interpolate_float_value(a, b, t);
+    match (a, b) {
+        (Translate(ax, ay), Translate(bx, by)) => Some(Translate(interpolate_pixel_value(ax, bx, t)?, interpolate_pixel_value(ay, by, t)?)),
+        (Rotate(a), Rotate(b)) => Some(Rotate(f(a, b))),
+        (Scale(ax, ay), Scale(bx, by)) => Some(Scale(f(ax, bx), f(ay, by))),
+        (Skew(ax, ay), Skew(bx, by)) => Some(Skew(f(ax, bx), f(ay, by))),
+        _ => None,
+    }
+}
+
+/// Returns the identity (no-op) version of a transform function, i.e. `scale(1, 1)` for `scale(2, 3)`
+fn get_identity_transform_function(function: StyleTransformFunction) -> StyleTransformFunction {
+    use self::StyleTransformFunction::*;
+    match function {
+        Translate(_, _) => Translate(PixelValue::px(0.0), PixelValue::px(0.0)),
+        Rotate(_) => Rotate(FloatValue::new(0.0)),
+        Scale(_, _) => Scale(FloatValue::new(1.0), FloatValue::new(1.0)),
+        Skew(_, _) => Skew(FloatValue::new(0.0), FloatValue::new(0.0)),
+    }
+}
+
+/// Interpolates two `transform` lists function by function. If one list is empty (`transform: none`),
+/// it is treated as the identity version of the other list. Lists with different functions
+/// can't be interpolated.
+fn interpolate_transform(a: &StyleTransform, b: &StyleTransform, t: f32) -> Option<StyleTransform> {
+
+    let identity = |list: &StyleTransform| list.0.iter().map(|f| get_identity_transform_function(*f)).collect::<Vec<_>>();
+
+    let (a, b) = match (a.0.is_empty(), b.0.is_empty()) {
+        (true, false) => (identity(b), b.0.clone()),
+        (false, true) => (a.0.clone(), identity(a)),
+        _ => (a.0.clone(), b.0.clone()),
+    };
+
+    if a.len() != b.len() {
+        return None;
+    }
+
+    a.into_iter().zip(b)
+        .map(|(a, b)| interpolate_transform_function(a, b, t))
+        .collect::<Option<Vec<_>>>()
+        .map(StyleTransform)
+}
+
+macro_rules! interpolate_sides {($struct:ident, $a:expr, $b:expr, $t:expr) => (
+    $struct {
+        top: interpolate_side($a.top, $b.top, $t)?,
+        bottom: interpolate_side($a.bottom, $b.bottom, $t)?,
+        left: interpolate_side($a.left, $b.left, $t)?,
+        right: interpolate_side($a.right, $b.right, $t)?,
+    }
+)}
+
+impl CssProperty {
+
+    /// Interpolates between two values of the same property: `t = 0.0` returns `self`,
+    /// `t = 1.0` returns `other`. Returns `None` if the two properties have a different
+    /// type or if the property can't be interpolated (i.e. `position` or `font-family`).
+    pub fn interpolate(&self, other: &CssProperty, t: f32) -> Option<CssProperty> {
+        self.interpolate_same_metric(other, t).or_else(|| interpolate_calc_size(self, other, t))
+    }
+
+    fn interpolate_same_metric(&self, other: &CssProperty, t: f32) -> Option<CssProperty> {
+
+        use self::CssProperty::*;
+
+        let interpolated = match (self, other) {
+            (TextColor(a), TextColor(b)) => TextColor(StyleTextColor(interpolate_color(a.0, b.0, t))),
+            (Background(StyleBackground::Color(a)), Background(StyleBackground::Color(b))) => {
+                Background(StyleBackground::Color(interpolate_color(*a, *b, t)))
+            },
+            (BorderRadius(a), BorderRadius(b)) => BorderRadius(StyleBorderRadius(crate::css_properties::BorderRadius {
+                top_left: interpolate_pixel_size(a.0.top_left, b.0.top_left, t)?,
+                top_right: interpolate_pixel_size(a.0.top_right, b.0.top_right, t)?,
+                bottom_left: interpolate_pixel_size(a.0.bottom_left, b.0.bottom_left, t)?,
+                bottom_right: interpolate_pixel_size(a.0.bottom_right, b.0.bottom_right, t)?,
+            })),
+            (FontSize(a), FontSize(b)) => FontSize(StyleFontSize(interpolate_pixel_value(a.0, b.0, t)?)),
+            (FontWeight(a), FontWeight(b)) => FontWeight(StyleFontWeight(interpolate_f32(a.0 as f32, b.0 as f32, t).round().max(1.0).min(1000.0) as u16)),
+            (LetterSpacing(a), LetterSpacing(b)) => LetterSpacing(StyleLetterSpacing(interpolate_pixel_value(a.0, b.0, t)?)),
+            (WordSpacing(a), WordSpacing(b)) => WordSpacing(StyleWordSpacing(interpolate_pixel_value(a.0, b.0, t)?)),
+            (LineHeight(a), LineHeight(b)) => LineHeight(StyleLineHeight(PercentageValue::new(interpolate_f32(a.0.get(), b.0.get(), t)))),
+            (TabWidth(a), TabWidth(b)) => TabWidth(StyleTabWidth(PercentageValue::new(interpolate_f32(a.0.get(), b.0.get(), t)))),
+            (Width(a), Width(b)) => Width(LayoutWidth(interpolate_pixel_value(a.0, b.0, t)?)),
+            (Height(a), Height(b)) => Height(LayoutHeight(interpolate_pixel_value(a.0, b.0, t)?)),
+            (MinWidth(a), MinWidth(b)) => MinWidth(LayoutMinWidth(interpolate_pixel_value(a.0, b.0, t)?)),
+            (MinHeight(a), MinHeight(b)) => MinHeight(LayoutMinHeight(interpolate_pixel_value(a.0, b.0, t)?)),
+            (MaxWidth(a), MaxWidth(b)) => MaxWidth(LayoutMaxWidth(interpolate_pixel_value(a.0, b.0, t)?)),
+            (MaxHeight(a), MaxHeight(b)) => MaxHeight(LayoutMaxHeight(interpolate_pixel_value(a.0, b.0, t)?)),
+            (Top(a), Top(b)) => Top(LayoutTop(interpolate_pixel_value(a.0, b.0, t)?)),
+            (Right(a), Right(b)) => Right(LayoutRight(interpolate_pixel_value(a.0, b.0, t)?)),
+            (Left(a), Left(b)) => Left(LayoutLeft(interpolate_pixel_value(a.0, b.0, t)?)),
+            (Bottom(a), Bottom(b)) => Bottom(LayoutBottom(interpolate_pixel_value(a.0, b.0, t)?)),
+            (Padding(a), Padding(b)) => Padding(interpolate_sides!(LayoutPadding, a, b, t)),
+            (Margin(a), Margin(b)) => Margin(interpolate_sides!(LayoutMargin, a, b, t)),
+            (FlexGrow(a), FlexGrow(b)) => FlexGrow(LayoutFlexGrow(FloatValue::new(interpolate_f32(a.0.get(), b.0.get(), t)))),
+            (FlexShrink(a), FlexShrink(b)) => FlexShrink(LayoutFlexShrink(FloatValue::new(interpolate_f32(a.0.get(), b.0.get(), t)))),
+            (Opacity(a), Opacity(b)) => Opacity(StyleOpacity(interpolate_float_value(a.0, b.0, t))),
+            (Transform(a), Transform(b)) => Transform(interpolate_transform(a, b, t)?),
+            (TransformOrigin(a), TransformOrigin(b)) => TransformOrigin(StyleTransformOrigin {
+                x: interpolate_pixel_value(a.x, b.x, t)?,
+                y: interpolate_pixel_value(a.y, b.y, t)?,
+            }),
+            _ => return None,
+        };
+
+        Some(interpolated)
+    }
+}
+
+#[test]
+fn test_timing_functions() {
+    use self::CssTimingFunction::*;
+
+    let is_close = |a: f32, b: f32| (a - b).abs() < 0.001;
+
+    for timing_function in &[Ease, Linear, EaseIn, EaseOut, EaseInOut] {
+        assert!(is_close(timing_function.evaluate(0.0), 0.0));
+        assert!(is_close(timing_function.evaluate(1.0), 1.0));
+    }
+
+    assert!(is_close(Linear.evaluate(0.25), 0.25));
+    assert!(is_close(EaseInOut.evaluate(0.5), 0.5));
+    // ease-in starts slow, ease-out starts fast
+    assert!(EaseIn.evaluate(0.25) < 0.25);
+    assert!(EaseOut.evaluate(0.25) > 0.25);
+    assert!(is_close(StepStart.evaluate(0.1), 1.0));
+    assert!(is_close(StepEnd.evaluate(0.9), 0.0));
+}
+
+#[test]
+fn test_interpolate_properties() {
+
+    let black = CssProperty::TextColor(StyleTextColor(ColorU { r: 0, g: 0, b: 0, a: 255 }));
+    let white = CssProperty::TextColor(StyleTextColor(ColorU { r: 255, g: 255, b: 255, a: 255 }));
+    let grey = CssProperty::TextColor(StyleTextColor(ColorU { r: 128, g: 128, b: 128, a: 255 }));
+
+    assert_eq!(black.interpolate(&white, 0.0), Some(black.clone()));
+    assert_eq!(black.interpolate(&white, 0.5), Some(grey));
+    assert_eq!(black.interpolate(&white, 1.0), Some(white.clone()));
+
+    let narrow = CssProperty::Width(LayoutWidth::px(100.0));
+    let wide = CssProperty::Width(LayoutWidth::px(200.0));
+    assert_eq!(narrow.interpolate(&wide, 0.25), Some(CssProperty::Width(LayoutWidth::px(125.0))));
+
+    // Properties of different types or discrete properties can't be interpolated
+    assert_eq!(black.interpolate(&wide, 0.5), None);
+    let relative = CssProperty::Position(LayoutPosition::Relative);
+    let absolute = CssProperty::Position(LayoutPosition::Absolute);
+    assert_eq!(relative.interpolate(&absolute, 0.5), None);
+}
+
+#[test]
+fn test_interpolate_transform() {
+
+    use self::StyleTransformFunction::*;
+
+    let none = CssProperty::Transform(StyleTransform(Vec::new()));
+    let rotated = CssProperty::Transform(StyleTransform(vec![Rotate(FloatValue::new(90.0)), Scale(FloatValue::new(3.0), FloatValue::new(1.0))]));
+
+    // `transform: none` is interpolated like `rotate(0deg) scale(1, 1)`
+    assert_eq!(
+        none.interpolate(&rotated, 0.5),
+        Some(CssProperty::Transform(StyleTransform(vec![Rotate(FloatValue::new(45.0)), Scale(FloatValue::new(2.0), FloatValue::new(1.0))])))
+    );
+
+    // Lists with different functions can't be interpolated
+    let translated = CssProperty::Transform(StyleTransform(vec![Translate(PixelValue::px(10.0), PixelValue::px(0.0))]));
+    assert_eq!(translated.interpolate(&rotated, 0.5), None);
+
+    let transparent = CssProperty::Opacity(StyleOpacity(FloatValue::new(0.0)));
+    let opaque = CssProperty::Opacity(StyleOpacity::default());
+    assert_eq!(transparent.interpolate(&opaque, 0.25), Some(CssProperty::Opacity(StyleOpacity(FloatValue::new(0.25)))));
+}
+
+#[test]
+fn test_keyframe_animation_progress() {
+
+    let animation = CssAnimation {
+        name: "fade-in".into(),
+        duration: Duration::from_millis(1000),
+        timing_function: CssTimingFunction::Linear,
+        delay: Duration::from_millis(0),
+        iteration_count: CssAnimationIterationCount::Count(FloatValue::const_new(2)),
+        direction: CssAnimationDirection::Alternate,
+        fill_mode: CssAnimationFillMode::Forwards,
+    };
+
+    let is_close = |a: Option<f32>, b: f32| a.map(|a| (a - b).abs() < 0.001).unwrap_or(false);
+
+    assert!(is_close(animation.get_progress(Duration::from_millis(250)), 0.25));
+    // Second iteration runs backwards
+    assert!(is_close(animation.get_progress(Duration::from_millis(1250)), 0.75));
+    assert!(!animation.is_finished(Duration::from_millis(1999)));
+    assert!(animation.is_finished(Duration::from_millis(2000)));
+    // fill-mode: forwards keeps the end state of the last (reversed) iteration
+    assert!(is_close(animation.get_progress(Duration::from_millis(5000)), 0.0));
+
+    let keyframes = CssKeyframes {
+        name: "fade-in".into(),
+        keyframes: vec![
+            CssKeyframe { offset: PercentageValue::const_new(50), properties: vec![CssProperty::Width(LayoutWidth::px(200.0))] },
+            CssKeyframe { offset: PercentageValue::const_new(100), properties: vec![CssProperty::Width(LayoutWidth::px(400.0))] },
+        ],
+    };
+
+    let base = CssProperty::Width(LayoutWidth::px(100.0));
+    assert_eq!(
+        keyframes.get_value(CssPropertyType::Width, 0.25, CssTimingFunction::Linear, Some(&base)),
+        Some(CssProperty::Width(LayoutWidth::px(150.0)))
+    );
+    assert_eq!(
+        keyframes.get_value(CssPropertyType::Width, 0.75, CssTimingFunction::Linear, Some(&base)),
+        Some(CssProperty::Width(LayoutWidth::px(300.0)))
+    );
+    assert_eq!(keyframes.get_value(CssPropertyType::Height, 0.75, CssTimingFunction::Linear, None), None);
+}
@@ -0,0 +1,136 @@
+//! Types and methods used to describe `@font-face` rules, which load fonts from files
+//! (`@font-face { font-family: "Roboto"; src: url("Roboto.ttf"); }`) and select the
+//! best matching face of a font family for a given `font-weight` and `font-style`
+
+use std::{fmt, path::PathBuf};
+use crate::css_properties::{StyleFontWeight, StyleFontStyle};
+
+/// `@font-face { font-family: "Roboto"; src: url("Roboto-Bold.ttf"); font-weight: bold; }` -
+/// adds one face (one weight / style combination) to a font family
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CssFontFace {
+    /// The name of the font family that the face is added to
+    pub family: String,
+    /// The sources of the face, in order of preference - the first source that can be loaded is used
+    pub sources: Vec<CssFontFaceSource>,
+    /// `font-weight` of the face, default: `normal`
+    pub weight: StyleFontWeight,
+    /// `font-style` of the face, default: `normal`
+    pub style: StyleFontStyle,
+}
+
+/// One (comma-separated) source in the `src` descriptor of a `@font-face` rule
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CssFontFaceSource {
+    /// `url("fonts/Roboto.ttf")` - the font is loaded from a file. Relative paths are
+    /// resolved against the directory of the stylesheet if it was loaded from a file.
+    Url(PathBuf),
+    /// `local("Roboto")` - the font is loaded from the fonts installed on the system
+    Local(String),
+}
+
+impl fmt::Display for CssFontFaceSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CssFontFaceSource::Url(path) => write!(f, "url(\"{}\")", path.display()),
+            CssFontFaceSource::Local(name) => write!(f, "local(\"{}\")", name),
+        }
+    }
+}
+
+/// Writes the `@font-face` block, with the descriptors indented by four spaces
+impl fmt::Display for CssFontFace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "@font-face {{")?;
+        writeln!(f, "    font-family: \"{}\";", self.family)?;
+        write!(f, "    src: ")?;
+        for (idx, source) in self.sources.iter().enumerate() {
+            if idx != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", source)?;
+        }
+        writeln!(f, ";")?;
+        writeln!(f, "    font-weight: {};", self.weight)?;
+        writeln!(f, "    font-style: {};", self.style)?;
+        write!(f, "}}")
+    }
+}
+
+/// Selects the face that best matches the requested weight and style, following the
+/// [CSS font matching algorithm](https://www.w3.org/TR/css-fonts-3/#font-style-matching):
+///
+/// - The style is matched first: `italic` falls back to `oblique` and then to `normal`,
+///   `oblique` falls back to `italic` and then to `normal`, `normal` falls back to `oblique`
+///   and then to `italic`.
+/// - Of the faces with the best style, the closest weight wins: for weights between `400`
+///   and `500`, heavier weights up to `500` are tried first, then lighter weights, then weights
+///   above `500`. Weights below `400` prefer lighter faces, weights above `500` prefer heavier faces.
+///
+/// Returns the index of the best face or `None` if `faces` is empty.
+pub fn match_font_face(faces: &[(StyleFontWeight, StyleFontStyle)], weight: StyleFontWeight, style: StyleFontStyle) -> Option<usize> {
+    faces.iter().enumerate()
+        .min_by_key(|(_, (face_weight, face_style))| (get_style_rank(style, *face_style), get_weight_rank(weight, *face_weight)))
+        .map(|(idx, _)| idx)
+}
+
+/// Lower is better: the position of the face style in the fallback order of the requested style
+fn get_style_rank(requested: StyleFontStyle, face: StyleFontStyle) -> usize {
+    use self::StyleFontStyle::*;
+    let fallback_order = match requested {
+        Italic => [Italic, Oblique, Normal],
+        Oblique => [Oblique, Italic, Normal],
+        Normal => [Normal, Oblique, Italic],
+    };
+    fallback_order.iter().position(|s| *s == face).unwrap_or(fallback_order.len())
+}
+
+/// Lower is better: `(group, distance)`, where the group encodes in which direction
+/// (lighter or heavier) the face weight is searched first
+fn get_weight_rank(requested: StyleFontWeight, face: StyleFontWeight) -> (usize, u16) {
+    let (requested, face) = (requested.0, face.0);
+    let heavier = |rank| (rank, face - requested);
+    let lighter = |rank| (rank, requested - face);
+
+    if requested >= 400 && requested <= 500 {
+        if face >= requested && face <= 500 {
+            heavier(0)
+        } else if face < requested {
+            lighter(1)
+        } else {
+            heavier(2)
+        }
+    } else if requested < 400 {
+        if face <= requested { lighter(0) } else { heavier(1) }
+    } else if face >= requested {
+        heavier(0)
+    } else {
+        lighter(1)
+    }
+}
+
+#[test]
+fn test_match_font_face() {
+    use self::StyleFontStyle::*;
+
+    let faces = [
+        (StyleFontWeight(300), Normal),
+        (StyleFontWeight(400), Normal),
+        (StyleFontWeight(700), Normal),
+        (StyleFontWeight(400), Italic),
+    ];
+
+    assert_eq!(match_font_face(&[], StyleFontWeight::NORMAL, Normal), None);
+    assert_eq!(match_font_face(&faces, StyleFontWeight::NORMAL, Normal), Some(1));
+    assert_eq!(match_font_face(&faces, StyleFontWeight::BOLD, Normal), Some(2));
+    // 500 prefers lighter weights over weights above 500
+    assert_eq!(match_font_face(&faces, StyleFontWeight(500), Normal), Some(1));
+    // 600 prefers heavier weights
+    assert_eq!(match_font_face(&faces, StyleFontWeight(600), Normal), Some(2));
+    // 200 prefers lighter weights, but takes the closest heavier one if there is none
+    assert_eq!(match_font_face(&faces, StyleFontWeight(200), Normal), Some(0));
+    // The style is matched before the weight
+    assert_eq!(match_font_face(&faces, StyleFontWeight::BOLD, Italic), Some(3));
+    assert_eq!(match_font_face(&faces, StyleFontWeight::NORMAL, Oblique), Some(3));
+    assert_eq!(match_font_face(&faces[..3], StyleFontWeight::NORMAL, Italic), Some(1));
+}
@@ -0,0 +1,176 @@
+//! Types and methods used to describe `@media` rules (`@media (max-width: 600px) { ... }`),
+//! which only apply if the window matches certain conditions
+
+use std::fmt;
+use crate::css::CssRuleBlock;
+use crate::css_properties::PixelValue;
+
+/// `@media (max-width: 600px), (prefers-color-scheme: dark) { ... }` - the rules of the block only
+/// apply if any of the (comma-separated) queries matches the window
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssMediaRule {
+    /// The rule applies if any of the queries matches
+    pub queries: Vec<CssMediaQuery>,
+    /// The rules inside of the `@media` block
+    pub rules: Vec<CssRuleBlock>,
+}
+
+/// `screen and (min-width: 600px) and (max-width: 900px)` - matches if all conditions match
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CssMediaQuery {
+    pub conditions: Vec<CssMediaCondition>,
+}
+
+/// One media feature of a `CssMediaQuery`, i.e. `(max-width: 600px)`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CssMediaCondition {
+    /// `(min-width: 600px)`, in logical pixels
+    MinWidth(f32),
+    /// `(max-width: 600px)`, in logical pixels
+    MaxWidth(f32),
+    /// `(min-height: 600px)`, in logical pixels
+    MinHeight(f32),
+    /// `(max-height: 600px)`, in logical pixels
+    MaxHeight(f32),
+    /// `(min-resolution: 2dppx)`, in device pixels per logical pixel (i.e. the HiDPI factor)
+    MinResolution(f32),
+    /// `(max-resolution: 2dppx)`, in device pixels per logical pixel (i.e. the HiDPI factor)
+    MaxResolution(f32),
+    /// `(prefers-color-scheme: dark)`
+    PrefersColorScheme(ColorScheme),
+}
+
+/// Light or dark color scheme, for `@media (prefers-color-scheme: dark)`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme::Light
+    }
+}
+
+impl_keyword_display!(ColorScheme,
+    [Light, "light"],
+    [Dark, "dark"]);
+
+/// Writes the `@media` block, with the rules indented by four spaces
+impl fmt::Display for CssMediaRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "@media ")?;
+        for (idx, query) in self.queries.iter().enumerate() {
+            if idx != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", query)?;
+        }
+        writeln!(f, " {{")?;
+        for rule in &self.rules {
+            for line in rule.to_string().lines() {
+                writeln!(f, "    {}", line)?;
+            }
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Writes the conditions of the query, joined by `and` - a query without any conditions matches every window
+impl fmt::Display for CssMediaQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.conditions.is_empty() {
+            return write!(f, "all");
+        }
+        for (idx, condition) in self.conditions.iter().enumerate() {
+            if idx != 0 {
+                write!(f, " and ")?;
+            }
+            write!(f, "{}", condition)?;
+        }
+        Ok(())
+    }
+}
+
+// Pixel values are parsed via `PixelValue`, so they are written via `PixelValue`, too
+impl fmt::Display for CssMediaCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssMediaCondition::*;
+        match self {
+            MinWidth(w) => write!(f, "(min-width: {})", PixelValue::px(*w)),
+            MaxWidth(w) => write!(f, "(max-width: {})", PixelValue::px(*w)),
+            MinHeight(h) => write!(f, "(min-height: {})", PixelValue::px(*h)),
+            MaxHeight(h) => write!(f, "(max-height: {})", PixelValue::px(*h)),
+            MinResolution(r) => write!(f, "(min-resolution: {}dppx)", r),
+            MaxResolution(r) => write!(f, "(max-resolution: {}dppx)", r),
+            PrefersColorScheme(scheme) => write!(f, "(prefers-color-scheme: {})", scheme),
+        }
+    }
+}
+
+/// The properties of a window that the `@media` queries are evaluated against
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct CssMediaInfo {
+    /// Width of the window, in logical pixels
+    pub width: f32,
+    /// Height of the window, in logical pixels
+    pub height: f32,
+    /// HiDPI factor of the window
+    pub resolution: f32,
+    /// Color scheme that the user prefers
+    pub color_scheme: ColorScheme,
+}
+
+impl CssMediaRule {
+    /// Returns whether the rules of this `@media` block apply to the window
+    pub fn matches(&self, media: &CssMediaInfo) -> bool {
+        self.queries.iter().any(|query| query.matches(media))
+    }
+}
+
+impl CssMediaQuery {
+    /// Returns whether all conditions of the query match
+    pub fn matches(&self, media: &CssMediaInfo) -> bool {
+        self.conditions.iter().all(|condition| condition.matches(media))
+    }
+}
+
+impl CssMediaCondition {
+    /// Returns whether the condition matches the window
+    pub fn matches(&self, media: &CssMediaInfo) -> bool {
+        use self::CssMediaCondition::*;
+        match *self {
+            MinWidth(width) => media.width >= width,
+            MaxWidth(width) => media.width <= width,
+            MinHeight(height) => media.height >= height,
+            MaxHeight(height) => media.height <= height,
+            MinResolution(resolution) => media.resolution >= resolution,
+            MaxResolution(resolution) => media.resolution <= resolution,
+            PrefersColorScheme(color_scheme) => media.color_scheme == color_scheme,
+        }
+    }
+}
+
+#[test]
+fn test_media_query_matches() {
+    use self::CssMediaCondition::*;
+
+    let media = CssMediaInfo { width: 500.0, height: 400.0, resolution: 2.0, color_scheme: ColorScheme::Dark };
+    let query = |conditions: Vec<CssMediaCondition>| CssMediaQuery { conditions };
+
+    assert!(query(vec![]).matches(&media));
+    assert!(query(vec![MaxWidth(600.0)]).matches(&media));
+    assert!(query(vec![MaxWidth(500.0), MinHeight(400.0)]).matches(&media));
+    assert!(!query(vec![MaxWidth(600.0), MinWidth(550.0)]).matches(&media));
+    assert!(query(vec![MinResolution(2.0)]).matches(&media));
+    assert!(!query(vec![MaxResolution(1.5)]).matches(&media));
+    assert!(query(vec![PrefersColorScheme(ColorScheme::Dark)]).matches(&media));
+    assert!(!query(vec![PrefersColorScheme(ColorScheme::Light)]).matches(&media));
+
+    let media_rule = CssMediaRule {
+        queries: vec![query(vec![MinWidth(800.0)]), query(vec![PrefersColorScheme(ColorScheme::Dark)])],
+        rules: Vec::new(),
+    };
+    assert!(media_rule.matches(&media));
+}
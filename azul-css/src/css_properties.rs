@@ -90,6 +90,17 @@ impl From<ColorF> for ColorU {
     }
 }
 
+/// Writes the color as a `#rrggbb` hex color, or as `#rrggbbaa` if the color is not fully opaque
+impl fmt::Display for ColorU {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.a == 255 {
+            write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            write!(f, "#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Ord, PartialOrd, Eq, Hash)]
 pub struct BorderRadius {
     pub top_left: PixelSize,
@@ -171,12 +182,24 @@ pub enum BorderStyle {
     Outset,
 }
 
+impl_keyword_display!(BorderStyle,
+    [None, "none"],
+    [Solid, "solid"],
+    [Double, "double"],
+    [Dotted, "dotted"],
+    [Dashed, "dashed"],
+    [Hidden, "hidden"],
+    [Groove, "groove"],
+    [Ridge, "ridge"],
+    [Inset, "inset"],
+    [Outset, "outset"]);
+
 #[derive(Debug, Copy, Clone, PartialEq, Ord, PartialOrd, Eq, Hash)]
 pub struct NinePatchBorder {
     // not implemented or parse-able yet, so no fields!
 }
 
-/// Creates `pt`, `px` and `em` constructors for any struct that has a
+/// Creates `pt`, `px`, `em`, `rem`, `percent`, `vw` and `vh` constructors for any struct that has a
 /// `PixelValue` as it's self.0 field.
 macro_rules! impl_pixel_value {($struct:ident) => (
     impl $struct {
@@ -194,6 +217,26 @@ macro_rules! impl_pixel_value {($struct:ident) => (
         pub fn pt(value: f32) -> Self {
             $struct(PixelValue::pt(value))
         }
+
+        #[inline]
+        pub fn rem(value: f32) -> Self {
+            $struct(PixelValue::rem(value))
+        }
+
+        #[inline]
+        pub fn percent(value: f32) -> Self {
+            $struct(PixelValue::percent(value))
+        }
+
+        #[inline]
+        pub fn vw(value: f32) -> Self {
+            $struct(PixelValue::vw(value))
+        }
+
+        #[inline]
+        pub fn vh(value: f32) -> Self {
+            $struct(PixelValue::vh(value))
+        }
     }
 
     impl ::std::fmt::Debug for $struct {
@@ -201,14 +244,27 @@ macro_rules! impl_pixel_value {($struct:ident) => (
             write!(f, "{}({:?})", stringify!($struct), self.0)
         }
     }
+
+    impl ::std::fmt::Display for $struct {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
 )}
 
+// Percentage values such as `line-height` are written as a factor, i.e. `1.2` instead of `120%`
 macro_rules! impl_percentage_value{($struct:ident) => (
     impl ::std::fmt::Debug for $struct {
         fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
             write!(f, "{}({:?})", stringify!($struct), self.0)
         }
     }
+
+    impl ::std::fmt::Display for $struct {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            write!(f, "{}", self.0.get())
+        }
+    }
 )}
 
 macro_rules! impl_float_value{($struct:ident) => (
@@ -217,10 +273,16 @@ macro_rules! impl_float_value{($struct:ident) => (
             write!(f, "{}({:?})", stringify!($struct), self.0)
         }
     }
+
+    impl ::std::fmt::Display for $struct {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
 )}
 
 /// Map between CSS keys and a statically typed enum
-const CSS_PROPERTY_KEY_MAP: [(CssPropertyType, &'static str);56] = [
+const CSS_PROPERTY_KEY_MAP: [(CssPropertyType, &'static str);76] = [
     (CssPropertyType::Background,       "background"),
     (CssPropertyType::BackgroundSize,   "background-size"),
     (CssPropertyType::BackgroundRepeat, "background-repeat"),
@@ -231,7 +293,10 @@ const CSS_PROPERTY_KEY_MAP: [(CssPropertyType, &'static str);56] = [
     (CssPropertyType::TextColor,        "color"),
     (CssPropertyType::FontSize,         "font-size"),
     (CssPropertyType::FontFamily,       "font-family"),
+    (CssPropertyType::FontWeight,       "font-weight"),
+    (CssPropertyType::FontStyle,        "font-style"),
     (CssPropertyType::TextAlign,        "text-align"),
+    (CssPropertyType::TextDirection,    "direction"),
     (CssPropertyType::LetterSpacing,    "letter-spacing"),
     (CssPropertyType::LineHeight,       "line-height"),
     (CssPropertyType::WordSpacing,      "word-spacing"),
@@ -243,18 +308,30 @@ const CSS_PROPERTY_KEY_MAP: [(CssPropertyType, &'static str);56] = [
     (CssPropertyType::MinHeight,        "min-height"),
     (CssPropertyType::MaxWidth,         "max-width"),
     (CssPropertyType::MaxHeight,        "max-height"),
+    (CssPropertyType::BoxSizing,        "box-sizing"),
     (CssPropertyType::Position,         "position"),
     (CssPropertyType::Top,              "top"),
     (CssPropertyType::Right,            "right"),
     (CssPropertyType::Left,             "left"),
     (CssPropertyType::Bottom,           "bottom"),
+    (CssPropertyType::Display,          "display"),
     (CssPropertyType::FlexWrap,         "flex-wrap"),
     (CssPropertyType::FlexDirection,    "flex-direction"),
     (CssPropertyType::FlexGrow,         "flex-grow"),
     (CssPropertyType::FlexShrink,       "flex-shrink"),
+    (CssPropertyType::FlexBasis,        "flex-basis"),
+    (CssPropertyType::Order,            "order"),
     (CssPropertyType::JustifyContent,   "justify-content"),
     (CssPropertyType::AlignItems,       "align-items"),
+    (CssPropertyType::AlignSelf,        "align-self"),
     (CssPropertyType::AlignContent,     "align-content"),
+    (CssPropertyType::RowGap,           "row-gap"),
+    (CssPropertyType::ColumnGap,        "column-gap"),
+    (CssPropertyType::GridTemplateColumns, "grid-template-columns"),
+    (CssPropertyType::GridTemplateRows, "grid-template-rows"),
+    (CssPropertyType::GridGap,          "grid-gap"),
+    (CssPropertyType::GridColumn,       "grid-column"),
+    (CssPropertyType::GridRow,          "grid-row"),
     (CssPropertyType::Overflow,         "overflow"),
     (CssPropertyType::OverflowX,        "overflow-x"),
     (CssPropertyType::OverflowY,        "overflow-y"),
@@ -278,6 +355,12 @@ const CSS_PROPERTY_KEY_MAP: [(CssPropertyType, &'static str);56] = [
     (CssPropertyType::BoxShadowLeft,    "box-shadow-left"),
     (CssPropertyType::BoxShadowRight,   "box-shadow-right"),
     (CssPropertyType::BoxShadowBottom,  "box-shadow-bottom"),
+
+    (CssPropertyType::Opacity,          "opacity"),
+    (CssPropertyType::Transform,        "transform"),
+    (CssPropertyType::TransformOrigin,  "transform-origin"),
+    (CssPropertyType::ZIndex,           "z-index"),
+    (CssPropertyType::Visibility,       "visibility"),
 ];
 
 /// Returns a map useful for parsing the keys of CSS stylesheets
@@ -299,7 +382,10 @@ pub enum CssPropertyType {
     TextColor,
     FontSize,
     FontFamily,
+    FontWeight,
+    FontStyle,
     TextAlign,
+    TextDirection,
     LetterSpacing,
     WordSpacing,
     TabWidth,
@@ -311,18 +397,30 @@ pub enum CssPropertyType {
     MinHeight,
     MaxWidth,
     MaxHeight,
+    BoxSizing,
     Position,
     Top,
     Right,
     Left,
     Bottom,
+    Display,
     FlexWrap,
     FlexDirection,
     FlexGrow,
     FlexShrink,
+    FlexBasis,
+    Order,
     JustifyContent,
     AlignItems,
+    AlignSelf,
     AlignContent,
+    RowGap,
+    ColumnGap,
+    GridTemplateColumns,
+    GridTemplateRows,
+    GridGap,
+    GridColumn,
+    GridRow,
 
     Overflow,
     OverflowX,
@@ -351,6 +449,12 @@ pub enum CssPropertyType {
     BoxShadowLeft,
     BoxShadowRight,
     BoxShadowBottom,
+
+    Opacity,
+    Transform,
+    TransformOrigin,
+    ZIndex,
+    Visibility,
 }
 
 impl CssPropertyType {
@@ -376,15 +480,34 @@ impl CssPropertyType {
         map.iter().find(|(_, v)| *v == self).and_then(|(k, _)| Some(k)).unwrap()
     }
 
+    /// Returns the type of the `CssProperty` that the value of this key is parsed into,
+    /// i.e. the value of `padding-top` is parsed into a `CssProperty::Padding`
+    pub fn get_shorthand_type(&self) -> CssPropertyType {
+        use self::CssPropertyType::*;
+        match self {
+            BackgroundColor | BackgroundImage => Background,
+            OverflowX | OverflowY => Overflow,
+            PaddingTop | PaddingLeft | PaddingRight | PaddingBottom => Padding,
+            MarginTop | MarginLeft | MarginRight | MarginBottom => Margin,
+            BorderTop | BorderLeft | BorderRight | BorderBottom => Border,
+            BoxShadowTop | BoxShadowLeft | BoxShadowRight | BoxShadowBottom => BoxShadow,
+            other => *other,
+        }
+    }
+
     /// Returns whether this property will be inherited during cascading
     pub fn is_inheritable(&self) -> bool {
         use self::CssPropertyType::*;
         match self {
             | TextColor
             | FontFamily
+            | FontWeight
+            | FontStyle
             | FontSize
             | LineHeight
-            | TextAlign => true,
+            | TextAlign
+            | TextDirection
+            | Visibility => true,
             _ => false,
         }
     }
@@ -397,7 +520,7 @@ impl CssPropertyType {
         // Since the border can be larger than the content,
         // in which case the content needs to be re-layouted, assume true for Border
 
-        // FontFamily, FontSize, LetterSpacing and LineHeight can affect
+        // FontFamily, FontWeight, FontStyle, FontSize, LetterSpacing and LineHeight can affect
         // the text layout and therefore the screen layout
 
         match self {
@@ -413,6 +536,11 @@ impl CssPropertyType {
             | BoxShadowLeft
             | BoxShadowBottom
             | BoxShadowRight
+            | Opacity
+            | Transform
+            | TransformOrigin
+            | ZIndex
+            | Visibility
             | Cursor => false,
             _ => true,
         }
@@ -421,7 +549,7 @@ impl CssPropertyType {
 
 impl fmt::Display for CssPropertyType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let key = CSS_PROPERTY_KEY_MAP.iter().find(|(v, _)| *v == *self).and_then(|(k, _)| Some(k)).unwrap();
+        let key = CSS_PROPERTY_KEY_MAP.iter().find(|(v, _)| *v == *self).and_then(|(_, k)| Some(k)).unwrap();
         write!(f, "{}", key)
     }
 }
@@ -437,7 +565,10 @@ pub enum CssProperty {
     Background(StyleBackground),
     FontSize(StyleFontSize),
     FontFamily(StyleFontFamily),
+    FontWeight(StyleFontWeight),
+    FontStyle(StyleFontStyle),
     TextAlign(StyleTextAlignmentHorz),
+    TextDirection(StyleTextDirection),
     LetterSpacing(StyleLetterSpacing),
     BoxShadow(StyleBoxShadow),
     LineHeight(StyleLineHeight),
@@ -445,11 +576,19 @@ pub enum CssProperty {
     TabWidth(StyleTabWidth),
     Cursor(StyleCursor),
     Width(LayoutWidth),
+    /// `width: min-content`, `width: max-content` or `width: fit-content` - has the same
+    /// `CssPropertyType` as `CssProperty::Width`, so that both override each other
+    IntrinsicWidth(LayoutIntrinsicWidth),
+    /// `width`, `height` or their `min-` / `max-` variants set to a `calc()` expression that
+    /// mixes metrics, i.e. `width: calc(100% - 20px)` - has the `CssPropertyType` of the
+    /// property it is set on, so that it overrides (and is overridden by) the plain value
+    Calc(CssPropertyType, CalcPixelValue),
     Height(LayoutHeight),
     MinWidth(LayoutMinWidth),
     MinHeight(LayoutMinHeight),
     MaxWidth(LayoutMaxWidth),
     MaxHeight(LayoutMaxHeight),
+    BoxSizing(LayoutBoxSizing),
     Position(LayoutPosition),
     Top(LayoutTop),
     Right(LayoutRight),
@@ -457,14 +596,30 @@ pub enum CssProperty {
     Bottom(LayoutBottom),
     Padding(LayoutPadding),
     Margin(LayoutMargin),
+    Display(LayoutDisplay),
     FlexWrap(LayoutWrap),
     FlexDirection(LayoutDirection),
     FlexGrow(LayoutFlexGrow),
     FlexShrink(LayoutFlexShrink),
+    FlexBasis(LayoutFlexBasis),
+    Order(LayoutOrder),
     JustifyContent(LayoutJustifyContent),
     AlignItems(LayoutAlignItems),
+    AlignSelf(LayoutAlignSelf),
     AlignContent(LayoutAlignContent),
+    RowGap(LayoutRowGap),
+    ColumnGap(LayoutColumnGap),
+    GridTemplateColumns(LayoutGridTemplate),
+    GridTemplateRows(LayoutGridTemplate),
+    GridGap(LayoutGridGap),
+    GridColumn(LayoutGridPlacement),
+    GridRow(LayoutGridPlacement),
     Overflow(LayoutOverflow),
+    Opacity(StyleOpacity),
+    Transform(StyleTransform),
+    TransformOrigin(StyleTransformOrigin),
+    ZIndex(StyleZIndex),
+    Visibility(StyleVisibility),
 }
 
 impl CssProperty {
@@ -480,7 +635,10 @@ impl CssProperty {
             CssProperty::Background(_) => CssPropertyType::Background,
             CssProperty::FontSize(_) => CssPropertyType::FontSize,
             CssProperty::FontFamily(_) => CssPropertyType::FontFamily,
+            CssProperty::FontWeight(_) => CssPropertyType::FontWeight,
+            CssProperty::FontStyle(_) => CssPropertyType::FontStyle,
             CssProperty::TextAlign(_) => CssPropertyType::TextAlign,
+            CssProperty::TextDirection(_) => CssPropertyType::TextDirection,
             CssProperty::LetterSpacing(_) => CssPropertyType::LetterSpacing,
             CssProperty::WordSpacing(_) => CssPropertyType::WordSpacing,
             CssProperty::TabWidth(_) => CssPropertyType::TabWidth,
@@ -488,11 +646,14 @@ impl CssProperty {
             CssProperty::LineHeight(_) => CssPropertyType::LineHeight,
             CssProperty::Cursor(_) => CssPropertyType::Cursor,
             CssProperty::Width(_) => CssPropertyType::Width,
+            CssProperty::IntrinsicWidth(_) => CssPropertyType::Width,
+            CssProperty::Calc(property_type, _) => *property_type,
             CssProperty::Height(_) => CssPropertyType::Height,
             CssProperty::MinWidth(_) => CssPropertyType::MinWidth,
             CssProperty::MinHeight(_) => CssPropertyType::MinHeight,
             CssProperty::MaxWidth(_) => CssPropertyType::MaxWidth,
             CssProperty::MaxHeight(_) => CssPropertyType::MaxHeight,
+            CssProperty::BoxSizing(_) => CssPropertyType::BoxSizing,
             CssProperty::Position(_) => CssPropertyType::Position,
             CssProperty::Top(_) => CssPropertyType::Top,
             CssProperty::Right(_) => CssPropertyType::Right,
@@ -500,14 +661,30 @@ impl CssProperty {
             CssProperty::Bottom(_) => CssPropertyType::Bottom,
             CssProperty::Padding(_) => CssPropertyType::Padding,
             CssProperty::Margin(_) => CssPropertyType::Margin,
+            CssProperty::Display(_) => CssPropertyType::Display,
             CssProperty::FlexWrap(_) => CssPropertyType::FlexWrap,
             CssProperty::FlexDirection(_) => CssPropertyType::FlexDirection,
             CssProperty::FlexGrow(_) => CssPropertyType::FlexGrow,
             CssProperty::FlexShrink(_) => CssPropertyType::FlexShrink,
+            CssProperty::FlexBasis(_) => CssPropertyType::FlexBasis,
+            CssProperty::Order(_) => CssPropertyType::Order,
             CssProperty::JustifyContent(_) => CssPropertyType::JustifyContent,
             CssProperty::AlignItems(_) => CssPropertyType::AlignItems,
+            CssProperty::AlignSelf(_) => CssPropertyType::AlignSelf,
             CssProperty::AlignContent(_) => CssPropertyType::AlignContent,
+            CssProperty::RowGap(_) => CssPropertyType::RowGap,
+            CssProperty::ColumnGap(_) => CssPropertyType::ColumnGap,
+            CssProperty::GridTemplateColumns(_) => CssPropertyType::GridTemplateColumns,
+            CssProperty::GridTemplateRows(_) => CssPropertyType::GridTemplateRows,
+            CssProperty::GridGap(_) => CssPropertyType::GridGap,
+            CssProperty::GridColumn(_) => CssPropertyType::GridColumn,
+            CssProperty::GridRow(_) => CssPropertyType::GridRow,
             CssProperty::Overflow(_) => CssPropertyType::Overflow,
+            CssProperty::Opacity(_) => CssPropertyType::Opacity,
+            CssProperty::Transform(_) => CssPropertyType::Transform,
+            CssProperty::TransformOrigin(_) => CssPropertyType::TransformOrigin,
+            CssProperty::ZIndex(_) => CssPropertyType::ZIndex,
+            CssProperty::Visibility(_) => CssPropertyType::Visibility,
         }
     }
 }
@@ -518,7 +695,10 @@ impl_from!(StyleBoxShadow, CssProperty::BoxShadow);
 impl_from!(StyleBorder, CssProperty::Border);
 impl_from!(StyleFontSize, CssProperty::FontSize);
 impl_from!(StyleFontFamily, CssProperty::FontFamily);
+impl_from!(StyleFontWeight, CssProperty::FontWeight);
+impl_from!(StyleFontStyle, CssProperty::FontStyle);
 impl_from!(StyleTextAlignmentHorz, CssProperty::TextAlign);
+impl_from!(StyleTextDirection, CssProperty::TextDirection);
 impl_from!(StyleLineHeight, CssProperty::LineHeight);
 impl_from!(StyleTabWidth, CssProperty::TabWidth);
 impl_from!(StyleWordSpacing, CssProperty::WordSpacing);
@@ -527,14 +707,21 @@ impl_from!(StyleBackgroundSize, CssProperty::BackgroundSize);
 impl_from!(StyleBackgroundRepeat, CssProperty::BackgroundRepeat);
 impl_from!(StyleTextColor, CssProperty::TextColor);
 impl_from!(StyleCursor, CssProperty::Cursor);
+impl_from!(StyleOpacity, CssProperty::Opacity);
+impl_from!(StyleTransform, CssProperty::Transform);
+impl_from!(StyleTransformOrigin, CssProperty::TransformOrigin);
+impl_from!(StyleZIndex, CssProperty::ZIndex);
+impl_from!(StyleVisibility, CssProperty::Visibility);
 
 impl_from!(LayoutOverflow, CssProperty::Overflow);
 impl_from!(LayoutWidth, CssProperty::Width);
+impl_from!(LayoutIntrinsicWidth, CssProperty::IntrinsicWidth);
 impl_from!(LayoutHeight, CssProperty::Height);
 impl_from!(LayoutMinWidth, CssProperty::MinWidth);
 impl_from!(LayoutMinHeight, CssProperty::MinHeight);
 impl_from!(LayoutMaxWidth, CssProperty::MaxWidth);
 impl_from!(LayoutMaxHeight, CssProperty::MaxHeight);
+impl_from!(LayoutBoxSizing, CssProperty::BoxSizing);
 
 impl_from!(LayoutPosition, CssProperty::Position);
 impl_from!(LayoutTop, CssProperty::Top);
@@ -545,13 +732,209 @@ impl_from!(LayoutLeft, CssProperty::Left);
 impl_from!(LayoutPadding, CssProperty::Padding);
 impl_from!(LayoutMargin, CssProperty::Margin);
 
+impl_from!(LayoutDisplay, CssProperty::Display);
 impl_from!(LayoutWrap, CssProperty::FlexWrap);
 impl_from!(LayoutDirection, CssProperty::FlexDirection);
 impl_from!(LayoutFlexGrow, CssProperty::FlexGrow);
 impl_from!(LayoutFlexShrink, CssProperty::FlexShrink);
+impl_from!(LayoutFlexBasis, CssProperty::FlexBasis);
+impl_from!(LayoutOrder, CssProperty::Order);
 impl_from!(LayoutJustifyContent, CssProperty::JustifyContent);
 impl_from!(LayoutAlignItems, CssProperty::AlignItems);
+impl_from!(LayoutAlignSelf, CssProperty::AlignSelf);
 impl_from!(LayoutAlignContent, CssProperty::AlignContent);
+impl_from!(LayoutRowGap, CssProperty::RowGap);
+impl_from!(LayoutColumnGap, CssProperty::ColumnGap);
+impl_from!(LayoutGridGap, CssProperty::GridGap);
+
+const PADDING_KEYS: [CssPropertyType;4] = [
+    CssPropertyType::PaddingTop, CssPropertyType::PaddingRight,
+    CssPropertyType::PaddingBottom, CssPropertyType::PaddingLeft,
+];
+const MARGIN_KEYS: [CssPropertyType;4] = [
+    CssPropertyType::MarginTop, CssPropertyType::MarginRight,
+    CssPropertyType::MarginBottom, CssPropertyType::MarginLeft,
+];
+const BORDER_KEYS: [CssPropertyType;4] = [
+    CssPropertyType::BorderTop, CssPropertyType::BorderRight,
+    CssPropertyType::BorderBottom, CssPropertyType::BorderLeft,
+];
+const BOX_SHADOW_KEYS: [CssPropertyType;4] = [
+    CssPropertyType::BoxShadowTop, CssPropertyType::BoxShadowRight,
+    CssPropertyType::BoxShadowBottom, CssPropertyType::BoxShadowLeft,
+];
+
+// Writes the property as a `key: value` declaration - properties that only set some of
+// their sides are written as multiple declarations, i.e. `padding-top: 5px; padding-left: 5px`
+impl fmt::Display for CssProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, key) in self.get_css_keys().into_iter().enumerate() {
+            if idx != 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}: {}", key, self.format_value(key))?;
+        }
+        Ok(())
+    }
+}
+
+impl CssProperty {
+
+    /// Returns the CSS keys that this property is written as. Usually this is only the key
+    /// of the property itself, but a property that doesn't set all of its sides (i.e. a
+    /// `LayoutPadding` parsed from `padding-top: 5px`) is written with one key per side.
+    pub fn get_css_keys(&self) -> Vec<CssPropertyType> {
+        match self {
+            CssProperty::Padding(p) => get_side_keys([p.top, p.right, p.bottom, p.left], false, CssPropertyType::Padding, PADDING_KEYS),
+            CssProperty::Margin(m) => get_side_keys([m.top, m.right, m.bottom, m.left], false, CssPropertyType::Margin, MARGIN_KEYS),
+            // `border` and `box-shadow` only take one value for all sides
+            CssProperty::Border(b) => get_side_keys([b.top, b.right, b.bottom, b.left], true, CssPropertyType::Border, BORDER_KEYS),
+            CssProperty::BoxShadow(b) => get_side_keys([b.top, b.right, b.bottom, b.left], true, CssPropertyType::BoxShadow, BOX_SHADOW_KEYS),
+            CssProperty::Overflow(o) => match (o.horizontal, o.vertical) {
+                (Some(horizontal), Some(vertical)) if horizontal == vertical => vec![CssPropertyType::Overflow],
+                (horizontal, vertical) => {
+                    let mut keys = Vec::new();
+                    if horizontal.is_some() {
+                        keys.push(CssPropertyType::OverflowX);
+                    }
+                    if vertical.is_some() {
+                        keys.push(CssPropertyType::OverflowY);
+                    }
+                    keys
+                },
+            },
+            other => vec![other.get_type()],
+        }
+    }
+
+    /// Formats the value of the property as it has to be written after the given CSS key,
+    /// i.e. the `5px` in `padding-top: 5px`. The value can be parsed again with
+    /// `azul_css_parser::parse_key_value_pair(key, value)`.
+    pub fn format_value(&self, key: CssPropertyType) -> String {
+        match self {
+            CssProperty::BorderRadius(r) => r.to_string(),
+            CssProperty::BackgroundSize(s) => s.to_string(),
+            CssProperty::BackgroundRepeat(r) => r.to_string(),
+            CssProperty::TextColor(c) => c.to_string(),
+            CssProperty::Border(b) => {
+                get_side(key, [b.top, b.right, b.bottom, b.left], BORDER_KEYS)
+                    .map(|side| side.to_string())
+                    .unwrap_or_else(|| String::from("none"))
+            },
+            // `background-image` only takes the (quoted) image ID, not `image("id")`
+            CssProperty::Background(StyleBackground::Image(id)) if key == CssPropertyType::BackgroundImage => format!("\"{}\"", id.0),
+            CssProperty::Background(b) => b.to_string(),
+            CssProperty::FontSize(s) => s.to_string(),
+            CssProperty::FontFamily(f) => f.to_string(),
+            CssProperty::FontWeight(w) => w.to_string(),
+            CssProperty::FontStyle(s) => s.to_string(),
+            CssProperty::TextAlign(a) => a.to_string(),
+            CssProperty::TextDirection(d) => d.to_string(),
+            CssProperty::LetterSpacing(s) => s.to_string(),
+            CssProperty::BoxShadow(b) => match get_side(key, [b.top, b.right, b.bottom, b.left], BOX_SHADOW_KEYS) {
+                Some(Some(shadow)) => shadow.to_string(),
+                _ => String::from("none"),
+            },
+            CssProperty::LineHeight(l) => l.to_string(),
+            CssProperty::WordSpacing(s) => s.to_string(),
+            CssProperty::TabWidth(t) => t.to_string(),
+            CssProperty::Cursor(c) => c.to_string(),
+            CssProperty::Width(w) => w.to_string(),
+            CssProperty::IntrinsicWidth(w) => w.to_string(),
+            CssProperty::Calc(_, c) => c.to_string(),
+            CssProperty::Height(h) => h.to_string(),
+            CssProperty::MinWidth(w) => w.to_string(),
+            CssProperty::MinHeight(h) => h.to_string(),
+            CssProperty::MaxWidth(w) => w.to_string(),
+            CssProperty::MaxHeight(h) => h.to_string(),
+            CssProperty::BoxSizing(b) => b.to_string(),
+            CssProperty::Position(p) => p.to_string(),
+            CssProperty::Top(t) => t.to_string(),
+            CssProperty::Right(r) => r.to_string(),
+            CssProperty::Left(l) => l.to_string(),
+            CssProperty::Bottom(b) => b.to_string(),
+            CssProperty::Padding(p) if PADDING_KEYS.contains(&key) => {
+                get_side(key, [p.top, p.right, p.bottom, p.left], PADDING_KEYS).unwrap_or(PIXEL_VALUE_ZERO).to_string()
+            },
+            CssProperty::Padding(p) => p.to_string(),
+            CssProperty::Margin(m) if MARGIN_KEYS.contains(&key) => {
+                get_side(key, [m.top, m.right, m.bottom, m.left], MARGIN_KEYS).unwrap_or(PIXEL_VALUE_ZERO).to_string()
+            },
+            CssProperty::Margin(m) => m.to_string(),
+            CssProperty::Display(d) => d.to_string(),
+            CssProperty::FlexWrap(w) => w.to_string(),
+            CssProperty::FlexDirection(d) => d.to_string(),
+            CssProperty::FlexGrow(g) => g.to_string(),
+            CssProperty::FlexShrink(s) => s.to_string(),
+            CssProperty::FlexBasis(b) => b.to_string(),
+            CssProperty::Order(o) => o.to_string(),
+            CssProperty::JustifyContent(j) => j.to_string(),
+            CssProperty::AlignItems(a) => a.to_string(),
+            CssProperty::AlignSelf(a) => a.to_string(),
+            CssProperty::AlignContent(a) => a.to_string(),
+            CssProperty::RowGap(g) => g.to_string(),
+            CssProperty::ColumnGap(g) => g.to_string(),
+            CssProperty::GridTemplateColumns(t) => t.to_string(),
+            CssProperty::GridTemplateRows(t) => t.to_string(),
+            CssProperty::GridGap(g) => g.to_string(),
+            CssProperty::GridColumn(c) => c.to_string(),
+            CssProperty::GridRow(r) => r.to_string(),
+            CssProperty::Overflow(o) => {
+                let overflow = match key {
+                    CssPropertyType::OverflowY => o.vertical.or(o.horizontal),
+                    _ => o.horizontal.or(o.vertical),
+                };
+                overflow.unwrap_or_default().to_string()
+            },
+            CssProperty::Opacity(o) => o.to_string(),
+            CssProperty::Transform(t) => t.to_string(),
+            CssProperty::TransformOrigin(t) => t.to_string(),
+            CssProperty::ZIndex(z) => z.to_string(),
+            CssProperty::Visibility(v) => v.to_string(),
+        }
+    }
+}
+
+/// Returns the shorthand key (i.e. `padding`) if all sides are set (and equal, if `needs_equal_sides`
+/// is set), otherwise the keys of the sides that are set (i.e. `padding-top`, `padding-left`).
+/// The sides are ordered `[top, right, bottom, left]`.
+fn get_side_keys<T: Copy + PartialEq>(
+    sides: [Option<T>;4],
+    needs_equal_sides: bool,
+    shorthand_key: CssPropertyType,
+    side_keys: [CssPropertyType;4])
+-> Vec<CssPropertyType>
+{
+    let all_sides_set = sides.iter().all(|side| side.is_some());
+    let all_sides_equal = sides.iter().all(|side| *side == sides[0]);
+
+    if all_sides_set && (all_sides_equal || !needs_equal_sides) {
+        vec![shorthand_key]
+    } else {
+        sides.iter().zip(side_keys.iter())
+            .filter(|(side, _)| side.is_some())
+            .map(|(_, key)| *key)
+            .collect()
+    }
+}
+
+/// Returns the side that the key refers to - if the key is the shorthand key
+/// (or the side isn't set), returns the first side that is set instead
+fn get_side<T: Copy>(key: CssPropertyType, sides: [Option<T>;4], side_keys: [CssPropertyType;4]) -> Option<T> {
+    side_keys.iter().position(|side_key| *side_key == key)
+        .and_then(|idx| sides[idx])
+        .or_else(|| sides.iter().filter_map(|side| *side).next())
+}
+
+/// Writes the value of the `padding` or `margin` shorthand, i.e. `5px` or `5px 10px 5px 10px`
+fn fmt_pixel_sides(f: &mut fmt::Formatter, sides: [Option<PixelValue>;4]) -> fmt::Result {
+    let sides = sides.iter().map(|side| side.unwrap_or(PIXEL_VALUE_ZERO)).collect::<Vec<_>>();
+    if sides.iter().all(|side| *side == sides[0]) {
+        write!(f, "{}", sides[0])
+    } else {
+        write!(f, "{} {} {} {}", sides[0], sides[1], sides[2], sides[3])
+    }
+}
 
 /// Multiplier for floating point accuracy. Elements such as px or %
 /// are only accurate until a certain number of decimal points, therefore
@@ -561,13 +944,48 @@ impl_from!(LayoutAlignContent, CssProperty::AlignContent);
 const FP_PRECISION_MULTIPLIER: f32 = 1000.0;
 const FP_PRECISION_MULTIPLIER_CONST: isize = FP_PRECISION_MULTIPLIER as isize;
 
-/// FloatValue, but associated with a certain metric (i.e. px, em, etc.)
+/// FloatValue, but associated with a certain metric (i.e. px, em, %, etc.)
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PixelValue {
     pub metric: SizeMetric,
     pub number: FloatValue,
 }
 
+const PIXEL_VALUE_ZERO: PixelValue = PixelValue::const_px(0);
+
+/// Values that the relative metrics (`%`, `em`, `rem`, `vw`, `vh`) of a `PixelValue` refer to
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct RelativeSizeContext {
+    /// Size in pixels that `100%` refers to, usually the width or height of the parent
+    pub percent_base: f32,
+    /// Font size of the current node in pixels, for `em` values
+    pub font_size: f32,
+    /// Font size of the root node in pixels, for `rem` values
+    pub root_font_size: f32,
+    /// Width of the window in pixels, for `vw` values
+    pub viewport_width: f32,
+    /// Height of the window in pixels, for `vh` values
+    pub viewport_height: f32,
+}
+
+impl Default for RelativeSizeContext {
+    fn default() -> Self {
+        Self {
+            percent_base: 0.0,
+            font_size: EM_HEIGHT,
+            root_font_size: EM_HEIGHT,
+            viewport_width: 0.0,
+            viewport_height: 0.0,
+        }
+    }
+}
+
+impl fmt::Display for PixelValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.number, self.metric)
+    }
+}
+
 // Manual Debug implementation, because the auto-generated one is nearly unreadable
 impl fmt::Debug for PixelValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -575,17 +993,33 @@ impl fmt::Debug for PixelValue {
     }
 }
 
-impl fmt::Debug for SizeMetric {
+impl fmt::Display for SizeMetric {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use self::SizeMetric::*;
         match self {
             Px => write!(f, "px"),
             Pt => write!(f, "pt"),
-            Em => write!(f, "pt"),
+            Em => write!(f, "em"),
+            Rem => write!(f, "rem"),
+            Percent => write!(f, "%"),
+            Vw => write!(f, "vw"),
+            Vh => write!(f, "vh"),
         }
     }
 }
 
+impl fmt::Debug for SizeMetric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for FloatValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get())
+    }
+}
+
 impl fmt::Debug for FloatValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.get())
@@ -615,6 +1049,13 @@ impl PixelValue {
         Self::const_from_metric(SizeMetric::Pt, value)
     }
 
+    /// Same as `PixelValue::percent()`, but only accepts whole numbers,
+    /// since using `f32` in const fn is not yet stabilized.
+    #[inline]
+    pub const fn const_percent(value: isize) -> Self {
+        Self::const_from_metric(SizeMetric::Percent, value)
+    }
+
     #[inline]
     pub const fn const_from_metric(metric: SizeMetric, value: isize) -> Self {
         Self {
@@ -638,6 +1079,27 @@ impl PixelValue {
         Self::from_metric(SizeMetric::Pt, value)
     }
 
+    #[inline]
+    pub fn rem(value: f32) -> Self {
+        Self::from_metric(SizeMetric::Rem, value)
+    }
+
+    /// Percentage of the parents size, i.e. `PixelValue::percent(50.0)` = `50%`
+    #[inline]
+    pub fn percent(value: f32) -> Self {
+        Self::from_metric(SizeMetric::Percent, value)
+    }
+
+    #[inline]
+    pub fn vw(value: f32) -> Self {
+        Self::from_metric(SizeMetric::Vw, value)
+    }
+
+    #[inline]
+    pub fn vh(value: f32) -> Self {
+        Self::from_metric(SizeMetric::Vh, value)
+    }
+
     #[inline]
     pub fn from_metric(metric: SizeMetric, value: f32) -> Self {
         Self {
@@ -646,17 +1108,152 @@ impl PixelValue {
         }
     }
 
+    /// Returns whether the value depends on the size of the parent (i.e. `50%`)
+    #[inline]
+    pub fn has_percentage(&self) -> bool {
+        self.metric == SizeMetric::Percent
+    }
+
     /// Returns the value of the SizeMetric in pixels
+    ///
+    /// Relative metrics are resolved against the `RelativeSizeContext::default()`,
+    /// i.e. percentages and viewport units resolve to 0. Use `to_pixels_relative` to
+    /// resolve them against the size of the parent / window.
     #[inline]
     pub fn to_pixels(&self) -> f32 {
+        self.to_pixels_relative(&RelativeSizeContext::default())
+    }
+
+    /// Returns the value in pixels, relative metrics (`%`, `em`, `rem`, `vw`, `vh`)
+    /// are resolved against the given `context`
+    pub fn to_pixels_relative(&self, context: &RelativeSizeContext) -> f32 {
         match self.metric {
             SizeMetric::Px => { self.number.get() },
-            SizeMetric::Pt => { (self.number.get()) * PT_TO_PX },
-            SizeMetric::Em => { (self.number.get()) * EM_HEIGHT },
+            SizeMetric::Pt => { self.number.get() * PT_TO_PX },
+            SizeMetric::Em => { self.number.get() * context.font_size },
+            SizeMetric::Rem => { self.number.get() * context.root_font_size },
+            SizeMetric::Percent => { self.number.get() / 100.0 * context.percent_base },
+            SizeMetric::Vw => { self.number.get() / 100.0 * context.viewport_width },
+            SizeMetric::Vh => { self.number.get() / 100.0 * context.viewport_height },
         }
     }
 }
 
+/// Result of a `calc()` expression that mixes metrics, i.e. `calc(100% - 20px)`
+///
+/// Since `calc()` can only add lengths and multiply them with numbers, every expression
+/// can be simplified to a sum with one `PixelValue` per metric (`100% + -20px`). The terms
+/// can only be added up once the size of the parent is known, so the expression is kept
+/// until the layout is solved.
+#[derive(Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalcPixelValue {
+    /// Terms that aren't zero, sorted by their metric
+    terms: Vec<PixelValue>,
+}
+
+impl CalcPixelValue {
+
+    /// Returns the terms of the expression, at most one per metric
+    #[inline]
+    pub fn get_terms(&self) -> &[PixelValue] {
+        &self.terms
+    }
+
+    /// Returns the value if the expression doesn't mix metrics, i.e. `calc(2 * 10px)` = `20px`
+    pub fn as_pixel_value(&self) -> Option<PixelValue> {
+        match self.terms.as_slice() {
+            [] => Some(PIXEL_VALUE_ZERO),
+            [term] => Some(*term),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the value depends on the size of the parent (i.e. `calc(50% + 10px)`)
+    #[inline]
+    pub fn has_percentage(&self) -> bool {
+        self.terms.iter().any(|term| term.has_percentage())
+    }
+
+    /// Returns the sum of the terms in pixels, relative metrics are
+    /// resolved against the given `context`
+    pub fn to_pixels_relative(&self, context: &RelativeSizeContext) -> f32 {
+        self.terms.iter().map(|term| term.to_pixels_relative(context)).sum()
+    }
+}
+
+impl From<PixelValue> for CalcPixelValue {
+    fn from(value: PixelValue) -> Self {
+        Self::default() + value
+    }
+}
+
+impl ::std::ops::Add<PixelValue> for CalcPixelValue {
+    type Output = Self;
+    fn add(mut self, term: PixelValue) -> Self {
+        match self.terms.binary_search_by_key(&term.metric, |t| t.metric) {
+            Ok(index) => {
+                self.terms[index].number.number += term.number.number;
+                if self.terms[index].number.number == 0 {
+                    self.terms.remove(index);
+                }
+            },
+            Err(index) => if term.number.number != 0 {
+                self.terms.insert(index, term);
+            },
+        }
+        self
+    }
+}
+
+impl ::std::ops::Add for CalcPixelValue {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        other.terms.into_iter().fold(self, |sum, term| sum + term)
+    }
+}
+
+impl ::std::ops::Sub for CalcPixelValue {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        self + other * -1.0
+    }
+}
+
+impl ::std::ops::Mul<f32> for CalcPixelValue {
+    type Output = Self;
+    fn mul(self, factor: f32) -> Self {
+        self.terms.into_iter()
+            .map(|term| PixelValue::from_metric(term.metric, term.number.get() * factor))
+            .fold(Self::default(), |product, term| product + term)
+    }
+}
+
+impl fmt::Display for CalcPixelValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut terms = self.terms.iter();
+        match terms.next() {
+            None => write!(f, "calc({})", PIXEL_VALUE_ZERO),
+            Some(first) => {
+                write!(f, "calc({}", first)?;
+                for term in terms {
+                    if term.number.number < 0 {
+                        write!(f, " - {}{}", -term.number.get(), term.metric)?;
+                    } else {
+                        write!(f, " + {}", term)?;
+                    }
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CalcPixelValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
 /// Wrapper around FloatValue, represents a percentage instead
 /// of just being a regular floating-point value, i.e `5` = `5%`
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -664,6 +1261,12 @@ pub struct PercentageValue {
     number: FloatValue,
 }
 
+impl fmt::Display for PercentageValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}%", self.get())
+    }
+}
+
 impl fmt::Debug for PercentageValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}%", self.get())
@@ -702,8 +1305,10 @@ impl FloatValue {
         Self { number: value * FP_PRECISION_MULTIPLIER_CONST }
     }
 
+    /// Rounds the value to the nearest representable `FloatValue` - rounding (instead
+    /// of truncating) ensures that a printed and re-parsed value results in the same `FloatValue`
     pub fn new(value: f32) -> Self {
-        Self { number: (value * FP_PRECISION_MULTIPLIER) as isize }
+        Self { number: (value * FP_PRECISION_MULTIPLIER).round() as isize }
     }
 
     pub fn get(&self) -> f32 {
@@ -722,7 +1327,16 @@ impl From<f32> for FloatValue {
 pub enum SizeMetric {
     Px,
     Pt,
+    /// Relative to the font size of the current node
     Em,
+    /// Relative to the font size of the root node
+    Rem,
+    /// Relative to the size of the parent node
+    Percent,
+    /// Relative to the width of the window (`1vw` = 1% of the window width)
+    Vw,
+    /// Relative to the height of the window (`1vh` = 1% of the window height)
+    Vh,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -734,6 +1348,18 @@ impl StyleBorderRadius {
     }
 }
 
+// Elliptical corners can't be parsed, so only the width of each corner is written
+impl fmt::Display for StyleBorderRadius {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let BorderRadius { top_left, top_right, bottom_right, bottom_left } = self.0;
+        if top_left == top_right && top_left == bottom_right && top_left == bottom_left {
+            write!(f, "{}", top_left.width)
+        } else {
+            write!(f, "{} {} {} {}", top_left.width, top_right.width, bottom_right.width, bottom_left.width)
+        }
+    }
+}
+
 /// Represents a `background-size` attribute
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum StyleBackgroundSize {
@@ -756,10 +1382,26 @@ impl Default for StyleBackgroundRepeat {
     }
 }
 
+impl_keyword_display!(StyleBackgroundSize,
+    [Contain, "contain"],
+    [Cover, "cover"]);
+
+impl_keyword_display!(StyleBackgroundRepeat,
+    [NoRepeat, "no-repeat"],
+    [Repeat, "repeat"],
+    [RepeatX, "repeat-x"],
+    [RepeatY, "repeat-y"]);
+
 /// Represents a `color` attribute
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct StyleTextColor(pub ColorU);
 
+impl fmt::Display for StyleTextColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// Represents a `padding` attribute
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LayoutPadding {
@@ -802,6 +1444,20 @@ merge_struct!(LayoutMargin);
 struct_all!(LayoutPadding, PixelValue);
 struct_all!(LayoutMargin, PixelValue);
 
+// Writes the `padding` shorthand, sides that aren't set are written as `0px`
+impl fmt::Display for LayoutPadding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel_sides(f, [self.top, self.right, self.bottom, self.left])
+    }
+}
+
+// Writes the `margin` shorthand, sides that aren't set are written as `0px`
+impl fmt::Display for LayoutMargin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel_sides(f, [self.top, self.right, self.bottom, self.left])
+    }
+}
+
 /// Represents a parsed `padding` attribute
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LayoutMargin {
@@ -924,6 +1580,12 @@ pub struct StyleBorderSide {
     pub border_color: ColorU,
 }
 
+impl fmt::Display for StyleBorderSide {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.border_width, self.border_style, self.border_color)
+    }
+}
+
 /// Represents a `box-shadow` attribute.
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct StyleBoxShadow {
@@ -946,6 +1608,16 @@ pub struct BoxShadowPreDisplayItem {
     pub clip_mode: BoxShadowClipMode,
 }
 
+impl fmt::Display for BoxShadowPreDisplayItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {} {} {}", self.offset[0], self.offset[1], self.blur_radius, self.spread_radius, self.color)?;
+        if self.clip_mode == BoxShadowClipMode::Inset {
+            write!(f, " inset")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum StyleBackground {
     LinearGradient(LinearGradient),
@@ -971,6 +1643,41 @@ impl<'a> From<CssImageId> for StyleBackground {
     }
 }
 
+impl fmt::Display for StyleBackground {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        fn repeating_prefix(extend_mode: ExtendMode) -> &'static str {
+            match extend_mode {
+                ExtendMode::Clamp => "",
+                ExtendMode::Repeat => "repeating-",
+            }
+        }
+
+        fn fmt_stops(f: &mut fmt::Formatter, stops: &[GradientStopPre]) -> fmt::Result {
+            for stop in stops {
+                write!(f, ", {}", stop)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            StyleBackground::LinearGradient(gradient) => {
+                write!(f, "{}linear-gradient({}", repeating_prefix(gradient.extend_mode), gradient.direction)?;
+                fmt_stops(f, &gradient.stops)?;
+                write!(f, ")")
+            },
+            StyleBackground::RadialGradient(gradient) => {
+                write!(f, "{}radial-gradient({}", repeating_prefix(gradient.extend_mode), gradient.shape)?;
+                fmt_stops(f, &gradient.stops)?;
+                write!(f, ")")
+            },
+            StyleBackground::Image(id) => write!(f, "image(\"{}\")", id.0),
+            StyleBackground::Color(c) => write!(f, "{}", c),
+            StyleBackground::NoBackground => write!(f, "none"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LinearGradient {
     pub direction: Direction,
@@ -1061,12 +1768,27 @@ impl Direction {
     }
 }
 
+// The parser always sets the start corner to the opposite of the end corner,
+// so only the end corner is written, i.e. `to bottom right`
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::Angle(deg) => write!(f, "{}deg", deg),
+            Direction::FromTo(_, to) => write!(f, "to {}", to),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Shape {
     Ellipse,
     Circle,
 }
 
+impl_keyword_display!(Shape,
+    [Ellipse, "ellipse"],
+    [Circle, "circle"]);
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum StyleCursor {
     /// `alias`
@@ -1137,6 +1859,38 @@ impl Default for StyleCursor {
     }
 }
 
+impl_keyword_display!(StyleCursor,
+    [Alias, "alias"],
+    [AllScroll, "all-scroll"],
+    [Cell, "cell"],
+    [ColResize, "col-resize"],
+    [ContextMenu, "context-menu"],
+    [Copy, "copy"],
+    [Crosshair, "crosshair"],
+    [Default, "default"],
+    [EResize, "e-resize"],
+    [EwResize, "ew-resize"],
+    [Grab, "grab"],
+    [Grabbing, "grabbing"],
+    [Help, "help"],
+    [Move, "move"],
+    [NResize, "n-resize"],
+    [NsResize, "ns-resize"],
+    [NeswResize, "nesw-resize"],
+    [NwseResize, "nwse-resize"],
+    [Pointer, "pointer"],
+    [Progress, "progress"],
+    [RowResize, "row-resize"],
+    [SResize, "s-resize"],
+    [SeResize, "se-resize"],
+    [Text, "text"],
+    [Unset, "unset"],
+    [VerticalText, "vertical-text"],
+    [WResize, "w-resize"],
+    [Wait, "wait"],
+    [ZoomIn, "zoom-in"],
+    [ZoomOut, "zoom-out"]);
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DirectionCorner {
     Right,
@@ -1149,6 +1903,16 @@ pub enum DirectionCorner {
     BottomLeft,
 }
 
+impl_keyword_display!(DirectionCorner,
+    [Right, "right"],
+    [Left, "left"],
+    [Top, "top"],
+    [Bottom, "bottom"],
+    [TopRight, "top right"],
+    [TopLeft, "top left"],
+    [BottomRight, "bottom right"],
+    [BottomLeft, "bottom left"]);
+
 impl DirectionCorner {
 
     pub fn opposite(&self) -> Self {
@@ -1218,6 +1982,15 @@ pub struct GradientStopPre {
     pub color: ColorU,
 }
 
+impl fmt::Display for GradientStopPre {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.offset {
+            Some(offset) => write!(f, "{} {}", self.color, offset),
+            None => write!(f, "{}", self.color),
+        }
+    }
+}
+
 /// Represents a `width` attribute
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LayoutWidth(pub PixelValue);
@@ -1237,6 +2010,22 @@ pub struct LayoutMinHeight(pub PixelValue);
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LayoutMaxHeight(pub PixelValue);
 
+/// Represents a `width` that is determined by the content of the node instead of a length
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LayoutIntrinsicWidth {
+    /// As narrow as possible without overflowing, i.e. as wide as the longest word
+    MinContent,
+    /// As wide as the content without any line breaks
+    MaxContent,
+    /// Fills the available space, but is at least `min-content` and at most `max-content` wide
+    FitContent,
+}
+
+impl_keyword_display!(LayoutIntrinsicWidth,
+    [MinContent, "min-content"],
+    [MaxContent, "max-content"],
+    [FitContent, "fit-content"]);
+
 /// Represents a `top` attribute
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LayoutTop(pub PixelValue);
@@ -1260,6 +2049,41 @@ pub struct LayoutFlexShrink(pub FloatValue);
 impl_float_value!(LayoutFlexGrow);
 impl_float_value!(LayoutFlexShrink);
 
+/// Represents a `flex-basis` attribute - default: `auto`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LayoutFlexBasis {
+    /// The item starts out at its `width` / `height` or at the size of its content
+    Auto,
+    /// The item starts out at a fixed size - percentages refer to the size of the flex container
+    Exact(PixelValue),
+}
+
+impl Default for LayoutFlexBasis {
+    fn default() -> Self {
+        LayoutFlexBasis::Auto
+    }
+}
+
+impl fmt::Display for LayoutFlexBasis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayoutFlexBasis::Auto => write!(f, "auto"),
+            LayoutFlexBasis::Exact(size) => write!(f, "{}", size),
+        }
+    }
+}
+
+/// Represents an `order` attribute - default: `0`. Flex items are laid out in
+/// ascending order, items with the same order keep their order in the DOM
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LayoutOrder(pub i32);
+
+impl fmt::Display for LayoutOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// Represents a `flex-direction` attribute - default: `Column`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LayoutDirection {
@@ -1275,6 +2099,12 @@ impl Default for LayoutDirection {
     }
 }
 
+impl_keyword_display!(LayoutDirection,
+    [Row, "row"],
+    [RowReverse, "row-reverse"],
+    [Column, "column"],
+    [ColumnReverse, "column-reverse"]);
+
 impl LayoutDirection {
     pub fn get_axis(&self) -> LayoutAxis {
         use self::{LayoutAxis::*, LayoutDirection::*};
@@ -1306,6 +2136,116 @@ pub struct StyleWordSpacing(pub PixelValue);
 impl_percentage_value!(StyleTabWidth);
 impl_percentage_value!(StyleLineHeight);
 
+/// Represents an `opacity` attribute, `1.0` is fully opaque, `0.0` is fully transparent
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StyleOpacity(pub FloatValue);
+
+impl_float_value!(StyleOpacity);
+
+impl Default for StyleOpacity {
+    fn default() -> Self {
+        StyleOpacity(FloatValue::const_new(1))
+    }
+}
+
+/// Represents a `z-index` attribute - default: `0` (`z-index: auto`)
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StyleZIndex(pub i32);
+
+impl fmt::Display for StyleZIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Represents a `visibility` attribute - default: `Visible`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StyleVisibility {
+    Visible,
+    /// The node still takes up space in the layout, but it is neither drawn nor hit-tested
+    Hidden,
+}
+
+impl Default for StyleVisibility {
+    fn default() -> Self {
+        StyleVisibility::Visible
+    }
+}
+
+impl_keyword_display!(StyleVisibility,
+    [Visible, "visible"],
+    [Hidden, "hidden"]);
+
+/// Represents a `transform` attribute, i.e. `translate(10px, 20px) rotate(45deg)`.
+///
+/// The functions are stored in the order they are written in, `transform: none`
+/// is represented as an empty list.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StyleTransform(pub Vec<StyleTransformFunction>);
+
+/// One function of a `transform` attribute, angles are stored in degrees
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StyleTransformFunction {
+    /// `translate(x, y)`, `translateX(x)` or `translateY(y)` - percentages
+    /// refer to the size of the transformed rectangle itself
+    Translate(PixelValue, PixelValue),
+    /// `rotate(angle)`, clockwise
+    Rotate(FloatValue),
+    /// `scale(x, y)`, `scaleX(x)` or `scaleY(y)`
+    Scale(FloatValue, FloatValue),
+    /// `skew(x, y)`, `skewX(x)` or `skewY(y)`
+    Skew(FloatValue, FloatValue),
+}
+
+impl fmt::Display for StyleTransform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "none");
+        }
+        for (idx, function) in self.0.iter().enumerate() {
+            if idx != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", function)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for StyleTransformFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::StyleTransformFunction::*;
+        match self {
+            Translate(x, y) => write!(f, "translate({}, {})", x, y),
+            Rotate(angle) => write!(f, "rotate({}deg)", angle),
+            Scale(x, y) => write!(f, "scale({}, {})", x, y),
+            Skew(x, y) => write!(f, "skew({}deg, {}deg)", x, y),
+        }
+    }
+}
+
+/// Represents a `transform-origin` attribute - default: `50% 50%`, i.e. the center of the rectangle
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StyleTransformOrigin {
+    pub x: PixelValue,
+    pub y: PixelValue,
+}
+
+impl Default for StyleTransformOrigin {
+    fn default() -> Self {
+        Self {
+            x: PixelValue::const_percent(50),
+            y: PixelValue::const_percent(50),
+        }
+    }
+}
+
+impl fmt::Display for StyleTransformOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.x, self.y)
+    }
+}
+
 /// Same as the `LayoutDirection`, but without the `-reverse` properties, used in the layout solver,
 /// makes decisions based on horizontal / vertical direction easier to write.
 /// Use `LayoutDirection::get_axis()` to get the axis for a given `LayoutDirection`.
@@ -1322,7 +2262,10 @@ pub enum LayoutAxis {
 pub enum LayoutPosition {
     Static,
     Relative,
+    /// Positioned against the nearest positioned (non-static) ancestor
     Absolute,
+    /// Positioned against the window
+    Fixed,
 }
 
 impl Default for LayoutPosition {
@@ -1331,19 +2274,83 @@ impl Default for LayoutPosition {
     }
 }
 
-/// Represents a `flex-wrap` attribute - default: `Wrap`
+impl_keyword_display!(LayoutPosition,
+    [Static, "static"],
+    [Relative, "relative"],
+    [Absolute, "absolute"],
+    [Fixed, "fixed"]);
+
+impl LayoutPosition {
+    /// Returns true, if the node is taken out of the normal flow (`position: absolute`
+    /// or `position: fixed`), i.e. it doesn't take up any space in its parent
+    pub fn is_out_of_flow(&self) -> bool {
+        *self == LayoutPosition::Absolute || *self == LayoutPosition::Fixed
+    }
+}
+
+/// Represents a `display` attribute - default: `Flex`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LayoutDisplay {
+    /// Children are layouted along the `flex-direction`
+    Flex,
+    /// Children are placed in the cells of the `grid-template-columns` / `grid-template-rows`
+    Grid,
+    /// The node and its children are not layouted, drawn or hit-tested at all
+    None,
+}
+
+impl Default for LayoutDisplay {
+    fn default() -> Self {
+        LayoutDisplay::Flex
+    }
+}
+
+impl_keyword_display!(LayoutDisplay,
+    [Flex, "flex"],
+    [Grid, "grid"],
+    [None, "none"]);
+
+/// Represents a `box-sizing` attribute - default: `BorderBox`
+///
+/// NOTE: Unlike in browsers (where the default is `content-box`), the `width` and
+/// `height` of a node in azul have always included the padding and border.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LayoutBoxSizing {
+    /// The `width` and `height` only contain the content, the padding and border are added on top
+    ContentBox,
+    /// The `width` and `height` contain the content, padding and border
+    BorderBox,
+}
+
+impl Default for LayoutBoxSizing {
+    fn default() -> Self {
+        LayoutBoxSizing::BorderBox
+    }
+}
+
+impl_keyword_display!(LayoutBoxSizing,
+    [ContentBox, "content-box"],
+    [BorderBox, "border-box"]);
+
+/// Represents a `flex-wrap` attribute - default: `NoWrap`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LayoutWrap {
+    /// Items that don't fit into the current line are moved into a new line
     Wrap,
+    /// All items are laid out in a single line (and shrink or overflow if they don't fit)
     NoWrap,
 }
 
 impl Default for LayoutWrap {
     fn default() -> Self {
-        LayoutWrap::Wrap
+        LayoutWrap::NoWrap
     }
 }
 
+impl_keyword_display!(LayoutWrap,
+    [Wrap, "wrap"],
+    [NoWrap, "nowrap"]);
+
 /// Represents a `justify-content` attribute
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LayoutJustifyContent {
@@ -1365,6 +2372,13 @@ impl Default for LayoutJustifyContent {
     }
 }
 
+impl_keyword_display!(LayoutJustifyContent,
+    [Start, "flex-start"],
+    [End, "flex-end"],
+    [Center, "center"],
+    [SpaceBetween, "space-between"],
+    [SpaceAround, "space-around"]);
+
 /// Represents a `align-items` attribute
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LayoutAlignItems {
@@ -1384,6 +2398,40 @@ impl Default for LayoutAlignItems {
     }
 }
 
+impl_keyword_display!(LayoutAlignItems,
+    [Stretch, "stretch"],
+    [Center, "center"],
+    [Start, "flex-start"],
+    [End, "flex-end"]);
+
+/// Represents a `align-self` attribute - overrides the `align-items` of the parent for a single item
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LayoutAlignSelf {
+    /// Default value. The item is aligned according to the `align-items` of its parent
+    Auto,
+    /// The item is stretched to fit its line
+    Stretch,
+    /// The item is positioned at the center of its line
+    Center,
+    /// The item is positioned at the beginning of its line
+    Start,
+    /// The item is positioned at the end of its line
+    End,
+}
+
+impl Default for LayoutAlignSelf {
+    fn default() -> Self {
+        LayoutAlignSelf::Auto
+    }
+}
+
+impl_keyword_display!(LayoutAlignSelf,
+    [Auto, "auto"],
+    [Stretch, "stretch"],
+    [Center, "center"],
+    [Start, "flex-start"],
+    [End, "flex-end"]);
+
 /// Represents a `align-content` attribute
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LayoutAlignContent {
@@ -1401,6 +2449,140 @@ pub enum LayoutAlignContent {
     SpaceAround,
 }
 
+impl Default for LayoutAlignContent {
+    fn default() -> Self {
+        LayoutAlignContent::Stretch
+    }
+}
+
+impl_keyword_display!(LayoutAlignContent,
+    [Stretch, "stretch"],
+    [Center, "center"],
+    [Start, "flex-start"],
+    [End, "flex-end"],
+    [SpaceBetween, "space-between"],
+    [SpaceAround, "space-around"]);
+
+/// Represents a `grid-template-columns` or `grid-template-rows` attribute, i.e. `100px 1fr auto`.
+///
+/// `repeat()` is expanded while parsing, so every track is stored individually.
+/// `none` is represented as an empty list.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LayoutGridTemplate(pub Vec<GridTrackSize>);
+
+/// Size of one column or row of a grid
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GridTrackSize {
+    /// Fixed size, i.e. `100px` - percentages refer to the size of the grid container
+    Fixed(PixelValue),
+    /// Fraction of the remaining space, i.e. `1fr`
+    Fraction(FloatValue),
+    /// Sized to fit the largest item in the track
+    Auto,
+}
+
+impl fmt::Display for LayoutGridTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "none");
+        }
+        for (idx, track) in self.0.iter().enumerate() {
+            if idx != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", track)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GridTrackSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::GridTrackSize::*;
+        match self {
+            Fixed(size) => write!(f, "{}", size),
+            Fraction(fr) => write!(f, "{}fr", fr),
+            Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// Represents a `grid-gap` attribute, i.e. `10px` or `10px 20px` (row gap, column gap)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LayoutGridGap {
+    /// Space between two rows
+    pub row: PixelValue,
+    /// Space between two columns
+    pub column: PixelValue,
+}
+
+impl fmt::Display for LayoutGridGap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.row == self.column {
+            write!(f, "{}", self.row)
+        } else {
+            write!(f, "{} {}", self.row, self.column)
+        }
+    }
+}
+
+/// Represents a `row-gap` attribute - space between the rows of a grid or the lines of a
+/// `flex-direction: row` container (or between the items of a `flex-direction: column` container)
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LayoutRowGap(pub PixelValue);
+
+/// Represents a `column-gap` attribute - space between the columns of a grid or the items of a
+/// `flex-direction: row` container (or between the lines of a `flex-direction: column` container)
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LayoutColumnGap(pub PixelValue);
+
+impl_pixel_value!(LayoutRowGap);
+impl_pixel_value!(LayoutColumnGap);
+
+/// Represents a `grid-column` or `grid-row` attribute, i.e. `1 / 3`, `2 / span 2` or `span 2`
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LayoutGridPlacement {
+    pub start: GridLine,
+    pub end: GridLine,
+}
+
+/// Start or end of a `grid-column` / `grid-row` - default: `Auto`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GridLine {
+    /// The item is placed automatically
+    Auto,
+    /// Number of the grid line, starting at 1 - negative numbers count from the last line
+    Line(i32),
+    /// The item spans this many tracks
+    Span(u32),
+}
+
+impl Default for GridLine {
+    fn default() -> Self {
+        GridLine::Auto
+    }
+}
+
+impl fmt::Display for LayoutGridPlacement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.end {
+            GridLine::Auto => write!(f, "{}", self.start),
+            end => write!(f, "{} / {}", self.start, end),
+        }
+    }
+}
+
+impl fmt::Display for GridLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::GridLine::*;
+        match self {
+            Auto => write!(f, "auto"),
+            Line(line) => write!(f, "{}", line),
+            Span(span) => write!(f, "span {}", span),
+        }
+    }
+}
+
 /// Represents a `overflow-x` or `overflow-y` property, see
 /// [`TextOverflowBehaviour`](./struct.TextOverflowBehaviour.html) - default: `Auto`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -1421,6 +2603,12 @@ impl Default for Overflow {
     }
 }
 
+impl_keyword_display!(Overflow,
+    [Scroll, "scroll"],
+    [Auto, "auto"],
+    [Hidden, "hidden"],
+    [Visible, "visible"]);
+
 impl Overflow {
 
     /// Returns whether this overflow value needs to display the scrollbars.
@@ -1442,6 +2630,16 @@ impl Overflow {
     pub fn is_overflow_visible(&self) -> bool {
         *self == Overflow::Visible
     }
+
+    /// Returns whether the overflowing content can be scrolled by the user
+    /// (`overflow:scroll` and `overflow:auto`)
+    pub fn allows_scrolling(&self) -> bool {
+        use self::Overflow::*;
+        match self {
+            Scroll | Auto => true,
+            Hidden | Visible => false,
+        }
+    }
 }
 
 /// Horizontal text alignment enum (left, center, right) - default: `Center`
@@ -1458,6 +2656,31 @@ impl Default for StyleTextAlignmentHorz {
     }
 }
 
+impl_keyword_display!(StyleTextAlignmentHorz,
+    [Left, "left"],
+    [Center, "center"],
+    [Right, "right"]);
+
+/// Base direction of a text (`direction: ltr | rtl`) - default: `ltr`
+///
+/// Determines the order of the words in a line that mixes left-to-right
+/// and right-to-left text, as well as the side that the text is aligned to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StyleTextDirection {
+    Ltr,
+    Rtl,
+}
+
+impl Default for StyleTextDirection {
+    fn default() -> Self {
+        StyleTextDirection::Ltr
+    }
+}
+
+impl_keyword_display!(StyleTextDirection,
+    [Ltr, "ltr"],
+    [Rtl, "rtl"]);
+
 /// Vertical text alignment enum (top, center, bottom) - default: `Center`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum StyleTextAlignmentVert {
@@ -1492,10 +2715,16 @@ pub struct RectStyle {
     pub font_size: Option<StyleFontSize>,
     /// Font name / family
     pub font_family: Option<StyleFontFamily>,
+    /// `font-weight` property
+    pub font_weight: Option<StyleFontWeight>,
+    /// `font-style` property
+    pub font_style: Option<StyleFontStyle>,
     /// Text color
     pub font_color: Option<StyleTextColor>,
     /// Text alignment
     pub text_align: Option<StyleTextAlignmentHorz,>,
+    /// `direction` property
+    pub direction: Option<StyleTextDirection>,
     /// `line-height` property
     pub line_height: Option<StyleLineHeight>,
     /// `letter-spacing` property
@@ -1504,6 +2733,16 @@ pub struct RectStyle {
     pub word_spacing: Option<StyleWordSpacing>,
     /// `tab-width` property
     pub tab_width: Option<StyleTabWidth>,
+    /// `opacity` property
+    pub opacity: Option<StyleOpacity>,
+    /// `transform` property
+    pub transform: Option<StyleTransform>,
+    /// `transform-origin` property
+    pub transform_origin: Option<StyleTransformOrigin>,
+    /// `z-index` property
+    pub z_index: Option<StyleZIndex>,
+    /// `visibility` property
+    pub visibility: Option<StyleVisibility>,
 }
 
 impl_pixel_value!(StyleLetterSpacing);
@@ -1576,7 +2815,7 @@ impl Default for ScrollbarInfo {
 }
 
 /// Options of a cascaded (styled) DOM node that are relevant for constructing the layout of a div
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct RectLayout {
 
     pub width: Option<LayoutWidth>,
@@ -1585,6 +2824,12 @@ pub struct RectLayout {
     pub min_height: Option<LayoutMinHeight>,
     pub max_width: Option<LayoutMaxWidth>,
     pub max_height: Option<LayoutMaxHeight>,
+    pub box_sizing: Option<LayoutBoxSizing>,
+    /// `width: min-content | max-content | fit-content`, only used if the `width` is not set
+    pub intrinsic_width: Option<LayoutIntrinsicWidth>,
+    /// `calc()` expressions of the `width`, `height` and their `min-` / `max-` variants,
+    /// which are resolved to the fields above once the size of the parent is known
+    pub calc_sizes: BTreeMap<CssPropertyType, CalcPixelValue>,
 
     pub position: Option<LayoutPosition>,
     pub top: Option<LayoutTop>,
@@ -1596,13 +2841,25 @@ pub struct RectLayout {
     pub margin: Option<LayoutMargin>,
     pub overflow: Option<LayoutOverflow>,
 
+    pub display: Option<LayoutDisplay>,
     pub direction: Option<LayoutDirection>,
     pub wrap: Option<LayoutWrap>,
     pub flex_grow: Option<LayoutFlexGrow>,
     pub flex_shrink: Option<LayoutFlexShrink>,
+    pub flex_basis: Option<LayoutFlexBasis>,
+    pub order: Option<LayoutOrder>,
     pub justify_content: Option<LayoutJustifyContent>,
     pub align_items: Option<LayoutAlignItems>,
+    pub align_self: Option<LayoutAlignSelf>,
     pub align_content: Option<LayoutAlignContent>,
+    pub row_gap: Option<LayoutRowGap>,
+    pub column_gap: Option<LayoutColumnGap>,
+
+    pub grid_template_columns: Option<LayoutGridTemplate>,
+    pub grid_template_rows: Option<LayoutGridTemplate>,
+    pub grid_gap: Option<LayoutGridGap>,
+    pub grid_column: Option<LayoutGridPlacement>,
+    pub grid_row: Option<LayoutGridPlacement>,
 }
 
 impl RectLayout {
@@ -1670,6 +2927,23 @@ pub struct StyleFontFamily {
     pub fonts: Vec<FontId>
 }
 
+impl fmt::Display for StyleFontFamily {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, font) in self.fonts.iter().enumerate() {
+            if idx != 0 {
+                write!(f, ", ")?;
+            }
+            // Font names such as "Times New Roman" have to be quoted
+            if font.0.chars().all(|c| c.is_alphanumeric() || c == '-') {
+                write!(f, "{}", font.0)?;
+            } else {
+                write!(f, "\"{}\"", font.0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FontId(pub String);
 
@@ -1678,3 +2952,51 @@ impl FontId {
         &self.0
     }
 }
+
+/// Represents a `font-weight` attribute, from `100` (thin) to `900` (black) - default: `400` (`normal`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StyleFontWeight(pub u16);
+
+impl StyleFontWeight {
+    /// `font-weight: normal`
+    pub const NORMAL: StyleFontWeight = StyleFontWeight(400);
+    /// `font-weight: bold`
+    pub const BOLD: StyleFontWeight = StyleFontWeight(700);
+
+    /// Returns whether the weight is closer to bold than to normal (`600` and above),
+    /// used for fonts that only come in a regular and a bold face
+    pub fn is_bold(&self) -> bool {
+        self.0 >= 600
+    }
+}
+
+impl Default for StyleFontWeight {
+    fn default() -> Self {
+        StyleFontWeight::NORMAL
+    }
+}
+
+impl fmt::Display for StyleFontWeight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Represents a `font-style` attribute - default: `normal`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StyleFontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+impl Default for StyleFontStyle {
+    fn default() -> Self {
+        StyleFontStyle::Normal
+    }
+}
+
+impl_keyword_display!(StyleFontStyle,
+    [Normal, "normal"],
+    [Italic, "italic"],
+    [Oblique, "oblique"]);
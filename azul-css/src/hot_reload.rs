@@ -14,6 +14,12 @@ pub trait HotReloadHandler {
     /// reloaded, and an human-readable error string otherwise (since the error needs to be printed
     /// to stdout when hot-reloading).
     fn reload_style(&mut self) -> Result<Css, String>;
+    /// Same as `reload_style`, but skips invalid rules instead of failing: returns the valid part
+    /// of the style together with a human-readable message for every error. Only returns an error
+    /// if nothing could be loaded. By default, this stops at the first error (via `reload_style`).
+    fn reload_style_lenient(&mut self) -> Result<(Css, Vec<String>), String> {
+        self.reload_style().map(|css| (css, Vec::new()))
+    }
     /// Returns how quickly the hot-reloader should reload the source format.
     fn get_reload_interval(&self) -> Duration;
 }
@@ -50,6 +56,18 @@ impl HotReloadHandler for HotReloadOverrideHandler {
         Ok(css)
     }
 
+    fn reload_style_lenient(&mut self) -> Result<(Css, Vec<String>), String> {
+        let (reloaded_css, errors) = self.hot_reloader.reload_style_lenient()?;
+        let mut css = Css::new();
+        for stylesheet in self.base_style.clone().stylesheets {
+            css.append_stylesheet(stylesheet);
+        }
+        for stylesheet in reloaded_css.stylesheets {
+            css.append_stylesheet(stylesheet);
+        }
+        Ok((css, errors))
+    }
+
     fn get_reload_interval(&self) -> Duration {
         self.hot_reloader.get_reload_interval()
     }
@@ -4,8 +4,14 @@
 mod macros;
 mod css;
 mod css_properties;
+mod css_animations;
+mod css_media;
+mod css_font_face;
 mod hot_reload;
 
 pub use crate::css::*;
 pub use crate::css_properties::*;
+pub use crate::css_animations::*;
+pub use crate::css_media::*;
+pub use crate::css_font_face::*;
 pub use crate::hot_reload::*;
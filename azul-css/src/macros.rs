@@ -1,14 +1,28 @@
 /// Implement the `From` trait for any type.
 /// Example usage:
 /// ```
+/// # macro_rules! impl_from {
+/// #     ($a:ident<$c:lifetime>, $b:ident::$enum_type:ident) => {
+/// #         impl<$c> From<$a<$c>> for $b<$c> {
+/// #             fn from(e: $a<$c>) -> Self {
+/// #                 $b::$enum_type(e)
+/// #             }
+/// #         }
+/// #     };
+/// # }
+/// struct BarError<'a>(&'a str);
+/// struct FooError<'a>(&'a str);
+///
 /// enum MyError<'a> {
-///     Bar(BarError<'a>)
-///     Foo(FooError<'a>)
+///     Bar(BarError<'a>),
+///     Foo(FooError<'a>),
 /// }
-/// 
-/// impl_from!(BarError<'a>, Error::Bar);
-/// impl_from!(BarError<'a>, Error::Bar);
-/// 
+///
+/// impl_from!(BarError<'a>, MyError::Bar);
+/// impl_from!(FooError<'a>, MyError::Foo);
+///
+/// let error: MyError = FooError("foo").into();
+/// assert!(match error { MyError::Foo(FooError(s)) => s == "foo", _ => false });
 /// ```
 macro_rules! impl_from {
     // From a type with a lifetime to a type which also has a lifetime
@@ -29,3 +43,38 @@ macro_rules! impl_from {
         }
     };
 }
+
+/// Implements `Display` for enums that are written as a single keyword in CSS.
+/// Example usage:
+/// ```
+/// # macro_rules! impl_keyword_display {($enum_name:ident, $([$variant:ident, $keyword:expr]),+) => (
+/// #     impl ::std::fmt::Display for $enum_name {
+/// #         fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+/// #             let keyword = match self {
+/// #                 $($enum_name::$variant => $keyword,)+
+/// #             };
+/// #             write!(f, "{}", keyword)
+/// #         }
+/// #     }
+/// # )}
+/// enum LayoutWrap {
+///     Wrap,
+///     NoWrap,
+/// }
+///
+/// impl_keyword_display!(LayoutWrap,
+///     [Wrap, "wrap"],
+///     [NoWrap, "nowrap"]);
+///
+/// assert_eq!(LayoutWrap::NoWrap.to_string(), "nowrap");
+/// ```
+macro_rules! impl_keyword_display {($enum_name:ident, $([$variant:ident, $keyword:expr]),+) => (
+    impl ::std::fmt::Display for $enum_name {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            let keyword = match self {
+                $($enum_name::$variant => $keyword,)+
+            };
+            write!(f, "{}", keyword)
+        }
+    }
+)}
@@ -0,0 +1,326 @@
+//! Runs the CSS transitions (`transition: background 200ms ease-in`) and the
+//! `@keyframes` animations (`animation: fade-in 1s`) of the styled nodes
+//!
+//! The animated values are stored in the `UiDescription` and override the values
+//! of the cascade when the display list is built. Since the animated values change
+//! every frame, the app loop has to keep redrawing the window while an animation is
+//! running - but as long as only styling properties (colors, border radii, ...) are
+//! animated, the DOM doesn't have to be rebuilt and the layout can be re-used.
+
+use std::{
+    mem,
+    collections::{BTreeMap, BTreeSet},
+    time::Instant,
+};
+use azul_css::{
+    CssProperty, CssPropertyType, CssDeclaration, CssTransition,
+    DynamicCssPropertyDefault,
+};
+use {
+    id_tree::NodeId,
+    traits::Layout,
+    dom::DomString,
+    ui_description::UiDescription,
+};
+
+/// A transition that is currently running on one property of a node
+#[derive(Debug, Clone, PartialEq)]
+struct RunningTransition {
+    /// Value of the property when the transition was started
+    from: CssProperty,
+    /// Value of the property once the transition is finished
+    to: CssProperty,
+    transition: CssTransition,
+    start: Instant,
+}
+
+/// Keeps track of the transitions and animations that are running in one window
+#[derive(Debug, Default, Clone)]
+pub(crate) struct AnimationState {
+    /// Transitions, by the node and the property that is transitioned
+    transitions: BTreeMap<(NodeId, CssPropertyType), RunningTransition>,
+    /// When the animations of a node (by the name of the animation) were started, so that
+    /// an animation doesn't restart every time the node is restyled
+    animation_starts: BTreeMap<(NodeId, String), Instant>,
+    /// Whether an animation was still running during the last `tick()`
+    has_running_animations: bool,
+}
+
+impl AnimationState {
+
+    /// Returns whether the window has to be redrawn in the next frame
+    pub(crate) fn is_running(&self) -> bool {
+        !self.transitions.is_empty() || self.has_running_animations
+    }
+
+    /// Has to be called every time the DOM is restyled: Starts the transitions of all properties
+    /// whose value changed compared to the `previous` frame and starts / stops the animations of the
+    /// nodes that got / lost an `animation` property.
+    ///
+    /// Nodes are matched to the nodes of the previous frame via the `DomDiff` of the `current`
+    /// `UiDescription`, so a node whose type, id or classes changed is treated as a new node
+    /// and doesn't transition (only the `:hover`, `:active`, `:focus`, ... states do).
+    pub(crate) fn update<T: Layout>(&mut self, previous: &UiDescription<T>, current: &UiDescription<T>, now: Instant) {
+
+        let node_hierarchy = &current.ui_descr_arena.node_layout;
+
+        let old_to_new_node_ids = current.dom_diff.as_ref().map(|dom_diff| {
+            node_hierarchy.linear_iter().filter_map(|new_node_id| {
+                dom_diff.get_old_node_id(new_node_id).map(|old_node_id| (old_node_id, new_node_id))
+            }).collect::<BTreeMap<NodeId, NodeId>>()
+        }).unwrap_or_default();
+
+        // Move the state of the previous frame to the new node IDs, drop the state of removed nodes
+        self.transitions = mem::replace(&mut self.transitions, BTreeMap::new()).into_iter()
+            .filter_map(|((old_node_id, property_type), transition)| {
+                let new_node_id = *old_to_new_node_ids.get(&old_node_id)?;
+                Some(((new_node_id, property_type), transition))
+            }).collect();
+
+        self.animation_starts = mem::replace(&mut self.animation_starts, BTreeMap::new()).into_iter()
+            .filter_map(|((old_node_id, name), start)| {
+                let new_node_id = *old_to_new_node_ids.get(&old_node_id)?;
+                // Stop the animation if the node doesn't have the animation anymore
+                if current.styled_nodes[new_node_id].animations.iter().any(|animation| animation.name == name) {
+                    Some(((new_node_id, name), start))
+                } else {
+                    None
+                }
+            }).collect();
+
+        for node_id in node_hierarchy.linear_iter() {
+
+            let styled_node = &current.styled_nodes[node_id];
+
+            for animation in &styled_node.animations {
+                self.animation_starts.entry((node_id, animation.name.clone())).or_insert(now);
+            }
+
+            let old_node_id = current.dom_diff.as_ref().and_then(|dom_diff| dom_diff.get_old_node_id(node_id));
+
+            let old_node_id = match old_node_id {
+                Some(s) if !styled_node.transitions.is_empty() => s,
+                _ => {
+                    self.remove_transitions(node_id);
+                    continue;
+                },
+            };
+
+            let property_types = styled_node.css_constraints.keys()
+                .chain(previous.styled_nodes[old_node_id].css_constraints.keys())
+                .cloned()
+                .collect::<BTreeSet<CssPropertyType>>();
+
+            for property_type in property_types {
+
+                let transition = match styled_node.transitions.iter().rev().find(|t| t.applies_to(property_type)) {
+                    Some(s) => *s,
+                    None => {
+                        self.transitions.remove(&(node_id, property_type));
+                        continue;
+                    },
+                };
+
+                let new_value = match get_property_value(current, node_id, property_type) {
+                    Some(s) => s,
+                    None => {
+                        self.transitions.remove(&(node_id, property_type));
+                        continue;
+                    },
+                };
+
+                // If a transition is already running, the new transition starts at the currently displayed value
+                let old_value = previous.animated_properties.get(&old_node_id)
+                    .and_then(|properties| properties.get(&property_type))
+                    .or_else(|| get_property_value(previous, old_node_id, property_type));
+
+                let old_value = match old_value {
+                    Some(s) => s,
+                    None => continue,
+                };
+
+                let is_already_running = self.transitions.get(&(node_id, property_type))
+                    .map(|running| running.to == *new_value)
+                    .unwrap_or(false);
+
+                if is_already_running {
+                    continue;
+                }
+
+                if old_value == new_value || old_value.interpolate(new_value, 0.0).is_none() {
+                    self.transitions.remove(&(node_id, property_type));
+                    continue;
+                }
+
+                self.transitions.insert((node_id, property_type), RunningTransition {
+                    from: old_value.clone(),
+                    to: new_value.clone(),
+                    transition,
+                    start: now,
+                });
+            }
+        }
+    }
+
+    /// Calculates the current values of all transitioned and animated properties and stores
+    /// them in the `animated_properties` of the `ui_description`.
+    ///
+    /// Returns whether the layout has to be recalculated, i.e. whether any property that
+    /// can affect the layout (such as `width`) changed compared to the last frame.
+    pub(crate) fn tick<T: Layout>(&mut self, ui_description: &mut UiDescription<T>, now: Instant) -> bool {
+
+        let mut animated_properties = BTreeMap::<NodeId, BTreeMap<CssPropertyType, CssProperty>>::new();
+        let mut finished_transitions = Vec::new();
+
+        for (&(node_id, property_type), running) in &self.transitions {
+            let elapsed = now.duration_since(running.start);
+            if running.transition.is_finished(elapsed) {
+                finished_transitions.push((node_id, property_type));
+                continue;
+            }
+            let progress = running.transition.get_progress(elapsed);
+            if let Some(value) = running.from.interpolate(&running.to, progress) {
+                animated_properties.entry(node_id).or_insert_with(BTreeMap::new).insert(property_type, value);
+            }
+        }
+
+        for finished in finished_transitions {
+            self.transitions.remove(&finished);
+        }
+
+        // Animations override the values of the transitions
+        let mut has_running_animations = false;
+
+        for ((node_id, name), start) in &self.animation_starts {
+
+            let animation = match ui_description.styled_nodes[*node_id].animations.iter().rev().find(|a| a.name == *name) {
+                Some(s) => s,
+                None => continue,
+            };
+
+            let keyframes = match ui_description.css.get_keyframes(name) {
+                Some(s) => s,
+                None => continue,
+            };
+
+            let elapsed = now.duration_since(*start);
+
+            if !animation.is_finished(elapsed) {
+                has_running_animations = true;
+            }
+
+            let progress = match animation.get_progress(elapsed) {
+                Some(s) => s,
+                None => continue,
+            };
+
+            for property_type in keyframes.get_animated_properties() {
+                let base_value = get_property_value(ui_description, *node_id, property_type);
+                if let Some(value) = keyframes.get_value(property_type, progress, animation.timing_function, base_value) {
+                    animated_properties.entry(*node_id).or_insert_with(BTreeMap::new).insert(property_type, value);
+                }
+            }
+        }
+
+        self.has_running_animations = has_running_animations;
+
+        let previous_properties = &ui_description.animated_properties;
+        let needs_relayout = animated_properties.iter().chain(previous_properties.iter())
+            .flat_map(|(node_id, properties)| properties.keys().map(move |property_type| (*node_id, *property_type)))
+            .filter(|(_, property_type)| property_type.can_trigger_relayout())
+            .any(|(node_id, property_type)| {
+                let old_value = previous_properties.get(&node_id).and_then(|p| p.get(&property_type));
+                let new_value = animated_properties.get(&node_id).and_then(|p| p.get(&property_type));
+                old_value != new_value
+            });
+
+        ui_description.animated_properties = animated_properties;
+
+        needs_relayout
+    }
+
+    fn remove_transitions(&mut self, node_id: NodeId) {
+        let removed = self.transitions.keys()
+            .filter(|(transition_node_id, _)| *transition_node_id == node_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in removed {
+            self.transitions.remove(&key);
+        }
+    }
+}
+
+/// Returns the value of the property after the cascade (without any animations applied)
+fn get_property_value<'a, T: Layout>(
+    ui_description: &'a UiDescription<T>,
+    node_id: NodeId,
+    property_type: CssPropertyType,
+) -> Option<&'a CssProperty> {
+    match ui_description.styled_nodes[node_id].css_constraints.get(&property_type)? {
+        CssDeclaration::Static(property) => Some(property),
+        CssDeclaration::Dynamic(dynamic_property) => {
+            let overridden_property = ui_description.dynamic_css_overrides.get(&node_id).and_then(|overrides| {
+                overrides.get(&DomString::Heap(dynamic_property.dynamic_id.clone()))
+            });
+            match (overridden_property, &dynamic_property.default) {
+                (Some(overridden_property), _) => Some(overridden_property),
+                (None, DynamicCssPropertyDefault::Exact(default)) => Some(default),
+                (None, DynamicCssPropertyDefault::Auto) => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+#[cfg(feature = "css_parser")]
+#[test]
+fn test_background_transition_on_hover() {
+
+    use std::time::Duration;
+    use webrender::api::{HitTestItem, PipelineId, LayoutPoint};
+    use azul_css::{StyleBackground, ColorU};
+    use prelude::*;
+    use window::ResolvedCss;
+
+    struct DataModel { }
+    impl Layout for DataModel { fn layout(&self) -> Dom<DataModel> { Dom::div() } }
+
+    let mut css = ::azul_css_parser::new_from_str("
+        .button { background: #000000; transition: background 1s linear; }
+        .button:hover { background: #ffffff; }
+    ").unwrap();
+    css.sort_by_specificity();
+    let css = ResolvedCss::new(css);
+
+    let mut hovered_nodes = BTreeMap::new();
+
+    let mut ui_state = Dom::<DataModel>::div().with_class("button").into_ui_state();
+    let first_frame = UiDescription::match_css_to_dom(&mut ui_state, &css, None, &mut None, &mut None, &hovered_nodes, false, None);
+
+    hovered_nodes.insert(NodeId::new(0), HitTestItem {
+        pipeline: PipelineId(0, 0),
+        tag: (0, 0),
+        point_in_viewport: LayoutPoint::zero(),
+        point_relative_to_item: LayoutPoint::zero(),
+    });
+
+    let mut ui_state = Dom::<DataModel>::div().with_class("button").into_ui_state();
+    let mut second_frame = UiDescription::match_css_to_dom(&mut ui_state, &css, Some(&first_frame), &mut None, &mut None, &hovered_nodes, false, None);
+
+    let start = Instant::now();
+    let mut animations = AnimationState::default();
+    animations.update(&first_frame, &second_frame, start);
+    assert!(animations.is_running());
+
+    // The background doesn't affect the layout
+    assert!(!animations.tick(&mut second_frame, start + Duration::from_millis(500)));
+    assert_eq!(
+        second_frame.animated_properties[&NodeId::new(0)].get(&CssPropertyType::Background),
+        Some(&CssProperty::Background(StyleBackground::Color(ColorU { r: 128, g: 128, b: 128, a: 255 })))
+    );
+
+    // Once the transition is finished, the value of the cascade is used again
+    animations.tick(&mut second_frame, start + Duration::from_secs(2));
+    assert!(second_frame.animated_properties.is_empty());
+    assert!(!animations.is_running());
+}
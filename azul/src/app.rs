@@ -1,10 +1,11 @@
 use std::{
     mem,
     fmt,
-    collections::BTreeMap,
+    io,
+    path::Path,
+    collections::{BTreeMap, BTreeSet},
     sync::{Arc, Mutex, PoisonError},
 };
-#[cfg(debug_assertions)]
 use std::time::Instant;
 #[cfg(debug_assertions)]
 use azul_css::HotReloadHandler;
@@ -36,9 +37,12 @@ use {
         Window, FakeWindow, ScrollStates,
         WindowCreateError, WindowCreateOptions, RendererType,
     },
-    window_state::{WindowSize, DebugState},
+    window_state::{WindowState, WindowSize, DebugState},
     app_resources::TextId,
-    dom::ScrollTagId,
+    dom::{ScrollTagId, EventFilter},
+    id_tree::NodeId,
+    display_list::ScrolledNodes,
+    scrollbar::handle_scrollbar_events,
     app_resources::{
         ImageId, FontSource, FontId, ImageReloadError,
         FontReloadError, CssImageId, RawImage,
@@ -46,8 +50,14 @@ use {
     traits::Layout,
     ui_state::UiState,
     ui_description::UiDescription,
+    diff::DomDiff,
     async::{Task, Timer, TimerId, TerminateTimer},
     callbacks::{FocusTarget, UpdateScreen, Redraw, DontRedraw},
+    headless::{
+        HeadlessWindow, HeadlessEvent, HeadlessEventResult,
+        layout_headless_window, inject_headless_event,
+    },
+    rasterizer::{self, RgbaImage},
 };
 pub use app_resources::AppResources;
 
@@ -88,8 +98,8 @@ pub struct AppConfig {
     /// gets logged to stdout and the logging file (only if logging is enabled).
     #[cfg(feature = "logging")]
     pub enable_logging_on_panic: bool,
-    /// (STUB) Whether keyboard navigation should be enabled (default: true).
-    /// Currently not implemented.
+    /// Whether keyboard navigation should be enabled (default: true). If enabled,
+    /// Tab and Shift + Tab move the focus between the nodes that have a `TabIndex`.
     pub enable_tab_navigation: bool,
     /// Whether to force a hardware or software renderer
     pub renderer_type: RendererType,
@@ -269,7 +279,7 @@ impl<T: Layout> App<T> {
 
         let mut app_state = AppState::new(initial_data, &config)?;
 
-        if let Some(r) = &mut app_state.resources.fake_display.renderer {
+        if let Some(r) = app_state.resources.fake_display.as_mut().and_then(|d| d.renderer.as_mut()) {
             set_webrender_debug_flags(r, &DebugState::default(), &config.debug_state);
         }
 
@@ -280,14 +290,26 @@ impl<T: Layout> App<T> {
         })
     }
 
+    /// Create a new application without a renderer, for running the layout of windows
+    /// without a GPU (i.e. for testing). Headless apps can't open regular windows,
+    /// use a `HeadlessWindow` and `run_headless` instead.
+    pub fn new_headless(initial_data: T, config: AppConfig) -> Self {
+        Self {
+            windows: BTreeMap::new(),
+            app_state: AppState::new_headless(initial_data),
+            config,
+        }
+    }
+
     /// Creates a new window
     pub fn create_window(&mut self, options: WindowCreateOptions<T>, css: Css)
     -> Result<Window<T>, WindowCreateError>
     {
+        let fake_display = self.app_state.resources.fake_display_mut().ok_or(WindowCreateError::Headless)?;
         Window::new(
-            &mut self.app_state.resources.fake_display.render_api,
-            &mut self.app_state.resources.fake_display.hidden_display.gl_window().context(),
-            &mut self.app_state.resources.fake_display.hidden_events_loop,
+            &mut fake_display.render_api,
+            &mut fake_display.hidden_display.gl_window().context(),
+            &mut fake_display.hidden_events_loop,
             options,
             css,
             self.config.background_color,
@@ -298,10 +320,11 @@ impl<T: Layout> App<T> {
     pub fn create_hot_reload_window(&mut self, options: WindowCreateOptions<T>, css_loader: Box<dyn HotReloadHandler>)
     -> Result<Window<T>, WindowCreateError>
     {
+        let fake_display = self.app_state.resources.fake_display_mut().ok_or(WindowCreateError::Headless)?;
         Window::new_hot_reload(
-            &mut self.app_state.resources.fake_display.render_api,
-            &mut self.app_state.resources.fake_display.hidden_display.gl_window().context(),
-            &mut self.app_state.resources.fake_display.hidden_events_loop,
+            &mut fake_display.render_api,
+            &mut fake_display.hidden_display.gl_window().context(),
+            &mut fake_display.hidden_events_loop,
             options,
             css_loader,
             self.config.background_color,
@@ -318,7 +341,7 @@ impl<T: Layout> App<T> {
         let fake_window = FakeWindow {
             state: window.state.clone(),
             default_callbacks: DefaultCallbackSystem::new(),
-            read_only_window: window.display.clone(),
+            read_only_window: Some(window.display.clone()),
         };
 
         self.app_state.windows.insert(window_id, fake_window);
@@ -384,7 +407,7 @@ impl<T: Layout> App<T> {
         #[cfg(debug_assertions)]
         let mut last_style_reload = Instant::now();
         #[cfg(debug_assertions)]
-        let mut should_print_css_error = true;
+        let mut last_css_errors = Vec::new();
 
         while !self.windows.is_empty() {
 
@@ -393,11 +416,13 @@ impl<T: Layout> App<T> {
             let mut frame_was_resize = false;
             let mut events = Vec::new();
 
-            self.app_state.resources.fake_display.hidden_events_loop.poll_events(|e| match e {
-                // Filter out all events that are uninteresting or unnecessary
-                Event::WindowEvent { event: WindowEvent::Refresh, .. } => { },
-                _ => { events.push(e); },
-            });
+            if let Some(fake_display) = self.app_state.resources.fake_display_mut() {
+                fake_display.hidden_events_loop.poll_events(|e| match e {
+                    // Filter out all events that are uninteresting or unnecessary
+                    Event::WindowEvent { event: WindowEvent::Refresh, .. } => { },
+                    _ => { events.push(e); },
+                });
+            }
 
             // let current_desktop_events = get_desktop_events(window, &events);
 
@@ -442,7 +467,7 @@ impl<T: Layout> App<T> {
                 hot_reload_css(
                     &mut self.windows,
                     &mut last_style_reload,
-                    &mut should_print_css_error,
+                    &mut last_css_errors,
                     &mut awakened_task
                 )?;
             }
@@ -486,6 +511,46 @@ impl<T: Layout> App<T> {
         Ok(())
     }
 
+    /// Runs a single frame of a headless window: calls the `Layout::layout()` function,
+    /// styles the DOM and solves the layout for the size and HiDPI factor of the window.
+    /// The result can be inspected via `HeadlessWindow::get_layout_result()`.
+    pub fn run_headless(&mut self, window: &mut HeadlessWindow<T>) -> Result<(), RuntimeError<T>> {
+        layout_headless_window(&mut self.app_state, window)
+    }
+
+    /// Simulates user input on a headless window: the event is hit-tested against the
+    /// layout of the last frame and invokes the same callbacks a real event would.
+    /// Returns which callbacks were called and whether the UI needed to be redrawn.
+    pub fn inject_event(&mut self, window: &mut HeadlessWindow<T>, event: HeadlessEvent)
+    -> Result<HeadlessEventResult, RuntimeError<T>>
+    {
+        inject_headless_event(&mut self.app_state, window, event, self.config.enable_tab_navigation)
+    }
+
+    /// Renders the last frame of a headless window into an image on the CPU (see the
+    /// `rasterizer` module), using the `background_color` of the `AppConfig` as the
+    /// clear color. Returns `None` if `App::run_headless` was never called.
+    pub fn render_headless(&self, window: &HeadlessWindow<T>) -> Option<RgbaImage> {
+        let snapshot = window.get_display_list_snapshot()?;
+        let LogicalSize { width, height } = window.state.size.dimensions;
+        Some(rasterizer::rasterize(
+            snapshot,
+            width as usize,
+            height as usize,
+            self.config.background_color,
+            &self.app_state.resources,
+        ))
+    }
+
+    /// Same as `render_headless`, but saves the image as a PNG file
+    pub fn save_headless_screenshot<P: AsRef<Path>>(&self, window: &HeadlessWindow<T>, path: P) -> Result<(), io::Error> {
+        let image = self.render_headless(window).ok_or_else(|| io::Error::new(
+            io::ErrorKind::Other,
+            "headless window has no frame yet, call App::run_headless first",
+        ))?;
+        image.save_png(path)
+    }
+
     /// See `AppState::add_task`.
     pub fn add_task(&mut self, task: Task<T>) {
         self.app_state.add_task(task);
@@ -493,7 +558,7 @@ impl<T: Layout> App<T> {
 
     /// Toggles debugging flags in webrender, updates `self.config.debug_state`
     pub fn toggle_debug_flags(&mut self, new_state: DebugState) {
-        if let Some(r) = &mut self.app_state.resources.fake_display.renderer {
+        if let Some(r) = self.app_state.resources.fake_display.as_mut().and_then(|d| d.renderer.as_mut()) {
             set_webrender_debug_flags(r, &self.config.debug_state, &new_state);
         }
         self.config.debug_state = new_state;
@@ -519,6 +584,17 @@ impl<T: Layout> AppState<T> {
         })
     }
 
+    /// Creates a new `AppState` whose resources have no renderer attached
+    fn new_headless(initial_data: T) -> Self {
+        Self {
+            data: Arc::new(Mutex::new(initial_data)),
+            windows: BTreeMap::new(),
+            resources: AppResources::new_headless(),
+            timers: FastHashMap::default(),
+            tasks: Vec::new(),
+        }
+    }
+
     impl_deamon_api!();
 
     /// Run all currently registered timers
@@ -600,7 +676,8 @@ fn render_single_window_content<T: Layout>(
 
     use self::RuntimeError::*;
 
-    if events.is_empty() && force_redraw_cache[window_id] == 0 {
+    // Running transitions / animations have to be redrawn every frame, even without any events
+    if events.is_empty() && force_redraw_cache[window_id] == 0 && !window.animations.is_running() {
         // Event was not a resize event, window should **not** close
         return Ok((false, false));
     }
@@ -623,10 +700,11 @@ fn render_single_window_content<T: Layout>(
             let callback_result = call_callbacks(
                 hit_test_results.as_ref(),
                 event,
-                window,
+                &mut window.state,
                 &window_id,
                 &ui_state_cache[&window_id],
-                app_state
+                app_state,
+                config.enable_tab_navigation,
             )?;
 
             if callback_result.should_update_screen == Redraw {
@@ -643,7 +721,7 @@ fn render_single_window_content<T: Layout>(
     }
 
     // Scroll for the scrolled amount for each node that registered a scroll state.
-    let should_scroll_render = update_scroll_state(window, hit_test_results, &mut app_state.resources);
+    let should_scroll_render = update_scroll_state(window, events, hit_test_results, &mut app_state.resources);
 
     if frame_event_info.is_resize_event {
         // This is a hack because during a resize event, winit eats the "awakened"
@@ -670,7 +748,9 @@ fn render_single_window_content<T: Layout>(
 
     // Update the window state that we got from the frame event (updates window dimensions and DPI)
     // Sets frame_event_info.needs redraw if the event was a
-    window.update_from_external_window_state(&mut frame_event_info, &app_state.resources.fake_display.hidden_events_loop);
+    if let Some(fake_display) = app_state.resources.fake_display() {
+        window.update_from_external_window_state(&mut frame_event_info, &fake_display.hidden_events_loop);
+    }
     // Update the window state every frame that was set by the user
     window.update_from_user_window_state(app_state.windows[&window_id].state.clone());
     // Reset the scroll amount to 0 (for the next frame)
@@ -689,16 +769,30 @@ fn render_single_window_content<T: Layout>(
 
         // Style the DOM (is_mouse_down is necessary for styling :hover, :active + :focus nodes)
         let is_mouse_down = window.state.internal.mouse_state.mouse_down();
+        let dragged_node = window.state.internal.get_dragged_node();
+
+        // Only the @media rules that match the current window size, DPI and color scheme apply.
+        // If a resize crosses a breakpoint, the resolved CSS changes and the whole DOM is restyled
+        window.resolved_css.update(&window.css, window.css_generation, &window.state.get_media_info());
+
+        // Only the nodes that changed since the last frame are restyled
+        let mut ui_description = UiDescription::match_css_to_dom(
+            ui_state_cache.get_mut(window_id).ok_or(WindowIndexError)?,
+            &window.resolved_css,
+            ui_description_cache.get(window_id),
+            &mut window.state.internal.focused_node,
+            &mut window.state.internal.pending_focus_target,
+            &window.state.internal.hovered_nodes,
+            is_mouse_down,
+            dragged_node,
+        );
 
-        *ui_description_cache.get_mut(window_id).ok_or(WindowIndexError)? =
-            UiDescription::match_css_to_dom(
-                ui_state_cache.get_mut(window_id).ok_or(WindowIndexError)?,
-                &window.css,
-                &mut window.state.internal.focused_node,
-                &mut window.state.internal.pending_focus_target,
-                &window.state.internal.hovered_nodes,
-                is_mouse_down,
-            );
+        // Start the transitions of all properties that changed since the last frame
+        let now = Instant::now();
+        window.animations.update(&ui_description_cache[window_id], &ui_description, now);
+        window.animations.tick(&mut ui_description, now);
+
+        *ui_description_cache.get_mut(window_id).ok_or(WindowIndexError)? = ui_description;
 
         // Render the window (webrender will send an Awakened event when the frame is done)
         let mut fake_window = app_state.windows.get_mut(window_id).ok_or(WindowIndexError)?;
@@ -709,15 +803,39 @@ fn render_single_window_content<T: Layout>(
             &mut *window,
             &mut fake_window,
             &mut app_state.resources,
+            true,
         );
         *awakened_task.get_mut(window_id).ok_or(WindowIndexError)? = false;
 
         if let Some(i) = force_redraw_cache.get_mut(window_id) {
             if *i > 0 { *i -= 1 };
             if *i == 1 {
-                clean_up_unused_opengl_textures(app_state.resources.fake_display.renderer.as_mut().unwrap().flush_pipeline_info());
+                if let Some(r) = app_state.resources.fake_display_mut().and_then(|d| d.renderer.as_mut()) {
+                    clean_up_unused_opengl_textures(r.flush_pipeline_info());
+                }
             }
         }
+    } else if window.animations.is_running() {
+
+        // Only the animated properties changed, so the DOM doesn't have to be rebuilt and restyled
+        let ui_description = ui_description_cache.get_mut(window_id).ok_or(WindowIndexError)?;
+        let needs_relayout = window.animations.tick(ui_description, Instant::now());
+
+        if needs_relayout {
+            // The DOM didn't change since the last layout, so the text layout of all nodes can be re-used
+            ui_description.dom_diff = Some(DomDiff::unchanged(&ui_description.ui_descr_arena));
+        }
+
+        let mut fake_window = app_state.windows.get_mut(window_id).ok_or(WindowIndexError)?;
+        update_display_list(
+            &mut app_state.data,
+            &*ui_description,
+            &ui_state_cache[window_id],
+            &mut *window,
+            &mut fake_window,
+            &mut app_state.resources,
+            needs_relayout,
+        );
     }
 
     // TODO: Render all windows again, not just this one!
@@ -728,16 +846,18 @@ fn render_single_window_content<T: Layout>(
     Ok((frame_event_info.is_resize_event, false))
 }
 
-/// Returns if there was an error with the CSS reloading, necessary so that the error message is only printed once
+/// Reloads the styles of all hot-reloaded windows. Invalid rules are skipped, the valid rules are applied.
+/// `last_css_errors` stores the errors of the last reload, so that the same errors are only printed once
 #[cfg(debug_assertions)]
 fn hot_reload_css<T: Layout>(
     windows: &mut BTreeMap<GliumWindowId, Window<T>>,
     last_style_reload: &mut Instant,
-    should_print_error: &mut bool,
+    last_css_errors: &mut Vec<String>,
     awakened_tasks: &mut BTreeMap<GliumWindowId, bool>)
 -> Result<(), RuntimeError<T>>
 {
     use self::RuntimeError::*;
+    use window::new_css_generation;
     for (window_id, window) in windows.iter_mut() {
         // Hot-reload a style if necessary
         let hot_reloader = match window.css_loader.as_mut() {
@@ -751,24 +871,32 @@ fn hot_reload_css<T: Layout>(
             continue;
         }
 
-        match hot_reloader.reload_style() {
-            Ok(mut new_css) => {
+        match hot_reloader.reload_style_lenient() {
+            Ok((mut new_css, errors)) => {
                 new_css.sort_by_specificity();
                 window.css = new_css;
-                if !(*should_print_error) {
-                    println!("--- OK: CSS parsed without errors, continuing hot-reload.");
+                window.css_generation = new_css_generation();
+                if errors != *last_css_errors {
+                    if errors.is_empty() {
+                        println!("--- OK: CSS parsed without errors, continuing hot-reload.");
+                    } else {
+                        println!("--- CSS parsed with {} error(s), invalid rules were skipped:", errors.len());
+                        for error in &errors {
+                            println!("{}", error);
+                        }
+                    }
                 }
                 *last_style_reload = Instant::now();
                 // window.events_loop.create_proxy().wakeup().unwrap_or(());
                 *awakened_tasks.get_mut(window_id).ok_or(WindowIndexError)? = true;
 
-                *should_print_error = true;
+                *last_css_errors = errors;
             },
             Err(why) => {
-                if *should_print_error {
+                if last_css_errors.len() != 1 || last_css_errors[0] != why {
                     println!("{}", why);
                 }
-                *should_print_error = false;
+                *last_css_errors = vec![why];
             },
         };
     }
@@ -782,7 +910,7 @@ fn do_hit_test<T: Layout>(window: &Window<T>, app_resources: &AppResources) -> O
     let cursor_location = window.state.internal.mouse_state.cursor_pos
         .map(|pos| WorldPoint::new(pos.x as f32, pos.y as f32))?;
 
-    let mut hit_test_results = app_resources.fake_display.render_api.hit_test(
+    let mut hit_test_results = app_resources.fake_display()?.render_api.hit_test(
         window.internal.document_id,
         Some(window.internal.pipeline_id),
         cursor_location,
@@ -798,22 +926,25 @@ fn do_hit_test<T: Layout>(window: &Window<T>, app_resources: &AppResources) -> O
 /// Struct returned from the `call_callbacks()` function -
 /// returns important information from the callbacks
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct CallCallbackReturn {
+pub(crate) struct CallCallbackReturn {
     /// Whether one or more callbacks say to redraw the screen or not
     pub should_update_screen: UpdateScreen,
     /// Whether one or more callbacks have messed with the current
     /// focused element i.e. via `.clear_focus()` or similar.
     pub callbacks_overwrites_focus: Option<FocusTarget>,
+    /// Which (default and normal) callbacks were called, in the order they were called in
+    pub callbacks_called: Vec<(NodeId, EventFilter)>,
 }
 
 /// Returns an bool whether the window should be redrawn or not (true - redraw the screen, false: don't redraw).
-fn call_callbacks<T: Layout>(
+pub(crate) fn call_callbacks<T: Layout>(
     hit_test_results: Option<&HitTestResult>,
     event: &WindowEvent,
-    window: &mut Window<T>,
+    window_state: &mut WindowState,
     window_id: &GliumWindowId,
     ui_state: &UiState<T>,
-    app_state: &mut AppState<T>)
+    app_state: &mut AppState<T>,
+    enable_tab_navigation: bool)
 -> Result<CallCallbackReturn, RuntimeError<T>>
 {
     use {
@@ -826,15 +957,18 @@ fn call_callbacks<T: Layout>(
 
     let hit_test_items = hit_test_results.map(|h| h.items.clone()).unwrap_or_default();
 
-    let callbacks_filter_list = window.state.determine_callbacks(&hit_test_items, event, ui_state);
+    let callbacks_filter_list = window_state.determine_callbacks(&hit_test_items, event, ui_state, enable_tab_navigation);
 
     // TODO: this should be refactored - currently very stateful and error-prone!
     app_state.windows.get_mut(window_id).ok_or(WindowIndexError)?
-        .set_keyboard_state(&window.state.internal.keyboard_state);
+        .set_keyboard_state(&window_state.internal.keyboard_state);
     app_state.windows.get_mut(window_id).ok_or(WindowIndexError)?
-        .set_mouse_state(&window.state.internal.mouse_state);
+        .set_mouse_state(&window_state.internal.mouse_state);
 
     let mut callbacks_overwrites_focus = None;
+    let mut callbacks_called = Vec::new();
+    // The callbacks can attach a payload to the current drag operation
+    let mut drag_data = callbacks_filter_list.drag_data.clone();
 
     let mut default_timers = FastHashMap::default();
     let mut default_tasks = Vec::new();
@@ -845,7 +979,7 @@ fn call_callbacks<T: Layout>(
 
         for (node_id, callback_results) in callbacks_filter_list.nodes_with_callbacks.iter() {
             let hit_item = &callback_results.hit_test_item;
-            for default_callback_id in callback_results.default_callbacks.values() {
+            for (event_filter, default_callback_id) in callback_results.default_callbacks.iter() {
 
                 let mut callback_info = CallbackInfo {
                     focus: None,
@@ -855,6 +989,7 @@ fn call_callbacks<T: Layout>(
                     hit_test_items: &hit_test_items,
                     cursor_relative_to_item: hit_item.as_ref().map(|hi| (hi.point_relative_to_item.x, hi.point_relative_to_item.y)),
                     cursor_in_viewport: hit_item.as_ref().map(|hi| (hi.point_in_viewport.x, hi.point_in_viewport.y)),
+                    drag_data: drag_data.clone(),
                 };
 
                 let mut app_state_no_data = AppStateNoData {
@@ -873,6 +1008,8 @@ fn call_callbacks<T: Layout>(
                     should_update_screen = Redraw;
                 }
 
+                callbacks_called.push((*node_id, *event_filter));
+
                 default_timers.extend(app_state_no_data.timers.into_iter());
                 default_tasks.extend(app_state_no_data.tasks.into_iter());
 
@@ -880,6 +1017,8 @@ fn call_callbacks<T: Layout>(
                 if let Some(new_focus) = callback_info.focus {
                     callbacks_overwrites_focus = Some(new_focus);
                 }
+
+                drag_data = callback_info.drag_data;
             }
         }
     }
@@ -895,7 +1034,7 @@ fn call_callbacks<T: Layout>(
 
     for (node_id, callback_results) in callbacks_filter_list.nodes_with_callbacks.iter() {
         let hit_item = &callback_results.hit_test_item;
-        for callback in callback_results.normal_callbacks.values() {
+        for (event_filter, callback) in callback_results.normal_callbacks.iter() {
 
             let mut callback_info = CallbackInfo {
                 focus: None,
@@ -905,18 +1044,28 @@ fn call_callbacks<T: Layout>(
                 hit_test_items: &hit_test_items,
                 cursor_relative_to_item: hit_item.as_ref().map(|hi| (hi.point_relative_to_item.x, hi.point_relative_to_item.y)),
                 cursor_in_viewport: hit_item.as_ref().map(|hi| (hi.point_in_viewport.x, hi.point_in_viewport.y)),
+                drag_data: drag_data.clone(),
             };
 
             if (callback.0)(app_state, &mut callback_info) == Redraw {
                 should_update_screen = Redraw;
             }
 
+            callbacks_called.push((*node_id, *event_filter));
+
             if let Some(new_focus) = callback_info.focus {
                 callbacks_overwrites_focus = Some(new_focus);
             }
+
+            drag_data = callback_info.drag_data;
         }
     }
 
+    // Store the payload for the next drag events (does nothing if the node was just dropped)
+    if let (Some(drag_state), Some(drag_data)) = (window_state.internal.drag_state.as_mut(), drag_data) {
+        drag_state.data.payload = drag_data.payload;
+    }
+
     if callbacks_filter_list.needs_redraw_anyways {
         should_update_screen = Redraw;
     }
@@ -929,6 +1078,7 @@ fn call_callbacks<T: Layout>(
     Ok(CallCallbackReturn {
         should_update_screen,
         callbacks_overwrites_focus,
+        callbacks_called,
     })
 }
 
@@ -940,6 +1090,7 @@ fn update_display_list<T: Layout>(
     window: &mut Window<T>,
     fake_window: &mut FakeWindow<T>,
     app_resources: &mut AppResources,
+    relayout: bool,
 ) {
     use display_list::DisplayList;
 
@@ -947,16 +1098,18 @@ fn update_display_list<T: Layout>(
 
     // NOTE: layout_result contains all words, text information, etc.
     // - very important for selection!
-    let (builder, scrolled_nodes, _layout_result) = display_list.into_display_list_builder(
+    let (builder, scrolled_nodes, layout_result) = display_list.into_display_list_builder(
         app_data,
         window,
         fake_window,
         app_resources,
+        relayout,
     );
 
     // NOTE: Display list has to be rebuilt every frame, otherwise, the epochs get out of sync
     let display_list_builder = builder.finalize().2;
     window.internal.last_scrolled_nodes = scrolled_nodes;
+    window.internal.last_layout_result = Some(layout_result);
 
     let (logical_size, _) = convert_window_size(&window.state.size);
 
@@ -969,17 +1122,16 @@ fn update_display_list<T: Layout>(
         true,
     );
 
-    app_resources.fake_display.render_api.send_transaction(window.internal.document_id, txn);
+    if let Some(fake_display) = app_resources.fake_display() {
+        fake_display.render_api.send_transaction(window.internal.document_id, txn);
+    }
 }
 
 /// Scroll all nodes in the ScrollStates to their correct position and insert
 /// the positions into the transaction
-///
-/// NOTE: scroll_states has to be mutable, since every key has a "visited" field, to
-/// indicate whether it was used during the current frame or not.
-fn scroll_all_nodes(scroll_states: &mut ScrollStates, txn: &mut Transaction) {
+fn scroll_all_nodes(scroll_states: &ScrollStates, txn: &mut Transaction) {
     use webrender::api::ScrollClamping;
-    for (key, value) in scroll_states.0.iter_mut() {
+    for (key, value) in scroll_states.0.iter() {
         let (x, y) = value.get();
         txn.scroll_node_with_id(LayoutPoint::new(x, y), *key, ScrollClamping::ToContentBounds);
     }
@@ -1003,47 +1155,77 @@ fn convert_window_size(size: &WindowSize) -> (LayoutSize, DeviceIntSize) {
 #[must_use]
 fn update_scroll_state<T: Layout>(
     window: &mut Window<T>,
+    events: &[WindowEvent],
     hit_test_results: Option<HitTestResult>,
     app_resources: &mut AppResources,
 ) -> bool {
 
-    const SCROLL_THRESHOLD: f64 = 0.5; // px
+    // Clicking on the scrollbars and dragging the thumbs doesn't need a hit-test
+    let scrolled_by_scrollbar = handle_scrollbar_events(
+        events,
+        &mut window.state,
+        &window.internal.last_scrolled_nodes,
+        &mut window.scroll_states,
+    );
 
     let hit_test_results = match hit_test_results {
         Some(s) => s,
         None => match do_hit_test(&window, app_resources) {
             Some(s) => s,
-            None => return false,
+            None => return scrolled_by_scrollbar,
         }
     };
 
-    let scroll_x = window.state.internal.mouse_state.scroll_x;
-    let scroll_y = window.state.internal.mouse_state.scroll_y;
+    let scrolled_by_wheel = scroll_hit_nodes(
+        &hit_test_results,
+        &window.state,
+        &window.internal.last_scrolled_nodes,
+        &mut window.scroll_states,
+    );
+
+    scrolled_by_scrollbar || scrolled_by_wheel
+}
+
+/// Scrolls the innermost node under the cursor by the current scroll amount of the window -
+/// if that node is already scrolled to the end, its scrolled parent is scrolled instead.
+/// Returns whether any node was scrolled (and therefore the screen needs to be re-rendered)
+#[must_use]
+pub(crate) fn scroll_hit_nodes(
+    hit_test_results: &HitTestResult,
+    window_state: &WindowState,
+    scrolled_nodes: &ScrolledNodes,
+    scroll_states: &mut ScrollStates,
+) -> bool {
+
+    const SCROLL_THRESHOLD: f64 = 0.5; // px
+
+    let scroll_x = window_state.internal.mouse_state.scroll_x;
+    let scroll_y = window_state.internal.mouse_state.scroll_y;
 
     if scroll_x.abs() < SCROLL_THRESHOLD && scroll_y.abs() < SCROLL_THRESHOLD {
         return false;
     }
 
-    let mut should_scroll_render = false;
-
-    let scrolled_nodes = &window.internal.last_scrolled_nodes;
-    let scroll_states = &mut window.scroll_states;
-
-    for scroll_node in hit_test_results.items.iter()
+    // Children always have a higher node ID than their parents,
+    // so the innermost scroll node comes first
+    let hit_scroll_nodes = hit_test_results.items.iter()
         .filter_map(|item| scrolled_nodes.tags_to_node_ids.get(&ScrollTagId(item.tag.0)))
+        .cloned()
+        .collect::<BTreeSet<NodeId>>();
+
+    for scroll_node in hit_scroll_nodes.iter().rev()
         .filter_map(|node_id| scrolled_nodes.overflowing_nodes.get(&node_id)) {
 
         // The external scroll ID is constructed from the DOM hash
         let scroll_id = scroll_node.parent_external_scroll_id;
 
-        if scroll_states.0.contains_key(&scroll_id) {
-            // TODO: make scroll speed configurable (system setting?)
-            scroll_states.scroll_node(&scroll_id, scroll_x as f32, scroll_y as f32);
-            should_scroll_render = true;
+        // TODO: make scroll speed configurable (system setting?)
+        if scroll_states.scroll_node(&scroll_id, scroll_x as f32, scroll_y as f32) {
+            return true;
         }
     }
 
-    should_scroll_render
+    false
 }
 
 fn clean_up_unused_opengl_textures(pipeline_info: PipelineInfo) {
@@ -1119,6 +1301,12 @@ fn render_inner<T: Layout>(
         return;
     }
 
+    // Headless apps have no renderer to draw with
+    let fake_display = match app_resources.fake_display_mut() {
+        Some(d) => d,
+        None => return,
+    };
+
     window.internal.epoch = increase_epoch(window.internal.epoch);
 
     txn.set_window_parameters(
@@ -1127,13 +1315,13 @@ fn render_inner<T: Layout>(
         window.state.size.hidpi_factor as f32
     );
     txn.set_root_pipeline(window.internal.pipeline_id);
-    scroll_all_nodes(&mut window.scroll_states, &mut txn);
+    scroll_all_nodes(&window.scroll_states, &mut txn);
     txn.generate_frame();
 
-    app_resources.fake_display.render_api.send_transaction(window.internal.document_id, txn);
+    fake_display.render_api.send_transaction(window.internal.document_id, txn);
 
     // Update WR texture cache
-    app_resources.fake_display.renderer.as_mut().unwrap().update();
+    fake_display.renderer.as_mut().unwrap().update();
 
     let background_color_f: ColorF = background_color.into();
 
@@ -1141,7 +1329,7 @@ fn render_inner<T: Layout>(
 
         // NOTE: GlContext is the context of the app-global, hidden window
         // (that shares the renderer), not the context of the window itself.
-        let gl_context = get_gl_context(&app_resources.fake_display.hidden_display).unwrap();
+        let gl_context = get_gl_context(&fake_display.hidden_display).unwrap();
 
         // NOTE: The `hidden_display` must share the OpenGL context with the `window`,
         // otherwise this will segfault! Use `ContextBuilder::with_shared_lists` to share the
@@ -1150,7 +1338,7 @@ fn render_inner<T: Layout>(
         // The context **must** be made current before calling `.bind_framebuffer()`,
         // otherwise EGL will panic with EGL_BAD_MATCH. The current context has to be the
         // hidden_display context, otherwise this will segfault on Windows.
-        app_resources.fake_display.hidden_display.gl_window().make_current().unwrap();
+        fake_display.hidden_display.gl_window().make_current().unwrap();
 
         let mut current_program = [0_i32];
         gl_context.get_integer_v(gl::CURRENT_PROGRAM, &mut current_program);
@@ -1192,7 +1380,7 @@ fn render_inner<T: Layout>(
         gl_context.disable(gl::MULTISAMPLE);
         gl_context.disable(gl::POLYGON_SMOOTH);
 
-        app_resources.fake_display.renderer.as_mut().unwrap().render(framebuffer_size).unwrap();
+        fake_display.renderer.as_mut().unwrap().render(framebuffer_size).unwrap();
 
         gl_context.delete_framebuffers(&framebuffers);
         gl_context.delete_renderbuffers(&depthbuffers);
@@ -1204,7 +1392,7 @@ fn render_inner<T: Layout>(
         draw_texture_to_screen(&*window_context, textures[0], framebuffer_size);
         window.display.swap_buffers().unwrap();
 
-        app_resources.fake_display.hidden_display.gl_window().make_current().unwrap();
+        fake_display.hidden_display.gl_window().make_current().unwrap();
 
         // Only delete the texture here...
         gl_context.delete_textures(&textures);
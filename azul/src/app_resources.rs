@@ -1,14 +1,16 @@
 use std::{
+    mem,
     path::PathBuf,
     io::Error as IoError,
     sync::atomic::{AtomicUsize, Ordering},
 };
 use webrender::api::{
-    FontKey, FontInstanceKey, ImageKey,
+    FontKey, FontInstanceKey, ImageKey, IdNamespace,
     ResourceUpdate, AddFont, AddFontInstance,
 };
 use app_units::Au;
 use clipboard2::{Clipboard, ClipboardError, SystemClipboard};
+use azul_css::{Css, CssFontFace, CssFontFaceSource, RectStyle, StyleFontWeight, StyleFontStyle};
 use {
     FastHashMap, FastHashSet,
     window::{FakeDisplay, WindowCreateError},
@@ -16,6 +18,7 @@ use {
     traits::Layout,
     display_list::DisplayList,
     text_layout::Words,
+    ui_solver,
 };
 pub use webrender::api::{ImageFormat as RawImageFormat, ImageData, ImageDescriptor};
 #[cfg(feature = "image_loading")]
@@ -32,11 +35,25 @@ pub type CssFontId = String;
 pub struct AppResources {
     /// In order to properly load / unload fonts and images as well as share resources
     /// between windows, this field stores the (application-global) Renderer.
-    pub(crate) fake_display: FakeDisplay,
+    ///
+    /// Is `None` if the app is running headless (without a window or an OpenGL context),
+    /// in which case the font and image keys are only generated, but never submitted.
+    pub(crate) fake_display: Option<FakeDisplay>,
     /// The CssImageId is the string used in the CSS, i.e. "my_image" -> ImageId(4)
     css_ids_to_image_ids: FastHashMap<CssImageId, ImageId>,
     /// Same as CssImageId -> ImageId, but for fonts, i.e. "Roboto" -> FontId(9)
     css_ids_to_font_ids: FastHashMap<CssFontId, FontId>,
+    /// The faces of the font families added via `add_font_face` or a CSS `@font-face` rule,
+    /// i.e. "Roboto" -> [(400, normal, FontId(3)), (700, italic, FontId(4))]
+    font_families: FastHashMap<CssFontId, Vec<(StyleFontWeight, StyleFontStyle, FontId)>>,
+    /// The `@font-face` rules of the CSS that were already added to the `font_families`
+    css_font_faces: FastHashSet<CssFontFace>,
+    /// Whether a font family is installed on the system - queried for the `font-family`
+    /// lists of all text nodes before the layout, since querying the system is slow
+    system_font_families: FastHashMap<CssFontId, bool>,
+    /// Font families for the characters that the font of a text doesn't contain (i.e. emoji
+    /// or CJK characters), in order of preference - families that aren't available are skipped
+    fallback_font_families: Vec<CssFontId>,
     /// Stores where the images were loaded from
     images: FastHashMap<ImageId, ImageSource>,
     /// Raw images are the same as regular images, but not in PNG or JPEG format, but rather as raw bytes
@@ -62,10 +79,16 @@ pub struct AppResources {
     last_frame_font_keys: FastHashMap<ImmediateFontId, LoadedFont>,
     /// Fonts that were loaded, but not yet used during this frame
     pending_frame_font_keys: FastHashMap<ImmediateFontId, LoadedFont>,
+    /// Whether the texts of the last frame need fallback fonts, so they don't have to be shaped every frame
+    missing_glyphs_cache: MissingGlyphsCache,
+    /// Decoded pixels of all images that were used in headless mode, since there is no
+    /// RenderApi that would keep them - necessary for the software rasterizer
+    headless_images: FastHashMap<ImageId, (ImageDescriptor, ImageData)>,
     /// Stores long texts across frames
     text_cache: TextCache,
-    /// Keyboard clipboard storage and retrieval functionality
-    clipboard: SystemClipboard,
+    /// Keyboard clipboard storage and retrieval functionality - may not be
+    /// available if the app is running headless (i.e. on a CI server)
+    clipboard: Option<SystemClipboard>,
 }
 
 static TEXT_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
@@ -83,6 +106,37 @@ pub struct TextId {
     inner: usize,
 }
 
+/// Namespace for the font and image keys generated in headless mode, chosen so that
+/// it doesn't collide with the namespaces that the RenderApi hands out
+const HEADLESS_ID_NAMESPACE: IdNamespace = IdNamespace(::std::u32::MAX);
+
+static HEADLESS_RESOURCE_KEY_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn new_headless_resource_key() -> u32 {
+    HEADLESS_RESOURCE_KEY_COUNTER.fetch_add(1, Ordering::SeqCst) as u32
+}
+
+pub(crate) fn generate_font_key(fake_display: &Option<FakeDisplay>) -> FontKey {
+    match fake_display {
+        Some(display) => display.render_api.generate_font_key(),
+        None => FontKey::new(HEADLESS_ID_NAMESPACE, new_headless_resource_key()),
+    }
+}
+
+pub(crate) fn generate_font_instance_key(fake_display: &Option<FakeDisplay>) -> FontInstanceKey {
+    match fake_display {
+        Some(display) => display.render_api.generate_font_instance_key(),
+        None => FontInstanceKey::new(HEADLESS_ID_NAMESPACE, new_headless_resource_key()),
+    }
+}
+
+pub(crate) fn generate_image_key(fake_display: &Option<FakeDisplay>) -> ImageKey {
+    match fake_display {
+        Some(display) => display.render_api.generate_image_key(),
+        None => ImageKey::new(HEADLESS_ID_NAMESPACE, new_headless_resource_key()),
+    }
+}
+
 static IMAGE_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 /// A unique ID by which an image can be uniquely identified
@@ -198,7 +252,10 @@ impl FontSource {
                 .map_err(|e| FontReloadError::Io(e, file_path.clone()))
                 .map(|f| (f, 0))
             },
-            System(id) => load_system_font(id).ok_or(FontReloadError::FontNotFound(id.clone())),
+            System(id) => {
+                load_system_font(id, StyleFontWeight::default(), StyleFontStyle::default())
+                .ok_or(FontReloadError::FontNotFound(id.clone()))
+            },
         }
     }
 }
@@ -280,10 +337,23 @@ impl AppResources {
 
     /// Creates a new renderer (the renderer manages the resources and is therefore tied to the resources).
     #[must_use] pub(crate) fn new(app_config: &AppConfig) -> Result<Self, WindowCreateError> {
-        Ok(Self {
-            fake_display: FakeDisplay::new(app_config.renderer_type)?,
+        let fake_display = FakeDisplay::new(app_config.renderer_type)?;
+        let mut resources = Self::new_headless();
+        resources.fake_display = Some(fake_display);
+        Ok(resources)
+    }
+
+    /// Creates the resources without a renderer - fonts and images still get loaded
+    /// (text layout needs the font metrics), but nothing gets submitted to WebRender.
+    pub(crate) fn new_headless() -> Self {
+        Self {
+            fake_display: None,
             css_ids_to_image_ids: FastHashMap::default(),
             css_ids_to_font_ids: FastHashMap::default(),
+            font_families: FastHashMap::default(),
+            css_font_faces: FastHashSet::default(),
+            system_font_families: FastHashMap::default(),
+            fallback_font_families: get_default_fallback_font_families(),
             images: FastHashMap::default(),
             raw_images: FastHashMap::default(),
             fonts: FastHashMap::default(),
@@ -293,9 +363,25 @@ impl AppResources {
             pending_frame_image_keys: FastHashMap::default(),
             last_frame_font_keys: FastHashMap::default(),
             pending_frame_font_keys: FastHashMap::default(),
+            missing_glyphs_cache: MissingGlyphsCache::default(),
+            headless_images: FastHashMap::default(),
             text_cache: TextCache::default(),
-            clipboard: SystemClipboard::new().unwrap(),
-        })
+            clipboard: SystemClipboard::new().ok(),
+        }
+    }
+
+    /// Returns whether the resources were created without a renderer, see `new_headless()`
+    pub fn is_headless(&self) -> bool {
+        self.fake_display.is_none()
+    }
+
+    /// Returns the renderer, or `None` for headless apps (which can't open windows)
+    pub(crate) fn fake_display(&self) -> Option<&FakeDisplay> {
+        self.fake_display.as_ref()
+    }
+
+    pub(crate) fn fake_display_mut(&mut self) -> Option<&mut FakeDisplay> {
+        self.fake_display.as_mut()
     }
 
     /// Returns the IDs of all currently loaded fonts in `self.font_data`
@@ -351,6 +437,7 @@ impl AppResources {
     pub fn delete_image(&mut self, image_id: &ImageId) {
         self.images.remove(image_id);
         self.raw_images.remove(image_id);
+        self.headless_images.remove(image_id);
     }
 
     pub fn add_css_image_id<S: Into<String>>(&mut self, css_id: S) -> ImageId {
@@ -410,6 +497,32 @@ impl AppResources {
         self.fonts.remove(id);
     }
 
+    /// Adds a face (one weight / style combination) to a font family, which is then used
+    /// for all text with a matching `font-family`, `font-weight` and `font-style`, i.e.:
+    ///
+    /// ```rust,ignore
+    /// app_resources.add_font_face("Roboto", StyleFontWeight::BOLD, StyleFontStyle::Normal, FontSource::Embedded(ROBOTO_BOLD));
+    /// ```
+    ///
+    /// A face with the same weight and style as an existing face of the family replaces it.
+    pub fn add_font_face<S: Into<String>>(&mut self, family: S, weight: StyleFontWeight, style: StyleFontStyle, font_source: FontSource) -> FontId {
+        let font_id = FontId::new();
+        self.add_font(font_id, font_source);
+        let faces = self.font_families.entry(family.into()).or_insert_with(|| Vec::new());
+        faces.retain(|(face_weight, face_style, _)| *face_weight != weight || *face_style != style);
+        faces.push((weight, style, font_id));
+        font_id
+    }
+
+    /// Returns the face of the font family that matches the weight and style best or
+    /// `None` if no faces were added to the family
+    pub fn get_font_face(&self, family: &str, weight: StyleFontWeight, style: StyleFontStyle) -> Option<FontId> {
+        use azul_css::match_font_face;
+        let faces = self.font_families.get(family)?;
+        let face_styles = faces.iter().map(|(face_weight, face_style, _)| (*face_weight, *face_style)).collect::<Vec<_>>();
+        match_font_face(&face_styles, weight, style).map(|idx| faces[idx].2)
+    }
+
     // -- TextId cache
 
     /// Adds a string to the internal text cache, but only store it as a string,
@@ -436,21 +549,166 @@ impl AppResources {
 
     /// Returns the contents of the system clipboard
     pub fn get_clipboard_string(&self) -> Result<String, ClipboardError> {
-        self.clipboard.get_string_contents()
+        self.clipboard.as_ref().ok_or(ClipboardError::Unimplemented)?.get_string_contents()
     }
 
     /// Sets the contents of the system clipboard - currently only strings are supported
     pub fn set_clipboard_string<S: Into<String>>(&mut self, contents: S) -> Result<(), ClipboardError> {
-        self.clipboard.set_string_contents(contents.into())
+        self.clipboard.as_mut().ok_or(ClipboardError::Unimplemented)?.set_string_contents(contents.into())
     }
 
     pub(crate) fn get_loaded_font(&self, font_id: &ImmediateFontId) -> Option<&LoadedFont> {
         self.currently_registered_fonts.get(font_id)
     }
 
+    /// Returns the decoded pixels of an image that was used in headless mode
+    pub(crate) fn get_headless_image(&self, image_id: &ImageId) -> Option<&(ImageDescriptor, ImageData)> {
+        self.headless_images.get(image_id)
+    }
+
+    /// Sets the font families that are used for the characters that the font of a text doesn't
+    /// contain, in order of preference. By default, the fallback fonts are fonts that are usually
+    /// installed on the platform and contain emoji, CJK, arabic and devanagari characters.
+    pub fn set_fallback_font_families(&mut self, families: Vec<CssFontId>) {
+        self.fallback_font_families = families;
+    }
+
+    pub fn get_fallback_font_families(&self) -> &[CssFontId] {
+        &self.fallback_font_families
+    }
+
+    /// Returns whether the family has a font: a family added via `add_font_face` or `@font-face`,
+    /// a font added via `add_css_font_id` or a font that is installed on the system
+    fn is_font_family_available(&self, family: &str) -> bool {
+        self.font_families.contains_key(family) ||
+        self.css_ids_to_font_ids.contains_key(family) ||
+        is_generic_font_family(family) ||
+        self.system_font_families.get(family).cloned().unwrap_or(false)
+    }
+
+    /// Returns the first family of the `font-family` list of the node that has a font
+    /// (see `is_font_family_available`). Falls back to the default font (`sans-serif`).
+    pub(crate) fn resolve_font_family<'a>(&self, rect_style: &'a RectStyle) -> &'a str {
+        ui_solver::get_font_families(rect_style)
+            .find(|family| self.is_font_family_available(family))
+            .unwrap_or(ui_solver::DEFAULT_FONT_ID)
+    }
+
+    /// Returns the available fallback fonts that were loaded for the weight, style and size
+    /// of the node, in the order of the fallback font families, together with their family
+    pub(crate) fn get_loaded_fallback_fonts<'a>(&'a self, rect_style: &RectStyle, font_size: Au) -> Vec<(&'a str, &'a LoadedFont, FontInstanceKey)> {
+        let weight = ui_solver::get_font_weight(rect_style);
+        let style = ui_solver::get_font_style(rect_style);
+        self.fallback_font_families.iter()
+            .filter(|family| self.is_font_family_available(family))
+            .filter_map(|family| {
+                let loaded_font = self.get_loaded_font(&self.get_immediate_font_id(family, weight, style))?;
+                let font_instance_key = loaded_font.font_instances.get(&font_size)?;
+                Some((family.as_str(), loaded_font, *font_instance_key))
+            })
+            .collect()
+    }
+
+    /// Returns the font of a (resolved) font family: the face that matches the weight
+    /// and style best, the font added via `add_css_font_id` or the system font
+    pub(crate) fn get_immediate_font_id(&self, family: &str, weight: StyleFontWeight, style: StyleFontStyle) -> ImmediateFontId {
+        if let Some(font_id) = self.get_font_face(family, weight, style) {
+            return ImmediateFontId::Resolved(font_id);
+        }
+        match self.css_ids_to_font_ids.get(family) {
+            Some(font_id) => ImmediateFontId::Resolved(*font_id),
+            None => ImmediateFontId::Unresolved(family.to_string(), weight, style),
+        }
+    }
+
+    /// Returns the font that is used for the text of a node with the given style
+    pub(crate) fn get_font_id_for_style(&self, rect_style: &RectStyle) -> ImmediateFontId {
+        let family = self.resolve_font_family(rect_style);
+        self.get_immediate_font_id(family, ui_solver::get_font_weight(rect_style), ui_solver::get_font_style(rect_style))
+    }
+
+    /// Returns the bytes of the font that is used for the given family (i.e. `"sans-serif"`), weight and style
+    pub(crate) fn get_font_bytes_for_face(&self, family: &str, weight: StyleFontWeight, style: StyleFontStyle) -> Option<Result<(Vec<u8>, i32), FontReloadError>> {
+        self.get_immediate_font_bytes(&self.get_immediate_font_id(family, weight, style))
+    }
+
+    fn get_immediate_font_bytes(&self, font_id: &ImmediateFontId) -> Option<Result<(Vec<u8>, i32), FontReloadError>> {
+        match font_id {
+            ImmediateFontId::Resolved(font_id) => self.get_font_bytes(font_id),
+            ImmediateFontId::Unresolved(family, weight, style) => {
+                Some(load_system_font(family, *weight, *style).ok_or_else(|| FontReloadError::FontNotFound(family.clone())))
+            },
+        }
+    }
+
+    /// Adds the faces of the `@font-face` rules of the CSS that weren't added yet -
+    /// of the sources of a rule, the first file that exists or installed font is used
+    fn add_css_font_faces(&mut self, css: &Css) {
+        for font_face in css.get_font_faces() {
+
+            if self.css_font_faces.contains(font_face) {
+                continue;
+            }
+
+            let font_source = font_face.sources.iter().filter_map(|source| match source {
+                CssFontFaceSource::Url(path) if path.exists() => Some(FontSource::File(path.clone())),
+                CssFontFaceSource::Local(name) if query_system_font(name) => Some(FontSource::System(name.clone())),
+                _ => None,
+            }).next();
+
+            match font_source {
+                Some(font_source) => {
+                    self.add_font_face(font_face.family.clone(), font_face.weight, font_face.style, font_source);
+                },
+                None => {
+                    #[cfg(feature = "logging")] {
+                        warn!("Could not find any source of the @font-face rule for the font family \"{}\"", font_face.family);
+                    }
+                },
+            }
+
+            self.css_font_faces.insert(font_face.clone());
+        }
+    }
+
+    /// Queries which families of the `font-family` lists of the text nodes are installed
+    /// on the system, up to the first family that has a font
+    fn query_system_fonts<'a, T: Layout>(&mut self, display_list: &DisplayList<'a, T>) {
+        use dom::NodeType::*;
+
+        for family in &self.fallback_font_families {
+            if !self.system_font_families.contains_key(family) {
+                self.system_font_families.insert(family.clone(), query_system_font(family));
+            }
+        }
+
+        for node_id in display_list.rectangles.linear_iter() {
+
+            match display_list.ui_descr.ui_descr_arena.node_data[node_id].node_type {
+                Text(_) | Label(_) => { },
+                _ => continue,
+            }
+
+            for family in ui_solver::get_font_families(&display_list.rectangles[node_id].style) {
+                if self.font_families.contains_key(family) || self.css_ids_to_font_ids.contains_key(family) || is_generic_font_family(family) {
+                    break;
+                }
+                let is_installed = *self.system_font_families
+                    .entry(family.to_string())
+                    .or_insert_with(|| query_system_font(family));
+                if is_installed {
+                    break;
+                }
+            }
+        }
+    }
+
     /// Scans the DisplayList for new images and fonts. After this call, the RenderApi is
     /// guaranteed to know about all FontKeys and FontInstanceKey
     pub(crate) fn add_fonts_and_images<T: Layout>(&mut self, display_list: &DisplayList<T>) {
+        self.add_css_font_faces(&display_list.ui_descr.css);
+        self.query_system_fonts(display_list);
+
         let font_keys = scan_ui_description_for_font_keys(&self, display_list);
         let image_keys = scan_ui_description_for_image_keys(&self, display_list);
 
@@ -460,6 +718,13 @@ impl AppResources {
         println!("adding fonts: {}", add_font_resource_updates.len());
 
         add_resources(self, add_font_resource_updates, add_image_resource_updates);
+
+        // Whether a text needs fallback fonts can only be determined once its font is loaded
+        let mut missing_glyphs_cache = mem::replace(&mut self.missing_glyphs_cache, MissingGlyphsCache::default());
+        let fallback_font_keys = scan_ui_description_for_fallback_font_keys(&self, display_list, &mut missing_glyphs_cache);
+        self.missing_glyphs_cache = missing_glyphs_cache;
+        let add_fallback_font_resource_updates = build_add_font_resource_updates(self, &fallback_font_keys);
+        add_resources(self, add_fallback_font_resource_updates, Vec::new());
     }
 
     /// To be called at the end of a frame (after the UI has rendered):
@@ -479,13 +744,15 @@ impl AppResources {
         self.last_frame_font_keys = self.pending_frame_font_keys.clone();
         self.pending_frame_font_keys.clear();
         self.pending_frame_image_keys.clear();
+        self.missing_glyphs_cache.end_frame();
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) enum ImmediateFontId {
     Resolved(FontId),
-    Unresolved(CssFontId),
+    /// System font of the family, with the requested weight and style
+    Unresolved(CssFontId, StyleFontWeight, StyleFontStyle),
 }
 
 /// Scans the display list for all font IDs + their font size
@@ -495,7 +762,6 @@ fn scan_ui_description_for_font_keys<'a, T: Layout>(
 ) -> FastHashMap<ImmediateFontId, FastHashSet<Au>>
 {
     use dom::NodeType::*;
-    use ui_solver;
 
     let mut font_keys = FastHashMap::default();
 
@@ -506,11 +772,7 @@ fn scan_ui_description_for_font_keys<'a, T: Layout>(
 
         match node_data.node_type {
             Text(_) | Label(_) => {
-                let css_font_id = ui_solver::get_font_id(&display_rect.style);
-                let font_id = match app_resources.css_ids_to_font_ids.get(css_font_id) {
-                    Some(s) => ImmediateFontId::Resolved(*s),
-                    None => ImmediateFontId::Unresolved(css_font_id.to_string()),
-                };
+                let font_id = app_resources.get_font_id_for_style(&display_rect.style);
                 let font_size = ui_solver::get_font_size(&display_rect.style);
                 font_keys
                     .entry(font_id)
@@ -524,6 +786,106 @@ fn scan_ui_description_for_font_keys<'a, T: Layout>(
     font_keys
 }
 
+/// Caches whether a text contains characters that its font doesn't contain - finding that
+/// out requires shaping the text. Texts that weren't displayed during the last frame are removed.
+#[derive(Debug, Default, Clone)]
+struct MissingGlyphsCache {
+    last_frame: FastHashMap<ImmediateFontId, FastHashMap<String, bool>>,
+    pending_frame: FastHashMap<ImmediateFontId, FastHashMap<String, bool>>,
+}
+
+impl MissingGlyphsCache {
+
+    /// Returns whether the text has missing glyphs in the font, only calls
+    /// `has_missing_glyphs` if the text wasn't displayed in the last frame
+    fn get_or_insert_with<F: FnOnce() -> bool>(&mut self, font_id: &ImmediateFontId, text: &str, has_missing_glyphs: F) -> bool {
+
+        if let Some(cached) = self.pending_frame.get(font_id).and_then(|texts| texts.get(text)) {
+            return *cached;
+        }
+
+        let (text, missing_glyphs) = match self.last_frame.get_mut(font_id).and_then(|texts| texts.remove_entry(text)) {
+            Some(cached) => cached,
+            None => (text.to_string(), has_missing_glyphs()),
+        };
+
+        self.pending_frame.entry(font_id.clone()).or_insert_with(|| FastHashMap::default()).insert(text, missing_glyphs);
+        missing_glyphs
+    }
+
+    /// To be called at the end of a frame: forgets all texts that weren't displayed during the frame
+    fn end_frame(&mut self) {
+        self.last_frame = mem::replace(&mut self.pending_frame, FastHashMap::default());
+    }
+}
+
+/// Scans the display list for text nodes that contain characters which their font doesn't
+/// contain and returns the fallback fonts + the font sizes of these nodes
+fn scan_ui_description_for_fallback_font_keys<'a, T: Layout>(
+    app_resources: &AppResources,
+    display_list: &DisplayList<'a, T>,
+    missing_glyphs_cache: &mut MissingGlyphsCache,
+) -> FastHashMap<ImmediateFontId, FastHashSet<Au>>
+{
+    use dom::NodeType::*;
+    use text_shaping::{HbFont, HbScaledFont, has_missing_glyphs};
+
+    let mut font_keys = FastHashMap::default();
+
+    let fallback_font_families = app_resources.fallback_font_families.iter()
+        .filter(|family| app_resources.is_font_family_available(family))
+        .collect::<Vec<_>>();
+
+    if fallback_font_families.is_empty() {
+        return font_keys;
+    }
+
+    for node_id in display_list.rectangles.linear_iter() {
+
+        let node_data = &display_list.ui_descr.ui_descr_arena.node_data[node_id];
+        let display_rect = &display_list.rectangles[node_id];
+
+        let text = match &node_data.node_type {
+            Label(string) => string.as_str(),
+            Text(text_id) => match app_resources.get_text(text_id) {
+                Some(words) => words.get_str(),
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        let font_id = app_resources.get_font_id_for_style(&display_rect.style);
+        let loaded_font = match app_resources.get_loaded_font(&font_id) {
+            Some(s) => s,
+            None => continue,
+        };
+
+        let font_size = ui_solver::get_font_size(&display_rect.style);
+
+        let text_has_missing_glyphs = missing_glyphs_cache.get_or_insert_with(&font_id, text, || {
+            let hb_font = HbFont::from_bytes(&loaded_font.font_bytes, loaded_font.font_index as u32);
+            let hb_scaled_font = HbScaledFont::from_font(&hb_font, font_size.0.to_pixels());
+            has_missing_glyphs(&hb_scaled_font, text)
+        });
+
+        if !text_has_missing_glyphs {
+            continue;
+        }
+
+        let weight = ui_solver::get_font_weight(&display_rect.style);
+        let style = ui_solver::get_font_style(&display_rect.style);
+
+        for family in &fallback_font_families {
+            font_keys
+                .entry(app_resources.get_immediate_font_id(family, weight, style))
+                .or_insert_with(|| FastHashSet::default())
+                .insert(ui_solver::font_size_to_au(font_size));
+        }
+    }
+
+    font_keys
+}
+
 /// Scans the display list for all image keys
 fn scan_ui_description_for_image_keys<'a, T: Layout>(
     app_resources: &AppResources,
@@ -576,7 +938,7 @@ fn build_add_font_resource_updates(
 
             if !font_instance_key_exists {
 
-                let font_instance_key = app_resources.fake_display.render_api.generate_font_instance_key();
+                let font_instance_key = generate_font_instance_key(&app_resources.fake_display);
 
                 app_resources.pending_frame_font_keys
                     .entry($font_id)
@@ -636,20 +998,13 @@ fn build_add_font_resource_updates(
                 }
             },
             None => {
-                use self::ImmediateFontId::*;
-
                 // If there is no font key, that means there's also no font instances
-                let font_source = match im_font_id {
-                    Resolved(font_id) => {
-                        match app_resources.fonts.get(font_id) {
-                            Some(s) => s.clone(),
-                            None => continue,
-                        }
-                    },
-                    Unresolved(css_font_id) => FontSource::System(css_font_id.clone()),
+                let font_bytes = match app_resources.get_immediate_font_bytes(im_font_id) {
+                    Some(s) => s,
+                    None => continue,
                 };
 
-                let (font_bytes, font_index) = match font_source.get_bytes() {
+                let (font_bytes, font_index) = match font_bytes {
                     Ok(o) => o,
                     Err(e) => {
                         #[cfg(feature = "logging")] {
@@ -660,7 +1015,7 @@ fn build_add_font_resource_updates(
                 };
 
                 if !font_sizes.is_empty() {
-                    let font_key = app_resources.fake_display.render_api.generate_font_key();
+                    let font_key = generate_font_key(&app_resources.fake_display);
                     resource_updates.push(ResourceUpdate::AddFont(AddFont::Raw(font_key, font_bytes.clone(), font_index as u32)));
 
                     for font_size in font_sizes {
@@ -698,7 +1053,8 @@ fn build_add_image_resource_updates(
     let raw_images = &mut app_resources.raw_images;
     let images = &mut app_resources.images;
     let currently_registered_images = &mut app_resources.currently_registered_images;
-    let render_api = &app_resources.fake_display.render_api;
+    let headless_images = &mut app_resources.headless_images;
+    let fake_display = &app_resources.fake_display;
 
     for image_id in images_in_dom.iter().cloned().filter(|id| {
         !currently_registered_images.contains_key(id)
@@ -732,12 +1088,16 @@ fn build_add_image_resource_updates(
                     }
                 };
 
-                let image_key = render_api.generate_image_key();
+                let image_key = generate_image_key(fake_display);
                 pending_frame_image_keys.insert(image_id, ImageInfo {
                     key: image_key,
                     descriptor: image_descriptor,
                 });
 
+                if fake_display.is_none() {
+                    headless_images.insert(image_id, (image_descriptor, decoded_image_data.clone()));
+                }
+
                 resource_updates.push(ResourceUpdate::AddImage(
                     AddImage { key: image_key, descriptor: image_descriptor, data: decoded_image_data, tiling: None }
                 ));
@@ -760,10 +1120,14 @@ fn build_add_image_resource_updates(
                         allow_mipmaps
                     );
                     let data = ImageData::new(pixels);
-                    let key = render_api.generate_image_key();
+                    let key = generate_image_key(fake_display);
 
                     pending_frame_image_keys.insert(image_id, ImageInfo { key, descriptor });
 
+                    if fake_display.is_none() {
+                        headless_images.insert(image_id, (descriptor, data.clone()));
+                    }
+
                     resource_updates.push(ResourceUpdate::AddImage(
                         AddImage { key, descriptor, data, tiling: None }
                     ));
@@ -787,10 +1151,12 @@ fn add_resources(
 ) {
     let mut merged_resource_updates = add_font_resources;
     merged_resource_updates.extend(add_image_resources.into_iter());
-    if !merged_resource_updates.is_empty() {
-        app_resources.fake_display.render_api.update_resources(merged_resource_updates);
-        // Assure that the AddFont / AddImage updates get processed immediately
-        app_resources.fake_display.render_api.flush_scene_builder();
+    if let Some(fake_display) = &app_resources.fake_display {
+        if !merged_resource_updates.is_empty() {
+            fake_display.render_api.update_resources(merged_resource_updates);
+            // Assure that the AddFont / AddImage updates get processed immediately
+            fake_display.render_api.flush_scene_builder();
+        }
     }
 
     let currently_registered_images = &mut app_resources.currently_registered_images;
@@ -877,10 +1243,13 @@ fn delete_resources(
     mut delete_font_resources: Vec<ResourceUpdate>,
     mut delete_image_resources: Vec<ResourceUpdate>,
 ) {
-    let render_api = &app_resources.fake_display.render_api;
+    let fake_display = match &app_resources.fake_display {
+        Some(s) => s,
+        None => return,
+    };
     delete_font_resources.append(&mut delete_image_resources);
     if !delete_font_resources.is_empty() {
-        render_api.update_resources(delete_font_resources);
+        fake_display.render_api.update_resources(delete_font_resources);
     }
 }
 
@@ -896,11 +1265,41 @@ fn decode_image_data<I: Into<Vec<u8>>>(image_data: I)
     Ok(prepare_image(decoded)?)
 }
 
-/// Returns the font + the index of the font (in case the font is a collection)
-fn load_system_font(id: &str) -> Option<(Vec<u8>, i32)> {
+/// Fonts that contain emoji and the characters of non-latin scripts, in order of preference
+fn get_default_fallback_font_families() -> Vec<CssFontId> {
+
+    #[cfg(target_os = "linux")]
+    let families = ["Noto Sans", "Noto Sans CJK SC", "Noto Sans Arabic", "Noto Sans Devanagari", "Noto Color Emoji", "DejaVu Sans"];
+    #[cfg(target_os = "windows")]
+    let families = ["Microsoft YaHei", "Yu Gothic", "Malgun Gothic", "Segoe UI", "Nirmala UI", "Segoe UI Emoji", "Segoe UI Symbol"];
+    #[cfg(target_os = "macos")]
+    let families = ["PingFang SC", "Hiragino Sans", "Apple SD Gothic Neo", "Geeza Pro", "Kohinoor Devanagari", "Apple Color Emoji"];
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    let families: [&str; 0] = [];
+
+    families.iter().map(|family| family.to_string()).collect()
+}
+
+/// Generic font families are mapped to the native fonts of the platform by `load_system_font`
+fn is_generic_font_family(family: &str) -> bool {
+    match family {
+        "serif" | "sans-serif" | "monospace" | "fantasy" => true,
+        _ => false,
+    }
+}
+
+/// Returns whether any font of the family is installed on the system
+fn query_system_font(family: &str) -> bool {
     use font_loader::system_fonts::{self, FontPropertyBuilder};
+    !system_fonts::query_specific(&mut FontPropertyBuilder::new().family(family).build()).is_empty()
+}
 
-    let font_builder = match id {
+/// Returns the font + the index of the font (in case the font is a collection).
+/// If the family has no face with the weight and style, the regular face is returned.
+fn load_system_font(id: &str, weight: StyleFontWeight, style: StyleFontStyle) -> Option<(Vec<u8>, i32)> {
+    use font_loader::system_fonts::{self, FontPropertyBuilder};
+
+    let font_builder = || match id {
         "monospace" => {
             #[cfg(target_os = "linux")] {
                 let native_monospace_font = linux_get_native_font(LinuxNativeFontType::Monospace);
@@ -929,7 +1328,21 @@ fn load_system_font(id: &str) -> Option<(Vec<u8>, i32)> {
         other => FontPropertyBuilder::new().family(other)
     };
 
-    system_fonts::get(&font_builder.build())
+    if !weight.is_bold() && style == StyleFontStyle::Normal {
+        return system_fonts::get(&font_builder().build());
+    }
+
+    let mut styled_font_builder = font_builder();
+    if weight.is_bold() {
+        styled_font_builder = styled_font_builder.bold();
+    }
+    match style {
+        StyleFontStyle::Normal => { },
+        StyleFontStyle::Italic => styled_font_builder = styled_font_builder.italic(),
+        StyleFontStyle::Oblique => styled_font_builder = styled_font_builder.oblique(),
+    }
+
+    system_fonts::get(&styled_font_builder.build()).or_else(|| system_fonts::get(&font_builder().build()))
 }
 
 /// Return the native fonts
@@ -1118,9 +1531,26 @@ fn premultiply(data: &mut [u8]) {
     }
 }
 
+#[test]
+fn test_missing_glyphs_cache() {
+    let font_id = ImmediateFontId::Resolved(FontId::new());
+    let mut cache = MissingGlyphsCache::default();
+
+    assert!(cache.get_or_insert_with(&font_id, "Hello", || true));
+    // The text is only shaped once per frame and is kept while it is displayed
+    assert!(cache.get_or_insert_with(&font_id, "Hello", || panic!("text was shaped twice")));
+    cache.end_frame();
+    assert!(cache.get_or_insert_with(&font_id, "Hello", || panic!("text was shaped twice")));
+
+    // Texts that weren't displayed in a frame are removed
+    cache.end_frame();
+    cache.end_frame();
+    assert!(!cache.get_or_insert_with(&font_id, "Hello", || false));
+}
+
 #[test]
 fn test_premultiply() {
     let mut color = [255, 0, 0, 127];
     premultiply(&mut color);
     assert_eq!(color, [127, 0, 0, 127]);
-}
\ No newline at end of file
+}
\ No newline at end of file
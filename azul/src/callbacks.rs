@@ -175,6 +175,9 @@ pub struct CallbackInfo<'a, T: 'a + Layout> {
     pub cursor_relative_to_item: Option<(f32, f32)>,
     /// The (x, y) position of the mouse cursor, **relative to top left of the window**.
     pub cursor_in_viewport: Option<(f32, f32)>,
+    /// The dragged node and its payload if a drag & drop operation is in progress
+    /// (or if the node was just dropped), see `get_drag_data` and `set_drag_payload`
+    pub(crate) drag_data: Option<DragData>,
 }
 
 impl<'a, T: 'a + Layout> Clone for CallbackInfo<'a, T> {
@@ -187,6 +190,7 @@ impl<'a, T: 'a + Layout> Clone for CallbackInfo<'a, T> {
             hit_test_items: self.hit_test_items,
             cursor_relative_to_item: self.cursor_relative_to_item,
             cursor_in_viewport: self.cursor_in_viewport,
+            drag_data: self.drag_data.clone(),
         }
     }
 }
@@ -201,6 +205,7 @@ impl<'a, T: 'a + Layout> fmt::Debug for CallbackInfo<'a, T> {
             hit_test_items: {:?}, \
             cursor_relative_to_item: {:?}, \
             cursor_in_viewport: {:?}, \
+            drag_data: {:?}, \
         }}",
             self.focus,
             self.window_id,
@@ -209,6 +214,7 @@ impl<'a, T: 'a + Layout> fmt::Debug for CallbackInfo<'a, T> {
             self.hit_test_items,
             self.cursor_relative_to_item,
             self.cursor_in_viewport,
+            self.drag_data,
         )
     }
 }
@@ -492,4 +498,32 @@ impl<'a, T: 'a + Layout> CallbackInfo<'a, T> {
     pub fn clear_focus(&mut self) {
         self.focus = Some(FocusTarget::NoFocus);
     }
+}
+
+/// Data of the current drag & drop operation
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DragData {
+    /// The (draggable) node that is being dragged
+    pub source_node: NodeId,
+    /// Custom data attached to the drag operation, i.e. the ID of
+    /// the list item or card that is being moved
+    pub payload: Option<String>,
+}
+
+impl<'a, T: 'a + Layout> CallbackInfo<'a, T> {
+
+    /// Returns the data of the current drag & drop operation or `None`
+    /// if no node is currently being dragged.
+    pub fn get_drag_data(&self) -> Option<&DragData> {
+        self.drag_data.as_ref()
+    }
+
+    /// Attaches a payload to the current drag & drop operation, so that the
+    /// `On::Drop` callback of the drop target knows what was dropped on it.
+    /// Does nothing if no node is currently being dragged.
+    pub fn set_drag_payload<S: Into<String>>(&mut self, payload: S) {
+        if let Some(drag_data) = self.drag_data.as_mut() {
+            drag_data.payload = Some(payload.into());
+        }
+    }
 }
\ No newline at end of file
@@ -19,8 +19,10 @@
 //! | `cursor`                                           |              |             |            |                  |
 //! | `width`, `min-width`, `max-width`                  |              |             |            |                  |
 //! | `height`, `min-height`, `max-height`               |              |             |            |                  |
+//! | `box-sizing`                                       |              |             |            |                  |
 //! | `position`                                         |              |             |            |                  |
 //! | `top`, `right`, `left`, `bottom`                   |              |             |            |                  |
+//! | `display`, `visibility`                            |              |             |            |                  |
 //! | `flex-wrap`                                        |              |             |            |                  |
 //! | `flex-direction`                                   |              |             |            |                  |
 //! | `flex-grow`                                        |              |             |            |                  |
@@ -28,6 +30,8 @@
 //! | `justify-content`                                  |              |             |            |                  |
 //! | `align-items`                                      |              |             |            |                  |
 //! | `align-content`                                    |              |             |            |                  |
+//! | `flex-basis`, `order`, `align-self`                |              |             |            |                  |
+//! | `row-gap`, `column-gap`                            |              |             |            |                  |
 //! | `overflow`, `overflow-x`, `overflow-y`             |              |             |            |                  |
 //! | `padding`, `-top`, `-left`, `-right`, `-bottom`    |              |             |            |                  |
 //! | `margin`,  `-top`, `-left`, `-right`, `-bottom`    |              |             |            |                  |
@@ -38,6 +42,8 @@
 use std::time::Duration;
 #[cfg(debug_assertions)]
 use std::path::PathBuf;
+#[cfg(feature = "css_parser")]
+use std::path::Path;
 
 pub use azul_css::*;
 #[cfg(feature = "css_parser")]
@@ -54,7 +60,7 @@ pub mod native_style {
 }
 
 #[cfg(feature = "css_parser")]
-use azul_css_parser::{self, CssParseError};
+use azul_css_parser::{self, CssParseError, CssFileError};
 
 /// Returns a style with the native appearance for the operating system. Convenience wrapper
 /// for functionality from the the `azul-native-style` crate.
@@ -69,6 +75,13 @@ pub fn from_str(input: &str) -> Result<Css, CssParseError> {
     azul_css_parser::new_from_str(input)
 }
 
+/// Loads a CSS stylesheet from a file and resolves its `@import` rules.
+/// Convenience wrapper for `azul-css-parser::new_from_file`.
+#[cfg(feature = "css_parser")]
+pub fn from_file<P: AsRef<Path>>(file_path: P) -> Result<Css, CssFileError> {
+    azul_css_parser::new_from_file(file_path)
+}
+
 /// Appends a custom stylesheet to `css::native()`.
 #[cfg(all(feature = "css_parser", feature = "native_style"))]
 pub fn override_native(input: &str) -> Result<Css, CssParseError> {
@@ -1,9 +1,7 @@
-#![allow(unused_variables)]
-
-use std::{collections::BTreeMap, marker::PhantomData};
+use std::marker::PhantomData;
 use {
-    id_tree::{NodeId, NodeHierarchy},
-    dom::{Dom, NodeData},
+    id_tree::{NodeId, NodeHierarchy, Arena},
+    dom::{Dom, NodeData, NodeType},
     traits::Layout,
 };
 
@@ -30,12 +28,26 @@ pub(crate) struct DomNode<F: FrameMarker> {
     pub(crate) marker: PhantomData<F>,
 }
 
+impl<F: FrameMarker> DomNode<F> {
+    #[inline]
+    fn new(id: NodeId) -> Self {
+        Self { id, marker: PhantomData }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) enum DomChange {
     Added(DomRange<NewState>),
     Removed(DomRange<OldState>),
 }
 
+/// Difference between the DOM of the last frame and the DOM of the current frame.
+///
+/// Nodes are matched by their node type, ids, classes and attributes (the only
+/// properties of a node that the CSS engine can select on) - the resulting diff is used to only restyle
+/// the subtrees of the DOM that actually changed and to re-use the text shaping of unchanged nodes.
+/// The sizes and positions of the nodes are still solved for the entire DOM, since a changed node
+/// can resize its parents and move its siblings.
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DomDiff {
     /// What the actual changes nodes (not trees / subtrees) were in this diff, in order of appearance
@@ -44,17 +56,32 @@ pub struct DomDiff {
     pub(crate) only_replace_images: Vec<NodeId>,
     /// Which nodes / subtrees need re-styling?
     pub(crate) need_restyling: Vec<DomRange<NewState>>,
-    /// Which nodes need a re-layout?
-    /// For example A would be:
+    /// Which nodes have new content? (i.e. their text has to be re-shaped
+    /// or the image dimensions have to be queried again)
     pub(crate) need_relayout: Vec<DomRange<NewState>>,
+    /// For each node in the new DOM, the node in the old DOM that it was
+    /// matched to - `None` if the node was added in this frame
+    pub(crate) old_node_ids: Vec<Option<DomNode<OldState>>>,
 }
 
-type TreeDepth = usize;
-type ParentNodeId = NodeId;
-type LeafNodeId = NodeId;
-
 impl<F: FrameMarker + PartialEq> DomRange<F> {
 
+    #[inline]
+    fn new(start: NodeId, end: NodeId) -> Self {
+        Self { start: DomNode::new(start), end: DomNode::new(end) }
+    }
+
+    /// Returns the range spanning the node and all of its children
+    fn subtree(hierarchy: &NodeHierarchy, node_id: NodeId) -> Self {
+        // Subtrees are stored continuously in the arena, so the last node
+        // of the subtree is the last child of the last child (etc.)
+        let mut end = node_id;
+        while let Some(last_child) = hierarchy[end].last_child {
+            end = last_child;
+        }
+        Self::new(node_id, end)
+    }
+
     /// Is `other` a subtree of `self`? - Assumes that the DOM was
     /// constructed in a linear order, i.e. the child being within
     /// the parents start / end bounds
@@ -70,78 +97,416 @@ impl<F: FrameMarker + PartialEq> DomRange<F> {
     }
 }
 
-// In order to test two DOM nodes for "equality", you'd need to
-// test if the node type, the classes and the ids are the same.
-// The rest of the attributes can be ignored, since they are not
-// used by the CSS engine.
-//
-// Right now the CSS doesn't support adjacent modifiers ("+" selectors),
-// which will make this algorithm a bit more complex (but should be
-// solvable with adjacency lists).
-//
-// for each leaf node (sorted by depth):
-//     - if the node has only changed its position:
-//         - node doesn't need restyle (but may need re-layout when "+" selectors are implemented)
-//     - else insert it it
-//     - add the end added / removed nodes
-//
-// for each parent node (sorted by depth, bubble up):
-//     - if the parent has changed:
-//
-//     - ask if that child has affected that parents layout
-//          - if yes, set the parent to be restyled
-//     - ask if that child affects its siblings layout
-//          - if yes, add the siblings to the changeset
-//
-
-const NODE_CHANGED_NOTHING: u8  = 0x01;
-const NODE_CHANGED_TYPE: u8     = 0x02;
-const NODE_CHANGED_CLASSES: u8  = 0x04;
-const NODE_CHANGED_IDS: u8      = 0x08;
+const NODE_CHANGED_NOTHING: u8    = 0x00;
+const NODE_CHANGED_TYPE: u8       = 0x02;
+const NODE_CHANGED_CLASSES: u8    = 0x04;
+const NODE_CHANGED_IDS: u8        = 0x08;
+const NODE_CHANGED_ATTRIBUTES: u8 = 0x10;
+const NODE_CHANGED_CSS_VARIABLES: u8 = 0x20;
+
+/// If any of these changed, two nodes are not matched to each other in the diff
+const NODE_CHANGED_IDENTITY: u8 = NODE_CHANGED_TYPE | NODE_CHANGED_CLASSES | NODE_CHANGED_IDS | NODE_CHANGED_ATTRIBUTES;
 
+/// Compares the attributes of two nodes that the CSS engine can select on, as well as the
+/// CSS variables that are set on the node (via `Dom::with_css_variable`).
+/// Note that the node type is only compared by its CSS name (`p`, `div`, ...),
+/// i.e. changing the text of a label does not require a restyle.
 fn node_needs_restyle<T: Layout>(old: &NodeData<T>, new: &NodeData<T>) -> u8 {
     let mut result = NODE_CHANGED_NOTHING;
 
-    if old.node_type != new.node_type {
-        result &= NODE_CHANGED_TYPE;
+    if old.node_type.get_path() != new.node_type.get_path() {
+        result |= NODE_CHANGED_TYPE;
     }
 
     if old.classes != new.classes {
-        result &= NODE_CHANGED_CLASSES;
+        result |= NODE_CHANGED_CLASSES;
     }
 
     if old.ids != new.ids {
-        result &= NODE_CHANGED_CLASSES;
+        result |= NODE_CHANGED_IDS;
+    }
+
+    if old.attributes != new.attributes {
+        result |= NODE_CHANGED_ATTRIBUTES;
+    }
+
+    if old.css_variables != new.css_variables {
+        result |= NODE_CHANGED_CSS_VARIABLES;
     }
 
     result
 }
 
-fn get_leaf_nodes_by_depth<T: FrameMarker>(hierarchy: &NodeHierarchy)
--> BTreeMap<TreeDepth, BTreeMap<ParentNodeId, Vec<DomNode<T>>>>
-{
-    let mut map = BTreeMap::new();
-    let parent_nodes = hierarchy.get_parents_sorted_by_depth();
+/// Returns whether the two nodes can be matched to each other in the diff - nodes whose
+/// CSS variables changed are still the same node, but their subtree has to be restyled
+fn is_same_node<T: Layout>(old: &NodeData<T>, new: &NodeData<T>) -> bool {
+    node_needs_restyle(old, new) & NODE_CHANGED_IDENTITY == NODE_CHANGED_NOTHING
+}
+
+/// Returns whether the (sorted, non-overlapping) ranges contain the node
+fn ranges_contain<F: FrameMarker>(ranges: &[DomRange<F>], node_id: NodeId) -> bool {
+    match ranges.binary_search_by(|range| range.start.id.cmp(&node_id)) {
+        Ok(_) => true,
+        Err(0) => false,
+        Err(idx) => ranges[idx - 1].end.id >= node_id,
+    }
+}
+
+/// Matches the children of two nodes, returns the `(old, new)` index pairs of the
+/// longest common subsequence of children, in ascending order
+fn match_children<F: Fn(NodeId, NodeId) -> bool>(old: &[NodeId], new: &[NodeId], is_same_node: F) -> Vec<(usize, usize)> {
+
+    // Usually only a few children change between two frames, so strip the common
+    // prefix and suffix first, the (quadratic) LCS only runs on the nodes in between
+    let prefix = old.iter().zip(new.iter()).take_while(|&(o, n)| is_same_node(*o, *n)).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|&(o, n)| is_same_node(*o, *n)).count();
+
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut matches = (0..prefix).map(|idx| (idx, idx)).collect::<Vec<_>>();
+
+    if !old_middle.is_empty() && !new_middle.is_empty() {
+
+        let row_len = new_middle.len() + 1;
+        let mut lcs_table = vec![0_usize; (old_middle.len() + 1) * row_len];
+
+        for (i, old_child) in old_middle.iter().enumerate().rev() {
+            for (j, new_child) in new_middle.iter().enumerate().rev() {
+                lcs_table[i * row_len + j] = if is_same_node(*old_child, *new_child) {
+                    lcs_table[(i + 1) * row_len + j + 1] + 1
+                } else {
+                    lcs_table[(i + 1) * row_len + j].max(lcs_table[i * row_len + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < old_middle.len() && j < new_middle.len() {
+            if is_same_node(old_middle[i], new_middle[j]) {
+                matches.push((prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if lcs_table[(i + 1) * row_len + j] >= lcs_table[i * row_len + j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+
+    matches.extend((0..suffix).map(|idx| (old.len() - suffix + idx, new.len() - suffix + idx)));
+    matches
+}
+
+struct DiffContext<'a, T: 'a + Layout> {
+    old: &'a Arena<NodeData<T>>,
+    new: &'a Arena<NodeData<T>>,
+}
+
+impl DomDiff {
+
+    /// Diffs the DOM of the previous frame against the DOM of the current frame
+    pub fn new<T: Layout>(old: &Dom<T>, new: &Dom<T>) -> Self {
+        Self::from_arenas(&old.arena, old.root, &new.arena, new.root)
+    }
+
+    /// Returns the diff of a DOM against itself, i.e. when only the animated
+    /// properties changed and the DOM of the last frame is re-used
+    pub(crate) fn unchanged<T: Layout>(arena: &Arena<NodeData<T>>) -> Self {
+        DomDiff {
+            old_node_ids: arena.linear_iter().map(|node_id| Some(DomNode::new(node_id))).collect(),
+            .. Default::default()
+        }
+    }
+
+    pub(crate) fn from_arenas<T: Layout>(
+        old: &Arena<NodeData<T>>,
+        old_root: NodeId,
+        new: &Arena<NodeData<T>>,
+        new_root: NodeId,
+    ) -> Self {
+
+        let mut diff = DomDiff {
+            old_node_ids: vec![None; new.len()],
+            .. Default::default()
+        };
+
+        let context = DiffContext { old, new };
+
+        if old.is_empty() || new.is_empty() {
+            if !old.is_empty() {
+                diff.remove_subtree(&context, old_root);
+            }
+            if !new.is_empty() {
+                diff.add_subtree(&context, new_root, false);
+            }
+        } else if is_same_node(&old.node_data[old_root], &new.node_data[new_root]) {
+            diff.diff_matched_nodes(&context, old_root, new_root, false);
+        } else {
+            // The root node changed, so the whole DOM has to be rebuilt
+            diff.remove_subtree(&context, old_root);
+            diff.add_subtree(&context, new_root, false);
+        }
+
+        diff.need_restyling.sort();
+        diff.need_relayout.sort();
+        diff
+    }
+
+    fn remove_subtree<T: Layout>(&mut self, context: &DiffContext<T>, old_node_id: NodeId) {
+        self.changed_nodes.push(DomChange::Removed(DomRange::subtree(&context.old.node_layout, old_node_id)));
+    }
+
+    fn add_subtree<T: Layout>(&mut self, context: &DiffContext<T>, new_node_id: NodeId, parent_is_restyled: bool) {
+        let range = DomRange::subtree(&context.new.node_layout, new_node_id);
+        self.changed_nodes.push(DomChange::Added(range));
+        if !parent_is_restyled {
+            self.need_restyling.push(range);
+        }
+        self.need_relayout.push(range);
+    }
+
+    /// Diffs two nodes that have the same type, ids and classes, then diffs their children
+    fn diff_matched_nodes<T: Layout>(
+        &mut self,
+        context: &DiffContext<T>,
+        old_node_id: NodeId,
+        new_node_id: NodeId,
+        parent_is_restyled: bool,
+    ) {
+        self.old_node_ids[new_node_id.index()] = Some(DomNode::new(old_node_id));
+
+        // CSS variables are inherited, so if they changed, the whole subtree has to be restyled
+        let variables_changed = node_needs_restyle(&context.old.node_data[old_node_id], &context.new.node_data[new_node_id]) & NODE_CHANGED_CSS_VARIABLES != 0;
+        if variables_changed && !parent_is_restyled {
+            self.need_restyling.push(DomRange::subtree(&context.new.node_layout, new_node_id));
+        }
+        let parent_is_restyled = parent_is_restyled || variables_changed;
+
+        let old_node_type = &context.old.node_data[old_node_id].node_type;
+        let new_node_type = &context.new.node_data[new_node_id].node_type;
+
+        // Same kind of node, but the content changed (text of a label, image source, ...)
+        if old_node_type != new_node_type {
+            if let (NodeType::Image(_), NodeType::Image(_)) = (old_node_type, new_node_type) {
+                self.only_replace_images.push(new_node_id);
+            }
+            self.need_relayout.push(DomRange::new(new_node_id, new_node_id));
+        }
+
+        let old_children = old_node_id.children(&context.old.node_layout).collect::<Vec<_>>();
+        let new_children = new_node_id.children(&context.new.node_layout).collect::<Vec<_>>();
+
+        let matches = match_children(&old_children, &new_children, |old_child, new_child| {
+            is_same_node(&context.old.node_data[old_child], &context.new.node_data[new_child])
+        });
+
+        let old_last_child = old_children.len().saturating_sub(1);
+        let new_last_child = new_children.len().saturating_sub(1);
+
+        let (mut old_idx, mut new_idx) = (0, 0);
+
+        for (old_match, new_match) in matches {
+
+            for old_child in &old_children[old_idx..old_match] {
+                self.remove_subtree(context, *old_child);
+            }
+            for new_child in &new_children[new_idx..new_match] {
+                self.add_subtree(context, *new_child, parent_is_restyled);
+            }
+
+            // If the child moved, the :nth-child / :first / :last selectors might now
+            // select something different, so the child needs to be restyled
+            let position_changed =
+                old_match != new_match ||
+                (old_match == old_last_child) != (new_match == new_last_child);
+
+            let child_is_restyled = parent_is_restyled || position_changed;
+            if child_is_restyled && !parent_is_restyled {
+                self.need_restyling.push(DomRange::subtree(&context.new.node_layout, new_children[new_match]));
+            }
+
+            self.diff_matched_nodes(context, old_children[old_match], new_children[new_match], child_is_restyled);
+
+            old_idx = old_match + 1;
+            new_idx = new_match + 1;
+        }
+
+        for old_child in &old_children[old_idx..] {
+            self.remove_subtree(context, *old_child);
+        }
+        for new_child in &new_children[new_idx..] {
+            self.add_subtree(context, *new_child, parent_is_restyled);
+        }
+    }
+
+    /// Returns whether the two DOMs are identical (as far as styling and layout are concerned)
+    pub fn is_empty(&self) -> bool {
+        self.changed_nodes.is_empty() &&
+        self.need_restyling.is_empty() &&
+        self.need_relayout.is_empty()
+    }
+
+    /// Returns the ID of the node in the old DOM that the node in the new DOM
+    /// was matched to, or `None` if the node was newly added
+    pub fn get_old_node_id(&self, node_id: NodeId) -> Option<NodeId> {
+        self.old_node_ids.get(node_id.index()).and_then(|node| node.map(|n| n.id))
+    }
+
+    /// Returns whether the CSS rules for the node (in the new DOM) have to be matched again
+    pub fn needs_restyle(&self, node_id: NodeId) -> bool {
+        self.get_old_node_id(node_id).is_none() || ranges_contain(&self.need_restyling, node_id)
+    }
 
-    for (parent_depth, parent_id) in parent_nodes {
-        for child_id in parent_id.children(hierarchy).filter(|child| hierarchy[*child].first_child.is_some()) {
-            map.entry(parent_depth + 1).or_insert_with(|| BTreeMap::new())
-               .entry(parent_id).or_insert_with(|| Vec::new())
-               .push(DomNode { id: child_id, marker: PhantomData });
+    /// Returns whether the content of the node (in the new DOM) has to be laid out again,
+    /// i.e. whether the text layout of the last frame can't be re-used for this node
+    pub fn needs_relayout(&self, node_id: NodeId) -> bool {
+        self.get_old_node_id(node_id).is_none() || ranges_contain(&self.need_relayout, node_id)
+    }
+
+    /// Returns the old node ID, but only if the node content didn't change,
+    /// i.e. the cached layout information (text, images) can be re-used
+    pub(crate) fn get_unchanged_old_node_id(&self, node_id: NodeId) -> Option<NodeId> {
+        if ranges_contain(&self.need_relayout, node_id) {
+            None
+        } else {
+            self.get_old_node_id(node_id)
         }
     }
 
-    map
+    /// Returns the first and last node IDs of all subtrees that were added in the new DOM
+    pub fn get_added_subtrees(&self) -> Vec<(NodeId, NodeId)> {
+        self.changed_nodes.iter().filter_map(|change| match change {
+            DomChange::Added(range) => Some((range.start.id, range.end.id)),
+            DomChange::Removed(_) => None,
+        }).collect()
+    }
+
+    /// Returns the first and last node IDs of all subtrees that were removed from the old DOM
+    pub fn get_removed_subtrees(&self) -> Vec<(NodeId, NodeId)> {
+        self.changed_nodes.iter().filter_map(|change| match change {
+            DomChange::Removed(range) => Some((range.start.id, range.end.id)),
+            DomChange::Added(_) => None,
+        }).collect()
+    }
+
+    /// Returns the image nodes (in the new DOM) where only the image source changed
+    pub fn get_replaced_images(&self) -> &[NodeId] {
+        &self.only_replace_images
+    }
+}
+
+#[cfg(test)]
+struct TestLayout { }
+
+#[cfg(test)]
+impl Layout for TestLayout {
+    fn layout(&self) -> Dom<Self> {
+        Dom::div()
+    }
 }
 
-pub(crate) fn diff_dom_tree<T: Layout>(old: &Dom<T>, new:Dom<T>) -> DomDiff {
+#[test]
+fn test_dom_diff_unchanged() {
+    let dom = || Dom::<TestLayout>::div()
+        .with_child(Dom::label("Hello").with_class("title"))
+        .with_child(Dom::div().with_id("content").with_child(Dom::label("World")));
 
-    // TODO!
+    let diff = DomDiff::new(&dom(), &dom());
 
-    // let old_leaf_nodes = get_leaf_nodes_by_depth(&old.arena.node_layout, OldState { });
-    // let new_leaf_nodes = get_leaf_nodes_by_depth(&new.arena.node_layout, NewState { });
+    assert!(diff.is_empty());
+    for node_id in 0..4 {
+        let node_id = NodeId::new(node_id);
+        assert_eq!(diff.get_old_node_id(node_id), Some(node_id));
+        assert!(!diff.needs_restyle(node_id));
+        assert!(!diff.needs_relayout(node_id));
+    }
+
+    let dom = dom();
+    assert_eq!(DomDiff::unchanged(&dom.arena), DomDiff::from_arenas(&dom.arena, dom.root, &dom.arena, dom.root));
+}
+
+#[test]
+fn test_dom_diff_label_text_changed() {
+    let old = Dom::<TestLayout>::div()
+        .with_child(Dom::label("Hello").with_class("title"))
+        .with_child(Dom::label("World"));
+    let new = Dom::<TestLayout>::div()
+        .with_child(Dom::label("Hello").with_class("title"))
+        .with_child(Dom::label("Everyone"));
+
+    let diff = DomDiff::new(&old, &new);
+
+    assert!(!diff.is_empty());
+    assert!(diff.get_added_subtrees().is_empty());
+    assert!(diff.get_removed_subtrees().is_empty());
+    // The text changed, but the CSS selectors still match the same nodes
+    assert!(!diff.needs_restyle(NodeId::new(2)));
+    assert!(diff.needs_relayout(NodeId::new(2)));
+    assert!(!diff.needs_relayout(NodeId::new(1)));
+    assert_eq!(diff.get_old_node_id(NodeId::new(2)), Some(NodeId::new(2)));
+}
 
-    // depth -> parents (in order) -> [leaf children]
+#[test]
+fn test_dom_diff_child_inserted() {
+    let old = Dom::<TestLayout>::div()
+        .with_child(Dom::div().with_id("a").with_child(Dom::label("a")))
+        .with_child(Dom::div().with_id("c"));
+    let new = Dom::<TestLayout>::div()
+        .with_child(Dom::div().with_id("a").with_child(Dom::label("a")))
+        .with_child(Dom::div().with_id("b").with_child(Dom::label("b")))
+        .with_child(Dom::div().with_id("c"));
 
-    DomDiff::default()
-}
\ No newline at end of file
+    let diff = DomDiff::new(&old, &new);
+
+    assert_eq!(diff.get_added_subtrees(), vec![(NodeId::new(3), NodeId::new(4))]);
+    assert!(diff.get_removed_subtrees().is_empty());
+
+    // #a didn't move, so it can re-use the style of the last frame
+    assert_eq!(diff.get_old_node_id(NodeId::new(1)), Some(NodeId::new(1)));
+    assert!(!diff.needs_restyle(NodeId::new(1)));
+    assert!(!diff.needs_restyle(NodeId::new(2)));
+
+    // The inserted subtree needs a restyle + relayout
+    assert!(diff.needs_restyle(NodeId::new(3)));
+    assert!(diff.needs_relayout(NodeId::new(4)));
+
+    // #c moved from the second to the third position (:nth-child)
+    assert_eq!(diff.get_old_node_id(NodeId::new(5)), Some(NodeId::new(3)));
+    assert!(diff.needs_restyle(NodeId::new(5)));
+    assert!(!diff.needs_relayout(NodeId::new(5)));
+}
+
+#[test]
+fn test_dom_diff_class_changed() {
+    let old = Dom::<TestLayout>::div()
+        .with_child(Dom::div().with_class("inactive").with_child(Dom::label("a")))
+        .with_child(Dom::div());
+    let new = Dom::<TestLayout>::div()
+        .with_child(Dom::div().with_class("active").with_child(Dom::label("a")))
+        .with_child(Dom::div());
+
+    let diff = DomDiff::new(&old, &new);
+
+    // Nodes are keyed by their classes, so the node is replaced
+    assert_eq!(diff.get_removed_subtrees(), vec![(NodeId::new(1), NodeId::new(2))]);
+    assert_eq!(diff.get_added_subtrees(), vec![(NodeId::new(1), NodeId::new(2))]);
+    assert_eq!(diff.get_old_node_id(NodeId::new(1)), None);
+    assert!(diff.needs_restyle(NodeId::new(2)));
+    assert!(!diff.needs_restyle(NodeId::new(3)));
+    assert!(!diff.needs_restyle(NodeId::new(0)));
+}
+
+#[test]
+fn test_dom_diff_root_changed() {
+    let old = Dom::<TestLayout>::div().with_child(Dom::div());
+    let new = Dom::<TestLayout>::label("root").with_child(Dom::div());
+
+    let diff = DomDiff::new(&old, &new);
+
+    assert_eq!(diff.get_removed_subtrees(), vec![(NodeId::new(0), NodeId::new(1))]);
+    assert_eq!(diff.get_added_subtrees(), vec![(NodeId::new(0), NodeId::new(1))]);
+    assert!(diff.needs_restyle(NodeId::new(1)));
+    assert!(diff.needs_relayout(NodeId::new(1)));
+}
@@ -5,7 +5,7 @@ use std::{
     sync::{Arc, Mutex},
     collections::BTreeMap,
 };
-use euclid::{TypedRect, TypedSize2D};
+use euclid::{TypedRect, TypedSize2D, Angle};
 use webrender::api::{
     LayoutPixel, DisplayListBuilder, PrimitiveInfo, GradientStop,
     ColorF, PipelineId, Epoch, ImageData, ImageDescriptor,
@@ -14,17 +14,19 @@ use webrender::api::{
     ComplexClipRegion, LayoutPrimitiveInfo, ExternalImageId,
     ExternalImageData, ImageFormat, ExternalImageType, TextureTarget,
     ImageRendering, AlphaType, FontInstanceFlags, FontRenderMode,
+    LayoutTransform, PropertyBinding, TransformStyle, MixBlendMode, FilterOp, RasterSpace,
 };
 use azul_css::{
-    Css, LayoutPosition,CssProperty, LayoutOverflow,
+    CssProperty, CssPropertyType, LayoutOverflow,
     StyleBorderRadius, LayoutMargin, LayoutPadding, BoxShadowClipMode,
     StyleTextColor, StyleBackground, StyleBoxShadow,
     StyleBackgroundSize, StyleBackgroundRepeat, StyleBorder, BoxShadowPreDisplayItem,
     RectStyle, RectLayout, ColorU as StyleColorU, DynamicCssPropertyDefault,
+    ScrollbarInfo, RelativeSizeContext, LayoutDisplay, StyleVisibility,
 };
 use {
     FastHashMap,
-    app_resources::AppResources,
+    app_resources::{AppResources, generate_image_key},
     callbacks::{IFrameCallback, GlTextureCallback, HidpiAdjustedBounds, StackCheckedPointer},
     traits::Layout,
     ui_state::UiState,
@@ -34,12 +36,15 @@ use {
         NodeData, ScrollTagId, DomHash, DomString, new_scroll_tag_id,
         NodeType::{self, Div, Text, Image, GlTexture, IFrame, Label},
     },
-    ui_solver::{do_the_layout, LayoutResult, PositionedRectangle},
+    ui_solver::{self, do_the_layout, LayoutResult, PositionedRectangle},
     app_resources::ImageId,
     compositor::new_opengl_texture_id,
-    window::{Window, FakeWindow, ScrollStates},
+    window::{Window, FakeWindow, ScrollStates, ResolvedCss},
     callbacks::LayoutInfo,
     window_state::WindowSize,
+    snapshot::{DisplayListSnapshot, SnapshotPrimitive, SnapshotPoint},
+    scrollbar::ScrollbarRects,
+    text_layout::LayoutedGlyphs,
 };
 
 const DEFAULT_FONT_COLOR: StyleTextColor = StyleTextColor(StyleColorU { r: 0, b: 0, g: 0, a: 255 });
@@ -94,6 +99,12 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
             let tag = ui_state.node_ids_to_tag_ids.get(&node_id).map(|tag| *tag);
             let mut rect = DisplayRectangle::new(tag, style);
             populate_css_properties(&mut rect, node_id, &ui_description.dynamic_css_overrides);
+            // Transitions and animations override the values of the cascade
+            if let Some(animated_properties) = ui_description.animated_properties.get(&node_id) {
+                for property in animated_properties.values() {
+                    apply_style_property(&mut rect, property);
+                }
+            }
             rect
         });
 
@@ -104,12 +115,52 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
     }
 
     /// Inserts and solves the top-level DOM (i.e. the DOM with the ID 0)
+    ///
+    /// If `relayout` is false, the layout of the last frame is re-used (i.e. if only
+    /// styling properties were animated since the last frame)
     pub(crate) fn into_display_list_builder(
         &self,
         app_data_access: &mut Arc<Mutex<T>>,
         window: &mut Window<T>,
         fake_window: &mut FakeWindow<T>,
-        app_resources: &mut AppResources
+        app_resources: &mut AppResources,
+        relayout: bool,
+    ) -> (DisplayListBuilder, ScrolledNodes, LayoutResult) {
+        self.into_display_list_builder_inner(
+            app_data_access,
+            DisplayListWindowParams {
+                pipeline_id: window.internal.pipeline_id,
+                epoch: window.internal.epoch,
+                size: window.state.size,
+                css: &self.ui_descr.css,
+                scroll_states: &mut window.scroll_states,
+            },
+            fake_window,
+            app_resources,
+            window.internal.last_layout_result.as_ref(),
+            relayout,
+            None,
+        )
+    }
+
+    /// Same as `into_display_list_builder`, but doesn't require a `Window`, only the
+    /// parts of it that are necessary for layout - used for rendering headless windows
+    ///
+    /// `previous_layout` is the layout of the frame that the `DomDiff` of the
+    /// `UiDescription` was created against, used to re-use the text layout of unchanged nodes.
+    /// If `relayout` is false, the `previous_layout` is re-used as it is.
+    ///
+    /// If `snapshot` is `Some`, all primitives that are pushed into the display list
+    /// are additionally recorded into the snapshot.
+    pub(crate) fn into_display_list_builder_inner(
+        &self,
+        app_data_access: &mut Arc<Mutex<T>>,
+        window: DisplayListWindowParams,
+        fake_window: &mut FakeWindow<T>,
+        app_resources: &mut AppResources,
+        previous_layout: Option<&LayoutResult>,
+        relayout: bool,
+        snapshot: Option<&mut DisplayListSnapshot>,
     ) -> (DisplayListBuilder, ScrolledNodes, LayoutResult) {
 
         use glium::glutin::dpi::LogicalSize;
@@ -136,44 +187,48 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
         //      - Repeat while number_of_iframe_callbacks != 0
         app_resources.add_fonts_and_images(&self);
 
-        let window_size = window.state.size.get_reverse_logical_size();
-        let layout_result = do_the_layout(
-            node_hierarchy,
-            node_data,
-            &self.rectangles,
-            &*app_resources,
-            LayoutSize::new(window_size.width as f32, window_size.height as f32),
-            LayoutPoint::new(0.0, 0.0),
-        );
+        let window_size = window.size.get_reverse_logical_size();
+        let layout_result = match previous_layout {
+            Some(previous) if !relayout => previous.clone(),
+            _ => do_the_layout(
+                node_hierarchy,
+                node_data,
+                &self.rectangles,
+                &*app_resources,
+                LayoutSize::new(window_size.width as f32, window_size.height as f32),
+                LayoutPoint::new(0.0, 0.0),
+                previous_layout.and_then(|previous| self.ui_descr.dom_diff.as_ref().map(|diff| (previous, diff))),
+            ),
+        };
 
         // TODO: After the layout has been done, call all IFrameCallbacks and get and insert
         // their font keys / image keys
 
         let mut scrollable_nodes = get_nodes_that_need_scroll_clip(
             node_hierarchy, &self.rectangles, node_data, &layout_result.rects,
-            &layout_result.node_depths, window.internal.pipeline_id
+            &layout_result.node_depths, window.pipeline_id, window.scroll_states,
         );
 
         // Make sure unused scroll states are garbage collected.
         window.scroll_states.remove_unused_scroll_states();
 
-        let LogicalSize { width, height } = window.state.size.dimensions;
-        let mut builder = DisplayListBuilder::with_capacity(window.internal.pipeline_id, TypedSize2D::new(width as f32, height as f32), self.rectangles.len());
+        let LogicalSize { width, height } = window.size.dimensions;
+        let mut builder = DisplayListBuilder::with_capacity(window.pipeline_id, TypedSize2D::new(width as f32, height as f32), self.rectangles.len());
 
         let rects_in_rendering_order = determine_rendering_order(node_hierarchy, &self.rectangles, &layout_result.rects);
 
         push_rectangles_into_displaylist(
-            window.internal.epoch,
-            window.state.size,
+            window.epoch,
+            window.size,
             rects_in_rendering_order,
             &mut scrollable_nodes,
-            &mut window.scroll_states,
+            window.scroll_states,
             &DisplayListParametersRef {
-                pipeline_id: window.internal.pipeline_id,
+                pipeline_id: window.pipeline_id,
                 node_hierarchy,
                 node_data,
                 display_rectangle_arena: &self.rectangles,
-                css: &window.css,
+                css: window.css,
                 layout_result: &layout_result,
             },
             &mut DisplayListParametersMut {
@@ -182,7 +237,8 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
                 fake_window,
                 builder: &mut builder,
                 resource_updates: &mut resource_updates,
-                pipeline_id: window.internal.pipeline_id,
+                pipeline_id: window.pipeline_id,
+                snapshot,
             },
         );
 
@@ -190,6 +246,17 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
     }
 }
 
+/// The parts of a window that are necessary to build a display list, borrowed
+/// either from a regular `Window` or from a `HeadlessWindow`
+pub(crate) struct DisplayListWindowParams<'a> {
+    pub(crate) pipeline_id: PipelineId,
+    pub(crate) epoch: Epoch,
+    pub(crate) size: WindowSize,
+    /// The CSS that should be applied to the DOM
+    pub(crate) css: &'a ResolvedCss,
+    pub(crate) scroll_states: &'a mut ScrollStates,
+}
+
 /// In order to render rectangles in the correct order, we have to group them together:
 /// As long as there are no position:absolute items, items are inserted in a parents-then-child order
 ///
@@ -230,6 +297,11 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
 /// ```
 /// Then the groups are simply rendered in-order: if there are multiple position:absolute
 /// groups, this has the side effect of later groups drawing on top of earlier groups.
+/// `position:fixed` nodes are grouped the same way.
+///
+/// Nodes with a `z-index` other than `0` are grouped the same way as absolute nodes: the
+/// groups are sorted by their `z-index`, groups with a negative `z-index` are drawn
+/// before (below) the content of their parent group, all others after (above) it.
 #[derive(Debug, Clone, PartialEq)]
 struct ContentGroup {
     /// The parent of the current node group, i.e. either the root node (0)
@@ -334,7 +406,11 @@ fn determine_rendering_order_inner<'a>(
                 NodeEdge::Start(node_id) => {
                     let rect_node = &rectangles[node_id];
                     let position = rect_node.layout.position.unwrap_or_default();
-                    if position == LayoutPosition::Absolute {
+                    if rect_node.layout.display == Some(LayoutDisplay::None) {
+                        // display: none - neither the node nor its children are rendered
+                        current_node_edge = NodeEdge::End(node_id);
+                        should_continue_loop = false;
+                    } else if position.is_out_of_flow() || get_z_index(&rect_node.style) != 0 {
                         // For now, ignore the node and put it aside for later
                         absolute_node_ids.push((depth, node_id));
                         // Skip this sub-tree and go straight to the next sibling
@@ -365,15 +441,28 @@ fn determine_rendering_order_inner<'a>(
         }
     }
 
+    // Stable sort, so that nodes with the same z-index keep the DOM order,
+    // i.e. later absolute items are drawn on top of earlier absolute items
+    absolute_node_ids.sort_by_key(|(_, node_id)| get_z_index(&rectangles[*node_id].style));
+
+    let (below_node_ids, above_node_ids): (Vec<_>, Vec<_>) = absolute_node_ids.into_iter()
+        .partition(|(_, node_id)| get_z_index(&rectangles[*node_id].style) < 0);
+
+    for (absolute_depth, absolute_node_id) in below_node_ids {
+        determine_rendering_order_inner(node_hierarchy, rectangles, layouted_rects, absolute_depth, absolute_node_id, content_groups);
+    }
+
     content_groups.push(root_group);
 
-    // Note: Currently reversed order, so that earlier absolute
-    // items are drawn on top of later absolute items
-    for (absolute_depth, absolute_node_id) in absolute_node_ids.into_iter().rev() {
+    for (absolute_depth, absolute_node_id) in above_node_ids {
         determine_rendering_order_inner(node_hierarchy, rectangles, layouted_rects, absolute_depth, absolute_node_id, content_groups);
     }
 }
 
+fn get_z_index(style: &RectStyle) -> i32 {
+    style.z_index.map(|z_index| z_index.0).unwrap_or(0)
+}
+
 #[derive(Default, Debug, Clone)]
 pub(crate)  struct ScrolledNodes {
     pub(crate) overflowing_nodes: BTreeMap<NodeId, OverflowingScrollNode>,
@@ -384,22 +473,25 @@ pub(crate)  struct ScrolledNodes {
 pub(crate) struct OverflowingScrollNode {
     pub(crate) parent_rect: PositionedRectangle,
     pub(crate) child_rect: LayoutRect,
+    /// The scrollable area, i.e. the bounds of the parent, extended by how far the children overflow
+    pub(crate) content_rect: LayoutRect,
+    /// The area that the children are clipped to, i.e. the bounds of the parent without the scrollbars
+    pub(crate) clip_rect: LayoutRect,
     pub(crate) parent_external_scroll_id: ExternalScrollId,
     pub(crate) parent_dom_hash: DomHash,
     pub(crate) scroll_tag_id: ScrollTagId,
+    /// The nearest scrolled ancestor of this node (if any)
+    pub(crate) parent_scroll_node: Option<NodeId>,
+    pub(crate) horizontal_scrollbar: Option<ScrollbarRects>,
+    pub(crate) vertical_scrollbar: Option<ScrollbarRects>,
 }
 
-/// Returns all node IDs where the children overflow the parent, together with the
+/// Returns all node IDs with `overflow: scroll` / `overflow: auto` where the children overflow
+/// the parent (or where the scrollbars are always visible), together with the
 /// `(parent_rect, child_rect)` - the child rect is the sum of the children.
 ///
-/// TODO: The performance of this function can be theoretically improved:
-///
-/// - Unioning the rectangles is heavier than just looping through the children and
-/// summing up their width / height / padding + margin.
-/// - Scroll nodes only need to be inserted if the parent doesn't have `overflow: hidden`
-/// activated
-/// - Overflow for X and Y needs to be tracked seperately (for overflow-x / overflow-y separation),
-/// so there we'd need to track in which direction the inner_rect is overflowing.
+/// Also initializes the scroll states of these nodes, so that the scroll position of
+/// a node is preserved for as long as the node is scrollable.
 fn get_nodes_that_need_scroll_clip<'a, T: 'a + Layout>(
     node_hierarchy: &NodeHierarchy,
     display_list_rects: &NodeDataContainer<DisplayRectangle<'a>>,
@@ -407,19 +499,19 @@ fn get_nodes_that_need_scroll_clip<'a, T: 'a + Layout>(
     layouted_rects: &NodeDataContainer<PositionedRectangle>,
     parents: &[(usize, NodeId)],
     pipeline_id: PipelineId,
+    scroll_states: &mut ScrollStates,
 ) -> ScrolledNodes {
 
+    use scrollbar::{ScrollbarAxis, get_scrollbar_rects};
+
     let mut nodes = BTreeMap::new();
     let mut tags_to_node_ids = BTreeMap::new();
 
     for (_, parent) in parents {
 
-        let mut children_sum_rect = None;
-
-        for child in parent.children(&node_hierarchy) {
-            let old = children_sum_rect.unwrap_or(LayoutRect::zero());
-            children_sum_rect = Some(old.union(&layouted_rects[child].bounds));
-        }
+        let children_sum_rect = parent.children(&node_hierarchy)
+            .map(|child| layouted_rects[child].bounds)
+            .fold(None, |sum: Option<LayoutRect>, bounds| Some(sum.map_or(bounds, |sum| sum.union(&bounds))));
 
         let children_sum_rect = match children_sum_rect {
             None => continue,
@@ -427,17 +519,65 @@ fn get_nodes_that_need_scroll_clip<'a, T: 'a + Layout>(
         };
 
         let parent_rect = layouted_rects.get(*parent).unwrap();
+        let bounds = parent_rect.bounds;
+        let overflow = display_list_rects[*parent].layout.overflow.unwrap_or_default();
+        let can_scroll_x = overflow.horizontal.unwrap_or_default().allows_scrolling();
+        let can_scroll_y = overflow.vertical.unwrap_or_default().allows_scrolling();
 
-        if children_sum_rect.contains_rect(&parent_rect.bounds) {
+        let mut overflow_x = if can_scroll_x { (children_sum_rect.max_x() - bounds.max_x()).max(0.0) } else { 0.0 };
+        let mut overflow_y = if can_scroll_y { (children_sum_rect.max_y() - bounds.max_y()).max(0.0) } else { 0.0 };
+
+        let has_horizontal_scrollbar = overflow.needs_horizontal_scrollbar(overflow_x > 0.0);
+        let has_vertical_scrollbar = overflow.needs_vertical_scrollbar(overflow_y > 0.0);
+
+        if !has_horizontal_scrollbar && !has_vertical_scrollbar {
             continue;
         }
 
+        let style = &display_list_rects[*parent].style;
+        let horizontal_scrollbar_style = style.get_horizontal_scrollbar_style();
+        let vertical_scrollbar_style = style.get_vertical_scrollbar_style();
+        let horizontal_scrollbar_height = if has_horizontal_scrollbar { horizontal_scrollbar_style.width.0.to_pixels() } else { 0.0 };
+        let vertical_scrollbar_width = if has_vertical_scrollbar { vertical_scrollbar_style.width.0.to_pixels() } else { 0.0 };
+
+        // The scrollbars cover the content, so the content has to be
+        // scrollable far enough to make the last line visible
+        if overflow_x > 0.0 {
+            overflow_x += vertical_scrollbar_width;
+        }
+        if overflow_y > 0.0 {
+            overflow_y += horizontal_scrollbar_height;
+        }
+
         let parent_dom_hash = dom_rects[*parent].calculate_node_data_hash();
 
         // Create an external scroll id. This id is required to preserve its
         // scroll state accross multiple frames.
         let parent_external_scroll_id  = ExternalScrollId(parent_dom_hash.0, pipeline_id);
 
+        scroll_states.ensure_initialized_scroll_state(parent_external_scroll_id, overflow_x, overflow_y);
+        let (scroll_x, scroll_y) = scroll_states.get_scroll_amount(&parent_external_scroll_id).unwrap_or((0.0, 0.0));
+
+        let content_size = LayoutSize::new(bounds.size.width + overflow_x, bounds.size.height + overflow_y);
+
+        let horizontal_scrollbar = if has_horizontal_scrollbar {
+            Some(get_scrollbar_rects(
+                ScrollbarAxis::Horizontal, bounds, &horizontal_scrollbar_style,
+                content_size.width, scroll_x, has_vertical_scrollbar,
+            ))
+        } else {
+            None
+        };
+
+        let vertical_scrollbar = if has_vertical_scrollbar {
+            Some(get_scrollbar_rects(
+                ScrollbarAxis::Vertical, bounds, &vertical_scrollbar_style,
+                content_size.height, scroll_y, has_horizontal_scrollbar,
+            ))
+        } else {
+            None
+        };
+
         // Create a unique scroll tag for hit-testing
         let scroll_tag_id = match display_list_rects.get(*parent).and_then(|node| node.tag) {
             Some(existing_tag) => ScrollTagId(existing_tag),
@@ -448,15 +588,41 @@ fn get_nodes_that_need_scroll_clip<'a, T: 'a + Layout>(
         nodes.insert(*parent, OverflowingScrollNode {
             parent_rect: parent_rect.clone(),
             child_rect: children_sum_rect,
+            content_rect: LayoutRect::new(bounds.origin, content_size),
+            clip_rect: LayoutRect::new(bounds.origin, LayoutSize::new(
+                (bounds.size.width - vertical_scrollbar_width).max(0.0),
+                (bounds.size.height - horizontal_scrollbar_height).max(0.0),
+            )),
             parent_external_scroll_id,
             parent_dom_hash,
             scroll_tag_id,
+            parent_scroll_node: None,
+            horizontal_scrollbar,
+            vertical_scrollbar,
         });
     }
 
+    // Link the scroll nodes to their scrolled ancestors, now that all scroll nodes are known
+    let scroll_node_ids = nodes.keys().cloned().collect::<Vec<NodeId>>();
+    for node_id in scroll_node_ids {
+        let parent_scroll_node = get_parent_scroll_node(node_id, node_hierarchy, &nodes);
+        if let Some(node) = nodes.get_mut(&node_id) {
+            node.parent_scroll_node = parent_scroll_node;
+        }
+    }
+
     ScrolledNodes { overflowing_nodes: nodes, tags_to_node_ids }
 }
 
+/// Returns the nearest ancestor of `node_id` that scrolls its children
+pub(crate) fn get_parent_scroll_node(
+    node_id: NodeId,
+    node_hierarchy: &NodeHierarchy,
+    overflowing_nodes: &BTreeMap<NodeId, OverflowingScrollNode>,
+) -> Option<NodeId> {
+    node_id.ancestors(node_hierarchy).skip(1).find(|ancestor| overflowing_nodes.contains_key(ancestor))
+}
+
 fn node_needs_to_clip_children(layout: &RectLayout) -> bool {
     let overflow = layout.overflow.unwrap_or_default();
     !overflow.is_horizontal_overflow_visible() ||
@@ -493,6 +659,54 @@ fn test_overflow_parsing() {
     assert_eq!(node_needs_to_clip_children(&layout3), true);
 }
 
+#[test]
+fn test_rendering_order_of_positioned_nodes() {
+
+    use azul_css::{LayoutPosition, StyleZIndex};
+    use dom::Dom;
+
+    struct TestLayout { }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::div()
+        }
+    }
+
+    // 0
+    // |- 1 [position: absolute]
+    // |- 2 [z-index: 1]
+    // |  |- 3
+    // |- 4 [position: fixed]
+    // |- 5 [z-index: -1]
+    let dom: Dom<TestLayout> = Dom::div()
+        .with_child(Dom::div())
+        .with_child(Dom::div().with_child(Dom::div()))
+        .with_child(Dom::div())
+        .with_child(Dom::div());
+    let node_hierarchy = &dom.arena.node_layout;
+
+    let styled_node = StyledNode::default();
+    let mut rectangles = NodeDataContainer::new((0..6).map(|_| DisplayRectangle::new(None, &styled_node)).collect());
+    rectangles[NodeId::new(1)].layout.position = Some(LayoutPosition::Absolute);
+    rectangles[NodeId::new(2)].style.z_index = Some(StyleZIndex(1));
+    rectangles[NodeId::new(4)].layout.position = Some(LayoutPosition::Fixed);
+    rectangles[NodeId::new(5)].style.z_index = Some(StyleZIndex(-1));
+
+    let layouted_rects = rectangles.transform(|_, _| PositionedRectangle {
+        bounds: LayoutRect::zero(),
+        content_width: None,
+        content_height: None,
+    });
+
+    let content_groups = determine_rendering_order(node_hierarchy, &rectangles, &layouted_rects).groups;
+
+    // Negative z-index below the root, then the positioned nodes in DOM order, then the positive z-index
+    let group_roots = content_groups.iter().map(|group| group.root.node_id.index()).collect::<Vec<_>>();
+    assert_eq!(group_roots, vec![5, 0, 1, 4, 2]);
+    assert_eq!(content_groups[4].node_ids.iter().map(|item| item.node_id.index()).collect::<Vec<_>>(), vec![3]);
+}
+
 fn push_rectangles_into_displaylist<'a, 'b, 'c, 'd, 'e, 'f, T: Layout>(
     epoch: Epoch,
     window_size: WindowSize,
@@ -505,24 +719,20 @@ fn push_rectangles_into_displaylist<'a, 'b, 'c, 'd, 'e, 'f, T: Layout>(
     let mut clip_stack = Vec::new();
 
     for content_group in content_grouped_rectangles.groups {
-        let rectangle = DisplayListRectParams {
-            epoch,
-            rect_idx: content_group.root.node_id,
-            html_node: &referenced_content.node_data[content_group.root.node_id].node_type,
-            window_size,
-        };
 
-        // Push the root of the node
-        push_rectangles_into_displaylist_inner(
-            content_group.root,
-            scrollable_nodes,
-            &rectangle,
-            referenced_content,
-            referenced_mutable_content,
-            &mut clip_stack
-        );
+        // The stacking contexts and scroll frames that are currently pushed, innermost last
+        let mut pushed_frames = Vec::<PushedFrame>::new();
 
-        for item in content_group.node_ids {
+        for item in Some(content_group.root).into_iter().chain(content_group.node_ids.into_iter()) {
+
+            // Close the frames that the current item isn't a child of
+            while let Some(pushed_frame) = pushed_frames.last().cloned() {
+                if item.node_id.ancestors(referenced_content.node_hierarchy).any(|ancestor| ancestor == pushed_frame.node_id()) {
+                    break;
+                }
+                pushed_frames.pop();
+                pop_frame(pushed_frame, scrollable_nodes, referenced_content, referenced_mutable_content);
+            }
 
             let rectangle = DisplayListRectParams {
                 epoch,
@@ -531,7 +741,7 @@ fn push_rectangles_into_displaylist<'a, 'b, 'c, 'd, 'e, 'f, T: Layout>(
                 window_size,
             };
 
-            push_rectangles_into_displaylist_inner(
+            let pushed_stacking_context = push_rectangles_into_displaylist_inner(
                 item,
                 scrollable_nodes,
                 &rectangle,
@@ -539,10 +749,312 @@ fn push_rectangles_into_displaylist<'a, 'b, 'c, 'd, 'e, 'f, T: Layout>(
                 referenced_mutable_content,
                 &mut clip_stack
             );
+
+            if pushed_stacking_context {
+                pushed_frames.push(PushedFrame::StackingContext(item.node_id));
+            }
+
+            if push_scroll_frame(item.node_id, scrollable_nodes, scroll_states, referenced_mutable_content) {
+                pushed_frames.push(PushedFrame::ScrollFrame(item.node_id));
+            }
+        }
+
+        while let Some(pushed_frame) = pushed_frames.pop() {
+            pop_frame(pushed_frame, scrollable_nodes, referenced_content, referenced_mutable_content);
+        }
+    }
+}
+
+/// Stacking context or scroll frame that has been pushed for a node and
+/// has to be popped once all children of the node have been pushed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum PushedFrame {
+    StackingContext(NodeId),
+    ScrollFrame(NodeId),
+}
+
+impl PushedFrame {
+    fn node_id(&self) -> NodeId {
+        match self {
+            PushedFrame::StackingContext(node_id) | PushedFrame::ScrollFrame(node_id) => *node_id,
         }
     }
 }
 
+fn pop_frame<'a,'b,'c,'d,'e,'f, T: Layout>(
+    pushed_frame: PushedFrame,
+    scrollable_nodes: &ScrolledNodes,
+    referenced_content: &DisplayListParametersRef<'a,'b,'c,'d,'e, T>,
+    referenced_mutable_content: &mut DisplayListParametersMut<'f, T>,
+) {
+    match pushed_frame {
+        PushedFrame::StackingContext(_) => pop_stacking_context(referenced_mutable_content),
+        PushedFrame::ScrollFrame(node_id) => pop_scroll_frame(node_id, scrollable_nodes, referenced_content, referenced_mutable_content),
+    }
+}
+
+/// Returns the transform of the node (`transform` + `transform-origin`), in window coordinates,
+/// or `None` if the node isn't transformed. Percentages refer to the size of the node itself.
+pub(crate) fn get_node_transform(style: &RectStyle, bounds: LayoutRect) -> Option<LayoutTransform> {
+
+    let transform = style.transform.as_ref()?;
+
+    if transform.0.is_empty() {
+        return None;
+    }
+
+    let horizontal_context = RelativeSizeContext { percent_base: bounds.size.width, .. Default::default() };
+    let vertical_context = RelativeSizeContext { percent_base: bounds.size.height, .. Default::default() };
+
+    let transform_origin = style.transform_origin.unwrap_or_default();
+    let origin_x = bounds.origin.x + transform_origin.x.to_pixels_relative(&horizontal_context);
+    let origin_y = bounds.origin.y + transform_origin.y.to_pixels_relative(&vertical_context);
+
+    // The functions are applied right-to-left, around the transform origin:
+    // translate(origin) * f1 * f2 * ... * translate(-origin)
+    let mut matrix = LayoutTransform::create_translation(-origin_x, -origin_y, 0.0);
+
+    for function in transform.0.iter().rev() {
+        use azul_css::StyleTransformFunction::*;
+        let function_matrix = match function {
+            Translate(x, y) => LayoutTransform::create_translation(
+                x.to_pixels_relative(&horizontal_context),
+                y.to_pixels_relative(&vertical_context),
+                0.0,
+            ),
+            Rotate(degrees) => LayoutTransform::create_rotation(0.0, 0.0, 1.0, Angle::radians(degrees.get().to_radians())),
+            Scale(x, y) => LayoutTransform::create_scale(x.get(), y.get(), 1.0),
+            Skew(x, y) => LayoutTransform::create_skew(Angle::radians(x.get().to_radians()), Angle::radians(y.get().to_radians())),
+        };
+        matrix = matrix.post_mul(&function_matrix);
+    }
+
+    Some(matrix.post_mul(&LayoutTransform::create_translation(origin_x, origin_y, 0.0)))
+}
+
+/// Returns the `opacity` of the node, `1.0` if the node has no opacity set
+fn get_node_opacity(style: &RectStyle) -> f32 {
+    style.opacity.unwrap_or_default().0.get()
+}
+
+/// If the node is transformed or transparent, pushes a reference frame (for the transform) and a
+/// stacking context (for the opacity) for the node and its children, so that WebRender draws and
+/// hit-tests the node and its children with the transform applied.
+///
+/// Returns whether a stacking context was pushed.
+fn push_stacking_context<'f, T: Layout>(
+    style: &RectStyle,
+    bounds: LayoutRect,
+    referenced_mutable_content: &mut DisplayListParametersMut<'f, T>,
+) -> bool {
+
+    let transform = get_node_transform(style, bounds);
+    let opacity = get_node_opacity(style);
+
+    if transform.is_none() && opacity >= 1.0 {
+        return false;
+    }
+
+    // The transform is already relative to the window, so the reference frame starts at (0, 0)
+    let frame_rect = LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(bounds.max_x(), bounds.max_y()));
+    let info = LayoutPrimitiveInfo {
+        rect: frame_rect,
+        clip_rect: frame_rect,
+        is_backface_visible: false,
+        tag: None,
+    };
+
+    let reference_frame_id = referenced_mutable_content.builder.push_reference_frame(
+        &info,
+        Some(PropertyBinding::Value(transform.unwrap_or_else(LayoutTransform::identity))),
+        None,
+    );
+    referenced_mutable_content.builder.push_clip_id(reference_frame_id);
+
+    let filters = if opacity < 1.0 {
+        vec![FilterOp::Opacity(PropertyBinding::Value(opacity), opacity)]
+    } else {
+        Vec::new()
+    };
+
+    referenced_mutable_content.builder.push_stacking_context(
+        &info,
+        None,
+        TransformStyle::Flat,
+        MixBlendMode::Normal,
+        filters,
+        RasterSpace::Screen,
+    );
+
+    if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+        snapshot.push(SnapshotPrimitive::PushStackingContext {
+            opacity,
+            transform: transform.map(|transform| transform.into()),
+        });
+    }
+
+    true
+}
+
+fn pop_stacking_context<'f, T: Layout>(referenced_mutable_content: &mut DisplayListParametersMut<'f, T>) {
+
+    referenced_mutable_content.builder.pop_stacking_context();
+    referenced_mutable_content.builder.pop_clip_id();
+    referenced_mutable_content.builder.pop_reference_frame();
+
+    if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+        snapshot.push(SnapshotPrimitive::PopStackingContext);
+    }
+}
+
+/// If the node scrolls its children, defines the scroll frame for the children
+/// and pushes it, so that the children are clipped and scrolled.
+///
+/// Returns whether a scroll frame was pushed.
+fn push_scroll_frame<'f, T: Layout>(
+    node_id: NodeId,
+    scrollable_nodes: &ScrolledNodes,
+    scroll_states: &ScrollStates,
+    referenced_mutable_content: &mut DisplayListParametersMut<'f, T>,
+) -> bool {
+
+    use webrender::api::ScrollSensitivity;
+
+    let scroll_node = match scrollable_nodes.overflowing_nodes.get(&node_id) {
+        Some(s) => s,
+        None => return false,
+    };
+
+    let scroll_frame_id = referenced_mutable_content.builder.define_scroll_frame(
+        Some(scroll_node.parent_external_scroll_id),
+        scroll_node.content_rect,
+        scroll_node.clip_rect,
+        Vec::<ComplexClipRegion>::new(),
+        None,
+        ScrollSensitivity::ScriptAndInputEvents,
+    );
+
+    referenced_mutable_content.builder.push_clip_id(scroll_frame_id);
+
+    if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+        let (x, y) = scroll_states.get_scroll_amount(&scroll_node.parent_external_scroll_id).unwrap_or((0.0, 0.0));
+        snapshot.push(SnapshotPrimitive::PushScrollFrame {
+            clip: scroll_node.clip_rect.into(),
+            offset: SnapshotPoint { x, y },
+        });
+    }
+
+    true
+}
+
+/// Pops the scroll frame of the node after all children have been pushed,
+/// then draws the scrollbars on top of the (clipped) children.
+fn pop_scroll_frame<'a,'b,'c,'d,'e,'f, T: Layout>(
+    node_id: NodeId,
+    scrollable_nodes: &ScrolledNodes,
+    referenced_content: &DisplayListParametersRef<'a,'b,'c,'d,'e, T>,
+    referenced_mutable_content: &mut DisplayListParametersMut<'f, T>,
+) {
+    referenced_mutable_content.builder.pop_clip_id();
+
+    if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+        snapshot.push(SnapshotPrimitive::PopScrollFrame);
+    }
+
+    let scroll_node = match scrollable_nodes.overflowing_nodes.get(&node_id) {
+        Some(s) => s,
+        None => return,
+    };
+
+    let style = &referenced_content.display_rectangle_arena[node_id].style;
+
+    if let Some(scrollbar) = &scroll_node.horizontal_scrollbar {
+        push_scrollbar(node_id, scrollbar, &style.get_horizontal_scrollbar_style(), referenced_mutable_content);
+    }
+
+    if let Some(scrollbar) = &scroll_node.vertical_scrollbar {
+        push_scrollbar(node_id, scrollbar, &style.get_vertical_scrollbar_style(), referenced_mutable_content);
+    }
+
+    // Fill the corner in which the two scrollbars meet (unstyled by default)
+    if let (Some(horizontal), Some(vertical)) = (&scroll_node.horizontal_scrollbar, &scroll_node.vertical_scrollbar) {
+        let corner_style = style.get_vertical_scrollbar_style().corner;
+        if corner_style.background.is_some() || corner_style.border.is_some() {
+            let corner = LayoutRect::new(
+                LayoutPoint::new(vertical.track.origin.x, horizontal.track.origin.y),
+                LayoutSize::new(vertical.track.size.width, horizontal.track.size.height),
+            );
+            push_scrollbar_part(node_id, "scrollbar-corner", corner, &corner_style, referenced_mutable_content);
+        }
+    }
+}
+
+/// Draws the track, the buttons and the thumb of a scrollbar
+fn push_scrollbar<'f, T: Layout>(
+    node_id: NodeId,
+    scrollbar: &ScrollbarRects,
+    info: &ScrollbarInfo,
+    referenced_mutable_content: &mut DisplayListParametersMut<'f, T>,
+) {
+    let parts = [
+        ("scrollbar-track", scrollbar.track, &info.track),
+        ("scrollbar-button", scrollbar.button_start, &info.button),
+        ("scrollbar-button", scrollbar.button_end, &info.button),
+        ("scrollbar-thumb", scrollbar.thumb, &info.thumb),
+    ];
+
+    for (name, bounds, style) in parts.iter() {
+        push_scrollbar_part(node_id, name, *bounds, style, referenced_mutable_content);
+    }
+}
+
+fn push_scrollbar_part<'f, T: Layout>(
+    node_id: NodeId,
+    name: &str,
+    bounds: LayoutRect,
+    style: &RectStyle,
+    referenced_mutable_content: &mut DisplayListParametersMut<'f, T>,
+) {
+    let info = LayoutPrimitiveInfo {
+        rect: bounds,
+        clip_rect: bounds,
+        is_backface_visible: false,
+        tag: None,
+    };
+
+    if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+        snapshot.begin_item(node_id.index(), name.to_string(), false, bounds);
+    }
+
+    if let Some(bg) = &style.background {
+        push_background(
+            &info,
+            &bounds,
+            referenced_mutable_content.builder,
+            bg,
+            &style.background_size,
+            &style.background_repeat,
+            &referenced_mutable_content.app_resources,
+        );
+        if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+            snapshot.extend(SnapshotPrimitive::background(bg, &bounds, &referenced_mutable_content.app_resources));
+        }
+    }
+
+    if let Some(border) = &style.border {
+        push_border(
+            &info,
+            referenced_mutable_content.builder,
+            border,
+            &style.border_radius,
+        );
+        if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+            snapshot.extend(SnapshotPrimitive::border(border, &style.border_radius));
+        }
+    }
+}
+
+/// Returns whether a stacking context was pushed for the node (see `displaylist_handle_rect`)
 fn push_rectangles_into_displaylist_inner<'a,'b,'c,'d,'e,'f, T: Layout>(
     item: RenderableNodeId,
     scrollable_nodes: &mut ScrolledNodes,
@@ -550,8 +1062,8 @@ fn push_rectangles_into_displaylist_inner<'a,'b,'c,'d,'e,'f, T: Layout>(
     referenced_content: &DisplayListParametersRef<'a,'b,'c,'d,'e, T>,
     referenced_mutable_content: &mut DisplayListParametersMut<'f, T>,
     clip_stack: &mut Vec<NodeId>,
-) {
-    displaylist_handle_rect(
+) -> bool {
+    let pushed_stacking_context = displaylist_handle_rect(
         scrollable_nodes,
         rectangle,
         referenced_content,
@@ -578,6 +1090,7 @@ fn push_rectangles_into_displaylist_inner<'a,'b,'c,'d,'e,'f, T: Layout>(
         clip_stack.pop();
     }
 */
+    pushed_stacking_context
 }
 
 /// Parameters that apply to a single rectangle / div node
@@ -601,12 +1114,17 @@ fn get_clip_region<'a>(bounds: LayoutRect, rect: &DisplayRectangle<'a>) -> Optio
 }
 
 /// Push a single rectangle into the display list builder
+///
+/// If the rectangle is transformed or transparent, a stacking context is pushed before the
+/// rectangle, which has to be popped after the children of the rectangle have been pushed.
+/// Returns whether such a stacking context was pushed.
 #[inline]
 fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
     scrollable_nodes: &mut ScrolledNodes,
     rectangle: &DisplayListRectParams<'a, T>,
     referenced_content: &DisplayListParametersRef<'b,'c,'d,'e,'f, T>,
     referenced_mutable_content: &mut DisplayListParametersMut<'g, T>)
+-> bool
 {
     let DisplayListParametersRef {
         css, display_rectangle_arena,
@@ -620,18 +1138,42 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
 
     let rect = &display_rectangle_arena[*rect_idx];
     let bounds = layout_result.rects[*rect_idx].bounds;
+    let is_visible = rect.style.visibility.unwrap_or_default() == StyleVisibility::Visible;
 
     let info = LayoutPrimitiveInfo {
         rect: bounds,
         clip_rect: bounds,
         is_backface_visible: false,
-        tag: rect.tag.map(|tag| (tag, 0)).or({
-            scrollable_nodes.overflowing_nodes
-            .get(&rect_idx)
-            .map(|scrolled| (scrolled.scroll_tag_id.0, 0))
-        }),
+        // Hidden nodes can't be hit, so they don't get a hit-testing tag
+        tag: if is_visible {
+            rect.tag.map(|tag| (tag, 0)).or({
+                scrollable_nodes.overflowing_nodes
+                .get(&rect_idx)
+                .map(|scrolled| (scrolled.scroll_tag_id.0, 0))
+            })
+        } else {
+            None
+        },
     };
 
+    if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+        snapshot.begin_item(rect_idx.index(), html_node.get_path().to_string(), info.tag.is_some(), bounds);
+    }
+
+    // The stacking context has to be pushed before the primitives of the node, so
+    // that the opacity and the transform also apply to the node itself
+    let pushed_stacking_context = push_stacking_context(&rect.style, bounds, referenced_mutable_content);
+
+    // visibility: hidden - the node still takes up space and its stacking context
+    // applies to its (possibly visible) children, but the node itself isn't drawn
+    if !is_visible {
+        return pushed_stacking_context;
+    }
+
+    if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+        snapshot.extend(SnapshotPrimitive::box_shadows(&rect.style.box_shadow, BoxShadowClipMode::Outset));
+    }
+
     let clip_region_id = get_clip_region(bounds, &rect).map(|clip|
         referenced_mutable_content.builder.define_clip(bounds, vec![clip], None)
     );
@@ -646,6 +1188,9 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
 
     if let Some(id) = clip_region_id {
         referenced_mutable_content.builder.push_clip_id(id);
+        if let (Some(snapshot), Some(radius)) = (referenced_mutable_content.snapshot.as_mut(), rect.style.border_radius) {
+            snapshot.push(SnapshotPrimitive::PushClip { rect: bounds.into(), radius: radius.0.into() });
+        }
     }
 
     // If the rect is hit-testing relevant, we need to push a rect anyway.
@@ -660,6 +1205,9 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
             &rect.style.background_repeat,
             &referenced_mutable_content.app_resources,
         );
+        if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+            snapshot.extend(SnapshotPrimitive::background(bg, &bounds, &referenced_mutable_content.app_resources));
+        }
     } else if info.tag.is_some() {
         const TRANSPARENT_BG: StyleColorU = StyleColorU { r: 0, g: 0, b: 0, a: 0 };
         push_rect(
@@ -667,6 +1215,9 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
             referenced_mutable_content.builder,
             &TRANSPARENT_BG,
         );
+        if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+            snapshot.push(SnapshotPrimitive::Rect { color: TRANSPARENT_BG.into() });
+        }
     }
 
     if let Some(ref border) = rect.style.border {
@@ -676,6 +1227,9 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
             &border,
             &rect.style.border_radius,
         );
+        if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+            snapshot.extend(SnapshotPrimitive::border(border, &rect.style.border_radius));
+        }
     }
 
     match html_node {
@@ -685,23 +1239,63 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
             // so this should succeed - if there were problems
             //
             // TODO: In the table demo, the numbers don't show - empty glyphs (why?)!
-            push_text(
+            let pushed_text = push_text(
                 &info,
                 referenced_mutable_content.builder,
                 layout_result,
                 rect_idx,
                 &rect.style,
                 &rect.layout,
-            )
+            );
+            if let (Some(snapshot), Some((glyphs, text_clip))) = (referenced_mutable_content.snapshot.as_mut(), pushed_text) {
+                let app_resources = &referenced_mutable_content.app_resources;
+                let font_size = ui_solver::font_size_to_au(ui_solver::get_font_size(&rect.style));
+                let fallback_fonts = app_resources.get_loaded_fallback_fonts(&rect.style, font_size);
+                // One text primitive per font, same as in push_text
+                for (font, font_glyphs) in glyphs.group_by_font() {
+                    let font_family = match font {
+                        0 => app_resources.resolve_font_family(&rect.style),
+                        _ => match fallback_fonts.get(font - 1) {
+                            Some((family, _, _)) => *family,
+                            None => continue,
+                        },
+                    };
+                    snapshot.push(SnapshotPrimitive::Text {
+                        color: rect.style.font_color.unwrap_or(DEFAULT_FONT_COLOR).0.into(),
+                        font_family: font_family.to_string(),
+                        font_weight: ui_solver::get_font_weight(&rect.style).0,
+                        font_style: ui_solver::get_font_style(&rect.style).to_string(),
+                        font_size: ui_solver::au_to_px(font_size),
+                        clip: text_clip.map(|clip| clip.into()),
+                        glyphs: font_glyphs.into_iter().map(|glyph| glyph.into()).collect(),
+                    });
+                }
+            }
         },
-        Image(image_id) => push_image(
-            &info,
-            referenced_mutable_content.builder,
-            referenced_mutable_content.app_resources,
-            image_id,
-            LayoutSize::new(info.rect.size.width, info.rect.size.height)
-        ),
-        GlTexture(callback) => push_opengl_texture(callback, &info, rectangle, referenced_content, referenced_mutable_content),
+        Image(image_id) => {
+            let size = LayoutSize::new(info.rect.size.width, info.rect.size.height);
+            push_image(
+                &info,
+                referenced_mutable_content.builder,
+                referenced_mutable_content.app_resources,
+                image_id,
+                size,
+            );
+            if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+                snapshot.push(SnapshotPrimitive::Image {
+                    width: size.width,
+                    height: size.height,
+                    image_id: Some(*image_id),
+                });
+            }
+        },
+        GlTexture(callback) => {
+            if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+                snapshot.push(SnapshotPrimitive::GlTexture);
+            }
+            push_opengl_texture(callback, &info, rectangle, referenced_content, referenced_mutable_content)
+        },
+        // The nodes of the iframe are recorded as separate items
         IFrame(callback) => push_iframe(callback, &info, scrollable_nodes, rectangle, referenced_content, referenced_mutable_content),
     };
 
@@ -716,6 +1310,15 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
     if clip_region_id.is_some() {
         referenced_mutable_content.builder.pop_clip_id();
     }
+
+    if let Some(snapshot) = referenced_mutable_content.snapshot.as_mut() {
+        snapshot.extend(SnapshotPrimitive::box_shadows(&rect.style.box_shadow, BoxShadowClipMode::Inset));
+        if clip_region_id.is_some() {
+            snapshot.push(SnapshotPrimitive::PopClip);
+        }
+    }
+
+    pushed_stacking_context
 }
 
 fn push_opengl_texture<'a,'b,'c,'d,'e,'f, T: Layout>(
@@ -734,6 +1337,11 @@ fn push_opengl_texture<'a,'b,'c,'d,'e,'f, T: Layout>(
         rectangle.window_size.winit_hidpi_factor
     );
 
+    // Headless windows have no OpenGL context to draw into, so the texture is skipped
+    if referenced_mutable_content.fake_window.is_headless() {
+        return;
+    }
+
     let texture;
 
     {
@@ -745,7 +1353,10 @@ fn push_opengl_texture<'a,'b,'c,'d,'e,'f, T: Layout>(
         }, bounds);
 
         // Reset the framebuffer and SRGB color target to 0
-        let gl_context = referenced_mutable_content.fake_window.read_only_window().get_gl_context();
+        let gl_context = match referenced_mutable_content.fake_window.read_only_window() {
+            Some(read_only_window) => read_only_window.get_gl_context(),
+            None => return,
+        };
 
         gl_context.bind_framebuffer(gl::FRAMEBUFFER, 0);
         gl_context.disable(gl::FRAMEBUFFER_SRGB);
@@ -768,7 +1379,7 @@ fn push_opengl_texture<'a,'b,'c,'d,'e,'f, T: Layout>(
 
     // Note: The ImageDescriptor has no effect on how large the image appears on-screen
     let descriptor = ImageDescriptor::new(texture_width as i32, texture_height as i32, ImageFormat::BGRA8, opaque, allow_mipmaps);
-    let key = referenced_mutable_content.app_resources.fake_display.render_api.generate_image_key();
+    let key = generate_image_key(&referenced_mutable_content.app_resources.fake_display);
     let external_image_id = ExternalImageId(new_opengl_texture_id() as u64);
 
     let data = ImageData::External(ExternalImageData {
@@ -822,7 +1433,7 @@ fn push_iframe<'a,'b,'c,'d,'e,'f, T: Layout>(
         (iframe_callback.0)(&iframe_pointer, window_info, bounds)
     };
 
-    // TODO: Right now, no focusing, hovering or :active allowed in iframes!
+    // TODO: Right now, no focusing, hovering, :active or drag & drop allowed in iframes!
     let is_mouse_down = false;
     let mut focused_node = None;
     let mut focus_target = None;
@@ -832,10 +1443,12 @@ fn push_iframe<'a,'b,'c,'d,'e,'f, T: Layout>(
     let ui_description = UiDescription::<T>::match_css_to_dom(
         &mut ui_state,
         &referenced_content.css,
+        None,
         &mut focused_node,
         &mut focus_target,
         &hovered_nodes,
-        is_mouse_down
+        is_mouse_down,
+        None,
     );
 
     let display_list = DisplayList::new_from_ui_description(&ui_description, &ui_state);
@@ -858,11 +1471,15 @@ fn push_iframe<'a,'b,'c,'d,'e,'f, T: Layout>(
         &*referenced_mutable_content.app_resources,
         rect_size,
         rect_origin,
+        None,
     );
 
+    // TODO: The scroll states of iframes aren't preserved between frames
+    let mut scroll_states = ScrollStates::new();
+
     let mut scrollable_nodes = get_nodes_that_need_scroll_clip(
         node_hierarchy, &display_list.rectangles, node_data, &layout_result.rects,
-        &layout_result.node_depths, referenced_content.pipeline_id
+        &layout_result.node_depths, referenced_content.pipeline_id, &mut scroll_states,
     );
 
     let rects_in_rendering_order = determine_rendering_order(
@@ -883,7 +1500,7 @@ fn push_iframe<'a,'b,'c,'d,'e,'f, T: Layout>(
         rectangle.window_size,
         rects_in_rendering_order,
         &mut scrollable_nodes,
-        &mut ScrollStates::new(),
+        &mut scroll_states,
         &referenced_content,
         referenced_mutable_content
     );
@@ -902,7 +1519,7 @@ fn push_iframe<'a,'b,'c,'d,'e,'f, T: Layout>(
 struct DisplayListParametersRef<'a, 'b, 'c, 'd, 'e, T: 'a + Layout> {
     pub node_data: &'a NodeDataContainer<NodeData<T>>,
     /// The CSS that should be applied to the DOM
-    pub css: &'b Css,
+    pub css: &'b ResolvedCss,
     /// Laid out words and rectangles (contains info about content bounds and text layout)
     pub layout_result: &'c LayoutResult,
     /// Reference to the arena that contains all the styled rectangles
@@ -929,6 +1546,8 @@ struct DisplayListParametersMut<'a, T: 'a + Layout> {
     /// Window access, so that sub-items can register OpenGL textures
     pub fake_window: &'a mut FakeWindow<T>,
     pub pipeline_id: PipelineId,
+    /// If set, all pushed primitives are also recorded here (for golden-file tests)
+    pub snapshot: Option<&'a mut DisplayListSnapshot>,
 }
 
 fn push_rect(
@@ -947,20 +1566,13 @@ fn push_text(
     node_id: &NodeId,
     rect_style: &RectStyle,
     rect_layout: &RectLayout,
-) {
+) -> Option<(LayoutedGlyphs, Option<LayoutRect>)> {
     use text_layout::get_layouted_glyphs;
     use css::webrender_translate::wr_translate_color_u;
     use ui_solver::determine_text_alignment;
 
-    let (scaled_words, _font_instance_key) = match layout_result.scaled_words.get(node_id) {
-        Some(s) => s,
-        None => return,
-    };
-
-    let (word_positions, font_instance_key) = match layout_result.positioned_word_cache.get(node_id) {
-        Some(s) => s,
-        None => return,
-    };
+    let (scaled_words, _font_instance_key) = layout_result.scaled_words.get(node_id)?;
+    let (word_positions, font_instance_keys) = layout_result.positioned_word_cache.get(node_id)?;
 
     let (horz_alignment, vert_alignment) = determine_text_alignment(rect_style, rect_layout);
 
@@ -1023,20 +1635,29 @@ fn push_text(
         builder.push_clip_id(clip_id);
     }
 
-    builder.push_text(
-        &info,
-        &layouted_glyphs.glyphs,
-        *font_instance_key,
-        font_color.into(),
-        Some(GlyphOptions {
-            render_mode: FontRenderMode::Subpixel,
-            flags: flags,
-        })
-    );
+    // Glyphs that were shaped with a fallback font have to be pushed as a separate text item
+    for (font, glyphs) in layouted_glyphs.group_by_font() {
+        let font_instance_key = match font_instance_keys.get(font) {
+            Some(s) => *s,
+            None => continue,
+        };
+        builder.push_text(
+            &info,
+            &glyphs,
+            font_instance_key,
+            font_color.into(),
+            Some(GlyphOptions {
+                render_mode: FontRenderMode::Subpixel,
+                flags: flags,
+            })
+        );
+    }
 
     if text_bounds.is_some() {
         builder.pop_clip_id();
     }
+
+    Some((layouted_glyphs, text_bounds))
 }
 
 enum ShouldPushShadow {
@@ -1529,6 +2150,10 @@ fn populate_css_properties(
                     apply_style_property(rect, default);
                 }
             }
+            // CSS variables are already substituted during the cascade
+            Variable(_) | VarReference(_) => { },
+            // Transitions and animations are stored separately in the `StyledNode`
+            Transition(_) | Animation(_) => { },
         }
     }
 }
@@ -1556,17 +2181,28 @@ fn apply_style_property(rect: &mut DisplayRectangle, property: &CssProperty) {
         Background(b)       => { rect.style.background = Some(b.clone());               },
         FontSize(f)         => { rect.style.font_size = Some(*f);                       },
         FontFamily(f)       => { rect.style.font_family = Some(f.clone());              },
+        FontWeight(w)       => { rect.style.font_weight = Some(*w);                     },
+        FontStyle(s)        => { rect.style.font_style = Some(*s);                      },
         LetterSpacing(l)    => { rect.style.letter_spacing = Some(*l);                  },
         TextAlign(ta)       => { rect.style.text_align = Some(*ta);                     },
+        TextDirection(d)    => { rect.style.direction = Some(*d);                       },
         BoxShadow(b)        => { StyleBoxShadow::merge(&mut rect.style.box_shadow, b);  },
         LineHeight(lh)      => { rect.style.line_height = Some(*lh);                    },
 
-        Width(w)            => { rect.layout.width = Some(*w);                          },
-        Height(h)           => { rect.layout.height = Some(*h);                         },
-        MinWidth(mw)        => { rect.layout.min_width = Some(*mw);                     },
-        MinHeight(mh)       => { rect.layout.min_height = Some(*mh);                    },
-        MaxWidth(mw)        => { rect.layout.max_width = Some(*mw);                     },
-        MaxHeight(mh)       => { rect.layout.max_height = Some(*mh);                    },
+        // A `calc()` size and the plain value of the same property override each other
+        Width(w)            => { rect.layout.width = Some(*w); rect.layout.calc_sizes.remove(&CssPropertyType::Width); },
+        IntrinsicWidth(w)   => {
+            rect.layout.intrinsic_width = Some(*w);
+            rect.layout.width = None;
+            rect.layout.calc_sizes.remove(&CssPropertyType::Width);
+        },
+        Height(h)           => { rect.layout.height = Some(*h); rect.layout.calc_sizes.remove(&CssPropertyType::Height); },
+        MinWidth(mw)        => { rect.layout.min_width = Some(*mw); rect.layout.calc_sizes.remove(&CssPropertyType::MinWidth); },
+        MinHeight(mh)       => { rect.layout.min_height = Some(*mh); rect.layout.calc_sizes.remove(&CssPropertyType::MinHeight); },
+        MaxWidth(mw)        => { rect.layout.max_width = Some(*mw); rect.layout.calc_sizes.remove(&CssPropertyType::MaxWidth); },
+        MaxHeight(mh)       => { rect.layout.max_height = Some(*mh); rect.layout.calc_sizes.remove(&CssPropertyType::MaxHeight); },
+        Calc(property, c)   => { rect.layout.calc_sizes.insert(*property, c.clone());   },
+        BoxSizing(b)        => { rect.layout.box_sizing = Some(*b);                     },
 
         Position(p)         => { rect.layout.position = Some(*p);                       },
         Top(t)              => { rect.layout.top = Some(*t);                            },
@@ -1580,13 +2216,29 @@ fn apply_style_property(rect: &mut DisplayRectangle, property: &CssProperty) {
         WordSpacing(ws)     => { rect.style.word_spacing = Some(*ws);                   },
         TabWidth(tw)        => { rect.style.tab_width = Some(*tw);                      },
 
+        Display(d)          => { rect.layout.display = Some(*d);                        },
         FlexGrow(g)         => { rect.layout.flex_grow = Some(*g)                       },
         FlexShrink(s)       => { rect.layout.flex_shrink = Some(*s)                     },
+        FlexBasis(b)        => { rect.layout.flex_basis = Some(*b);                     },
+        Order(o)            => { rect.layout.order = Some(*o);                          },
         FlexWrap(w)         => { rect.layout.wrap = Some(*w);                           },
         FlexDirection(d)    => { rect.layout.direction = Some(*d);                      },
         JustifyContent(j)   => { rect.layout.justify_content = Some(*j);                },
         AlignItems(a)       => { rect.layout.align_items = Some(*a);                    },
+        AlignSelf(a)        => { rect.layout.align_self = Some(*a);                     },
         AlignContent(a)     => { rect.layout.align_content = Some(*a);                  },
+        RowGap(g)           => { rect.layout.row_gap = Some(*g);                        },
+        ColumnGap(g)        => { rect.layout.column_gap = Some(*g);                     },
+        GridTemplateColumns(t) => { rect.layout.grid_template_columns = Some(t.clone()); },
+        GridTemplateRows(t) => { rect.layout.grid_template_rows = Some(t.clone());      },
+        GridGap(g)          => { rect.layout.grid_gap = Some(*g);                       },
+        GridColumn(c)       => { rect.layout.grid_column = Some(*c);                    },
+        GridRow(r)          => { rect.layout.grid_row = Some(*r);                       },
         Cursor(_)           => { /* cursor neither affects layout nor styling */        },
+        Opacity(o)          => { rect.style.opacity = Some(*o);                         },
+        Transform(t)        => { rect.style.transform = Some(t.clone());                },
+        TransformOrigin(o)  => { rect.style.transform_origin = Some(*o);                },
+        ZIndex(z)           => { rect.style.z_index = Some(*z);                         },
+        Visibility(v)       => { rect.style.visibility = Some(*v);                      },
     }
 }
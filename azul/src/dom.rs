@@ -244,6 +244,16 @@ pub enum On {
     DroppedFile,
     /// A file was hovered, but has exited the window
     HoveredFileCancelled,
+    /// The user has started dragging this (draggable) element
+    DragStart,
+    /// A dragged element has entered this element
+    DragEnter,
+    /// An element is being dragged over this element
+    DragOver,
+    /// A dragged element has left this element
+    DragLeave,
+    /// A dragged element has been dropped on this element
+    Drop,
     /// Equivalent to `onfocus`
     FocusReceived,
     /// Equivalent to `onblur`
@@ -342,6 +352,11 @@ impl From<On> for EventFilter {
             HoveredFile          => EventFilter::Hover(HoverEventFilter::HoveredFile),
             DroppedFile          => EventFilter::Hover(HoverEventFilter::DroppedFile),
             HoveredFileCancelled => EventFilter::Hover(HoverEventFilter::HoveredFileCancelled),
+            DragStart            => EventFilter::Hover(HoverEventFilter::DragStart),
+            DragEnter            => EventFilter::Hover(HoverEventFilter::DragEnter),
+            DragOver             => EventFilter::Hover(HoverEventFilter::DragOver),
+            DragLeave            => EventFilter::Hover(HoverEventFilter::DragLeave),
+            Drop                 => EventFilter::Hover(HoverEventFilter::Drop),
             FocusReceived        => EventFilter::Focus(FocusEventFilter::FocusReceived),        // focus!
             FocusLost            => EventFilter::Focus(FocusEventFilter::FocusLost),            // focus!
         }
@@ -369,6 +384,11 @@ pub enum HoverEventFilter {
     HoveredFile,
     DroppedFile,
     HoveredFileCancelled,
+    DragStart,
+    DragEnter,
+    DragOver,
+    DragLeave,
+    Drop,
 }
 
 impl HoverEventFilter {
@@ -393,6 +413,11 @@ impl HoverEventFilter {
             HoveredFile => None,
             DroppedFile => None,
             HoveredFileCancelled => None,
+            DragStart => None,
+            DragEnter => None,
+            DragOver => None,
+            DragLeave => None,
+            Drop => None,
         }
     }
 }
@@ -491,6 +516,9 @@ pub struct NodeData<T: Layout> {
     pub ids: Vec<DomString>,
     /// `.myclass .otherclass`
     pub classes: Vec<DomString>,
+    /// `[data-state="open"]` - similar to the `data-*` attributes in HTML, attributes
+    /// don't have any effect on their own, but can be selected in the CSS
+    pub attributes: BTreeMap<DomString, DomString>,
     /// `On::MouseUp` -> `Callback(my_button_click_handler)`
     pub callbacks: Vec<(EventFilter, Callback<T>)>,
     /// Usually not set by the user directly - `FakeWindow::add_default_callback`
@@ -515,9 +543,31 @@ pub struct NodeData<T: Layout> {
     /// }
     /// ```
     pub dynamic_css_overrides: Vec<(DomString, CssProperty)>,
-    /// Whether this div can be dragged or not, similar to `draggable = "true"` in HTML, .
+    /// Sets CSS variables (custom properties) on this node, which are inherited by
+    /// all children of this node. Overrides the variables set in the stylesheet.
     ///
-    /// **TODO**: Currently doesn't do anything, since the drag & drop implementation is missing, API stub.
+    /// For example, in the CSS stylesheet:
+    ///
+    /// ```css,ignore
+    /// * { --main-color: blue; }
+    /// .button { background: var(--main-color); }
+    /// ```
+    ///
+    /// ```rust,ignore
+    /// // All buttons in this subtree will have a red background
+    /// let node = NodeData {
+    ///     css_variables: vec![("--main-color".into(), "red".into())],
+    ///     .. Default::default()
+    /// }
+    /// ```
+    pub css_variables: Vec<(DomString, DomString)>,
+    /// Whether this div can be dragged or not, similar to `draggable = "true"` in HTML.
+    ///
+    /// Pressing the left mouse button on a draggable node and moving the mouse sends
+    /// an `On::DragStart` event to the node, followed by `On::DragEnter`, `On::DragOver`,
+    /// `On::DragLeave` and `On::Drop` events to the nodes below the cursor. While
+    /// the node is dragged, it can be styled via `:dragging`, nodes with an
+    /// `On::Drop` callback can be styled via `:drop-target`.
     pub is_draggable: bool,
     /// Whether this div can be focused, and if yes, in what default to `None` (not focusable).
     /// Note that without this, there can be no `On::FocusReceived` (equivalent to onfocus),
@@ -568,9 +618,11 @@ impl<T: Layout> PartialEq for NodeData<T> {
         self.node_type == other.node_type &&
         self.ids == other.ids &&
         self.classes == other.classes &&
+        self.attributes == other.attributes &&
         self.callbacks == other.callbacks &&
         self.default_callback_ids == other.default_callback_ids &&
         self.dynamic_css_overrides == other.dynamic_css_overrides &&
+        self.css_variables == other.css_variables &&
         self.is_draggable == other.is_draggable &&
         self.tab_index == other.tab_index
     }
@@ -584,9 +636,11 @@ impl<T: Layout> Default for NodeData<T> {
             node_type: NodeType::Div,
             ids: Vec::new(),
             classes: Vec::new(),
+            attributes: BTreeMap::new(),
             callbacks: Vec::new(),
             default_callback_ids: Vec::new(),
             dynamic_css_overrides: Vec::new(),
+            css_variables: Vec::new(),
             is_draggable: false,
             tab_index: None,
         }
@@ -602,6 +656,9 @@ impl<T: Layout> Hash for NodeData<T> {
         for class in &self.classes {
             class.hash(state);
         }
+        for attribute in &self.attributes {
+            attribute.hash(state);
+        }
         for callback in &self.callbacks {
             callback.hash(state);
         }
@@ -611,6 +668,9 @@ impl<T: Layout> Hash for NodeData<T> {
         for dynamic_css_override in &self.dynamic_css_overrides {
             dynamic_css_override.hash(state);
         }
+        for css_variable in &self.css_variables {
+            css_variable.hash(state);
+        }
         self.is_draggable.hash(state);
         self.tab_index.hash(state);
     }
@@ -622,9 +682,11 @@ impl<T: Layout> Clone for NodeData<T> {
             node_type: self.node_type.clone(),
             ids: self.ids.clone(),
             classes: self.classes.clone(),
+            attributes: self.attributes.clone(),
             callbacks: self.callbacks.clone(),
             default_callback_ids: self.default_callback_ids.clone(),
             dynamic_css_overrides: self.dynamic_css_overrides.clone(),
+            css_variables: self.css_variables.clone(),
             is_draggable: self.is_draggable.clone(),
             tab_index: self.tab_index.clone(),
         }
@@ -659,18 +721,22 @@ impl<T: Layout> fmt::Debug for NodeData<T> {
                 \tnode_type: {:?}, \
                 \tids: {:?}, \
                 \tclasses: {:?}, \
+                \tattributes: {:?}, \
                 \tcallbacks: {:?}, \
                 \tdefault_callback_ids: {:?}, \
                 \tdynamic_css_overrides: {:?}, \
+                \tcss_variables: {:?}, \
                 \tis_draggable: {:?}, \
                 \ttab_index: {:?}, \
             }}",
             self.node_type,
             self.ids,
             self.classes,
+            self.attributes,
             self.callbacks,
             self.default_callback_ids,
             self.dynamic_css_overrides,
+            self.css_variables,
             self.is_draggable,
             self.tab_index,
         )
@@ -714,6 +780,13 @@ impl<T: Layout> NodeData<T> {
     pub fn has_class(&self, class: &str) -> bool {
         self.classes.iter().any(|self_class| self_class.equals_str(class))
     }
+
+    /// Returns the value of the attribute with the given name, if it is set
+    pub fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(attribute_name, _)| attribute_name.equals_str(name))
+            .map(|(_, value)| value.as_str())
+    }
 }
 
 /// Most strings are known at compile time, spares a bit of
@@ -1050,6 +1123,13 @@ impl<T: Layout> Dom<T> {
         self
     }
 
+    /// Sets an attribute (i.e. `"data-state"`) that can be selected via `[data-state="open"]` in the CSS
+    #[inline]
+    pub fn with_attribute<S: Into<DomString>, V: Into<DomString>>(mut self, name: S, value: V) -> Self {
+        self.add_attribute(name, value);
+        self
+    }
+
     /// Same as `event`, but easier to use for method chaining in a builder-style pattern
     #[inline]
     pub fn with_callback<O: Into<EventFilter>>(mut self, on: O, callback: Callback<T>) -> Self {
@@ -1069,6 +1149,13 @@ impl<T: Layout> Dom<T> {
         self
     }
 
+    /// Sets a CSS variable (i.e. `"--main-color"`) on this node and all of its children
+    #[inline]
+    pub fn with_css_variable<S: Into<DomString>, V: Into<DomString>>(mut self, name: S, value: V) -> Self {
+        self.add_css_variable(name, value);
+        self
+    }
+
     #[inline]
     pub fn with_tab_index(mut self, tab_index: TabIndex) -> Self {
         self.set_tab_index(tab_index);
@@ -1091,6 +1178,11 @@ impl<T: Layout> Dom<T> {
         self.arena.node_data[self.head].classes.push(class.into());
     }
 
+    #[inline]
+    pub fn add_attribute<S: Into<DomString>, V: Into<DomString>>(&mut self, name: S, value: V) {
+        self.arena.node_data[self.head].attributes.insert(name.into(), value.into());
+    }
+
     #[inline]
     pub fn add_callback<O: Into<EventFilter>>(&mut self, on: O, callback: Callback<T>) {
         self.arena.node_data[self.head].callbacks.push((on.into(), callback));
@@ -1106,6 +1198,11 @@ impl<T: Layout> Dom<T> {
         self.arena.node_data[self.head].dynamic_css_overrides.push((override_id.into(), property));
     }
 
+    #[inline]
+    pub fn add_css_variable<S: Into<DomString>, V: Into<DomString>>(&mut self, name: S, value: V) {
+        self.arena.node_data[self.head].css_variables.push((name.into(), value.into()));
+    }
+
     #[inline]
     pub fn set_tab_index(&mut self, tab_index: TabIndex) {
         self.arena.node_data[self.head].tab_index = Some(tab_index);
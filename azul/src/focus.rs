@@ -0,0 +1,169 @@
+//! Keyboard focus traversal, i.e. moving the focus between the
+//! focusable nodes of the DOM via Tab and Shift + Tab
+
+use std::collections::BTreeMap;
+use glium::glutin::{WindowEvent, KeyboardInput, ElementState, VirtualKeyCode};
+use {
+    dom::TabIndex,
+    id_tree::{NodeId, NodeHierarchy},
+    traits::Layout,
+    ui_state::UiState,
+};
+
+/// In which direction the focus should move
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum FocusDirection {
+    /// Tab - focus the next node
+    Next,
+    /// Shift + Tab - focus the previous node
+    Previous,
+}
+
+/// Returns the direction the focus should move in if the event is a Tab key press
+pub(crate) fn get_focus_direction(event: &WindowEvent) -> Option<FocusDirection> {
+    match event {
+        WindowEvent::KeyboardInput {
+            input: KeyboardInput { state: ElementState::Pressed, virtual_keycode: Some(VirtualKeyCode::Tab), modifiers, .. }, ..
+        } => {
+            Some(if modifiers.shift { FocusDirection::Previous } else { FocusDirection::Next })
+        },
+        _ => None,
+    }
+}
+
+/// Returns all nodes that can be focused via the keyboard, in the order
+/// that they get focused in when pressing Tab repeatedly:
+///
+/// - Nodes with a `TabIndex::Global(n)` (where `n > 0`) come first, ordered by `n`
+/// - All other nodes are ordered in document order, except that siblings with a
+///   `TabIndex::OverrideInParent(n)` come before their other siblings, ordered by `n`
+/// - Nodes with a `TabIndex::NoKeyboardFocus` are skipped
+pub(crate) fn get_tab_order<T: Layout>(ui_state: &UiState<T>) -> Vec<NodeId> {
+    if ui_state.dom.arena.is_empty() {
+        return Vec::new();
+    }
+    let tab_indices = ui_state.tab_index_tags.values().cloned().collect::<BTreeMap<NodeId, TabIndex>>();
+    get_tab_order_inner(&ui_state.dom.arena.node_layout, ui_state.dom.root, &tab_indices)
+}
+
+fn get_tab_order_inner(
+    node_hierarchy: &NodeHierarchy,
+    root: NodeId,
+    tab_indices: &BTreeMap<NodeId, TabIndex>,
+) -> Vec<NodeId> {
+
+    // Depth-first traversal, but siblings with an OverrideInParent index are visited first
+    let mut document_order = Vec::with_capacity(node_hierarchy.len());
+    let mut stack = vec![root];
+
+    while let Some(node_id) = stack.pop() {
+        document_order.push(node_id);
+        let mut children = node_id.children(node_hierarchy).collect::<Vec<_>>();
+        // NOTE: sort_by_key is stable, so the siblings stay in document order otherwise
+        children.sort_by_key(|child| match tab_indices.get(child) {
+            Some(TabIndex::OverrideInParent(index)) => (0, *index),
+            _ => (1, 0),
+        });
+        stack.extend(children.into_iter().rev());
+    }
+
+    let mut focusable_nodes = document_order.into_iter().filter_map(|node_id| {
+        match tab_indices.get(&node_id)? {
+            TabIndex::NoKeyboardFocus => None,
+            TabIndex::Global(index) if *index > 0 => Some((*index, node_id)),
+            _ => Some((::std::usize::MAX, node_id)),
+        }
+    }).collect::<Vec<_>>();
+
+    focusable_nodes.sort_by_key(|(global_index, _)| *global_index);
+    focusable_nodes.into_iter().map(|(_, node_id)| node_id).collect()
+}
+
+/// Returns which node should be focused next, wraps around at the end of the tab order.
+///
+/// If the current node isn't in the tab order (or nothing is focused), the first
+/// (or last, when moving backwards) node is focused.
+pub(crate) fn get_next_focus_node(
+    tab_order: &[NodeId],
+    current_focus: Option<NodeId>,
+    direction: FocusDirection,
+) -> Option<NodeId> {
+
+    use self::FocusDirection::*;
+
+    if tab_order.is_empty() {
+        return None;
+    }
+
+    let last = tab_order.len() - 1;
+    let current_position = current_focus.and_then(|focus| tab_order.iter().position(|node_id| *node_id == focus));
+
+    let next_position = match (current_position, direction) {
+        (None, Next) => 0,
+        (None, Previous) => last,
+        (Some(position), Next) => if position == last { 0 } else { position + 1 },
+        (Some(position), Previous) => if position == 0 { last } else { position - 1 },
+    };
+
+    Some(tab_order[next_position])
+}
+
+#[cfg(test)]
+struct TestLayout { }
+
+#[cfg(test)]
+impl Layout for TestLayout {
+    fn layout(&self) -> ::dom::Dom<Self> {
+        ::dom::Dom::div()
+    }
+}
+
+#[test]
+fn test_tab_order() {
+
+    use dom::Dom;
+
+    // See the documentation of `TabIndex::OverrideInParent`
+    let ui_state = Dom::<TestLayout>::div().with_tab_index(TabIndex::Auto)
+        .with_child(Dom::div().with_tab_index(TabIndex::OverrideInParent(0)))
+        .with_child(Dom::div().with_tab_index(TabIndex::OverrideInParent(5)))
+        .with_child(Dom::div().with_tab_index(TabIndex::OverrideInParent(2)))
+        .with_child(Dom::div().with_tab_index(TabIndex::Global(5)))
+        .with_child(Dom::div().with_tab_index(TabIndex::NoKeyboardFocus))
+        .with_child(Dom::div())
+        .into_ui_state();
+
+    let tab_order = get_tab_order(&ui_state);
+    let ids = |ids: &[usize]| ids.iter().map(|id| NodeId::new(*id)).collect::<Vec<_>>();
+
+    assert_eq!(tab_order, ids(&[4, 0, 1, 3, 2]));
+
+    // element1 -> element3 -> element2 -> element4 -> div
+    let mut focus = Some(NodeId::new(1));
+    let mut visited = Vec::new();
+    for _ in 0..4 {
+        focus = get_next_focus_node(&tab_order, focus, FocusDirection::Next);
+        visited.push(focus.unwrap());
+    }
+    assert_eq!(visited, ids(&[3, 2, 4, 0]));
+
+    assert_eq!(get_next_focus_node(&tab_order, Some(NodeId::new(4)), FocusDirection::Previous), Some(NodeId::new(2)));
+    assert_eq!(get_next_focus_node(&tab_order, None, FocusDirection::Next), Some(NodeId::new(4)));
+    assert_eq!(get_next_focus_node(&tab_order, None, FocusDirection::Previous), Some(NodeId::new(2)));
+    assert_eq!(get_next_focus_node(&[], None, FocusDirection::Next), None);
+}
+
+#[test]
+fn test_tab_order_nested() {
+
+    use dom::Dom;
+
+    let ui_state = Dom::<TestLayout>::div()
+        .with_child(Dom::div()
+            .with_child(Dom::div().with_tab_index(TabIndex::Auto))
+            .with_child(Dom::div().with_tab_index(TabIndex::Auto)))
+        .with_child(Dom::div().with_tab_index(TabIndex::Auto))
+        .into_ui_state();
+
+    assert_eq!(get_tab_order(&ui_state), vec![NodeId::new(2), NodeId::new(3), NodeId::new(4)]);
+}
@@ -0,0 +1,856 @@
+//! Headless windows, for running the layout of an app without opening a
+//! window or creating an OpenGL context (i.e. for unit tests on a CI server)
+//!
+//! A `HeadlessWindow` runs the same pipeline as a regular window - `Layout::layout()`,
+//! CSS matching, layout solving and building the display list - against a virtual
+//! window size and DPI factor, but never submits anything to WebRender. Afterwards,
+//! the positioned rectangles can be inspected via `HeadlessWindow::get_layout_result()`.
+//!
+//! ```no_run,ignore
+//! let mut app = App::new_headless(MyDataModel { }, AppConfig::default());
+//! let mut window = HeadlessWindow::new(WindowState::default(), css::native());
+//! app.run_headless(&mut window).unwrap();
+//!
+//! // Node IDs are assigned in depth-first order, so the first node is the root
+//! let node_ids = window.get_layout_result().unwrap().rects.linear_iter().collect::<Vec<_>>();
+//! let root_rect = window.get_rect(node_ids[0]).unwrap();
+//! assert_eq!(root_rect.bounds.size.width, 800.0);
+//! ```
+//!
+//! Note that `GlTextureCallback`s are not invoked in headless windows, since there
+//! is no OpenGL context to draw into.
+//!
+//! Input can be simulated via `App::inject_event`, which runs the event through the
+//! same hit-testing and callback logic as a regular window event would (except that
+//! the hit-testing is done on the CPU, using the rectangles of the last layout):
+//!
+//! ```no_run,ignore
+//! let result = app.inject_event(&mut window, HeadlessEvent::Click(LogicalPosition::new(10.0, 10.0))).unwrap();
+//! assert!(result.should_redraw);
+//! assert!(result.callbacks_called.contains(&(node_ids[1], EventFilter::Hover(HoverEventFilter::MouseUp))));
+//! ```
+
+use std::{slice, path::PathBuf, collections::BTreeMap};
+use webrender::api::{PipelineId, Epoch, HitTestResult, HitTestItem, LayoutPoint};
+use glium::glutin::{
+    WindowEvent, DeviceId, ElementState, MouseScrollDelta, TouchPhase,
+    KeyboardInput, ModifiersState, VirtualKeyCode, dpi::LogicalPosition,
+};
+use azul_css::{Css, StyleVisibility};
+use {
+    app::{AppState, RuntimeError, call_callbacks, scroll_hit_nodes},
+    callbacks::{GliumWindowId, DefaultCallbackSystem, Redraw},
+    diff::DomDiff,
+    display_list::{DisplayList, DisplayListWindowParams, ScrolledNodes, get_parent_scroll_node, get_node_transform},
+    dom::EventFilter,
+    id_tree::NodeId,
+    scrollbar::{handle_scrollbar_events, translate_to_scrolled_content},
+    snapshot::DisplayListSnapshot,
+    traits::Layout,
+    ui_description::UiDescription,
+    ui_state::UiState,
+    window::{FakeWindow, ScrollStates, ResolvedCss, new_pipeline_id, new_css_generation},
+    window_state::{WindowState, KeyboardState},
+};
+pub use ui_solver::{LayoutResult, PositionedRectangle};
+pub use glium::glutin::MouseButton;
+
+/// A window without an OpenGL context, see the module-level documentation
+pub struct HeadlessWindow<T: Layout> {
+    /// Headless windows have no system handle, so this is a dummy ID, shared by
+    /// all headless windows. To prevent them from overwriting each others state,
+    /// the `FakeWindow` is only registered in the `AppState` under this ID while
+    /// the window is being laid out or handles an event.
+    pub(crate) id: GliumWindowId,
+    /// The user-facing state of this window, `None` while it is registered in the `AppState`
+    pub(crate) fake_window: Option<FakeWindow<T>>,
+    /// Current state of the window - modify `state.size` to change the virtual
+    /// window size or HiDPI factor the layout is solved for
+    pub state: WindowState,
+    /// The style of this window, use `set_css` to replace it
+    pub(crate) css: Css,
+    /// Changes every time the `css` is replaced
+    pub(crate) css_generation: usize,
+    /// The `css` with the `@media` rules resolved for the current window state
+    pub(crate) resolved_css: ResolvedCss,
+    /// Purely internal, scroll states of the nodes that are currently scrolled
+    pub(crate) scroll_states: ScrollStates,
+    pub(crate) pipeline_id: PipelineId,
+    pub(crate) epoch: Epoch,
+    pub(crate) last_scrolled_nodes: ScrolledNodes,
+    /// The UI state of the last frame, `None` if `App::run_headless` was never called
+    pub(crate) ui_state: Option<UiState<T>>,
+    /// The styled DOM of the last frame, `None` if `App::run_headless` was never called
+    pub(crate) ui_description: Option<UiDescription<T>>,
+    /// The layout of the last frame, `None` if `App::run_headless` was never called
+    pub(crate) layout_result: Option<LayoutResult>,
+    /// The display list of the last frame, `None` if `App::run_headless` was never called
+    pub(crate) display_list_snapshot: Option<DisplayListSnapshot>,
+}
+
+impl<T: Layout> HeadlessWindow<T> {
+
+    /// Creates a new headless window - the window size and HiDPI factor are
+    /// taken from `state.size`
+    pub fn new(state: WindowState, mut css: Css) -> Self {
+        css.sort_by_specificity();
+        Self {
+            id: unsafe { GliumWindowId::dummy() },
+            fake_window: Some(FakeWindow {
+                state: state.clone(),
+                default_callbacks: DefaultCallbackSystem::new(),
+                read_only_window: None,
+            }),
+            state,
+            css,
+            css_generation: new_css_generation(),
+            resolved_css: ResolvedCss::default(),
+            scroll_states: ScrollStates::new(),
+            pipeline_id: new_pipeline_id(),
+            epoch: Epoch(0),
+            last_scrolled_nodes: ScrolledNodes::default(),
+            ui_state: None,
+            ui_description: None,
+            layout_result: None,
+            display_list_snapshot: None,
+        }
+    }
+
+    /// Replaces the style of this window, the whole DOM is restyled in the next frame
+    pub fn set_css(&mut self, mut css: Css) {
+        css.sort_by_specificity();
+        self.css = css;
+        self.css_generation = new_css_generation();
+    }
+
+    /// Returns the layout of the last frame (all positioned rectangles and the laid out text)
+    pub fn get_layout_result(&self) -> Option<&LayoutResult> {
+        self.layout_result.as_ref()
+    }
+
+    /// Returns how far the content of an `overflow: scroll` / `overflow: auto` node is
+    /// scrolled (in pixels), `None` if the node isn't scrollable
+    pub fn get_scroll_offset(&self, node_id: NodeId) -> Option<(f32, f32)> {
+        let scroll_node = self.last_scrolled_nodes.overflowing_nodes.get(&node_id)?;
+        self.scroll_states.get_scroll_amount(&scroll_node.parent_external_scroll_id)
+    }
+
+    /// Returns the node that currently has the keyboard focus
+    pub fn get_focused_node(&self) -> Option<NodeId> {
+        self.state.internal.focused_node
+    }
+
+    /// Returns the difference between the DOM of the last frame and the frame before it,
+    /// `None` if less than two frames were rendered
+    pub fn get_dom_diff(&self) -> Option<&DomDiff> {
+        self.ui_description.as_ref()?.dom_diff.as_ref()
+    }
+
+    /// Returns the positioned rectangle of the given node in the last frame
+    pub fn get_rect(&self, node_id: NodeId) -> Option<PositionedRectangle> {
+        self.layout_result.as_ref()?.rects.get(node_id).cloned()
+    }
+
+    /// Returns the display list of the last frame, for comparing it against a golden file
+    pub fn get_display_list_snapshot(&self) -> Option<&DisplayListSnapshot> {
+        self.display_list_snapshot.as_ref()
+    }
+}
+
+/// Synthetic input event, which can be sent to a `HeadlessWindow` via `App::inject_event`
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeadlessEvent {
+    /// Moves the cursor to the given (logical) position in the window
+    MouseMove(LogicalPosition),
+    /// Moves the cursor out of the window
+    MouseLeave,
+    /// Presses a mouse button at the current cursor position
+    MouseDown(MouseButton),
+    /// Releases a mouse button at the current cursor position
+    MouseUp(MouseButton),
+    /// Moves the cursor to the given position, then presses and releases the left mouse button
+    Click(LogicalPosition),
+    /// Scrolls the nodes under the cursor by the given amount of pixels
+    Scroll { x: f64, y: f64 },
+    /// Presses a key - the scancode of synthetic keyboard events is always 0
+    KeyDown(VirtualKeyCode),
+    /// Releases a key
+    KeyUp(VirtualKeyCode),
+    /// Sends one `ReceivedCharacter` event for each character of the text
+    Text(String),
+    /// A file is dragged over the window
+    HoveredFile(PathBuf),
+    /// A file that was dragged over the window is dragged out again
+    HoveredFileCancelled,
+    /// A file is dropped onto the window
+    DroppedFile(PathBuf),
+}
+
+impl HeadlessEvent {
+
+    /// Converts the event into the `WindowEvent`(s) that a real window would receive
+    fn into_window_events(self, window_state: &WindowState) -> Vec<WindowEvent> {
+
+        use self::HeadlessEvent::*;
+
+        // Synthetic events have no input device attached
+        let device_id = unsafe { DeviceId::dummy() };
+        let keyboard_state = &window_state.internal.keyboard_state;
+        let modifiers = get_modifiers(keyboard_state, None, None);
+
+        // The cursor position of a real window event is not adjusted by the HiDPI factor
+        let cursor_moved = |position: LogicalPosition| WindowEvent::CursorMoved {
+            device_id,
+            position: LogicalPosition::new(
+                position.x * window_state.size.hidpi_factor / window_state.size.winit_hidpi_factor,
+                position.y * window_state.size.hidpi_factor / window_state.size.winit_hidpi_factor,
+            ),
+            modifiers,
+        };
+
+        let mouse_input = |state: ElementState, button: MouseButton| WindowEvent::MouseInput {
+            device_id,
+            state,
+            button,
+            modifiers,
+        };
+
+        let keyboard_input = |state: ElementState, virtual_keycode: VirtualKeyCode, modifiers: ModifiersState| WindowEvent::KeyboardInput {
+            device_id,
+            input: KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(virtual_keycode),
+                modifiers,
+            },
+        };
+
+        match self {
+            MouseMove(position) => vec![cursor_moved(position)],
+            MouseLeave => vec![WindowEvent::CursorLeft { device_id }],
+            MouseDown(button) => vec![mouse_input(ElementState::Pressed, button)],
+            MouseUp(button) => vec![mouse_input(ElementState::Released, button)],
+            Click(position) => vec![
+                cursor_moved(position),
+                mouse_input(ElementState::Pressed, MouseButton::Left),
+                mouse_input(ElementState::Released, MouseButton::Left),
+            ],
+            Scroll { x, y } => vec![WindowEvent::MouseWheel {
+                device_id,
+                // update_scroll_state() inverts the delta, so the delta has to be inverted here
+                delta: MouseScrollDelta::PixelDelta(LogicalPosition::new(-x, -y)),
+                phase: TouchPhase::Moved,
+                modifiers,
+            }],
+            KeyDown(vk) => vec![keyboard_input(ElementState::Pressed, vk, get_modifiers(keyboard_state, Some(vk), None))],
+            KeyUp(vk) => vec![keyboard_input(ElementState::Released, vk, get_modifiers(keyboard_state, None, Some(vk)))],
+            Text(text) => text.chars().map(WindowEvent::ReceivedCharacter).collect(),
+            HoveredFile(path) => vec![WindowEvent::HoveredFile(path)],
+            HoveredFileCancelled => vec![WindowEvent::HoveredFileCancelled],
+            DroppedFile(path) => vec![WindowEvent::DroppedFile(path)],
+        }
+    }
+}
+
+/// Returns the modifier keys that are held down after the `pressed_key` is pressed
+/// and the `released_key` is released
+fn get_modifiers(
+    keyboard_state: &KeyboardState,
+    pressed_key: Option<VirtualKeyCode>,
+    released_key: Option<VirtualKeyCode>,
+) -> ModifiersState {
+    use glium::glutin::VirtualKeyCode::*;
+    let key_is_down = |key: VirtualKeyCode| {
+        (keyboard_state.current_virtual_keycodes.contains(&key) || pressed_key == Some(key)) &&
+        released_key != Some(key)
+    };
+    let is_down = |a: VirtualKeyCode, b: VirtualKeyCode| key_is_down(a) || key_is_down(b);
+    ModifiersState {
+        shift: is_down(LShift, RShift),
+        ctrl: is_down(LControl, RControl),
+        alt: is_down(LAlt, RAlt),
+        logo: is_down(LWin, RWin),
+    }
+}
+
+/// What happened when an event was injected via `App::inject_event`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlessEventResult {
+    /// Which callbacks were called (the node the callback was attached to + the
+    /// event filter that triggered the callback), in the order they were called in
+    pub callbacks_called: Vec<(NodeId, EventFilter)>,
+    /// Whether a callback requested a redraw (or a `:hover` / `:active` style changed).
+    /// If this is true, the window was already re-laid out.
+    pub should_redraw: bool,
+}
+
+/// Registers the `FakeWindow` of the headless window in the `AppState` (so that the
+/// callbacks can access it), runs the closure and takes the `FakeWindow` back out again
+fn with_registered_window<T: Layout, U, F>(
+    app_state: &mut AppState<T>,
+    window: &mut HeadlessWindow<T>,
+    closure: F,
+) -> Result<U, RuntimeError<T>>
+    where F: FnOnce(&mut AppState<T>, &mut HeadlessWindow<T>) -> Result<U, RuntimeError<T>>
+{
+    let fake_window = window.fake_window.take().ok_or(RuntimeError::WindowIndexError)?;
+    app_state.windows.insert(window.id, fake_window);
+    let result = closure(app_state, window);
+    window.fake_window = app_state.windows.remove(&window.id);
+    result
+}
+
+/// Runs a single frame for the headless window: calls the `Layout::layout()` function,
+/// styles the DOM, solves the layout and builds the display list (which is then discarded)
+pub(crate) fn layout_headless_window<T: Layout>(
+    app_state: &mut AppState<T>,
+    window: &mut HeadlessWindow<T>,
+) -> Result<(), RuntimeError<T>> {
+    with_registered_window(app_state, window, layout_registered_window)
+}
+
+fn layout_registered_window<T: Layout>(
+    app_state: &mut AppState<T>,
+    window: &mut HeadlessWindow<T>,
+) -> Result<(), RuntimeError<T>> {
+
+    use self::RuntimeError::*;
+
+    // The user-facing state is overwritten with the state of the headless window,
+    // since there is no "real" window whose state could diverge from it
+    app_state.windows.get_mut(&window.id).ok_or(WindowIndexError)?.state = window.state.clone();
+
+    let mut ui_state = UiState::from_app_state(app_state, &window.id)?;
+
+    let is_mouse_down = window.state.internal.mouse_state.mouse_down();
+    let dragged_node = window.state.internal.get_dragged_node();
+
+    window.resolved_css.update(&window.css, window.css_generation, &window.state.get_media_info());
+
+    let ui_description = UiDescription::match_css_to_dom(
+        &mut ui_state,
+        &window.resolved_css,
+        window.ui_description.as_ref(),
+        &mut window.state.internal.focused_node,
+        &mut window.state.internal.pending_focus_target,
+        &window.state.internal.hovered_nodes,
+        is_mouse_down,
+        dragged_node,
+    );
+
+    let mut display_list_snapshot = DisplayListSnapshot::new();
+
+    let (scrolled_nodes, layout_result) = {
+        let display_list = DisplayList::new_from_ui_description(&ui_description, &ui_state);
+        let fake_window = app_state.windows.get_mut(&window.id).ok_or(WindowIndexError)?;
+        let (_, scrolled_nodes, layout_result) = display_list.into_display_list_builder_inner(
+            &mut app_state.data,
+            DisplayListWindowParams {
+                pipeline_id: window.pipeline_id,
+                epoch: window.epoch,
+                size: window.state.size,
+                css: &ui_description.css,
+                scroll_states: &mut window.scroll_states,
+            },
+            fake_window,
+            &mut app_state.resources,
+            window.layout_result.as_ref(),
+            true,
+            Some(&mut display_list_snapshot),
+        );
+        (scrolled_nodes, layout_result)
+    };
+
+    window.last_scrolled_nodes = scrolled_nodes;
+    window.ui_state = Some(ui_state);
+    window.ui_description = Some(ui_description);
+    window.layout_result = Some(layout_result);
+    window.display_list_snapshot = Some(display_list_snapshot);
+
+    app_state.resources.garbage_collect_fonts_and_images();
+
+    Ok(())
+}
+
+/// Runs the event through the same pipeline as a regular window event: updates the
+/// window state, hit-tests the nodes of the last frame and invokes the callbacks.
+/// Each resulting `WindowEvent` is processed as a separate frame.
+pub(crate) fn inject_headless_event<T: Layout>(
+    app_state: &mut AppState<T>,
+    window: &mut HeadlessWindow<T>,
+    event: HeadlessEvent,
+    enable_tab_navigation: bool,
+) -> Result<HeadlessEventResult, RuntimeError<T>> {
+    with_registered_window(app_state, window, |app_state, window| {
+        inject_registered_event(app_state, window, event, enable_tab_navigation)
+    })
+}
+
+fn inject_registered_event<T: Layout>(
+    app_state: &mut AppState<T>,
+    window: &mut HeadlessWindow<T>,
+    event: HeadlessEvent,
+    enable_tab_navigation: bool,
+) -> Result<HeadlessEventResult, RuntimeError<T>> {
+
+    use self::RuntimeError::*;
+
+    // Hit-testing needs the layout of the last frame
+    if window.layout_result.is_none() {
+        layout_registered_window(app_state, window)?;
+    }
+
+    let mut result = HeadlessEventResult {
+        callbacks_called: Vec::new(),
+        should_redraw: false,
+    };
+
+    for event in event.into_window_events(&window.state) {
+
+        let (mut frame_event_info, _) = window.state.update_window_state(&[event.clone()]);
+
+        if frame_event_info.should_hittest {
+
+            let hit_test_results = hit_test_headless_window(window);
+
+            let callback_result = call_callbacks(
+                hit_test_results.as_ref(),
+                &event,
+                &mut window.state,
+                &window.id,
+                window.ui_state.as_ref().ok_or(WindowIndexError)?,
+                app_state,
+                enable_tab_navigation,
+            )?;
+
+            if callback_result.should_update_screen == Redraw {
+                frame_event_info.should_redraw_window = true;
+            }
+
+            if let Some(overwrites_focus) = callback_result.callbacks_overwrites_focus {
+                window.state.internal.pending_focus_target = Some(overwrites_focus);
+            }
+
+            result.callbacks_called.extend(callback_result.callbacks_called.into_iter());
+
+            if let Some(hit_test_results) = &hit_test_results {
+                if scroll_hit_nodes(hit_test_results, &window.state, &window.last_scrolled_nodes, &mut window.scroll_states) {
+                    frame_event_info.should_redraw_window = true;
+                }
+            }
+        }
+
+        if handle_scrollbar_events(slice::from_ref(&event), &mut window.state, &window.last_scrolled_nodes, &mut window.scroll_states) {
+            frame_event_info.should_redraw_window = true;
+        }
+
+        // Reset the scroll amount to 0 (for the next frame)
+        window.state.internal.mouse_state.scroll_x = 0.0;
+        window.state.internal.mouse_state.scroll_y = 0.0;
+
+        if frame_event_info.should_redraw_window {
+            result.should_redraw = true;
+            layout_registered_window(app_state, window)?;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Same as the hit-testing that WebRender does, but on the CPU, using the rectangles of the
+/// last layout. Returns the hit-tested items in back-to-front order.
+fn hit_test_headless_window<T: Layout>(window: &HeadlessWindow<T>) -> Option<HitTestResult> {
+
+    let cursor_location = window.state.internal.mouse_state.cursor_pos
+        .map(|pos| LayoutPoint::new(pos.x as f32, pos.y as f32))?;
+
+    let layout_result = window.layout_result.as_ref()?;
+    let ui_state = window.ui_state.as_ref()?;
+    let ui_description = window.ui_description.as_ref()?;
+    let node_hierarchy = &ui_description.ui_descr_arena.node_layout;
+    let scrolled_nodes = &window.last_scrolled_nodes;
+
+    // Inverse transforms of all transformed nodes (`None` if the transform can't be inverted,
+    // i.e. if the node is scaled to zero), to move the cursor into the untransformed content
+    let display_list = DisplayList::new_from_ui_description(ui_description, ui_state);
+    let inverse_transforms = layout_result.rects.linear_iter().filter_map(|node_id| {
+        let transform = get_node_transform(&display_list.rectangles[node_id].style, layout_result.rects[node_id].bounds)?;
+        Some((node_id, transform.inverse()))
+    }).collect::<BTreeMap<_, _>>();
+
+    // The nodes are stored in depth-first order, which is the
+    // order in which they are pushed into the display list
+    let items = layout_result.rects.linear_iter().filter_map(|node_id| {
+        // Hidden nodes still take up space, but can't be hit
+        if display_list.rectangles[node_id].style.visibility == Some(StyleVisibility::Hidden) {
+            return None;
+        }
+        // The rectangles of the layout don't know about scrolling, so the cursor
+        // has to be moved into the scrolled content of the parent nodes
+        let scroll_parent = get_parent_scroll_node(node_id, node_hierarchy, &scrolled_nodes.overflowing_nodes);
+        let mut point = translate_to_scrolled_content(cursor_location, scroll_parent, scrolled_nodes, &window.scroll_states)?;
+        // A transform applies to the node itself and all of its children, outermost transform first
+        let ancestor_transforms = node_id.ancestors(node_hierarchy)
+            .filter_map(|ancestor| inverse_transforms.get(&ancestor))
+            .collect::<Vec<_>>();
+        for inverse_transform in ancestor_transforms.into_iter().rev() {
+            point = inverse_transform.as_ref()?.transform_point2d(&point)?;
+        }
+        let bounds = layout_result.rects[node_id].bounds;
+        if !bounds.contains(&point) {
+            return None;
+        }
+        let tag = ui_state.node_ids_to_tag_ids.get(&node_id).cloned().or_else(|| {
+            scrolled_nodes.overflowing_nodes.get(&node_id).map(|scrolled| scrolled.scroll_tag_id.0)
+        })?;
+        Some(HitTestItem {
+            pipeline: window.pipeline_id,
+            tag: (tag, 0),
+            point_in_viewport: cursor_location,
+            point_relative_to_item: LayoutPoint::new(point.x - bounds.origin.x, point.y - bounds.origin.y),
+        })
+    }).collect();
+
+    Some(HitTestResult { items })
+}
+
+/// Lays out the DOM of `data`, styled with the given CSS, in a headless window of the default size
+#[cfg(all(test, feature = "css_parser"))]
+fn run_headless_with_css<T: Layout>(data: T, css: &str) -> (::app::App<T>, HeadlessWindow<T>) {
+    use app::{App, AppConfig};
+    let css = ::azul_css_parser::new_from_str(css).unwrap();
+    let mut app = App::new_headless(data, AppConfig::default());
+    let mut window = HeadlessWindow::new(WindowState::default(), css);
+    app.run_headless(&mut window).unwrap();
+    (app, window)
+}
+
+/// Clicks the left mouse button at the given position, returns the callbacks that were called
+#[cfg(all(test, feature = "css_parser"))]
+fn click<T: Layout>(app: &mut ::app::App<T>, window: &mut HeadlessWindow<T>, x: f64, y: f64) -> Vec<(NodeId, EventFilter)> {
+    app.inject_event(window, HeadlessEvent::MouseMove(LogicalPosition::new(x, y))).unwrap();
+    app.inject_event(window, HeadlessEvent::MouseDown(MouseButton::Left)).unwrap();
+    app.inject_event(window, HeadlessEvent::MouseUp(MouseButton::Left)).unwrap().callbacks_called
+}
+
+#[test]
+fn test_headless_window_layout() {
+
+    use app::{App, AppConfig};
+    use dom::Dom;
+
+    struct TestLayout { }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::div()
+        }
+    }
+
+    let mut app = App::new_headless(TestLayout { }, AppConfig::default());
+    let mut window = HeadlessWindow::new(WindowState::default(), Css::default());
+
+    assert!(window.get_layout_result().is_none());
+
+    app.run_headless(&mut window).unwrap();
+
+    assert!(app.app_state.resources.is_headless());
+    assert!(app.app_state.windows.is_empty());
+    assert!(window.fake_window.as_ref().unwrap().is_headless());
+    assert_eq!(window.get_layout_result().unwrap().rects.len(), 1);
+    assert!(window.get_rect(NodeId::new(0)).is_some());
+
+    let snapshot = window.get_display_list_snapshot().unwrap();
+    assert_eq!(snapshot.items.len(), 1);
+    assert_eq!(snapshot.items[0].node_type, "div");
+    assert!(snapshot.items[0].primitives.is_empty());
+
+    let image = app.render_headless(&window).unwrap();
+    assert_eq!(image.pixels.len(), image.width * image.height * 4);
+
+    // The second frame is diffed against the first one
+    assert!(window.get_dom_diff().is_none());
+    app.run_headless(&mut window).unwrap();
+    assert!(window.get_dom_diff().unwrap().is_empty());
+}
+
+#[test]
+fn test_multiple_headless_windows() {
+
+    use glium::glutin::dpi::LogicalSize;
+    use app::{App, AppConfig};
+    use dom::Dom;
+
+    struct TestLayout { }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::div()
+        }
+    }
+
+    let mut app = App::new_headless(TestLayout { }, AppConfig::default());
+    let mut small_window = HeadlessWindow::new(WindowState::default(), Css::default());
+    let mut large_window = HeadlessWindow::new(WindowState::default(), Css::default());
+    small_window.state.size.dimensions = LogicalSize::new(400.0, 300.0);
+
+    app.run_headless(&mut small_window).unwrap();
+    app.run_headless(&mut large_window).unwrap();
+    app.inject_event(&mut small_window, HeadlessEvent::Click(LogicalPosition::new(10.0, 10.0))).unwrap();
+
+    // Each window keeps its own state, none of them stays registered in the app
+    assert!(app.app_state.windows.is_empty());
+    assert_eq!(small_window.fake_window.as_ref().unwrap().state.size.dimensions, LogicalSize::new(400.0, 300.0));
+    assert_eq!(large_window.fake_window.as_ref().unwrap().state.size.dimensions, LogicalSize::new(800.0, 600.0));
+    assert_eq!(small_window.get_rect(NodeId::new(0)).unwrap().bounds.size.width, 400.0);
+    assert_eq!(large_window.get_rect(NodeId::new(0)).unwrap().bounds.size.width, 800.0);
+}
+
+#[test]
+fn test_headless_window_inject_event() {
+
+    use app::{App, AppConfig};
+    use dom::Dom;
+
+    struct TestLayout { }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::div()
+        }
+    }
+
+    let mut app = App::new_headless(TestLayout { }, AppConfig::default());
+    let mut window = HeadlessWindow::new(WindowState::default(), Css::default());
+
+    let result = app.inject_event(&mut window, HeadlessEvent::MouseMove(LogicalPosition::new(10.0, 20.0))).unwrap();
+    assert!(result.callbacks_called.is_empty());
+    assert_eq!(window.state.get_mouse_state().cursor_pos, Some(LogicalPosition::new(10.0, 20.0)));
+
+    app.inject_event(&mut window, HeadlessEvent::KeyDown(VirtualKeyCode::LShift)).unwrap();
+    assert!(window.state.get_keyboard_state().shift_down);
+    app.inject_event(&mut window, HeadlessEvent::Text("ab".into())).unwrap();
+    assert_eq!(window.state.get_keyboard_state().current_char, Some('b'));
+    app.inject_event(&mut window, HeadlessEvent::KeyUp(VirtualKeyCode::LShift)).unwrap();
+    assert!(!window.state.get_keyboard_state().shift_down);
+
+    app.inject_event(&mut window, HeadlessEvent::MouseLeave).unwrap();
+    assert_eq!(window.state.get_mouse_state().cursor_pos, None);
+}
+
+#[cfg(feature = "css_parser")]
+#[test]
+fn test_headless_window_drag_and_drop() {
+
+    use app::{App, AppConfig};
+    use callbacks::{Callback, CallbackInfo, UpdateScreen, DontRedraw};
+    use dom::{Dom, On, HoverEventFilter};
+    use traits::Modify;
+
+    struct TestLayout {
+        dropped_payload: Option<String>,
+    }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::div()
+                .with_callback(On::Drop, Callback(on_drop))
+                .with_child(Dom::div().with_id("card")
+                    .is_draggable(true)
+                    .with_callback(On::DragStart, Callback(on_drag_start))
+                    .with_callback(On::Drop, Callback(on_drop)))
+        }
+    }
+
+    fn on_drag_start(_: &mut AppState<TestLayout>, event: &mut CallbackInfo<TestLayout>) -> UpdateScreen {
+        event.set_drag_payload("card-1");
+        DontRedraw
+    }
+
+    fn on_drop(app_state: &mut AppState<TestLayout>, event: &mut CallbackInfo<TestLayout>) -> UpdateScreen {
+        let payload = event.get_drag_data().and_then(|drag_data| drag_data.payload.clone());
+        app_state.data.modify(|data| data.dropped_payload = payload);
+        DontRedraw
+    }
+
+    let css = ::azul_css_parser::new_from_str("#card { height: 100px; }").unwrap();
+
+    let mut app = App::new_headless(TestLayout { dropped_payload: None }, AppConfig::default());
+    let mut window = HeadlessWindow::new(WindowState::default(), css);
+
+    app.inject_event(&mut window, HeadlessEvent::MouseMove(LogicalPosition::new(10.0, 10.0))).unwrap();
+    app.inject_event(&mut window, HeadlessEvent::MouseDown(MouseButton::Left)).unwrap();
+
+    // Moving the cursor by less than the drag threshold doesn't start the drag
+    let result = app.inject_event(&mut window, HeadlessEvent::MouseMove(LogicalPosition::new(11.0, 11.0))).unwrap();
+    assert!(result.callbacks_called.is_empty());
+    assert_eq!(window.state.internal.get_dragged_node(), None);
+
+    let result = app.inject_event(&mut window, HeadlessEvent::MouseMove(LogicalPosition::new(50.0, 50.0))).unwrap();
+    assert_eq!(result.callbacks_called, vec![(NodeId::new(1), EventFilter::Hover(HoverEventFilter::DragStart))]);
+    assert!(result.should_redraw);
+    assert_eq!(window.state.internal.get_dragged_node(), Some(NodeId::new(1)));
+
+    // The card can't be dropped onto itself, so the drop goes to the closest drop target behind it
+    let result = app.inject_event(&mut window, HeadlessEvent::MouseUp(MouseButton::Left)).unwrap();
+    assert_eq!(result.callbacks_called, vec![(NodeId::new(0), EventFilter::Hover(HoverEventFilter::Drop))]);
+    assert_eq!(window.state.internal.get_dragged_node(), None);
+    assert_eq!(app.app_state.data.lock().unwrap().dropped_payload, Some("card-1".to_string()));
+}
+
+#[cfg(feature = "css_parser")]
+#[test]
+fn test_headless_window_drop_on_nested_targets() {
+
+    use app::{App, AppConfig};
+    use callbacks::{Callback, CallbackInfo, UpdateScreen, DontRedraw};
+    use dom::{Dom, On, HoverEventFilter};
+
+    struct TestLayout { }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::div()
+                .with_child(Dom::div().with_id("source").is_draggable(true))
+                .with_child(Dom::div().with_id("outer")
+                    .with_callback(On::Drop, Callback(on_drop))
+                    .with_child(Dom::div().with_id("inner").with_callback(On::Drop, Callback(on_drop))))
+        }
+    }
+
+    fn on_drop(_: &mut AppState<TestLayout>, _: &mut CallbackInfo<TestLayout>) -> UpdateScreen {
+        DontRedraw
+    }
+
+    let css = ::azul_css_parser::new_from_str("
+        #source { height: 100px; }
+        #outer { height: 200px; }
+        #inner { height: 50px; }
+    ").unwrap();
+
+    let mut app = App::new_headless(TestLayout { }, AppConfig::default());
+    let mut window = HeadlessWindow::new(WindowState::default(), css);
+
+    let mut drag_and_drop = |window: &mut HeadlessWindow<TestLayout>, drop_position: LogicalPosition| {
+        app.inject_event(window, HeadlessEvent::MouseMove(LogicalPosition::new(10.0, 10.0))).unwrap();
+        app.inject_event(window, HeadlessEvent::MouseDown(MouseButton::Left)).unwrap();
+        app.inject_event(window, HeadlessEvent::MouseMove(LogicalPosition::new(10.0, 60.0))).unwrap();
+        app.inject_event(window, HeadlessEvent::MouseMove(drop_position)).unwrap();
+        app.inject_event(window, HeadlessEvent::MouseUp(MouseButton::Left)).unwrap().callbacks_called
+    };
+
+    // Only the innermost drop target receives the drop, not its parent
+    let callbacks_called = drag_and_drop(&mut window, LogicalPosition::new(10.0, 120.0));
+    assert_eq!(callbacks_called, vec![(NodeId::new(3), EventFilter::Hover(HoverEventFilter::Drop))]);
+
+    let callbacks_called = drag_and_drop(&mut window, LogicalPosition::new(10.0, 250.0));
+    assert_eq!(callbacks_called, vec![(NodeId::new(2), EventFilter::Hover(HoverEventFilter::Drop))]);
+}
+
+#[cfg(feature = "css_parser")]
+#[test]
+fn test_headless_window_hit_test_transformed_node() {
+
+    use callbacks::{Callback, CallbackInfo, UpdateScreen, DontRedraw};
+    use dom::{Dom, On, HoverEventFilter};
+
+    struct TestLayout { }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::div().with_child(Dom::div().with_id("box").with_callback(On::MouseUp, Callback(on_mouse_up)))
+        }
+    }
+
+    fn on_mouse_up(_: &mut AppState<TestLayout>, _: &mut CallbackInfo<TestLayout>) -> UpdateScreen {
+        DontRedraw
+    }
+
+    let (mut app, mut window) = run_headless_with_css(TestLayout { }, "
+        #box { width: 100px; height: 100px; transform: translateX(200px) rotate(45deg); }
+    ");
+
+    let box_clicked = vec![(NodeId::new(1), EventFilter::Hover(HoverEventFilter::MouseUp))];
+
+    // The untransformed position of the box isn't hit anymore
+    assert!(click(&mut app, &mut window, 50.0, 50.0).is_empty());
+    // Center of the moved box
+    assert_eq!(click(&mut app, &mut window, 250.0, 50.0), box_clicked);
+    // Inside of the bounding box of the rotated box, but outside of the box itself
+    assert!(click(&mut app, &mut window, 205.0, 5.0).is_empty());
+}
+
+#[cfg(feature = "css_parser")]
+#[test]
+fn test_headless_window_media_queries() {
+
+    use glium::glutin::dpi::LogicalSize;
+    use dom::Dom;
+    use azul_css::ColorScheme;
+
+    struct TestLayout { }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::div().with_child(Dom::div().with_id("box"))
+        }
+    }
+
+    let (mut app, mut window) = run_headless_with_css(TestLayout { }, "
+        #box { width: 100px; height: 100px; }
+        @media (max-width: 600px) { #box { width: 50px; } }
+        @media (prefers-color-scheme: dark) and (min-resolution: 2dppx) { #box { width: 20px; } }
+    ");
+
+    let box_width = |window: &HeadlessWindow<TestLayout>| window.get_rect(NodeId::new(1)).unwrap().bounds.size.width as usize;
+
+    assert_eq!(box_width(&window), 100);
+
+    // Resizing the window across the breakpoint restyles the box
+    window.state.size.dimensions = LogicalSize::new(500.0, 600.0);
+    app.run_headless(&mut window).unwrap();
+    assert_eq!(box_width(&window), 50);
+
+    window.state.size.dimensions = LogicalSize::new(800.0, 600.0);
+    window.state.size.hidpi_factor = 2.0;
+    window.state.color_scheme = ColorScheme::Dark;
+    app.run_headless(&mut window).unwrap();
+    assert_eq!(box_width(&window), 20);
+
+    // Replacing the CSS restyles the DOM, even though the DOM itself didn't change
+    window.set_css(::azul_css_parser::new_from_str("#box { width: 70px; height: 100px; }").unwrap());
+    app.run_headless(&mut window).unwrap();
+    assert!(window.get_dom_diff().unwrap().is_empty());
+    assert_eq!(box_width(&window), 70);
+}
+
+#[cfg(feature = "css_parser")]
+#[test]
+fn test_headless_window_hit_test_hidden_node() {
+
+    use callbacks::{Callback, CallbackInfo, UpdateScreen, DontRedraw};
+    use dom::{Dom, On, HoverEventFilter};
+
+    struct TestLayout { }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::div()
+                .with_child(Dom::div().with_id("hidden").with_callback(On::MouseUp, Callback(on_mouse_up)))
+                .with_child(Dom::div().with_id("visible").with_callback(On::MouseUp, Callback(on_mouse_up)))
+        }
+    }
+
+    fn on_mouse_up(_: &mut AppState<TestLayout>, _: &mut CallbackInfo<TestLayout>) -> UpdateScreen {
+        DontRedraw
+    }
+
+    let (mut app, mut window) = run_headless_with_css(TestLayout { }, "
+        #hidden { visibility: hidden; height: 100px; }
+        #visible { height: 100px; }
+    ");
+
+    // The hidden node still takes up space, but can't be hit, the node below it can
+    assert!(click(&mut app, &mut window, 10.0, 50.0).is_empty());
+    assert_eq!(click(&mut app, &mut window, 10.0, 150.0), vec![(NodeId::new(2), EventFilter::Hover(HoverEventFilter::MouseUp))]);
+}
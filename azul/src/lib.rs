@@ -60,12 +60,20 @@
 //!
 //! There are a few limitations that should be noted:
 //!
-//! - There are no scrollbars yet. Creating scrollable frames can be done by
-//!   [creating an `IFrameCallback`].
-//! - Similarly, there is no clipping of overflowing content yet - clipping only
-//!   works for `IFrameCallback`s.
-//! - There is no support for CSS animations of any kind yet
+//! - Only `overflow: scroll` and `overflow: auto` nodes clip their overflowing content,
+//!   `overflow: hidden` doesn't clip yet. Scrollable nodes get scrollbars that can be
+//!   scrolled with the mouse wheel, by dragging the thumb or by clicking on the track / buttons.
+//!   The scrollbars can't be styled via CSS yet.
+//! - CSS transitions and animations only interpolate colors, sizes, paddings, margins and
+//!   similar numeric properties - other properties don't transition and switch at the
+//!   half-way point between two keyframes
+//! - Percentages are only resolved against sizes that are known before the layout is solved
+//!   (fixed sizes, the window size or the size of stretched items), otherwise they are treated
+//!   as `auto` (or `0` for margins and paddings). `calc()` expressions that mix metrics are
+//!   only supported for `width`, `height` and their `min-` / `max-` variants
 //! - Changing dynamic variables will trigger an entire UI relayout and restyling
+//! - The DOM diffing only skips the restyling and text shaping of unchanged nodes, the sizes
+//!   and positions of all nodes are solved again on every relayout
 //!
 //! # Hello world
 //!
@@ -115,7 +123,6 @@
 //! [`Redraw`]: ../azul/callbacks/constant.Redraw.html
 //! [`DontRedraw`]: ../azul/callbacks/constant.DontRedraw.html
 //! [`GlTextureCallback`]: ../azul/callbacks/struct.GlTextureCallback.html
-//! [creating an `IFrameCallback`]: ../azul/dom/struct.Dom.html#method.iframe
 //! [from a file]: ../azul/dom/struct.Dom.html#method.from_file
 //! [CSS]: ../azul/css/index.html
 //! [load and parse stylesheets]: ../azul/css/fn.from_str.html
@@ -137,6 +144,7 @@
 #[macro_use(warn, error, lazy_static)]
 #[cfg_attr(feature = "svg", macro_use(implement_vertex, uniform))]
 pub extern crate azul_dependencies;
+extern crate unicode_bidi;
 #[cfg(feature = "serde_serialization")]
 #[cfg_attr(feature = "serde_serialization", macro_use)]
 extern crate serde;
@@ -197,6 +205,14 @@ pub mod dialogs;
 pub mod dom;
 /// Re-exports of errors
 pub mod error;
+/// Window-less layout of an app, for testing without a GPU (`HeadlessWindow`)
+pub mod headless;
+/// CPU rasterizer for rendering frames into images without a GPU (`RgbaImage`)
+pub mod rasterizer;
+/// Stable snapshots of the display list, for golden-file tests
+pub mod snapshot;
+/// DOM diffing, used to only restyle / re-shape the text of the parts of the DOM that changed
+pub mod diff;
 /// Handles text layout (modularized, can be used as a standalone module)
 pub mod text_layout;
 /// Main `Layout` trait definition + convenience traits for `Arc<Mutex<T>>`
@@ -229,8 +245,12 @@ mod logging;
 mod ui_solver;
 /// DOM styling module
 mod style;
-/// DOM diffing
-mod diff;
+/// Keyboard focus traversal (tab navigation)
+mod focus;
+/// Scrollbars of scrollable nodes (layout + handling of mouse events)
+mod scrollbar;
+/// CSS transitions and `@keyframes` animations
+mod animation;
 /// Checks that two-way bound values are on the stack
 mod stack_checked_pointer;
 /// Window state handling and diffing
@@ -272,12 +292,13 @@ pub mod prelude {
     pub use callbacks::{
         Callback, TimerCallback, IFrameCallback, GlTextureCallback,
         UpdateScreen, Redraw, DontRedraw,
-        CallbackInfo, FocusTarget, LayoutInfo, HidpiAdjustedBounds, Texture,
+        CallbackInfo, FocusTarget, DragData, LayoutInfo, HidpiAdjustedBounds, Texture,
     };
     pub use dom::{
         Dom, DomHash, NodeType, NodeData, On, DomString, TabIndex,
         EventFilter, HoverEventFilter, FocusEventFilter, NotEventFilter, WindowEventFilter,
     };
+    pub use headless::HeadlessWindow;
     pub use traits::{Layout, Modify};
     pub use window::{
         MonitorIter, Window, WindowCreateOptions,
@@ -0,0 +1,1016 @@
+//! Software rasterizer - renders the display list of a frame into an RGBA image
+//! on the CPU, so that screenshots can be taken on machines without a GPU
+//! (CI servers, documentation generation, bug reports)
+//!
+//! The rasterizer works on the `DisplayListSnapshot` that is recorded while the
+//! display list is built (see the `snapshot` module), so it draws the same rects,
+//! borders, gradients, images, text runs, box shadows and clips as WebRender does.
+//! The output is not pixel-identical to WebRender, but close enough for screenshots:
+//!
+//! - Dashed, dotted, double, groove, ridge, inset and outset borders are drawn as solid borders
+//! - Box shadows ignore the border radius of the element
+//! - Transformed content is sampled per pixel, without any additional anti-aliasing
+//! - Background images are always drawn at their original size and repeated
+//! - The content of `GlTexture` nodes is not drawn, since there is no OpenGL context
+//! - Glyphs are rasterized with `stb_truetype`, which is only available with the
+//!   `svg` feature - without it, text is not drawn
+//!
+//! ```no_run,ignore
+//! let mut app = App::new_headless(MyDataModel { }, AppConfig::default());
+//! let mut window = HeadlessWindow::new(WindowState::default(), css::native());
+//! app.run_headless(&mut window).unwrap();
+//! app.save_headless_screenshot(&window, "screenshot.png").unwrap();
+//! ```
+
+use std::{fs, io, path::Path};
+use webrender::api::{ImageData, ImageDescriptor, ImageFormat};
+use azul_css::ColorU;
+use {
+    app_resources::{AppResources, ImageId},
+    snapshot::{
+        DisplayListSnapshot, SnapshotPrimitive, SnapshotRect, SnapshotPoint,
+        SnapshotColor, SnapshotBorderRadius, SnapshotBorderSide, SnapshotBoxShadow,
+        SnapshotGradientStop, SnapshotTransform,
+    },
+};
+#[cfg(feature = "svg")]
+use {
+    FastHashMap,
+    snapshot::SnapshotGlyph,
+    stb_truetype::FontInfo,
+};
+
+/// Premultiplied RGBA color, components in the range `0.0..=1.0`
+type Rgba = [f32; 4];
+
+const TRANSPARENT: Rgba = [0.0, 0.0, 0.0, 0.0];
+
+/// Image made up of RGBA8 pixels (not premultiplied), row by row from top to bottom
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+
+    /// Returns the color of the pixel at (x, y) or `None` if the pixel is out of bounds
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<ColorU> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let idx = (y * self.width + x) * 4;
+        Some(ColorU {
+            r: self.pixels[idx],
+            g: self.pixels[idx + 1],
+            b: self.pixels[idx + 2],
+            a: self.pixels[idx + 3],
+        })
+    }
+
+    /// Encodes the image as an (uncompressed) PNG file
+    pub fn encode_png(&self) -> Vec<u8> {
+        png::encode_rgba8(self.width as u32, self.height as u32, &self.pixels)
+    }
+
+    /// Saves the image as a PNG file
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        fs::write(path, self.encode_png())
+    }
+}
+
+/// Rasterizes the snapshot of a display list into a `width` x `height` image,
+/// which is cleared with the `background_color` first
+pub fn rasterize(
+    snapshot: &DisplayListSnapshot,
+    width: usize,
+    height: usize,
+    background_color: ColorU,
+    app_resources: &AppResources,
+) -> RgbaImage {
+
+    let mut canvas = Canvas::new(width, height, premultiply(background_color.into()));
+    #[cfg(feature = "svg")]
+    let mut font_cache = FontCache::default();
+
+    for item in &snapshot.items {
+
+        // Box shadows have to be drawn with the radius of the element
+        let radius = item.primitives.iter().filter_map(|primitive| match primitive {
+            SnapshotPrimitive::PushClip { radius, .. } => Some(*radius),
+            _ => None,
+        }).next().unwrap_or_default();
+
+        for primitive in &item.primitives {
+            use self::SnapshotPrimitive::*;
+            match primitive {
+                PushClip { rect, radius } => canvas.push_clip(rect, radius),
+                PopClip => { canvas.clips.pop(); },
+                PushScrollFrame { clip, offset } => {
+                    canvas.push_clip(clip, &SnapshotBorderRadius::default());
+                    canvas.push_transform(&translation(-offset.x, -offset.y));
+                },
+                PopScrollFrame => {
+                    canvas.transforms.pop();
+                    canvas.clips.pop();
+                },
+                PushStackingContext { opacity, transform } => {
+                    canvas.push_transform(&transform.unwrap_or_else(|| translation(0.0, 0.0)));
+                    canvas.push_layer(*opacity);
+                },
+                PopStackingContext => {
+                    canvas.pop_layer();
+                    canvas.transforms.pop();
+                },
+                BoxShadow(shadow) => draw_box_shadow(&mut canvas, &item.bounds, &radius, shadow),
+                Rect { color } => {
+                    let color = premultiply(*color);
+                    canvas.fill(&item.bounds, |x, y| scale(color, rect_coverage(&item.bounds, x, y)));
+                },
+                LinearGradient { start, end, extend_mode, stops } => {
+                    let direction = (end.x - start.x, end.y - start.y);
+                    let length_squared = direction.0 * direction.0 + direction.1 * direction.1;
+                    let repeat = extend_mode == "repeat";
+                    canvas.fill(&item.bounds, |x, y| {
+                        let t = if length_squared <= 0.0 {
+                            0.0
+                        } else {
+                            ((x - start.x) * direction.0 + (y - start.y) * direction.1) / length_squared
+                        };
+                        scale(sample_gradient(stops, t, repeat), rect_coverage(&item.bounds, x, y))
+                    });
+                },
+                RadialGradient { center, radius, extend_mode, stops } => {
+                    let repeat = extend_mode == "repeat";
+                    canvas.fill(&item.bounds, |x, y| {
+                        let dx = if radius.x <= 0.0 { 0.0 } else { (x - center.x) / radius.x };
+                        let dy = if radius.y <= 0.0 { 0.0 } else { (y - center.y) / radius.y };
+                        let t = (dx * dx + dy * dy).sqrt();
+                        scale(sample_gradient(stops, t, repeat), rect_coverage(&item.bounds, x, y))
+                    });
+                },
+                BackgroundImage { css_id, .. } => {
+                    if let Some(image_id) = app_resources.get_css_image_id(css_id) {
+                        draw_background_image(&mut canvas, &item.bounds, app_resources, image_id);
+                    }
+                },
+                Border { sides, radius } => draw_border(&mut canvas, &item.bounds, sides, &radius.unwrap_or_default()),
+                Text { color, font_family, font_weight, font_style, font_size, clip, glyphs } => {
+                    #[cfg(not(feature = "svg"))] {
+                        let _ = (color, font_family, font_weight, font_style, font_size, clip, glyphs);
+                    }
+                    #[cfg(feature = "svg")] {
+                        if let Some(clip) = clip {
+                            canvas.push_clip(clip, &SnapshotBorderRadius::default());
+                        }
+                        if let Some(font) = font_cache.get(font_family, *font_weight, font_style, app_resources) {
+                            draw_glyphs(&mut canvas, font, *font_size, premultiply(*color), glyphs);
+                        }
+                        if clip.is_some() {
+                            canvas.clips.pop();
+                        }
+                    }
+                },
+                Image { width, height, image_id } => {
+                    if let Some(image_id) = image_id {
+                        let rect = SnapshotRect { x: item.bounds.x, y: item.bounds.y, width: *width, height: *height };
+                        draw_image(&mut canvas, &rect, app_resources, image_id);
+                    }
+                },
+                GlTexture => { },
+            }
+        }
+    }
+
+    canvas.into_image()
+}
+
+/// Premultiplied pixels + the current clip stack
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgba>,
+    /// Clip rectangles, together with the transform from window coordinates
+    /// to the coordinates that the clip rectangle was pushed in
+    clips: Vec<(SnapshotRect, SnapshotBorderRadius, Option<SnapshotTransform>)>,
+    /// Transforms from the coordinates of the primitives to window coordinates (scroll
+    /// offsets and transforms of stacking contexts combined), innermost last
+    transforms: Vec<SnapshotTransform>,
+    /// Layers of the stacking contexts, which are blended onto the layer below
+    /// with their opacity once the stacking context is popped, innermost last
+    layers: Vec<(f32, Vec<Rgba>)>,
+}
+
+impl Canvas {
+
+    fn new(width: usize, height: usize, clear_color: Rgba) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![clear_color; width * height],
+            clips: Vec::new(),
+            transforms: Vec::new(),
+            layers: Vec::new(),
+        }
+    }
+
+    /// Returns the transform from the coordinates of the primitives to window coordinates
+    fn get_transform(&self) -> SnapshotTransform {
+        self.transforms.last().cloned().unwrap_or_else(|| translation(0.0, 0.0))
+    }
+
+    /// Pushes a transform, relative to the current transform
+    fn push_transform(&mut self, transform: &SnapshotTransform) {
+        let combined = concat(&self.get_transform(), transform);
+        self.transforms.push(combined);
+    }
+
+    /// Pushes a clip rectangle, which is given in the coordinates of the current transform
+    fn push_clip(&mut self, rect: &SnapshotRect, radius: &SnapshotBorderRadius) {
+        let inverse = self.get_transform().inverse();
+        self.clips.push((*rect, *radius, inverse));
+    }
+
+    /// Starts drawing into a new, transparent layer
+    fn push_layer(&mut self, opacity: f32) {
+        self.layers.push((opacity, vec![TRANSPARENT; self.width * self.height]));
+    }
+
+    /// Blends the topmost layer onto the layer below
+    fn pop_layer(&mut self) {
+        let (opacity, layer) = match self.layers.pop() {
+            Some(s) => s,
+            None => return,
+        };
+        let target = self.layers.last_mut().map(|(_, pixels)| pixels).unwrap_or(&mut self.pixels);
+        for (dst, src) in target.iter_mut().zip(layer.into_iter()) {
+            blend(dst, scale(src, opacity));
+        }
+    }
+
+    /// Calls `shader` with the center of every pixel in `area` and blends the
+    /// returned (premultiplied) color onto the canvas, respecting the clip stack
+    ///
+    /// `area` and the coordinates that the `shader` is called with are in the coordinates
+    /// of the current transform, i.e. they are shifted by the current scroll offset.
+    fn fill<F: FnMut(f32, f32) -> Rgba>(&mut self, area: &SnapshotRect, mut shader: F) {
+
+        let transform = self.get_transform();
+        let inverse = match transform.inverse() {
+            Some(s) => s,
+            None => return,
+        };
+
+        // Bounding box of the transformed area, in window coordinates
+        let corners = [
+            transform.transform_point(area.x, area.y),
+            transform.transform_point(area.x + area.width, area.y),
+            transform.transform_point(area.x, area.y + area.height),
+            transform.transform_point(area.x + area.width, area.y + area.height),
+        ];
+        let min_x = corners.iter().map(|c| c.0).fold(::std::f32::MAX, f32::min);
+        let min_y = corners.iter().map(|c| c.1).fold(::std::f32::MAX, f32::min);
+        let max_x = corners.iter().map(|c| c.0).fold(::std::f32::MIN, f32::max);
+        let max_y = corners.iter().map(|c| c.1).fold(::std::f32::MIN, f32::max);
+
+        let x_start = min_x.floor().max(0.0) as usize;
+        let y_start = min_y.floor().max(0.0) as usize;
+        let x_end = (max_x.ceil().max(0.0) as usize).min(self.width);
+        let y_end = (max_y.ceil().max(0.0) as usize).min(self.height);
+
+        let width = self.width;
+        let clips = &self.clips;
+        let target = self.layers.last_mut().map(|(_, pixels)| pixels).unwrap_or(&mut self.pixels);
+
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let (center_x, center_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                let clip_coverage = clips.iter()
+                    .map(|(rect, radius, clip_inverse)| match clip_inverse {
+                        Some(clip_inverse) => {
+                            let (clip_x, clip_y) = clip_inverse.transform_point(center_x, center_y);
+                            rounded_rect_coverage(rect, radius, clip_x, clip_y)
+                        },
+                        None => 0.0,
+                    })
+                    .product::<f32>();
+                if clip_coverage <= 0.0 {
+                    continue;
+                }
+                let (local_x, local_y) = inverse.transform_point(center_x, center_y);
+                let src = scale(shader(local_x, local_y), clip_coverage);
+                blend(&mut target[y * width + x], src);
+            }
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let mut pixels = Vec::with_capacity(self.width * self.height * 4);
+        for [r, g, b, a] in self.pixels {
+            let unpremultiply = |c: f32| if a <= 0.0 { 0 } else { to_u8(c / a) };
+            pixels.extend_from_slice(&[unpremultiply(r), unpremultiply(g), unpremultiply(b), to_u8(a)]);
+        }
+        RgbaImage { width: self.width, height: self.height, pixels }
+    }
+}
+
+fn to_u8(c: f32) -> u8 {
+    (c.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+fn premultiply(color: SnapshotColor) -> Rgba {
+    let a = f32::from(color.a) / 255.0;
+    [
+        f32::from(color.r) / 255.0 * a,
+        f32::from(color.g) / 255.0 * a,
+        f32::from(color.b) / 255.0 * a,
+        a,
+    ]
+}
+
+/// Blends the (premultiplied) `src` color over the `dst` color
+fn blend(dst: &mut Rgba, src: Rgba) {
+    let inv_alpha = 1.0 - src[3];
+    for (dst, src) in dst.iter_mut().zip(src.iter()) {
+        *dst = src + *dst * inv_alpha;
+    }
+}
+
+/// Transform that moves everything by (x, y)
+fn translation(x: f32, y: f32) -> SnapshotTransform {
+    SnapshotTransform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: x, f: y }
+}
+
+/// Returns the transform that first applies `inner`, then `outer`
+fn concat(outer: &SnapshotTransform, inner: &SnapshotTransform) -> SnapshotTransform {
+    SnapshotTransform {
+        a: outer.a * inner.a + outer.c * inner.b,
+        b: outer.b * inner.a + outer.d * inner.b,
+        c: outer.a * inner.c + outer.c * inner.d,
+        d: outer.b * inner.c + outer.d * inner.d,
+        e: outer.a * inner.e + outer.c * inner.f + outer.e,
+        f: outer.b * inner.e + outer.d * inner.f + outer.f,
+    }
+}
+
+fn scale(color: Rgba, factor: f32) -> Rgba {
+    [color[0] * factor, color[1] * factor, color[2] * factor, color[3] * factor]
+}
+
+fn clamp01(value: f32) -> f32 {
+    value.max(0.0).min(1.0)
+}
+
+/// How much of the 1px wide pixel around `center` overlaps with `start..end`
+fn span_coverage(start: f32, end: f32, center: f32) -> f32 {
+    clamp01((center + 0.5).min(end) - (center - 0.5).max(start))
+}
+
+/// How much of the pixel at (x, y) is covered by the rectangle
+fn rect_coverage(rect: &SnapshotRect, x: f32, y: f32) -> f32 {
+    span_coverage(rect.x, rect.x + rect.width, x) * span_coverage(rect.y, rect.y + rect.height, y)
+}
+
+/// Same as `rect_coverage`, but with rounded (elliptical) corners
+fn rounded_rect_coverage(rect: &SnapshotRect, radius: &SnapshotBorderRadius, x: f32, y: f32) -> f32 {
+
+    let coverage = rect_coverage(rect, x, y);
+    if coverage <= 0.0 {
+        return 0.0;
+    }
+
+    let [top_left, top_right, bottom_left, bottom_right] = radius.corners;
+    let (left, top) = (rect.x, rect.y);
+    let (right, bottom) = (rect.x + rect.width, rect.y + rect.height);
+
+    // (corner radius, center of the corner ellipse, whether the point is inside the corner box)
+    let corners = [
+        (top_left, left + top_left.x, top + top_left.y, x < left + top_left.x && y < top + top_left.y),
+        (top_right, right - top_right.x, top + top_right.y, x > right - top_right.x && y < top + top_right.y),
+        (bottom_left, left + bottom_left.x, bottom - bottom_left.y, x < left + bottom_left.x && y > bottom - bottom_left.y),
+        (bottom_right, right - bottom_right.x, bottom - bottom_right.y, x > right - bottom_right.x && y > bottom - bottom_right.y),
+    ];
+
+    for (radius, center_x, center_y, is_in_corner) in corners.iter() {
+        if !*is_in_corner || radius.x <= 0.0 || radius.y <= 0.0 {
+            continue;
+        }
+        let dx = (x - center_x) / radius.x;
+        let dy = (y - center_y) / radius.y;
+        // Approximate distance to the ellipse, in pixels
+        let distance = ((dx * dx + dy * dy).sqrt() - 1.0) * radius.x.min(radius.y);
+        return coverage * clamp01(0.5 - distance);
+    }
+
+    coverage
+}
+
+/// Returns the (premultiplied) color of the gradient at `t`
+fn sample_gradient(stops: &[SnapshotGradientStop], t: f32, repeat: bool) -> Rgba {
+
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return TRANSPARENT,
+    };
+
+    let t = if repeat { t - t.floor() } else { t };
+    // Stop offsets are in percent
+    let t = t * 100.0;
+
+    if t <= first.offset {
+        return premultiply(first.color);
+    }
+
+    for window in stops.windows(2) {
+        let (a, b) = (&window[0], &window[1]);
+        if t <= b.offset {
+            let span = b.offset - a.offset;
+            let f = if span <= 0.0 { 1.0 } else { (t - a.offset) / span };
+            let (a, b) = (premultiply(a.color), premultiply(b.color));
+            return [
+                a[0] + (b[0] - a[0]) * f,
+                a[1] + (b[1] - a[1]) * f,
+                a[2] + (b[2] - a[2]) * f,
+                a[3] + (b[3] - a[3]) * f,
+            ];
+        }
+    }
+
+    premultiply(last.color)
+}
+
+fn draw_border(canvas: &mut Canvas, bounds: &SnapshotRect, sides: &[SnapshotBorderSide; 4], radius: &SnapshotBorderRadius) {
+
+    let [top, right, bottom, left] = sides;
+
+    let inner = SnapshotRect {
+        x: bounds.x + left.width,
+        y: bounds.y + top.width,
+        width: bounds.width - left.width - right.width,
+        height: bounds.height - top.width - bottom.width,
+    };
+
+    let shrink = |corner: SnapshotPoint, horizontal: f32, vertical: f32| SnapshotPoint {
+        x: (corner.x - horizontal).max(0.0),
+        y: (corner.y - vertical).max(0.0),
+    };
+
+    let [top_left, top_right, bottom_left, bottom_right] = radius.corners;
+    let inner_radius = SnapshotBorderRadius {
+        corners: [
+            shrink(top_left, left.width, top.width),
+            shrink(top_right, right.width, top.width),
+            shrink(bottom_left, left.width, bottom.width),
+            shrink(bottom_right, right.width, bottom.width),
+        ],
+    };
+
+    let is_visible = |side: &SnapshotBorderSide| side.width > 0.0 && side.style != "none" && side.style != "hidden";
+    let colors = [top, right, bottom, left].iter()
+        .map(|side| if is_visible(side) { premultiply(side.color) } else { TRANSPARENT })
+        .collect::<Vec<_>>();
+
+    canvas.fill(bounds, |x, y| {
+
+        let coverage = rounded_rect_coverage(bounds, radius, x, y) *
+            (1.0 - rounded_rect_coverage(&inner, &inner_radius, x, y));
+
+        if coverage <= 0.0 {
+            return TRANSPARENT;
+        }
+
+        // The pixel belongs to the side that it is relatively closest to
+        let distances = [
+            (y - bounds.y) / top.width,
+            (bounds.x + bounds.width - x) / right.width,
+            (bounds.y + bounds.height - y) / bottom.width,
+            (x - bounds.x) / left.width,
+        ];
+
+        let side = (0..4)
+            .filter(|i| [top, right, bottom, left][*i].width > 0.0)
+            .min_by(|a, b| distances[*a].partial_cmp(&distances[*b]).unwrap_or(::std::cmp::Ordering::Equal));
+
+        match side {
+            Some(side) => scale(colors[side], coverage),
+            None => TRANSPARENT,
+        }
+    });
+}
+
+/// Approximates the coverage of a rectangle that was blurred with a gaussian
+/// blur of the standard deviation `sigma`, along one axis
+fn blurred_span_coverage(start: f32, end: f32, center: f32, sigma: f32) -> f32 {
+    if sigma <= 0.0 {
+        return span_coverage(start, end, center);
+    }
+    let s = sigma * ::std::f32::consts::SQRT_2;
+    0.5 * (erf((end - center) / s) - erf((start - center) / s))
+}
+
+/// Error function, approximation from Abramowitz & Stegun (max. error: 1.5e-7)
+fn erf(x: f32) -> f32 {
+    let x = f64::from(x);
+    let sign = if x < 0.0 { -1.0 } else { 1.0 };
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let y = 1.0 - (((((1.061_405_429 * t - 1.453_152_027) * t) + 1.421_413_741) * t - 0.284_496_736) * t + 0.254_829_592) * t * (-x * x).exp();
+    (sign * y) as f32
+}
+
+fn draw_box_shadow(canvas: &mut Canvas, bounds: &SnapshotRect, radius: &SnapshotBorderRadius, shadow: &SnapshotBoxShadow) {
+
+    let color = premultiply(shadow.color);
+    let sigma = shadow.blur_radius / 2.0;
+    let is_inset = shadow.clip_mode == "inset";
+
+    // The area of the shadow before blurring
+    let spread = if is_inset { -shadow.spread_radius } else { shadow.spread_radius };
+    let shadow_rect = SnapshotRect {
+        x: bounds.x + shadow.offset.x - spread,
+        y: bounds.y + shadow.offset.y - spread,
+        width: bounds.width + spread * 2.0,
+        height: bounds.height + spread * 2.0,
+    };
+
+    // Only the side that the shadow applies to is drawn, see `push_single_box_shadow_edge`
+    let displace = (shadow.spread_radius + shadow.blur_radius) * 2.0;
+    let (left, top) = (bounds.x, bounds.y);
+    let (right, bottom) = (bounds.x + bounds.width, bounds.y + bounds.height);
+    let is_on_side = |x: f32, y: f32| match (shadow.side.as_str(), is_inset) {
+        ("top", false) => y < top,
+        ("bottom", false) => y > bottom,
+        ("left", false) => x < left,
+        ("right", false) => x > right,
+        ("top", true) => y < top + displace,
+        ("bottom", true) => y > bottom - displace,
+        ("left", true) => x < left + displace,
+        ("right", true) => x > right - displace,
+        _ => true,
+    };
+
+    let shadow_coverage = |x: f32, y: f32| {
+        blurred_span_coverage(shadow_rect.x, shadow_rect.x + shadow_rect.width, x, sigma) *
+        blurred_span_coverage(shadow_rect.y, shadow_rect.y + shadow_rect.height, y, sigma)
+    };
+
+    if is_inset {
+        canvas.fill(bounds, |x, y| {
+            if !is_on_side(x, y) {
+                return TRANSPARENT;
+            }
+            let coverage = rounded_rect_coverage(bounds, radius, x, y) * (1.0 - shadow_coverage(x, y));
+            scale(color, coverage)
+        });
+    } else {
+        let extent = sigma * 3.0;
+        let area = SnapshotRect {
+            x: shadow_rect.x - extent,
+            y: shadow_rect.y - extent,
+            width: shadow_rect.width + extent * 2.0,
+            height: shadow_rect.height + extent * 2.0,
+        };
+        canvas.fill(&area, |x, y| {
+            if !is_on_side(x, y) {
+                return TRANSPARENT;
+            }
+            // Outset shadows are not drawn below the element itself
+            let coverage = shadow_coverage(x, y) * (1.0 - rounded_rect_coverage(bounds, radius, x, y));
+            scale(color, coverage)
+        });
+    }
+}
+
+/// Premultiplied RGBA pixels of an image, converted from the formats WebRender uses
+struct DecodedImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgba>,
+}
+
+impl DecodedImage {
+
+    fn new(descriptor: &ImageDescriptor, data: &ImageData) -> Option<Self> {
+
+        let bytes = match data {
+            ImageData::Raw(bytes) => bytes,
+            _ => return None,
+        };
+
+        let width = descriptor.size.width.max(0) as usize;
+        let height = descriptor.size.height.max(0) as usize;
+
+        let pixels = match descriptor.format {
+            // BGRA8 images are already premultiplied, see `app_resources::prepare_image`
+            ImageFormat::BGRA8 => bytes.chunks(4).map(|bgra| [
+                f32::from(bgra[2]) / 255.0,
+                f32::from(bgra[1]) / 255.0,
+                f32::from(bgra[0]) / 255.0,
+                f32::from(bgra[3]) / 255.0,
+            ]).collect(),
+            ImageFormat::R8 => bytes.iter().map(|grey| {
+                let grey = f32::from(*grey) / 255.0;
+                [grey, grey, grey, 1.0]
+            }).collect::<Vec<_>>(),
+            _ => return None,
+        };
+
+        if pixels.len() < width * height {
+            return None;
+        }
+
+        Some(Self { width, height, pixels })
+    }
+
+    /// Returns the pixel at the (clamped) position
+    fn get(&self, x: isize, y: isize) -> Rgba {
+        let x = x.max(0).min(self.width as isize - 1) as usize;
+        let y = y.max(0).min(self.height as isize - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+}
+
+fn get_decoded_image(app_resources: &AppResources, image_id: &ImageId) -> Option<DecodedImage> {
+    let (descriptor, data) = app_resources.get_headless_image(image_id)?;
+    DecodedImage::new(descriptor, data).filter(|image| image.width > 0 && image.height > 0)
+}
+
+/// Draws the image stretched to the size of the `rect` (nearest-neighbor sampling)
+fn draw_image(canvas: &mut Canvas, rect: &SnapshotRect, app_resources: &AppResources, image_id: &ImageId) {
+
+    let image = match get_decoded_image(app_resources, image_id) {
+        Some(s) => s,
+        None => return,
+    };
+
+    if rect.width <= 0.0 || rect.height <= 0.0 {
+        return;
+    }
+
+    let scale_x = image.width as f32 / rect.width;
+    let scale_y = image.height as f32 / rect.height;
+
+    canvas.fill(rect, |x, y| {
+        let pixel = image.get(((x - rect.x) * scale_x) as isize, ((y - rect.y) * scale_y) as isize);
+        scale(pixel, rect_coverage(rect, x, y))
+    });
+}
+
+/// Draws the image at its original size, repeated over the `bounds`
+fn draw_background_image(canvas: &mut Canvas, bounds: &SnapshotRect, app_resources: &AppResources, image_id: &ImageId) {
+
+    let image = match get_decoded_image(app_resources, image_id) {
+        Some(s) => s,
+        None => return,
+    };
+
+    canvas.fill(bounds, |x, y| {
+        let image_x = (x - bounds.x) as isize % image.width as isize;
+        let image_y = (y - bounds.y) as isize % image.height as isize;
+        scale(image.get(image_x, image_y), rect_coverage(bounds, x, y))
+    });
+}
+
+/// Fonts are loaded lazily, by their family, weight and style
+#[cfg(feature = "svg")]
+#[derive(Default)]
+struct FontCache {
+    fonts: FastHashMap<(String, u16, String), Option<FontInfo<Vec<u8>>>>,
+}
+
+#[cfg(feature = "svg")]
+impl FontCache {
+    fn get(&mut self, font_family: &str, font_weight: u16, font_style: &str, app_resources: &AppResources) -> Option<&FontInfo<Vec<u8>>> {
+        use stb_truetype::get_font_offset_for_index;
+        use azul_css::{StyleFontWeight, StyleFontStyle};
+        let key = (font_family.to_string(), font_weight, font_style.to_string());
+        self.fonts.entry(key).or_insert_with(|| {
+            let style = match font_style {
+                "italic" => StyleFontStyle::Italic,
+                "oblique" => StyleFontStyle::Oblique,
+                _ => StyleFontStyle::Normal,
+            };
+            let (font_bytes, font_index) = app_resources.get_font_bytes_for_face(font_family, StyleFontWeight(font_weight), style)?.ok()?;
+            let offset = get_font_offset_for_index(&font_bytes, font_index)?;
+            FontInfo::new(font_bytes, offset as usize)
+        }).as_ref()
+    }
+}
+
+/// Number of line segments that a quadratic curve of a glyph outline is split into
+#[cfg(feature = "svg")]
+const CURVE_SEGMENTS: usize = 8;
+
+/// Number of samples per pixel (in each direction) for anti-aliasing glyphs
+#[cfg(feature = "svg")]
+const GLYPH_SAMPLES: usize = 4;
+
+#[cfg(feature = "svg")]
+fn draw_glyphs(canvas: &mut Canvas, font: &FontInfo<Vec<u8>>, font_size: f32, color: Rgba, glyphs: &[SnapshotGlyph]) {
+
+    use stb_truetype::VertexType;
+
+    let font_scale = font.scale_for_mapping_em_to_pixels(font_size);
+
+    for glyph in glyphs {
+
+        let vertices = match font.get_glyph_shape(glyph.index) {
+            Some(s) => s,
+            None => continue,
+        };
+
+        // Font units (y up) to pixels (y down), relative to the glyph origin on the baseline
+        let to_px = |x: i16, y: i16| (glyph.x + f32::from(x) * font_scale, glyph.y - f32::from(y) * font_scale);
+
+        // Flatten the outline into closed polygons
+        let mut edges = Vec::new();
+        let mut contour_start = (0.0, 0.0);
+        let mut current = (0.0, 0.0);
+
+        for vertex in &vertices {
+            let target = to_px(vertex.x, vertex.y);
+            match vertex.vertex_type() {
+                VertexType::MoveTo => {
+                    if current != contour_start {
+                        edges.push((current, contour_start));
+                    }
+                    contour_start = target;
+                },
+                VertexType::LineTo => edges.push((current, target)),
+                VertexType::CurveTo => {
+                    let control = to_px(vertex.cx, vertex.cy);
+                    let mut last = current;
+                    for i in 1..=CURVE_SEGMENTS {
+                        let t = i as f32 / CURVE_SEGMENTS as f32;
+                        let mt = 1.0 - t;
+                        let point = (
+                            mt * mt * current.0 + 2.0 * mt * t * control.0 + t * t * target.0,
+                            mt * mt * current.1 + 2.0 * mt * t * control.1 + t * t * target.1,
+                        );
+                        edges.push((last, point));
+                        last = point;
+                    }
+                },
+            }
+            current = target;
+        }
+        if current != contour_start {
+            edges.push((current, contour_start));
+        }
+
+        if edges.is_empty() {
+            continue;
+        }
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (::std::f32::MAX, ::std::f32::MAX, ::std::f32::MIN, ::std::f32::MIN);
+        for ((x0, y0), (x1, y1)) in &edges {
+            min_x = min_x.min(*x0).min(*x1);
+            min_y = min_y.min(*y0).min(*y1);
+            max_x = max_x.max(*x0).max(*x1);
+            max_y = max_y.max(*y0).max(*y1);
+        }
+
+        let area = SnapshotRect { x: min_x, y: min_y, width: max_x - min_x, height: max_y - min_y };
+
+        canvas.fill(&area, |x, y| {
+            let mut inside = 0;
+            for sample_y in 0..GLYPH_SAMPLES {
+                for sample_x in 0..GLYPH_SAMPLES {
+                    let px = x - 0.5 + (sample_x as f32 + 0.5) / GLYPH_SAMPLES as f32;
+                    let py = y - 0.5 + (sample_y as f32 + 0.5) / GLYPH_SAMPLES as f32;
+                    if winding_number(&edges, px, py) != 0 {
+                        inside += 1;
+                    }
+                }
+            }
+            scale(color, inside as f32 / (GLYPH_SAMPLES * GLYPH_SAMPLES) as f32)
+        });
+    }
+}
+
+/// Non-zero winding number of the point, relative to the closed polygon made up of `edges`
+#[cfg(feature = "svg")]
+fn winding_number(edges: &[((f32, f32), (f32, f32))], x: f32, y: f32) -> i32 {
+    let mut winding = 0;
+    for ((x0, y0), (x1, y1)) in edges {
+        let (upwards, downwards) = (*y0 <= y && *y1 > y, *y1 <= y && *y0 > y);
+        if !upwards && !downwards {
+            continue;
+        }
+        let intersection_x = x0 + (y - y0) / (y1 - y0) * (x1 - x0);
+        if intersection_x > x {
+            winding += if upwards { 1 } else { -1 };
+        }
+    }
+    winding
+}
+
+/// Minimal PNG encoder (8-bit RGBA, deflate "stored" blocks), so that
+/// screenshots can be saved without depending on the `image` crate
+mod png {
+
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    /// Maximum length of an uncompressed deflate block
+    const MAX_STORED_BLOCK_LEN: usize = 0xFFFF;
+
+    pub(super) fn encode_rgba8(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+
+        // Every scanline starts with the filter type (0 = no filter)
+        let row_len = width as usize * 4;
+        let mut raw = Vec::with_capacity((row_len + 1) * height as usize);
+        for row in pixels.chunks(row_len.max(1)).take(height as usize) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+
+        let mut header = Vec::with_capacity(13);
+        push_u32_be(&mut header, width);
+        push_u32_be(&mut header, height);
+        // bit depth 8, color type 6 (RGBA), deflate, adaptive filtering, no interlacing
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+        push_u32_be(png, data.len() as u32);
+        let crc_start = png.len();
+        png.extend_from_slice(chunk_type);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[crc_start..]);
+        push_u32_be(png, crc);
+    }
+
+    fn push_u32_be(out: &mut Vec<u8>, value: u32) {
+        out.extend_from_slice(&[(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8]);
+    }
+
+    fn push_u16_le(out: &mut Vec<u8>, value: u16) {
+        out.extend_from_slice(&[value as u8, (value >> 8) as u8]);
+    }
+
+    fn zlib_stored(data: &[u8]) -> Vec<u8> {
+        // CMF / FLG: deflate with a 32K window, no preset dictionary, fastest compression
+        let mut out = vec![0x78, 0x01];
+        let mut blocks = data.chunks(MAX_STORED_BLOCK_LEN).peekable();
+        if blocks.peek().is_none() {
+            out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+        }
+        while let Some(block) = blocks.next() {
+            let is_final = blocks.peek().is_none();
+            let len = block.len() as u16;
+            out.push(u8::from(is_final));
+            push_u16_le(&mut out, len);
+            push_u16_le(&mut out, !len);
+            out.extend_from_slice(block);
+        }
+        let checksum = adler32(data);
+        push_u32_be(&mut out, checksum);
+        out
+    }
+
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = 0xFFFF_FFFF_u32;
+        for byte in data {
+            crc ^= u32::from(*byte);
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            }
+        }
+        !crc
+    }
+
+    fn adler32(data: &[u8]) -> u32 {
+        const MOD_ADLER: u32 = 65521;
+        let (mut a, mut b) = (1_u32, 0_u32);
+        for byte in data {
+            a = (a + u32::from(*byte)) % MOD_ADLER;
+            b = (b + a) % MOD_ADLER;
+        }
+        (b << 16) | a
+    }
+
+    #[test]
+    fn test_crc32_adler32() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+}
+
+#[test]
+fn test_rasterize_rect_and_border() {
+
+    use webrender::api::{LayoutRect, LayoutPoint, LayoutSize};
+    use snapshot::SnapshotItem;
+
+    let mut snapshot = DisplayListSnapshot::new();
+    snapshot.items.push(SnapshotItem {
+        node_id: 0,
+        node_type: "div".into(),
+        hit_test: false,
+        bounds: LayoutRect::new(LayoutPoint::new(2.0, 2.0), LayoutSize::new(6.0, 6.0)).into(),
+        primitives: vec![
+            SnapshotPrimitive::Rect { color: SnapshotColor { r: 255, g: 0, b: 0, a: 255 } },
+            SnapshotPrimitive::Border {
+                sides: [
+                    SnapshotBorderSide { width: 1.0, style: "solid".into(), color: SnapshotColor { r: 0, g: 0, b: 255, a: 255 } },
+                    SnapshotBorderSide { width: 0.0, style: "solid".into(), color: SnapshotColor::default() },
+                    SnapshotBorderSide { width: 0.0, style: "solid".into(), color: SnapshotColor::default() },
+                    SnapshotBorderSide { width: 0.0, style: "solid".into(), color: SnapshotColor::default() },
+                ],
+                radius: None,
+            },
+        ],
+    });
+
+    let white = ColorU { r: 255, g: 255, b: 255, a: 255 };
+    let image = rasterize(&snapshot, 10, 10, white, &AppResources::new_headless());
+
+    assert_eq!(image.pixels.len(), 10 * 10 * 4);
+    assert_eq!(image.get_pixel(0, 0), Some(white));
+    assert_eq!(image.get_pixel(5, 5), Some(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+    assert_eq!(image.get_pixel(5, 2), Some(ColorU { r: 0, g: 0, b: 255, a: 255 }));
+    assert_eq!(image.get_pixel(10, 0), None);
+
+    let png = image.encode_png();
+    assert_eq!(&png[1..4], b"PNG");
+}
+
+#[test]
+fn test_rasterize_scroll_frame() {
+
+    use webrender::api::{LayoutRect, LayoutPoint, LayoutSize};
+    use snapshot::SnapshotItem;
+
+    let red = SnapshotColor { r: 255, g: 0, b: 0, a: 255 };
+    let rect = |y: f32| LayoutRect::new(LayoutPoint::new(0.0, y), LayoutSize::new(10.0, 5.0)).into();
+
+    // Two stacked 5px rects in a 10x5 scroll frame, scrolled down by 5px:
+    // only the second rect is visible, at the position of the first rect
+    let mut snapshot = DisplayListSnapshot::new();
+    snapshot.items.push(SnapshotItem {
+        node_id: 0,
+        node_type: "div".into(),
+        hit_test: true,
+        bounds: rect(0.0),
+        primitives: vec![
+            SnapshotPrimitive::PushScrollFrame { clip: rect(0.0), offset: SnapshotPoint { x: 0.0, y: 5.0 } },
+        ],
+    });
+    snapshot.items.push(SnapshotItem {
+        node_id: 1,
+        node_type: "div".into(),
+        hit_test: false,
+        bounds: rect(0.0),
+        primitives: vec![SnapshotPrimitive::Rect { color: SnapshotColor { r: 0, g: 0, b: 255, a: 255 } }],
+    });
+    snapshot.items.push(SnapshotItem {
+        node_id: 2,
+        node_type: "div".into(),
+        hit_test: false,
+        bounds: rect(5.0),
+        primitives: vec![SnapshotPrimitive::Rect { color: red }, SnapshotPrimitive::PopScrollFrame],
+    });
+
+    let white = ColorU { r: 255, g: 255, b: 255, a: 255 };
+    let image = rasterize(&snapshot, 10, 10, white, &AppResources::new_headless());
+
+    assert_eq!(image.get_pixel(5, 2), Some(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+    // Clipped by the scroll frame
+    assert_eq!(image.get_pixel(5, 7), Some(white));
+}
+
+#[test]
+fn test_rasterize_stacking_context() {
+
+    use webrender::api::{LayoutRect, LayoutPoint, LayoutSize};
+    use snapshot::SnapshotItem;
+
+    // 5x10 red rect, moved 5px to the right and drawn with 50% opacity
+    let mut snapshot = DisplayListSnapshot::new();
+    snapshot.items.push(SnapshotItem {
+        node_id: 0,
+        node_type: "div".into(),
+        hit_test: false,
+        bounds: LayoutRect::new(LayoutPoint::new(0.0, 0.0), LayoutSize::new(5.0, 10.0)).into(),
+        primitives: vec![
+            SnapshotPrimitive::PushStackingContext {
+                opacity: 0.5,
+                transform: Some(SnapshotTransform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 5.0, f: 0.0 }),
+            },
+            SnapshotPrimitive::Rect { color: SnapshotColor { r: 255, g: 0, b: 0, a: 255 } },
+            SnapshotPrimitive::PopStackingContext,
+        ],
+    });
+
+    let white = ColorU { r: 255, g: 255, b: 255, a: 255 };
+    let image = rasterize(&snapshot, 10, 10, white, &AppResources::new_headless());
+
+    assert_eq!(image.get_pixel(2, 5), Some(white));
+    assert_eq!(image.get_pixel(7, 5), Some(ColorU { r: 255, g: 128, b: 128, a: 255 }));
+}
@@ -0,0 +1,371 @@
+//! Scrollbars of `overflow: scroll` / `overflow: auto` nodes
+//!
+//! The scrollbars are drawn on top of the scrolled node, along its right edge (vertical
+//! scrollbar) and its bottom edge (horizontal scrollbar). Each scrollbar consists of a track,
+//! a button at each end of the track and the draggable thumb in between, styled via the
+//! `ScrollbarInfo` of the node.
+
+use glium::glutin::{WindowEvent, ElementState, MouseButton};
+use webrender::api::{LayoutRect, LayoutPoint, LayoutSize, ExternalScrollId};
+use azul_css::ScrollbarInfo;
+use {
+    id_tree::NodeId,
+    display_list::ScrolledNodes,
+    window::ScrollStates,
+    window_state::WindowState,
+};
+
+/// The thumb is never shorter than this (in pixels), so that it can still be grabbed
+const MIN_THUMB_LENGTH: f32 = 20.0;
+/// How far (in pixels) the content is scrolled when clicking on a scrollbar button
+const SCROLLBAR_BUTTON_STEP: f32 = 40.0;
+
+/// Whether a scrollbar scrolls the content horizontally or vertically
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum ScrollbarAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl ScrollbarAxis {
+    /// Returns the component of the `(x, y)` pair along this axis
+    fn select(&self, x: f32, y: f32) -> f32 {
+        match self {
+            ScrollbarAxis::Horizontal => x,
+            ScrollbarAxis::Vertical => y,
+        }
+    }
+
+    /// Creates a rectangle from its position along the axis (`main_*`)
+    /// and its position perpendicular to the axis (`cross_*`)
+    fn rect(&self, main_start: f32, main_length: f32, cross_start: f32, cross_length: f32) -> LayoutRect {
+        match self {
+            ScrollbarAxis::Horizontal => LayoutRect::new(
+                LayoutPoint::new(main_start, cross_start),
+                LayoutSize::new(main_length, cross_length),
+            ),
+            ScrollbarAxis::Vertical => LayoutRect::new(
+                LayoutPoint::new(cross_start, main_start),
+                LayoutSize::new(cross_length, main_length),
+            ),
+        }
+    }
+}
+
+/// The positioned parts of a single scrollbar
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct ScrollbarRects {
+    pub(crate) axis: ScrollbarAxis,
+    /// Background of the whole scrollbar
+    pub(crate) track: LayoutRect,
+    /// The "up" / "left" button
+    pub(crate) button_start: LayoutRect,
+    /// The "down" / "right" button
+    pub(crate) button_end: LayoutRect,
+    /// The draggable thumb, its length is proportional to the visible part of the content
+    pub(crate) thumb: LayoutRect,
+    /// How far (in pixels) the content scrolls when clicking on the track
+    pub(crate) page_length: f32,
+    /// How far (in pixels) the content scrolls when the thumb is moved by one pixel
+    pub(crate) scroll_per_thumb_px: f32,
+}
+
+/// Which part of a scrollbar is under the cursor
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum ScrollbarHit {
+    ButtonStart,
+    ButtonEnd,
+    /// The part of the track before the thumb
+    TrackStart,
+    /// The part of the track after the thumb
+    TrackEnd,
+    Thumb,
+}
+
+impl ScrollbarRects {
+
+    /// Returns which part of the scrollbar the point is over, if any
+    pub(crate) fn hit_test(&self, point: LayoutPoint) -> Option<ScrollbarHit> {
+
+        use self::ScrollbarHit::*;
+
+        if !self.track.contains(&point) {
+            None
+        } else if self.button_start.contains(&point) {
+            Some(ButtonStart)
+        } else if self.button_end.contains(&point) {
+            Some(ButtonEnd)
+        } else {
+            // The thumb is narrower than the track (because of the padding),
+            // but clicking next to the thumb should still grab it
+            let position = self.axis.select(point.x, point.y);
+            let thumb_start = self.axis.select(self.thumb.origin.x, self.thumb.origin.y);
+            let thumb_end = self.axis.select(self.thumb.max_x(), self.thumb.max_y());
+            if position < thumb_start {
+                Some(TrackStart)
+            } else if position > thumb_end {
+                Some(TrackEnd)
+            } else {
+                Some(Thumb)
+            }
+        }
+    }
+
+    /// Returns how far clicking on the given part of the scrollbar scrolls the content
+    /// (negative values scroll up / left). Clicking on the thumb doesn't scroll.
+    pub(crate) fn get_click_scroll_amount(&self, hit: ScrollbarHit) -> f32 {
+        use self::ScrollbarHit::*;
+        match hit {
+            ButtonStart => -SCROLLBAR_BUTTON_STEP,
+            ButtonEnd => SCROLLBAR_BUTTON_STEP,
+            TrackStart => -self.page_length,
+            TrackEnd => self.page_length,
+            Thumb => 0.0,
+        }
+    }
+}
+
+/// Positions the scrollbar of one axis inside of the `bounds` of the scrolled node.
+///
+/// `content_length` is the length of the content along the axis (including the overflowing
+/// part), `scroll_offset` how far the content is currently scrolled. If both scrollbars are
+/// visible, `has_other_scrollbar` leaves room for the corner in which the scrollbars meet.
+pub(crate) fn get_scrollbar_rects(
+    axis: ScrollbarAxis,
+    bounds: LayoutRect,
+    info: &ScrollbarInfo,
+    content_length: f32,
+    scroll_offset: f32,
+    has_other_scrollbar: bool,
+) -> ScrollbarRects {
+
+    let width = info.width.0.to_pixels();
+    let padding_start = info.padding.left.map(|p| p.to_pixels()).unwrap_or(0.0);
+    let padding_end = info.padding.right.map(|p| p.to_pixels()).unwrap_or(0.0);
+
+    let (main_start, visible_length, cross_end) = match axis {
+        ScrollbarAxis::Horizontal => (bounds.origin.x, bounds.size.width, bounds.max_y()),
+        ScrollbarAxis::Vertical => (bounds.origin.y, bounds.size.height, bounds.max_x()),
+    };
+
+    let cross_start = cross_end - width;
+    let track_length = (visible_length - if has_other_scrollbar { width } else { 0.0 }).max(0.0);
+    let button_length = width.min(track_length / 2.0);
+
+    // The thumb moves between the two buttons
+    let thumb_area_start = main_start + button_length;
+    let thumb_area_length = (track_length - 2.0 * button_length).max(0.0);
+
+    let max_scroll_offset = (content_length - visible_length).max(0.0);
+    let visible_fraction = if content_length > 0.0 { (visible_length / content_length).min(1.0) } else { 1.0 };
+    let thumb_length = (thumb_area_length * visible_fraction).max(MIN_THUMB_LENGTH).min(thumb_area_length);
+    let thumb_free_length = thumb_area_length - thumb_length;
+
+    let (thumb_start, scroll_per_thumb_px) = if max_scroll_offset > 0.0 && thumb_free_length > 0.0 {
+        let scrolled_fraction = (scroll_offset / max_scroll_offset).max(0.0).min(1.0);
+        (thumb_area_start + thumb_free_length * scrolled_fraction, max_scroll_offset / thumb_free_length)
+    } else {
+        (thumb_area_start, 0.0)
+    };
+
+    ScrollbarRects {
+        axis,
+        track: axis.rect(main_start, track_length, cross_start, width),
+        button_start: axis.rect(main_start, button_length, cross_start, width),
+        button_end: axis.rect(main_start + track_length - button_length, button_length, cross_start, width),
+        thumb: axis.rect(thumb_start, thumb_length, cross_start + padding_start, (width - padding_start - padding_end).max(0.0)),
+        page_length: visible_length,
+        scroll_per_thumb_px,
+    }
+}
+
+/// State of dragging a scrollbar thumb with the mouse
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct ScrollbarDragState {
+    /// The node that is scrolled by the thumb
+    pub(crate) scroll_id: ExternalScrollId,
+    pub(crate) axis: ScrollbarAxis,
+    /// Position of the cursor along the axis when the thumb was grabbed
+    pub(crate) start_cursor_position: f32,
+    /// Scroll offset along the axis when the thumb was grabbed
+    pub(crate) start_scroll_offset: f32,
+    pub(crate) scroll_per_thumb_px: f32,
+}
+
+/// Translates a point in window coordinates into the coordinates of the content inside
+/// the scroll node `scroll_parent` (and inside all of its scrolled ancestors), i.e. the
+/// coordinates of the last layout. Returns `None` if the point is clipped away by one
+/// of the scroll nodes.
+pub(crate) fn translate_to_scrolled_content(
+    point: LayoutPoint,
+    scroll_parent: Option<NodeId>,
+    scrolled_nodes: &ScrolledNodes,
+    scroll_states: &ScrollStates,
+) -> Option<LayoutPoint> {
+
+    let mut scroll_parents = Vec::new();
+    let mut current = scroll_parent;
+
+    while let Some(node_id) = current {
+        let scroll_node = scrolled_nodes.overflowing_nodes.get(&node_id)?;
+        scroll_parents.push(scroll_node);
+        current = scroll_node.parent_scroll_node;
+    }
+
+    let mut point = point;
+
+    // Start with the outermost scroll node
+    for scroll_node in scroll_parents.into_iter().rev() {
+        if !scroll_node.clip_rect.contains(&point) {
+            return None;
+        }
+        let (scroll_x, scroll_y) = scroll_states
+            .get_scroll_amount(&scroll_node.parent_external_scroll_id)
+            .unwrap_or((0.0, 0.0));
+        point = LayoutPoint::new(point.x + scroll_x, point.y + scroll_y);
+    }
+
+    Some(point)
+}
+
+/// Returns the topmost scrollbar under the cursor, together with the node it scrolls
+fn hit_test_scrollbars(
+    cursor: LayoutPoint,
+    scrolled_nodes: &ScrolledNodes,
+    scroll_states: &ScrollStates,
+) -> Option<(ExternalScrollId, ScrollbarRects, ScrollbarHit)> {
+    // Children always have a higher node ID than their parents, so their
+    // scrollbars are drawn on top of the scrollbars of the parents
+    scrolled_nodes.overflowing_nodes.values().rev().filter_map(|scroll_node| {
+        let point = translate_to_scrolled_content(cursor, scroll_node.parent_scroll_node, scrolled_nodes, scroll_states)?;
+        scroll_node.horizontal_scrollbar.iter().chain(scroll_node.vertical_scrollbar.iter())
+            .filter_map(|scrollbar| scrollbar.hit_test(point).map(|hit| (scroll_node.parent_external_scroll_id, *scrollbar, hit)))
+            .next()
+    }).next()
+}
+
+/// Handles clicks on the scrollbars of the last frame as well as dragging the scrollbar
+/// thumbs. Returns whether any node was scrolled (and therefore the screen needs to be re-rendered)
+#[must_use]
+pub(crate) fn handle_scrollbar_events(
+    events: &[WindowEvent],
+    window_state: &mut WindowState,
+    scrolled_nodes: &ScrolledNodes,
+    scroll_states: &mut ScrollStates,
+) -> bool {
+
+    let mut should_scroll_render = false;
+
+    let cursor = window_state.internal.mouse_state.cursor_pos
+        .map(|pos| LayoutPoint::new(pos.x as f32, pos.y as f32));
+
+    for event in events {
+        match event {
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+
+                let cursor = match cursor {
+                    Some(s) => s,
+                    None => continue,
+                };
+
+                let (scroll_id, scrollbar, hit) = match hit_test_scrollbars(cursor, scrolled_nodes, scroll_states) {
+                    Some(s) => s,
+                    None => continue,
+                };
+
+                let (scroll_x, scroll_y) = scroll_states.get_scroll_amount(&scroll_id).unwrap_or((0.0, 0.0));
+
+                if hit == ScrollbarHit::Thumb {
+                    window_state.internal.scrollbar_drag = Some(ScrollbarDragState {
+                        scroll_id,
+                        axis: scrollbar.axis,
+                        start_cursor_position: scrollbar.axis.select(cursor.x, cursor.y),
+                        start_scroll_offset: scrollbar.axis.select(scroll_x, scroll_y),
+                        scroll_per_thumb_px: scrollbar.scroll_per_thumb_px,
+                    });
+                } else {
+                    let amount = scrollbar.get_click_scroll_amount(hit);
+                    let (scroll_by_x, scroll_by_y) = match scrollbar.axis {
+                        ScrollbarAxis::Horizontal => (amount, 0.0),
+                        ScrollbarAxis::Vertical => (0.0, amount),
+                    };
+                    should_scroll_render |= scroll_states.scroll_node(&scroll_id, scroll_by_x, scroll_by_y);
+                }
+            },
+            WindowEvent::CursorMoved { .. } => {
+
+                let (drag, cursor) = match (window_state.internal.scrollbar_drag, cursor) {
+                    (Some(drag), Some(cursor)) => (drag, cursor),
+                    _ => continue,
+                };
+
+                let (scroll_x, scroll_y) = match scroll_states.get_scroll_amount(&drag.scroll_id) {
+                    Some(s) => s,
+                    None => continue,
+                };
+
+                let cursor_delta = drag.axis.select(cursor.x, cursor.y) - drag.start_cursor_position;
+                let new_scroll_offset = drag.start_scroll_offset + cursor_delta * drag.scroll_per_thumb_px;
+                let (new_scroll_x, new_scroll_y) = match drag.axis {
+                    ScrollbarAxis::Horizontal => (new_scroll_offset, scroll_y),
+                    ScrollbarAxis::Vertical => (scroll_x, new_scroll_offset),
+                };
+
+                should_scroll_render |= scroll_states.set_scroll_amount(&drag.scroll_id, new_scroll_x, new_scroll_y);
+            },
+            WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } => {
+                window_state.internal.scrollbar_drag = None;
+            },
+            _ => { },
+        }
+    }
+
+    should_scroll_render
+}
+
+#[test]
+fn test_scrollbar_rects() {
+
+    let bounds = LayoutRect::new(LayoutPoint::new(10.0, 20.0), LayoutSize::new(200.0, 100.0));
+    let info = ScrollbarInfo::default();
+
+    // 100px visible out of 400px content, scrolled to the middle
+    let scrollbar = get_scrollbar_rects(ScrollbarAxis::Vertical, bounds, &info, 400.0, 150.0, false);
+
+    assert_eq!(scrollbar.track, LayoutRect::new(LayoutPoint::new(193.0, 20.0), LayoutSize::new(17.0, 100.0)));
+    assert_eq!(scrollbar.button_start, LayoutRect::new(LayoutPoint::new(193.0, 20.0), LayoutSize::new(17.0, 17.0)));
+    assert_eq!(scrollbar.button_end, LayoutRect::new(LayoutPoint::new(193.0, 103.0), LayoutSize::new(17.0, 17.0)));
+
+    // The thumb area is 66px long, the thumb covers a quarter of it
+    // (but is never shorter than MIN_THUMB_LENGTH)
+    assert_eq!(scrollbar.thumb.size, LayoutSize::new(13.0, 20.0));
+    assert_eq!(scrollbar.thumb.origin, LayoutPoint::new(195.0, 37.0 + 46.0 / 2.0));
+    assert!((scrollbar.scroll_per_thumb_px - 300.0 / 46.0).abs() < 0.001);
+
+    assert_eq!(scrollbar.hit_test(LayoutPoint::new(200.0, 25.0)), Some(ScrollbarHit::ButtonStart));
+    assert_eq!(scrollbar.hit_test(LayoutPoint::new(200.0, 40.0)), Some(ScrollbarHit::TrackStart));
+    assert_eq!(scrollbar.hit_test(LayoutPoint::new(194.0, 70.0)), Some(ScrollbarHit::Thumb));
+    assert_eq!(scrollbar.hit_test(LayoutPoint::new(200.0, 90.0)), Some(ScrollbarHit::TrackEnd));
+    assert_eq!(scrollbar.hit_test(LayoutPoint::new(200.0, 110.0)), Some(ScrollbarHit::ButtonEnd));
+    assert_eq!(scrollbar.hit_test(LayoutPoint::new(150.0, 70.0)), None);
+
+    assert!((scrollbar.get_click_scroll_amount(ScrollbarHit::TrackEnd) - 100.0).abs() < 0.001);
+    assert!(scrollbar.get_click_scroll_amount(ScrollbarHit::ButtonStart) < 0.0);
+}
+
+#[test]
+fn test_scrollbar_rects_with_corner() {
+
+    let bounds = LayoutRect::new(LayoutPoint::new(0.0, 0.0), LayoutSize::new(200.0, 100.0));
+    let info = ScrollbarInfo::default();
+
+    // Not overflowing, with a vertical scrollbar in the corner
+    let scrollbar = get_scrollbar_rects(ScrollbarAxis::Horizontal, bounds, &info, 200.0, 0.0, true);
+
+    assert_eq!(scrollbar.track, LayoutRect::new(LayoutPoint::new(0.0, 83.0), LayoutSize::new(183.0, 17.0)));
+    assert_eq!(scrollbar.button_end.origin, LayoutPoint::new(166.0, 83.0));
+
+    // The thumb fills the whole area between the buttons and can't be moved
+    assert_eq!(scrollbar.thumb, LayoutRect::new(LayoutPoint::new(17.0, 85.0), LayoutSize::new(149.0, 13.0)));
+    assert!(scrollbar.scroll_per_thumb_px.abs() < 0.001);
+}
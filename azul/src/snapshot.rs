@@ -0,0 +1,752 @@
+//! Snapshots of the display list, for golden-file tests
+//!
+//! While the display list is built, every primitive that gets pushed into the
+//! WebRender `DisplayListBuilder` (rectangles, borders, backgrounds, box shadows,
+//! text runs and clip regions) can also be recorded into a `DisplayListSnapshot`.
+//! Unlike the WebRender display list, the snapshot doesn't contain any resource keys
+//! or pipeline IDs, so it is stable across runs and machines.
+//!
+//! The text form of a snapshot (`snapshot.to_string()`) is meant to be checked into
+//! the repository. A test can then compare the current output against it:
+//!
+//! ```no_run,ignore
+//! let mut app = App::new_headless(MyDataModel { }, AppConfig::default());
+//! let mut window = HeadlessWindow::new(WindowState::default(), css::native());
+//! app.run_headless(&mut window).unwrap();
+//!
+//! window.get_display_list_snapshot().unwrap()
+//!     .assert_matches_golden_file("tests/golden/my_app.txt");
+//! ```
+//!
+//! If the golden file doesn't exist yet or if the `AZUL_UPDATE_GOLDEN` environment
+//! variable is set, the golden file is (re-)written instead of being compared against.
+//!
+//! With the `serde_serialization` feature, all snapshot types implement `Serialize`
+//! and `Deserialize`, so that they can also be stored as JSON, etc.
+
+use std::{fmt, fs, env, io, path::Path};
+use webrender::api::{LayoutRect, LayoutTransform, GlyphInstance};
+use azul_css::{
+    ColorU, BorderRadius, BorderStyle, BoxShadowClipMode, BoxShadowPreDisplayItem,
+    ExtendMode, GradientStopPre, StyleBackground, StyleBorder, StyleBorderRadius,
+};
+use app_resources::{AppResources, ImageId};
+
+/// If this environment variable is set, `assert_matches_golden_file` overwrites
+/// the golden files with the current snapshot instead of comparing against them
+pub const UPDATE_GOLDEN_FILES_ENV_VAR: &str = "AZUL_UPDATE_GOLDEN";
+
+/// Number of unchanged lines that are printed around each change in a diff
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Stable, serializable form of a display list (see the module-level documentation)
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct DisplayListSnapshot {
+    /// All rectangles, in the order that they were pushed into the display list
+    pub items: Vec<SnapshotItem>,
+}
+
+/// A single rectangle (DOM node) of the display list
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotItem {
+    /// ID of the node in the DOM - nodes of an iframe are numbered separately
+    /// and follow directly after the item of the iframe itself
+    pub node_id: usize,
+    /// Type of the node, in CSS notation (`"div"`, `"p"`, `"img"`, ...)
+    pub node_type: String,
+    /// Whether the node is relevant for hit-testing (has a callback or is scrollable)
+    pub hit_test: bool,
+    /// Positioned bounds of the node
+    pub bounds: SnapshotRect,
+    /// Primitives that were pushed for this node, in order
+    pub primitives: Vec<SnapshotPrimitive>,
+}
+
+/// Primitive that was pushed into the display list
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub enum SnapshotPrimitive {
+    /// Pushes a clip region, active until the next `PopClip`
+    PushClip { rect: SnapshotRect, radius: SnapshotBorderRadius },
+    /// Pops the last clip region
+    PopClip,
+    /// Clips the following primitives to `clip` and scrolls them by `offset`, active
+    /// until the next `PopScrollFrame` - the primitives inside the scroll frame
+    /// are positioned as if the content wasn't scrolled
+    PushScrollFrame { clip: SnapshotRect, offset: SnapshotPoint },
+    /// Pops the last scroll frame
+    PopScrollFrame,
+    /// Draws the following primitives with the given `opacity` and transforms them
+    /// with the `transform` (in window coordinates), active until the next `PopStackingContext`
+    PushStackingContext { opacity: f32, transform: Option<SnapshotTransform> },
+    /// Pops the last stacking context
+    PopStackingContext,
+    /// `box-shadow` of one or more sides of the rectangle
+    BoxShadow(SnapshotBoxShadow),
+    /// Rectangle filled with a single color
+    Rect { color: SnapshotColor },
+    /// `background: linear-gradient(...)`, from `start` to `end`
+    LinearGradient {
+        start: SnapshotPoint,
+        end: SnapshotPoint,
+        extend_mode: String,
+        stops: Vec<SnapshotGradientStop>,
+    },
+    /// `background: radial-gradient(...)`
+    RadialGradient {
+        center: SnapshotPoint,
+        radius: SnapshotPoint,
+        extend_mode: String,
+        stops: Vec<SnapshotGradientStop>,
+    },
+    /// `background: image("...")` - `found` is false if the CSS ID wasn't registered
+    BackgroundImage { css_id: String, found: bool },
+    /// Border of the rectangle, sides in the order top, right, bottom, left
+    Border { sides: [SnapshotBorderSide; 4], radius: Option<SnapshotBorderRadius> },
+    /// Laid out text, `clip` is the clip rectangle if the text overflow is hidden
+    Text {
+        color: SnapshotColor,
+        /// The family of the `font-family` list that the font was selected from, i.e. `"sans-serif"`
+        font_family: String,
+        /// `font-weight`, i.e. `400`
+        font_weight: u16,
+        /// `font-style`, i.e. `"italic"`
+        font_style: String,
+        font_size: f32,
+        clip: Option<SnapshotRect>,
+        glyphs: Vec<SnapshotGlyph>,
+    },
+    /// Content of an image node
+    Image {
+        width: f32,
+        height: f32,
+        /// The `ImageId` is only valid for the current `App`, so it isn't
+        /// part of the text form and isn't serialized
+        #[cfg_attr(feature = "serde_serialization", serde(skip))]
+        image_id: Option<ImageId>,
+    },
+    /// OpenGL texture - the content of the texture isn't part of the snapshot
+    GlTexture,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// 2D affine transform, in the same notation as the CSS `matrix(a, b, c, d, e, f)` function:
+/// `x' = a * x + c * y + e`, `y' = b * x + d * y + f`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotTransform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl SnapshotTransform {
+
+    /// Transforms a point
+    pub fn transform_point(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a * x + self.c * y + self.e, self.b * x + self.d * y + self.f)
+    }
+
+    /// Returns the inverse transform, or `None` if the transform can't
+    /// be inverted (i.e. because it is scaled to zero)
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.a * self.d - self.b * self.c;
+        if determinant.abs() <= ::std::f32::EPSILON {
+            return None;
+        }
+        Some(Self {
+            a: self.d / determinant,
+            b: -self.b / determinant,
+            c: -self.c / determinant,
+            d: self.a / determinant,
+            e: (self.c * self.f - self.d * self.e) / determinant,
+            f: (self.b * self.e - self.a * self.f) / determinant,
+        })
+    }
+}
+
+/// RGBA color, printed as `#rrggbbaa`
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// Corner radii, in the order top-left, top-right, bottom-left, bottom-right
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotBorderRadius {
+    pub corners: [SnapshotPoint; 4],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotBorderSide {
+    pub width: f32,
+    pub style: String,
+    pub color: SnapshotColor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotBoxShadow {
+    /// `"all"` or the side of the rectangle (`"top"`, `"left"`, ...) the shadow applies to
+    pub side: String,
+    /// `"outset"` or `"inset"`
+    pub clip_mode: String,
+    pub offset: SnapshotPoint,
+    pub color: SnapshotColor,
+    pub blur_radius: f32,
+    pub spread_radius: f32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotGradientStop {
+    /// Offset of the stop in percent
+    pub offset: f32,
+    pub color: SnapshotColor,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
+pub struct SnapshotGlyph {
+    pub index: u32,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl From<LayoutRect> for SnapshotRect {
+    fn from(rect: LayoutRect) -> Self {
+        Self {
+            x: rect.origin.x,
+            y: rect.origin.y,
+            width: rect.size.width,
+            height: rect.size.height,
+        }
+    }
+}
+
+impl From<LayoutTransform> for SnapshotTransform {
+    fn from(transform: LayoutTransform) -> Self {
+        // Only the 2D part of the transform is used, since all CSS transforms are 2D
+        Self {
+            a: transform.m11,
+            b: transform.m12,
+            c: transform.m21,
+            d: transform.m22,
+            e: transform.m41,
+            f: transform.m42,
+        }
+    }
+}
+
+impl From<ColorU> for SnapshotColor {
+    fn from(color: ColorU) -> Self {
+        Self { r: color.r, g: color.g, b: color.b, a: color.a }
+    }
+}
+
+impl From<BorderRadius> for SnapshotBorderRadius {
+    fn from(radius: BorderRadius) -> Self {
+        let corner = |size: ::azul_css::PixelSize| SnapshotPoint {
+            x: size.width.to_pixels(),
+            y: size.height.to_pixels(),
+        };
+        Self {
+            corners: [
+                corner(radius.top_left),
+                corner(radius.top_right),
+                corner(radius.bottom_left),
+                corner(radius.bottom_right),
+            ],
+        }
+    }
+}
+
+impl From<GlyphInstance> for SnapshotGlyph {
+    fn from(glyph: GlyphInstance) -> Self {
+        Self { index: glyph.index, x: glyph.point.x, y: glyph.point.y }
+    }
+}
+
+impl From<GradientStopPre> for SnapshotGradientStop {
+    fn from(stop: GradientStopPre) -> Self {
+        Self {
+            offset: stop.offset.map(|o| o.get()).unwrap_or(0.0),
+            color: stop.color.into(),
+        }
+    }
+}
+
+impl SnapshotBoxShadow {
+    fn new(side: &str, shadow: &BoxShadowPreDisplayItem) -> Self {
+        Self {
+            side: side.into(),
+            clip_mode: match shadow.clip_mode {
+                BoxShadowClipMode::Outset => "outset".into(),
+                BoxShadowClipMode::Inset => "inset".into(),
+            },
+            offset: SnapshotPoint {
+                x: shadow.offset[0].to_pixels(),
+                y: shadow.offset[1].to_pixels(),
+            },
+            color: shadow.color.into(),
+            blur_radius: shadow.blur_radius.to_pixels(),
+            spread_radius: shadow.spread_radius.to_pixels(),
+        }
+    }
+}
+
+impl SnapshotPrimitive {
+
+    /// Returns the box shadows of the given clip mode, mirrors `push_box_shadow`
+    pub(crate) fn box_shadows(
+        box_shadow: &Option<::azul_css::StyleBoxShadow>,
+        clip_mode: BoxShadowClipMode,
+    ) -> Vec<Self> {
+
+        let shadow = match box_shadow {
+            Some(s) => s,
+            None => return Vec::new(),
+        };
+
+        let sides = [("top", shadow.top), ("left", shadow.left), ("bottom", shadow.bottom), ("right", shadow.right)];
+
+        // If all four sides are set, only the top shadow is drawn (see `push_box_shadow`)
+        if sides.iter().all(|(_, s)| s.is_some()) {
+            return shadow.top.and_then(|s| s)
+                .filter(|s| s.clip_mode == clip_mode)
+                .map(|s| SnapshotPrimitive::BoxShadow(SnapshotBoxShadow::new("all", &s)))
+                .into_iter()
+                .collect();
+        }
+
+        sides.iter()
+            .filter_map(|(side, s)| s.and_then(|s| s).map(|s| (side, s)))
+            .filter(|(_, s)| s.clip_mode == clip_mode)
+            .map(|(side, s)| SnapshotPrimitive::BoxShadow(SnapshotBoxShadow::new(side, &s)))
+            .collect()
+    }
+
+    /// Returns the resolved background, mirrors `push_background`
+    pub(crate) fn background(
+        background: &StyleBackground,
+        bounds: &LayoutRect,
+        app_resources: &AppResources,
+    ) -> Option<Self> {
+
+        use azul_css::Shape;
+        use css::webrender_translate::wr_translate_layout_rect;
+
+        fn extend_mode(mode: ExtendMode) -> String {
+            match mode {
+                ExtendMode::Clamp => "clamp".into(),
+                ExtendMode::Repeat => "repeat".into(),
+            }
+        }
+
+        match background {
+            StyleBackground::LinearGradient(gradient) => {
+                let (start, end) = gradient.direction.to_points(&wr_translate_layout_rect(*bounds));
+                Some(SnapshotPrimitive::LinearGradient {
+                    start: SnapshotPoint { x: start.x, y: start.y },
+                    end: SnapshotPoint { x: end.x, y: end.y },
+                    extend_mode: extend_mode(gradient.extend_mode),
+                    stops: gradient.stops.iter().map(|s| (*s).into()).collect(),
+                })
+            },
+            StyleBackground::RadialGradient(gradient) => {
+                let center = bounds.center();
+                let (radius_x, radius_y) = match gradient.shape {
+                    Shape::Ellipse => (bounds.size.width / 2.0, bounds.size.height / 2.0),
+                    Shape::Circle => {
+                        let largest_bound_size = bounds.size.width.max(bounds.size.height);
+                        (largest_bound_size / 2.0, largest_bound_size / 2.0)
+                    },
+                };
+                Some(SnapshotPrimitive::RadialGradient {
+                    center: SnapshotPoint { x: center.x, y: center.y },
+                    radius: SnapshotPoint { x: radius_x, y: radius_y },
+                    extend_mode: extend_mode(gradient.extend_mode),
+                    stops: gradient.stops.iter().map(|s| (*s).into()).collect(),
+                })
+            },
+            StyleBackground::Image(css_id) => Some(SnapshotPrimitive::BackgroundImage {
+                css_id: css_id.0.clone(),
+                found: app_resources.get_css_image_id(&css_id.0).is_some(),
+            }),
+            StyleBackground::Color(c) => Some(SnapshotPrimitive::Rect { color: (*c).into() }),
+            StyleBackground::NoBackground => None,
+        }
+    }
+
+    /// Returns the border, mirrors `push_border`
+    pub(crate) fn border(border: &StyleBorder, border_radius: &Option<StyleBorderRadius>) -> Option<Self> {
+
+        // Same defaults as in `StyleBorder::get_webrender_border`
+        const DEFAULT_BORDER_STYLE: BorderStyle = BorderStyle::Solid;
+        const DEFAULT_BORDER_COLOR: ColorU = ColorU { r: 0, g: 0, b: 0, a: 255 };
+
+        if border.top.is_none() && border.right.is_none() && border.bottom.is_none() && border.left.is_none() {
+            return None;
+        }
+
+        let side = |side: Option<::azul_css::StyleBorderSide>| SnapshotBorderSide {
+            width: side.map(|s| s.border_width.to_pixels()).unwrap_or(0.0),
+            style: format!("{:?}", side.map(|s| s.border_style).unwrap_or(DEFAULT_BORDER_STYLE)).to_lowercase(),
+            color: side.map(|s| s.border_color).unwrap_or(DEFAULT_BORDER_COLOR).into(),
+        };
+
+        Some(SnapshotPrimitive::Border {
+            sides: [side(border.top), side(border.right), side(border.bottom), side(border.left)],
+            radius: border_radius.map(|r| r.0.into()),
+        })
+    }
+}
+
+impl DisplayListSnapshot {
+
+    /// Creates an empty snapshot
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts recording the primitives of a new rectangle
+    pub(crate) fn begin_item(&mut self, node_id: usize, node_type: String, hit_test: bool, bounds: LayoutRect) {
+        self.items.push(SnapshotItem {
+            node_id,
+            node_type,
+            hit_test,
+            bounds: bounds.into(),
+            primitives: Vec::new(),
+        });
+    }
+
+    /// Records a primitive for the rectangle that was last started with `begin_item`
+    pub(crate) fn push(&mut self, primitive: SnapshotPrimitive) {
+        if let Some(item) = self.items.last_mut() {
+            item.primitives.push(primitive);
+        }
+    }
+
+    /// Records multiple primitives for the rectangle that was last started with `begin_item`
+    pub(crate) fn extend<I: IntoIterator<Item=SnapshotPrimitive>>(&mut self, primitives: I) {
+        if let Some(item) = self.items.last_mut() {
+            item.primitives.extend(primitives);
+        }
+    }
+
+    /// Compares the text form of the snapshot against the expected text and returns
+    /// a line-based diff (`-` = expected, `+` = actual), or `None` if both are equal.
+    pub fn diff(&self, expected: &str) -> Option<String> {
+        let actual = self.to_string();
+        if normalize_newlines(expected) == normalize_newlines(&actual) {
+            None
+        } else {
+            Some(diff_lines(&normalize_newlines(expected), &normalize_newlines(&actual)))
+        }
+    }
+
+    /// Compares the snapshot against the golden file at `path`
+    ///
+    /// If the file doesn't exist or if the `AZUL_UPDATE_GOLDEN` environment variable
+    /// is set, the golden file is written instead. Returns `Ok(Some(diff))` if the
+    /// snapshot differs from the golden file.
+    pub fn compare_with_golden_file<P: AsRef<Path>>(&self, path: P) -> Result<Option<String>, io::Error> {
+        let path = path.as_ref();
+
+        if env::var_os(UPDATE_GOLDEN_FILES_ENV_VAR).is_some() || !path.exists() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, self.to_string())?;
+            return Ok(None);
+        }
+
+        let expected = fs::read_to_string(path)?;
+        Ok(self.diff(&expected))
+    }
+
+    /// Same as `compare_with_golden_file`, but panics with a readable diff if
+    /// the snapshot doesn't match the golden file - meant to be used in tests
+    pub fn assert_matches_golden_file<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        match self.compare_with_golden_file(path) {
+            Ok(None) => { },
+            Ok(Some(diff)) => panic!(
+                "display list doesn't match the golden file {} (- expected, + actual):\n{}\n\
+                 Set {}=1 to update the golden file if the change is intended.",
+                path.display(), diff, UPDATE_GOLDEN_FILES_ENV_VAR
+            ),
+            Err(e) => panic!("could not access golden file {}: {}", path.display(), e),
+        }
+    }
+}
+
+fn normalize_newlines(s: &str) -> String {
+    s.replace("\r\n", "\n")
+}
+
+/// Simple line-based diff (longest common subsequence), prints changed lines
+/// prefixed with `-` / `+` and `DIFF_CONTEXT_LINES` unchanged lines around them
+fn diff_lines(expected: &str, actual: &str) -> String {
+
+    #[derive(Copy, Clone, PartialEq)]
+    enum Line<'a> {
+        Same(&'a str),
+        Removed(&'a str),
+        Added(&'a str),
+    }
+
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j] = length of the longest common subsequence of a[i..] and b[j..]
+    let mut lcs = vec![vec![0_usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            lines.push(Line::Same(a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(Line::Removed(a[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(b[j]));
+            j += 1;
+        }
+    }
+    lines.extend(a[i..].iter().map(|l| Line::Removed(l)));
+    lines.extend(b[j..].iter().map(|l| Line::Added(l)));
+
+    let is_change = |line: &Line| match line { Line::Same(_) => false, _ => true };
+
+    let mut out = String::new();
+    let mut last_printed = None;
+    for (idx, line) in lines.iter().enumerate() {
+        let start = idx.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (idx + DIFF_CONTEXT_LINES + 1).min(lines.len());
+        if !lines[start..end].iter().any(is_change) {
+            continue;
+        }
+        if let Some(last) = last_printed {
+            if idx > last + 1 {
+                out.push_str("...\n");
+            }
+        }
+        match line {
+            Line::Same(l) => { out.push_str("  "); out.push_str(l); },
+            Line::Removed(l) => { out.push_str("- "); out.push_str(l); },
+            Line::Added(l) => { out.push_str("+ "); out.push_str(l); },
+        }
+        out.push('\n');
+        last_printed = Some(idx);
+    }
+
+    out
+}
+
+/// Formats a float with a fixed precision, so that the output is stable
+struct Px(f32);
+
+impl fmt::Display for Px {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // + 0.0 turns -0.0 into 0.0
+        let s = format!("{:.2}", self.0 + 0.0);
+        if s == "-0.00" { write!(f, "0.00") } else { write!(f, "{}", s) }
+    }
+}
+
+impl fmt::Display for SnapshotRect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}, {} x {}]", Px(self.x), Px(self.y), Px(self.width), Px(self.height))
+    }
+}
+
+impl fmt::Display for SnapshotPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", Px(self.x), Px(self.y))
+    }
+}
+
+impl fmt::Display for SnapshotTransform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "matrix({}, {}, {}, {}, {}, {})", Px(self.a), Px(self.b), Px(self.c), Px(self.d), Px(self.e), Px(self.f))
+    }
+}
+
+impl fmt::Display for SnapshotColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+}
+
+impl fmt::Display for SnapshotBorderRadius {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [tl, tr, bl, br] = self.corners;
+        write!(f, "[top-left {}, top-right {}, bottom-left {}, bottom-right {}]", tl, tr, bl, br)
+    }
+}
+
+impl fmt::Display for SnapshotGradientStop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}%", self.color, Px(self.offset))
+    }
+}
+
+fn write_stops(f: &mut fmt::Formatter, stops: &[SnapshotGradientStop]) -> fmt::Result {
+    for (i, stop) in stops.iter().enumerate() {
+        if i != 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", stop)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for SnapshotPrimitive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::SnapshotPrimitive::*;
+        match self {
+            PushClip { rect, radius } => write!(f, "push-clip {} radius {}", rect, radius),
+            PopClip => write!(f, "pop-clip"),
+            PushScrollFrame { clip, offset } => write!(f, "push-scroll-frame {} offset {}", clip, offset),
+            PopScrollFrame => write!(f, "pop-scroll-frame"),
+            PushStackingContext { opacity, transform } => {
+                write!(f, "push-stacking-context opacity {}", Px(*opacity))?;
+                if let Some(transform) = transform {
+                    write!(f, " transform {}", transform)?;
+                }
+                Ok(())
+            },
+            PopStackingContext => write!(f, "pop-stacking-context"),
+            BoxShadow(s) => write!(f,
+                "box-shadow {} {} offset {} color {} blur {} spread {}",
+                s.clip_mode, s.side, s.offset, s.color, Px(s.blur_radius), Px(s.spread_radius)
+            ),
+            Rect { color } => write!(f, "rect {}", color),
+            LinearGradient { start, end, extend_mode, stops } => {
+                write!(f, "linear-gradient {} -> {} {} [", start, end, extend_mode)?;
+                write_stops(f, stops)?;
+                write!(f, "]")
+            },
+            RadialGradient { center, radius, extend_mode, stops } => {
+                write!(f, "radial-gradient center {} radius {} {} [", center, radius, extend_mode)?;
+                write_stops(f, stops)?;
+                write!(f, "]")
+            },
+            BackgroundImage { css_id, found } => write!(f,
+                "background-image {:?}{}", css_id, if *found { "" } else { " (missing)" }
+            ),
+            Border { sides, radius } => {
+                write!(f, "border")?;
+                for (name, side) in ["top", "right", "bottom", "left"].iter().zip(sides.iter()) {
+                    write!(f, " {} {} {} {};", name, Px(side.width), side.style, side.color)?;
+                }
+                if let Some(radius) = radius {
+                    write!(f, " radius {}", radius)?;
+                }
+                Ok(())
+            },
+            Text { color, font_family, font_weight, font_style, font_size, clip, glyphs } => {
+                write!(f, "text {} font {:?} {} {} {} glyphs {}", color, font_family, font_weight, font_style, Px(*font_size), glyphs.len())?;
+                if let Some(clip) = clip {
+                    write!(f, " clip {}", clip)?;
+                }
+                for glyph in glyphs {
+                    write!(f, "\n    glyph {} at {}", glyph.index, SnapshotPoint { x: glyph.x, y: glyph.y })?;
+                }
+                Ok(())
+            },
+            Image { width, height, .. } => write!(f, "image {} x {}", Px(*width), Px(*height)),
+            GlTexture => write!(f, "gl-texture"),
+        }
+    }
+}
+
+impl fmt::Display for SnapshotItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} #{} {}", self.node_type, self.node_id, self.bounds)?;
+        if self.hit_test {
+            write!(f, " hit-test")?;
+        }
+        for primitive in &self.primitives {
+            write!(f, "\n  {}", primitive)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DisplayListSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for item in &self.items {
+            writeln!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_display_list_snapshot_text_form() {
+    let mut snapshot = DisplayListSnapshot::new();
+    snapshot.begin_item(0, "div".into(), true, LayoutRect::new(
+        ::webrender::api::LayoutPoint::new(0.0, -0.0),
+        ::webrender::api::LayoutSize::new(100.0, 50.5),
+    ));
+    snapshot.push(SnapshotPrimitive::Rect { color: ColorU { r: 255, g: 0, b: 0, a: 255 }.into() });
+
+    assert_eq!(snapshot.to_string(), "div #0 [0.00, 0.00, 100.00 x 50.50] hit-test\n  rect #ff0000ff\n");
+}
+
+#[test]
+fn test_display_list_snapshot_diff() {
+    let mut snapshot = DisplayListSnapshot::new();
+    snapshot.begin_item(0, "div".into(), false, LayoutRect::zero());
+    snapshot.push(SnapshotPrimitive::Rect { color: ColorU { r: 0, g: 0, b: 0, a: 255 }.into() });
+
+    let expected = snapshot.to_string();
+    assert_eq!(snapshot.diff(&expected), None);
+    assert_eq!(snapshot.diff(&expected.replace("\n", "\r\n")), None);
+
+    let changed = expected.replace("#000000ff", "#ffffffff");
+    assert_eq!(
+        snapshot.diff(&changed),
+        Some("  div #0 [0.00, 0.00, 0.00 x 0.00]\n-   rect #ffffffff\n+   rect #000000ff\n".into())
+    );
+}
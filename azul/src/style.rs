@@ -2,27 +2,46 @@
 
 use std::{fmt, collections::BTreeMap};
 use azul_css::{
-    Css, CssContentGroup, CssPath,
-    CssPathSelector, CssPathPseudoSelector, CssNthChildSelector::*,
+    Css, CssContentGroup, CssPath, CssProperty, CssPropertyType,
+    CssPathSelector, CssPathPseudoSelector, CssNthChildSelector, CssNthChildSelector::*,
 };
 use webrender::api::HitTestItem;
 use {
     traits::Layout,
     ui_description::{UiDescription, StyledNode},
-    dom::NodeData,
+    dom::{NodeData, DomString},
     ui_state::UiState,
     id_tree::{NodeId, NodeHierarchy, NodeDataContainer},
     callbacks::FocusTarget,
+    diff::DomDiff,
+    window::ResolvedCss,
 };
 
 /// Has all the necessary information about the style CSS path
 pub(crate) struct HtmlCascadeInfo<'a, T: 'a + Layout> {
     pub node_data: &'a NodeData<T>,
     pub index_in_parent: usize,
+    /// Same as `index_in_parent`, but counted from the last child (necessary for `:nth-last-child`)
+    pub index_from_end: usize,
     pub is_last_child: bool,
+    /// Whether the node has no children (necessary for `:empty`)
+    pub is_empty: bool,
     pub is_hovered_over: bool,
     pub is_focused: bool,
     pub is_active: bool,
+    pub is_dragging: bool,
+    pub is_drop_target: bool,
+}
+
+/// State of the `:hover`, `:focus`, `:active`, `:dragging` and `:drop-target` pseudo-selectors for a node -
+/// if the state changes between two frames, the node has to be restyled
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct PseudoClassState {
+    pub is_hovered_over: bool,
+    pub is_focused: bool,
+    pub is_active: bool,
+    pub is_dragging: bool,
+    pub is_drop_target: bool,
 }
 
 impl<'a, T: 'a + Layout> fmt::Debug for HtmlCascadeInfo<'a, T> {
@@ -30,17 +49,25 @@ impl<'a, T: 'a + Layout> fmt::Debug for HtmlCascadeInfo<'a, T> {
         write!(f, "HtmlCascadeInfo {{ \
             node_data: {:?}, \
             index_in_parent: {}, \
+            index_from_end: {}, \
             is_last_child: {:?}, \
+            is_empty: {:?}, \
             is_hovered_over: {:?}, \
             is_focused: {:?}, \
             is_active: {:?}, \
+            is_dragging: {:?}, \
+            is_drop_target: {:?}, \
          }}",
             self.node_data,
             self.index_in_parent,
+            self.index_from_end,
             self.is_last_child,
+            self.is_empty,
             self.is_hovered_over,
             self.is_focused,
             self.is_active,
+            self.is_dragging,
+            self.is_drop_target,
          )
     }
 }
@@ -53,48 +80,61 @@ pub(crate) fn matches_html_element<'a, T: Layout>(
     html_node_tree: &NodeDataContainer<HtmlCascadeInfo<'a, T>>)
 -> bool
 {
-    use self::CssGroupSplitReason::*;
-
     if css_path.selectors.is_empty() {
         return false;
     }
 
-    let mut current_node = Some(node_id);
-    let mut direct_parent_has_to_match = false;
-    let mut last_selector_matched = true;
+    let content_groups = CssGroupIterator::new(&css_path.selectors).collect::<Vec<_>>();
+    content_groups_match(&content_groups, node_id, node_hierarchy, html_node_tree)
+}
 
-    for (content_group, reason) in CssGroupIterator::new(&css_path.selectors) {
-        let cur_node_id = match current_node {
-            Some(c) => c,
-            None => {
-                // The node has no parent, but the CSS path
-                // still has an extra limitation - only valid if the
-                // next content group is a "*" element
-                return *content_group == [&CssPathSelector::Global];
-            },
-        };
-        let current_selector_matches = selector_group_matches(&content_group, &html_node_tree[cur_node_id]);
+/// Matches the content groups (in the order of the `CssGroupIterator`, i.e. from right to left)
+/// against the node and its ancestors / preceding siblings.
+///
+/// For ` ` and `~` combinators, all ancestors / preceding siblings are tried, so that
+/// `.a > .b .c` matches even if the closest `.b` isn't a child of an `.a`, but another `.b` is.
+fn content_groups_match<'a, T: Layout>(
+    content_groups: &[(CssContentGroup, CssGroupSplitReason)],
+    node_id: NodeId,
+    node_hierarchy: &NodeHierarchy,
+    html_node_tree: &NodeDataContainer<HtmlCascadeInfo<'a, T>>)
+-> bool
+{
+    use self::CssGroupSplitReason::*;
 
-        if direct_parent_has_to_match && !current_selector_matches {
-            // If the element was a ">" element and the current,
-            // direct parent does not match, return false
-            return false; // not executed (maybe this is the bug)
-        }
+    let (content_group, reason) = match content_groups.first() {
+        Some(s) => s,
+        None => return true,
+    };
 
-        // If the current selector matches, but the previous one didn't,
-        // that means that the CSS path chain is broken and therefore doesn't match the element
-        if current_selector_matches && !last_selector_matched {
-            return false;
-        }
+    if !selector_group_matches(content_group, &html_node_tree[node_id]) {
+        return false;
+    }
 
-        // Important: Set if the current selector has matched the element
-        last_selector_matched = current_selector_matches;
-        // Select if the next content group has to exactly match or if it can potentially be skipped
-        direct_parent_has_to_match = reason == DirectChildren;
-        current_node = node_hierarchy[cur_node_id].parent;
+    let remaining_groups = &content_groups[1..];
+
+    if remaining_groups.is_empty() {
+        return true;
     }
 
-    last_selector_matched
+    let remaining_groups_match = |next_node_id: NodeId| {
+        content_groups_match(remaining_groups, next_node_id, node_hierarchy, html_node_tree)
+    };
+
+    let node = &node_hierarchy[node_id];
+
+    match reason {
+        DirectChildren | Children if node.parent.is_none() => {
+            // The node has no parent, but the CSS path
+            // still has an extra limitation - only valid if the
+            // next content group is a "*" element
+            remaining_groups.len() == 1 && remaining_groups[0].0 == [&CssPathSelector::Global]
+        },
+        DirectChildren => node.parent.map(remaining_groups_match).unwrap_or(false),
+        Children => node_id.ancestors(node_hierarchy).skip(1).any(remaining_groups_match),
+        AdjacentSibling => node.previous_sibling.map(remaining_groups_match).unwrap_or(false),
+        GeneralSibling => node_id.preceding_siblings(node_hierarchy).skip(1).any(remaining_groups_match),
+    }
 }
 
 struct CssGroupIterator<'a> {
@@ -107,6 +147,8 @@ struct CssGroupIterator<'a> {
 enum CssGroupSplitReason {
     Children,
     DirectChildren,
+    AdjacentSibling,
+    GeneralSibling,
 }
 
 impl<'a> CssGroupIterator<'a> {
@@ -144,6 +186,14 @@ impl<'a> Iterator for CssGroupIterator<'a> {
                     self.last_reason = CssGroupSplitReason::DirectChildren;
                     break;
                 },
+                AdjacentSibling => {
+                    self.last_reason = CssGroupSplitReason::AdjacentSibling;
+                    break;
+                },
+                GeneralSibling => {
+                    self.last_reason = CssGroupSplitReason::GeneralSibling;
+                    break;
+                },
                 other => current_path.push(other),
             }
             new_idx -= 1;
@@ -163,7 +213,7 @@ impl<'a> Iterator for CssGroupIterator<'a> {
                 Some((current_path, self.last_reason))
             }
         } else {
-            // skip the "Children | DirectChildren" element itself
+            // skip the "Children | DirectChildren | AdjacentSibling | GeneralSibling" element itself
             self.current_idx = new_idx - 1;
             Some((current_path, self.last_reason))
         }
@@ -179,41 +229,62 @@ fn construct_html_cascade_tree<'a, T: Layout>(
     is_mouse_down: bool
 ) -> NodeDataContainer<HtmlCascadeInfo<'a, T>> {
 
-    let mut nodes = (0..node_hierarchy.len()).map(|_| HtmlCascadeInfo {
-        node_data: &input[NodeId::new(0)],
-        index_in_parent: 0,
-        is_last_child: false,
-        is_hovered_over: false,
-        is_active: false,
-        is_focused: false,
+    // NOTE: The pseudo-selector state has to be set here, too, otherwise
+    // a root node without any children could never be :hover / :focus-ed
+    let mut nodes = (0..node_hierarchy.len()).map(|node_index| {
+        let node_id = NodeId::new(node_index);
+        let is_hovered_over = hovered_items.contains_key(&node_id);
+        HtmlCascadeInfo {
+            node_data: &input[node_id],
+            index_in_parent: 0,
+            index_from_end: 0,
+            is_last_child: false,
+            is_empty: node_hierarchy[node_id].first_child.is_none(),
+            is_hovered_over,
+            is_active: is_hovered_over && is_mouse_down,
+            is_focused: focused_item == Some(node_id),
+            is_dragging: false,
+            is_drop_target: false,
+        }
     }).collect::<Vec<_>>();
 
     for (_depth, parent_id) in node_depths_sorted {
 
         // Note: :nth-child() starts at 1 instead of 0
         let index_in_parent = parent_id.preceding_siblings(node_hierarchy).count();
+        let index_from_end = parent_id.following_siblings(node_hierarchy).count();
 
         let is_parent_hovered_over = hovered_items.contains_key(parent_id);
         let parent_html_matcher = HtmlCascadeInfo {
             node_data: &input[*parent_id],
             index_in_parent: index_in_parent, // necessary for nth-child
+            index_from_end: index_from_end, // necessary for nth-last-child
             is_last_child: node_hierarchy[*parent_id].next_sibling.is_none(), // Necessary for :last selectors
+            is_empty: false, // parents always have children
             is_hovered_over: is_parent_hovered_over,
             is_active: is_parent_hovered_over && is_mouse_down,
             is_focused: focused_item == Some(*parent_id),
+            is_dragging: false,
+            is_drop_target: false,
         };
 
         nodes[parent_id.index()] = parent_html_matcher;
 
+        let child_count = parent_id.children(node_hierarchy).count();
+
         for (child_idx, child_id) in parent_id.children(node_hierarchy).enumerate() {
             let is_child_hovered_over = hovered_items.contains_key(&child_id);
             let child_html_matcher = HtmlCascadeInfo {
                 node_data: &input[child_id],
                 index_in_parent: child_idx + 1, // necessary for nth-child
+                index_from_end: child_count - child_idx, // necessary for nth-last-child
                 is_last_child: node_hierarchy[child_id].next_sibling.is_none(),
+                is_empty: node_hierarchy[child_id].first_child.is_none(),
                 is_hovered_over: is_child_hovered_over,
                 is_active: is_child_hovered_over && is_mouse_down,
                 is_focused: focused_item == Some(child_id),
+                is_dragging: false,
+                is_drop_target: false,
             };
 
             nodes[child_id.index()] = child_html_matcher;
@@ -294,7 +365,7 @@ fn match_hover_selectors<'a, T: Layout>(
     btree_map
 }
 
-/// Matches a single group of items, panics on Children or DirectChildren selectors
+/// Matches a single group of items, panics on Children, DirectChildren, AdjacentSibling or GeneralSibling selectors
 ///
 /// The intent is to "split" the CSS path into groups by selectors, then store and cache
 /// whether the direct or any parent has matched the path correctly
@@ -319,6 +390,11 @@ fn selector_group_matches<'a, T: Layout>(selectors: &[&CssPathSelector], html_no
                     return false;
                 }
             },
+            Attribute(attribute) => {
+                if !attribute.matches(html_node.node_data.get_attribute(&attribute.name)) {
+                    return false;
+                }
+            },
             PseudoSelector(CssPathPseudoSelector::First) => {
                 // Notice: index_in_parent is 1-indexed
                 if html_node.index_in_parent != 1 { return false; }
@@ -328,13 +404,20 @@ fn selector_group_matches<'a, T: Layout>(selectors: &[&CssPathSelector], html_no
                 if !html_node.is_last_child { return false; }
             },
             PseudoSelector(CssPathPseudoSelector::NthChild(x)) => {
-                match *x {
-                    Number(value) => if html_node.index_in_parent != value { return false; },
-                    Even => if html_node.index_in_parent % 2 == 0 { return false; },
-                    Odd => if html_node.index_in_parent % 2 == 1 { return false; },
-                    Pattern { repeat, offset } => if html_node.index_in_parent >= offset &&
-                        ((html_node.index_in_parent - offset) % repeat != 0) { return false; },
-                }
+                if !nth_child_matches(*x, html_node.index_in_parent) { return false; }
+            },
+            PseudoSelector(CssPathPseudoSelector::NthLastChild(x)) => {
+                if !nth_child_matches(*x, html_node.index_from_end) { return false; }
+            },
+            PseudoSelector(CssPathPseudoSelector::OnlyChild) => {
+                if html_node.index_in_parent != 1 || !html_node.is_last_child { return false; }
+            },
+            PseudoSelector(CssPathPseudoSelector::Empty) => {
+                if !html_node.is_empty { return false; }
+            },
+            PseudoSelector(CssPathPseudoSelector::Not(selectors)) => {
+                let selectors = selectors.iter().collect::<Vec<_>>();
+                if selector_group_matches(&selectors, html_node) { return false; }
             },
             PseudoSelector(CssPathPseudoSelector::Hover) => {
                 if !html_node.is_hovered_over { return false; }
@@ -345,8 +428,14 @@ fn selector_group_matches<'a, T: Layout>(selectors: &[&CssPathSelector], html_no
             PseudoSelector(CssPathPseudoSelector::Focus) => {
                 if !html_node.is_focused { return false; }
             },
-            DirectChildren | Children => {
-                panic!("Unreachable: DirectChildren or Children in CSS path!");
+            PseudoSelector(CssPathPseudoSelector::Dragging) => {
+                if !html_node.is_dragging { return false; }
+            },
+            PseudoSelector(CssPathPseudoSelector::DropTarget) => {
+                if !html_node.is_drop_target { return false; }
+            },
+            DirectChildren | Children | AdjacentSibling | GeneralSibling => {
+                panic!("Unreachable: DirectChildren, Children, AdjacentSibling or GeneralSibling in CSS path!");
             },
         }
     }
@@ -354,13 +443,119 @@ fn selector_group_matches<'a, T: Layout>(selectors: &[&CssPathSelector], html_no
     true
 }
 
+/// Returns whether the (1-indexed) position of a node matches a `:nth-child()` / `:nth-last-child()` selector
+fn nth_child_matches(selector: CssNthChildSelector, index: usize) -> bool {
+    match selector {
+        Number(value) => index == value,
+        Even => index % 2 == 0,
+        Odd => index % 2 == 1,
+        Pattern { repeat: 0, offset } => index == offset,
+        Pattern { repeat, offset } => index >= offset && (index - offset) % repeat == 0,
+    }
+}
+
+/// Returns whether any CSS rule contains a selector for which the predicate returns true
+/// (including the selectors inside of a `:not()`)
+fn css_contains_selector<F: Fn(&CssPathSelector) -> bool>(css: &Css, predicate: F) -> bool {
+
+    fn contains_selector(selectors: &[CssPathSelector], predicate: &dyn Fn(&CssPathSelector) -> bool) -> bool {
+        selectors.iter().any(|selector| predicate(selector) || match selector {
+            CssPathSelector::PseudoSelector(CssPathPseudoSelector::Not(inner)) => contains_selector(inner, predicate),
+            _ => false,
+        })
+    }
+
+    css.rules().any(|rule| contains_selector(&rule.path.selectors, &predicate))
+}
+
+/// Determines which nodes can re-use the style of the previous frame, returns the
+/// ID of the node in the previous frame for each node that doesn't need a restyle.
+///
+/// A node can only re-use its style if it wasn't changed in the DOM diff (which includes
+/// changes of the CSS variables set on the node or its parents), its pseudo-selector state is the same and its parent can re-use its style, too
+/// (because of inheritance and selectors such as `.a:hover .b`).
+///
+/// If the CSS contains `+` or `~` selectors, the previous sibling has to be able to
+/// re-use its style as well. The `DomDiff` only tracks the position of a node relative
+/// to its first and last sibling, so if the CSS contains `:nth-last-child` or `:empty`
+/// selectors, the number of following siblings and children have to be unchanged, too.
+fn get_reusable_styles<T: Layout>(
+    css: &Css,
+    node_hierarchy: &NodeHierarchy,
+    pseudo_class_states: &NodeDataContainer<PseudoClassState>,
+    previous_ui_description: &UiDescription<T>,
+    dom_diff: &DomDiff,
+) -> Vec<Option<NodeId>> {
+
+    use azul_css::CssPathSelector::*;
+    use azul_css::CssPathPseudoSelector::{NthLastChild, Empty};
+
+    let has_sibling_selectors = css_contains_selector(css, |selector| match selector {
+        AdjacentSibling | GeneralSibling => true,
+        _ => false,
+    });
+
+    let has_structural_selectors = css_contains_selector(css, |selector| match selector {
+        PseudoSelector(NthLastChild(_)) | PseudoSelector(Empty) => true,
+        _ => false,
+    });
+
+    let old_node_hierarchy = &previous_ui_description.ui_descr_arena.node_layout;
+    let mut reusable_styles = vec![None; node_hierarchy.len()];
+
+    // Parents always have a lower index than their children,
+    // so the parent is always visited before the child
+    for node_id in node_hierarchy.linear_iter() {
+
+        let parent_is_reusable = match node_hierarchy[node_id].parent {
+            Some(parent_id) => reusable_styles[parent_id.index()].is_some(),
+            None => true,
+        };
+
+        if !parent_is_reusable || dom_diff.needs_restyle(node_id) {
+            continue;
+        }
+
+        if has_sibling_selectors {
+            if let Some(previous_sibling) = node_hierarchy[node_id].previous_sibling {
+                if reusable_styles[previous_sibling.index()].is_none() {
+                    continue;
+                }
+            }
+        }
+
+        let old_node_id = match dom_diff.get_old_node_id(node_id) {
+            Some(s) => s,
+            None => continue,
+        };
+
+        if has_structural_selectors && (
+            old_node_hierarchy[old_node_id].first_child.is_none() != node_hierarchy[node_id].first_child.is_none() ||
+            old_node_id.following_siblings(old_node_hierarchy).count() != node_id.following_siblings(node_hierarchy).count()
+        ) {
+            continue;
+        }
+
+        if previous_ui_description.pseudo_class_states.get(old_node_id) == Some(&pseudo_class_states[node_id]) {
+            reusable_styles[node_id.index()] = Some(old_node_id);
+        }
+    }
+
+    reusable_styles
+}
+
+/// If `previous_frame` is set, the style of all nodes that didn't change in the
+/// `DomDiff` is copied from the previous `UiDescription` instead of re-matching the CSS
+/// (the `CssKey` has to be the same as the one of the previous frame).
 pub(crate) fn match_dom_selectors<T: Layout>(
     ui_state: &UiState<T>,
-    css: &Css,
+    css: &ResolvedCss,
+    previous_frame: Option<(&UiDescription<T>, &DomDiff)>,
     focused_node: &mut Option<NodeId>,
     pending_focus_target: &mut Option<FocusTarget>,
     hovered_nodes: &BTreeMap<NodeId, HitTestItem>,
     is_mouse_down: bool,
+    dragged_node: Option<NodeId>,
 ) -> UiDescription<T> {
 
     use azul_css::CssDeclaration;
@@ -385,30 +580,113 @@ pub(crate) fn match_dom_selectors<T: Layout>(
         &mut html_tree,
     );
 
+    if let Some(dragged_node) = dragged_node {
+        set_drag_and_drop_state(dragged_node, ui_state, &mut html_tree);
+    }
+
+    let pseudo_class_states = html_tree.transform(|html_node, _| PseudoClassState {
+        is_hovered_over: html_node.is_hovered_over,
+        is_focused: html_node.is_focused,
+        is_active: html_node.is_active,
+        is_dragging: html_node.is_dragging,
+        is_drop_target: html_node.is_drop_target,
+    });
+
+    let reusable_styles = previous_frame.map(|(previous_ui_description, dom_diff)| {
+        get_reusable_styles(css, &ui_state.dom.arena.node_layout, &pseudo_class_states, previous_ui_description, dom_diff)
+    });
+
     // First, apply all rules normally (no inheritance) of CSS values
     // This is an O(n^2) operation, but it can be parallelized in the future
-    let mut styled_nodes = ui_state.dom.arena.node_data.transform(|_, node_id| StyledNode {
-        css_constraints: css
+    //
+    // Nodes that didn't change since the last frame simply copy their old style
+    // (which already contains the inherited values, see below)
+    let mut styled_nodes = ui_state.dom.arena.node_data.transform(|_, node_id| {
+
+        if let (Some((previous_ui_description, _)), Some(reusable_styles)) = (previous_frame, reusable_styles.as_ref()) {
+            if let Some(old_node_id) = reusable_styles[node_id.index()] {
+                return previous_ui_description.styled_nodes[old_node_id].clone();
+            }
+        }
+
+        let mut css_constraints = BTreeMap::new();
+        let mut css_variables = BTreeMap::new();
+        let mut transitions = Vec::new();
+        let mut animations = Vec::new();
+
+        let matched_declarations = css
             .rules()
             .filter(|rule| matches_html_element(&rule.path, node_id, &ui_state.dom.arena.node_layout, &html_tree))
-            .flat_map(|matched_rule| matched_rule.declarations.iter().map(|declaration| (declaration.get_type(), declaration.clone())))
-            .collect(),
+            .flat_map(|matched_rule| matched_rule.declarations.iter());
+
+        for declaration in matched_declarations {
+            match declaration {
+                CssDeclaration::Variable(variable) => {
+                    css_variables.insert(variable.name.clone(), variable.value.clone());
+                },
+                // The rules are sorted by specificity, so the last matched transition / animation wins
+                CssDeclaration::Transition(t) => { transitions = t.clone(); },
+                CssDeclaration::Animation(a) => { animations = a.clone(); },
+                _ => {
+                    if let Some(property_type) = declaration.get_type() {
+                        css_constraints.insert(property_type, declaration.clone());
+                    }
+                },
+            }
+        }
+
+        StyledNode {
+            css_constraints,
+            css_variables,
+            transitions,
+            animations,
+        }
     });
 
+    // Then, inherit the CSS variables and substitute the `var()` references of each node.
+    // Parents always have a lower index than their children, so the variables of
+    // the parent are always resolved before the child is visited.
+    //
+    // Re-used styles already contain the resolved variables and properties.
+    for node_id in ui_state.dom.arena.node_layout.linear_iter() {
+
+        let is_reused = reusable_styles.as_ref().and_then(|reusable| reusable[node_id.index()]).is_some();
+        if is_reused {
+            continue;
+        }
+
+        let inherited_variables = match ui_state.dom.arena.node_layout[node_id].parent {
+            Some(parent_id) => styled_nodes[parent_id].css_variables.clone(),
+            None => BTreeMap::new(),
+        };
+
+        resolve_css_variables(
+            &mut styled_nodes[node_id],
+            inherited_variables,
+            &ui_state.dom.arena.node_data[node_id].css_variables,
+        );
+    }
+
     // Then, inherit all values of the parent to the children, but only if the property is
     // inheritable and isn't yet set. NOTE: This step can't be parallelized!
+    //
+    // Re-used styles already contain the inherited properties and since their parents
+    // are re-used as well, inheriting them again doesn't change anything.
     for (_depth, parent_id) in non_leaf_nodes {
 
-        let inherited_rules: Vec<CssDeclaration> = styled_nodes[parent_id].css_constraints.values().filter(|prop| prop.is_inheritable()).cloned().collect();
+        let inherited_rules: Vec<(CssPropertyType, CssDeclaration)> = styled_nodes[parent_id].css_constraints.iter()
+            .filter(|(_, prop)| prop.is_inheritable())
+            .map(|(property_type, prop)| (*property_type, prop.clone()))
+            .collect();
+
         if inherited_rules.is_empty() {
             continue;
         }
 
         for child_id in parent_id.children(&ui_state.dom.arena.node_layout) {
-            for inherited_rule in &inherited_rules {
+            for (inherited_rule_type, inherited_rule) in &inherited_rules {
                 // Only override the rule if the child already has an inherited rule, don't override it
-                let inherited_rule_type = inherited_rule.get_type();
-                styled_nodes[child_id].css_constraints.entry(inherited_rule_type).or_insert_with(|| inherited_rule.clone());
+                styled_nodes[child_id].css_constraints.entry(*inherited_rule_type).or_insert_with(|| inherited_rule.clone());
             }
         }
     }
@@ -438,6 +716,107 @@ pub(crate) fn match_dom_selectors<T: Layout>(
         ui_descr_root: ui_state.dom.root,
         styled_nodes,
         selected_hover_nodes,
+        pseudo_class_states,
+        css: css.clone(),
+        dom_diff: None,
+        animated_properties: BTreeMap::new(),
+    }
+}
+
+/// Inherits the CSS variables of the parent, overrides them with the variables of the
+/// node itself and substitutes the `var()` references of the node with the resulting values.
+///
+/// Variables set on the `Dom` (via `Dom::with_css_variable`) take precedence over the variables
+/// set in the stylesheet. Properties whose `var()` references can't be substituted or whose value
+/// is invalid after the substitution are ignored, i.e. they are inherited from the parent, if possible.
+fn resolve_css_variables(
+    styled_node: &mut StyledNode,
+    inherited_variables: BTreeMap<String, String>,
+    dom_variables: &[(DomString, DomString)],
+) {
+    use azul_css::CssDeclaration::{Static, VarReference};
+    use std::mem;
+
+    let mut own_variables = mem::replace(&mut styled_node.css_variables, BTreeMap::new());
+    own_variables.extend(dom_variables.iter().map(|(name, value)| (name.as_str().to_string(), value.as_str().to_string())));
+
+    let mut unresolved_variables = inherited_variables;
+    unresolved_variables.extend(own_variables.iter().map(|(name, value)| (name.clone(), value.clone())));
+
+    // The variables of the node can reference each other or the inherited variables
+    let mut variables = unresolved_variables.clone();
+    for (name, value) in &own_variables {
+        match substitute_css_variables(value, &unresolved_variables) {
+            Some(substituted) => { variables.insert(name.clone(), substituted); },
+            None => {
+                warn!("Could not resolve CSS variable \"{}: {}\"", name, value);
+                variables.remove(name);
+            },
+        }
+    }
+
+    let var_references = styled_node.css_constraints.values().filter_map(|declaration| match declaration {
+        VarReference(var_reference) => Some((var_reference.property_type, var_reference.value.clone())),
+        _ => None,
+    }).collect::<Vec<_>>();
+
+    for (property_type, value) in var_references {
+        match substitute_css_variables(&value, &variables).and_then(|substituted| parse_css_property(property_type, &substituted)) {
+            Some(property) => { styled_node.css_constraints.insert(property_type, Static(property)); },
+            None => {
+                warn!("Could not resolve CSS property \"{}: {}\"", property_type, value);
+                styled_node.css_constraints.remove(&property_type);
+            },
+        }
+    }
+
+    styled_node.css_variables = variables;
+}
+
+#[cfg(feature = "css_parser")]
+fn substitute_css_variables(value: &str, variables: &BTreeMap<String, String>) -> Option<String> {
+    ::azul_css_parser::substitute_css_variables(value, variables).ok()
+}
+
+#[cfg(feature = "css_parser")]
+fn parse_css_property(property_type: CssPropertyType, value: &str) -> Option<CssProperty> {
+    ::azul_css_parser::parse_key_value_pair(property_type, value).ok()
+}
+
+// Without the CSS parser, the values of CSS variables can't be parsed
+#[cfg(not(feature = "css_parser"))]
+fn substitute_css_variables(_value: &str, _variables: &BTreeMap<String, String>) -> Option<String> {
+    None
+}
+
+#[cfg(not(feature = "css_parser"))]
+fn parse_css_property(_property_type: CssPropertyType, _value: &str) -> Option<CssProperty> {
+    None
+}
+
+/// Marks the node that is currently being dragged as `:dragging` and all nodes
+/// that have an `On::Drop` callback as `:drop-target`
+fn set_drag_and_drop_state<'a, T: 'a + Layout>(
+    dragged_node: NodeId,
+    ui_state: &UiState<T>,
+    html_node_tree: &mut NodeDataContainer<HtmlCascadeInfo<'a, T>>,
+) {
+    use dom::HoverEventFilter;
+
+    // The DOM could have changed since the drag has started
+    if dragged_node.index() < html_node_tree.len() {
+        html_node_tree[dragged_node].is_dragging = true;
+    }
+
+    let drop_targets = ui_state.hover_callbacks.iter()
+        .filter(|(_, callbacks)| callbacks.contains_key(&HoverEventFilter::Drop))
+        .map(|(node_id, _)| *node_id)
+        .chain(ui_state.hover_default_callbacks.iter()
+            .filter(|(_, callbacks)| callbacks.contains_key(&HoverEventFilter::Drop))
+            .map(|(node_id, _)| *node_id));
+
+    for node_id in drop_targets {
+        html_node_tree[node_id].is_drop_target = true;
     }
 }
 
@@ -462,7 +841,7 @@ fn update_focus_from_callbacks<'a, T: 'a + Layout>(
 
     match new_focus_target {
         FocusTarget::Id(node_id) => {
-            if html_node_tree.len() < node_id.index() {
+            if node_id.index() < html_node_tree.len() {
                 *focused_node = Some(node_id);
             } else {
                 warn!("Focusing on node with invalid ID: {}", node_id);
@@ -569,6 +948,65 @@ fn test_case_issue_93() {
     assert_eq!(matches_html_element(&tab_active_close, NodeId::new(4), &node_hierarchy, &html_node_tree), true);
 }
 
+#[test]
+fn test_sibling_attribute_and_structural_selectors() {
+
+    use azul_css::CssPathSelector::*;
+    use azul_css::CssPathPseudoSelector::*;
+    use azul_css::{CssAttributeSelector, CssAttributeCondition};
+    use azul_css::NodeTypePath;
+    use prelude::*;
+
+    struct DataModel { }
+    impl Layout for DataModel { fn layout(&self) -> Dom<DataModel> { Dom::div() } }
+
+    //  0: [div]
+    //   |-- 1: [div .item] [data-state="open"]
+    //   |-- 2: [div .item]
+    //   |-- 3: [div .item]
+    //   |    |-- 4: [p]
+    let dom: Dom<DataModel> = Dom::div()
+        .with_child(Dom::div().with_class("item").with_attribute("data-state", "open"))
+        .with_child(Dom::div().with_class("item"))
+        .with_child(Dom::div().with_class("item").with_child(Dom::label("")));
+
+    let node_hierarchy = &dom.arena.node_layout;
+    let nodes_sorted = node_hierarchy.get_parents_sorted_by_depth();
+    let html_node_tree = construct_html_cascade_tree(
+        &dom.arena.node_data,
+        &node_hierarchy,
+        &nodes_sorted,
+        None,
+        &BTreeMap::new(),
+        false,
+    );
+
+    let item = || Class("item".into());
+    let open = || Attribute(CssAttributeSelector { name: "data-state".into(), condition: CssAttributeCondition::Equals("open".into()) });
+    let has_state = || Attribute(CssAttributeSelector { name: "data-state".into(), condition: CssAttributeCondition::Exists });
+
+    let matching_nodes = |selectors: Vec<CssPathSelector>| {
+        let css_path = CssPath { selectors };
+        html_node_tree.linear_iter()
+            .filter(|node_id| matches_html_element(&css_path, *node_id, &node_hierarchy, &html_node_tree))
+            .map(|node_id| node_id.index())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(matching_nodes(vec![item(), AdjacentSibling, item()]), vec![2, 3]);
+    assert_eq!(matching_nodes(vec![open(), AdjacentSibling, item()]), vec![2]);
+    assert_eq!(matching_nodes(vec![open(), GeneralSibling, item()]), vec![2, 3]);
+    assert_eq!(matching_nodes(vec![item(), open()]), vec![1]);
+    assert_eq!(matching_nodes(vec![item(), PseudoSelector(Not(vec![has_state()]))]), vec![2, 3]);
+    assert_eq!(matching_nodes(vec![item(), PseudoSelector(NthChild(CssNthChildSelector::Even))]), vec![2]);
+    assert_eq!(matching_nodes(vec![item(), PseudoSelector(NthLastChild(CssNthChildSelector::Number(1)))]), vec![3]);
+    assert_eq!(matching_nodes(vec![item(), PseudoSelector(Empty)]), vec![1, 2]);
+    assert_eq!(matching_nodes(vec![Type(NodeTypePath::P), PseudoSelector(OnlyChild)]), vec![4]);
+    // The label is inside of the third `.item`, which follows the `[data-state]` node, but not directly
+    assert_eq!(matching_nodes(vec![open(), GeneralSibling, item(), Children, Type(NodeTypePath::P)]), vec![4]);
+    assert_eq!(matching_nodes(vec![open(), AdjacentSibling, item(), Children, Type(NodeTypePath::P)]), Vec::<usize>::new());
+}
+
 #[test]
 fn test_css_group_iterator() {
 
@@ -617,4 +1055,89 @@ fn test_css_group_iterator() {
     ], CssGroupSplitReason::Children)));
 
     assert_eq!(it.next(), None);
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "css_parser")]
+#[test]
+fn test_css_variables_cascade() {
+
+    use azul_css::{CssDeclaration, StyleTextColor, ColorU};
+    use prelude::*;
+
+    struct DataModel { }
+    impl Layout for DataModel { fn layout(&self) -> Dom<DataModel> { Dom::div() } }
+
+    let css = ::azul_css_parser::new_from_str("
+        .root { --main-color: #ff0000; --border-color: var(--main-color); }
+        .label { color: var(--main-color, #000000); }
+        .undefined { color: var(--other-color, #0000ff); }
+        .border-label { color: var(--border-color); }
+    ").unwrap();
+
+    //  0: [div .root]
+    //   |-- 1: [p .label]
+    //   |-- 2: [div] (--main-color: #00ff00)
+    //   |    |-- 3: [p .label]
+    //   |    |-- 4: [p .undefined]
+    //   |    |-- 5: [p .border-label]
+    let dom: Dom<DataModel> = Dom::div().with_class("root")
+        .with_child(Dom::label("").with_class("label"))
+        .with_child(Dom::div().with_css_variable("--main-color", "#00ff00")
+            .with_child(Dom::label("").with_class("label"))
+            .with_child(Dom::label("").with_class("undefined"))
+            .with_child(Dom::label("").with_class("border-label"))
+        );
+
+    let ui_state = dom.into_ui_state();
+    let css = ResolvedCss::new(css);
+    let ui_description = match_dom_selectors(&ui_state, &css, None, &mut None, &mut None, &BTreeMap::new(), false, None);
+
+    let text_color = |node_id: usize| ui_description.styled_nodes[NodeId::new(node_id)].css_constraints.get(&CssPropertyType::TextColor).cloned();
+    let color = |r, g, b| Some(CssDeclaration::Static(CssProperty::TextColor(StyleTextColor(ColorU { r, g, b, a: 255 }))));
+
+    assert_eq!(text_color(1), color(255, 0, 0));
+    // Variables set in Rust override the inherited variables for the whole subtree
+    assert_eq!(text_color(3), color(0, 255, 0));
+    assert_eq!(text_color(4), color(0, 0, 255));
+    // --border-color is resolved on the root node, so it still refers to the red color
+    assert_eq!(text_color(5), color(255, 0, 0));
+}
+
+#[cfg(feature = "css_parser")]
+#[test]
+fn test_changed_css_variable_restyles_subtree() {
+
+    use azul_css::{CssDeclaration, StyleTextColor, ColorU};
+    use prelude::*;
+
+    struct DataModel { }
+    impl Layout for DataModel { fn layout(&self) -> Dom<DataModel> { Dom::div() } }
+
+    fn dom(main_color: &str) -> Dom<DataModel> {
+        Dom::div().with_css_variable("--main-color", main_color.to_string())
+            .with_child(Dom::label("").with_class("label"))
+    }
+
+    let css = ResolvedCss::new(::azul_css_parser::new_from_str(".label { color: var(--main-color); }").unwrap());
+
+    let first_ui_state = dom("#ff0000").into_ui_state();
+    let first_frame = match_dom_selectors(&first_ui_state, &css, None, &mut None, &mut None, &BTreeMap::new(), false, None);
+
+    let second_ui_state = dom("#00ff00").into_ui_state();
+    let dom_diff = DomDiff::from_arenas(
+        &first_ui_state.dom.arena, first_ui_state.dom.root,
+        &second_ui_state.dom.arena, second_ui_state.dom.root,
+    );
+
+    // The node is still the same, but it inherits a different variable
+    assert_eq!(dom_diff.get_old_node_id(NodeId::new(1)), Some(NodeId::new(1)));
+    assert!(dom_diff.needs_restyle(NodeId::new(1)));
+
+    let second_frame = match_dom_selectors(&second_ui_state, &css, Some((&first_frame, &dom_diff)), &mut None, &mut None, &BTreeMap::new(), false, None);
+
+    let text_color = |ui_description: &UiDescription<DataModel>| ui_description.styled_nodes[NodeId::new(1)].css_constraints.get(&CssPropertyType::TextColor).cloned();
+    let color = |r, g, b| Some(CssDeclaration::Static(CssProperty::TextColor(StyleTextColor(ColorU { r, g, b, a: 255 }))));
+
+    assert_eq!(text_color(&first_frame), color(255, 0, 0));
+    assert_eq!(text_color(&second_frame), color(0, 255, 0));
+}
@@ -1,7 +1,7 @@
 #![allow(unused_variables, dead_code)]
 
 use azul_css::{
-    StyleTextAlignmentHorz, StyleTextAlignmentVert, ScrollbarInfo,
+    StyleTextAlignmentHorz, StyleTextAlignmentVert, StyleTextDirection, ScrollbarInfo,
 };
 pub use webrender::api::{
     GlyphInstance, LayoutSize, LayoutRect, LayoutPoint,
@@ -81,6 +81,11 @@ pub struct ScaledWords {
     pub space_advance_px: f32,
     /// Glyph index of the space character
     pub space_codepoint: u32,
+    /// Base direction of the paragraph that was used to determine the `word_bidi_levels`
+    pub direction: StyleTextDirection,
+    /// Bidi embedding level of each word (same indices as `items`): even levels are
+    /// left-to-right, odd levels are right-to-left, see `get_bidi_levels`
+    pub word_bidi_levels: Vec<u8>,
 }
 
 /// Word that is scaled (to a font / font instance), but not yet positioned
@@ -93,6 +98,9 @@ pub struct ScaledWord {
     pub glyph_positions: Vec<GlyphPosition>,
     /// The sum of the width of all the characters in this word
     pub word_width: f32,
+    /// The font that each glyph was shaped with: `0` is the primary font, `1..` are the
+    /// fallback fonts, in the order in which they were passed to `words_to_scaled_words`
+    pub glyph_fonts: Vec<usize>,
 }
 
 /// Stores the positions of the vertically laid out texts
@@ -181,6 +189,21 @@ pub struct LeftAlignedGlyphs<'a> {
 #[derive(Debug, Clone, PartialEq)]
 pub struct LayoutedGlyphs {
     pub glyphs: Vec<GlyphInstance>,
+    /// The font of each glyph, see `ScaledWord::glyph_fonts`
+    pub glyph_fonts: Vec<usize>,
+}
+
+impl LayoutedGlyphs {
+    /// Groups the glyphs by their font, since every font has to be pushed as a separate
+    /// text item - returns the font index and the glyphs of every font that is used, in
+    /// order of the font index
+    pub fn group_by_font(&self) -> Vec<(usize, Vec<GlyphInstance>)> {
+        let mut groups = ::std::collections::BTreeMap::<usize, Vec<GlyphInstance>>::new();
+        for (glyph, font) in self.glyphs.iter().zip(self.glyph_fonts.iter()) {
+            groups.entry(*font).or_insert_with(Vec::new).push(*glyph);
+        }
+        groups.into_iter().collect()
+    }
 }
 
 /// Whether the text overflows the parent rectangle, and if yes, by how many pixels,
@@ -375,19 +398,93 @@ pub fn split_text_into_words(text: &str) -> Words {
     }
 }
 
+/// Returns the bidi embedding level of every item in `words.items`, as determined by the
+/// Unicode Bidirectional Algorithm for a paragraph with the base `direction`: even levels are
+/// left-to-right, odd levels are right-to-left. Words that mix both directions (without a
+/// space in between) take the level of their first character.
+pub fn get_bidi_levels(words: &Words, direction: StyleTextDirection) -> Vec<u8> {
+
+    use unicode_bidi::{BidiInfo, Level};
+
+    let paragraph_level = match direction {
+        StyleTextDirection::Ltr => Level::ltr(),
+        StyleTextDirection::Rtl => Level::rtl(),
+    };
+
+    // The levels are stored per byte, the words store character indices
+    let bidi_info = BidiInfo::new(&words.internal_str, Some(paragraph_level));
+    let char_byte_offsets = words.internal_str.char_indices().map(|(byte_idx, _)| byte_idx).collect::<Vec<_>>();
+
+    words.items.iter().map(|word| {
+        char_byte_offsets.get(word.start)
+            .and_then(|byte_idx| bidi_info.levels.get(*byte_idx))
+            .unwrap_or(&paragraph_level)
+            .number()
+    }).collect()
+}
+
+/// Returns the visual order of items with the given bidi levels (rule L2 of the Unicode
+/// Bidirectional Algorithm): from the highest level down to the lowest odd level, every
+/// sequence of items at that level or higher is reversed.
+///
+/// `visual_order[0]` is the index of the leftmost item.
+pub fn get_visual_order(levels: &[u8]) -> Vec<usize> {
+
+    let mut visual_order = (0..levels.len()).collect::<Vec<_>>();
+
+    let max_level = match levels.iter().max() {
+        Some(s) => *s,
+        None => return visual_order,
+    };
+    let min_level = levels.iter().min().cloned().unwrap_or(0);
+    let lowest_odd_level = if min_level % 2 == 1 { min_level } else { min_level + 1 };
+
+    for level in (lowest_odd_level..=max_level).rev() {
+        let mut idx = 0;
+        while idx < visual_order.len() {
+            if levels[visual_order[idx]] < level {
+                idx += 1;
+                continue;
+            }
+            let sequence_start = idx;
+            while idx < visual_order.len() && levels[visual_order[idx]] >= level {
+                idx += 1;
+            }
+            visual_order[sequence_start..idx].reverse();
+        }
+    }
+
+    visual_order
+}
+
 /// Takes a text broken into semantic items and a font instance and
 /// scales the font accordingly.
+///
+/// Characters that the font doesn't contain (i.e. emoji or CJK characters in a latin font)
+/// are shaped with the first of the `fallback_fonts` that contains them.
+///
+/// The text is shaped in runs of the same direction (see `get_bidi_levels`), so the glyphs
+/// of right-to-left words are in visual order, but the words themselves stay in logical
+/// order - they are reordered per line in `position_words`.
 pub fn words_to_scaled_words(
     words: &Words,
     font_bytes: &[u8],
     font_index: u32,
+    fallback_fonts: &[(&[u8], u32)],
+    direction: StyleTextDirection,
     font_size_px: f32,
 ) -> ScaledWords {
 
     use text_shaping::{self, HbBuffer, HbFont, HbScaledFont};
 
-    let hb_font = HbFont::from_bytes(font_bytes, font_index);
-    let hb_scaled_font = HbScaledFont::from_font(&hb_font, font_size_px);
+    let hb_fonts = ::std::iter::once((font_bytes, font_index))
+        .chain(fallback_fonts.iter().cloned())
+        .map(|(font_bytes, font_index)| HbFont::from_bytes(font_bytes, font_index))
+        .collect::<Vec<_>>();
+    let hb_scaled_fonts = hb_fonts.iter()
+        .map(|hb_font| HbScaledFont::from_font(hb_font, font_size_px))
+        .collect::<Vec<_>>();
+    let hb_scaled_font = &hb_scaled_fonts[0];
 
     // Get the dimensions of the space glyph
     let hb_space_buffer = HbBuffer::from_str(" ");
@@ -395,33 +492,56 @@ pub fn words_to_scaled_words(
     let space_advance_px = hb_shaped_space.glyph_positions[0].x_advance as f32 / 128.0; // TODO: Half width for spaces?
     let space_codepoint = hb_shaped_space.glyph_infos[0].codepoint;
 
-    let hb_buffer_entire_paragraph = HbBuffer::from_str(&words.internal_str);
-    let hb_shaped_entire_paragraph = text_shaping::shape_word_hb(&hb_buffer_entire_paragraph, &hb_scaled_font);
+    let bidi_levels = get_bidi_levels(words, direction);
 
-    let mut shaped_word_positions = Vec::new();
-    let mut shaped_word_infos = Vec::new();
-    let mut current_word_positions = Vec::new();
-    let mut current_word_infos = Vec::new();
+    // The glyph clusters are byte offsets into the text, so the glyphs are assigned to
+    // the words via the byte ranges of the words - whitespace glyphs are not part of any word
+    let char_byte_offsets = words.internal_str.char_indices()
+        .map(|(byte_idx, _)| byte_idx)
+        .chain(::std::iter::once(words.internal_str.len()))
+        .collect::<Vec<_>>();
+    let get_byte_offset = |char_idx: usize| char_byte_offsets.get(char_idx).cloned().unwrap_or(words.internal_str.len());
 
-    for i in 0..hb_shaped_entire_paragraph.glyph_positions.len() {
-        let glyph_info = hb_shaped_entire_paragraph.glyph_infos[i];
-        let glyph_position = hb_shaped_entire_paragraph.glyph_positions[i];
+    let mut word_of_byte = vec![None; words.internal_str.len()];
+    let mut word_bidi_levels = Vec::new();
 
-        let is_space = glyph_info.codepoint == space_codepoint;
-        if is_space {
-            shaped_word_positions.push(current_word_positions.clone());
-            shaped_word_infos.push(current_word_infos.clone());
-            current_word_positions.clear();
-            current_word_infos.clear();
-        } else {
-            current_word_positions.push(glyph_position);
-            current_word_infos.push(glyph_info);
+    for (word_idx, (word, level)) in words.items.iter().zip(bidi_levels.iter())
+        .filter(|(w, _)| w.word_type == WordType::Word)
+        .enumerate() {
+        for byte_word in &mut word_of_byte[get_byte_offset(word.start)..get_byte_offset(word.end)] {
+            *byte_word = Some(word_idx);
         }
+        word_bidi_levels.push(*level);
     }
 
-    if !current_word_positions.is_empty() {
-        shaped_word_positions.push(current_word_positions);
-        shaped_word_infos.push(current_word_infos);
+    let mut shaped_word_positions = vec![Vec::new(); word_bidi_levels.len()];
+    let mut shaped_word_infos = vec![Vec::new(); word_bidi_levels.len()];
+    let mut shaped_word_fonts = vec![Vec::new(); word_bidi_levels.len()];
+
+    // Shape runs of items that have the same direction
+    let mut run_start = 0;
+    while run_start < words.items.len() {
+
+        let run_is_rtl = bidi_levels[run_start] % 2 == 1;
+        let mut run_end = run_start + 1;
+        while run_end < words.items.len() && (bidi_levels[run_end] % 2 == 1) == run_is_rtl {
+            run_end += 1;
+        }
+
+        let run_byte_start = get_byte_offset(words.items[run_start].start);
+        let run_byte_end = get_byte_offset(words.items[run_end - 1].end);
+        let run_direction = if run_is_rtl { StyleTextDirection::Rtl } else { StyleTextDirection::Ltr };
+
+        for mut glyph in text_shaping::shape_text_with_fallback(&words.internal_str[run_byte_start..run_byte_end], &hb_scaled_fonts, run_direction) {
+            glyph.info.cluster += run_byte_start as u32;
+            if let Some(Some(word_idx)) = word_of_byte.get(glyph.info.cluster as usize) {
+                shaped_word_positions[*word_idx].push(glyph.position);
+                shaped_word_infos[*word_idx].push(glyph.info);
+                shaped_word_fonts[*word_idx].push(glyph.font);
+            }
+        }
+
+        run_start = run_end;
     }
 
     let mut longest_word_width = 0.0_f32;
@@ -433,6 +553,7 @@ pub fn words_to_scaled_words(
 
             let hb_glyph_positions = shaped_word_positions.get(word_idx)?;
             let hb_glyph_infos = shaped_word_infos.get(word_idx)?;
+            let glyph_fonts = shaped_word_fonts.get(word_idx)?.clone();
 
             let hb_word_width = text_shaping::get_word_visual_width_hb(&hb_glyph_positions);
             let hb_glyph_positions = text_shaping::get_glyph_positions_hb(&hb_glyph_positions);
@@ -444,6 +565,7 @@ pub fn words_to_scaled_words(
                 glyph_infos: hb_glyph_infos,
                 glyph_positions: hb_glyph_positions,
                 word_width: hb_word_width,
+                glyph_fonts,
             })
         }).collect();
 
@@ -453,9 +575,23 @@ pub fn words_to_scaled_words(
         space_advance_px,
         space_codepoint,
         font_size_px,
+        direction,
+        word_bidi_levels,
     }
 }
 
+/// Returns the `min-content` width of a text: the width of its longest word (including the
+/// letter spacing), since the text can't get any narrower without overflowing
+pub fn get_min_content_width(scaled_words: &ScaledWords, text_layout_options: &TextLayoutOptions) -> f32 {
+    scaled_words.items.iter().map(|scaled_word| {
+        let reserved_letter_spacing_px = match text_layout_options.letter_spacing {
+            None => 0.0,
+            Some(spacing_multiplier) => spacing_multiplier * scaled_word.number_of_clusters().saturating_sub(1) as f32,
+        };
+        scaled_word.word_width.abs() + reserved_letter_spacing_px
+    }).fold(0.0, f32::max)
+}
+
 /// Positions the words on the screen (does not layout any glyph positions!), necessary for estimating
 /// the intrinsic width + height of the text content.
 pub fn position_words(
@@ -610,6 +746,8 @@ pub fn position_words(
         line_breaks.push((current_word_idx, line_caret_x));
     }
 
+    reorder_words_visually(&mut word_positions, &line_breaks, scaled_words, text_layout_options);
+
     let trailing = line_caret_x;
     let number_of_lines = line_number + 1;
     let number_of_words = current_word_idx + 1;
@@ -631,6 +769,68 @@ pub fn position_words(
     }
 }
 
+/// Moves the words of every line from their logical to their visual position, for lines that
+/// contain right-to-left words (see `get_visual_order`). The words are positioned in logical
+/// order by `position_words`, so the line starts at the position of its first logical word.
+///
+/// The gaps between the words are reordered together with the words - a gap takes the lower
+/// level of the two words around it, so spaces between two right-to-left words are also right-to-left.
+fn reorder_words_visually(
+    word_positions: &mut [LayoutPoint],
+    line_breaks: &[(WordIndex, LineLength)],
+    scaled_words: &ScaledWords,
+    text_layout_options: &TextLayoutOptions,
+) {
+    let levels = &scaled_words.word_bidi_levels;
+
+    if levels.iter().all(|level| *level == 0) {
+        return;
+    }
+
+    let get_word_advance = |word_idx: usize| {
+        let scaled_word = &scaled_words.items[word_idx];
+        let reserved_letter_spacing_px = match text_layout_options.letter_spacing {
+            None => 0.0,
+            Some(spacing_multiplier) => spacing_multiplier * scaled_word.number_of_clusters().saturating_sub(1) as f32,
+        };
+        scaled_word.word_width + reserved_letter_spacing_px
+    };
+
+    let mut line_start = 0;
+
+    for (line_end, _) in line_breaks {
+
+        let line_end = (*line_end).min(word_positions.len()).min(scaled_words.items.len());
+        if line_end <= line_start {
+            continue;
+        }
+
+        // (bidi level, advance, word index or None for the gap after a word)
+        let mut line_items = Vec::new();
+        for word_idx in line_start..line_end {
+            let advance = get_word_advance(word_idx);
+            line_items.push((levels[word_idx], advance, Some(word_idx)));
+            if word_idx + 1 < line_end {
+                let gap = word_positions[word_idx + 1].x - (word_positions[word_idx].x + advance);
+                line_items.push((levels[word_idx].min(levels[word_idx + 1]), gap, None));
+            }
+        }
+
+        let item_levels = line_items.iter().map(|(level, _, _)| *level).collect::<Vec<_>>();
+        let mut caret_x = word_positions[line_start].x;
+
+        for item_idx in get_visual_order(&item_levels) {
+            let (_, advance, word_idx) = line_items[item_idx];
+            if let Some(word_idx) = word_idx {
+                word_positions[word_idx].x = caret_x;
+            }
+            caret_x += advance;
+        }
+
+        line_start = line_end;
+    }
+}
+
 pub fn get_layouted_glyphs_unpositioned(
     word_positions: &WordPositions,
     scaled_words: &ScaledWords,
@@ -639,6 +839,7 @@ pub fn get_layouted_glyphs_unpositioned(
     use text_shaping;
 
     let mut glyphs = Vec::with_capacity(scaled_words.items.len());
+    let mut glyph_fonts = Vec::with_capacity(scaled_words.items.len());
 
     let letter_spacing_px = word_positions.text_layout_options.letter_spacing.unwrap_or(0.0);
 
@@ -654,10 +855,11 @@ pub fn get_layouted_glyphs_unpositioned(
                 glyph.point.x += letter_spacing_px * cluster_info.cluster_idx as f32;
                 glyph
             })
-        )
+        );
+        glyph_fonts.extend(scaled_word.glyph_fonts.iter().cloned());
     }
 
-    LayoutedGlyphs { glyphs }
+    LayoutedGlyphs { glyphs, glyph_fonts }
 }
 
 pub fn get_layouted_glyphs_with_horizonal_alignment(
@@ -1208,3 +1410,24 @@ fn test_caret_intersects_with_holes_4() {
 
     assert_eq!(result, LineCaretIntersection::NoIntersection);
 }
+
+#[test]
+fn test_get_bidi_levels() {
+    // "abc" "שלום" "עולם" "def", separated by spaces
+    let words = split_text_into_words("abc שלום עולם def");
+
+    assert_eq!(get_bidi_levels(&words, StyleTextDirection::Ltr), vec![0, 0, 1, 1, 1, 0, 0]);
+    assert_eq!(get_bidi_levels(&words, StyleTextDirection::Rtl), vec![2, 1, 1, 1, 1, 1, 2]);
+}
+
+#[test]
+fn test_get_visual_order() {
+    assert_eq!(get_visual_order(&[]), Vec::<usize>::new());
+    assert_eq!(get_visual_order(&[0, 0, 0]), vec![0, 1, 2]);
+    assert_eq!(get_visual_order(&[1, 1, 1]), vec![2, 1, 0]);
+    // Right-to-left words in a left-to-right paragraph
+    assert_eq!(get_visual_order(&[0, 0, 1, 1, 1, 0, 0]), vec![0, 1, 4, 3, 2, 5, 6]);
+    // Left-to-right words in a right-to-left paragraph keep their order
+    assert_eq!(get_visual_order(&[2, 1, 2, 2, 2]), vec![2, 3, 4, 1, 0]);
+    assert_eq!(get_visual_order(&[2, 2, 2]), vec![0, 1, 2]);
+}
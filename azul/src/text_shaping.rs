@@ -3,6 +3,7 @@
 
 use std::{slice, ptr, u32, ops::Deref, os::raw::{c_char, c_uint}};
 use webrender::api::{LayoutPoint, GlyphInstance as WrGlyphInstance};
+use azul_css::StyleTextDirection;
 use harfbuzz_sys::{
     hb_blob_create, hb_blob_destroy,
     hb_font_create, hb_font_destroy,
@@ -11,10 +12,11 @@ use harfbuzz_sys::{
     hb_shape, hb_font_set_scale, hb_buffer_add_utf8,
     hb_buffer_get_glyph_infos, hb_buffer_get_glyph_positions,
     hb_buffer_guess_segment_properties, hb_buffer_allocation_successful,
+    hb_buffer_set_direction,
     hb_blob_t, hb_memory_mode_t, hb_buffer_t,
     hb_glyph_position_t, hb_glyph_info_t, hb_font_t, hb_face_t,
     hb_feature_t, hb_tag_t,
-    HB_MEMORY_MODE_READONLY,
+    HB_MEMORY_MODE_READONLY, HB_DIRECTION_LTR, HB_DIRECTION_RTL,
 };
 
 // Translates to the ".codepoint" in HarfBuzz
@@ -25,6 +27,9 @@ pub type GlyphPosition = hb_glyph_position_t;
 const MEMORY_MODE_READONLY: hb_memory_mode_t = HB_MEMORY_MODE_READONLY;
 const HB_SCALE_FACTOR: f32 = 128.0;
 
+/// Glyph index of the `.notdef` glyph, which fonts use for the characters they don't contain
+pub(crate) const NOTDEF_GLYPH: GlyphIndex = 0;
+
 // NOTE: hb_tag_t = u32
 // See: https://github.com/tangrams/harfbuzz-example/blob/master/src/hbshaper.h
 //
@@ -155,6 +160,18 @@ impl<'a> HbBuffer<'a> {
             hb_buffer,
         }
     }
+
+    /// Same as `from_str`, but shapes the text in the given direction instead of guessing it -
+    /// right-to-left text returns the glyphs in visual order, so the clusters are decreasing
+    pub fn from_str_with_direction(words: &'a str, direction: StyleTextDirection) -> Self {
+        let buffer = Self::from_str(words);
+        let hb_direction = match direction {
+            StyleTextDirection::Ltr => HB_DIRECTION_LTR,
+            StyleTextDirection::Rtl => HB_DIRECTION_RTL,
+        };
+        unsafe { hb_buffer_set_direction(buffer.hb_buffer, hb_direction) };
+        buffer
+    }
 }
 
 impl<'a> Drop for HbBuffer<'a> {
@@ -232,6 +249,91 @@ pub(crate) fn shape_word_hb<'a>(
     }
 }
 
+/// Returns whether the font lacks glyphs for any of the characters of the text
+pub(crate) fn has_missing_glyphs(scaled_font: &HbScaledFont, text: &str) -> bool {
+    let buffer = HbBuffer::from_str(text);
+    let shaped = shape_word_hb(&buffer, scaled_font);
+    shaped.glyph_infos.iter().any(|info| info.codepoint == NOTDEF_GLYPH)
+}
+
+/// Glyph that was shaped by one of the fonts of a fallback chain
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct FallbackGlyph {
+    pub info: GlyphInfo,
+    pub position: GlyphPosition,
+    /// Index of the font that shaped the glyph, `0` is the primary font
+    pub font: usize,
+}
+
+/// Shapes the text with the first font of `scaled_fonts`. Runs of characters that the font has
+/// no glyphs for (`.notdef` glyphs) are shaped again with the next font, and so on. Characters
+/// that no font contains keep the `.notdef` glyph of the last font.
+///
+/// The clusters of the returned glyphs are byte offsets into `text`, like regular HarfBuzz clusters.
+/// The glyphs are returned in visual order, i.e. reversed if the `direction` is right-to-left.
+pub(crate) fn shape_text_with_fallback(text: &str, scaled_fonts: &[HbScaledFont], direction: StyleTextDirection) -> Vec<FallbackGlyph> {
+    let mut glyphs = Vec::new();
+    if !scaled_fonts.is_empty() {
+        shape_run_with_fallback(text, 0, scaled_fonts, 0, direction, &mut glyphs);
+    }
+    glyphs
+}
+
+fn shape_run_with_fallback(
+    text: &str,
+    cluster_offset: u32,
+    scaled_fonts: &[HbScaledFont],
+    font: usize,
+    direction: StyleTextDirection,
+    glyphs: &mut Vec<FallbackGlyph>,
+) {
+    let buffer = HbBuffer::from_str_with_direction(text, direction);
+    let shaped = shape_word_hb(&buffer, &scaled_fonts[font]);
+    let has_fallback = font + 1 < scaled_fonts.len();
+
+    let mut glyph_idx = 0;
+
+    while glyph_idx < shaped.glyph_infos.len() {
+
+        if !has_fallback || shaped.glyph_infos[glyph_idx].codepoint != NOTDEF_GLYPH {
+            let mut info = shaped.glyph_infos[glyph_idx];
+            info.cluster += cluster_offset;
+            glyphs.push(FallbackGlyph { info, position: shaped.glyph_positions[glyph_idx], font });
+            glyph_idx += 1;
+            continue;
+        }
+
+        let run_start = glyph_idx;
+        while glyph_idx < shaped.glyph_infos.len() && shaped.glyph_infos[glyph_idx].codepoint == NOTDEF_GLYPH {
+            glyph_idx += 1;
+        }
+
+        // Clusters are byte offsets into the text, the run ends where the next cluster starts -
+        // in right-to-left text, the next cluster is the glyph before the run
+        let (start_byte, end_byte) = match direction {
+            StyleTextDirection::Ltr => (
+                shaped.glyph_infos[run_start].cluster as usize,
+                shaped.glyph_infos.get(glyph_idx).map(|info| info.cluster as usize).unwrap_or(text.len()),
+            ),
+            StyleTextDirection::Rtl => (
+                shaped.glyph_infos[glyph_idx - 1].cluster as usize,
+                run_start.checked_sub(1).map(|idx| shaped.glyph_infos[idx].cluster as usize).unwrap_or(text.len()),
+            ),
+        };
+
+        if start_byte < end_byte && text.is_char_boundary(start_byte) && text.is_char_boundary(end_byte) {
+            shape_run_with_fallback(&text[start_byte..end_byte], cluster_offset + start_byte as u32, scaled_fonts, font + 1, direction, glyphs);
+        } else {
+            // The clusters of the run are not in logical order, keep the .notdef glyphs
+            for missing_idx in run_start..glyph_idx {
+                let mut info = shaped.glyph_infos[missing_idx];
+                info.cluster += cluster_offset;
+                glyphs.push(FallbackGlyph { info, position: shaped.glyph_positions[missing_idx], font });
+            }
+        }
+    }
+}
+
 pub(crate) fn get_word_visual_width_hb(glyph_positions: &[GlyphPosition]) -> f32 {
     glyph_positions.iter().map(|pos| pos.x_advance as f32 / HB_SCALE_FACTOR).sum()
 }
@@ -2,7 +2,7 @@ use std::{
     fmt,
     collections::BTreeMap,
 };
-use azul_css::{ Css, CssDeclaration, CssProperty, CssPropertyType };
+use azul_css::{ CssDeclaration, CssProperty, CssPropertyType, CssTransition, CssAnimation };
 use webrender::api::HitTestItem;
 use {
     FastHashMap,
@@ -10,8 +10,10 @@ use {
     traits::Layout,
     dom::{Dom, NodeData, DomString},
     ui_state::UiState,
-    style::HoverGroup,
+    style::{HoverGroup, PseudoClassState},
     callbacks::FocusTarget,
+    diff::DomDiff,
+    window::ResolvedCss,
 };
 
 pub struct UiDescription<T: Layout> {
@@ -26,6 +28,16 @@ pub struct UiDescription<T: Layout> {
     /// that have a non-:hover path, for example if we have `#thing:hover`, then all nodes selected by `#thing`
     /// need to get a TagId, otherwise, they can't be hit-tested.
     pub(crate) selected_hover_nodes: BTreeMap<NodeId, HoverGroup>,
+    /// The `:hover`, `:focus` and `:active` state of each node when the styles were matched
+    pub(crate) pseudo_class_states: NodeDataContainer<PseudoClassState>,
+    /// The stylesheet that was used to style the nodes - if its `CssKey` changes,
+    /// the styles from the previous frame can't be re-used
+    pub(crate) css: ResolvedCss,
+    /// Difference between the DOM of the previous `UiDescription` and this one,
+    /// `None` if there was no previous frame
+    pub(crate) dom_diff: Option<DomDiff>,
+    /// The current values of the transitioned and animated properties, override the `styled_nodes`
+    pub(crate) animated_properties: BTreeMap<NodeId, BTreeMap<CssPropertyType, CssProperty>>,
 }
 
 impl<T: Layout> fmt::Debug for UiDescription<T> {
@@ -36,12 +48,20 @@ impl<T: Layout> fmt::Debug for UiDescription<T> {
             styled_nodes: {:?},
             dynamic_css_overrides: {:?},
             selected_hover_nodes: {:?},
+            pseudo_class_states: {:?},
+            css: {:?},
+            dom_diff: {:?},
+            animated_properties: {:?},
         }}",
             self.ui_descr_arena,
             self.ui_descr_root,
             self.styled_nodes,
             self.dynamic_css_overrides,
             self.selected_hover_nodes,
+            self.pseudo_class_states,
+            self.css,
+            self.dom_diff,
+            self.animated_properties,
         )
     }
 }
@@ -54,6 +74,10 @@ impl<T: Layout> Clone for UiDescription<T> {
             styled_nodes: self.styled_nodes.clone(),
             dynamic_css_overrides: self.dynamic_css_overrides.clone(),
             selected_hover_nodes: self.selected_hover_nodes.clone(),
+            pseudo_class_states: self.pseudo_class_states.clone(),
+            css: self.css.clone(),
+            dom_diff: self.dom_diff.clone(),
+            animated_properties: self.animated_properties.clone(),
         }
     }
 }
@@ -68,11 +92,13 @@ impl<T: Layout> Default for UiDescription<T> {
         let mut focus_target = None;
         Self::match_css_to_dom(
             &mut default_dom.into_ui_state(),
-            &Css::default(),
+            &ResolvedCss::default(),
+            None,
             &mut focused_node,
             &mut focus_target,
             &hovered_nodes,
             is_mouse_down,
+            None,
         )
     }
 }
@@ -81,23 +107,51 @@ impl<T: Layout> UiDescription<T> {
     /// Applies the styles to the nodes calculated from the `layout_screen`
     /// function and calculates the final display list that is submitted to the
     /// renderer.
+    ///
+    /// If the `UiDescription` of the previous frame is given, the new DOM is diffed
+    /// against the previous DOM and only the nodes that changed are restyled. The
+    /// diff is stored in the returned `UiDescription`, so that the layout step
+    /// can re-use the text layout of unchanged nodes, too.
+    ///
+    /// `dragged_node` is the node that is currently dragged (for `:dragging` and `:drop-target`).
     pub fn match_css_to_dom(
         ui_state: &mut UiState<T>,
-        style: &Css,
+        style: &ResolvedCss,
+        previous_ui_description: Option<&UiDescription<T>>,
         focused_node: &mut Option<NodeId>,
         pending_focus_target: &mut Option<FocusTarget>,
         hovered_nodes: &BTreeMap<NodeId, HitTestItem>,
         is_mouse_down: bool,
+        dragged_node: Option<NodeId>,
     ) -> Self
     {
-        let ui_description = ::style::match_dom_selectors(
-            ui_state,
-            &style,
-            focused_node,
-            pending_focus_target,
-            hovered_nodes,
-            is_mouse_down
-        );
+        let dom_diff = previous_ui_description.map(|previous| DomDiff::from_arenas(
+            &previous.ui_descr_arena,
+            previous.ui_descr_root,
+            &ui_state.dom.arena,
+            ui_state.dom.root,
+        ));
+
+        let mut ui_description = {
+            // The styles of the previous frame can only be re-used if the stylesheet didn't change
+            let previous_frame = match (previous_ui_description, dom_diff.as_ref()) {
+                (Some(previous), Some(diff)) if previous.css.key == style.key => Some((previous, diff)),
+                _ => None,
+            };
+
+            ::style::match_dom_selectors(
+                ui_state,
+                &style,
+                previous_frame,
+                focused_node,
+                pending_focus_target,
+                hovered_nodes,
+                is_mouse_down,
+                dragged_node,
+            )
+        };
+
+        ui_description.dom_diff = dom_diff;
 
         // Important: Create all the tags for the :hover and :active selectors
         ui_state.create_tags_for_hover_nodes(&ui_description.selected_hover_nodes);
@@ -109,4 +163,12 @@ impl<T: Layout> UiDescription<T> {
 pub(crate) struct StyledNode {
     /// The CSS constraints, after the cascading step
     pub(crate) css_constraints: BTreeMap<CssPropertyType, CssDeclaration>,
+    /// The CSS variables (`--main-color: red`) of this node, including the
+    /// variables inherited from the parent nodes - the `var()` references in the
+    /// `css_constraints` are already substituted
+    pub(crate) css_variables: BTreeMap<String, String>,
+    /// The `transition` property of this node (not inherited)
+    pub(crate) transitions: Vec<CssTransition>,
+    /// The `animation` property of this node (not inherited)
+    pub(crate) animations: Vec<CssAnimation>,
 }
@@ -1,23 +1,30 @@
-use std::{f32, collections::BTreeMap};
+use std::{f32, mem, collections::BTreeMap};
 use azul_css::{
-    LayoutPosition, LayoutMargin, LayoutPadding,
-    RectLayout, StyleFontSize, RectStyle,
-    StyleTextAlignmentHorz, StyleTextAlignmentVert, PixelValue,
+    LayoutPosition, LayoutMargin, LayoutPadding, LayoutAlignItems, LayoutAlignSelf,
+    LayoutAlignContent, LayoutJustifyContent,
+    RectLayout, StyleFontSize, StyleFontWeight, StyleFontStyle, RectStyle,
+    StyleTextAlignmentHorz, StyleTextAlignmentVert, StyleTextDirection, PixelValue,
+    RelativeSizeContext, CalcPixelValue, CssPropertyType, LayoutDisplay, LayoutGridPlacement, GridTrackSize,
+    LayoutBoxSizing, LayoutWidth, LayoutHeight, LayoutMinWidth, LayoutMinHeight,
+    LayoutMaxWidth, LayoutMaxHeight, StyleBorderSide, BorderStyle, LayoutIntrinsicWidth, Overflow,
 };
 use app_units::Au;
 use {
     id_tree::{NodeId, NodeDataContainer, NodeHierarchy},
     display_list::DisplayRectangle,
     dom::{NodeData, NodeType},
-    app_resources::AppResources,
+    app_resources::{AppResources, ImmediateFontId},
     text_layout::{Words, ScaledWords, TextLayoutOptions, WordPositions},
     traits::Layout,
+    diff::DomDiff,
+    FastHashSet,
 };
 use webrender::api::{LayoutRect, LayoutPoint, LayoutSize, FontInstanceKey};
 
 const DEFAULT_FLEX_GROW_FACTOR: f32 = 1.0;
+const DEFAULT_FLEX_SHRINK_FACTOR: f32 = 1.0;
 const DEFAULT_FONT_SIZE: StyleFontSize = StyleFontSize(PixelValue::const_px(10));
-const DEFAULT_FONT_ID: &str = "sans-serif";
+pub(crate) const DEFAULT_FONT_ID: &str = "sans-serif";
 
 type PixelSize = f32;
 
@@ -65,6 +72,25 @@ impl WhConstraint {
     }
 }
 
+/// Resolves a length in pixels. Returns `None` if the value is relative to the size of
+/// the parent (i.e. `50%`), but the size of the parent isn't known before the layout is solved.
+fn resolve_size(value: PixelValue, context: &RelativeSizeContext, percent_base: Option<f32>) -> Option<f32> {
+    match percent_base {
+        Some(percent_base) => Some(value.to_pixels_relative(&RelativeSizeContext { percent_base, .. *context })),
+        None if value.has_percentage() => None,
+        None => Some(value.to_pixels_relative(context)),
+    }
+}
+
+/// Same as `resolve_size`, but for `calc()` expressions, i.e. `calc(100% - 20px)`
+fn resolve_calc_size(value: &CalcPixelValue, context: &RelativeSizeContext, percent_base: Option<f32>) -> Option<f32> {
+    match percent_base {
+        Some(percent_base) => Some(value.to_pixels_relative(&RelativeSizeContext { percent_base, .. *context })),
+        None if value.has_percentage() => None,
+        None => Some(value.to_pixels_relative(context)),
+    }
+}
+
 macro_rules! determine_preferred {
     ($fn_name:ident, $width:ident, $min_width:ident, $max_width:ident) => (
 
@@ -190,6 +216,8 @@ struct WidthCalculatedRect {
     pub padding: LayoutPadding,
     pub flex_grow_px: f32,
     pub min_inner_size_px: f32,
+    /// Size that a flex item can't shrink below if it has no `min-width` (the `min-content` width)
+    pub min_content_size: f32,
 }
 
 impl WidthCalculatedRect {
@@ -208,6 +236,12 @@ impl WidthCalculatedRect {
         self.padding.right.map(|px| px.to_pixels()).unwrap_or(0.0)
     }
 
+    /// Get the sum of the horizontal margin amount (`margin.left + margin.right`)
+    pub fn get_horizontal_margin(&self) -> f32 {
+        self.margin.left.map(|px| px.to_pixels()).unwrap_or(0.0)    +
+        self.margin.right.map(|px| px.to_pixels()).unwrap_or(0.0)
+    }
+
     /// Called after solver has run: Solved width of rectangle
     pub fn solved_result(&self) -> WidthSolvedResult {
         WidthSolvedResult {
@@ -224,6 +258,8 @@ struct HeightCalculatedRect {
     pub padding: LayoutPadding,
     pub flex_grow_px: f32,
     pub min_inner_size_px: f32,
+    /// Size that a flex item can't shrink below if it has no `min-height`
+    pub min_content_size: f32,
 }
 
 impl HeightCalculatedRect {
@@ -242,6 +278,12 @@ impl HeightCalculatedRect {
         self.padding.bottom.map(|px| px.to_pixels()).unwrap_or(0.0)
     }
 
+    /// Get the sum of the vertical margin amount (`margin.top + margin.bottom`)
+    pub fn get_vertical_margin(&self) -> f32 {
+        self.margin.top.map(|px| px.to_pixels()).unwrap_or(0.0) +
+        self.margin.bottom.map(|px| px.to_pixels()).unwrap_or(0.0)
+    }
+
     /// Called after solver has run: Solved width of rectangle
     pub fn solved_result(&self) -> HeightSolvedResult {
         HeightSolvedResult {
@@ -251,15 +293,510 @@ impl HeightCalculatedRect {
     }
 }
 
+/// Cell area of a grid item: the first column / row (starting at 0) and
+/// the number of columns / rows that the item spans
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct GridArea {
+    column_start: usize,
+    column_span: usize,
+    row_start: usize,
+    row_span: usize,
+}
+
+impl GridArea {
+
+    fn is_free(&self, occupied_cells: &FastHashSet<(usize, usize)>) -> bool {
+        (self.row_start..(self.row_start + self.row_span)).all(|row| {
+            (self.column_start..(self.column_start + self.column_span)).all(|column| !occupied_cells.contains(&(row, column)))
+        })
+    }
+
+    fn occupy(&self, occupied_cells: &mut FastHashSet<(usize, usize)>) {
+        for row in self.row_start..(self.row_start + self.row_span) {
+            for column in self.column_start..(self.column_start + self.column_span) {
+                occupied_cells.insert((row, column));
+            }
+        }
+    }
+}
+
+/// Solved sizes of the columns or rows of a `display: grid` node
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GridTracks {
+    /// Size of each track in pixels
+    pub sizes: Vec<f32>,
+    /// Space between two tracks in pixels
+    pub gap: f32,
+}
+
+impl GridTracks {
+
+    /// Returns the distance from the start of the grid to the start of the track
+    pub fn get_offset(&self, track: usize) -> f32 {
+        self.sizes.iter().take(track).sum::<f32>() + self.gap * track as f32
+    }
+
+    /// Returns the size of `span` tracks starting at `start`, including the gaps between them
+    pub fn get_span_size(&self, start: usize, span: usize) -> f32 {
+        if span == 0 {
+            return 0.0;
+        }
+        self.sizes.iter().skip(start).take(span).sum::<f32>() + self.gap * (span - 1) as f32
+    }
+
+    /// Returns the size of all tracks, including the gaps
+    pub fn get_total_size(&self) -> f32 {
+        self.get_span_size(0, self.sizes.len())
+    }
+}
+
+/// Size of a column or row of a grid, with percentages already resolved
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum GridTrack {
+    Fixed(f32),
+    Fraction(f32),
+    Auto,
+}
+
+/// Resolves the `grid-column` or `grid-row` of an item to the first track (or `None`
+/// if the item is auto-placed along this axis) and the number of tracks the item spans
+fn resolve_grid_placement(placement: Option<LayoutGridPlacement>, explicit_track_count: usize) -> (Option<usize>, usize) {
+
+    use azul_css::GridLine::*;
+
+    // Grid lines start at 1, negative lines count backwards from the last explicit line
+    let line_to_track = |line: i32| -> usize {
+        if line > 0 {
+            line as usize - 1
+        } else {
+            (explicit_track_count as i32 + 1 + line).max(0) as usize
+        }
+    };
+
+    let placement = placement.unwrap_or_default();
+
+    match (placement.start, placement.end) {
+        (Line(start), Line(end)) => {
+            let (start, end) = (line_to_track(start), line_to_track(end));
+            if start == end {
+                (Some(start), 1)
+            } else {
+                (Some(start.min(end)), start.max(end) - start.min(end))
+            }
+        },
+        (Line(start), Span(span)) => (Some(line_to_track(start)), span as usize),
+        (Line(start), Auto) => (Some(line_to_track(start)), 1),
+        (Span(span), Line(end)) => (Some(line_to_track(end).saturating_sub(span as usize)), span as usize),
+        (Auto, Line(end)) => (Some(line_to_track(end).saturating_sub(1)), 1),
+        (Span(span), _) | (Auto, Span(span)) => (None, span as usize),
+        (Auto, Auto) => (None, 1),
+    }
+}
+
+/// Places the children of a `display: grid` node in the cells of the grid.
+///
+/// Items with a definite `grid-column` and `grid-row` are placed first, then the items
+/// with a definite row. The remaining items fill the next free cells, row by row.
+/// Items outside of the explicit grid create implicit tracks. Children with
+/// `position: absolute` or `display: none` are taken out of the grid.
+fn place_grid_items(
+    node_id: NodeId,
+    node_hierarchy: &NodeHierarchy,
+    arena_data: &NodeDataContainer<RectLayout>)
+-> Vec<(NodeId, GridArea)>
+{
+    let grid_node = &arena_data[node_id];
+    let explicit_columns = grid_node.grid_template_columns.as_ref().map(|template| template.0.len()).unwrap_or(0);
+    let explicit_rows = grid_node.grid_template_rows.as_ref().map(|template| template.0.len()).unwrap_or(0);
+
+    // Without a `grid-template-columns`, the items are stacked in a single column
+    let column_count = explicit_columns.max(1);
+
+    let items = node_id
+        .children(node_hierarchy)
+        .filter(|child_id| is_in_flow(&arena_data[*child_id]))
+        .map(|child_id| {
+            let (column_start, column_span) = resolve_grid_placement(arena_data[child_id].grid_column, explicit_columns);
+            let (row_start, row_span) = resolve_grid_placement(arena_data[child_id].grid_row, explicit_rows);
+            (child_id, column_start, column_span, row_start, row_span)
+        })
+        .collect::<Vec<_>>();
+
+    let mut occupied_cells = FastHashSet::default();
+    let mut areas = vec![None; items.len()];
+
+    // 1. Items with a definite row and column
+    for (idx, (_, column_start, column_span, row_start, row_span)) in items.iter().enumerate() {
+        if let (Some(column_start), Some(row_start)) = (column_start, row_start) {
+            let area = GridArea { column_start: *column_start, column_span: *column_span, row_start: *row_start, row_span: *row_span };
+            area.occupy(&mut occupied_cells);
+            areas[idx] = Some(area);
+        }
+    }
+
+    // 2. Items with a definite row take the first free column(s) of that row
+    for (idx, (_, column_start, column_span, row_start, row_span)) in items.iter().enumerate() {
+        if let (None, Some(row_start)) = (column_start, row_start) {
+            let area = (0..)
+                .map(|column_start| GridArea { column_start, column_span: *column_span, row_start: *row_start, row_span: *row_span })
+                .find(|area| area.is_free(&occupied_cells))
+                .unwrap();
+            area.occupy(&mut occupied_cells);
+            areas[idx] = Some(area);
+        }
+    }
+
+    // 3. All other items are placed in the next free cell after the previously placed item
+    let (mut cursor_row, mut cursor_column) = (0, 0);
+
+    for (idx, (_, column_start, column_span, _, row_span)) in items.iter().enumerate() {
+
+        if areas[idx].is_some() {
+            continue;
+        }
+
+        let area = match column_start {
+            // Item with a definite column: take the next row that is free in that column
+            Some(column_start) => (cursor_row..)
+                .map(|row_start| GridArea { column_start: *column_start, column_span: *column_span, row_start, row_span: *row_span })
+                .find(|area| area.is_free(&occupied_cells))
+                .unwrap(),
+            None => loop {
+                // Wrap into the next row if the item doesn't fit into the remaining columns
+                if cursor_column > 0 && cursor_column + *column_span > column_count {
+                    cursor_row += 1;
+                    cursor_column = 0;
+                }
+                let area = GridArea { column_start: cursor_column, column_span: *column_span, row_start: cursor_row, row_span: *row_span };
+                if area.is_free(&occupied_cells) {
+                    break area;
+                }
+                cursor_column += 1;
+            },
+        };
+
+        cursor_row = area.row_start;
+        cursor_column = area.column_start + area.column_span;
+        area.occupy(&mut occupied_cells);
+        areas[idx] = Some(area);
+    }
+
+    items.iter()
+        .zip(areas)
+        .filter_map(|((child_id, ..), area)| area.map(|area| (*child_id, area)))
+        .collect()
+}
+
+/// Calculates the sizes of the columns (or rows) of a grid. `items` contains the first track,
+/// the number of spanned tracks and the minimum size (including the margin) of each grid item.
+///
+/// `auto` and `fr` tracks are at least as large as the items in them. If the `available_space`
+/// is known, the remaining space is distributed to the `fr` tracks or - if there are no `fr`
+/// tracks - evenly to the `auto` tracks.
+fn size_grid_tracks(tracks: &[GridTrack], items: &[(usize, usize, f32)], gap: f32, available_space: Option<f32>) -> Vec<f32> {
+
+    let get_fraction = |track: GridTrack| match track {
+        GridTrack::Fraction(fr) => Some(fr),
+        _ => None,
+    };
+
+    let mut sizes = tracks.iter().map(|track| match track {
+        GridTrack::Fixed(size) => *size,
+        _ => 0.0,
+    }).collect::<Vec<f32>>();
+
+    // Items that span one track grow the track to their size
+    for (start, _, item_size) in items.iter().filter(|(_, span, _)| *span == 1) {
+        match tracks[*start] {
+            GridTrack::Fixed(_) => { },
+            _ => sizes[*start] = sizes[*start].max(*item_size),
+        }
+    }
+
+    // Items that span multiple tracks distribute the missing space evenly to the
+    // spanned `auto` tracks (or the spanned `fr` tracks, if there are no `auto` tracks)
+    for (start, span, item_size) in items.iter().filter(|(_, span, _)| *span > 1) {
+        let spanned_tracks = *start..(*start + *span);
+        let missing_space = *item_size - sizes[spanned_tracks.clone()].iter().sum::<f32>() - gap * (*span - 1) as f32;
+        if missing_space <= 0.0 {
+            continue;
+        }
+
+        let mut growable_tracks = spanned_tracks.clone().filter(|idx| tracks[*idx] == GridTrack::Auto).collect::<Vec<_>>();
+        if growable_tracks.is_empty() {
+            growable_tracks = spanned_tracks.filter(|idx| get_fraction(tracks[*idx]).is_some()).collect();
+        }
+
+        for idx in &growable_tracks {
+            sizes[*idx] += missing_space / growable_tracks.len() as f32;
+        }
+    }
+
+    let available_space = match available_space {
+        Some(s) => s,
+        None => return sizes,
+    };
+
+    let total_gap = gap * tracks.len().saturating_sub(1) as f32;
+    let fraction_tracks = (0..tracks.len()).filter(|idx| get_fraction(tracks[*idx]).is_some()).collect::<Vec<_>>();
+
+    if fraction_tracks.is_empty() {
+        // Stretch the `auto` tracks to fill the remaining space
+        let free_space = available_space - total_gap - sizes.iter().sum::<f32>();
+        let auto_tracks = (0..tracks.len()).filter(|idx| tracks[*idx] == GridTrack::Auto).collect::<Vec<_>>();
+        if free_space > 0.0 {
+            for idx in &auto_tracks {
+                sizes[*idx] += free_space / auto_tracks.len() as f32;
+            }
+        }
+        return sizes;
+    }
+
+    // Find the size of `1fr` - `fr` tracks that are larger than their share (because of
+    // the items in them) keep their size and the share is calculated again without them
+    let space_for_fractions = available_space - total_gap - (0..tracks.len())
+        .filter(|idx| get_fraction(tracks[*idx]).is_none())
+        .map(|idx| sizes[idx])
+        .sum::<f32>();
+
+    let mut flexible_tracks = fraction_tracks;
+    let mut inflexible_space = 0.0;
+
+    loop {
+        let fraction_sum = flexible_tracks.iter().filter_map(|idx| get_fraction(tracks[*idx])).sum::<f32>();
+        // Fractions that add up to less than `1fr` don't fill the whole grid
+        let fraction_size = (space_for_fractions - inflexible_space) / fraction_sum.max(1.0);
+
+        let (inflexible, flexible): (Vec<usize>, Vec<usize>) = flexible_tracks
+            .into_iter()
+            .partition(|idx| sizes[*idx] > fraction_size * get_fraction(tracks[*idx]).unwrap_or(0.0));
+
+        if inflexible.is_empty() {
+            for idx in flexible {
+                sizes[idx] = fraction_size * get_fraction(tracks[idx]).unwrap_or(0.0);
+            }
+            break;
+        }
+
+        inflexible_space += inflexible.iter().map(|idx| sizes[*idx]).sum::<f32>();
+        flexible_tracks = flexible;
+    }
+
+    sizes
+}
+
+/// Returns the children of a flex container that take part in the flex layout (all children
+/// except the `position: absolute`, `position: fixed` and `display: none` ones), sorted by their `order`
+fn get_flex_items(node_id: NodeId, node_hierarchy: &NodeHierarchy, arena_data: &NodeDataContainer<RectLayout>) -> Vec<NodeId> {
+    let mut items = node_id
+        .children(node_hierarchy)
+        .filter(|child_id| is_in_flow(&arena_data[*child_id]))
+        .collect::<Vec<_>>();
+    // The sort is stable, so items with the same `order` stay in the order of the DOM
+    items.sort_by_key(|item_id| arena_data[*item_id].order.unwrap_or_default());
+    items
+}
+
+/// Returns the children of a node that are taken out of the normal flow (`position: absolute` and `position: fixed`)
+fn get_out_of_flow_children(node_id: NodeId, node_hierarchy: &NodeHierarchy, arena_data: &NodeDataContainer<RectLayout>) -> Vec<NodeId> {
+    node_id
+        .children(node_hierarchy)
+        .filter(|child_id| is_displayed(&arena_data[*child_id]) && arena_data[*child_id].position.unwrap_or_default().is_out_of_flow())
+        .collect()
+}
+
+/// Returns whether the node takes part in the layout at all, i.e. is not `display: none`
+fn is_displayed(layout: &RectLayout) -> bool {
+    layout.display != Some(LayoutDisplay::None)
+}
+
+/// Returns whether the node is laid out by its parent (flex or grid container)
+fn is_in_flow(layout: &RectLayout) -> bool {
+    is_displayed(layout) && !layout.position.unwrap_or_default().is_out_of_flow()
+}
+
+/// Returns the containing block of an out-of-flow node, i.e. the node that it is sized and
+/// positioned against: for `position: absolute`, this is the nearest positioned (non-static)
+/// ancestor or the root node. Returns `None` for `position: fixed`, which is relative to the window.
+fn get_containing_block(node_id: NodeId, node_hierarchy: &NodeHierarchy, arena_data: &NodeDataContainer<RectLayout>) -> Option<NodeId> {
+    if arena_data[node_id].position == Some(LayoutPosition::Fixed) {
+        return None;
+    }
+
+    let positioned_ancestor = node_id
+        .ancestors(node_hierarchy)
+        .skip(1)
+        .find(|ancestor| arena_data[*ancestor].position.unwrap_or_default() != LayoutPosition::Static);
+
+    Some(positioned_ancestor.unwrap_or_else(|| NodeId::new(0)))
+}
+
+/// Breaks the items of a `flex-wrap: wrap` container into lines: an item that doesn't fit into the
+/// remaining space of the current line starts a new line. `items` contains the outer size of each
+/// item along the main axis. Every line contains at least one item, even if it overflows the line.
+fn break_into_flex_lines(items: &[(NodeId, f32)], line_size: f32, gap: f32) -> Vec<Vec<NodeId>> {
+
+    let mut lines = Vec::new();
+    let mut current_line = Vec::new();
+    let mut current_line_size = 0.0;
+
+    for (item_id, item_size) in items {
+        if !current_line.is_empty() && current_line_size + gap + item_size > line_size {
+            lines.push(mem::replace(&mut current_line, Vec::new()));
+        }
+        current_line_size = if current_line.is_empty() { *item_size } else { current_line_size + gap + item_size };
+        current_line.push(*item_id);
+    }
+
+    if !current_line.is_empty() {
+        lines.push(current_line);
+    }
+
+    lines
+}
+
+/// Returns the alignment of a flex item along the cross axis - `align-self: auto` uses the `align-items` of the container
+fn get_align_self(item: &RectLayout, align_items: LayoutAlignItems) -> LayoutAlignItems {
+    match item.align_self.unwrap_or_default() {
+        LayoutAlignSelf::Auto => align_items,
+        LayoutAlignSelf::Stretch => LayoutAlignItems::Stretch,
+        LayoutAlignSelf::Center => LayoutAlignItems::Center,
+        LayoutAlignSelf::Start => LayoutAlignItems::Start,
+        LayoutAlignSelf::End => LayoutAlignItems::End,
+    }
+}
+
+/// Returns the offset of the first item (or line) and the additional space between two items
+/// (or lines) for distributing the `free_space` of a flex container, i.e. for `justify-content`
+fn get_flex_free_space_distribution(justify_content: LayoutJustifyContent, free_space: f32, item_count: usize) -> (f32, f32) {
+
+    use azul_css::LayoutJustifyContent::*;
+
+    let count = item_count as f32;
+
+    match justify_content {
+        Start => (0.0, 0.0),
+        End => (free_space, 0.0),
+        Center => (free_space / 2.0, 0.0),
+        SpaceBetween if free_space > 0.0 && item_count > 1 => (0.0, free_space / (count - 1.0)),
+        SpaceBetween => (0.0, 0.0),
+        SpaceAround if free_space > 0.0 && item_count > 0 => (free_space / count / 2.0, free_space / count),
+        // Overflowing items are centered
+        SpaceAround => (free_space / 2.0, 0.0),
+    }
+}
+
+/// Size constraints and flex factors of one item of a flex line, the sizes include
+/// the padding of the item, but not its margin
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct FlexItem {
+    /// Size of the item before it is flexed (the `flex-basis` or the preferred size)
+    base_size: f32,
+    min_size: f32,
+    max_size: f32,
+    flex_grow: f32,
+    flex_shrink: f32,
+}
+
+/// Distributes the `available_space` of a flex line to its items ("resolving flexible lengths"):
+/// every item starts out at its base size, then the free space is distributed according to the
+/// `flex-grow` factors - or, if the items overflow the line, taken away according to the `flex-shrink`
+/// factors, weighted by the base size of each item. Items that would violate their min or max size
+/// are frozen at that size and the remaining free space is distributed again.
+///
+/// Returns the final size of each item.
+fn resolve_flexible_lengths(items: &[FlexItem], available_space: f32) -> Vec<f32> {
+
+    let clamp = |item: &FlexItem, size: f32| size.min(item.max_size).max(item.min_size);
+
+    let mut sizes = items.iter().map(|item| clamp(item, item.base_size)).collect::<Vec<f32>>();
+    let is_growing = sizes.iter().sum::<f32>() < available_space;
+
+    // Items that can't flex keep their (clamped) base size
+    let mut frozen = items.iter().zip(&sizes).map(|(item, size)| {
+        if is_growing {
+            item.flex_grow <= 0.0 || item.base_size > *size
+        } else {
+            item.flex_shrink <= 0.0 || item.base_size < *size
+        }
+    }).collect::<Vec<bool>>();
+
+    loop {
+
+        let unfrozen = (0..items.len()).filter(|idx| !frozen[*idx]).collect::<Vec<_>>();
+
+        if unfrozen.is_empty() {
+            break;
+        }
+
+        let free_space = available_space - (0..items.len())
+            .map(|idx| if frozen[idx] { sizes[idx] } else { items[idx].base_size })
+            .sum::<f32>();
+
+        if is_growing {
+            // Flex factors that add up to less than 1 only distribute a part of the free space
+            let grow_sum = unfrozen.iter().map(|idx| items[*idx].flex_grow).sum::<f32>();
+            for idx in &unfrozen {
+                sizes[*idx] = items[*idx].base_size + free_space * items[*idx].flex_grow / grow_sum.max(1.0);
+            }
+        } else {
+            // Larger items shrink more than smaller ones
+            let shrink_sum = unfrozen.iter().map(|idx| items[*idx].flex_shrink).sum::<f32>();
+            let scaled_shrink_sum = unfrozen.iter().map(|idx| items[*idx].flex_shrink * items[*idx].base_size).sum::<f32>();
+            for idx in &unfrozen {
+                let scaled_shrink = items[*idx].flex_shrink * items[*idx].base_size;
+                sizes[*idx] = if scaled_shrink_sum > 0.0 {
+                    items[*idx].base_size + free_space * shrink_sum.min(1.0) * scaled_shrink / scaled_shrink_sum
+                } else {
+                    items[*idx].base_size
+                };
+            }
+        }
+
+        // Clamp the items to their min / max size and record by how much they were adjusted
+        let violations = unfrozen.iter().map(|idx| {
+            let clamped_size = clamp(&items[*idx], sizes[*idx]);
+            let violation = clamped_size - sizes[*idx];
+            sizes[*idx] = clamped_size;
+            violation
+        }).collect::<Vec<f32>>();
+
+        let total_violation = violations.iter().sum::<f32>();
+
+        // If the items were made larger in total, the items that violated their min size are
+        // final (and vice versa) - if nothing was violated, all items are solved
+        for (idx, violation) in unfrozen.iter().zip(&violations) {
+            frozen[*idx] = if total_violation > 0.0 {
+                *violation > 0.0
+            } else if total_violation < 0.0 {
+                *violation < 0.0
+            } else {
+                true
+            };
+        }
+    }
+
+    sizes
+}
+
 // `typed_arena!(WidthCalculatedRect, preferred_width, determine_preferred_width, get_horizontal_padding, get_flex_basis_horizontal)`
 macro_rules! typed_arena {(
     $struct_name:ident,
     $preferred_field:ident,
     $determine_preferred_fn:ident,
     $get_padding_fn:ident,
+    $get_margin_fn:ident,
     $get_flex_basis:ident,
     $bubble_fn_name:ident,
-    $main_axis:ident
+    $main_axis:ident,
+    $start_offset:ident,
+    $end_offset:ident,
+    $min_field:ident,
+    $gap_field:ident,
+    $grid_template:ident,
+    $grid_gap:ident,
+    $grid_start:ident,
+    $grid_span:ident
 ) => (
 
 impl NodeDataContainer<$struct_name> {
@@ -272,7 +809,11 @@ impl NodeDataContainer<$struct_name> {
     ///
     /// NOTE: Later on, this could maybe be a NodeDataContainer<&'a RectLayout>.
     #[must_use]
-    fn from_rect_layout_arena(node_data: &NodeDataContainer<RectLayout>, widths: &NodeDataContainer<Option<f32>>) -> Self {
+    fn from_rect_layout_arena(
+        node_data: &NodeDataContainer<RectLayout>,
+        widths: &NodeDataContainer<Option<f32>>,
+        min_content_sizes: &NodeDataContainer<f32>,
+    ) -> Self {
         let new_nodes = node_data.internal.iter().enumerate().map(|(node_id, node_data)|{
             let id = NodeId::new(node_id);
             $struct_name {
@@ -282,6 +823,7 @@ impl NodeDataContainer<$struct_name> {
                 padding: node_data.padding.unwrap_or_default(),
                 flex_grow_px: 0.0,
                 min_inner_size_px: 0.0,
+                min_content_size: min_content_sizes[id],
             }
         }).collect();
         NodeDataContainer { internal: new_nodes }
@@ -290,12 +832,18 @@ impl NodeDataContainer<$struct_name> {
     /// Bubble the inner sizes to their parents -  on any parent nodes, fill out
     /// the width so that the `preferred_width` can contain the child nodes (if
     /// that doesn't violate the constraints of the parent)
+    ///
+    /// `cross_axis_flex_lines` are the lines of the flex containers whose main axis was already solved
+    /// (i.e. when solving the heights, the lines of the `flex-direction: row` containers)
     fn $bubble_fn_name(
         &mut self,
         node_hierarchy: &NodeHierarchy,
         arena_data: &NodeDataContainer<RectLayout>,
-        non_leaf_nodes: &[(usize, NodeId)])
+        non_leaf_nodes: &[(usize, NodeId)],
+        cross_axis_flex_lines: &BTreeMap<NodeId, Vec<Vec<NodeId>>>)
     {
+        use azul_css::{LayoutAxis, LayoutWrap};
+
         // Reverse, since we want to go from the inside out (depth 5 needs to be filled out first)
         //
         // Set the preferred_width of the parent nodes
@@ -303,8 +851,26 @@ impl NodeDataContainer<$struct_name> {
 
             use self::WhConstraint::*;
 
-            // Sum of the direct children's flex-basis = the parents preferred width
-            let children_flex_basis = self.sum_children_flex_basis(*non_leaf_id, node_hierarchy, arena_data);
+            let parent_node = &arena_data[*non_leaf_id];
+
+            // Sum of the direct children's flex-basis (or the minimum size of
+            // the grid tracks) = the parents preferred width
+            let children_flex_basis = if parent_node.display.unwrap_or_default() == LayoutDisplay::Grid {
+                self.get_grid_tracks(*non_leaf_id, node_hierarchy, arena_data, None).get_total_size()
+            } else if parent_node.direction.unwrap_or_default().get_axis() == LayoutAxis::$main_axis {
+                self.sum_children_flex_basis(*non_leaf_id, node_hierarchy, arena_data)
+            } else {
+                // Along the cross axis, the container has to be as large as its largest item -
+                // or, if the items wrap into multiple lines, as large as all lines together
+                match cross_axis_flex_lines.get(non_leaf_id) {
+                    Some(lines) if parent_node.wrap.unwrap_or_default() == LayoutWrap::Wrap => {
+                        let gap = parent_node.$gap_field.map(|gap| gap.0.to_pixels()).unwrap_or(0.0);
+                        lines.iter().map(|line| self.get_flex_line_cross_size(line)).sum::<f32>()
+                        + gap * lines.len().saturating_sub(1) as f32
+                    },
+                    _ => self.get_flex_line_cross_size(&get_flex_items(*non_leaf_id, node_hierarchy, arena_data)),
+                }
+            };
 
             // Calculate the new flex-basis width
             let parent_width_metrics = self[*non_leaf_id];
@@ -337,14 +903,16 @@ impl NodeDataContainer<$struct_name> {
 
     /// Go from the root down and flex_grow the children if needed - respects the `width`, `min_width` and `max_width` properties
     /// The layout step doesn't account for the min_width and max_width constraints, so we have to adjust them manually
+    ///
+    /// Returns the solved grid tracks and flex lines, which are needed to position the children
     fn apply_flex_grow(
         &mut self,
         node_hierarchy: &NodeHierarchy,
         arena_data: &NodeDataContainer<RectLayout>,
         parent_ids_sorted_by_depth: &[(usize, NodeId)],
-        root_width: f32
-    ) {
-        use azul_css::LayoutAlignItems;
+        root_width: f32,
+        cross_axis_flex_lines: &BTreeMap<NodeId, Vec<Vec<NodeId>>>,
+    ) -> SolvedContainers {
 
         debug_assert!(self[NodeId::new(0)].flex_grow_px == 0.0);
 
@@ -364,329 +932,330 @@ impl NodeDataContainer<$struct_name> {
 
         self[NodeId::new(0)].flex_grow_px = root_preferred_width - top_level_flex_basis;
 
-        // Keep track of the nearest relative or absolute positioned element
-        let mut positioned_node_stack = vec![NodeId::new(0)];
+        let mut solved_containers = SolvedContainers::default();
 
         for (_node_depth, parent_id) in parent_ids_sorted_by_depth {
 
-            use azul_css::{LayoutAxis, LayoutPosition};
+            use azul_css::{LayoutAxis, LayoutWrap};
 
             let parent_node = &arena_data[*parent_id];
-            let parent_is_positioned = parent_node.position.unwrap_or_default() != LayoutPosition::Static;
 
-            if parent_is_positioned {
-                positioned_node_stack.push(*parent_id);
-            }
+            // The size of the parent is final at this point
+            let parent_inner_size = self[*parent_id].min_inner_size_px + self[*parent_id].flex_grow_px - self[*parent_id].$get_padding_fn();
 
-            // How much width is there to distribute along the main and cross axis?
-            let (width_main_axis, width_cross_axis) = {
-                let parent_width_metrics = &self[*parent_id];
+            // The ancestors of the parent are already solved, so the out-of-flow children can
+            // be sized against their containing block
+            self.distribute_space_to_out_of_flow_children(*parent_id, node_hierarchy, arena_data, root_width);
 
-                let width_horizontal_axis = {
-                    let children_margin: f32 = parent_id.children(node_hierarchy).map(|child_id| arena_data[child_id].get_horizontal_margin()).sum();
-                    parent_width_metrics.min_inner_size_px + parent_width_metrics.flex_grow_px - parent_node.get_horizontal_padding() - children_margin
-                };
+            if parent_node.display.unwrap_or_default() == LayoutDisplay::Grid {
+                let tracks = self.get_grid_tracks(*parent_id, node_hierarchy, arena_data, Some(parent_inner_size));
+                self.distribute_space_in_grid(*parent_id, &tracks, node_hierarchy, arena_data);
+                solved_containers.grid_tracks.insert(*parent_id, tracks);
+            } else {
 
-                let width_vertical_axis = {
-                    // let children_margin: f32 = parent_id.children(node_hierarchy).map(|child_id| arena_data[child_id].get_vertical_margin()).sum();
-                    parent_width_metrics.min_inner_size_px + parent_width_metrics.flex_grow_px - parent_node.get_vertical_padding()
-                };
+                let is_wrapping = parent_node.wrap.unwrap_or_default() == LayoutWrap::Wrap;
+                let gap = parent_node.$gap_field.map(|gap| gap.0.to_pixels()).unwrap_or(0.0);
+                let align_items = parent_node.align_items.unwrap_or_default();
 
-                let width_main_axis = match LayoutAxis::$main_axis {
-                    LayoutAxis::Horizontal => width_horizontal_axis,
-                    LayoutAxis::Vertical => width_vertical_axis,
-                };
+                if parent_node.direction.unwrap_or_default().get_axis() == LayoutAxis::$main_axis {
 
-                let width_cross_axis = match LayoutAxis::$main_axis {
-                    LayoutAxis::Horizontal => width_vertical_axis,
-                    LayoutAxis::Vertical => width_horizontal_axis,
-                };
+                    let items = get_flex_items(*parent_id, node_hierarchy, arena_data);
 
-                (width_main_axis, width_cross_axis)
-            };
+                    let lines = if is_wrapping {
+                        let item_sizes = items.iter().map(|item_id| {
+                            (*item_id, self.get_flex_base_size(*item_id, arena_data) + self[*item_id].$get_margin_fn())
+                        }).collect::<Vec<_>>();
+                        break_into_flex_lines(&item_sizes, parent_inner_size, gap)
+                    } else {
+                        vec![items]
+                    };
+
+                    for line in &lines {
+                        let margins = line.iter().map(|item_id| self[*item_id].$get_margin_fn()).sum::<f32>();
+                        let gaps = gap * line.len().saturating_sub(1) as f32;
+                        self.distribute_space_along_main_axis(line, parent_inner_size - margins - gaps, arena_data);
+                    }
+
+                    solved_containers.flex_lines.insert(*parent_id, lines);
 
-            // Only stretch the items, if they have a align-items: stretch!
-            if parent_node.align_items.unwrap_or_default() == LayoutAlignItems::Stretch {
-                if parent_node.direction.unwrap_or_default().get_axis() == LayoutAxis::$main_axis {
-                    Self::distribute_space_along_main_axis(parent_id, width_main_axis, node_hierarchy, arena_data, self, &positioned_node_stack);
                 } else {
-                    Self::distribute_space_along_cross_axis(parent_id, width_cross_axis, node_hierarchy, arena_data, self, &positioned_node_stack);
+                    match cross_axis_flex_lines.get(parent_id) {
+                        Some(lines) if is_wrapping => {
+                            let align_content = parent_node.align_content.unwrap_or_default();
+                            let line_sizes = self.get_flex_line_cross_sizes(lines, parent_inner_size, gap, align_content);
+                            for (line, line_size) in lines.iter().zip(&line_sizes) {
+                                self.distribute_space_along_cross_axis(line, Some(*line_size), align_items, arena_data);
+                            }
+                            solved_containers.flex_line_sizes.insert(*parent_id, line_sizes);
+                        },
+                        _ => {
+                            // A single-line container: the line is as large as the container. If the
+                            // container wraps, but its main axis isn't solved yet (`flex-direction: column`
+                            // when solving the widths), the items keep the size of their content.
+                            let line_size = if is_wrapping { None } else { Some(parent_inner_size) };
+                            let items = get_flex_items(*parent_id, node_hierarchy, arena_data);
+                            self.distribute_space_along_cross_axis(&items, line_size, align_items, arena_data);
+                        },
+                    }
                 }
             }
-
-            if parent_is_positioned {
-                positioned_node_stack.pop();
-            }
         }
+
+        solved_containers
     }
 
-    /// Returns the sum of the flex-basis of the current nodes' children
-    fn sum_children_flex_basis(
+    /// Returns the sizes of the columns (horizontal) or rows (vertical) of a `display: grid` node.
+    /// Without an `available_space`, only the minimum sizes of the tracks are calculated.
+    fn get_grid_tracks(
         &self,
         node_id: NodeId,
         node_hierarchy: &NodeHierarchy,
-        display_arena: &NodeDataContainer<RectLayout>)
-    -> f32
-    {
-        node_id
-            .children(node_hierarchy)
-            .filter(|child_node_id| display_arena[*child_node_id].position != Some(LayoutPosition::Absolute))
-            .map(|child_node_id| self[child_node_id].$get_flex_basis())
-            .sum()
-    }
-
-    /// Does the actual width layout, respects the `width`, `min_width` and `max_width`
-    /// properties as well as the `flex_grow` factor. `flex_shrink` currently does nothing.
-    fn distribute_space_along_main_axis(
-        node_id: &NodeId,
-        width_to_distribute: f32,
-        node_hierarchy: &NodeHierarchy,
         arena_data: &NodeDataContainer<RectLayout>,
-        width_calculated_arena: &mut NodeDataContainer<$struct_name>,
-        positioned_node_stack: &[NodeId]
-    ) {
-        let mut parent_node_inner_width = width_to_distribute;
-
-        // 1. Set all child elements that have an exact width to that width, record their violations
-        //    and add their violation to the leftover horizontal space.
-        // let mut horizontal_space_from_fixed_width_items = 0.0;
-        let mut horizontal_space_taken_up_by_fixed_width_items = 0.0;
-
-        {
-            // Vec<(NodeId, PreferredWidth)>
-            let exact_width_childs = node_id
-                    .children(node_hierarchy)
-                    .filter_map(|id| if let WhConstraint::EqualTo(exact) = width_calculated_arena[id].$preferred_field {
-                        Some((id, exact))
-                    } else {
-                        None
-                    })
-                    .collect::<Vec<(NodeId, f32)>>();
-
-            for (exact_width_child_id, exact_width) in exact_width_childs {
-
-                // If this child node is `position: absolute`, it doesn't take any space away from
-                // its siblings, since it is taken out of the regular content flow
-                if arena_data[exact_width_child_id].position.unwrap_or_default() != LayoutPosition::Absolute {
-                    horizontal_space_taken_up_by_fixed_width_items += exact_width;
-                }
-
-                // so that node.min_inner_size_px + node.flex_grow_px = exact_width
-                width_calculated_arena[exact_width_child_id].flex_grow_px =
-                    exact_width - width_calculated_arena[exact_width_child_id].min_inner_size_px;
-            }
-        }
+        available_space: Option<f32>)
+    -> GridTracks
+    {
+        let grid_node = &arena_data[node_id];
 
-        // The fixed-width items are now considered solved, so subtract them out of the width of the parent.
-        parent_node_inner_width -= horizontal_space_taken_up_by_fixed_width_items;
+        // `grid-gap` is a shorthand for `row-gap` and `column-gap`
+        let gap = grid_node.grid_gap.map(|gap| gap.$grid_gap).or_else(|| grid_node.$gap_field.map(|gap| gap.0)).map(|gap| gap.to_pixels()).unwrap_or(0.0);
+        let template = grid_node.$grid_template.as_ref().map(|template| template.0.as_slice()).unwrap_or(&[]);
 
-        // Now we can be sure that if we write #x { width: 500px; } that it will actually be 500px large
-        // and not be influenced by flex in any way.
+        let items = place_grid_items(node_id, node_hierarchy, arena_data)
+            .into_iter()
+            .map(|(child_id, area)| (area.$grid_start, area.$grid_span, self[child_id].$get_flex_basis()))
+            .collect::<Vec<_>>();
 
-        // 2. Set all items to their minimum width. Record how much space is gained by doing so.
-        let mut horizontal_space_taken_up_by_variable_items = 0.0;
+        // Items outside of the explicit grid create implicit `auto` tracks
+        let track_count = items.iter().map(|(start, span, _)| start + span).max().unwrap_or(0).max(template.len());
 
-        use FastHashSet;
+        let tracks = (0..track_count).map(|idx| match template.get(idx) {
+            Some(GridTrackSize::Fixed(size)) => GridTrack::Fixed(size.to_pixels()),
+            Some(GridTrackSize::Fraction(fr)) => GridTrack::Fraction(fr.get()),
+            Some(GridTrackSize::Auto) | None => GridTrack::Auto,
+        }).collect::<Vec<_>>();
 
-        let mut variable_width_childs = node_id
-            .children(node_hierarchy)
-            .filter(|id| !width_calculated_arena[*id].$preferred_field.is_fixed_constraint())
-            .collect::<FastHashSet<NodeId>>();
+        GridTracks { sizes: size_grid_tracks(&tracks, &items, gap, available_space), gap }
+    }
 
-        let mut absolute_variable_width_nodes = Vec::new();
+    /// Sizes the children of a `display: grid` node to the tracks they span
+    fn distribute_space_in_grid(
+        &mut self,
+        node_id: NodeId,
+        grid_tracks: &GridTracks,
+        node_hierarchy: &NodeHierarchy,
+        arena_data: &NodeDataContainer<RectLayout>)
+    {
+        use azul_css::LayoutAxis;
 
-        for variable_child_id in &variable_width_childs {
+        for (child_id, area) in place_grid_items(node_id, node_hierarchy, arena_data) {
 
-            if arena_data[*variable_child_id].position.unwrap_or_default() != LayoutPosition::Absolute {
+            let child_margin = match LayoutAxis::$main_axis {
+                LayoutAxis::Horizontal => arena_data[child_id].get_horizontal_margin(),
+                LayoutAxis::Vertical => arena_data[child_id].get_vertical_margin(),
+            };
 
-                let min_width = width_calculated_arena[*variable_child_id].$preferred_field.min_needed_space().unwrap_or(0.0);
+            let area_size = grid_tracks.get_span_size(area.$grid_start, area.$grid_span) - child_margin;
 
-                horizontal_space_taken_up_by_variable_items += min_width;
+            // Items without a fixed size are stretched to fill their grid area
+            let child_size = match self[child_id].$preferred_field {
+                WhConstraint::EqualTo(exact) => exact,
+                other => other.max_available_space().map(|max| area_size.min(max)).unwrap_or(area_size),
+            };
 
-                // so that node.min_inner_size_px + node.flex_grow_px = min_width
-                width_calculated_arena[*variable_child_id].flex_grow_px =
-                    min_width - width_calculated_arena[*variable_child_id].min_inner_size_px;
+            // so that node.min_inner_size_px + node.flex_grow_px = child_size
+            self[child_id].flex_grow_px = child_size - self[child_id].min_inner_size_px;
+        }
+    }
 
-            } else {
+    /// Returns the sum of the flex-basis of the current nodes' children, including the gaps between them
+    fn sum_children_flex_basis(
+        &self,
+        node_id: NodeId,
+        node_hierarchy: &NodeHierarchy,
+        display_arena: &NodeDataContainer<RectLayout>)
+    -> f32
+    {
+        let items = get_flex_items(node_id, node_hierarchy, display_arena);
+        let gap = display_arena[node_id].$gap_field.map(|gap| gap.0.to_pixels()).unwrap_or(0.0);
 
-                // `position: absolute` items don't take space away from their siblings, rather
-                // they take the minimum needed space by their content
+        items.iter()
+            .map(|item_id| self.get_flex_base_size(*item_id, display_arena) + self[*item_id].$get_margin_fn())
+            .sum::<f32>()
+        + gap * items.len().saturating_sub(1) as f32
+    }
 
-                let root_id = NodeId::new(0);
-                let nearest_relative_parent_node = positioned_node_stack.get(positioned_node_stack.len() - 1).unwrap_or(&root_id);
-                let relative_parent_width = {
-                    let relative_parent_node = &width_calculated_arena[*nearest_relative_parent_node];
-                    relative_parent_node.flex_grow_px + relative_parent_node.min_inner_size_px
-                };
+    /// Returns the size of a flex item before it is flexed (including the padding, but without the
+    /// margin): the `flex-basis` if it is set, otherwise the preferred size of the item.
+    fn get_flex_base_size(&self, node_id: NodeId, arena_data: &NodeDataContainer<RectLayout>) -> f32 {
 
-                // By default, absolute positioned elements take the width of their content
-                // let min_inner_width = width_calculated_arena[*variable_child_id].$preferred_field.min_needed_space().unwrap_or(0.0);
-
-                // The absolute positioned node might have a max-width constraint, which has a
-                // higher precedence than `top, bottom, left, right`.
-                let max_space_current_node = match width_calculated_arena[*variable_child_id].$preferred_field {
-                    WhConstraint::EqualTo(e) => e,
-                    WhConstraint::Between(min, max) => {
-                        if relative_parent_width > min {
-                            if relative_parent_width < max {
-                                relative_parent_width
-                            } else {
-                                max
-                            }
-                        } else {
-                            min
-                        }
-                    },
-                    WhConstraint::Unconstrained => relative_parent_width,
-                };
+        use azul_css::LayoutFlexBasis;
 
-                // so that node.min_inner_size_px + node.flex_grow_px = max_space_current_node
-                width_calculated_arena[*variable_child_id].flex_grow_px =
-                    max_space_current_node - width_calculated_arena[*variable_child_id].min_inner_size_px;
+        let node = &self[node_id];
 
-                absolute_variable_width_nodes.push(*variable_child_id);
-            }
+        let flex_basis = match arena_data[node_id].flex_basis.unwrap_or_default() {
+            LayoutFlexBasis::Exact(basis) => Some(basis.to_pixels()),
+            LayoutFlexBasis::Auto => None,
+        };
 
+        match flex_basis {
+            // A fixed width / height takes precedence over the flex-basis
+            Some(basis) if !node.$preferred_field.is_fixed_constraint() => basis + node.$get_padding_fn(),
+            _ => node.$get_flex_basis() - node.$get_margin_fn(),
         }
+    }
 
-        // Absolute positioned nodes aren't in the space-to-distribute set
-        for absolute_node in absolute_variable_width_nodes {
-            variable_width_childs.remove(&absolute_node);
+    /// Returns the size of a node if it isn't stretched (including the padding, but
+    /// without the margin): its fixed size or the size of its content
+    fn get_content_size(&self, node_id: NodeId) -> f32 {
+        let node = &self[node_id];
+        match node.$preferred_field {
+            WhConstraint::EqualTo(exact) => exact,
+            other => other.min_needed_space().unwrap_or(0.0).max(node.min_inner_size_px) + node.$get_padding_fn(),
         }
+    }
 
-        // This satisfies the `width` and `min_width` constraints. However, we still need to worry about
-        // the `max_width` and unconstrained children.
-        //
-        // By setting the items to their minimum size, we've gained some space that we now need to distribute
-        // according to the flex_grow values
-        parent_node_inner_width -= horizontal_space_taken_up_by_variable_items;
-
-        let mut total_horizontal_space_available = parent_node_inner_width;
-        let mut max_width_violations = Vec::new();
+    /// Returns the size of a flex line along the cross axis, before it is stretched: the size of its largest item
+    fn get_flex_line_cross_size(&self, line: &[NodeId]) -> f32 {
+        line.iter()
+            .map(|item_id| self.get_content_size(*item_id) + self[*item_id].$get_margin_fn())
+            .fold(0.0, f32::max)
+    }
 
-        loop {
+    /// Returns the cross sizes of the lines of a wrapping flex container - with
+    /// `align-content: stretch`, the remaining space is distributed evenly to the lines
+    fn get_flex_line_cross_sizes(
+        &self,
+        lines: &[Vec<NodeId>],
+        parent_inner_size: f32,
+        gap: f32,
+        align_content: LayoutAlignContent)
+    -> Vec<f32>
+    {
+        let mut line_sizes = lines.iter().map(|line| self.get_flex_line_cross_size(line)).collect::<Vec<f32>>();
+        let free_space = parent_inner_size - line_sizes.iter().sum::<f32>() - gap * lines.len().saturating_sub(1) as f32;
 
-            if total_horizontal_space_available <= 0.0 || variable_width_childs.is_empty() {
-                break;
+        if align_content == LayoutAlignContent::Stretch && free_space > 0.0 {
+            let line_count = line_sizes.len() as f32;
+            for line_size in &mut line_sizes {
+                *line_size += free_space / line_count;
             }
+        }
 
-            // In order to apply flex-grow correctly, we need the sum of
-            // the flex-grow factors of all the variable-width children
-            //
-            // NOTE: variable_width_childs can change its length, have to recalculate every loop!
-            let children_combined_flex_grow: f32 = variable_width_childs
-                .iter()
-                .map(|child_id|
-                        // Prevent flex-grow and flex-shrink to be less than 1
-                        arena_data[*child_id].flex_grow
-                            .map(|grow| grow.0.get().max(1.0))
-                            .unwrap_or(DEFAULT_FLEX_GROW_FACTOR))
-                .sum();
-
-            // Grow all variable children by the same amount.
-            for variable_child_id in &variable_width_childs {
-
-                let flex_grow = arena_data[*variable_child_id].flex_grow
-                    .and_then(|grow| Some(grow.0.get()))
-                    .unwrap_or(DEFAULT_FLEX_GROW_FACTOR);
-
-                // Do not expand the item on "flex-grow: 0" (prevent division by 0)
-                if flex_grow as usize == 0 {
-                    continue;
-                }
+        line_sizes
+    }
 
-                let added_space_for_one_child = total_horizontal_space_available * (flex_grow / children_combined_flex_grow);
+    /// Sizes the `position: absolute` and `position: fixed` children of a node: they don't take any
+    /// space away from their siblings, instead they fill their containing block (unless they have a
+    /// fixed size). If both offsets (`left` and `right` / `top` and `bottom`) are set, the node is
+    /// stretched between them instead.
+    fn distribute_space_to_out_of_flow_children(
+        &mut self,
+        node_id: NodeId,
+        node_hierarchy: &NodeHierarchy,
+        arena_data: &NodeDataContainer<RectLayout>,
+        window_size: f32)
+    {
+        for child_id in get_out_of_flow_children(node_id, node_hierarchy, arena_data) {
 
-                let current_width_of_child = width_calculated_arena[*variable_child_id].min_inner_size_px +
-                                             width_calculated_arena[*variable_child_id].flex_grow_px;
+            let containing_block_inner_size = match get_containing_block(child_id, node_hierarchy, arena_data) {
+                Some(containing_block_id) => {
+                    let containing_block = &self[containing_block_id];
+                    containing_block.min_inner_size_px + containing_block.flex_grow_px - containing_block.$get_padding_fn()
+                },
+                None => window_size,
+            };
 
-                if let Some(max_width) = width_calculated_arena[*variable_child_id].$preferred_field.max_available_space() {
-                    if (current_width_of_child + added_space_for_one_child) > max_width {
-                        // so that node.min_inner_size_px + node.flex_grow_px = max_width
-                        width_calculated_arena[*variable_child_id].flex_grow_px =
-                            max_width - width_calculated_arena[*variable_child_id].min_inner_size_px;
+            let child_layout = &arena_data[child_id];
+            let available_size = match (child_layout.$start_offset, child_layout.$end_offset) {
+                (Some(start), Some(end)) => {
+                    containing_block_inner_size - start.0.to_pixels() - end.0.to_pixels() - self[child_id].$get_margin_fn()
+                },
+                _ => containing_block_inner_size,
+            };
 
-                        max_width_violations.push(*variable_child_id);
-                    } else {
-                        // so that node.min_inner_size_px + node.flex_grow_px = added_space_for_one_child
-                        width_calculated_arena[*variable_child_id].flex_grow_px =
-                            added_space_for_one_child - width_calculated_arena[*variable_child_id].min_inner_size_px;
-                    }
-                } else {
-                    // so that node.min_inner_size_px + node.flex_grow_px = added_space_for_one_child
-                    width_calculated_arena[*variable_child_id].flex_grow_px =
-                        added_space_for_one_child - width_calculated_arena[*variable_child_id].min_inner_size_px;
-                }
-            }
+            // The absolute positioned node might have a max-width constraint, which has a
+            // higher precedence than `top, bottom, left, right`.
+            let child_size = match self[child_id].$preferred_field {
+                WhConstraint::EqualTo(exact) => exact,
+                WhConstraint::Between(min, max) => available_size.min(max).max(min),
+                WhConstraint::Unconstrained => available_size,
+            };
 
-            // If we haven't violated any max_width constraints, then we have
-            // added all remaining widths and thereby successfully solved the layout
-            if max_width_violations.is_empty() {
-                break;
-            } else {
-                // Nodes that were violated can't grow anymore in the next iteration,
-                // so we remove them from the solution and consider them "solved".
-                // Their amount of violation then gets distributed across the remaining
-                // items in the next iteration.
-                for solved_node_id in max_width_violations.drain(..) {
-
-                    // Since the node now gets removed, it doesn't contribute to the pool anymore
-                    total_horizontal_space_available -=
-                        width_calculated_arena[solved_node_id].min_inner_size_px +
-                        width_calculated_arena[solved_node_id].flex_grow_px;
-
-                    variable_width_childs.remove(&solved_node_id);
-                }
-            }
+            // so that node.min_inner_size_px + node.flex_grow_px = child_size
+            self[child_id].flex_grow_px = child_size - self[child_id].min_inner_size_px;
         }
     }
 
-    fn distribute_space_along_cross_axis(
-        node_id: &NodeId,
-        width_to_distribute: f32,
-        node_hierarchy: &NodeHierarchy,
-        arena_data: &NodeDataContainer<RectLayout>,
-        width_calculated_arena: &mut NodeDataContainer<$struct_name>,
-        positioned_node_stack: &[NodeId])
+    /// Sizes the items of one flex line along the main axis, respecting the `width`, `min-width`
+    /// and `max-width` as well as the `flex-basis`, `flex-grow` and `flex-shrink` of each item.
+    /// `available_space` is the inner size of the container minus the margins of the items and the gaps.
+    ///
+    /// Items with a fixed width are not influenced by flex in any way, so that if we write
+    /// `#x { width: 500px; }`, it will actually be 500px large.
+    fn distribute_space_along_main_axis(
+        &mut self,
+        line: &[NodeId],
+        available_space: f32,
+        arena_data: &NodeDataContainer<RectLayout>)
     {
-        let parent_node_inner_width = width_to_distribute;
+        let items = line.iter().map(|item_id| {
+            let item = &self[*item_id];
+            let item_layout = &arena_data[*item_id];
+            match item.$preferred_field {
+                WhConstraint::EqualTo(exact) => FlexItem {
+                    base_size: exact,
+                    min_size: exact,
+                    max_size: exact,
+                    flex_grow: 0.0,
+                    flex_shrink: 0.0,
+                },
+                other => FlexItem {
+                    base_size: self.get_flex_base_size(*item_id, arena_data),
+                    // Items can shrink down to their `min-width` (without a `min-width`, down to
+                    // their `min-content` size), but never below their padding
+                    min_size: item_layout.$min_field
+                        .map(|min| min.0.to_pixels())
+                        .unwrap_or(item.min_content_size)
+                        .max(item.$get_padding_fn()),
+                    max_size: other.max_available_space().unwrap_or(f32::MAX),
+                    flex_grow: item_layout.flex_grow.map(|grow| grow.0.get()).unwrap_or(DEFAULT_FLEX_GROW_FACTOR),
+                    flex_shrink: item_layout.flex_shrink.map(|shrink| shrink.0.get()).unwrap_or(DEFAULT_FLEX_SHRINK_FACTOR),
+                },
+            }
+        }).collect::<Vec<_>>();
 
-        let last_relative_node_width = {
-            let zero_node = NodeId::new(0);
-            let last_relative_node_id = positioned_node_stack.get(positioned_node_stack.len() - 1).unwrap_or(&zero_node);
-            let last_relative_node = &width_calculated_arena[*last_relative_node_id];
-            last_relative_node.min_inner_size_px + last_relative_node.flex_grow_px - last_relative_node.$get_padding_fn()
-        };
+        for (item_id, item_size) in line.iter().zip(resolve_flexible_lengths(&items, available_space)) {
+            // so that node.min_inner_size_px + node.flex_grow_px = item_size
+            self[*item_id].flex_grow_px = item_size - self[*item_id].min_inner_size_px;
+        }
+    }
 
-        for child_id in node_id.children(node_hierarchy) {
+    /// Sizes the items of one flex line along the cross axis: items with `align-self: stretch` fill
+    /// the line (unless they have a fixed size), all other items keep the size of their content.
+    /// Without a `line_size` (because the lines aren't known yet), no item is stretched.
+    fn distribute_space_along_cross_axis(
+        &mut self,
+        line: &[NodeId],
+        line_size: Option<f32>,
+        align_items: LayoutAlignItems,
+        arena_data: &NodeDataContainer<RectLayout>)
+    {
+        for item_id in line {
 
-            let parent_node_inner_width = if arena_data[child_id].position.unwrap_or_default() != LayoutPosition::Absolute {
-                parent_node_inner_width
-            } else {
-                last_relative_node_width
-            };
+            let preferred_size = self[*item_id].$preferred_field;
 
-            let preferred_width = {
-                let min_width = width_calculated_arena[child_id].$preferred_field.min_needed_space().unwrap_or(0.0);
-                // In this case we want to overflow if the min width of the cross axis
-                if min_width > parent_node_inner_width {
-                    min_width
-                } else {
-                    if let Some(max_width) = width_calculated_arena[child_id].$preferred_field.max_available_space() {
-                        if max_width > parent_node_inner_width {
-                            parent_node_inner_width
-                        } else {
-                            max_width
-                        }
-                    } else {
-                        parent_node_inner_width
-                    }
-                }
+            let item_size = match (line_size, get_align_self(&arena_data[*item_id], align_items)) {
+                (Some(line_size), LayoutAlignItems::Stretch) if !preferred_size.is_fixed_constraint() => {
+                    let stretched_size = line_size - self[*item_id].$get_margin_fn();
+                    let stretched_size = preferred_size.max_available_space().map(|max| stretched_size.min(max)).unwrap_or(stretched_size);
+                    // In this case we want to overflow if the min width of the cross axis is larger than the line
+                    stretched_size.max(preferred_size.min_needed_space().unwrap_or(0.0))
+                },
+                _ => self.get_content_size(*item_id),
             };
 
-            // so that node.min_inner_size_px + node.flex_grow_px = preferred_width
-            width_calculated_arena[child_id].flex_grow_px =
-                preferred_width - width_calculated_arena[child_id].min_inner_size_px;
+            // so that node.min_inner_size_px + node.flex_grow_px = item_size
+            self[*item_id].flex_grow_px = item_size - self[*item_id].min_inner_size_px;
         }
     }
 }
@@ -698,9 +1267,18 @@ typed_arena!(
     preferred_width,
     determine_preferred_width,
     get_horizontal_padding,
+    get_horizontal_margin,
     get_flex_basis_horizontal,
     bubble_preferred_widths_to_parents,
-    Horizontal
+    Horizontal,
+    left,
+    right,
+    min_width,
+    column_gap,
+    grid_template_columns,
+    column,
+    column_start,
+    column_span
 );
 
 typed_arena!(
@@ -708,9 +1286,18 @@ typed_arena!(
     preferred_height,
     determine_preferred_height,
     get_vertical_padding,
+    get_vertical_margin,
     get_flex_basis_vertical,
     bubble_preferred_heights_to_parents,
-    Vertical
+    Vertical,
+    top,
+    bottom,
+    min_height,
+    row_gap,
+    grid_template_rows,
+    row,
+    row_start,
+    row_span
 );
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -742,27 +1329,505 @@ pub(crate) struct SolvedWidthLayout {
     pub solved_widths: NodeDataContainer<WidthSolvedResult>,
     pub layout_only_arena: NodeDataContainer<RectLayout>,
     pub non_leaf_nodes_sorted_by_depth: Vec<(usize, NodeId)>,
+    /// Solved columns of the grid containers and lines of the flex containers
+    pub containers: SolvedContainers,
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct SolvedHeightLayout {
     pub solved_heights: NodeDataContainer<HeightSolvedResult>,
+    /// Solved rows of the grid containers and lines of the flex containers
+    pub containers: SolvedContainers,
+}
+
+/// Solved tracks of the grid containers and lines of the flex containers along one
+/// axis, which are necessary to position the children of the containers
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct SolvedContainers {
+    /// Columns (or rows) of the `display: grid` nodes
+    pub grid_tracks: BTreeMap<NodeId, GridTracks>,
+    /// Items of the flex containers whose main axis is this axis, sorted by
+    /// `order` and broken into lines (if the container has `flex-wrap: wrap`)
+    pub flex_lines: BTreeMap<NodeId, Vec<Vec<NodeId>>>,
+    /// Cross sizes of the lines of the wrapping flex containers whose cross axis is this axis
+    pub flex_line_sizes: BTreeMap<NodeId, Vec<f32>>,
+}
+
+/// Resolves all relative lengths (`%`, `em`, `rem`, `vw`, `vh` and `calc()` expressions) of the
+/// nodes to pixels, so that the solver only has to deal with absolute sizes.
+///
+/// Percentages refer to the inner size of the parent (of the containing block for out-of-flow
+/// nodes, of the window for the root node). Since this happens before the sizes are bubbled to
+/// the parents, that size is only known if it is definite: a fixed size, the window size or the
+/// size of a stretched flex item. Percentages of any other size are treated as `auto` (as `0`
+/// for the padding and margin).
+///
+/// `font_sizes` are the font sizes of the nodes, which the `em` values refer to,
+/// `border_widths` are the horizontal and vertical border widths (see `get_border_widths`).
+fn resolve_relative_lengths(
+    node_hierarchy: &NodeHierarchy,
+    mut layouts: NodeDataContainer<RectLayout>,
+    font_sizes: &NodeDataContainer<StyleFontSize>,
+    border_widths: &NodeDataContainer<(f32, f32)>,
+    window_size: LayoutSize,
+) -> NodeDataContainer<RectLayout> {
+
+    use azul_css::{LayoutAxis, LayoutWrap};
+
+    let root_font_size = font_sizes.internal.get(0).cloned().unwrap_or(DEFAULT_FONT_SIZE).0.to_pixels();
+
+    let window = (Some(window_size.width), Some(window_size.height));
+    // Definite inner width and height of each node
+    let mut inner_sizes: NodeDataContainer<(Option<f32>, Option<f32>)> = layouts.transform(|_, _| (None, None));
+
+    // Parents always have a lower index than their children, so
+    // the parent of a node is always resolved before the node itself
+    for node_id in (0..layouts.len()).map(NodeId::new) {
+
+        let context = RelativeSizeContext {
+            percent_base: 0.0,
+            font_size: font_sizes[node_id].0.to_pixels(),
+            root_font_size,
+            viewport_width: window_size.width,
+            viewport_height: window_size.height,
+        };
+
+        let parent_id = node_hierarchy[node_id].parent;
+        let is_out_of_flow = layouts[node_id].position.unwrap_or_default().is_out_of_flow();
+
+        let (width_base, height_base) = match parent_id {
+            Some(_) if is_out_of_flow => get_containing_block(node_id, node_hierarchy, &layouts).map(|id| inner_sizes[id]).unwrap_or(window),
+            Some(parent_id) => inner_sizes[parent_id],
+            None => window,
+        };
+
+        let parent_axis = parent_id.map(|parent_id| layouts[parent_id].direction.unwrap_or_default().get_axis());
+        let flex_basis_base = match parent_axis {
+            Some(LayoutAxis::Horizontal) => width_base,
+            Some(LayoutAxis::Vertical) => height_base,
+            None => None,
+        };
+
+        let mut layout = resolve_lengths(&layouts[node_id], &context, width_base, height_base, flex_basis_base);
+
+        // Size that the node is stretched to if it doesn't have a fixed size (including the padding)
+        let (available_width, available_height) = match parent_id {
+            None => window,
+            Some(_) if is_out_of_flow => (
+                get_out_of_flow_available_size(width_base, layout.left.map(|l| l.0), layout.right.map(|r| r.0), layout.get_horizontal_margin()),
+                get_out_of_flow_available_size(height_base, layout.top.map(|t| t.0), layout.bottom.map(|b| b.0), layout.get_vertical_margin()),
+            ),
+            Some(parent_id) => {
+                let parent = &layouts[parent_id];
+                let is_stretched = is_in_flow(&layout) &&
+                    parent.display.unwrap_or_default() != LayoutDisplay::Grid &&
+                    parent.wrap.unwrap_or_default() != LayoutWrap::Wrap &&
+                    get_align_self(&layout, parent.align_items.unwrap_or_default()) == LayoutAlignItems::Stretch;
+                match parent_axis {
+                    Some(LayoutAxis::Vertical) if is_stretched => (width_base.map(|w| w - layout.get_horizontal_margin()), None),
+                    Some(LayoutAxis::Horizontal) if is_stretched => (None, height_base.map(|h| h - layout.get_vertical_margin())),
+                    _ => (None, None),
+                }
+            },
+        };
+
+        let box_sizing = layout.box_sizing.unwrap_or_default();
+        let (border_width, border_height) = border_widths[node_id];
+        let inner_width = get_definite_inner_size(
+            layout.width.map(|w| w.0.to_pixels()),
+            layout.min_width.map(|w| w.0.to_pixels()),
+            layout.max_width.map(|w| w.0.to_pixels()),
+            layout.get_horizontal_padding() + border_width,
+            box_sizing,
+            available_width,
+        );
+        let inner_height = get_definite_inner_size(
+            layout.height.map(|h| h.0.to_pixels()),
+            layout.min_height.map(|h| h.0.to_pixels()),
+            layout.max_height.map(|h| h.0.to_pixels()),
+            layout.get_vertical_padding() + border_height,
+            box_sizing,
+            available_height,
+        );
+
+        // The gaps and tracks are relative to the size of the container itself
+        resolve_container_lengths(&mut layout, &context, inner_width, inner_height);
+
+        inner_sizes[node_id] = (inner_width, inner_height);
+        layouts[node_id] = layout;
+    }
+
+    layouts
+}
+
+/// Resolves the sizes, padding, margin, offsets and `flex-basis` of a node
+fn resolve_lengths(
+    layout: &RectLayout,
+    context: &RelativeSizeContext,
+    width_base: Option<f32>,
+    height_base: Option<f32>,
+    flex_basis_base: Option<f32>,
+) -> RectLayout {
+
+    use azul_css::{LayoutTop, LayoutBottom, LayoutLeft, LayoutRight, LayoutFlexBasis};
+
+    let mut layout = layout.clone();
+
+    // A `calc()` expression replaces the plain value of the same property
+    macro_rules! resolve_size_field {($field:ident, $struct:ident, $property_type:ident, $percent_base:expr) => (
+        layout.$field = match layout.calc_sizes.get(&CssPropertyType::$property_type) {
+            Some(calc) => resolve_calc_size(calc, context, $percent_base),
+            None => layout.$field.and_then(|size| resolve_size(size.0, context, $percent_base)),
+        }.map($struct::px);
+    )}
+
+    resolve_size_field!(width, LayoutWidth, Width, width_base);
+    resolve_size_field!(min_width, LayoutMinWidth, MinWidth, width_base);
+    resolve_size_field!(max_width, LayoutMaxWidth, MaxWidth, width_base);
+    resolve_size_field!(height, LayoutHeight, Height, height_base);
+    resolve_size_field!(min_height, LayoutMinHeight, MinHeight, height_base);
+    resolve_size_field!(max_height, LayoutMaxHeight, MaxHeight, height_base);
+    layout.calc_sizes.clear();
+
+    // Percentages of the padding and margin always refer to the width, even for the top and bottom
+    let resolve_spacing = |value: Option<PixelValue>| value.map(|value| PixelValue::px(resolve_size(value, context, width_base).unwrap_or(0.0)));
+
+    layout.padding = layout.padding.map(|padding| LayoutPadding {
+        top: resolve_spacing(padding.top),
+        bottom: resolve_spacing(padding.bottom),
+        left: resolve_spacing(padding.left),
+        right: resolve_spacing(padding.right),
+    });
+
+    layout.margin = layout.margin.map(|margin| LayoutMargin {
+        top: resolve_spacing(margin.top),
+        bottom: resolve_spacing(margin.bottom),
+        left: resolve_spacing(margin.left),
+        right: resolve_spacing(margin.right),
+    });
+
+    layout.top = layout.top.and_then(|top| resolve_size(top.0, context, height_base)).map(LayoutTop::px);
+    layout.bottom = layout.bottom.and_then(|bottom| resolve_size(bottom.0, context, height_base)).map(LayoutBottom::px);
+    layout.left = layout.left.and_then(|left| resolve_size(left.0, context, width_base)).map(LayoutLeft::px);
+    layout.right = layout.right.and_then(|right| resolve_size(right.0, context, width_base)).map(LayoutRight::px);
+
+    layout.flex_basis = layout.flex_basis.map(|flex_basis| match flex_basis {
+        LayoutFlexBasis::Exact(basis) => match resolve_size(basis, context, flex_basis_base) {
+            Some(basis) => LayoutFlexBasis::Exact(PixelValue::px(basis)),
+            None => LayoutFlexBasis::Auto,
+        },
+        LayoutFlexBasis::Auto => LayoutFlexBasis::Auto,
+    });
+
+    layout
+}
+
+/// Resolves the gaps and the fixed grid tracks of a container against its own inner size
+fn resolve_container_lengths(
+    layout: &mut RectLayout,
+    context: &RelativeSizeContext,
+    inner_width: Option<f32>,
+    inner_height: Option<f32>,
+) {
+    use azul_css::{LayoutColumnGap, LayoutRowGap, LayoutGridGap, LayoutGridTemplate};
+
+    let resolve_gap = |gap: PixelValue, percent_base: Option<f32>| PixelValue::px(resolve_size(gap, context, percent_base).unwrap_or(0.0));
+    let resolve_template = |template: &LayoutGridTemplate, percent_base: Option<f32>| LayoutGridTemplate(template.0.iter().map(|track| match track {
+        GridTrackSize::Fixed(size) => match resolve_size(*size, context, percent_base) {
+            Some(size) => GridTrackSize::Fixed(PixelValue::px(size)),
+            None => GridTrackSize::Auto,
+        },
+        other => *other,
+    }).collect());
+
+    layout.column_gap = layout.column_gap.map(|gap| LayoutColumnGap(resolve_gap(gap.0, inner_width)));
+    layout.row_gap = layout.row_gap.map(|gap| LayoutRowGap(resolve_gap(gap.0, inner_height)));
+    layout.grid_gap = layout.grid_gap.map(|gap| LayoutGridGap {
+        row: resolve_gap(gap.row, inner_height),
+        column: resolve_gap(gap.column, inner_width),
+    });
+    layout.grid_template_columns = layout.grid_template_columns.as_ref().map(|template| resolve_template(template, inner_width));
+    layout.grid_template_rows = layout.grid_template_rows.as_ref().map(|template| resolve_template(template, inner_height));
+}
+
+/// Returns the size that an out-of-flow node fills if it doesn't have a fixed size (see
+/// `distribute_space_to_out_of_flow_children`): its containing block, or the space between
+/// its offsets if both are set
+fn get_out_of_flow_available_size(
+    containing_block_size: Option<f32>,
+    start_offset: Option<PixelValue>,
+    end_offset: Option<PixelValue>,
+    margin: f32,
+) -> Option<f32> {
+    match (start_offset, end_offset) {
+        (Some(start), Some(end)) => containing_block_size.map(|size| size - start.to_pixels() - end.to_pixels() - margin),
+        _ => containing_block_size,
+    }
+}
+
+/// Returns the inner size (without the padding and border) of a node along one axis if it is known
+/// before the layout is solved: either the node has a fixed size or it fills the `available_size`.
+/// The sizes of a node are already resolved to pixels.
+fn get_definite_inner_size(
+    size: Option<f32>,
+    min_size: Option<f32>,
+    max_size: Option<f32>,
+    padding_and_border: f32,
+    box_sizing: LayoutBoxSizing,
+    available_size: Option<f32>,
+) -> Option<f32> {
+
+    // The sizes of `box-sizing: content-box` nodes don't include the padding and border
+    let size_padding = if box_sizing == LayoutBoxSizing::ContentBox { 0.0 } else { padding_and_border };
+
+    let inner_size = match size {
+        Some(size) => size - size_padding,
+        None => available_size? - padding_and_border,
+    };
+
+    // If the min-size is larger than the max-size, the max-size wins
+    let inner_size = min_size.map(|min| inner_size.max(min - size_padding)).unwrap_or(inner_size);
+    let inner_size = max_size.map(|max| inner_size.min(max - size_padding)).unwrap_or(inner_size);
+
+    Some(inner_size.max(0.0))
+}
+
+/// Returns the horizontal and vertical width of the border of a node in pixels -
+/// borders with the style `none` or `hidden` don't take up any space
+fn get_border_widths(style: &RectStyle) -> (f32, f32) {
+
+    fn border_width(side: Option<StyleBorderSide>) -> f32 {
+        side.map(|side| match side.border_style {
+            BorderStyle::None | BorderStyle::Hidden => 0.0,
+            _ => side.border_width.to_pixels(),
+        }).unwrap_or(0.0)
+    }
+
+    let border = style.border.unwrap_or_default();
+    (border_width(border.left) + border_width(border.right), border_width(border.top) + border_width(border.bottom))
+}
+
+/// The solver treats all sizes as `box-sizing: border-box` - for nodes with
+/// `box-sizing: content-box`, the padding and border (see `get_border_widths`) are added to the sizes
+fn apply_box_sizing(layout: &RectLayout, border_widths: (f32, f32)) -> RectLayout {
+
+    let mut layout = layout.clone();
+
+    if layout.box_sizing.unwrap_or_default() != LayoutBoxSizing::ContentBox {
+        return layout;
+    }
+
+    let (border_width, border_height) = border_widths;
+    let horizontal = layout.get_horizontal_padding() + border_width;
+    let vertical = layout.get_vertical_padding() + border_height;
+
+    layout.width = layout.width.map(|width| LayoutWidth::px(width.0.to_pixels() + horizontal));
+    layout.min_width = layout.min_width.map(|min_width| LayoutMinWidth::px(min_width.0.to_pixels() + horizontal));
+    layout.max_width = layout.max_width.map(|max_width| LayoutMaxWidth::px(max_width.0.to_pixels() + horizontal));
+    layout.height = layout.height.map(|height| LayoutHeight::px(height.0.to_pixels() + vertical));
+    layout.min_height = layout.min_height.map(|min_height| LayoutMinHeight::px(min_height.0.to_pixels() + vertical));
+    layout.max_height = layout.max_height.map(|max_height| LayoutMaxHeight::px(max_height.0.to_pixels() + vertical));
+
+    layout
+}
+
+/// `min-content` and `max-content` width of a node
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub(crate) struct IntrinsicWidth {
+    /// Narrowest width without overflowing, i.e. the width of the longest word of a text
+    pub min_content: f32,
+    /// Width if none of the content is wrapped
+    pub max_content: f32,
+}
+
+/// Calculates the intrinsic widths (including the padding) of all nodes from the inside out:
+/// a node is as wide as its content (`content_widths`, the size of the text or image of a node)
+/// or its in-flow children. A fixed `width`, `min-width` or `max-width` overrides the content size.
+fn get_intrinsic_widths(
+    node_hierarchy: &NodeHierarchy,
+    layout_arena: &NodeDataContainer<RectLayout>,
+    content_widths: &NodeDataContainer<Option<IntrinsicWidth>>,
+) -> NodeDataContainer<IntrinsicWidth> {
+
+    let mut intrinsic_widths = layout_arena.transform(|_, _| IntrinsicWidth::default());
+
+    // Parents always have a lower index than their children, so
+    // all children are solved before their parent is visited
+    for node_id in (0..layout_arena.len()).rev().map(NodeId::new) {
+
+        let layout = &layout_arena[node_id];
+
+        let content = content_widths[node_id].unwrap_or_else(|| {
+            get_children_intrinsic_width(node_id, node_hierarchy, layout_arena, &intrinsic_widths)
+        });
+
+        let padding = layout.get_horizontal_padding();
+        let mut intrinsic_width = IntrinsicWidth {
+            min_content: content.min_content + padding,
+            max_content: content.max_content + padding,
+        };
+
+        match (layout.width.map(|width| width.0.to_pixels()), layout.intrinsic_width) {
+            (Some(width), _) => intrinsic_width = IntrinsicWidth { min_content: width, max_content: width },
+            (None, Some(LayoutIntrinsicWidth::MinContent)) => intrinsic_width.max_content = intrinsic_width.min_content,
+            (None, Some(LayoutIntrinsicWidth::MaxContent)) => intrinsic_width.min_content = intrinsic_width.max_content,
+            _ => { },
+        }
+
+        if let Some(max_width) = layout.max_width.map(|max_width| max_width.0.to_pixels()) {
+            intrinsic_width.min_content = intrinsic_width.min_content.min(max_width);
+            intrinsic_width.max_content = intrinsic_width.max_content.min(max_width);
+        }
+
+        if let Some(min_width) = layout.min_width.map(|min_width| min_width.0.to_pixels()) {
+            intrinsic_width.min_content = intrinsic_width.min_content.max(min_width);
+            intrinsic_width.max_content = intrinsic_width.max_content.max(min_width);
+        }
+
+        intrinsic_widths[node_id] = intrinsic_width;
+    }
+
+    intrinsic_widths
+}
+
+/// Returns the intrinsic width of the in-flow children of a node (including their margins):
+/// the items of a `flex-direction: row` container are next to each other (unless they wrap),
+/// the items of a `flex-direction: column` container are below each other. The items of a
+/// grid are distributed evenly to the columns they span.
+fn get_children_intrinsic_width(
+    node_id: NodeId,
+    node_hierarchy: &NodeHierarchy,
+    layout_arena: &NodeDataContainer<RectLayout>,
+    intrinsic_widths: &NodeDataContainer<IntrinsicWidth>,
+) -> IntrinsicWidth {
+
+    use azul_css::{LayoutAxis, LayoutWrap};
+
+    let layout = &layout_arena[node_id];
+
+    let with_margin = |child_id: NodeId| {
+        let margin = layout_arena[child_id].get_horizontal_margin();
+        IntrinsicWidth {
+            min_content: intrinsic_widths[child_id].min_content + margin,
+            max_content: intrinsic_widths[child_id].max_content + margin,
+        }
+    };
+
+    if layout.display.unwrap_or_default() == LayoutDisplay::Grid {
+
+        let gap = layout.grid_gap.map(|gap| gap.column).or_else(|| layout.column_gap.map(|gap| gap.0)).map(|gap| gap.to_pixels()).unwrap_or(0.0);
+        let template = layout.grid_template_columns.as_ref().map(|template| template.0.as_slice()).unwrap_or(&[]);
+
+        let items = place_grid_items(node_id, node_hierarchy, layout_arena);
+        let column_count = items.iter().map(|(_, area)| area.column_start + area.column_span).max().unwrap_or(0).max(template.len());
+        let mut columns = vec![IntrinsicWidth::default(); column_count];
+
+        for (child_id, area) in &items {
+            let item = with_margin(*child_id);
+            let span = area.column_span as f32;
+            for column in &mut columns[area.column_start..(area.column_start + area.column_span)] {
+                column.min_content = column.min_content.max(item.min_content / span);
+                column.max_content = column.max_content.max(item.max_content / span);
+            }
+        }
+
+        // Fixed columns don't depend on their items
+        for (column, track) in columns.iter_mut().zip(template) {
+            if let GridTrackSize::Fixed(size) = track {
+                let size = size.to_pixels();
+                *column = IntrinsicWidth { min_content: size, max_content: size };
+            }
+        }
+
+        let gaps = gap * column_count.saturating_sub(1) as f32;
+        return IntrinsicWidth {
+            min_content: columns.iter().map(|column| column.min_content).sum::<f32>() + gaps,
+            max_content: columns.iter().map(|column| column.max_content).sum::<f32>() + gaps,
+        };
+    }
+
+    let items = get_flex_items(node_id, node_hierarchy, layout_arena).into_iter().map(with_margin).collect::<Vec<_>>();
+    let largest_min_content = items.iter().map(|item| item.min_content).fold(0.0, f32::max);
+
+    if layout.direction.unwrap_or_default().get_axis() == LayoutAxis::Horizontal {
+        let gaps = layout.column_gap.map(|gap| gap.0.to_pixels()).unwrap_or(0.0) * items.len().saturating_sub(1) as f32;
+        IntrinsicWidth {
+            // If the items can wrap, every item can be put into its own line
+            min_content: if layout.wrap.unwrap_or_default() == LayoutWrap::Wrap {
+                largest_min_content
+            } else {
+                items.iter().map(|item| item.min_content).sum::<f32>() + gaps
+            },
+            max_content: items.iter().map(|item| item.max_content).sum::<f32>() + gaps,
+        }
+    } else {
+        IntrinsicWidth {
+            min_content: largest_min_content,
+            max_content: items.iter().map(|item| item.max_content).fold(0.0, f32::max),
+        }
+    }
+}
+
+/// Resolves `width: min-content` and `width: max-content` to the intrinsic width of the node.
+/// `width: fit-content` sets the `min-width` to `min-content` and the `max-width` to `max-content`
+/// (unless they are already set), so that the node fills the available space, but only up to its content.
+fn apply_intrinsic_width(layout: &RectLayout, intrinsic_width: IntrinsicWidth) -> RectLayout {
+
+    let mut layout = layout.clone();
+
+    // A `width` takes precedence (i.e. if it was set by an animation)
+    if layout.width.is_some() {
+        return layout;
+    }
+
+    match layout.intrinsic_width {
+        Some(LayoutIntrinsicWidth::MinContent) => layout.width = Some(LayoutWidth::px(intrinsic_width.min_content)),
+        Some(LayoutIntrinsicWidth::MaxContent) => layout.width = Some(LayoutWidth::px(intrinsic_width.max_content)),
+        Some(LayoutIntrinsicWidth::FitContent) => {
+            if layout.min_width.is_none() {
+                layout.min_width = Some(LayoutMinWidth::px(intrinsic_width.min_content));
+            }
+            if layout.max_width.is_none() {
+                layout.max_width = Some(LayoutMaxWidth::px(intrinsic_width.max_content));
+            }
+        },
+        None => { },
+    }
+
+    layout
 }
 
 /// Returns the solved widths of the items in a BTree form
+///
+/// `content_intrinsic_widths` are the `min-content` and `max-content` widths of the texts and images
 pub(crate) fn solve_flex_layout_width<'a>(
     node_hierarchy: &NodeHierarchy,
     display_rectangles: &NodeDataContainer<DisplayRectangle<'a>>,
     preferred_widths: &NodeDataContainer<Option<f32>>,
-    window_width: f32
+    content_intrinsic_widths: &NodeDataContainer<Option<IntrinsicWidth>>,
+    window_size: LayoutSize,
 ) -> SolvedWidthLayout {
-    let layout_only_arena = display_rectangles.transform(|node, _| node.layout);
-    let mut width_calculated_arena = NodeDataContainer::<WidthCalculatedRect>::from_rect_layout_arena(&layout_only_arena, preferred_widths);
+    let font_sizes = display_rectangles.transform(|node, _| get_font_size(&node.style));
+    let border_widths = display_rectangles.transform(|node, _| get_border_widths(&node.style));
+    let resolved_arena = resolve_relative_lengths(node_hierarchy, display_rectangles.transform(|node, _| node.layout.clone()), &font_sizes, &border_widths, window_size);
+    let box_sized_arena = resolved_arena.transform(|layout, node_id| apply_box_sizing(layout, border_widths[node_id]));
+    let intrinsic_widths = get_intrinsic_widths(node_hierarchy, &box_sized_arena, content_intrinsic_widths);
+    let layout_only_arena = box_sized_arena.transform(|layout, node_id| apply_intrinsic_width(layout, intrinsic_widths[node_id]));
+    // Flex items don't shrink below their `min-content` width (unless they clip their content)
+    let min_content_widths = layout_only_arena.transform(|layout, node_id| {
+        match layout.overflow.and_then(|overflow| overflow.horizontal) {
+            Some(Overflow::Hidden) | Some(Overflow::Scroll) => 0.0,
+            _ => intrinsic_widths[node_id].min_content,
+        }
+    });
+    let mut width_calculated_arena = NodeDataContainer::<WidthCalculatedRect>::from_rect_layout_arena(&layout_only_arena, preferred_widths, &min_content_widths);
     let non_leaf_nodes_sorted_by_depth = node_hierarchy.get_parents_sorted_by_depth();
-    width_calculated_arena.bubble_preferred_widths_to_parents(node_hierarchy, &layout_only_arena, &non_leaf_nodes_sorted_by_depth);
-    width_calculated_arena.apply_flex_grow(node_hierarchy, &layout_only_arena, &non_leaf_nodes_sorted_by_depth, window_width);
+    // The widths are solved first, so the lines of the `flex-direction: column` containers aren't known yet
+    let no_cross_axis_lines = BTreeMap::new();
+    width_calculated_arena.bubble_preferred_widths_to_parents(node_hierarchy, &layout_only_arena, &non_leaf_nodes_sorted_by_depth, &no_cross_axis_lines);
+    let containers = width_calculated_arena.apply_flex_grow(node_hierarchy, &layout_only_arena, &non_leaf_nodes_sorted_by_depth, window_size.width, &no_cross_axis_lines);
     let solved_widths = width_calculated_arena.transform(|node, _| node.solved_result());
-    SolvedWidthLayout { solved_widths , layout_only_arena, non_leaf_nodes_sorted_by_depth }
+    SolvedWidthLayout { solved_widths , layout_only_arena, non_leaf_nodes_sorted_by_depth, containers }
 }
 
 /// Returns the solved height of the items in a BTree form
@@ -773,11 +1838,15 @@ pub(crate) fn solve_flex_layout_height(
     window_height: f32
 ) -> SolvedHeightLayout {
     let SolvedWidthLayout { layout_only_arena, .. } = solved_widths;
-    let mut height_calculated_arena = NodeDataContainer::<HeightCalculatedRect>::from_rect_layout_arena(&layout_only_arena, preferred_heights);
-    height_calculated_arena.bubble_preferred_heights_to_parents(node_hierarchy, &layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth);
-    height_calculated_arena.apply_flex_grow(node_hierarchy, &layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth, window_height);
+    // TODO: The automatic minimum height of flex items (`min-height: auto`) isn't implemented yet
+    let min_content_heights = layout_only_arena.transform(|_, _| 0.0);
+    let mut height_calculated_arena = NodeDataContainer::<HeightCalculatedRect>::from_rect_layout_arena(&layout_only_arena, preferred_heights, &min_content_heights);
+    // The lines of the `flex-direction: row` containers are known from solving the widths
+    let row_lines = &solved_widths.containers.flex_lines;
+    height_calculated_arena.bubble_preferred_heights_to_parents(node_hierarchy, &layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth, row_lines);
+    let containers = height_calculated_arena.apply_flex_grow(node_hierarchy, &layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth, window_height, row_lines);
     let solved_heights = height_calculated_arena.transform(|node, _| node.solved_result());
-    SolvedHeightLayout { solved_heights }
+    SolvedHeightLayout { solved_heights, containers }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
@@ -794,20 +1863,29 @@ macro_rules! get_position {
  $min_width:ident,
  $left:ident,
  $right:ident,
- $axis:ident
+ $axis:ident,
+ $grid_start:ident,
+ $gap_field:ident
 ) => (
 
 /// Traverses along the DOM and solve for the X or Y position
+///
+/// `cross_axis_containers` are the solved containers of the other axis: for the X position,
+/// the lines of a wrapping `flex-direction: column` container are only known after the heights are solved.
+/// `window_width` is the width of the window, which `position: fixed` nodes are positioned against.
 fn $fn_name(
     node_hierarchy: &NodeHierarchy,
     node_data: &NodeDataContainer<RectLayout>,
     non_leaf_nodes: &[(usize, NodeId)],
-    solved_widths: &$width_layout)
+    solved_widths: &$width_layout,
+    cross_axis_containers: &SolvedContainers,
+    window_width: f32)
 -> NodeDataContainer<$height_solved_position>
 {
     fn determine_child_x_absolute(
         child_id: NodeId,
-        positioned_node_stack: &[NodeId],
+        containing_block: Option<NodeId>,
+        window_width: f32,
         arena_data: &NodeDataContainer<RectLayout>,
         arena_solved_data: &mut NodeDataContainer<$height_solved_position>,
         solved_widths: &$width_layout,
@@ -822,132 +1900,66 @@ fn $fn_name(
         let child_margin_left = child_margin.$left.map(|x| x.to_pixels()).unwrap_or(0.0);
         let child_margin_right = child_margin.$right.map(|x| x.to_pixels()).unwrap_or(0.0);
 
-        let zero_node = NodeId::new(0);
-        let last_relative_node_id = positioned_node_stack.get(positioned_node_stack.len() - 1).unwrap_or(&zero_node);
-
-        let last_relative_node = arena_data[*last_relative_node_id];
-        let last_relative_padding = last_relative_node.padding.unwrap_or_default();
-        let last_relative_padding_left = last_relative_padding.$left.map(|x| x.to_pixels()).unwrap_or(0.0);
-        let last_relative_padding_right = last_relative_padding.$right.map(|x| x.to_pixels()).unwrap_or(0.0);
-
-        let last_relative_node_x = arena_solved_data[*last_relative_node_id].0 + last_relative_padding_left;
-        let last_relative_node_inner_width = {
-            let last_relative_node = &solved_widths.$solved_widths_field[*last_relative_node_id];
-            last_relative_node.$min_width + last_relative_node.space_added - (last_relative_padding_left + last_relative_padding_right)
-        };
-
-        let child_left = &arena_data[child_id].$left.map(|s| s.0.to_pixels());
-        let child_right = &arena_data[child_id].$right.map(|s| s.0.to_pixels());
-
-        if let Some(child_right) = child_right {
-            // align right / bottom of last relative parent
-            arena_solved_data[child_id].0 =
-                last_relative_node_x
-                + last_relative_node_inner_width
-                - child_width_with_padding
-                - child_margin_right
-                - child_right;
-        } else {
-            // align left / top of last relative parent
-            arena_solved_data[child_id].0 =
-                last_relative_node_x
-                + child_margin_left
-                + child_left.unwrap_or(0.0);
-        }
-    }
-
-    fn determine_child_x_along_main_axis(
-        parent_id: NodeId,
-        main_axis_alignment: LayoutJustifyContent,
-        arena_data: &NodeDataContainer<RectLayout>,
-        arena_solved_data: &mut NodeDataContainer<$height_solved_position>,
-        solved_widths: &$width_layout,
-        child_id: NodeId,
-        parent_x_position: f32,
-        parent_inner_width: f32,
-        sum_x_of_children_so_far: &mut f32,
-        positioned_node_stack: &[NodeId],
-    ) {
-        use azul_css::LayoutJustifyContent::*;
+        let (last_relative_node_x, last_relative_node_inner_width) = match containing_block {
+            Some(last_relative_node_id) => {
+                let last_relative_node = &arena_data[last_relative_node_id];
+                let last_relative_padding = last_relative_node.padding.unwrap_or_default();
+                let last_relative_padding_left = last_relative_padding.$left.map(|x| x.to_pixels()).unwrap_or(0.0);
+                let last_relative_padding_right = last_relative_padding.$right.map(|x| x.to_pixels()).unwrap_or(0.0);
+
+                let last_relative_node_x = arena_solved_data[last_relative_node_id].0 + last_relative_padding_left;
+                let last_relative_node_inner_width = {
+                    let last_relative_node = &solved_widths.$solved_widths_field[last_relative_node_id];
+                    last_relative_node.$min_width + last_relative_node.space_added - (last_relative_padding_left + last_relative_padding_right)
+                };
 
-        let child_width_with_padding = {
-            let child_node = &solved_widths.$solved_widths_field[child_id];
-            child_node.$min_width + child_node.space_added
+                (last_relative_node_x, last_relative_node_inner_width)
+            },
+            // `position: fixed`: the origin of the window is added on top of all positions later on
+            None => (0.0, window_width),
         };
 
-        // width: increase X according to the main axis, Y according to the cross_axis
-        let child_node = &arena_data[child_id];
-        let child_margin = child_node.margin.unwrap_or_default();
-        let child_margin_left = child_margin.$left.map(|x| x.to_pixels()).unwrap_or(0.0);
-        let child_margin_right = child_margin.$right.map(|x| x.to_pixels()).unwrap_or(0.0);
-
-        if child_node.position.unwrap_or_default() == LayoutPosition::Absolute {
-            determine_child_x_absolute(
-                child_id,
-                positioned_node_stack,
-                arena_data,
-                arena_solved_data,
-                solved_widths
-            );
-        } else {
-            // X position of the top left corner
-            // WARNING: End has to be added after all children!
-            let x_of_top_left_corner = match main_axis_alignment {
-                Start | End => {
-                    parent_x_position + *sum_x_of_children_so_far + child_margin_left
-                },
-                Center => {
-                    parent_x_position
-                    + ((parent_inner_width / 2.0)
-                    - ((*sum_x_of_children_so_far + child_margin_right + child_width_with_padding) / 2.0))
-                },
-                SpaceBetween => {
-                    parent_x_position // TODO!
-                },
-                SpaceAround => {
-                    parent_x_position // TODO!
-                },
-            };
-
-            arena_solved_data[child_id].0 = x_of_top_left_corner;
-            *sum_x_of_children_so_far += child_margin_right + child_width_with_padding + child_margin_left;
-        }
-    }
-
-    fn determine_child_x_along_cross_axis(
-        arena_data: &NodeDataContainer<RectLayout>,
-        solved_widths: &$width_layout,
-        child_id: NodeId,
-        positioned_node_stack: &[NodeId],
-        arena_solved_data: &mut NodeDataContainer<$height_solved_position>,
-        parent_x_position: f32)
-    {
-        let child_node = &arena_data[child_id];
-        let child_margin_left = child_node.margin.unwrap_or_default().$left.map(|x| x.to_pixels()).unwrap_or(0.0);
+        let child_left = &arena_data[child_id].$left.map(|s| s.0.to_pixels());
+        let child_right = &arena_data[child_id].$right.map(|s| s.0.to_pixels());
 
-        if child_node.position.unwrap_or_default() == LayoutPosition::Absolute {
-            determine_child_x_absolute(
-                child_id,
-                positioned_node_stack,
-                arena_data,
-                arena_solved_data,
-                solved_widths
-            );
+        if let Some(child_right) = child_right {
+            // align right / bottom of last relative parent
+            arena_solved_data[child_id].0 =
+                last_relative_node_x
+                + last_relative_node_inner_width
+                - child_width_with_padding
+                - child_margin_right
+                - child_right;
         } else {
-            arena_solved_data[child_id].0 = parent_x_position + child_margin_left;
+            // align left / top of last relative parent
+            arena_solved_data[child_id].0 =
+                last_relative_node_x
+                + child_margin_left
+                + child_left.unwrap_or(0.0);
         }
     }
 
-    use azul_css::{LayoutAxis, LayoutJustifyContent};
+    use azul_css::{LayoutAxis, LayoutWrap};
 
-    let mut arena_solved_data = NodeDataContainer::new(vec![$height_solved_position(0.0); node_data.len()]);
+    // Width of a node, including its padding, but without its margin
+    let get_width_with_padding = |child_id: NodeId| {
+        let child_node = &solved_widths.$solved_widths_field[child_id];
+        child_node.$min_width + child_node.space_added
+    };
 
-    // Stack of the positioned nodes (nearest relative or absolute positioned node)
-    let mut positioned_node_stack = vec![NodeId::new(0)];
+    // Width of a node, including its padding and margin
+    let get_outer_width = |child_id: NodeId| {
+        let child_margin = node_data[child_id].margin.unwrap_or_default();
+        child_margin.$left.map(|x| x.to_pixels()).unwrap_or(0.0)
+        + get_width_with_padding(child_id)
+        + child_margin.$right.map(|x| x.to_pixels()).unwrap_or(0.0)
+    };
+
+    let mut arena_solved_data = NodeDataContainer::new(vec![$height_solved_position(0.0); node_data.len()]);
 
     for (_node_depth, parent_id) in non_leaf_nodes {
 
-        let parent_node = node_data[*parent_id];
+        let parent_node = &node_data[*parent_id];
 
         let parent_padding = parent_node.padding.unwrap_or_default();
         let parent_padding_left = parent_padding.$left.map(|x| x.to_pixels()).unwrap_or(0.0);
@@ -956,101 +1968,123 @@ fn $fn_name(
         let parent_x_position = arena_solved_data[*parent_id].0 + parent_padding_left;
         let parent_direction = parent_node.direction.unwrap_or_default();
 
-        // Push nearest relative or absolute positioned element
-        let parent_is_positioned = parent_node.position.unwrap_or_default() != LayoutPosition::Static;
-        if parent_is_positioned {
-            positioned_node_stack.push(*parent_id);
-        }
-
         let parent_inner_width = {
             let parent_node = &solved_widths.$solved_widths_field[*parent_id];
             parent_node.$min_width + parent_node.space_added - (parent_padding_left + parent_padding_right)
         };
 
+        // The containing blocks are ancestors of the parent, so their position is already solved
+        for child_id in get_out_of_flow_children(*parent_id, node_hierarchy, node_data) {
+            determine_child_x_absolute(
+                child_id,
+                get_containing_block(child_id, node_hierarchy, node_data),
+                window_width,
+                node_data,
+                &mut arena_solved_data,
+                solved_widths
+            );
+        }
+
+        if parent_node.display.unwrap_or_default() == LayoutDisplay::Grid {
+            // Grid items start at the start of their first track
+            let grid_tracks = solved_widths.containers.grid_tracks.get(parent_id);
+
+            for (child_id, area) in place_grid_items(*parent_id, node_hierarchy, node_data) {
+                let child_margin_left = node_data[child_id].margin.unwrap_or_default().$left.map(|x| x.to_pixels()).unwrap_or(0.0);
+                let track_offset = grid_tracks.map(|tracks| tracks.get_offset(area.$grid_start)).unwrap_or(0.0);
+                arena_solved_data[child_id].0 = parent_x_position + track_offset + child_margin_left;
+            }
+
+            continue;
+        }
+
+        let gap = parent_node.$gap_field.map(|gap| gap.0.to_pixels()).unwrap_or(0.0);
+
         if parent_direction.get_axis() == LayoutAxis::$axis {
-            // Along main axis: Take X of parent
-            let main_axis_alignment = node_data[*parent_id].justify_content.unwrap_or_default();
-            let mut sum_x_of_children_so_far = 0.0;
-
-            if parent_direction.is_reverse() {
-                for child_id in parent_id.reverse_children(node_hierarchy) {
-                    determine_child_x_along_main_axis(
-                        *parent_id,
-                        main_axis_alignment,
-                        &node_data,
-                        &mut arena_solved_data,
-                        solved_widths,
-                        child_id,
-                        parent_x_position,
-                        parent_inner_width,
-                        &mut sum_x_of_children_so_far,
-                        &positioned_node_stack,
-                    );
-                }
-            } else {
-                for child_id in parent_id.children(node_hierarchy) {
-                    determine_child_x_along_main_axis(
-                        *parent_id,
-                        main_axis_alignment,
-                        &node_data,
-                        &mut arena_solved_data,
-                        solved_widths,
-                        child_id,
-                        parent_x_position,
-                        parent_inner_width,
-                        &mut sum_x_of_children_so_far,
-                        &positioned_node_stack,
-                    );
+            // Along main axis: the items of each line are placed next to each other,
+            // the free space of the line is distributed according to the `justify-content`
+            let lines = solved_widths.containers.flex_lines.get(parent_id).cloned()
+                .unwrap_or_else(|| vec![get_flex_items(*parent_id, node_hierarchy, node_data)]);
+
+            // For `row-reverse` / `column-reverse`, the lines are filled starting at the end
+            let justify_content = match (parent_node.justify_content.unwrap_or_default(), parent_direction.is_reverse()) {
+                (LayoutJustifyContent::Start, true) => LayoutJustifyContent::End,
+                (LayoutJustifyContent::End, true) => LayoutJustifyContent::Start,
+                (justify_content, _) => justify_content,
+            };
+
+            for mut line in lines {
+                if parent_direction.is_reverse() {
+                    line.reverse();
                 }
-            }
 
-            // If the direction is `flex-end`, we can't add the X position during the iteration,
-            // so we have to "add" the diff to the parent_inner_width at the end
-            let should_align_towards_end =
-                (!parent_direction.is_reverse() && main_axis_alignment == LayoutJustifyContent::End) ||
-                (parent_direction.is_reverse() && main_axis_alignment == LayoutJustifyContent::Start);
-
-            if should_align_towards_end {
-                let diff = parent_inner_width - sum_x_of_children_so_far;
-                for child_id in parent_id.children(node_hierarchy).filter(|ch| {
-                    node_data[*ch].position.unwrap_or_default() != LayoutPosition::Absolute
-                }) {
-                    arena_solved_data[child_id].0 += diff;
+                let used_space = line.iter().map(|child_id| get_outer_width(*child_id)).sum::<f32>()
+                    + gap * line.len().saturating_sub(1) as f32;
+                let (start_offset, space_between_items) = get_flex_free_space_distribution(
+                    justify_content,
+                    parent_inner_width - used_space,
+                    line.len()
+                );
+
+                let mut x = parent_x_position + start_offset;
+
+                for child_id in line {
+                    let child_margin_left = node_data[child_id].margin.unwrap_or_default().$left.map(|x| x.to_pixels()).unwrap_or(0.0);
+                    arena_solved_data[child_id].0 = x + child_margin_left;
+                    x += get_outer_width(child_id) + gap + space_between_items;
                 }
             }
 
         } else {
-            // Along cross axis: Increase X with width of current element
-
-            if parent_direction.is_reverse() {
-                for child_id in parent_id.reverse_children(node_hierarchy) {
-                    determine_child_x_along_cross_axis(
-                        node_data,
-                        solved_widths,
-                        child_id,
-                        &positioned_node_stack,
-                        &mut arena_solved_data,
-                        parent_x_position,
-                    );
-                }
+            // Along cross axis: the lines are placed according to the `align-content`,
+            // the items inside of each line according to their `align-self` / the `align-items`
+            let lines = cross_axis_containers.flex_lines.get(parent_id).cloned()
+                .unwrap_or_else(|| vec![get_flex_items(*parent_id, node_hierarchy, node_data)]);
+
+            let line_sizes = if parent_node.wrap.unwrap_or_default() == LayoutWrap::Wrap {
+                // The lines of a wrapping `flex-direction: column` container are not known
+                // while solving the widths, so they are as wide as their widest item
+                solved_widths.containers.flex_line_sizes.get(parent_id).cloned().unwrap_or_else(|| {
+                    lines.iter().map(|line| {
+                        line.iter().map(|child_id| get_outer_width(*child_id)).fold(0.0, f32::max)
+                    }).collect()
+                })
             } else {
-                for child_id in parent_id.children(node_hierarchy) {
-                    determine_child_x_along_cross_axis(
-                        node_data,
-                        solved_widths,
-                        child_id,
-                        &positioned_node_stack,
-                        &mut arena_solved_data,
-                        parent_x_position,
-                    );
+                vec![parent_inner_width; lines.len()]
+            };
+
+            let align_content = match parent_node.align_content.unwrap_or_default() {
+                LayoutAlignContent::Stretch | LayoutAlignContent::Start => LayoutJustifyContent::Start,
+                LayoutAlignContent::End => LayoutJustifyContent::End,
+                LayoutAlignContent::Center => LayoutJustifyContent::Center,
+                LayoutAlignContent::SpaceBetween => LayoutJustifyContent::SpaceBetween,
+                LayoutAlignContent::SpaceAround => LayoutJustifyContent::SpaceAround,
+            };
+
+            let used_space = line_sizes.iter().sum::<f32>() + gap * lines.len().saturating_sub(1) as f32;
+            let (start_offset, space_between_lines) = get_flex_free_space_distribution(
+                align_content,
+                parent_inner_width - used_space,
+                lines.len()
+            );
+
+            let align_items = parent_node.align_items.unwrap_or_default();
+            let mut line_x = parent_x_position + start_offset;
+
+            for (line, line_size) in lines.iter().zip(line_sizes) {
+                for child_id in line {
+                    let child_margin_left = node_data[*child_id].margin.unwrap_or_default().$left.map(|x| x.to_pixels()).unwrap_or(0.0);
+                    let free_space_in_line = line_size - get_outer_width(*child_id);
+                    let offset_in_line = match get_align_self(&node_data[*child_id], align_items) {
+                        LayoutAlignItems::Stretch | LayoutAlignItems::Start => 0.0,
+                        LayoutAlignItems::Center => free_space_in_line / 2.0,
+                        LayoutAlignItems::End => free_space_in_line,
+                    };
+                    arena_solved_data[*child_id].0 = line_x + offset_in_line + child_margin_left;
                 }
+                line_x += line_size + gap + space_between_lines;
             }
         }
-
-        if parent_is_positioned {
-            positioned_node_stack.pop();
-        }
-
     }
 
     arena_solved_data
@@ -1060,12 +2094,14 @@ fn $fn_name(
 
 fn get_x_positions(
     solved_widths: &SolvedWidthLayout,
+    solved_heights: &SolvedHeightLayout,
     node_hierarchy: &NodeHierarchy,
     origin: LayoutPoint,
+    window_width: f32,
 ) -> NodeDataContainer<HorizontalSolvedPosition>
 {
-    get_position!(get_pos_x, SolvedWidthLayout, HorizontalSolvedPosition, solved_widths, min_width, left, right, Horizontal);
-    let mut arena = get_pos_x(node_hierarchy, &solved_widths.layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth, solved_widths);
+    get_position!(get_pos_x, SolvedWidthLayout, HorizontalSolvedPosition, solved_widths, min_width, left, right, Horizontal, column_start, column_gap);
+    let mut arena = get_pos_x(node_hierarchy, &solved_widths.layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth, solved_widths, &solved_heights.containers, window_width);
 
     // Add the origin on top of the position
     let x = origin.x as f32;
@@ -1081,11 +2117,12 @@ fn get_y_positions(
     solved_heights: &SolvedHeightLayout,
     solved_widths: &SolvedWidthLayout,
     node_hierarchy: &NodeHierarchy,
-    origin: LayoutPoint
+    origin: LayoutPoint,
+    window_height: f32,
 ) -> NodeDataContainer<VerticalSolvedPosition>
 {
-    get_position!(get_pos_y, SolvedHeightLayout, VerticalSolvedPosition, solved_heights, min_height, top, bottom, Vertical);
-    let mut arena = get_pos_y(node_hierarchy, &solved_widths.layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth, solved_heights);
+    get_position!(get_pos_y, SolvedHeightLayout, VerticalSolvedPosition, solved_heights, min_height, top, bottom, Vertical, row_start, row_gap);
+    let mut arena = get_pos_y(node_hierarchy, &solved_widths.layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth, solved_heights, &solved_widths.containers, window_height);
 
     // Add the origin on top of the position
     let y = origin.y as f32;
@@ -1103,7 +2140,7 @@ fn get_content_width<T: Layout>(
         node_id: &NodeId,
         node_type: &NodeType<T>,
         app_resources: &AppResources,
-        positioned_words: &BTreeMap<NodeId, (WordPositions, FontInstanceKey)>,
+        positioned_words: &BTreeMap<NodeId, (WordPositions, Vec<FontInstanceKey>)>,
 ) -> Option<f32> {
     use dom::NodeType::*;
     match node_type {
@@ -1113,11 +2150,37 @@ fn get_content_width<T: Layout>(
     }
 }
 
+/// Returns the `min-content` and `max-content` width of the text or image of a node: an image always
+/// keeps its width, a text can be wrapped until it is as narrow as its longest word
+fn get_content_intrinsic_width<T: Layout>(
+    node_id: &NodeId,
+    node_type: &NodeType<T>,
+    app_resources: &AppResources,
+    scaled_words: &BTreeMap<NodeId, (ScaledWords, Vec<FontInstanceKey>)>,
+    positioned_words: &BTreeMap<NodeId, (WordPositions, Vec<FontInstanceKey>)>,
+) -> Option<IntrinsicWidth> {
+    use dom::NodeType::*;
+    use text_layout::get_min_content_width;
+    match node_type {
+        Image(image_id) => {
+            let image_width = app_resources.get_image_info(image_id)?.descriptor.size.width as f32;
+            Some(IntrinsicWidth { min_content: image_width, max_content: image_width })
+        },
+        Label(_) | Text(_) => {
+            let (scaled_words, _) = scaled_words.get(node_id)?;
+            let (word_positions, _) = positioned_words.get(node_id)?;
+            let min_content = get_min_content_width(scaled_words, &word_positions.text_layout_options);
+            Some(IntrinsicWidth { min_content, max_content: word_positions.content_size.width.max(min_content) })
+        },
+        _ => None,
+    }
+}
+
 fn get_content_height<T: Layout>(
     node_id: &NodeId,
     node_type: &NodeType<T>,
     app_resources: &AppResources,
-    positioned_words: &BTreeMap<NodeId, (WordPositions, FontInstanceKey)>,
+    positioned_words: &BTreeMap<NodeId, (WordPositions, Vec<FontInstanceKey>)>,
     div_width: f32,
 ) -> Option<f32> {
     use dom::NodeType::*;
@@ -1168,9 +2231,24 @@ pub(crate) fn au_to_px(au: Au) -> f32 {
     (au.0 as f32) / (AU_PER_PX as f32) as f32
 }
 
-pub(crate) fn get_font_id(rect_style: &RectStyle) -> &str {
-    let font_id = rect_style.font_family.as_ref().and_then(|family| family.fonts.get(0));
-    font_id.map(|f| f.get_str()).unwrap_or(DEFAULT_FONT_ID)
+/// Returns the families of the `font-family` list, followed by the default font family,
+/// so that the font fallback always ends with a font that is installed on the system
+pub(crate) fn get_font_families<'a>(rect_style: &'a RectStyle) -> impl Iterator<Item = &'a str> {
+    rect_style.font_family.iter()
+        .flat_map(|family| family.fonts.iter().map(|f| f.get_str()))
+        .chain(::std::iter::once(DEFAULT_FONT_ID))
+}
+
+pub(crate) fn get_font_weight(rect_style: &RectStyle) -> StyleFontWeight {
+    rect_style.font_weight.unwrap_or_default()
+}
+
+pub(crate) fn get_font_style(rect_style: &RectStyle) -> StyleFontStyle {
+    rect_style.font_style.unwrap_or_default()
+}
+
+pub(crate) fn get_text_direction(rect_style: &RectStyle) -> StyleTextDirection {
+    rect_style.direction.unwrap_or_default()
 }
 
 pub(crate) fn get_font_size(rect_style: &RectStyle) -> StyleFontSize {
@@ -1190,8 +2268,12 @@ pub struct PositionedRectangle {
 pub struct LayoutResult {
     pub rects: NodeDataContainer<PositionedRectangle>,
     pub word_cache: BTreeMap<NodeId, Words>,
-    pub scaled_words: BTreeMap<NodeId, (ScaledWords, FontInstanceKey)>,
-    pub positioned_word_cache: BTreeMap<NodeId, (WordPositions, FontInstanceKey)>,
+    /// The scaled words of each text node, with the font instance keys of the primary font
+    /// and of the fallback fonts (the index in `ScaledWord::glyph_fonts` refers to this list)
+    pub scaled_words: BTreeMap<NodeId, (ScaledWords, Vec<FontInstanceKey>)>,
+    /// The resolved font and font size that the `scaled_words` of each text node were shaped with
+    pub(crate) scaled_words_fonts: BTreeMap<NodeId, (ImmediateFontId, StyleFontSize)>,
+    pub positioned_word_cache: BTreeMap<NodeId, (WordPositions, Vec<FontInstanceKey>)>,
     pub node_depths: Vec<(usize, NodeId)>,
 }
 
@@ -1205,6 +2287,10 @@ struct InlineText {
 
 /// At this point in time, all font keys, image keys, etc. have
 /// to be already submitted in the RenderApi!
+///
+/// If the layout of the previous frame is given (together with the diff between
+/// the previous and the current DOM), the text of nodes that didn't change
+/// is not broken into words / shaped again.
 pub(crate) fn do_the_layout<'a,'b, T: Layout>(
     node_hierarchy: &NodeHierarchy,
     node_data: &NodeDataContainer<NodeData<T>>,
@@ -1212,6 +2298,7 @@ pub(crate) fn do_the_layout<'a,'b, T: Layout>(
     app_resources: &'b AppResources,
     rect_size: LayoutSize,
     rect_offset: LayoutPoint,
+    previous_layout: Option<(&LayoutResult, &DomDiff)>,
 ) -> LayoutResult {
 
     // Determine what the width for each div would be if the content size didn't matter
@@ -1219,7 +2306,8 @@ pub(crate) fn do_the_layout<'a,'b, T: Layout>(
         node_hierarchy,
         &display_rects,
         &node_data.transform(|_, _| None),
-        rect_size.width as f32,
+        &node_data.transform(|_, _| None),
+        rect_size,
     );
 
     // Determine what the "maximum width" for each div is, except for divs where overflow:visible is set
@@ -1234,9 +2322,10 @@ pub(crate) fn do_the_layout<'a,'b, T: Layout>(
     let inline_text_blocks = BTreeMap::<NodeId, InlineText>::new();
 
     // Resolve cached text IDs or break new, uncached strings into words / text runs
-    let word_cache = create_word_cache(app_resources, node_data);
-    // Scale the words to the correct size - TODO: Caching / GC!
-    let scaled_words = create_scaled_words(app_resources, &word_cache, display_rects);
+    let word_cache = create_word_cache(app_resources, node_data, previous_layout);
+    // Scale the words to the correct size, re-using the scaled words of unchanged nodes
+    let scaled_words_fonts = get_scaled_words_fonts(app_resources, &word_cache, display_rects);
+    let scaled_words = create_scaled_words(app_resources, &word_cache, &scaled_words_fonts, display_rects, previous_layout);
     // Layout all words as if there was no max-width constraint
     let word_positions_no_max_width = create_word_positions(
         &word_cache,
@@ -1253,17 +2342,31 @@ pub(crate) fn do_the_layout<'a,'b, T: Layout>(
         get_content_width(&node_id, &node.node_type, app_resources, &word_positions_no_max_width)
     );
 
+    // Layout all words without any line breaks, for the `max-content` width of the texts
+    let word_positions_max_content = create_word_positions(
+        &word_cache,
+        &scaled_words,
+        display_rects,
+        &BTreeMap::new(),
+        &inline_text_blocks
+    );
+
+    let content_intrinsic_widths = node_data.transform(|node, node_id|
+        get_content_intrinsic_width(&node_id, &node.node_type, app_resources, &scaled_words, &word_positions_max_content)
+    );
+
     // Solve the widths again, this time incorporating the maximum widths
     let solved_widths = solve_flex_layout_width(
         node_hierarchy,
         &display_rects,
         &content_widths,
-        rect_size.width as f32,
+        &content_intrinsic_widths,
+        rect_size,
     );
 
     // Layout all texts again with the resolved width constraints
     let proper_max_widths = solved_widths.solved_widths.linear_iter().map(|node_id| {
-        (node_id, solved_widths.solved_widths[node_id].total() - display_rects[node_id].layout.get_horizontal_padding())
+        (node_id, solved_widths.solved_widths[node_id].total() - solved_widths.layout_only_arena[node_id].get_horizontal_padding())
     }).collect();
 
     // Resolve the word positions relative to each divs upper left corner
@@ -1291,17 +2394,23 @@ pub(crate) fn do_the_layout<'a,'b, T: Layout>(
         rect_size.height as f32,
     );
 
-    let x_positions = get_x_positions(&solved_widths, node_hierarchy, rect_offset.clone());
-    let y_positions = get_y_positions(&solved_heights, &solved_widths, node_hierarchy, rect_offset);
+    let x_positions = get_x_positions(&solved_widths, &solved_heights, node_hierarchy, rect_offset.clone(), rect_size.width);
+    let y_positions = get_y_positions(&solved_heights, &solved_widths, node_hierarchy, rect_offset, rect_size.height);
 
     let layouted_rects = node_data.transform(|_node, node_id| {
+        // Nodes with `display: none` (or inside of such a node) don't take up any space
+        let takes_up_space = node_id.ancestors(node_hierarchy).all(|id| is_displayed(&display_rects[id].layout));
         PositionedRectangle {
             bounds: LayoutRect::new(
                 LayoutPoint::new(x_positions[node_id].0, y_positions[node_id].0),
-                LayoutSize::new(
-                    solved_widths.solved_widths[node_id].total(),
-                    solved_heights.solved_heights[node_id].total(),
-                )
+                if takes_up_space {
+                    LayoutSize::new(
+                        solved_widths.solved_widths[node_id].total(),
+                        solved_heights.solved_heights[node_id].total(),
+                    )
+                } else {
+                    LayoutSize::zero()
+                }
             ),
             content_width: Some(proper_max_widths[&node_id]),
             content_height: content_heights[node_id],
@@ -1312,20 +2421,40 @@ pub(crate) fn do_the_layout<'a,'b, T: Layout>(
         rects: layouted_rects,
         word_cache,
         scaled_words,
+        scaled_words_fonts,
         positioned_word_cache: word_positions_with_max_width,
         node_depths: solved_widths.non_leaf_nodes_sorted_by_depth,
     }
 }
 
+/// Returns the node in the previous layout that has the same content as `node_id`
+/// (`None` if there is no previous layout or the node changed)
+fn get_unchanged_previous_node<'a>(
+    previous_layout: Option<(&'a LayoutResult, &DomDiff)>,
+    node_id: NodeId,
+) -> Option<(&'a LayoutResult, NodeId)> {
+    let (previous_layout, dom_diff) = previous_layout?;
+    let old_node_id = dom_diff.get_unchanged_old_node_id(node_id)?;
+    Some((previous_layout, old_node_id))
+}
+
 fn create_word_cache<T: Layout>(
     app_resources: &AppResources,
     node_data: &NodeDataContainer<NodeData<T>>,
+    previous_layout: Option<(&LayoutResult, &DomDiff)>,
 ) -> BTreeMap<NodeId, Words>
 {
     use text_layout::split_text_into_words;
     node_data
     .linear_iter()
     .filter_map(|node_id| {
+
+        if let Some((previous_layout, old_node_id)) = get_unchanged_previous_node(previous_layout, node_id) {
+            if let Some(words) = previous_layout.word_cache.get(&old_node_id) {
+                return Some((node_id, words.clone()));
+            }
+        }
+
         match &node_data[node_id].node_type {
             NodeType::Label(string) => Some((node_id, split_text_into_words(string.as_str()))),
             NodeType::Text(text_id) => {
@@ -1336,55 +2465,91 @@ fn create_word_cache<T: Layout>(
     }).collect()
 }
 
+/// Resolves the font and the font size of every text node
+fn get_scaled_words_fonts<'a>(
+    app_resources: &AppResources,
+    words: &BTreeMap<NodeId, Words>,
+    display_rects: &NodeDataContainer<DisplayRectangle<'a>>,
+) -> BTreeMap<NodeId, (ImmediateFontId, StyleFontSize)> {
+    words.keys().map(|node_id| {
+        let style = &display_rects[*node_id].style;
+        (*node_id, (app_resources.get_font_id_for_style(&style), get_font_size(&style)))
+    }).collect()
+}
+
 fn create_scaled_words<'a>(
     app_resources: &AppResources,
     words: &BTreeMap<NodeId, Words>,
+    scaled_words_fonts: &BTreeMap<NodeId, (ImmediateFontId, StyleFontSize)>,
     display_rects: &NodeDataContainer<DisplayRectangle<'a>>,
-) -> BTreeMap<NodeId, (ScaledWords, FontInstanceKey)> {
+    previous_layout: Option<(&LayoutResult, &DomDiff)>,
+) -> BTreeMap<NodeId, (ScaledWords, Vec<FontInstanceKey>)> {
 
     use text_layout::words_to_scaled_words;
-    use app_resources::ImmediateFontId;
 
     words.iter().filter_map(|(node_id, words)| {
         let style = &display_rects[*node_id].style;
-        let font_size = get_font_size(&style);
+        let (font_id, font_size) = scaled_words_fonts.get(node_id)?;
+        let font_size = *font_size;
         let font_size_au = font_size_to_au(font_size);
-        let css_font_id = get_font_id(&style);
-        let font_id = match app_resources.get_css_font_id(css_font_id) {
-            Some(s) => ImmediateFontId::Resolved(*s),
-            None => ImmediateFontId::Unresolved(css_font_id.to_string()),
-        };
 
-        let loaded_font = app_resources.get_loaded_font(&font_id)?;
+        let loaded_font = app_resources.get_loaded_font(font_id)?;
         let font_instance_key = loaded_font.font_instances.get(&font_size_au)?;
+        let fallback_fonts = app_resources.get_loaded_fallback_fonts(&style, font_size_au);
+
+        let font_instance_keys = ::std::iter::once(*font_instance_key)
+            .chain(fallback_fonts.iter().map(|(_, _, key)| *key))
+            .collect::<Vec<_>>();
+
+        let direction = get_text_direction(&style);
+
+        // Shaping the text is expensive, so if neither the text nor the fonts changed,
+        // re-use the scaled words from the last frame. The DOM diff doesn't know about
+        // style changes (i.e. hot-reloaded CSS), so the font has to be compared, too.
+        if let Some((previous_layout, old_node_id)) = get_unchanged_previous_node(previous_layout, *node_id) {
+            let previous_words = previous_layout.scaled_words.get(&old_node_id);
+            let previous_font = previous_layout.scaled_words_fonts.get(&old_node_id);
+            if let (Some((scaled_words, previous_font_instance_keys)), Some(previous_font)) = (previous_words, previous_font) {
+                if previous_font.0 == *font_id && previous_font.1 == font_size &&
+                   *previous_font_instance_keys == font_instance_keys &&
+                   scaled_words.direction == direction {
+                    return Some((*node_id, (scaled_words.clone(), font_instance_keys)));
+                }
+            }
+        }
 
         let font_bytes = &loaded_font.font_bytes;
         let font_index = loaded_font.font_index as u32;
+        let fallback_font_bytes = fallback_fonts.iter()
+            .map(|(_, loaded_font, _)| (loaded_font.font_bytes.as_slice(), loaded_font.font_index as u32))
+            .collect::<Vec<_>>();
 
         let scaled_words = words_to_scaled_words(
             words,
             font_bytes,
             font_index,
+            &fallback_font_bytes,
+            direction,
             font_size.0.to_pixels(),
         );
-        Some((*node_id, (scaled_words, *font_instance_key)))
+        Some((*node_id, (scaled_words, font_instance_keys)))
     }).collect()
 }
 
 fn create_word_positions<'a>(
     words: &BTreeMap<NodeId, Words>,
-    scaled_words: &BTreeMap<NodeId, (ScaledWords, FontInstanceKey)>,
+    scaled_words: &BTreeMap<NodeId, (ScaledWords, Vec<FontInstanceKey>)>,
     display_rects: &NodeDataContainer<DisplayRectangle<'a>>,
     max_widths: &BTreeMap<NodeId, PixelSize>,
     inline_texts: &BTreeMap<NodeId, InlineText>,
-) -> BTreeMap<NodeId, (WordPositions, FontInstanceKey)> {
+) -> BTreeMap<NodeId, (WordPositions, Vec<FontInstanceKey>)> {
 
     use text_layout;
 
     words.iter().filter_map(|(node_id, words)| {
 
         let rect = &display_rects[*node_id];
-        let (scaled_words, font_instance_key) = scaled_words.get(&node_id)?;
+        let (scaled_words, font_instance_keys) = scaled_words.get(&node_id)?;
 
         let font_size = get_font_size(&rect.style).0;
         let max_horizontal_width = max_widths.get(&node_id).cloned();
@@ -1401,7 +2566,7 @@ fn create_word_positions<'a>(
             font_size.to_pixels()
         );
 
-        Some((*node_id, (positioned_words, *font_instance_key)))
+        Some((*node_id, (positioned_words, font_instance_keys.clone())))
     }).collect()
 }
 
@@ -1426,7 +2591,9 @@ fn get_text_layout_options(
 pub(crate) fn determine_text_alignment(rect_style: &RectStyle, rect_layout: &RectLayout)
     -> (StyleTextAlignmentHorz, StyleTextAlignmentVert)
 {
-    let mut horz_alignment = StyleTextAlignmentHorz::default();
+    // Right-to-left text starts on the right, unless an alignment is set explicitly
+    let is_rtl = get_text_direction(rect_style) == StyleTextDirection::Rtl;
+    let mut horz_alignment = if is_rtl { StyleTextAlignmentHorz::Right } else { StyleTextAlignmentHorz::default() };
     let mut vert_alignment = StyleTextAlignmentVert::default();
 
     if let Some(align_items) = rect_layout.align_items {
@@ -1442,10 +2609,10 @@ pub(crate) fn determine_text_alignment(rect_style: &RectStyle, rect_layout: &Rec
 
     if let Some(justify_content) = rect_layout.justify_content {
         use azul_css::LayoutJustifyContent;
-        // Horizontal text alignment
-        match justify_content {
-            LayoutJustifyContent::Start => horz_alignment = StyleTextAlignmentHorz::Left,
-            LayoutJustifyContent::End => horz_alignment = StyleTextAlignmentHorz::Right,
+        // Horizontal text alignment - the start of right-to-left text is on the right
+        match (justify_content, is_rtl) {
+            (LayoutJustifyContent::Start, false) | (LayoutJustifyContent::End, true) => horz_alignment = StyleTextAlignmentHorz::Left,
+            (LayoutJustifyContent::End, false) | (LayoutJustifyContent::Start, true) => horz_alignment = StyleTextAlignmentHorz::Right,
             _ => horz_alignment = StyleTextAlignmentHorz::Center,
         }
     }
@@ -1537,7 +2704,7 @@ mod layout_tests {
         let arena = get_testing_hierarchy();
         let mut arena_data = vec![RectLayout::default(); arena.len()];
         for (id, rect) in constraints {
-            arena_data[*id] = *rect;
+            arena_data[*id] = rect.clone();
         }
         (arena, NodeDataContainer { internal: arena_data })
     }
@@ -1619,6 +2786,73 @@ mod layout_tests {
         assert_eq!(determine_preferred_width(&layout, None), WhConstraint::EqualTo(400.0));
     }
 
+    #[test]
+    fn test_resolve_relative_lengths() {
+
+        use azul_css::*;
+
+        let (node_hierarchy, node_data) = get_display_rectangle_arena(&[
+            // The root node fills the window (1000x500)
+            (0, RectLayout::default()),
+            (1, RectLayout {
+                width: Some(LayoutWidth::percent(50.0)),
+                padding: Some(LayoutPadding { left: Some(PixelValue::percent(10.0)), .. Default::default() }),
+                margin: Some(LayoutMargin { top: Some(PixelValue::percent(5.0)), .. Default::default() }),
+                .. Default::default()
+            }),
+            (2, RectLayout {
+                // The height of the parent isn't known before the layout is solved
+                height: Some(LayoutHeight::percent(50.0)),
+                calc_sizes: vec![
+                    (CssPropertyType::Width, CalcPixelValue::from(PixelValue::percent(100.0)) + PixelValue::px(-20.0)),
+                ].into_iter().collect(),
+                .. Default::default()
+            }),
+            (3, RectLayout {
+                width: Some(LayoutWidth::em(2.0)),
+                min_width: Some(LayoutMinWidth::rem(5.0)),
+                max_width: Some(LayoutMaxWidth::vw(10.0)),
+                // Relative to the containing block (the root node), not to the parent
+                position: Some(LayoutPosition::Absolute),
+                left: Some(LayoutLeft::percent(10.0)),
+                .. Default::default()
+            }),
+            (4, RectLayout {
+                width: Some(LayoutWidth::percent(25.0)),
+                margin: Some(LayoutMargin { left: Some(PixelValue::percent(10.0)), .. Default::default() }),
+                .. Default::default()
+            }),
+            (5, RectLayout {
+                // Stretched to the inner width of its parent
+                padding: Some(LayoutPadding { left: Some(PixelValue::percent(50.0)), .. Default::default() }),
+                .. Default::default()
+            }),
+        ]);
+
+        let font_sizes = node_data.transform(|_, node_id| if node_id == NodeId::new(3) { StyleFontSize::px(20.0) } else { StyleFontSize::px(10.0) });
+        let border_widths = node_data.transform(|_, _| (0.0, 0.0));
+        let resolved = resolve_relative_lengths(&node_hierarchy, node_data, &font_sizes, &border_widths, LayoutSize::new(1000.0, 500.0));
+
+        assert_eq!(resolved[NodeId::new(1)].width, Some(LayoutWidth::px(500.0)));
+        assert_eq!(resolved[NodeId::new(1)].padding.unwrap().left, Some(PixelValue::px(100.0)));
+        assert_eq!(resolved[NodeId::new(1)].margin.unwrap().top, Some(PixelValue::px(50.0)));
+
+        // calc(100% - 20px) of the inner width of the parent (500px - 100px padding)
+        assert_eq!(resolved[NodeId::new(2)].width, Some(LayoutWidth::px(380.0)));
+        assert_eq!(resolved[NodeId::new(2)].height, None);
+        assert!(resolved[NodeId::new(2)].calc_sizes.is_empty());
+
+        assert_eq!(resolved[NodeId::new(3)].width, Some(LayoutWidth::px(40.0)));
+        assert_eq!(resolved[NodeId::new(3)].min_width, Some(LayoutMinWidth::px(50.0)));
+        assert_eq!(resolved[NodeId::new(3)].max_width, Some(LayoutMaxWidth::px(100.0)));
+        assert_eq!(resolved[NodeId::new(3)].left, Some(LayoutLeft::px(100.0)));
+
+        assert_eq!(resolved[NodeId::new(4)].width, Some(LayoutWidth::px(95.0)));
+        assert_eq!(resolved[NodeId::new(4)].margin.unwrap().left, Some(PixelValue::px(38.0)));
+
+        assert_eq!(resolved[NodeId::new(5)].padding.unwrap().left, Some(PixelValue::px(200.0)));
+    }
+
     /// Tests that the nodes get filled correctly
     #[test]
     fn test_fill_out_preferred_width() {
@@ -1643,7 +2877,7 @@ mod layout_tests {
         ]);
 
         let preferred_widths = node_data.transform(|_, _| None);
-        let mut width_filled_out_data = NodeDataContainer::<WidthCalculatedRect>::from_rect_layout_arena(&node_data, &preferred_widths);
+        let mut width_filled_out_data = NodeDataContainer::<WidthCalculatedRect>::from_rect_layout_arena(&node_data, &preferred_widths, &node_data.transform(|_, _| 0.0));
 
         // Test some basic stuff - test that `get_flex_basis` works
 
@@ -1683,7 +2917,7 @@ mod layout_tests {
             (2, NodeId::new(2)),
         ]);
 
-        width_filled_out_data.bubble_preferred_widths_to_parents(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth);
+        width_filled_out_data.bubble_preferred_widths_to_parents(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth, &BTreeMap::new());
 
         // This step shouldn't have touched the flex_grow_px
         for node in &width_filled_out_data.internal {
@@ -1723,7 +2957,7 @@ mod layout_tests {
         //    '   '-- 4     -- [] - expecting width to stretch to 80px (half of 160)
         //    '-- 5         -- [] - expecting width to stretch to 554px (754 - 200px max-width of earlier sibling)
 
-        width_filled_out_data.apply_flex_grow(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth, window_width);
+        width_filled_out_data.apply_flex_grow(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth, window_width, &BTreeMap::new());
 
         assert_eq!(width_filled_out_data[NodeId::new(0)].solved_result(), WidthSolvedResult {
             min_width: 40.0,
@@ -1750,4 +2984,324 @@ mod layout_tests {
             space_added: window_width - 200.0,
         });
     }
+
+    #[test]
+    fn test_size_grid_tracks() {
+
+        use super::GridTrack::*;
+
+        // The first `fr` track is larger than its share because of the 300px item in it,
+        // so the second `fr` track only gets the remaining space
+        let tracks = [Auto, Fraction(1.0), Fraction(1.0)];
+        let items = [(0, 1, 50.0), (1, 1, 300.0)];
+        assert_eq!(size_grid_tracks(&tracks, &items, 0.0, None), vec![50.0, 300.0, 0.0]);
+        assert_eq!(size_grid_tracks(&tracks, &items, 0.0, Some(400.0)), vec![50.0, 300.0, 50.0]);
+
+        // Without `fr` tracks, the `auto` tracks are stretched evenly
+        let tracks = [Fixed(100.0), Auto, Auto];
+        let items = [(1, 2, 60.0)];
+        assert_eq!(size_grid_tracks(&tracks, &items, 10.0, None), vec![100.0, 25.0, 25.0]);
+        assert_eq!(size_grid_tracks(&tracks, &items, 10.0, Some(300.0)), vec![100.0, 90.0, 90.0]);
+    }
+
+    #[test]
+    fn test_grid_layout() {
+
+        use azul_css::*;
+
+        // 0                -- [] - 754px
+        // '- 1             -- [max-width: 200px; padding: 20px] - 200px
+        //    '-- 2         -- [display: grid; grid-template-columns: 100px 1fr 3fr; grid-gap: 10px] - 160px
+        //    '   '-- 3     -- [grid-column: 2 / span 2] - spans the two fr columns: 10px + 10px + 30px
+        //    '   '-- 4     -- [] - wraps into the first column of the second row: 100px
+        //    '-- 5
+        let (node_hierarchy, node_data) = get_display_rectangle_arena(&[
+            (0, RectLayout {
+                direction: Some(LayoutDirection::Row),
+                .. Default::default()
+            }),
+            (1, RectLayout {
+                max_width: Some(LayoutMaxWidth(PixelValue::px(200.0))),
+                padding: Some(LayoutPadding { left: Some(PixelValue::px(20.0)), right: Some(PixelValue::px(20.0)), .. Default::default() }),
+                direction: Some(LayoutDirection::Row),
+                .. Default::default()
+            }),
+            (2, RectLayout {
+                display: Some(LayoutDisplay::Grid),
+                grid_template_columns: Some(LayoutGridTemplate(vec![
+                    GridTrackSize::Fixed(PixelValue::px(100.0)),
+                    GridTrackSize::Fraction(FloatValue::new(1.0)),
+                    GridTrackSize::Fraction(FloatValue::new(3.0)),
+                ])),
+                grid_gap: Some(LayoutGridGap { row: PixelValue::px(10.0), column: PixelValue::px(10.0) }),
+                .. Default::default()
+            }),
+            (3, RectLayout {
+                grid_column: Some(LayoutGridPlacement { start: GridLine::Line(2), end: GridLine::Span(2) }),
+                .. Default::default()
+            }),
+        ]);
+
+        assert_eq!(place_grid_items(NodeId::new(2), &node_hierarchy, &node_data), vec![
+            (NodeId::new(3), GridArea { column_start: 1, column_span: 2, row_start: 0, row_span: 1 }),
+            (NodeId::new(4), GridArea { column_start: 0, column_span: 1, row_start: 1, row_span: 1 }),
+        ]);
+
+        let preferred_widths = node_data.transform(|_, _| None);
+        let mut width_filled_out_data = NodeDataContainer::<WidthCalculatedRect>::from_rect_layout_arena(&node_data, &preferred_widths, &node_data.transform(|_, _| 0.0));
+        let non_leaf_nodes_sorted_by_depth = node_hierarchy.get_parents_sorted_by_depth();
+
+        width_filled_out_data.bubble_preferred_widths_to_parents(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth, &BTreeMap::new());
+
+        // The minimum width of the grid is the fixed column plus the two gaps
+        assert_eq!(width_filled_out_data[NodeId::new(2)].min_inner_size_px, 120.0);
+
+        let containers = width_filled_out_data.apply_flex_grow(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth, 754.0, &BTreeMap::new());
+
+        // 160px - 100px - 2 * 10px gap = 40px for 4fr
+        assert_eq!(containers.grid_tracks.get(&NodeId::new(2)), Some(&GridTracks { sizes: vec![100.0, 10.0, 30.0], gap: 10.0 }));
+        assert_eq!(width_filled_out_data[NodeId::new(2)].solved_result().total(), 160.0);
+        assert_eq!(width_filled_out_data[NodeId::new(3)].solved_result().total(), 50.0);
+        assert_eq!(width_filled_out_data[NodeId::new(4)].solved_result().total(), 100.0);
+    }
+
+    #[test]
+    fn test_resolve_flexible_lengths() {
+
+        let item = |base_size, max_size, flex_grow, flex_shrink| FlexItem {
+            base_size,
+            min_size: 0.0,
+            max_size,
+            flex_grow,
+            flex_shrink,
+        };
+
+        // The second item is capped by its max size, the remaining space goes to the first item
+        let items = [item(0.0, f32::MAX, 1.0, 1.0), item(0.0, 100.0, 1.0, 1.0)];
+        assert_eq!(resolve_flexible_lengths(&items, 400.0), vec![300.0, 100.0]);
+
+        // Flex factors that add up to less than 1 don't fill the whole line
+        let items = [item(100.0, f32::MAX, 0.25, 1.0), item(100.0, f32::MAX, 0.0, 1.0)];
+        assert_eq!(resolve_flexible_lengths(&items, 600.0), vec![200.0, 100.0]);
+
+        // Overflowing items shrink in proportion to their base size, items with `flex-shrink: 0` don't shrink
+        let items = [item(300.0, f32::MAX, 1.0, 1.0), item(100.0, f32::MAX, 1.0, 1.0), item(100.0, f32::MAX, 1.0, 0.0)];
+        assert_eq!(resolve_flexible_lengths(&items, 300.0), vec![150.0, 50.0, 100.0]);
+
+        // An item doesn't shrink below its min size (its `min-content` width without a `min-width`),
+        // so the other item shrinks instead
+        let items = [FlexItem { min_size: 150.0, .. item(150.0, f32::MAX, 0.0, 1.0) }, item(150.0, f32::MAX, 0.0, 1.0)];
+        assert_eq!(resolve_flexible_lengths(&items, 200.0), vec![150.0, 50.0]);
+    }
+
+    #[test]
+    fn test_get_intrinsic_widths() {
+
+        use azul_css::*;
+
+        // 0                -- [] - 320px
+        // '- 1             -- [width: max-content; padding: 10px] - 320px
+        //    '-- 2         -- [flex-wrap: wrap] - min-content: 100px (largest item), max-content: 300px
+        //    '   '-- 3     -- text - min-content: 50px, max-content: 200px
+        //    '   '-- 4     -- [width: 100px]
+        //    '-- 5         -- empty
+        let (node_hierarchy, node_data) = get_display_rectangle_arena(&[
+            (1, RectLayout {
+                intrinsic_width: Some(LayoutIntrinsicWidth::MaxContent),
+                padding: Some(LayoutPadding { left: Some(PixelValue::px(10.0)), right: Some(PixelValue::px(10.0)), .. Default::default() }),
+                direction: Some(LayoutDirection::Row),
+                .. Default::default()
+            }),
+            (2, RectLayout {
+                direction: Some(LayoutDirection::Row),
+                wrap: Some(LayoutWrap::Wrap),
+                .. Default::default()
+            }),
+            (4, RectLayout {
+                width: Some(LayoutWidth(PixelValue::px(100.0))),
+                .. Default::default()
+            }),
+        ]);
+
+        let content_widths = node_data.transform(|_, node_id| if node_id == NodeId::new(3) {
+            Some(IntrinsicWidth { min_content: 50.0, max_content: 200.0 })
+        } else {
+            None
+        });
+        let intrinsic_widths = get_intrinsic_widths(&node_hierarchy, &node_data, &content_widths);
+
+        let expected = [(320.0, 320.0), (320.0, 320.0), (100.0, 300.0), (50.0, 200.0), (100.0, 100.0), (0.0, 0.0)];
+        for (node_id, (min_content, max_content)) in expected.iter().enumerate() {
+            assert_eq!(intrinsic_widths[NodeId::new(node_id)], IntrinsicWidth { min_content: *min_content, max_content: *max_content });
+        }
+
+        assert_eq!(apply_intrinsic_width(&node_data[NodeId::new(1)], intrinsic_widths[NodeId::new(1)]).width, Some(LayoutWidth::px(320.0)));
+
+        // `fit-content` only limits the width, so that the node can still be stretched or shrunk
+        let fit_content = RectLayout { intrinsic_width: Some(LayoutIntrinsicWidth::FitContent), .. Default::default() };
+        let fit_content = apply_intrinsic_width(&fit_content, intrinsic_widths[NodeId::new(2)]);
+        assert_eq!(fit_content.width, None);
+        assert_eq!(fit_content.min_width, Some(LayoutMinWidth::px(100.0)));
+        assert_eq!(fit_content.max_width, Some(LayoutMaxWidth::px(300.0)));
+    }
+
+    #[test]
+    fn test_break_into_flex_lines() {
+
+        let items = [
+            (NodeId::new(1), 300.0),
+            (NodeId::new(2), 300.0),
+            (NodeId::new(3), 300.0),
+            (NodeId::new(4), 900.0),
+        ];
+
+        assert_eq!(break_into_flex_lines(&items, 800.0, 20.0), vec![
+            vec![NodeId::new(1), NodeId::new(2)],
+            vec![NodeId::new(3)],
+            // An item that is larger than the line still gets a line of its own
+            vec![NodeId::new(4)],
+        ]);
+
+        // 300 + 100 + 300 fits exactly into the line
+        assert_eq!(break_into_flex_lines(&items[..2], 700.0, 100.0), vec![
+            vec![NodeId::new(1), NodeId::new(2)],
+        ]);
+    }
+
+    #[test]
+    fn test_flex_item_order_and_alignment() {
+
+        use azul_css::LayoutOrder;
+
+        // The children of node 2: node 4 is placed first because of its `order`
+        let (node_hierarchy, node_data) = get_display_rectangle_arena(&[
+            (3, RectLayout {
+                align_self: Some(LayoutAlignSelf::End),
+                .. Default::default()
+            }),
+            (4, RectLayout {
+                order: Some(LayoutOrder(-1)),
+                .. Default::default()
+            }),
+        ]);
+
+        assert_eq!(get_flex_items(NodeId::new(2), &node_hierarchy, &node_data), vec![NodeId::new(4), NodeId::new(3)]);
+
+        // `align-self` overrides the `align-items` of the container, unless it is `auto`
+        assert_eq!(get_align_self(&node_data[NodeId::new(3)], LayoutAlignItems::Center), LayoutAlignItems::End);
+        assert_eq!(get_align_self(&node_data[NodeId::new(4)], LayoutAlignItems::Center), LayoutAlignItems::Center);
+
+        // 90px of free space for three items
+        assert_eq!(get_flex_free_space_distribution(LayoutJustifyContent::SpaceBetween, 90.0, 3), (0.0, 45.0));
+        assert_eq!(get_flex_free_space_distribution(LayoutJustifyContent::SpaceAround, 90.0, 3), (15.0, 30.0));
+        // Overflowing items can't be spaced out, so they are centered instead
+        assert_eq!(get_flex_free_space_distribution(LayoutJustifyContent::SpaceAround, -90.0, 3), (-45.0, 0.0));
+    }
+
+    #[test]
+    fn test_get_containing_block() {
+
+        let absolute = RectLayout { position: Some(LayoutPosition::Absolute), .. Default::default() };
+        let fixed = RectLayout { position: Some(LayoutPosition::Fixed), .. Default::default() };
+
+        // Without a positioned ancestor, an absolute node is positioned against the root node
+        let (node_hierarchy, node_data) = get_display_rectangle_arena(&[(3, absolute.clone())]);
+        assert_eq!(get_containing_block(NodeId::new(3), &node_hierarchy, &node_data), Some(NodeId::new(0)));
+
+        // 0
+        // '- 1             -- [position: relative]
+        //    '-- 2
+        //    '   '-- 3     -- [position: absolute]
+        //    '   '-- 4     -- [position: fixed]
+        //    '-- 5
+        let (node_hierarchy, node_data) = get_display_rectangle_arena(&[
+            (1, RectLayout { position: Some(LayoutPosition::Relative), .. Default::default() }),
+            (3, absolute),
+            (4, fixed),
+        ]);
+
+        // Out-of-flow nodes don't take part in the flex layout of their parent
+        assert!(get_flex_items(NodeId::new(2), &node_hierarchy, &node_data).is_empty());
+        assert_eq!(get_out_of_flow_children(NodeId::new(2), &node_hierarchy, &node_data), vec![NodeId::new(3), NodeId::new(4)]);
+
+        // The static parent is skipped, a fixed node is positioned against the window
+        assert_eq!(get_containing_block(NodeId::new(3), &node_hierarchy, &node_data), Some(NodeId::new(1)));
+        assert_eq!(get_containing_block(NodeId::new(4), &node_hierarchy, &node_data), None);
+    }
+
+    #[test]
+    fn test_display_none_and_box_sizing() {
+
+        use azul_css::*;
+
+        // Removed nodes are neither laid out by their parent nor positioned against it
+        let (node_hierarchy, node_data) = get_display_rectangle_arena(&[
+            (3, RectLayout { display: Some(LayoutDisplay::None), .. Default::default() }),
+            (4, RectLayout { display: Some(LayoutDisplay::None), position: Some(LayoutPosition::Absolute), .. Default::default() }),
+        ]);
+        assert!(get_flex_items(NodeId::new(2), &node_hierarchy, &node_data).is_empty());
+        assert!(get_out_of_flow_children(NodeId::new(2), &node_hierarchy, &node_data).is_empty());
+
+        let layout = RectLayout {
+            box_sizing: Some(LayoutBoxSizing::ContentBox),
+            width: Some(LayoutWidth::px(100.0)),
+            max_height: Some(LayoutMaxHeight::px(50.0)),
+            padding: Some(LayoutPadding::all(PixelValue::px(10.0))),
+            .. Default::default()
+        };
+        let border = |border_style| StyleBorderSide {
+            border_width: PixelValue::px(5.0),
+            border_style,
+            border_color: ColorU { r: 0, g: 0, b: 0, a: 255 },
+        };
+        let style = RectStyle {
+            border: Some(StyleBorder {
+                top: Some(border(BorderStyle::Solid)),
+                left: Some(border(BorderStyle::Solid)),
+                bottom: Some(border(BorderStyle::None)),
+                right: Some(border(BorderStyle::Solid)),
+            }),
+            .. Default::default()
+        };
+
+        // The padding and the border (unless it is `none`) are added to the sizes of a `content-box` node
+        let border_widths = get_border_widths(&style);
+        assert_eq!(border_widths, (10.0, 5.0));
+        let content_box = apply_box_sizing(&layout, border_widths);
+        assert_eq!(content_box.width.unwrap().0.to_pixels(), 130.0);
+        assert_eq!(content_box.max_height.unwrap().0.to_pixels(), 75.0);
+        assert_eq!(content_box.height, None);
+
+        // The sizes of a `border-box` node already include the padding and border
+        let border_box = RectLayout { box_sizing: Some(LayoutBoxSizing::BorderBox), .. layout };
+        assert_eq!(apply_box_sizing(&border_box, border_widths), border_box);
+
+        // Either way, the inner size of a node excludes the padding and border
+        let padding_and_border = layout.get_horizontal_padding() + border_widths.0;
+        assert_eq!(get_definite_inner_size(Some(100.0), None, None, padding_and_border, LayoutBoxSizing::ContentBox, None), Some(100.0));
+        assert_eq!(get_definite_inner_size(Some(130.0), None, None, padding_and_border, LayoutBoxSizing::BorderBox, None), Some(100.0));
+        assert_eq!(get_definite_inner_size(None, None, None, padding_and_border, LayoutBoxSizing::ContentBox, Some(200.0)), Some(170.0));
+    }
+
+    #[test]
+    fn test_determine_text_alignment_rtl() {
+        use azul_css::LayoutJustifyContent;
+
+        let ltr = RectStyle { direction: Some(StyleTextDirection::Ltr), .. Default::default() };
+        let rtl = RectStyle { direction: Some(StyleTextDirection::Rtl), .. Default::default() };
+        let no_layout = RectLayout::default();
+
+        // Without an explicit alignment, right-to-left text is aligned to the right
+        assert_eq!(determine_text_alignment(&ltr, &no_layout), (StyleTextAlignmentHorz::Center, StyleTextAlignmentVert::Center));
+        assert_eq!(determine_text_alignment(&rtl, &no_layout), (StyleTextAlignmentHorz::Right, StyleTextAlignmentVert::Center));
+
+        // The start of right-to-left text is on the right
+        let start = RectLayout { justify_content: Some(LayoutJustifyContent::Start), .. Default::default() };
+        assert_eq!(determine_text_alignment(&ltr, &start).0, StyleTextAlignmentHorz::Left);
+        assert_eq!(determine_text_alignment(&rtl, &start).0, StyleTextAlignmentHorz::Right);
+
+        // An explicit `text-align` always wins
+        let rtl_left = RectStyle { text_align: Some(StyleTextAlignmentHorz::Left), .. rtl };
+        assert_eq!(determine_text_alignment(&rtl_left, &no_layout).0, StyleTextAlignmentHorz::Left);
+    }
 }
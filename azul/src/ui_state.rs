@@ -139,4 +139,11 @@ impl<T: Layout> UiState<T> {
             self.tag_ids_to_hover_active_states.insert(hover_tag, (*hover_node_id, *hover_group));
         }
     }
+
+    /// Returns whether the node has a (normal or default) `Drop` callback, i.e. whether
+    /// dragged nodes can be dropped onto it
+    pub(crate) fn is_drop_target(&self, node_id: NodeId) -> bool {
+        self.hover_callbacks.get(&node_id).map(|callbacks| callbacks.contains_key(&HoverEventFilter::Drop)).unwrap_or(false) ||
+        self.hover_default_callbacks.get(&node_id).map(|callbacks| callbacks.contains_key(&HoverEventFilter::Drop)).unwrap_or(false)
+    }
 }
@@ -29,7 +29,7 @@ use lyon::{
 };
 #[cfg(feature = "svg_parsing")]
 use usvg::{Error as SvgError};
-use azul_css::{ColorU, ColorF, StyleTextAlignmentHorz};
+use azul_css::{ColorU, ColorF, StyleTextAlignmentHorz, StyleTextDirection};
 use {
     FastHashMap,
     prelude::GlyphInstance,
@@ -1793,7 +1793,7 @@ impl SvgTextLayout {
         use text_layout;
 
         let words = text_layout::split_text_into_words(text);
-        let scaled_words = text_layout::words_to_scaled_words(&words, font_bytes, font_index, SVG_FAKE_FONT_SIZE);
+        let scaled_words = text_layout::words_to_scaled_words(&words, font_bytes, font_index, &[], StyleTextDirection::Ltr, SVG_FAKE_FONT_SIZE);
         let word_positions = text_layout::position_words(&words, &scaled_words, text_layout_options, SVG_FAKE_FONT_SIZE);
         let (layouted_glyphs, line_breaks) = text_layout::get_layouted_glyphs_with_horizonal_alignment(&word_positions, &scaled_words, horizontal_alignment);
 
@@ -2045,15 +2045,17 @@ impl Svg {
     /// The final texture will be width * height large. Note that width and height
     /// need to be multiplied with the current `HiDPI` factor, otherwise the texture
     /// will be blurry on HiDPI screens. This isn't done automatically.
+    ///
+    /// Returns `None` if the window is headless (there is no OpenGL context to render into).
     pub fn render_svg<T: Layout>(
         &self,
         svg_cache: &SvgCache,
         window: &FakeWindow<T>,
         width: usize,
         height: usize
-    ) -> Texture
+    ) -> Option<Texture>
     {
-        let read_only_window = window.read_only_window();
+        let read_only_window = window.read_only_window()?;
 
         let texture_width = (width as f32 * self.multisampling_factor) as u32;
         let texture_height = (height as f32 * self.multisampling_factor) as u32;
@@ -2131,7 +2133,7 @@ impl Svg {
 
         } // unbind surface framebuffer
 
-        tex
+        Some(tex)
     }
 }
 
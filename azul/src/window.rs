@@ -25,7 +25,7 @@ use glium::{
     backend::{Context as BackendContext, Facade, glutin::DisplayCreationError},
 };
 use gleam::gl::{self, Gl};
-use azul_css::{Css, ColorU};
+use azul_css::{Css, CssMediaInfo, ColorU};
 #[cfg(debug_assertions)]
 use azul_css::HotReloadHandler;
 use {
@@ -38,16 +38,74 @@ use {
         DefaultCallback, DefaultCallbackId, Texture,
     },
     display_list::ScrolledNodes,
+    animation::AnimationState,
+    ui_solver::LayoutResult,
 };
 pub use webrender::api::HitTestItem;
 pub use window_state::*;
 
 static LAST_PIPELINE_ID: AtomicUsize = AtomicUsize::new(0);
 
-fn new_pipeline_id() -> PipelineId {
+pub(crate) fn new_pipeline_id() -> PipelineId {
     PipelineId(LAST_PIPELINE_ID.fetch_add(1, Ordering::SeqCst) as u32, 0)
 }
 
+static LAST_CSS_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a new, unique ID for a stylesheet that was (re-)loaded into a window -
+/// `0` is never returned, it is reserved for the `CssKey::default()`
+pub(crate) fn new_css_generation() -> usize {
+    LAST_CSS_GENERATION.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+/// Identifies the CSS that a `UiDescription` was styled with, so that the styles
+/// of the previous frame can be re-used without comparing the entire stylesheet
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct CssKey {
+    /// Changes every time the CSS of the window is replaced, see `new_css_generation`
+    pub(crate) generation: usize,
+    /// Which `@media` blocks of the CSS matched the window
+    pub(crate) matching_media_rules: Vec<bool>,
+}
+
+/// The CSS of a window with the `@media` rules resolved for the current window state.
+/// The resolved CSS is shared with the `UiDescription` and only resolved again if the
+/// CSS was replaced or a different set of `@media` blocks matches the window.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ResolvedCss {
+    pub(crate) key: CssKey,
+    pub(crate) css: Rc<Css>,
+}
+
+impl ResolvedCss {
+
+    /// Wraps a CSS without resolving its `@media` rules
+    #[cfg(test)]
+    pub(crate) fn new(css: Css) -> Self {
+        Self {
+            key: CssKey { generation: new_css_generation(), matching_media_rules: Vec::new() },
+            css: Rc::new(css),
+        }
+    }
+
+    /// Resolves the `@media` rules of the `css` again, if necessary
+    pub(crate) fn update(&mut self, css: &Css, generation: usize, media: &CssMediaInfo) {
+        let matching_media_rules = css.get_matching_media_rules(media);
+        if self.key.generation == generation && self.key.matching_media_rules == matching_media_rules {
+            return;
+        }
+        self.css = Rc::new(css.resolve_media_queries(media).into_owned());
+        self.key = CssKey { generation, matching_media_rules };
+    }
+}
+
+impl ::std::ops::Deref for ResolvedCss {
+    type Target = Css;
+    fn deref(&self) -> &Css {
+        &self.css
+    }
+}
+
 /// User-modifiable fake window
 #[derive(Clone)]
 pub struct FakeWindow<T: Layout> {
@@ -60,17 +118,27 @@ pub struct FakeWindow<T: Layout> {
     /// the user can create textures and other OpenGL content in the window
     /// but not change any window properties from underneath - this would
     /// lead to mismatch between the
-    pub(crate) read_only_window: Rc<Display>,
+    ///
+    /// Is `None` if the window is headless (see `App::new_headless`).
+    pub(crate) read_only_window: Option<Rc<Display>>,
 }
 
 impl<T: Layout> FakeWindow<T> {
 
     /// Returns a read-only window which can be used to create / draw
     /// custom OpenGL texture during the `.layout()` phase
-    pub fn read_only_window(&self) -> ReadOnlyWindow {
-        ReadOnlyWindow {
-            inner: self.read_only_window.clone()
-        }
+    ///
+    /// Returns `None` if the window is headless, since there is no OpenGL context
+    /// to draw into.
+    pub fn read_only_window(&self) -> Option<ReadOnlyWindow> {
+        Some(ReadOnlyWindow {
+            inner: self.read_only_window.clone()?,
+        })
+    }
+
+    /// Returns whether the window is headless, i.e. has no OpenGL context
+    pub fn is_headless(&self) -> bool {
+        self.read_only_window.is_none()
     }
 
     pub fn get_physical_size(&self) -> (u32, u32) {
@@ -182,8 +250,8 @@ impl<T: Layout> fmt::Debug for FakeWindow<T> {
         write!(f,
             "FakeWindow {{\
                 state: {:?}, \
-                read_only_window: Rc<Display>, \
-            }}", self.state)
+                read_only_window: {}, \
+            }}", self.state, if self.is_headless() { "None" } else { "Some(Rc<Display>)" })
     }
 }
 
@@ -261,6 +329,8 @@ pub enum WindowCreateError {
     Io(::std::io::Error),
     /// WebRender creation error (probably OpenGL missing?)
     Renderer/*(RendererError)*/,
+    /// The app was created with `App::new_headless` and has no renderer to open a window with
+    Headless,
 }
 
 impl_display! {
@@ -274,6 +344,7 @@ impl_display! {
         Io(e) => format!("{}", e),
         WebGlNotSupported => "WebGl is not supported by WebRender",
         Renderer => "Webrender creation error (probably OpenGL missing?)",
+        Headless => "Headless apps can't open windows",
     }
 }
 
@@ -359,10 +430,16 @@ pub struct Window<T: Layout> {
     pub(crate) internal: WindowInternal,
     /// States of scrolling animations, updated every frame
     pub(crate) scroll_states: ScrollStates,
+    /// Running CSS transitions and animations, updated every frame
+    pub(crate) animations: AnimationState,
     // The background thread that is running for this window.
     // pub(crate) background_thread: Option<JoinHandle<()>>,
     /// The style applied to the current window
     pub(crate) css: Css,
+    /// Changes every time the `css` is replaced, see `new_css_generation`
+    pub(crate) css_generation: usize,
+    /// The `css` with the `@media` rules resolved for the current window state
+    pub(crate) resolved_css: ResolvedCss,
     /// An optional style hot-reloader for the current window, only available with debug_assertions
     /// enabled
     #[cfg(debug_assertions)]
@@ -389,28 +466,56 @@ impl ScrollStates {
         ScrollStates(FastHashMap::default())
     }
 
-    /// NOTE: This has to be a getter, because we need to update
-    #[must_use]
-    pub(crate) fn get_scroll_amount(&mut self, scroll_id: &ExternalScrollId) -> Option<(f32, f32)> {
-        let entry = self.0.get_mut(&scroll_id)?;
+    /// Returns how far the node is currently scrolled (in pixels)
+    pub(crate) fn get_scroll_amount(&self, scroll_id: &ExternalScrollId) -> Option<(f32, f32)> {
+        let entry = self.0.get(&scroll_id)?;
         Some(entry.get())
     }
 
-    /// Updating the scroll amount does not update the `entry.used_this_frame`,
-    /// since that is only relevant when we are actually querying the renderer.
-    pub(crate) fn scroll_node(&mut self, scroll_id: &ExternalScrollId, scroll_by_x: f32, scroll_by_y: f32) {
-        if let Some(entry) = self.0.get_mut(scroll_id) {
-            entry.add(scroll_by_x, scroll_by_y);
+    /// Scrolls the node by the given amount (clamped to the overflow of the node),
+    /// returns whether the scroll position changed
+    pub(crate) fn scroll_node(&mut self, scroll_id: &ExternalScrollId, scroll_by_x: f32, scroll_by_y: f32) -> bool {
+        match self.0.get_mut(scroll_id) {
+            Some(entry) => {
+                let old = entry.get();
+                entry.add(scroll_by_x, scroll_by_y);
+                entry.get() != old
+            },
+            None => false,
         }
     }
 
+    /// Scrolls the node to the given position (clamped to the overflow of the node),
+    /// returns whether the scroll position changed
+    pub(crate) fn set_scroll_amount(&mut self, scroll_id: &ExternalScrollId, scroll_x: f32, scroll_y: f32) -> bool {
+        match self.0.get_mut(scroll_id) {
+            Some(entry) => {
+                let old = entry.get();
+                entry.set(scroll_x, scroll_y);
+                entry.get() != old
+            },
+            None => false,
+        }
+    }
+
+    /// Creates the scroll state for a node that is scrollable in the current frame. If the
+    /// node was already scrolled in the last frame, only updates how far the content overflows
+    /// (so that the scroll position stays valid if the content got smaller).
     pub(crate) fn ensure_initialized_scroll_state(&mut self, scroll_id: ExternalScrollId, overflow_x: f32, overflow_y: f32) {
-        self.0.entry(scroll_id).or_insert_with(|| ScrollState::new(overflow_x, overflow_y));
+        let entry = self.0.entry(scroll_id).or_insert_with(|| ScrollState::new(overflow_x, overflow_y));
+        entry.overflow_x = overflow_x;
+        entry.overflow_y = overflow_y;
+        entry.add(0.0, 0.0);
+        entry.used_this_frame = true;
     }
 
-    /// Removes all scroll states that weren't used in the last frame
+    /// Removes all scroll states that weren't initialized in the current frame
+    /// and resets the remaining ones for the next frame
     pub(crate) fn remove_unused_scroll_states(&mut self) {
         self.0.retain(|_, state| state.used_this_frame);
+        for state in self.0.values_mut() {
+            state.used_this_frame = false;
+        }
     }
 }
 
@@ -419,9 +524,10 @@ pub struct ScrollState {
     /// Amount in pixel that the current node is scrolled
     scroll_amount_x: f32,
     scroll_amount_y: f32,
+    /// How far (in pixels) the node can be scrolled, i.e. how far the content overflows
     overflow_x: f32,
     overflow_y: f32,
-    /// Was the scroll amount used in this frame?
+    /// Is the node still scrollable in the current frame?
     used_this_frame: bool,
 }
 
@@ -436,14 +542,18 @@ impl ScrollState {
         }
     }
 
-    pub fn get(&mut self) -> (f32, f32) {
-        self.used_this_frame = true;
+    pub fn get(&self) -> (f32, f32) {
         (self.scroll_amount_x, self.scroll_amount_y)
     }
 
     pub fn add(&mut self, x: f32, y: f32) {
-        self.scroll_amount_x = self.overflow_x.min(self.scroll_amount_x + x).max(0.0);
-        self.scroll_amount_y = self.overflow_y.min(self.scroll_amount_y + y).max(0.0);
+        let (current_x, current_y) = self.get();
+        self.set(current_x + x, current_y + y);
+    }
+
+    pub fn set(&mut self, x: f32, y: f32) {
+        self.scroll_amount_x = self.overflow_x.min(x).max(0.0);
+        self.scroll_amount_y = self.overflow_y.min(y).max(0.0);
     }
 }
 
@@ -461,6 +571,8 @@ impl Default for ScrollState {
 
 pub(crate) struct WindowInternal {
     pub(crate) last_scrolled_nodes: ScrolledNodes,
+    /// Layout of the last frame, so that the text layout of unchanged nodes can be re-used
+    pub(crate) last_layout_result: Option<LayoutResult>,
     pub(crate) epoch: Epoch,
     pub(crate) pipeline_id: PipelineId,
     pub(crate) document_id: DocumentId,
@@ -595,10 +707,13 @@ impl<'a, T: Layout> Window<T> {
             state: state,
             display: Rc::new(display),
             css,
+            css_generation: new_css_generation(),
+            resolved_css: ResolvedCss::default(),
             #[cfg(debug_assertions)]
             css_loader: None,
             scroll_states: ScrollStates::new(),
-            internal: WindowInternal { epoch, pipeline_id, document_id, last_scrolled_nodes },
+            animations: AnimationState::default(),
+            internal: WindowInternal { epoch, pipeline_id, document_id, last_scrolled_nodes, last_layout_result: None },
             marker: PhantomData,
         };
 
@@ -695,6 +810,8 @@ impl<'a, T: Layout> Window<T> {
             window.set_max_dimensions(new_state.size.max_dimensions.map(Into::into));
             old_state.size.max_dimensions = new_state.size.max_dimensions;
         }
+
+        old_state.color_scheme = new_state.color_scheme;
     }
 
     #[allow(unused_variables)]
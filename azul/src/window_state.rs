@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashSet, BTreeMap},
+    collections::{HashSet, BTreeMap, BTreeSet},
     path::PathBuf,
     fmt,
 };
@@ -9,6 +9,7 @@ use glium::glutin::{
     ModifiersState, dpi::{LogicalPosition, LogicalSize},
 };
 use webrender::api::HitTestItem;
+use azul_css::{ColorScheme, CssMediaInfo};
 use {
     app::FrameEventInfo,
     dom::{EventFilter, NotEventFilter, HoverEventFilter, FocusEventFilter, WindowEventFilter},
@@ -16,13 +17,18 @@ use {
     id_tree::NodeId,
     ui_state::UiState,
     traits::Layout,
-    callbacks::FocusTarget,
+    callbacks::{FocusTarget, DragData},
     app::AppState,
+    focus::{get_tab_order, get_focus_direction, get_next_focus_node},
+    scrollbar::ScrollbarDragState,
 };
 
 const DEFAULT_TITLE: &str = "Azul App";
 const DEFAULT_WIDTH: f64 = 800.0;
 const DEFAULT_HEIGHT: f64 = 600.0;
+/// How far (in logical pixels) the cursor has to move while the left mouse
+/// button is held down on a draggable node before the node is dragged
+const DRAG_THRESHOLD: f64 = 3.0;
 
 /// Determines which keys are pressed currently (modifiers, etc.)
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -183,8 +189,43 @@ pub(crate) struct CrateInternalWindowState {
     pub(crate) previous_window_state: Option<Box<WindowState>>,
     /// Whether there is a focus field overwrite from the last callback calls.
     pub(crate) pending_focus_target: Option<FocusTarget>,
+    /// The node that `On::FocusReceived` was last sent to. Focus changes from callbacks
+    /// are only applied when the DOM is restyled, so the `On::FocusReceived` / `On::FocusLost`
+    /// events for these changes are sent together with the next event.
+    pub(crate) last_focused_node: Option<NodeId>,
     /// What the last motion was in case a controller was used.
     pub(crate) last_motion: Option<(AxisId, f64)>,
+    /// The current drag & drop operation, set when the left mouse
+    /// button is pressed on a draggable node
+    pub(crate) drag_state: Option<DragState>,
+    /// The scrollbar thumb that is currently dragged with the mouse
+    pub(crate) scrollbar_drag: Option<ScrollbarDragState>,
+}
+
+impl CrateInternalWindowState {
+    /// Returns the node that is currently being dragged (for styling
+    /// `:dragging` and `:drop-target` nodes)
+    pub(crate) fn get_dragged_node(&self) -> Option<NodeId> {
+        self.drag_state.as_ref().and_then(|drag_state| drag_state.get_dragged_node())
+    }
+}
+
+/// State of a drag & drop operation
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DragState {
+    /// Where the left mouse button was pressed
+    pub(crate) start_position: LogicalPosition,
+    /// Whether the cursor has moved further than the `DRAG_THRESHOLD`,
+    /// i.e. whether the `On::DragStart` event has already been sent
+    pub(crate) is_dragging: bool,
+    /// The dragged node + the payload that the callbacks attached to it
+    pub(crate) data: DragData,
+}
+
+impl DragState {
+    pub(crate) fn get_dragged_node(&self) -> Option<NodeId> {
+        if self.is_dragging { Some(self.data.source_node) } else { None }
+    }
 }
 
 impl Default for CrateInternalWindowState {
@@ -197,7 +238,10 @@ impl Default for CrateInternalWindowState {
             hovered_file: None,
             previous_window_state: None,
             pending_focus_target: None,
+            last_focused_node: None,
             last_motion: None,
+            drag_state: None,
+            scrollbar_drag: None,
         }
     }
 }
@@ -226,6 +270,8 @@ pub struct WindowState {
     pub is_visible: bool,
     /// Is the window always on top?
     pub is_always_on_top: bool,
+    /// Color scheme that `@media (prefers-color-scheme: dark)` queries are matched against
+    pub color_scheme: ColorScheme,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -283,6 +329,7 @@ impl Default for WindowState {
             has_decorations: true,
             is_visible: true,
             is_always_on_top: false,
+            color_scheme: ColorScheme::default(),
             debug_state: DebugState::default(),
         }
     }
@@ -327,6 +374,9 @@ pub(crate) struct CallbacksOfHitTest<T: Layout> {
     /// Each `:hover` and `:active` group stores whether it modifies the layout, as
     /// a performance optimization.
     pub needs_relayout_anyways: bool,
+    /// If a node is dragged (or was just dropped), the data of the drag operation,
+    /// which is passed to the callbacks via the `CallbackInfo`
+    pub drag_data: Option<DragData>,
 }
 
 impl<T: Layout> fmt::Debug for DetermineCallbackResult<T> {
@@ -341,6 +391,7 @@ impl<T: Layout> Default for CallbacksOfHitTest<T> {
             nodes_with_callbacks: BTreeMap::new(),
             needs_redraw_anyways: false,
             needs_relayout_anyways: false,
+            drag_data: None,
         }
     }
 }
@@ -363,6 +414,17 @@ impl WindowState
         self.internal.last_motion
     }
 
+    /// Returns the size, HiDPI factor and color scheme of the window, which
+    /// the `@media` queries of the stylesheet are evaluated against
+    pub fn get_media_info(&self) -> CssMediaInfo {
+        CssMediaInfo {
+            width: self.size.dimensions.width as f32,
+            height: self.size.dimensions.height as f32,
+            resolution: self.size.hidpi_factor as f32,
+            color_scheme: self.color_scheme,
+        }
+    }
+
     /// Returns the window state of the previous frame, useful for calculating
     /// metrics for dragging motions. Note that you can't call this function
     /// recursively - calling `get_previous_window_state()` on the returned
@@ -374,16 +436,17 @@ impl WindowState
     /// Determine which event / which callback(s) should be called and in which order
     ///
     /// This function also updates / mutates the current window state, so that
-    /// the window state is updated for the next frame
+    /// the window state is updated for the next frame. If `enable_tab_navigation`
+    /// is set, pressing Tab / Shift + Tab moves the focus to the next / previous
+    /// focusable node.
     pub(crate) fn determine_callbacks<T: Layout>(
         &mut self,
         hit_test_items: &[HitTestItem],
         event: &WindowEvent,
-        ui_state: &UiState<T>
+        ui_state: &UiState<T>,
+        enable_tab_navigation: bool,
     ) -> CallbacksOfHitTest<T>
     {
-        use std::collections::BTreeSet;
-
         // Store the current window state so we can set it in this.previous_window_state later on
         let mut previous_state = Box::new(self.clone());
         previous_state.internal.previous_window_state = None;
@@ -403,9 +466,6 @@ impl WindowState
         let event_was_mouse_enter = if let WindowEvent::CursorEntered { .. } = event { true } else { false };
         let event_was_mouse_leave = if let WindowEvent::CursorLeft { .. } = event { true } else { false };
 
-        // TODO: If the current mouse is down, but the event
-        // wasn't a click, that means it was a drag
-
         // Figure out what the hovered NodeIds are
         let mut new_hit_node_ids: BTreeMap<NodeId, HitTestItem> = hit_test_items.iter().filter_map(|hit_test_item| {
             ui_state.tag_ids_to_node_ids
@@ -512,10 +572,26 @@ impl WindowState
             insert_callbacks!(current_focused_node, None, focus_callbacks, focus_default_callbacks, current_focus_events, Focus);
         }
 
+        // Move the focus on Tab / Shift + Tab - the key press itself is
+        // still sent to the node that was focused before
+        if enable_tab_navigation {
+            if let Some(direction) = get_focus_direction(event) {
+                let tab_order = get_tab_order(ui_state);
+                if let Some(next_focus) = get_next_focus_node(&tab_order, self.internal.focused_node, direction) {
+                    if self.internal.focused_node != Some(next_focus) {
+                        self.internal.focused_node = Some(next_focus);
+                        // The :focus style has changed
+                        needs_hover_redraw = true;
+                        needs_hover_relayout = true;
+                    }
+                }
+            }
+        }
+
         // If the last focused node and the current focused node aren't the same,
         // submit a FocusLost for the last node and a FocusReceived for the current one.
         let mut focus_received_lost_events: BTreeMap<NodeId, FocusEventFilter> = BTreeMap::new();
-        match (self.internal.focused_node, previous_state.internal.focused_node) {
+        match (self.internal.focused_node, self.internal.last_focused_node) {
             (Some(cur), None) => {
                 focus_received_lost_events.insert(cur, FocusEventFilter::FocusReceived);
             },
@@ -537,6 +613,44 @@ impl WindowState
             insert_callbacks!(node_id, None, focus_callbacks, focus_default_callbacks, current_focus_leave_events, Focus);
         }
 
+        self.internal.last_focused_node = self.internal.focused_node;
+
+        // Drag & drop - the drag starts when the cursor is moved while the left
+        // mouse button is held down on a draggable node
+        let dragged_node_before = self.internal.get_dragged_node();
+
+        let closest_draggable_node = hit_test_items.iter().rev()
+            .find_map(|item| ui_state.draggable_tags.get(&item.tag.0))
+            .cloned();
+
+        // The hovered drop targets, the one closest to the cursor first
+        let hovered_drop_targets = hit_test_items.iter().rev()
+            .filter_map(|item| ui_state.tag_ids_to_node_ids.get(&item.tag.0))
+            .filter(|node_id| new_hit_node_ids.contains_key(node_id) && ui_state.is_drop_target(**node_id))
+            .cloned()
+            .collect::<Vec<NodeId>>();
+
+        let (drag_events, drag_data) = update_drag_state(
+            &mut self.internal.drag_state,
+            event,
+            self.internal.mouse_state.cursor_pos,
+            closest_draggable_node,
+            &hovered_drop_targets,
+            &new_hit_node_ids.keys().cloned().collect(),
+            &previous_state.internal.hovered_nodes.keys().cloned().collect(),
+        );
+
+        for (node_id, drag_event) in &drag_events {
+            let current_drag_events = [*drag_event];
+            insert_callbacks!(node_id, new_hit_node_ids.get(node_id).cloned(), hover_callbacks, hover_default_callbacks, current_drag_events, Hover);
+        }
+
+        // The :dragging and :drop-target styles have changed
+        if self.internal.get_dragged_node() != dragged_node_before {
+            needs_hover_redraw = true;
+            needs_hover_relayout = true;
+        }
+
         // If the mouse is down, but was up previously or vice versa, that means
         // that a :hover or :active state may be invalidated. In that case we need
         // to redraw the screen anyways. Setting relayout to true here in order to
@@ -698,6 +812,7 @@ impl WindowState
             needs_redraw_anyways: needs_hover_redraw,
             needs_relayout_anyways: needs_hover_relayout,
             nodes_with_callbacks,
+            drag_data,
         }
     }
 
@@ -909,6 +1024,82 @@ fn get_window_events(window_state: &mut WindowState, event: &WindowEvent) -> Has
     events_vec
 }
 
+/// Updates the drag & drop state for the current event and returns which drag events
+/// have to be sent to which nodes, as well as the data of the drag operation that
+/// these events belong to. `draggable_node` is the draggable node closest to the
+/// cursor, `hovered_drop_targets` are the drop targets under the cursor (closest to the
+/// cursor first), `hovered_nodes` and `previous_hovered_nodes` are the nodes that are hit
+/// by the cursor in this and in the previous event.
+///
+/// On release, the `Drop` event is only sent to the closest drop target that isn't the
+/// dragged node itself, so that nested drop targets don't all receive the same drop.
+fn update_drag_state(
+    drag_state: &mut Option<DragState>,
+    event: &WindowEvent,
+    cursor_pos: Option<LogicalPosition>,
+    draggable_node: Option<NodeId>,
+    hovered_drop_targets: &[NodeId],
+    hovered_nodes: &BTreeSet<NodeId>,
+    previous_hovered_nodes: &BTreeSet<NodeId>,
+) -> (Vec<(NodeId, HoverEventFilter)>, Option<DragData>) {
+
+    use glium::glutin::MouseButton;
+
+    let mut drag_events = Vec::new();
+
+    match event {
+        WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+            *drag_state = match (draggable_node, cursor_pos) {
+                (Some(source_node), Some(start_position)) => Some(DragState {
+                    start_position,
+                    is_dragging: false,
+                    data: DragData { source_node, payload: None },
+                }),
+                _ => None,
+            };
+            (drag_events, None)
+        },
+        WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } => {
+            match drag_state.take() {
+                Some(DragState { is_dragging: true, data, .. }) => {
+                    let drop_target = hovered_drop_targets.iter().find(|node_id| **node_id != data.source_node);
+                    drag_events.extend(drop_target.map(|node_id| (*node_id, HoverEventFilter::Drop)));
+                    (drag_events, Some(data))
+                },
+                _ => (drag_events, None),
+            }
+        },
+        WindowEvent::CursorMoved { .. } | WindowEvent::CursorLeft { .. } => {
+
+            let drag_state = match drag_state.as_mut() {
+                Some(s) => s,
+                None => return (drag_events, None),
+            };
+
+            if drag_state.is_dragging {
+                drag_events.extend(previous_hovered_nodes.difference(hovered_nodes).map(|node_id| (*node_id, HoverEventFilter::DragLeave)));
+                drag_events.extend(hovered_nodes.difference(previous_hovered_nodes).map(|node_id| (*node_id, HoverEventFilter::DragEnter)));
+            } else {
+                let cursor_pos = match cursor_pos {
+                    Some(s) => s,
+                    None => return (drag_events, None),
+                };
+                let (dx, dy) = (cursor_pos.x - drag_state.start_position.x, cursor_pos.y - drag_state.start_position.y);
+                if dx * dx + dy * dy <= DRAG_THRESHOLD * DRAG_THRESHOLD {
+                    return (drag_events, None);
+                }
+                drag_state.is_dragging = true;
+                drag_events.push((drag_state.data.source_node, HoverEventFilter::DragStart));
+                drag_events.extend(hovered_nodes.iter().map(|node_id| (*node_id, HoverEventFilter::DragEnter)));
+            }
+
+            drag_events.extend(hovered_nodes.iter().map(|node_id| (*node_id, HoverEventFilter::DragOver)));
+            (drag_events, Some(drag_state.data.clone()))
+        },
+        _ => (drag_events, drag_state.as_ref().filter(|s| s.is_dragging).map(|s| s.data.clone())),
+    }
+}
+
 fn get_hover_events(input: &HashSet<WindowEventFilter>) -> HashSet<HoverEventFilter> {
     input.iter().filter_map(|window_event| window_event.to_hover_event_filter()).collect()
 }
@@ -950,6 +1141,11 @@ pub(crate) fn window_should_close(event: &WindowEvent, frame_event_info: &mut Fr
         WindowEvent::ReceivedCharacter(_) |
         WindowEvent::MouseWheel { .. } |
         WindowEvent::MouseInput { .. } |
+        WindowEvent::CursorEntered { .. } |
+        WindowEvent::CursorLeft { .. } |
+        WindowEvent::HoveredFile(_) |
+        WindowEvent::HoveredFileCancelled |
+        WindowEvent::DroppedFile(_) |
         WindowEvent::Touch(_) => {
             frame_event_info.should_hittest = true;
         },
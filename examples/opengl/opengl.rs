@@ -21,7 +21,7 @@ fn render_my_texture(
 -> Option<Texture>
 {
     let physical_size = hi_dpi_bounds.get_physical_size();
-    let texture = info.window.read_only_window().create_texture(
+    let texture = info.window.read_only_window()?.create_texture(
         physical_size.width as u32,
         physical_size.height as u32
     );
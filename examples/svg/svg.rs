@@ -69,16 +69,14 @@ fn render_map_callback(ptr: &StackCheckedPointer<MyAppData>, window_info: Layout
 
 fn render_map(map: &mut Map, info: LayoutInfo<MyAppData>, dimensions: HidpiAdjustedBounds) -> Option<Texture> {
     let physical_size = dimensions.get_physical_size();
-    Some(
-         Svg::with_layers(build_layers(&map.layers, &map.texts, &map.hovered_text, &map.font_cache, &info.resources))
-            .with_pan(map.pan_horz as f32, map.pan_vert as f32)
-            .with_zoom(map.zoom as f32)
-            .render_svg(
-                &map.cache, &info.window,
-                physical_size.width as usize,
-                physical_size.height  as usize,
-            )
-    )
+    Svg::with_layers(build_layers(&map.layers, &map.texts, &map.hovered_text, &map.font_cache, &info.resources))
+        .with_pan(map.pan_horz as f32, map.pan_vert as f32)
+        .with_zoom(map.zoom as f32)
+        .render_svg(
+            &map.cache, &info.window,
+            physical_size.width as usize,
+            physical_size.height  as usize,
+        )
 }
 
 fn build_layers(